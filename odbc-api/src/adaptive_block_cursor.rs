@@ -0,0 +1,194 @@
+use crate::{handles::AsStatementRef, BlockCursor, Cursor, Error, RowSetBuffer};
+
+/// Determines the number of rows fetched with each batch by an [`AdaptiveBlockCursor`]. Starts
+/// with a small batch size, so the first rows arrive with low latency and grows the batch size
+/// geometrically up to a maximum for throughput.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdaptiveBatchSize {
+    current: usize,
+    max: usize,
+    growth_factor: usize,
+}
+
+impl AdaptiveBatchSize {
+    /// Starts with batches of `initial` rows and doubles the batch size after each fetch, until it
+    /// reaches `max` rows.
+    ///
+    /// # Panics
+    ///
+    /// If `initial` is `0` or larger than `max`.
+    pub fn new(initial: usize, max: usize) -> Self {
+        assert!(initial > 0, "Initial batch size must be at least one.");
+        assert!(
+            initial <= max,
+            "Initial batch size must not exceed the maximum batch size."
+        );
+        Self {
+            current: initial,
+            max,
+            growth_factor: 2,
+        }
+    }
+
+    /// Like [`Self::new`], but derives the maximum batch size from an upper bound for the memory
+    /// used by the buffer. [`crate::buffers::BufferDesc::bytes_per_row`] can help you estimate
+    /// `bytes_per_row`. The maximum batch size is at least one row, even if a single row exceeds
+    /// `max_bytes`.
+    pub fn with_memory_cap(initial: usize, bytes_per_row: usize, max_bytes: usize) -> Self {
+        let max = (max_bytes / bytes_per_row.max(1)).max(1);
+        Self::new(initial.min(max), max)
+    }
+
+    /// Factor by which the batch size is multiplied after each fetch. Default is `2`.
+    ///
+    /// # Panics
+    ///
+    /// If `growth_factor` is smaller than `2`.
+    pub fn growth_factor(mut self, growth_factor: usize) -> Self {
+        assert!(growth_factor >= 2, "Growth factor must be at least two.");
+        self.growth_factor = growth_factor;
+        self
+    }
+
+    /// Number of rows to be fetched with the next batch.
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    /// Grows the batch size. `true` if it changed, `false` if it has already been at its maximum.
+    pub fn grow(&mut self) -> bool {
+        let next = self
+            .current
+            .saturating_mul(self.growth_factor)
+            .min(self.max);
+        let changed = next != self.current;
+        self.current = next;
+        changed
+    }
+}
+
+/// A block cursor, which adapts the row capacity of its buffer to an [`AdaptiveBatchSize`]. Each
+/// time the batch size grows, a new buffer is created and bound to the cursor, before fetching the
+/// next batch. This spares you the need to choose a static batch size, which is either too large
+/// for the latency of the first batch or too small for throughput.
+///
+/// ```
+/// use odbc_api::{
+///     buffers::{BufferDesc, ColumnarAnyBuffer}, AdaptiveBatchSize, AdaptiveBlockCursor, Cursor,
+///     Error,
+/// };
+///
+/// fn sum_first_column(cursor: impl Cursor) -> Result<i64, Error> {
+///     let desc = BufferDesc::I64 { nullable: false };
+///     // Start with ten rows and grow to up to 64 MiB.
+///     let batch_size = AdaptiveBatchSize::with_memory_cap(10, desc.bytes_per_row(), 64 << 20);
+///     let mut cursor = AdaptiveBlockCursor::new(cursor, batch_size, |capacity| {
+///         ColumnarAnyBuffer::try_from_descs(capacity, [desc])
+///     })?;
+///     let mut sum = 0;
+///     while let Some(batch) = cursor.fetch()? {
+///         sum += batch.column(0).as_slice::<i64>().unwrap().iter().sum::<i64>();
+///     }
+///     Ok(sum)
+/// }
+/// ```
+pub struct AdaptiveBlockCursor<C: AsStatementRef, B, F> {
+    /// Only `None` if binding a new buffer failed.
+    block_cursor: Option<BlockCursor<C, B>>,
+    batch_size: AdaptiveBatchSize,
+    make_buffer: F,
+    /// Batch size grows before each fetch, except the first one.
+    is_first_fetch: bool,
+}
+
+impl<C, B, F> AdaptiveBlockCursor<C, B, F>
+where
+    C: Cursor,
+    B: RowSetBuffer,
+    F: FnMut(usize) -> Result<B, Error>,
+{
+    /// Binds a buffer created by `make_buffer` to the `cursor`. `make_buffer` is called with the
+    /// required row capacity each time the batch size grows.
+    pub fn new(
+        cursor: C,
+        batch_size: AdaptiveBatchSize,
+        mut make_buffer: F,
+    ) -> Result<Self, Error> {
+        let buffer = make_buffer(batch_size.current())?;
+        let block_cursor = cursor.bind_buffer(buffer)?;
+        Ok(Self {
+            block_cursor: Some(block_cursor),
+            batch_size,
+            make_buffer,
+            is_first_fetch: true,
+        })
+    }
+
+    /// Fills the bound buffer with the next row set, after binding a larger buffer if the batch
+    /// size grows.
+    ///
+    /// # Return
+    ///
+    /// `None` if the result set is empty and all row sets have been extracted. `Some` with a
+    /// reference to the internal buffer otherwise.
+    ///
+    /// # Panics
+    ///
+    /// If called again after binding a new buffer failed in a previous call.
+    pub fn fetch(&mut self) -> Result<Option<&B>, Error> {
+        if !self.is_first_fetch && self.batch_size.grow() {
+            let buffer = (self.make_buffer)(self.batch_size.current())?;
+            let (cursor, _previous_buffer) = self.take_block_cursor().unbind()?;
+            self.block_cursor = Some(cursor.bind_buffer(buffer)?);
+        }
+        self.is_first_fetch = false;
+        self.block_cursor
+            .as_mut()
+            .expect("Adaptive block cursor must not be used after binding a buffer failed.")
+            .fetch()
+    }
+
+    /// Row capacity of the currently bound buffer.
+    pub fn batch_size(&self) -> usize {
+        self.batch_size.current()
+    }
+
+    /// Unbinds the buffer and gives back ownership of the cursor.
+    pub fn unbind(mut self) -> Result<C, Error> {
+        let (cursor, _buffer) = self.take_block_cursor().unbind()?;
+        Ok(cursor)
+    }
+
+    fn take_block_cursor(&mut self) -> BlockCursor<C, B> {
+        self.block_cursor
+            .take()
+            .expect("Adaptive block cursor must not be used after binding a buffer failed.")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AdaptiveBatchSize;
+
+    #[test]
+    fn grow_batch_size_up_to_maximum() {
+        let mut batch_size = AdaptiveBatchSize::new(10, 50);
+
+        let mut sizes = vec![batch_size.current()];
+        while batch_size.grow() {
+            sizes.push(batch_size.current());
+        }
+
+        assert_eq!(vec![10, 20, 40, 50], sizes);
+    }
+
+    #[test]
+    fn derive_maximum_from_memory_cap() {
+        let mut batch_size = AdaptiveBatchSize::with_memory_cap(1, 100, 1000).growth_factor(4);
+
+        batch_size.grow();
+        batch_size.grow();
+
+        assert_eq!(10, batch_size.current());
+    }
+}
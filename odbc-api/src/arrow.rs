@@ -0,0 +1,608 @@
+//! Encodes result sets in the Arrow IPC streaming format. Requires the `arrow` feature. See
+//! [`crate::Cursor::write_arrow_ipc`].
+//!
+//! The stream consists of a schema message, followed by one record batch message for each fetched
+//! batch and the end of stream marker. It can be read by any Arrow implementation, e.g. piped into
+//! `duckdb` or read with `pyarrow.ipc.open_stream`. The messages are encoded by this crate, so no
+//! dependency on the Arrow crates is required. Dictionaries, compression and nested types are not
+//! used.
+//!
+//! Columns are mapped to Arrow types based on their SQL data type:
+//!
+//! | SQL                                | Arrow                                         |
+//! |------------------------------------|-----------------------------------------------|
+//! | `BIT`                              | `Boolean`                                     |
+//! | `TINYINT`                          | `Int8`                                        |
+//! | `SMALLINT`                         | `Int16`                                       |
+//! | `INTEGER`                          | `Int32`                                       |
+//! | `BIGINT`                           | `Int64`                                       |
+//! | `REAL`, `FLOAT(p <= 24)`           | `Float32`                                     |
+//! | `DOUBLE`, `FLOAT`                  | `Float64`                                     |
+//! | `DECIMAL`, `NUMERIC` (`p <= 38`)   | `Decimal128`                                  |
+//! | `DATE`                             | `Date32`                                      |
+//! | `TIME`                             | `Time64` in microseconds                      |
+//! | `TIMESTAMP`                        | `Timestamp` in microseconds, without timezone |
+//! | `BINARY`, `VARBINARY`              | `Binary`                                      |
+//! | Everything else                    | `Utf8`                                        |
+//!
+//! Fields are nullable, unless the driver reports the column to contain no `NULL`s.
+
+mod flatbuffers;
+
+use std::io::{self, Write};
+
+use crate::{
+    buffers::{AnySlice, BufferDesc, ColumnarAnyBuffer, NullableSlice},
+    decimal_to_scaled_integer,
+    epoch::{days_since_epoch, micros_since_midnight, seconds_since_epoch},
+    sys::NULL_DATA,
+    Cursor, DataType, Error, Nullability, ResultSetSchema,
+};
+
+use self::flatbuffers::{Builder, Offset};
+
+/// Options for writing result sets as an Arrow IPC stream. See [`crate::Cursor::write_arrow_ipc`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArrowIpcOptions {
+    batch_size: usize,
+    max_str_len: Option<usize>,
+}
+
+impl Default for ArrowIpcOptions {
+    fn default() -> Self {
+        Self {
+            batch_size: 1000,
+            max_str_len: Some(4096),
+        }
+    }
+}
+
+impl ArrowIpcOptions {
+    /// Rows are written in record batches of 1000 rows. Text and binary columns hold at most 4096
+    /// bytes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of rows fetched at once. Each batch is written as one record batch message.
+    ///
+    /// # Panics
+    ///
+    /// If `batch_size` is zero.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        assert!(batch_size > 0, "batch size must be at least one");
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Upper bound for the length of text and binary values in bytes. The size reported by the
+    /// driver is used if it is smaller. `None` to always use the size reported by the driver, which
+    /// fails with [`Error::UnboundedColumnLength`] for columns without an upper bound, like
+    /// `VARCHAR(max)`.
+    pub fn with_max_str_len(mut self, max_str_len: Option<usize>) -> Self {
+        self.max_str_len = max_str_len;
+        self
+    }
+}
+
+/// Arrow type a column is mapped to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArrowType {
+    Boolean,
+    Int { bit_width: i32 },
+    Float { precision: i16 },
+    Decimal { precision: i32, scale: i32 },
+    Date,
+    TimeMicros,
+    TimestampMicros,
+    Binary,
+    Utf8,
+}
+
+impl ArrowType {
+    /// Arrow type of a column and the buffer used to fetch it.
+    fn for_data_type(
+        data_type: DataType,
+        column_number: u16,
+        max_str_len: Option<usize>,
+    ) -> Result<(Self, BufferDesc), Error> {
+        let limit = |length: usize| match max_str_len {
+            Some(upper_bound) if length == 0 || length > upper_bound => Ok(upper_bound),
+            None if length == 0 => Err(Error::UnboundedColumnLength { column_number }),
+            _ => Ok(length),
+        };
+        let nullable = true;
+        let mapped = match data_type {
+            DataType::Bit => (ArrowType::Boolean, BufferDesc::Bit { nullable }),
+            DataType::TinyInt => (ArrowType::Int { bit_width: 8 }, BufferDesc::I8 { nullable }),
+            DataType::SmallInt => (
+                ArrowType::Int { bit_width: 16 },
+                BufferDesc::I16 { nullable },
+            ),
+            DataType::Integer => (
+                ArrowType::Int { bit_width: 32 },
+                BufferDesc::I32 { nullable },
+            ),
+            DataType::BigInt => (
+                ArrowType::Int { bit_width: 64 },
+                BufferDesc::I64 { nullable },
+            ),
+            DataType::Real | DataType::Float { precision: 0..=24 } => (
+                ArrowType::Float { precision: SINGLE },
+                BufferDesc::F32 { nullable },
+            ),
+            DataType::Double | DataType::Float { .. } => (
+                ArrowType::Float { precision: DOUBLE },
+                BufferDesc::F64 { nullable },
+            ),
+            // A precision of zero indicates the driver does not know it, so we can not declare
+            // the decimal type.
+            DataType::Decimal { precision, scale } | DataType::Numeric { precision, scale }
+                if (1..=38).contains(&precision) && (0..=precision as i16).contains(&scale) =>
+            {
+                (
+                    ArrowType::Decimal {
+                        precision: precision as i32,
+                        scale: scale.into(),
+                    },
+                    // Sign, decimal point and digits.
+                    BufferDesc::Text {
+                        max_str_len: precision + 2,
+                    },
+                )
+            }
+            DataType::Date => (ArrowType::Date, BufferDesc::Date { nullable }),
+            DataType::Time { .. } => (ArrowType::TimeMicros, BufferDesc::Time { nullable }),
+            DataType::Timestamp { .. } => (
+                ArrowType::TimestampMicros,
+                BufferDesc::Timestamp { nullable },
+            ),
+            DataType::Binary { length }
+            | DataType::Varbinary { length }
+            | DataType::LongVarbinary { length } => (
+                ArrowType::Binary,
+                BufferDesc::Binary {
+                    length: limit(length)?,
+                },
+            ),
+            other => (
+                ArrowType::Utf8,
+                BufferDesc::Text {
+                    max_str_len: limit(other.utf8_len().unwrap_or(0))?,
+                },
+            ),
+        };
+        Ok(mapped)
+    }
+
+    /// Creates the table describing the type and returns it, together with its id in the `Type`
+    /// union of the Arrow schema.
+    fn create(self, builder: &mut Builder) -> (u8, Offset) {
+        builder.start_table();
+        let type_id = match self {
+            ArrowType::Boolean => 6,
+            ArrowType::Int { bit_width } => {
+                builder.add_i32(0, bit_width);
+                builder.add_bool(1, true);
+                2
+            }
+            ArrowType::Float { precision } => {
+                builder.add_i16(0, precision);
+                3
+            }
+            ArrowType::Decimal { precision, scale } => {
+                builder.add_i32(0, precision);
+                builder.add_i32(1, scale);
+                builder.add_i32(2, 128);
+                7
+            }
+            ArrowType::Date => {
+                // `DateUnit::DAY`
+                builder.add_i16(0, 0);
+                8
+            }
+            ArrowType::TimeMicros => {
+                builder.add_i16(0, MICROSECOND);
+                builder.add_i32(1, 64);
+                9
+            }
+            ArrowType::TimestampMicros => {
+                builder.add_i16(0, MICROSECOND);
+                10
+            }
+            ArrowType::Binary => 4,
+            ArrowType::Utf8 => 5,
+        };
+        (type_id, builder.end_table())
+    }
+}
+
+/// `Precision::SINGLE` of floating point types.
+const SINGLE: i16 = 1;
+/// `Precision::DOUBLE` of floating point types.
+const DOUBLE: i16 = 2;
+/// `TimeUnit::MICROSECOND`.
+const MICROSECOND: i16 = 2;
+/// `MetadataVersion::V5`.
+const METADATA_VERSION: i16 = 4;
+/// Ids of the `MessageHeader` union.
+const SCHEMA: u8 = 1;
+const RECORD_BATCH: u8 = 3;
+
+/// Fetches all rows of `cursor` and writes them to `writer` as an Arrow IPC stream. Each fetched
+/// batch is written as one record batch, so the result is streamed rather than held in memory.
+/// Returns the number of rows written.
+///
+/// ```no_run
+/// use std::io::stdout;
+/// use odbc_api::{arrow::{write_arrow_ipc, ArrowIpcOptions}, Connection, Error};
+///
+/// fn orders_as_arrow(conn: &Connection<'_>) -> Result<(), Error> {
+///     if let Some(cursor) = conn.execute("SELECT id, amount, placed FROM Orders", ())? {
+///         write_arrow_ipc(cursor, stdout().lock(), &ArrowIpcOptions::new())?;
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn write_arrow_ipc(
+    mut cursor: impl Cursor,
+    mut writer: impl Write,
+    options: &ArrowIpcOptions,
+) -> Result<usize, Error> {
+    let schema = ResultSetSchema::for_cursor(&mut cursor)?;
+    let mut types = Vec::with_capacity(schema.columns.len());
+    let mut descs = Vec::with_capacity(schema.columns.len());
+    for (index, column) in schema.columns.iter().enumerate() {
+        let column_number = (index + 1) as u16;
+        let (arrow_type, desc) =
+            ArrowType::for_data_type(column.data_type, column_number, options.max_str_len)?;
+        let nullable = column.nullability != Nullability::NoNulls;
+        types.push((arrow_type, nullable));
+        descs.push(desc);
+    }
+    let buffer = ColumnarAnyBuffer::try_from_descs(options.batch_size, descs)?;
+    let mut row_set_cursor = cursor.bind_buffer(buffer)?;
+
+    write_message(&mut writer, &schema_message(&schema, &types), &[])
+        .map_err(Error::FailedWritingArrow)?;
+    let mut num_rows = 0;
+    let mut body = Body::default();
+    while let Some(batch) = row_set_cursor.fetch_with_truncation_check(true)? {
+        body.clear();
+        for (index, &(arrow_type, nullable)) in types.iter().enumerate() {
+            body.encode_column(&batch.column(index), batch.num_rows(), arrow_type, nullable)
+                .map_err(Error::FailedWritingArrow)?;
+        }
+        let metadata = record_batch_message(batch.num_rows(), &body);
+        write_message(&mut writer, &metadata, &body.bytes).map_err(Error::FailedWritingArrow)?;
+        num_rows += batch.num_rows();
+    }
+    // End of stream marker
+    writer
+        .write_all(&[0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 0])
+        .and_then(|()| writer.flush())
+        .map_err(Error::FailedWritingArrow)?;
+    Ok(num_rows)
+}
+
+/// Writes an encapsulated message: continuation marker, length of the metadata, the metadata
+/// padded to eight bytes and the body.
+fn write_message(writer: &mut impl Write, metadata: &[u8], body: &[u8]) -> io::Result<()> {
+    let padding = (8 - metadata.len() % 8) % 8;
+    writer.write_all(&[0xFF, 0xFF, 0xFF, 0xFF])?;
+    writer.write_all(&((metadata.len() + padding) as i32).to_le_bytes())?;
+    writer.write_all(metadata)?;
+    writer.write_all(&[0; 8][..padding])?;
+    writer.write_all(body)
+}
+
+/// Flatbuffer of the message holding the schema.
+fn schema_message(schema: &ResultSetSchema, types: &[(ArrowType, bool)]) -> Vec<u8> {
+    let mut builder = Builder::new();
+    let fields: Vec<_> = schema
+        .columns
+        .iter()
+        .zip(types)
+        .map(|(column, &(arrow_type, nullable))| {
+            let name = builder.create_string(&column.name);
+            let (type_id, type_table) = arrow_type.create(&mut builder);
+            // Readers expect a vector of children, even if it is empty.
+            let children = builder.create_offset_vector(&[]);
+            builder.start_table();
+            builder.add_offset(0, name);
+            builder.add_bool(1, nullable);
+            builder.add_u8(2, type_id);
+            builder.add_offset(3, type_table);
+            builder.add_offset(5, children);
+            builder.end_table()
+        })
+        .collect();
+    let fields = builder.create_offset_vector(&fields);
+    builder.start_table();
+    // `Endianness::Little`
+    builder.add_i16(0, 0);
+    builder.add_offset(1, fields);
+    let header = builder.end_table();
+    finish_message(builder, SCHEMA, header, 0)
+}
+
+/// Flatbuffer of the message describing the layout of a record batch with `num_rows` rows.
+fn record_batch_message(num_rows: usize, body: &Body) -> Vec<u8> {
+    let mut builder = Builder::new();
+    let nodes = builder.create_struct_vector(&body.nodes, 16, 8);
+    let buffers = builder.create_struct_vector(&body.buffers, 16, 8);
+    builder.start_table();
+    builder.add_i64(0, num_rows as i64);
+    builder.add_offset(1, nodes);
+    builder.add_offset(2, buffers);
+    let header = builder.end_table();
+    finish_message(builder, RECORD_BATCH, header, body.bytes.len())
+}
+
+fn finish_message(
+    mut builder: Builder,
+    header_type: u8,
+    header: Offset,
+    body_len: usize,
+) -> Vec<u8> {
+    builder.start_table();
+    builder.add_i16(0, METADATA_VERSION);
+    builder.add_u8(1, header_type);
+    builder.add_offset(2, header);
+    builder.add_i64(3, body_len as i64);
+    let message = builder.end_table();
+    builder.finish(message)
+}
+
+/// Body of a record batch, together with the `FieldNode` and `Buffer` structs describing it.
+#[derive(Debug, Default)]
+struct Body {
+    bytes: Vec<u8>,
+    /// Length and null count of each column.
+    nodes: Vec<u8>,
+    /// Offset and length of each buffer within `bytes`.
+    buffers: Vec<u8>,
+}
+
+impl Body {
+    fn clear(&mut self) {
+        self.bytes.clear();
+        self.nodes.clear();
+        self.buffers.clear();
+    }
+
+    /// Appends a buffer, padded to eight bytes.
+    fn push_buffer(&mut self, buffer: &[u8]) {
+        self.buffers
+            .extend_from_slice(&(self.bytes.len() as i64).to_le_bytes());
+        self.buffers
+            .extend_from_slice(&(buffer.len() as i64).to_le_bytes());
+        self.bytes.extend_from_slice(buffer);
+        self.bytes.resize(self.bytes.len().next_multiple_of(8), 0);
+    }
+
+    /// Appends the validity bitmap and the buffers holding the values of a column.
+    fn encode_column(
+        &mut self,
+        view: &AnySlice<'_>,
+        num_rows: usize,
+        arrow_type: ArrowType,
+        nullable: bool,
+    ) -> io::Result<()> {
+        let mut validity = vec![0u8; num_rows.div_ceil(8)];
+        let mut null_count = 0;
+        let mut set_valid = |row_index: usize, is_valid: bool| {
+            if is_valid {
+                validity[row_index / 8] |= 1 << (row_index % 8);
+            } else {
+                null_count += 1;
+            }
+        };
+        let mut values = Vec::new();
+        let mut offsets = Vec::new();
+        match view {
+            AnySlice::Text(view) if arrow_type == ArrowType::Utf8 => {
+                offsets.extend_from_slice(&0i32.to_le_bytes());
+                for row_index in 0..num_rows {
+                    let text = view.get(row_index);
+                    set_valid(row_index, text.is_some());
+                    if let Some(text) = text {
+                        values.extend_from_slice(String::from_utf8_lossy(text).as_bytes());
+                    }
+                    offsets.extend_from_slice(&value_offset(values.len())?.to_le_bytes());
+                }
+            }
+            AnySlice::Text(view) => {
+                let scale = match arrow_type {
+                    ArrowType::Decimal { scale, .. } => scale as u8,
+                    _ => unreachable!("Only text and decimals are fetched as text."),
+                };
+                for row_index in 0..num_rows {
+                    let text = view.get(row_index);
+                    set_valid(row_index, text.is_some());
+                    let unscaled = text.map(|text| parse_decimal(text, scale)).transpose()?;
+                    values.extend_from_slice(&unscaled.unwrap_or_default().to_le_bytes());
+                }
+            }
+            AnySlice::Binary(view) => {
+                offsets.extend_from_slice(&0i32.to_le_bytes());
+                for row_index in 0..num_rows {
+                    let bytes = view.get(row_index);
+                    set_valid(row_index, bytes.is_some());
+                    values.extend_from_slice(bytes.unwrap_or_default());
+                    offsets.extend_from_slice(&value_offset(values.len())?.to_le_bytes());
+                }
+            }
+            AnySlice::NullableBit(view) => {
+                values.resize(num_rows.div_ceil(8), 0);
+                for row_index in 0..num_rows {
+                    let bit = at(view, row_index);
+                    set_valid(row_index, bit.is_some());
+                    if bit.is_some_and(|bit| bit.as_bool()) {
+                        values[row_index / 8] |= 1 << (row_index % 8);
+                    }
+                }
+            }
+            AnySlice::NullableI8(view) => {
+                encode_fixed(view, num_rows, set_valid, &mut values, |v| v.to_le_bytes())
+            }
+            AnySlice::NullableI16(view) => {
+                encode_fixed(view, num_rows, set_valid, &mut values, |v| v.to_le_bytes())
+            }
+            AnySlice::NullableI32(view) => {
+                encode_fixed(view, num_rows, set_valid, &mut values, |v| v.to_le_bytes())
+            }
+            AnySlice::NullableI64(view) => {
+                encode_fixed(view, num_rows, set_valid, &mut values, |v| v.to_le_bytes())
+            }
+            AnySlice::NullableF32(view) => {
+                encode_fixed(view, num_rows, set_valid, &mut values, |v| v.to_le_bytes())
+            }
+            AnySlice::NullableF64(view) => {
+                encode_fixed(view, num_rows, set_valid, &mut values, |v| v.to_le_bytes())
+            }
+            AnySlice::NullableDate(view) => {
+                encode_fixed(view, num_rows, set_valid, &mut values, |date| {
+                    // Dates with a year of type `i16` are always within range.
+                    (days_since_epoch(date) as i32).to_le_bytes()
+                })
+            }
+            AnySlice::NullableTime(view) => {
+                encode_fixed(view, num_rows, set_valid, &mut values, |time| {
+                    micros_since_midnight(time).to_le_bytes()
+                })
+            }
+            AnySlice::NullableTimestamp(view) => {
+                encode_fixed(view, num_rows, set_valid, &mut values, |timestamp| {
+                    // Fraction is in nanoseconds
+                    (seconds_since_epoch(timestamp) * 1_000_000
+                        + i64::from(timestamp.fraction) / 1_000)
+                        .to_le_bytes()
+                })
+            }
+            _ => unreachable!("Arrow encoding does not bind buffers of this type."),
+        }
+        if null_count != 0 && !nullable {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Column reported as not nullable contains NULL.",
+            ));
+        }
+        self.nodes
+            .extend_from_slice(&(num_rows as i64).to_le_bytes());
+        self.nodes
+            .extend_from_slice(&(null_count as i64).to_le_bytes());
+        // The validity bitmap may be omitted, if there are no nulls.
+        if null_count == 0 {
+            validity.clear();
+        }
+        self.push_buffer(&validity);
+        if !offsets.is_empty() {
+            self.push_buffer(&offsets);
+        }
+        self.push_buffer(&values);
+        Ok(())
+    }
+}
+
+/// Appends the values of a fixed size column, encoded with `encode`. `NULL`s are encoded as
+/// zeroes.
+fn encode_fixed<T, const N: usize>(
+    view: &NullableSlice<'_, T>,
+    num_rows: usize,
+    mut set_valid: impl FnMut(usize, bool),
+    values: &mut Vec<u8>,
+    encode: impl Fn(&T) -> [u8; N],
+) {
+    for row_index in 0..num_rows {
+        let value = at(view, row_index);
+        set_valid(row_index, value.is_some());
+        values.extend_from_slice(&value.map(&encode).unwrap_or([0; N]));
+    }
+}
+
+fn at<'a, T>(values: &NullableSlice<'a, T>, row_index: usize) -> Option<&'a T> {
+    let (values, indicators) = values.raw_values();
+    if indicators[row_index] == NULL_DATA {
+        None
+    } else {
+        Some(&values[row_index])
+    }
+}
+
+/// Offsets of variable sized values are 32 Bit integers.
+fn value_offset(len: usize) -> io::Result<i32> {
+    i32::try_from(len).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Text or binary values of a batch exceed 2 GiB. Use a smaller batch size.",
+        )
+    })
+}
+
+fn parse_decimal(text: &[u8], scale: u8) -> io::Result<i128> {
+    std::str::from_utf8(text)
+        .ok()
+        .and_then(|text| decimal_to_scaled_integer(text.trim(), scale).ok())
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid decimal: {}", String::from_utf8_lossy(text)),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::DataType;
+
+    use super::{write_message, ArrowType, Body};
+
+    #[test]
+    fn buffers_are_padded_to_eight_bytes() {
+        let mut body = Body::default();
+
+        body.push_buffer(&[1, 2, 3]);
+        body.push_buffer(&[4]);
+
+        assert_eq!(
+            vec![1, 2, 3, 0, 0, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0],
+            body.bytes
+        );
+        // Offset and length of each buffer
+        let buffers: Vec<_> = [0i64, 3, 8, 1]
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        assert_eq!(buffers, body.buffers);
+    }
+
+    #[test]
+    fn message_metadata_is_padded_to_eight_bytes() {
+        let mut out = Vec::new();
+
+        write_message(&mut out, &[1, 2, 3, 4], &[5; 8]).unwrap();
+
+        assert_eq!(
+            vec![
+                0xFF, 0xFF, 0xFF, 0xFF, 8, 0, 0, 0, 1, 2, 3, 4, 0, 0, 0, 0, 5, 5, 5, 5, 5, 5, 5, 5
+            ],
+            out
+        );
+    }
+
+    #[test]
+    fn decimal_without_precision_is_text() {
+        let (arrow_type, _) = ArrowType::for_data_type(
+            DataType::Decimal {
+                precision: 0,
+                scale: 0,
+            },
+            1,
+            Some(10),
+        )
+        .unwrap();
+
+        assert_eq!(ArrowType::Utf8, arrow_type);
+    }
+}
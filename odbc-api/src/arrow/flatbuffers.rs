@@ -0,0 +1,199 @@
+//! Minimal builder for flatbuffers, sufficient to encode the metadata of Arrow IPC messages.
+
+/// Position of an object within a [`Builder`], referenced by objects created later.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Offset(usize);
+
+/// Builds a flatbuffer. Like the builders of the flatbuffers project, the buffer is filled back to
+/// front, so objects must be created before the objects referring to them. Positions are counted
+/// from the end of the buffer, so they do not change as more data is prepended. Default values of
+/// table fields are not omitted. Tables must not be nested, i.e. all objects referenced by a table
+/// must be created before [`Builder::start_table`] is called.
+#[derive(Debug)]
+pub(super) struct Builder {
+    /// Content of the buffer in reverse order.
+    reversed: Vec<u8>,
+    /// Largest alignment required by any value written so far.
+    min_align: usize,
+    /// Slot and position of each field of the table currently being built.
+    fields: Vec<(usize, usize)>,
+    /// Position at which the table currently being built started.
+    table_start: usize,
+}
+
+impl Builder {
+    pub(super) fn new() -> Self {
+        Self {
+            reversed: Vec::new(),
+            min_align: 1,
+            fields: Vec::new(),
+            table_start: 0,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.reversed.len()
+    }
+
+    /// Pads the buffer, so it is aligned to `align` after prepending `additional` bytes.
+    fn prep(&mut self, align: usize, additional: usize) {
+        self.min_align = self.min_align.max(align);
+        let padding = (align - (self.len() + additional) % align) % align;
+        self.reversed.resize(self.len() + padding, 0);
+    }
+
+    fn prepend(&mut self, bytes: &[u8]) {
+        self.reversed.extend(bytes.iter().rev());
+    }
+
+    /// Prepends a scalar, given in little endian, aligned to its size.
+    fn prepend_scalar(&mut self, bytes: &[u8]) {
+        self.prep(bytes.len(), 0);
+        self.prepend(bytes);
+    }
+
+    /// Prepends an unsigned offset pointing to `target`, which is relative to the offset itself.
+    fn prepend_offset(&mut self, target: Offset) {
+        self.prep(4, 0);
+        let relative = (self.len() + 4 - target.0) as u32;
+        self.prepend(&relative.to_le_bytes());
+    }
+
+    /// Zero terminated, length prefixed UTF-8 string.
+    pub(super) fn create_string(&mut self, text: &str) -> Offset {
+        self.prep(4, text.len() + 1);
+        self.prepend(&[0]);
+        self.prepend(text.as_bytes());
+        self.prepend(&(text.len() as u32).to_le_bytes());
+        Offset(self.len())
+    }
+
+    /// Vector of structs. `structs` holds the elements, each `struct_size` bytes long and encoded
+    /// in little endian. `align` is the alignment of the struct.
+    pub(super) fn create_struct_vector(
+        &mut self,
+        structs: &[u8],
+        struct_size: usize,
+        align: usize,
+    ) -> Offset {
+        self.prep(align.max(4), structs.len());
+        self.prepend(structs);
+        self.prepend(&((structs.len() / struct_size) as u32).to_le_bytes());
+        Offset(self.len())
+    }
+
+    /// Vector of offsets to tables or strings.
+    pub(super) fn create_offset_vector(&mut self, targets: &[Offset]) -> Offset {
+        self.prep(4, 4 * targets.len());
+        for &target in targets.iter().rev() {
+            self.prepend_offset(target);
+        }
+        self.prepend(&(targets.len() as u32).to_le_bytes());
+        Offset(self.len())
+    }
+
+    pub(super) fn start_table(&mut self) {
+        self.fields.clear();
+        self.table_start = self.len();
+    }
+
+    pub(super) fn add_u8(&mut self, slot: usize, value: u8) {
+        self.prepend_scalar(&[value]);
+        self.fields.push((slot, self.len()));
+    }
+
+    pub(super) fn add_bool(&mut self, slot: usize, value: bool) {
+        self.add_u8(slot, u8::from(value))
+    }
+
+    pub(super) fn add_i16(&mut self, slot: usize, value: i16) {
+        self.prepend_scalar(&value.to_le_bytes());
+        self.fields.push((slot, self.len()));
+    }
+
+    pub(super) fn add_i32(&mut self, slot: usize, value: i32) {
+        self.prepend_scalar(&value.to_le_bytes());
+        self.fields.push((slot, self.len()));
+    }
+
+    pub(super) fn add_i64(&mut self, slot: usize, value: i64) {
+        self.prepend_scalar(&value.to_le_bytes());
+        self.fields.push((slot, self.len()));
+    }
+
+    pub(super) fn add_offset(&mut self, slot: usize, target: Offset) {
+        self.prepend_offset(target);
+        self.fields.push((slot, self.len()));
+    }
+
+    /// Completes the table, by prepending its vtable. The vtable holds the positions of the fields
+    /// relative to the start of the table.
+    pub(super) fn end_table(&mut self) -> Offset {
+        // Placeholder for the offset of the vtable, which is the first field of every table.
+        self.prepend_scalar(&0i32.to_le_bytes());
+        let table = self.len();
+        let num_slots = self
+            .fields
+            .iter()
+            .map(|&(slot, _)| slot + 1)
+            .max()
+            .unwrap_or(0);
+        let mut vtable = vec![0u16; num_slots + 2];
+        vtable[0] = (2 * vtable.len()) as u16;
+        vtable[1] = (table - self.table_start) as u16;
+        for &(slot, position) in &self.fields {
+            vtable[slot + 2] = (table - position) as u16;
+        }
+        // Tables are aligned to four bytes, so the vtable is aligned to two bytes.
+        for entry in vtable.iter().rev() {
+            self.prepend(&entry.to_le_bytes());
+        }
+        // The table refers to its vtable with a signed offset, which is subtracted from the address
+        // of the table.
+        let relative = (self.len() - table) as i32;
+        for (index, byte) in relative.to_le_bytes().into_iter().enumerate() {
+            self.reversed[table - 1 - index] = byte;
+        }
+        Offset(table)
+    }
+
+    /// Prepends the offset of the `root` table and returns the finished buffer.
+    pub(super) fn finish(mut self, root: Offset) -> Vec<u8> {
+        self.prep(self.min_align, 4);
+        self.prepend_offset(root);
+        self.reversed.reverse();
+        self.reversed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Builder;
+
+    #[test]
+    fn table_with_scalar_and_string() {
+        let mut builder = Builder::new();
+        let name = builder.create_string("a");
+        builder.start_table();
+        builder.add_offset(0, name);
+        builder.add_i16(1, 7);
+        let root = builder.end_table();
+        let bytes = builder.finish(root);
+
+        let read_u32 = |pos: usize| u32::from_le_bytes(bytes[pos..pos + 4].try_into().unwrap());
+        let read_u16 = |pos: usize| u16::from_le_bytes(bytes[pos..pos + 2].try_into().unwrap());
+        assert_eq!(0, bytes.len() % 4);
+        // Follow the root offset to the table and the table to its vtable.
+        let table = read_u32(0) as usize;
+        let vtable = (table as i64
+            - i32::from_le_bytes(bytes[table..table + 4].try_into().unwrap()) as i64)
+            as usize;
+        assert_eq!(8, read_u16(vtable));
+        let name_field = table + read_u16(vtable + 4) as usize;
+        let number_field = table + read_u16(vtable + 6) as usize;
+        assert_eq!(7, read_u16(number_field));
+        let string = name_field + read_u32(name_field) as usize;
+        assert_eq!(1, read_u32(string));
+        assert_eq!(b"a\0", &bytes[string + 4..string + 6]);
+    }
+}
@@ -0,0 +1,673 @@
+//! Encodes result sets as Avro object container files. Requires the `avro` feature. See
+//! [`crate::Cursor::write_avro`].
+//!
+//! Columns are mapped to Avro types based on their SQL data type:
+//!
+//! | SQL                                | Avro                                          |
+//! |------------------------------------|-----------------------------------------------|
+//! | `BIT`                              | `boolean`                                     |
+//! | `TINYINT`, `SMALLINT`, `INTEGER`   | `int`                                         |
+//! | `BIGINT`                           | `long`                                        |
+//! | `REAL`, `FLOAT(p <= 24)`           | `float`                                       |
+//! | `DOUBLE`, `FLOAT`                  | `double`                                      |
+//! | `DECIMAL`, `NUMERIC` (`p <= 38`)   | `bytes` with logical type `decimal`           |
+//! | `DATE`                             | `int` with logical type `date`                |
+//! | `TIME`                             | `long` with logical type `time-micros`        |
+//! | `TIMESTAMP`                        | `long` with logical type `timestamp-micros`   |
+//! | `BINARY`, `VARBINARY`              | `bytes`                                       |
+//! | Everything else                    | `string`                                      |
+//!
+//! Columns which may contain `NULL` are mapped to a union of `null` and the respective type.
+
+use std::{
+    collections::{hash_map::RandomState, HashSet},
+    hash::{BuildHasher, Hasher},
+    io::{self, Write},
+};
+
+use crate::{
+    buffers::{AnySlice, BufferDesc, ColumnarAnyBuffer, NullableSlice},
+    epoch::{days_since_epoch, micros_since_midnight, seconds_since_epoch},
+    sys::{Timestamp, NULL_DATA},
+    Cursor, DataType, Error, Nullability, ResultSetSchema,
+};
+
+/// Precision of timestamps written to Avro files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampPrecision {
+    /// Logical type `timestamp-millis`.
+    Millis,
+    /// Logical type `timestamp-micros`.
+    Micros,
+}
+
+/// Options for writing result sets as Avro. See [`crate::Cursor::write_avro`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AvroOptions {
+    record_name: String,
+    namespace: Option<String>,
+    batch_size: usize,
+    max_str_len: Option<usize>,
+    timestamp_precision: TimestampPrecision,
+}
+
+impl Default for AvroOptions {
+    fn default() -> Self {
+        Self {
+            record_name: "Row".to_owned(),
+            namespace: None,
+            batch_size: 1000,
+            max_str_len: Some(4096),
+            timestamp_precision: TimestampPrecision::Micros,
+        }
+    }
+}
+
+impl AvroOptions {
+    /// Rows are written as records named `Row`, without a namespace, in blocks of 1000 rows.
+    /// Text and binary columns hold at most 4096 bytes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Name of the record type in the schema.
+    pub fn with_record_name(mut self, record_name: impl Into<String>) -> Self {
+        self.record_name = record_name.into();
+        self
+    }
+
+    /// Namespace of the record type in the schema.
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    /// Number of rows fetched at once. Each batch is written as one block of the container file.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Upper bound for the length of text and binary values in bytes. The size reported by the
+    /// driver is used if it is smaller. `None` to always use the size reported by the driver, which
+    /// fails with [`Error::UnboundedColumnLength`] for columns without an upper bound, like
+    /// `VARCHAR(max)`.
+    pub fn with_max_str_len(mut self, max_str_len: Option<usize>) -> Self {
+        self.max_str_len = max_str_len;
+        self
+    }
+
+    /// Precision of timestamps. Microseconds by default.
+    pub fn with_timestamp_precision(mut self, timestamp_precision: TimestampPrecision) -> Self {
+        self.timestamp_precision = timestamp_precision;
+        self
+    }
+}
+
+/// Avro type a column is mapped to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AvroType {
+    Boolean,
+    Int,
+    Long,
+    Float,
+    Double,
+    Decimal { precision: usize, scale: usize },
+    Date,
+    TimeMicros,
+    Timestamp,
+    Bytes,
+    String,
+}
+
+impl AvroType {
+    /// Avro type of a column and the buffer used to fetch it.
+    fn for_data_type(
+        data_type: DataType,
+        nullable: bool,
+        column_number: u16,
+        max_str_len: Option<usize>,
+    ) -> Result<(Self, BufferDesc), Error> {
+        let limit = |length: usize| match max_str_len {
+            Some(upper_bound) if length == 0 || length > upper_bound => Ok(upper_bound),
+            None if length == 0 => Err(Error::UnboundedColumnLength { column_number }),
+            _ => Ok(length),
+        };
+        let mapped = match data_type {
+            DataType::Bit => (AvroType::Boolean, BufferDesc::Bit { nullable }),
+            DataType::TinyInt | DataType::SmallInt | DataType::Integer => {
+                (AvroType::Int, BufferDesc::I32 { nullable })
+            }
+            DataType::BigInt => (AvroType::Long, BufferDesc::I64 { nullable }),
+            DataType::Real | DataType::Float { precision: 0..=24 } => {
+                (AvroType::Float, BufferDesc::F32 { nullable })
+            }
+            DataType::Double | DataType::Float { .. } => {
+                (AvroType::Double, BufferDesc::F64 { nullable })
+            }
+            // A precision of zero indicates the driver does not know it, so we can not declare
+            // the logical type.
+            DataType::Decimal { precision, scale } | DataType::Numeric { precision, scale }
+                if (1..=38).contains(&precision) && (0..=precision as i16).contains(&scale) =>
+            {
+                (
+                    AvroType::Decimal {
+                        precision,
+                        scale: scale as usize,
+                    },
+                    // Sign, decimal point and digits.
+                    BufferDesc::Text {
+                        max_str_len: precision + 2,
+                    },
+                )
+            }
+            DataType::Date => (AvroType::Date, BufferDesc::Date { nullable }),
+            DataType::Time { .. } => (AvroType::TimeMicros, BufferDesc::Time { nullable }),
+            DataType::Timestamp { .. } => (AvroType::Timestamp, BufferDesc::Timestamp { nullable }),
+            DataType::Binary { length }
+            | DataType::Varbinary { length }
+            | DataType::LongVarbinary { length } => (
+                AvroType::Bytes,
+                BufferDesc::Binary {
+                    length: limit(length)?,
+                },
+            ),
+            other => (
+                AvroType::String,
+                BufferDesc::Text {
+                    max_str_len: limit(other.utf8_len().unwrap_or(0))?,
+                },
+            ),
+        };
+        Ok(mapped)
+    }
+
+    fn schema(self, timestamp_precision: TimestampPrecision) -> String {
+        match self {
+            AvroType::Boolean => r#""boolean""#.to_owned(),
+            AvroType::Int => r#""int""#.to_owned(),
+            AvroType::Long => r#""long""#.to_owned(),
+            AvroType::Float => r#""float""#.to_owned(),
+            AvroType::Double => r#""double""#.to_owned(),
+            AvroType::Decimal { precision, scale } => format!(
+                r#"{{"type":"bytes","logicalType":"decimal","precision":{precision},"scale":{scale}}}"#
+            ),
+            AvroType::Date => r#"{"type":"int","logicalType":"date"}"#.to_owned(),
+            AvroType::TimeMicros => r#"{"type":"long","logicalType":"time-micros"}"#.to_owned(),
+            AvroType::Timestamp => {
+                let logical_type = match timestamp_precision {
+                    TimestampPrecision::Millis => "timestamp-millis",
+                    TimestampPrecision::Micros => "timestamp-micros",
+                };
+                format!(r#"{{"type":"long","logicalType":"{logical_type}"}}"#)
+            }
+            AvroType::Bytes => r#""bytes""#.to_owned(),
+            AvroType::String => r#""string""#.to_owned(),
+        }
+    }
+}
+
+/// Avro schema of the records written for a result set with the given `schema`, as JSON. Fails
+/// with [`Error::UnboundedColumnLength`] under the same conditions as [`write_avro`].
+pub fn avro_schema(schema: &ResultSetSchema, options: &AvroOptions) -> Result<String, Error> {
+    let fields = schema
+        .columns
+        .iter()
+        .zip(field_names(schema))
+        .enumerate()
+        .map(|(index, (column, name))| {
+            let nullable = column.nullability != Nullability::NoNulls;
+            let (avro_type, _) = AvroType::for_data_type(
+                column.data_type,
+                nullable,
+                (index + 1) as u16,
+                options.max_str_len,
+            )?;
+            let avro_type = avro_type.schema(options.timestamp_precision);
+            Ok(if nullable {
+                format!(r#"{{"name":"{name}","type":["null",{avro_type}],"default":null}}"#)
+            } else {
+                format!(r#"{{"name":"{name}","type":{avro_type}}}"#)
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    let namespace = options
+        .namespace
+        .as_ref()
+        .map(|namespace| format!(r#","namespace":"{}""#, escape_json(namespace)))
+        .unwrap_or_default();
+    Ok(format!(
+        r#"{{"type":"record","name":"{}"{namespace},"fields":[{}]}}"#,
+        escape_json(&options.record_name),
+        fields.join(",")
+    ))
+}
+
+/// Fetches all rows of `cursor` and writes them to `writer` as an Avro object container file,
+/// without compression. Each fetched batch is written as one block, so the result is streamed
+/// rather than held in memory. Returns the number of rows written.
+///
+/// ```no_run
+/// use std::io::stdout;
+/// use odbc_api::{avro::{write_avro, AvroOptions}, Connection, Error};
+///
+/// fn orders_as_avro(conn: &Connection<'_>) -> Result<(), Error> {
+///     if let Some(cursor) = conn.execute("SELECT id, amount, placed FROM Orders", ())? {
+///         let options = AvroOptions::new().with_record_name("Order");
+///         write_avro(cursor, stdout().lock(), &options)?;
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn write_avro(
+    mut cursor: impl Cursor,
+    mut writer: impl Write,
+    options: &AvroOptions,
+) -> Result<usize, Error> {
+    let schema = ResultSetSchema::for_cursor(&mut cursor)?;
+    let mut types = Vec::with_capacity(schema.columns.len());
+    let mut descs = Vec::with_capacity(schema.columns.len());
+    for (index, column) in schema.columns.iter().enumerate() {
+        let nullable = column.nullability != Nullability::NoNulls;
+        let (avro_type, desc) = AvroType::for_data_type(
+            column.data_type,
+            nullable,
+            (index + 1) as u16,
+            options.max_str_len,
+        )?;
+        types.push((avro_type, nullable));
+        descs.push(desc);
+    }
+    let buffer = ColumnarAnyBuffer::try_from_descs(options.batch_size, descs)?;
+    let mut row_set_cursor = cursor.bind_buffer(buffer)?;
+
+    let sync_marker = sync_marker();
+    let mut header = Vec::new();
+    header.extend_from_slice(b"Obj\x01");
+    write_long(&mut header, 2);
+    write_bytes(&mut header, b"avro.schema");
+    write_bytes(&mut header, avro_schema(&schema, options)?.as_bytes());
+    write_bytes(&mut header, b"avro.codec");
+    write_bytes(&mut header, b"null");
+    write_long(&mut header, 0);
+    header.extend_from_slice(&sync_marker);
+    writer
+        .write_all(&header)
+        .map_err(Error::FailedWritingAvro)?;
+
+    let mut num_rows = 0;
+    let mut block = Vec::new();
+    let mut data = Vec::new();
+    while let Some(batch) = row_set_cursor.fetch_with_truncation_check(true)? {
+        let views: Vec<_> = (0..batch.num_cols())
+            .map(|index| batch.column(index))
+            .collect();
+        data.clear();
+        for row_index in 0..batch.num_rows() {
+            for (view, &(avro_type, nullable)) in views.iter().zip(&types) {
+                encode_value(&mut data, view, row_index, avro_type, nullable, options)
+                    .map_err(Error::FailedWritingAvro)?;
+            }
+        }
+        block.clear();
+        write_long(&mut block, batch.num_rows() as i64);
+        write_long(&mut block, data.len() as i64);
+        block.extend_from_slice(&data);
+        block.extend_from_slice(&sync_marker);
+        writer.write_all(&block).map_err(Error::FailedWritingAvro)?;
+        num_rows += batch.num_rows();
+    }
+    writer.flush().map_err(Error::FailedWritingAvro)?;
+    Ok(num_rows)
+}
+
+/// Appends the value at `row_index` of `view`, encoded as `avro_type`.
+fn encode_value(
+    out: &mut Vec<u8>,
+    view: &AnySlice<'_>,
+    row_index: usize,
+    avro_type: AvroType,
+    nullable: bool,
+    options: &AvroOptions,
+) -> io::Result<()> {
+    let precision = options.timestamp_precision;
+    match view {
+        AnySlice::Text(view) => encode_nullable(out, nullable, view.get(row_index), |out, text| {
+            if let AvroType::Decimal { scale, .. } = avro_type {
+                let unscaled = unscaled_decimal(text, scale).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Invalid decimal: {}", String::from_utf8_lossy(text)),
+                    )
+                })?;
+                write_bytes(out, &twos_complement(unscaled));
+            } else {
+                write_bytes(out, String::from_utf8_lossy(text).as_bytes());
+            }
+            Ok(())
+        }),
+        AnySlice::Binary(view) => {
+            encode_nullable(out, nullable, view.get(row_index), |out, bytes| {
+                write_bytes(out, bytes);
+                Ok(())
+            })
+        }
+        AnySlice::Bit(values) => {
+            out.push(u8::from(values[row_index].as_bool()));
+            Ok(())
+        }
+        AnySlice::NullableBit(values) => {
+            encode_nullable(out, nullable, at(values, row_index), |out, bit| {
+                out.push(u8::from(bit.as_bool()));
+                Ok(())
+            })
+        }
+        AnySlice::I32(values) => {
+            write_long(out, values[row_index].into());
+            Ok(())
+        }
+        AnySlice::NullableI32(values) => {
+            encode_nullable(out, nullable, at(values, row_index), |out, &value| {
+                write_long(out, value.into());
+                Ok(())
+            })
+        }
+        AnySlice::I64(values) => {
+            write_long(out, values[row_index]);
+            Ok(())
+        }
+        AnySlice::NullableI64(values) => {
+            encode_nullable(out, nullable, at(values, row_index), |out, &value| {
+                write_long(out, value);
+                Ok(())
+            })
+        }
+        AnySlice::F32(values) => {
+            out.extend_from_slice(&values[row_index].to_le_bytes());
+            Ok(())
+        }
+        AnySlice::NullableF32(values) => {
+            encode_nullable(out, nullable, at(values, row_index), |out, value| {
+                out.extend_from_slice(&value.to_le_bytes());
+                Ok(())
+            })
+        }
+        AnySlice::F64(values) => {
+            out.extend_from_slice(&values[row_index].to_le_bytes());
+            Ok(())
+        }
+        AnySlice::NullableF64(values) => {
+            encode_nullable(out, nullable, at(values, row_index), |out, value| {
+                out.extend_from_slice(&value.to_le_bytes());
+                Ok(())
+            })
+        }
+        AnySlice::Date(values) => {
+            write_long(out, days_since_epoch(&values[row_index]));
+            Ok(())
+        }
+        AnySlice::NullableDate(values) => {
+            encode_nullable(out, nullable, at(values, row_index), |out, date| {
+                write_long(out, days_since_epoch(date));
+                Ok(())
+            })
+        }
+        AnySlice::Time(values) => {
+            write_long(out, micros_since_midnight(&values[row_index]));
+            Ok(())
+        }
+        AnySlice::NullableTime(values) => {
+            encode_nullable(out, nullable, at(values, row_index), |out, time| {
+                write_long(out, micros_since_midnight(time));
+                Ok(())
+            })
+        }
+        AnySlice::Timestamp(values) => {
+            write_long(out, since_epoch(&values[row_index], precision));
+            Ok(())
+        }
+        AnySlice::NullableTimestamp(values) => {
+            encode_nullable(out, nullable, at(values, row_index), |out, timestamp| {
+                write_long(out, since_epoch(timestamp, precision));
+                Ok(())
+            })
+        }
+        _ => unreachable!("Avro encoding does not bind buffers of this type."),
+    }
+}
+
+/// Encodes `value` as a member of the union `["null", T]` if `nullable` is `true`.
+fn encode_nullable<T>(
+    out: &mut Vec<u8>,
+    nullable: bool,
+    value: Option<T>,
+    encode: impl FnOnce(&mut Vec<u8>, T) -> io::Result<()>,
+) -> io::Result<()> {
+    match (value, nullable) {
+        (None, true) => {
+            write_long(out, 0);
+            Ok(())
+        }
+        (Some(value), true) => {
+            write_long(out, 1);
+            encode(out, value)
+        }
+        (Some(value), false) => encode(out, value),
+        (None, false) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Column reported as not nullable contains NULL.",
+        )),
+    }
+}
+
+fn at<'a, T>(values: &NullableSlice<'a, T>, row_index: usize) -> Option<&'a T> {
+    let (values, indicators) = values.raw_values();
+    if indicators[row_index] == NULL_DATA {
+        None
+    } else {
+        Some(&values[row_index])
+    }
+}
+
+/// Zig zag encoded variable length integer, used for `int` and `long`.
+fn write_long(out: &mut Vec<u8>, value: i64) {
+    let mut zig_zag = ((value << 1) ^ (value >> 63)) as u64;
+    while zig_zag >= 0x80 {
+        out.push((zig_zag as u8 & 0x7F) | 0x80);
+        zig_zag >>= 7;
+    }
+    out.push(zig_zag as u8);
+}
+
+/// Length prefixed bytes, used for `bytes` and `string`.
+fn write_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    write_long(out, bytes.len() as i64);
+    out.extend_from_slice(bytes);
+}
+
+/// Parses decimal text like `-12.3` into its unscaled value with `scale` digits after the decimal
+/// point, i.e. `-1230` for a scale of two. `None` if the text is not a decimal number, or has more
+/// digits after the decimal point than `scale`.
+fn unscaled_decimal(text: &[u8], scale: usize) -> Option<i128> {
+    let text = std::str::from_utf8(text).ok()?.trim();
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    if fraction.len() > scale
+        || integer.is_empty() && fraction.is_empty()
+        || !integer
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    let mut unscaled: i128 = 0;
+    for digit in integer.bytes().chain(fraction.bytes()) {
+        unscaled = unscaled
+            .checked_mul(10)?
+            .checked_add(i128::from(digit - b'0'))?;
+    }
+    for _ in fraction.len()..scale {
+        unscaled = unscaled.checked_mul(10)?;
+    }
+    Some(if negative { -unscaled } else { unscaled })
+}
+
+/// Shortest big endian two's complement representation, as required for Avro decimals.
+fn twos_complement(value: i128) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let fill = if value < 0 { 0xFF } else { 0x00 };
+    // Skip leading bytes which only repeat the sign.
+    let start = (0..bytes.len() - 1)
+        .find(|&index| bytes[index] != fill || (bytes[index + 1] & 0x80) != (fill & 0x80))
+        .unwrap_or(bytes.len() - 1);
+    bytes[start..].to_vec()
+}
+
+/// Timestamp without time zone interpreted as UTC.
+fn since_epoch(timestamp: &Timestamp, precision: TimestampPrecision) -> i64 {
+    let seconds = seconds_since_epoch(timestamp);
+    // Fraction is in nanoseconds
+    let fraction = i64::from(timestamp.fraction);
+    match precision {
+        TimestampPrecision::Millis => seconds * 1_000 + fraction / 1_000_000,
+        TimestampPrecision::Micros => seconds * 1_000_000 + fraction / 1_000,
+    }
+}
+
+/// Avro names must start with a letter or underscore and contain only letters, digits and
+/// underscores.
+/// Valid and unique Avro field names for the columns of `schema`. Duplicate names, e.g. from
+/// joins or after replacing invalid characters, are suffixed with `_2`, `_3` and so on.
+fn field_names(schema: &ResultSetSchema) -> Vec<String> {
+    let mut used = HashSet::new();
+    schema
+        .columns
+        .iter()
+        .enumerate()
+        .map(|(index, column)| {
+            let base = field_name(&column.name, index);
+            let mut name = base.clone();
+            let mut suffix = 2;
+            while !used.insert(name.clone()) {
+                name = format!("{base}_{suffix}");
+                suffix += 1;
+            }
+            name
+        })
+        .collect()
+}
+
+fn field_name(column_name: &str, index: usize) -> String {
+    let mut name: String = column_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if name.is_empty() {
+        name = format!("column_{}", index + 1);
+    } else if name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    name
+}
+
+fn escape_json(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Marker separating the blocks of a container file. Should be unique for each file.
+fn sync_marker() -> [u8; 16] {
+    let random = || RandomState::new().build_hasher().finish().to_le_bytes();
+    let mut marker = [0; 16];
+    marker[..8].copy_from_slice(&random());
+    marker[8..].copy_from_slice(&random());
+    marker
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ColumnSchema, DataType, Error, Nullability, ResultSetSchema};
+
+    use super::{field_names, twos_complement, unscaled_decimal, write_long, AvroType};
+
+    #[test]
+    fn zig_zag_encoding() {
+        let encode = |value| {
+            let mut out = Vec::new();
+            write_long(&mut out, value);
+            out
+        };
+
+        assert_eq!(vec![0x00], encode(0));
+        assert_eq!(vec![0x01], encode(-1));
+        assert_eq!(vec![0x02], encode(1));
+        assert_eq!(vec![0x7F], encode(-64));
+        assert_eq!(vec![0x80, 0x01], encode(64));
+    }
+
+    #[test]
+    fn decimal_encoding() {
+        assert_eq!(Some(-1230), unscaled_decimal(b"-12.3", 2));
+        assert_eq!(Some(50), unscaled_decimal(b".5", 2));
+        assert_eq!(Some(700), unscaled_decimal(b"7", 2));
+        assert_eq!(None, unscaled_decimal(b"1.234", 2));
+        assert_eq!(None, unscaled_decimal(b"abc", 2));
+
+        assert_eq!(vec![0x00], twos_complement(0));
+        assert_eq!(vec![0x7F], twos_complement(127));
+        assert_eq!(vec![0x00, 0x80], twos_complement(128));
+        assert_eq!(vec![0xFF], twos_complement(-1));
+        assert_eq!(vec![0x80], twos_complement(-128));
+        assert_eq!(vec![0xFF, 0x7F], twos_complement(-129));
+    }
+
+    #[test]
+    fn duplicate_field_names_are_suffixed() {
+        let column = |name: &str| ColumnSchema {
+            name: name.to_owned(),
+            data_type: DataType::Integer,
+            nullability: Nullability::Nullable,
+        };
+        let schema = ResultSetSchema {
+            columns: vec![column("id"), column("id"), column("id_2"), column("i-d")],
+        };
+
+        assert_eq!(vec!["id", "id_2", "id_2_2", "i_d"], field_names(&schema));
+    }
+
+    #[test]
+    fn decimal_without_precision_is_string() {
+        let decimal = DataType::Decimal {
+            precision: 0,
+            scale: 0,
+        };
+
+        let (avro_type, _) = AvroType::for_data_type(decimal, true, 1, Some(10)).unwrap();
+
+        assert_eq!(AvroType::String, avro_type);
+    }
+
+    #[test]
+    fn unbounded_text_requires_max_str_len() {
+        let text = DataType::Varchar { length: 0 };
+
+        let result = AvroType::for_data_type(text, true, 3, None);
+
+        assert!(matches!(
+            result,
+            Err(Error::UnboundedColumnLength { column_number: 3 })
+        ));
+    }
+}
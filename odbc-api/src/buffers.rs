@@ -1,23 +1,59 @@
 //! This module contains buffers intended to be bound to ODBC statement handles.
 
 mod any_buffer;
+mod batch_extractor;
 mod bin_column;
+mod buffer_builder;
+mod column_statistics;
 mod column_with_indicator;
 mod columnar;
 mod description;
+mod dictionary_text;
+mod from_cell;
 mod indicator;
 mod item;
+mod manual_row_set;
+mod owned_batch;
+mod quarantine;
+mod row_comparison;
 mod text_column;
 
 pub use self::{
     any_buffer::{AnyBuffer, AnySlice, AnySliceMut, ColumnarAnyBuffer},
+    batch_extractor::BatchExtractor,
     bin_column::{BinColumn, BinColumnIt, BinColumnSliceMut, BinColumnView},
+    buffer_builder::BufferBuilder,
+    column_statistics::{ColumnStatistics, Profile},
     column_with_indicator::{NullableSlice, NullableSliceMut},
     columnar::{ColumnBuffer, ColumnarBuffer, TextRowSet},
     description::BufferDesc,
+    dictionary_text::{DictionaryTextColumn, DictionaryTextIt},
+    from_cell::{CellAccessError, FromCell},
     indicator::Indicator,
     item::Item,
+    manual_row_set::{ColumnWiseLayout, LayoutError, ManualRowSet, RowWiseLayout},
+    owned_batch::OwnedBatch,
+    quarantine::{CellError, CellErrorCause, Quarantine},
+    row_comparison::{CellValue, OwnedCellValue, RowEq, RowOrd},
     text_column::{
         CharColumn, TextColumn, TextColumnIt, TextColumnSliceMut, TextColumnView, WCharColumn,
     },
 };
+
+use std::mem::size_of;
+
+use crate::error::TooLargeBufferSize;
+
+/// Allocates a vector holding `len` copies of `value`. Fails rather than aborting the process, if
+/// not enough memory is available.
+pub(crate) fn try_filled_vec<T: Clone>(len: usize, value: T) -> Result<Vec<T>, TooLargeBufferSize> {
+    let mut values = Vec::new();
+    values
+        .try_reserve_exact(len)
+        .map_err(|_| TooLargeBufferSize {
+            num_elements: len,
+            element_size: size_of::<T>(),
+        })?;
+    values.resize(len, value);
+    Ok(values)
+}
@@ -1,4 +1,4 @@
-use std::{collections::HashSet, ffi::c_void};
+use std::{collections::HashSet, ffi::c_void, mem::size_of};
 
 use odbc_sys::{CDataType, Date, Time, Timestamp};
 
@@ -12,13 +12,14 @@ use crate::{
 use super::{
     bin_column::BinColumnSliceMut,
     column_with_indicator::{
-        OptBitColumn, OptDateColumn, OptF32Column, OptF64Column, OptI16Column, OptI32Column,
-        OptI64Column, OptI8Column, OptTimeColumn, OptTimestampColumn, OptU8Column,
+        ColumnWithIndicator, OptBitColumn, OptDateColumn, OptF32Column, OptF64Column, OptI16Column,
+        OptI32Column, OptI64Column, OptI8Column, OptTimeColumn, OptTimestampColumn, OptU16Column,
+        OptU32Column, OptU64Column, OptU8Column,
     },
     columnar::ColumnBuffer,
     text_column::TextColumnSliceMut,
-    BinColumn, BinColumnView, BufferDesc, CharColumn, ColumnarBuffer, Item, NullableSlice,
-    NullableSliceMut, TextColumn, TextColumnView, WCharColumn,
+    try_filled_vec, BinColumn, BinColumnView, BufferDesc, CharColumn, ColumnarBuffer, Item,
+    NullableSlice, NullableSliceMut, TextColumn, TextColumnView, WCharColumn,
 };
 
 /// Since buffer shapes are same for all time / timestamps independent of the precision and we do
@@ -47,6 +48,9 @@ pub enum AnyBuffer {
     I32(Vec<i32>),
     I64(Vec<i64>),
     U8(Vec<u8>),
+    U16(Vec<u16>),
+    U32(Vec<u32>),
+    U64(Vec<u64>),
     Bit(Vec<Bit>),
     NullableDate(OptDateColumn),
     NullableTime(OptTimeColumn),
@@ -58,9 +62,33 @@ pub enum AnyBuffer {
     NullableI32(OptI32Column),
     NullableI64(OptI64Column),
     NullableU8(OptU8Column),
+    NullableU16(OptU16Column),
+    NullableU32(OptU32Column),
+    NullableU64(OptU64Column),
     NullableBit(OptBitColumn),
 }
 
+/// Buffer of `len` default values. Allocated fallibly if `fallible` is `true`.
+fn filled<T: Default + Clone>(len: usize, fallible: bool) -> Result<Vec<T>, TooLargeBufferSize> {
+    if fallible {
+        try_filled_vec(len, T::default())
+    } else {
+        Ok(vec![T::default(); len])
+    }
+}
+
+/// Buffer of `len` `NULL` values. Allocated fallibly if `fallible` is `true`.
+fn nullable<T: Default + Clone>(
+    len: usize,
+    fallible: bool,
+) -> Result<ColumnWithIndicator<T>, TooLargeBufferSize> {
+    if fallible {
+        ColumnWithIndicator::try_new(len)
+    } else {
+        Ok(ColumnWithIndicator::new(len))
+    }
+}
+
 impl AnyBuffer {
     /// Map buffer description to actual buffer.
     pub fn try_from_desc(max_rows: usize, desc: BufferDesc) -> Result<Self, TooLargeBufferSize> {
@@ -80,73 +108,86 @@ impl AnyBuffer {
         desc: BufferDesc,
         fallible_allocations: bool,
     ) -> Result<Self, TooLargeBufferSize> {
+        let fallible = fallible_allocations;
         let buffer = match desc {
             BufferDesc::Binary { length } => {
-                if fallible_allocations {
+                if fallible {
                     AnyBuffer::Binary(BinColumn::try_new(max_rows, length)?)
                 } else {
                     AnyBuffer::Binary(BinColumn::new(max_rows, length))
                 }
             }
             BufferDesc::Text { max_str_len } => {
-                if fallible_allocations {
+                if fallible {
                     AnyBuffer::Text(TextColumn::try_new(max_rows, max_str_len)?)
                 } else {
                     AnyBuffer::Text(TextColumn::new(max_rows, max_str_len))
                 }
             }
             BufferDesc::WText { max_str_len } => {
-                if fallible_allocations {
+                if fallible {
                     AnyBuffer::WText(TextColumn::try_new(max_rows, max_str_len)?)
                 } else {
                     AnyBuffer::WText(TextColumn::new(max_rows, max_str_len))
                 }
             }
-            BufferDesc::Date { nullable: false } => {
-                AnyBuffer::Date(vec![Date::default(); max_rows])
-            }
-            BufferDesc::Time { nullable: false } => {
-                AnyBuffer::Time(vec![Time::default(); max_rows])
-            }
+            BufferDesc::Date { nullable: false } => AnyBuffer::Date(filled(max_rows, fallible)?),
+            BufferDesc::Time { nullable: false } => AnyBuffer::Time(filled(max_rows, fallible)?),
             BufferDesc::Timestamp { nullable: false } => {
-                AnyBuffer::Timestamp(vec![Timestamp::default(); max_rows])
+                AnyBuffer::Timestamp(filled(max_rows, fallible)?)
             }
-            BufferDesc::F64 { nullable: false } => AnyBuffer::F64(vec![f64::default(); max_rows]),
-            BufferDesc::F32 { nullable: false } => AnyBuffer::F32(vec![f32::default(); max_rows]),
-            BufferDesc::I8 { nullable: false } => AnyBuffer::I8(vec![i8::default(); max_rows]),
-            BufferDesc::I16 { nullable: false } => AnyBuffer::I16(vec![i16::default(); max_rows]),
-            BufferDesc::I32 { nullable: false } => AnyBuffer::I32(vec![i32::default(); max_rows]),
-            BufferDesc::I64 { nullable: false } => AnyBuffer::I64(vec![i64::default(); max_rows]),
-            BufferDesc::U8 { nullable: false } => AnyBuffer::U8(vec![u8::default(); max_rows]),
-            BufferDesc::Bit { nullable: false } => AnyBuffer::Bit(vec![Bit::default(); max_rows]),
+            BufferDesc::F64 { nullable: false } => AnyBuffer::F64(filled(max_rows, fallible)?),
+            BufferDesc::F32 { nullable: false } => AnyBuffer::F32(filled(max_rows, fallible)?),
+            BufferDesc::I8 { nullable: false } => AnyBuffer::I8(filled(max_rows, fallible)?),
+            BufferDesc::I16 { nullable: false } => AnyBuffer::I16(filled(max_rows, fallible)?),
+            BufferDesc::I32 { nullable: false } => AnyBuffer::I32(filled(max_rows, fallible)?),
+            BufferDesc::I64 { nullable: false } => AnyBuffer::I64(filled(max_rows, fallible)?),
+            BufferDesc::U8 { nullable: false } => AnyBuffer::U8(filled(max_rows, fallible)?),
+            BufferDesc::U16 { nullable: false } => AnyBuffer::U16(filled(max_rows, fallible)?),
+            BufferDesc::U32 { nullable: false } => AnyBuffer::U32(filled(max_rows, fallible)?),
+            BufferDesc::U64 { nullable: false } => AnyBuffer::U64(filled(max_rows, fallible)?),
+            BufferDesc::Bit { nullable: false } => AnyBuffer::Bit(filled(max_rows, fallible)?),
             BufferDesc::Date { nullable: true } => {
-                AnyBuffer::NullableDate(OptDateColumn::new(max_rows))
+                AnyBuffer::NullableDate(nullable(max_rows, fallible)?)
             }
             BufferDesc::Time { nullable: true } => {
-                AnyBuffer::NullableTime(OptTimeColumn::new(max_rows))
+                AnyBuffer::NullableTime(nullable(max_rows, fallible)?)
             }
             BufferDesc::Timestamp { nullable: true } => {
-                AnyBuffer::NullableTimestamp(OptTimestampColumn::new(max_rows))
+                AnyBuffer::NullableTimestamp(nullable(max_rows, fallible)?)
             }
             BufferDesc::F64 { nullable: true } => {
-                AnyBuffer::NullableF64(OptF64Column::new(max_rows))
+                AnyBuffer::NullableF64(nullable(max_rows, fallible)?)
             }
             BufferDesc::F32 { nullable: true } => {
-                AnyBuffer::NullableF32(OptF32Column::new(max_rows))
+                AnyBuffer::NullableF32(nullable(max_rows, fallible)?)
+            }
+            BufferDesc::I8 { nullable: true } => {
+                AnyBuffer::NullableI8(nullable(max_rows, fallible)?)
             }
-            BufferDesc::I8 { nullable: true } => AnyBuffer::NullableI8(OptI8Column::new(max_rows)),
             BufferDesc::I16 { nullable: true } => {
-                AnyBuffer::NullableI16(OptI16Column::new(max_rows))
+                AnyBuffer::NullableI16(nullable(max_rows, fallible)?)
             }
             BufferDesc::I32 { nullable: true } => {
-                AnyBuffer::NullableI32(OptI32Column::new(max_rows))
+                AnyBuffer::NullableI32(nullable(max_rows, fallible)?)
             }
             BufferDesc::I64 { nullable: true } => {
-                AnyBuffer::NullableI64(OptI64Column::new(max_rows))
+                AnyBuffer::NullableI64(nullable(max_rows, fallible)?)
+            }
+            BufferDesc::U8 { nullable: true } => {
+                AnyBuffer::NullableU8(nullable(max_rows, fallible)?)
+            }
+            BufferDesc::U16 { nullable: true } => {
+                AnyBuffer::NullableU16(nullable(max_rows, fallible)?)
+            }
+            BufferDesc::U32 { nullable: true } => {
+                AnyBuffer::NullableU32(nullable(max_rows, fallible)?)
+            }
+            BufferDesc::U64 { nullable: true } => {
+                AnyBuffer::NullableU64(nullable(max_rows, fallible)?)
             }
-            BufferDesc::U8 { nullable: true } => AnyBuffer::NullableU8(OptU8Column::new(max_rows)),
             BufferDesc::Bit { nullable: true } => {
-                AnyBuffer::NullableBit(OptBitColumn::new(max_rows))
+                AnyBuffer::NullableBit(nullable(max_rows, fallible)?)
             }
         };
         Ok(buffer)
@@ -175,6 +216,9 @@ impl AnyBuffer {
             AnyBuffer::I64(col) => col,
             AnyBuffer::Bit(col) => col,
             AnyBuffer::U8(col) => col,
+            AnyBuffer::U16(col) => col,
+            AnyBuffer::U32(col) => col,
+            AnyBuffer::U64(col) => col,
             AnyBuffer::NullableF64(col) => col,
             AnyBuffer::NullableF32(col) => col,
             AnyBuffer::NullableDate(col) => col,
@@ -186,6 +230,9 @@ impl AnyBuffer {
             AnyBuffer::NullableI64(col) => col,
             AnyBuffer::NullableBit(col) => col,
             AnyBuffer::NullableU8(col) => col,
+            AnyBuffer::NullableU16(col) => col,
+            AnyBuffer::NullableU32(col) => col,
+            AnyBuffer::NullableU64(col) => col,
         }
     }
 
@@ -205,6 +252,9 @@ impl AnyBuffer {
             AnyBuffer::I64(col) => col,
             AnyBuffer::Bit(col) => col,
             AnyBuffer::U8(col) => col,
+            AnyBuffer::U16(col) => col,
+            AnyBuffer::U32(col) => col,
+            AnyBuffer::U64(col) => col,
             AnyBuffer::NullableF64(col) => col,
             AnyBuffer::NullableF32(col) => col,
             AnyBuffer::NullableDate(col) => col,
@@ -216,6 +266,86 @@ impl AnyBuffer {
             AnyBuffer::NullableI64(col) => col,
             AnyBuffer::NullableBit(col) => col,
             AnyBuffer::NullableU8(col) => col,
+            AnyBuffer::NullableU16(col) => col,
+            AnyBuffer::NullableU32(col) => col,
+            AnyBuffer::NullableU64(col) => col,
+        }
+    }
+
+    /// Copy of the first `num_rows` elements, holding no more rows than that.
+    pub(crate) fn copy_rows(&self, num_rows: usize) -> Self {
+        match self {
+            AnyBuffer::Binary(col) => AnyBuffer::Binary(col.copy_rows(num_rows)),
+            AnyBuffer::Text(col) => AnyBuffer::Text(col.copy_rows(num_rows)),
+            AnyBuffer::WText(col) => AnyBuffer::WText(col.copy_rows(num_rows)),
+            AnyBuffer::Date(col) => AnyBuffer::Date(col[..num_rows].to_vec()),
+            AnyBuffer::Time(col) => AnyBuffer::Time(col[..num_rows].to_vec()),
+            AnyBuffer::Timestamp(col) => AnyBuffer::Timestamp(col[..num_rows].to_vec()),
+            AnyBuffer::F64(col) => AnyBuffer::F64(col[..num_rows].to_vec()),
+            AnyBuffer::F32(col) => AnyBuffer::F32(col[..num_rows].to_vec()),
+            AnyBuffer::I8(col) => AnyBuffer::I8(col[..num_rows].to_vec()),
+            AnyBuffer::I16(col) => AnyBuffer::I16(col[..num_rows].to_vec()),
+            AnyBuffer::I32(col) => AnyBuffer::I32(col[..num_rows].to_vec()),
+            AnyBuffer::I64(col) => AnyBuffer::I64(col[..num_rows].to_vec()),
+            AnyBuffer::U8(col) => AnyBuffer::U8(col[..num_rows].to_vec()),
+            AnyBuffer::U16(col) => AnyBuffer::U16(col[..num_rows].to_vec()),
+            AnyBuffer::U32(col) => AnyBuffer::U32(col[..num_rows].to_vec()),
+            AnyBuffer::U64(col) => AnyBuffer::U64(col[..num_rows].to_vec()),
+            AnyBuffer::Bit(col) => AnyBuffer::Bit(col[..num_rows].to_vec()),
+            AnyBuffer::NullableDate(col) => AnyBuffer::NullableDate(col.copy_rows(num_rows)),
+            AnyBuffer::NullableTime(col) => AnyBuffer::NullableTime(col.copy_rows(num_rows)),
+            AnyBuffer::NullableTimestamp(col) => {
+                AnyBuffer::NullableTimestamp(col.copy_rows(num_rows))
+            }
+            AnyBuffer::NullableF64(col) => AnyBuffer::NullableF64(col.copy_rows(num_rows)),
+            AnyBuffer::NullableF32(col) => AnyBuffer::NullableF32(col.copy_rows(num_rows)),
+            AnyBuffer::NullableI8(col) => AnyBuffer::NullableI8(col.copy_rows(num_rows)),
+            AnyBuffer::NullableI16(col) => AnyBuffer::NullableI16(col.copy_rows(num_rows)),
+            AnyBuffer::NullableI32(col) => AnyBuffer::NullableI32(col.copy_rows(num_rows)),
+            AnyBuffer::NullableI64(col) => AnyBuffer::NullableI64(col.copy_rows(num_rows)),
+            AnyBuffer::NullableU8(col) => AnyBuffer::NullableU8(col.copy_rows(num_rows)),
+            AnyBuffer::NullableU16(col) => AnyBuffer::NullableU16(col.copy_rows(num_rows)),
+            AnyBuffer::NullableU32(col) => AnyBuffer::NullableU32(col.copy_rows(num_rows)),
+            AnyBuffer::NullableU64(col) => AnyBuffer::NullableU64(col.copy_rows(num_rows)),
+            AnyBuffer::NullableBit(col) => AnyBuffer::NullableBit(col.copy_rows(num_rows)),
+        }
+    }
+
+    /// Size in bytes of the first truncated value in the range [0, num_rows), if any, together
+    /// with the maximum size the buffer can hold. The size is `None` if the driver did not report
+    /// it.
+    fn first_truncation(&self, num_rows: usize) -> Option<(Option<usize>, usize)> {
+        match self {
+            AnyBuffer::Text(col) => col
+                .first_truncated_len(num_rows)
+                .map(|size| (size, col.max_len())),
+            AnyBuffer::WText(col) => col
+                .first_truncated_len(num_rows)
+                .map(|size| (size, col.max_len() * size_of::<u16>())),
+            AnyBuffer::Binary(col) => col
+                .first_truncated_len(num_rows)
+                .map(|size| (size, col.max_len())),
+            _ => None,
+        }
+    }
+
+    /// Grows text and binary buffers, so the values in the range [0, num_rows) fit without
+    /// truncation. `true` if the buffer has been reallocated, in which case it must be bound again.
+    pub(crate) fn grow_to_fit(&mut self, num_rows: usize) -> bool {
+        match self {
+            AnyBuffer::Text(col) => col
+                .required_max_str_len(num_rows)
+                .map(|max_str_len| col.resize_max_str(max_str_len, num_rows))
+                .is_some(),
+            AnyBuffer::WText(col) => col
+                .required_max_str_len(num_rows)
+                .map(|max_str_len| col.resize_max_str(max_str_len, num_rows))
+                .is_some(),
+            AnyBuffer::Binary(col) => col
+                .required_max_len(num_rows)
+                .map(|max_len| col.resize_max_element_length(max_len, num_rows))
+                .is_some(),
+            _ => false,
         }
     }
 }
@@ -271,6 +401,13 @@ impl HasDataType for AnyBuffer {
             // stuff if the database has type is signed. I guess. Let's bind it as SmallInt by
             // default, just to be on the safe side.
             AnyBuffer::U8(_) | AnyBuffer::NullableU8(_) => DataType::SmallInt,
+            // Same reasoning as for U8. Bind unsigned integers as the next larger signed type.
+            AnyBuffer::U16(_) | AnyBuffer::NullableU16(_) => DataType::Integer,
+            AnyBuffer::U32(_) | AnyBuffer::NullableU32(_) => DataType::BigInt,
+            AnyBuffer::U64(_) | AnyBuffer::NullableU64(_) => DataType::Numeric {
+                precision: 20,
+                scale: 0,
+            },
             AnyBuffer::Bit(_) | AnyBuffer::NullableBit(_) => DataType::Bit,
         }
     }
@@ -329,7 +466,31 @@ impl ColumnarAnyBuffer {
                 (col_index, AnyBuffer::from_desc(max_rows, buffer_desc))
             })
             .collect();
+        Self::with_unique_indices(columns)
+    }
+
+    /// Like [`Self::from_descs_and_indices`], but fails with [`Error::TooLargeColumnBufferSize`]
+    /// if not enough memory is available to allocate the buffers, rather than aborting the
+    /// process.
+    pub fn try_from_descs_and_indices(
+        max_rows: usize,
+        description: impl Iterator<Item = (u16, BufferDesc)>,
+    ) -> Result<ColumnarBuffer<AnyBuffer>, Error> {
+        let columns: Vec<_> = description
+            .enumerate()
+            .map(|(buffer_index, (col_index, buffer_desc))| {
+                let buffer = AnyBuffer::try_from_desc(max_rows, buffer_desc)
+                    .map_err(|source| source.add_context(buffer_index as u16))?;
+                Ok((col_index, buffer))
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(Self::with_unique_indices(columns))
+    }
 
+    /// # Panics
+    ///
+    /// If the column indices are not unique.
+    fn with_unique_indices(columns: Vec<(u16, AnyBuffer)>) -> ColumnarBuffer<AnyBuffer> {
         // Assert uniqueness of indices
         let mut indices = HashSet::new();
         if columns
@@ -341,6 +502,32 @@ impl ColumnarAnyBuffer {
 
         ColumnarBuffer::new(columns)
     }
+
+    /// Error for the first text or binary value in the current row set, which does not fit into
+    /// the buffer of its column. See [`crate::TruncationPolicy::Cap`].
+    pub(crate) fn value_too_large(&self) -> Option<Error> {
+        let num_rows = self.num_rows();
+        self.columns().iter().find_map(|(column, buffer)| {
+            let (size, cap) = buffer.first_truncation(num_rows)?;
+            Some(Error::ValueTooLarge {
+                column: *column,
+                size,
+                cap,
+            })
+        })
+    }
+
+    /// Grows all text and binary columns holding truncated values in the current row set, so
+    /// these values would fit. `true` if any column has been grown, in which case the buffer must
+    /// be bound again. See [`crate::TruncationPolicy::Grow`].
+    pub(crate) fn grow_truncated_columns(&mut self) -> bool {
+        let num_rows = self.num_rows();
+        let mut grown = false;
+        for column in self.columns_mut() {
+            grown |= column.grow_to_fit(num_rows);
+        }
+        grown
+    }
 }
 
 /// A borrowed view on the valid rows in a column of a [`crate::buffers::ColumnarBuffer`].
@@ -365,6 +552,9 @@ pub enum AnySlice<'a> {
     I32(&'a [i32]),
     I64(&'a [i64]),
     U8(&'a [u8]),
+    U16(&'a [u16]),
+    U32(&'a [u32]),
+    U64(&'a [u64]),
     Bit(&'a [Bit]),
     NullableDate(NullableSlice<'a, Date>),
     NullableTime(NullableSlice<'a, Time>),
@@ -376,6 +566,9 @@ pub enum AnySlice<'a> {
     NullableI32(NullableSlice<'a, i32>),
     NullableI64(NullableSlice<'a, i64>),
     NullableU8(NullableSlice<'a, u8>),
+    NullableU16(NullableSlice<'a, u16>),
+    NullableU32(NullableSlice<'a, u32>),
+    NullableU64(NullableSlice<'a, u64>),
     NullableBit(NullableSlice<'a, Bit>),
 }
 
@@ -448,6 +641,9 @@ unsafe impl<'a> BoundInputSlice<'a> for AnyBuffer {
             AnyBuffer::I32(column) => AnySliceMut::I32(column),
             AnyBuffer::I64(column) => AnySliceMut::I64(column),
             AnyBuffer::U8(column) => AnySliceMut::U8(column),
+            AnyBuffer::U16(column) => AnySliceMut::U16(column),
+            AnyBuffer::U32(column) => AnySliceMut::U32(column),
+            AnyBuffer::U64(column) => AnySliceMut::U64(column),
             AnyBuffer::Bit(column) => AnySliceMut::Bit(column),
             AnyBuffer::NullableDate(column) => AnySliceMut::NullableDate(column.writer_n(num_rows)),
             AnyBuffer::NullableTime(column) => AnySliceMut::NullableTime(column.writer_n(num_rows)),
@@ -461,6 +657,9 @@ unsafe impl<'a> BoundInputSlice<'a> for AnyBuffer {
             AnyBuffer::NullableI32(column) => AnySliceMut::NullableI32(column.writer_n(num_rows)),
             AnyBuffer::NullableI64(column) => AnySliceMut::NullableI64(column.writer_n(num_rows)),
             AnyBuffer::NullableU8(column) => AnySliceMut::NullableU8(column.writer_n(num_rows)),
+            AnyBuffer::NullableU16(column) => AnySliceMut::NullableU16(column.writer_n(num_rows)),
+            AnyBuffer::NullableU32(column) => AnySliceMut::NullableU32(column.writer_n(num_rows)),
+            AnyBuffer::NullableU64(column) => AnySliceMut::NullableU64(column.writer_n(num_rows)),
             AnyBuffer::NullableBit(column) => AnySliceMut::NullableBit(column.writer_n(num_rows)),
         }
     }
@@ -483,6 +682,9 @@ pub enum AnySliceMut<'a> {
     I32(&'a mut [i32]),
     I64(&'a mut [i64]),
     U8(&'a mut [u8]),
+    U16(&'a mut [u16]),
+    U32(&'a mut [u32]),
+    U64(&'a mut [u64]),
     Bit(&'a mut [Bit]),
     NullableDate(NullableSliceMut<'a, Date>),
     NullableTime(NullableSliceMut<'a, Time>),
@@ -494,6 +696,9 @@ pub enum AnySliceMut<'a> {
     NullableI32(NullableSliceMut<'a, i32>),
     NullableI64(NullableSliceMut<'a, i64>),
     NullableU8(NullableSliceMut<'a, u8>),
+    NullableU16(NullableSliceMut<'a, u16>),
+    NullableU32(NullableSliceMut<'a, u32>),
+    NullableU64(NullableSliceMut<'a, u64>),
     NullableBit(NullableSliceMut<'a, Bit>),
 }
 
@@ -557,6 +762,9 @@ unsafe impl ColumnBuffer for AnyBuffer {
             AnyBuffer::I32(col) => col.capacity(),
             AnyBuffer::I64(col) => col.capacity(),
             AnyBuffer::U8(col) => col.capacity(),
+            AnyBuffer::U16(col) => col.capacity(),
+            AnyBuffer::U32(col) => col.capacity(),
+            AnyBuffer::U64(col) => col.capacity(),
             AnyBuffer::Bit(col) => col.capacity(),
             AnyBuffer::NullableDate(col) => col.capacity(),
             AnyBuffer::NullableTime(col) => col.capacity(),
@@ -568,6 +776,9 @@ unsafe impl ColumnBuffer for AnyBuffer {
             AnyBuffer::NullableI32(col) => col.capacity(),
             AnyBuffer::NullableI64(col) => col.capacity(),
             AnyBuffer::NullableU8(col) => col.capacity(),
+            AnyBuffer::NullableU16(col) => col.capacity(),
+            AnyBuffer::NullableU32(col) => col.capacity(),
+            AnyBuffer::NullableU64(col) => col.capacity(),
             AnyBuffer::NullableBit(col) => col.capacity(),
         }
     }
@@ -587,6 +798,9 @@ unsafe impl ColumnBuffer for AnyBuffer {
             AnyBuffer::I32(col) => AnySlice::I32(&col[0..valid_rows]),
             AnyBuffer::I64(col) => AnySlice::I64(&col[0..valid_rows]),
             AnyBuffer::U8(col) => AnySlice::U8(&col[0..valid_rows]),
+            AnyBuffer::U16(col) => AnySlice::U16(&col[0..valid_rows]),
+            AnyBuffer::U32(col) => AnySlice::U32(&col[0..valid_rows]),
+            AnyBuffer::U64(col) => AnySlice::U64(&col[0..valid_rows]),
             AnyBuffer::Bit(col) => AnySlice::Bit(&col[0..valid_rows]),
             AnyBuffer::NullableDate(col) => AnySlice::NullableDate(col.iter(valid_rows)),
             AnyBuffer::NullableTime(col) => AnySlice::NullableTime(col.iter(valid_rows)),
@@ -598,6 +812,9 @@ unsafe impl ColumnBuffer for AnyBuffer {
             AnyBuffer::NullableI32(col) => AnySlice::NullableI32(col.iter(valid_rows)),
             AnyBuffer::NullableI64(col) => AnySlice::NullableI64(col.iter(valid_rows)),
             AnyBuffer::NullableU8(col) => AnySlice::NullableU8(col.iter(valid_rows)),
+            AnyBuffer::NullableU16(col) => AnySlice::NullableU16(col.iter(valid_rows)),
+            AnyBuffer::NullableU32(col) => AnySlice::NullableU32(col.iter(valid_rows)),
+            AnyBuffer::NullableU64(col) => AnySlice::NullableU64(col.iter(valid_rows)),
             AnyBuffer::NullableBit(col) => AnySlice::NullableBit(col.iter(valid_rows)),
         }
     }
@@ -618,6 +835,9 @@ unsafe impl ColumnBuffer for AnyBuffer {
             AnyBuffer::I32(col) => Self::fill_default_slice(&mut col[from..to]),
             AnyBuffer::I64(col) => Self::fill_default_slice(&mut col[from..to]),
             AnyBuffer::U8(col) => Self::fill_default_slice(&mut col[from..to]),
+            AnyBuffer::U16(col) => Self::fill_default_slice(&mut col[from..to]),
+            AnyBuffer::U32(col) => Self::fill_default_slice(&mut col[from..to]),
+            AnyBuffer::U64(col) => Self::fill_default_slice(&mut col[from..to]),
             AnyBuffer::Bit(col) => Self::fill_default_slice(&mut col[from..to]),
             AnyBuffer::NullableDate(col) => col.fill_null(from, to),
             AnyBuffer::NullableTime(col) => col.fill_null(from, to),
@@ -629,6 +849,9 @@ unsafe impl ColumnBuffer for AnyBuffer {
             AnyBuffer::NullableI32(col) => col.fill_null(from, to),
             AnyBuffer::NullableI64(col) => col.fill_null(from, to),
             AnyBuffer::NullableU8(col) => col.fill_null(from, to),
+            AnyBuffer::NullableU16(col) => col.fill_null(from, to),
+            AnyBuffer::NullableU32(col) => col.fill_null(from, to),
+            AnyBuffer::NullableU64(col) => col.fill_null(from, to),
             AnyBuffer::NullableBit(col) => col.fill_null(from, to),
         }
     }
@@ -641,14 +864,158 @@ unsafe impl ColumnBuffer for AnyBuffer {
             _ => false,
         }
     }
+
+    fn check_indicators(&self, num_rows: usize) -> Result<(), Error> {
+        match self {
+            AnyBuffer::Binary(col) => col.check_indicators(num_rows),
+            AnyBuffer::Text(col) => col.check_indicators(num_rows),
+            AnyBuffer::WText(col) => col.check_indicators(num_rows),
+            _ => Ok(()),
+        }
+    }
+
+    fn payload_bytes(&self, num_rows: usize) -> usize {
+        /// Payload of a nullable column with fixed size elements
+        fn non_null_bytes<T>(col: &ColumnWithIndicator<T>, num_rows: usize) -> usize
+        where
+            T: Default + Clone,
+        {
+            col.iter(num_rows).flatten().count() * size_of::<T>()
+        }
+
+        match self {
+            AnyBuffer::Binary(col) => (0..num_rows)
+                .map(|row_index| col.indicator_at(row_index).length_in_buffer(col.max_len()))
+                .sum(),
+            AnyBuffer::Text(col) => col.payload_bytes(num_rows),
+            AnyBuffer::WText(col) => col.payload_bytes(num_rows),
+            AnyBuffer::Date(col) => col.payload_bytes(num_rows),
+            AnyBuffer::Time(col) => col.payload_bytes(num_rows),
+            AnyBuffer::Timestamp(col) => col.payload_bytes(num_rows),
+            AnyBuffer::F64(col) => col.payload_bytes(num_rows),
+            AnyBuffer::F32(col) => col.payload_bytes(num_rows),
+            AnyBuffer::I8(col) => col.payload_bytes(num_rows),
+            AnyBuffer::I16(col) => col.payload_bytes(num_rows),
+            AnyBuffer::I32(col) => col.payload_bytes(num_rows),
+            AnyBuffer::I64(col) => col.payload_bytes(num_rows),
+            AnyBuffer::U8(col) => col.payload_bytes(num_rows),
+            AnyBuffer::U16(col) => col.payload_bytes(num_rows),
+            AnyBuffer::U32(col) => col.payload_bytes(num_rows),
+            AnyBuffer::U64(col) => col.payload_bytes(num_rows),
+            AnyBuffer::Bit(col) => col.payload_bytes(num_rows),
+            AnyBuffer::NullableDate(col) => non_null_bytes(col, num_rows),
+            AnyBuffer::NullableTime(col) => non_null_bytes(col, num_rows),
+            AnyBuffer::NullableTimestamp(col) => non_null_bytes(col, num_rows),
+            AnyBuffer::NullableF64(col) => non_null_bytes(col, num_rows),
+            AnyBuffer::NullableF32(col) => non_null_bytes(col, num_rows),
+            AnyBuffer::NullableI8(col) => non_null_bytes(col, num_rows),
+            AnyBuffer::NullableI16(col) => non_null_bytes(col, num_rows),
+            AnyBuffer::NullableI32(col) => non_null_bytes(col, num_rows),
+            AnyBuffer::NullableI64(col) => non_null_bytes(col, num_rows),
+            AnyBuffer::NullableU8(col) => non_null_bytes(col, num_rows),
+            AnyBuffer::NullableU16(col) => non_null_bytes(col, num_rows),
+            AnyBuffer::NullableU32(col) => non_null_bytes(col, num_rows),
+            AnyBuffer::NullableU64(col) => non_null_bytes(col, num_rows),
+            AnyBuffer::NullableBit(col) => non_null_bytes(col, num_rows),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::buffers::{AnySlice, AnySliceMut, ColumnBuffer};
+    use odbc_sys::{NO_TOTAL, NULL_DATA};
+
+    use crate::{
+        buffers::{
+            column_with_indicator::OptI32Column, AnySlice, AnySliceMut, BufferDesc, ColumnBuffer,
+            TextColumn,
+        },
+        handles::CDataMut,
+        Error,
+    };
 
     use super::AnyBuffer;
 
+    #[test]
+    fn allocating_too_many_rows_fails_instead_of_aborting() {
+        let result = AnyBuffer::try_from_desc(usize::MAX / 2, BufferDesc::I64 { nullable: true });
+
+        let error = result.unwrap_err().into_allocation_error();
+        assert!(matches!(
+            error,
+            Error::TooLargeAllocation {
+                requested_bytes: usize::MAX
+            }
+        ));
+    }
+
+    #[test]
+    fn grow_wide_text_to_fit_truncated_values() {
+        let mut buffer = AnyBuffer::from_desc(3, BufferDesc::WText { max_str_len: 5 });
+        // Lengths of the values in bytes, as reported by a driver. The last one is truncated.
+        let indicators = [10, NULL_DATA, 16];
+        unsafe {
+            let target = buffer.mut_indicator_ptr();
+            for (row_index, indicator) in indicators.into_iter().enumerate() {
+                *target.add(row_index) = indicator;
+            }
+        }
+
+        let grown = buffer.grow_to_fit(3);
+
+        assert!(grown);
+        match &buffer {
+            AnyBuffer::WText(column) => assert_eq!(8, column.max_len()),
+            _ => panic!("Growing must not change the kind of buffer."),
+        }
+        assert!(!buffer.grow_to_fit(3));
+    }
+
+    #[test]
+    fn first_truncated_wide_text_value() {
+        let mut buffer = AnyBuffer::from_desc(3, BufferDesc::WText { max_str_len: 5 });
+        // Lengths of the values in bytes, as reported by a driver. The last two are truncated.
+        let indicators = [10, 16, NO_TOTAL];
+        unsafe {
+            let target = buffer.mut_indicator_ptr();
+            for (row_index, indicator) in indicators.into_iter().enumerate() {
+                *target.add(row_index) = indicator;
+            }
+        }
+
+        assert_eq!(None, buffer.first_truncation(1));
+        assert_eq!(Some((Some(16), 10)), buffer.first_truncation(3));
+    }
+
+    #[test]
+    fn invalid_indicator_is_reported() {
+        let mut buffer = AnyBuffer::from_desc(2, BufferDesc::Binary { length: 5 });
+        unsafe {
+            *buffer.mut_indicator_ptr().add(1) = -7;
+        }
+
+        assert!(buffer.check_indicators(1).is_ok());
+        assert!(matches!(
+            buffer.check_indicators(2),
+            Err(Error::InvalidDriverValue { value: -7, .. })
+        ));
+    }
+
+    #[test]
+    fn payload_bytes_of_text_and_nullable_columns() {
+        let mut text = TextColumn::new(3, 10);
+        text.set_value(0, Some(b"abc"));
+        text.set_value(1, None);
+        text.set_value(2, Some(b"de"));
+        let text = AnyBuffer::Text(text);
+        let mut ints = OptI32Column::new(2);
+        ints.writer_n(2).set_cell(0, Some(42));
+        let ints = AnyBuffer::NullableI32(ints);
+
+        assert_eq!(5, text.payload_bytes(3));
+        assert_eq!(4, ints.payload_bytes(2));
+    }
+
     #[test]
     fn slice_should_only_contain_part_of_the_buffer() {
         let buffer = AnyBuffer::I32(vec![1, 2, 3]);
@@ -0,0 +1,218 @@
+use std::any::type_name;
+
+use crate::Error;
+
+use super::{AnySlice, CellAccessError, ColumnarAnyBuffer, Item};
+
+/// Appends the rows of a [`ColumnarAnyBuffer`] to a `Vec<T>`, using one closure per column. Each
+/// closure is invoked once per batch with the entire column, so the variant of the column buffer is
+/// matched once per batch rather than once per cell. See [`crate::BlockCursor::fetch_into`].
+///
+/// ```no_run
+/// use odbc_api::{buffers::{BatchExtractor, BufferDesc, ColumnarAnyBuffer}, Cursor, Error};
+///
+/// #[derive(Default)]
+/// struct Movie {
+///     title: String,
+///     year: Option<i32>,
+/// }
+///
+/// fn fetch_movies(cursor: impl Cursor) -> Result<Vec<Movie>, Error> {
+///     let buffer = ColumnarAnyBuffer::from_descs(
+///         1000,
+///         [
+///             BufferDesc::Text { max_str_len: 255 },
+///             BufferDesc::I32 { nullable: true },
+///         ],
+///     );
+///     let extractor = BatchExtractor::new()
+///         .text_column(0, |movie: &mut Movie, title| {
+///             movie.title = String::from_utf8_lossy(title.unwrap_or_default()).into_owned()
+///         })
+///         .column(1, |movie: &mut Movie, year| movie.year = year);
+///     let mut block_cursor = cursor.bind_buffer(buffer)?;
+///     let mut movies = Vec::new();
+///     while block_cursor.fetch_into(&mut movies, &extractor)? {}
+///     Ok(movies)
+/// }
+/// ```
+pub struct BatchExtractor<T> {
+    columns: Vec<ColumnExtractor<T>>,
+}
+
+struct ColumnExtractor<T> {
+    buffer_index: usize,
+    extract: ExtractColumn<T>,
+}
+
+/// Sets a field of each row from a column buffer. See [`BatchExtractor::raw_column`].
+type ExtractColumn<T> = Box<dyn Fn(AnySlice<'_>, &mut [T]) -> Result<(), CellAccessError>>;
+
+impl<T> BatchExtractor<T> {
+    /// An extractor without any columns. Appending rows with it only appends default values.
+    pub fn new() -> Self {
+        Self {
+            columns: Vec::new(),
+        }
+    }
+
+    /// Sets a field of each row from the fixed sized column buffer at `buffer_index`. The column
+    /// buffer may be either nullable or not. `set` is passed `None` for `NULL` cells.
+    pub fn column<I>(self, buffer_index: usize, set: impl Fn(&mut T, Option<I>) + 'static) -> Self
+    where
+        I: Item,
+    {
+        self.raw_column(buffer_index, move |column, rows| {
+            if let Some(values) = I::as_slice(column) {
+                for (row, &value) in rows.iter_mut().zip(values) {
+                    set(row, Some(value))
+                }
+            } else if let Some(values) = I::as_nullable_slice(column) {
+                for (row, value) in rows.iter_mut().zip(values) {
+                    set(row, value.copied())
+                }
+            } else {
+                return Err(type_mismatch::<I>(column));
+            }
+            Ok(())
+        })
+    }
+
+    /// Sets a field of each row from the narrow text column buffer at `buffer_index`. `set` is
+    /// passed `None` for `NULL` cells.
+    pub fn text_column(
+        self,
+        buffer_index: usize,
+        set: impl Fn(&mut T, Option<&[u8]>) + 'static,
+    ) -> Self {
+        self.raw_column(buffer_index, move |column, rows| {
+            let values = column
+                .as_text_view()
+                .ok_or_else(|| type_mismatch::<&str>(column))?;
+            for (row, value) in rows.iter_mut().zip(values.iter()) {
+                set(row, value)
+            }
+            Ok(())
+        })
+    }
+
+    /// Sets a field of each row from the binary column buffer at `buffer_index`. `set` is passed
+    /// `None` for `NULL` cells.
+    pub fn binary_column(
+        self,
+        buffer_index: usize,
+        set: impl Fn(&mut T, Option<&[u8]>) + 'static,
+    ) -> Self {
+        self.raw_column(buffer_index, move |column, rows| {
+            let values = column
+                .as_bin_view()
+                .ok_or_else(|| type_mismatch::<&[u8]>(column))?;
+            for (row, value) in rows.iter_mut().zip(values.iter()) {
+                set(row, value)
+            }
+            Ok(())
+        })
+    }
+
+    /// Registers a closure which is invoked for each batch with the column buffer at
+    /// `buffer_index` and the rows appended for the batch. The element at index `0` of the rows
+    /// corresponds to the first row of the column. Use this for column buffers not covered by the
+    /// other methods, or to process an entire column at once.
+    pub fn raw_column(
+        mut self,
+        buffer_index: usize,
+        extract: impl Fn(AnySlice<'_>, &mut [T]) -> Result<(), CellAccessError> + 'static,
+    ) -> Self {
+        self.columns.push(ColumnExtractor {
+            buffer_index,
+            extract: Box::new(extract),
+        });
+        self
+    }
+
+    /// Appends one default constructed `T` for each row in `batch` to `rows` and sets their fields
+    /// using the registered closures. Should any of them fail, or a `buffer_index` passed to the
+    /// extractor be out of bounds for `batch`, `rows` is truncated to its original length and an
+    /// [`Error::BatchExtraction`] is returned.
+    pub fn extract(&self, batch: &ColumnarAnyBuffer, rows: &mut Vec<T>) -> Result<(), Error>
+    where
+        T: Default,
+    {
+        let start = rows.len();
+        rows.resize_with(start + batch.num_rows(), T::default);
+        for column in &self.columns {
+            let result = if column.buffer_index < batch.num_cols() {
+                (column.extract)(batch.column(column.buffer_index), &mut rows[start..])
+            } else {
+                Err(CellAccessError::NoSuchColumn {
+                    num_cols: batch.num_cols(),
+                })
+            };
+            if let Err(source) = result {
+                rows.truncate(start);
+                return Err(Error::BatchExtraction {
+                    buffer_index: column.buffer_index,
+                    source,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T> Default for BatchExtractor<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn type_mismatch<R>(column: AnySlice<'_>) -> CellAccessError {
+    CellAccessError::TypeMismatch {
+        column: column.variant_name(),
+        requested: type_name::<R>(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffers::{BufferDesc, CellAccessError, ColumnarAnyBuffer};
+
+    use super::BatchExtractor;
+
+    #[test]
+    fn type_mismatch_leaves_rows_untouched() {
+        let buffer = ColumnarAnyBuffer::from_descs(10, [BufferDesc::I32 { nullable: false }]);
+        let extractor = BatchExtractor::new().text_column(0, |_row: &mut i32, _text| ());
+        let mut rows = vec![42];
+
+        let error = extractor.extract(&buffer, &mut rows).unwrap_err();
+
+        assert!(matches!(
+            error,
+            crate::Error::BatchExtraction {
+                buffer_index: 0,
+                source: CellAccessError::TypeMismatch { column: "I32", .. }
+            }
+        ));
+        assert_eq!(vec![42], rows);
+    }
+
+    #[test]
+    fn out_of_bounds_buffer_index() {
+        let buffer = ColumnarAnyBuffer::from_descs(10, [BufferDesc::I32 { nullable: false }]);
+        let extractor = BatchExtractor::new()
+            .column(1, |row: &mut i32, value| *row = value.unwrap_or_default());
+        let mut rows = vec![42];
+
+        let error = extractor.extract(&buffer, &mut rows).unwrap_err();
+
+        assert!(matches!(
+            error,
+            crate::Error::BatchExtraction {
+                buffer_index: 1,
+                source: CellAccessError::NoSuchColumn { num_cols: 1 }
+            }
+        ));
+        assert_eq!(vec![42], rows);
+    }
+}
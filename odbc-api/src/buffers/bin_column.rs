@@ -1,5 +1,5 @@
 use crate::{
-    buffers::Indicator,
+    buffers::{indicator::check_indicators, try_filled_vec, Indicator},
     columnar_bulk_inserter::BoundInputSlice,
     error::TooLargeBufferSize,
     handles::{CData, CDataMut, HasDataType, Statement, StatementRef},
@@ -33,19 +33,17 @@ impl BinColumn {
     /// types like VARBINARY(MAX), or IMAGE. On the downside, this method is potentially slower than
     /// new.
     pub fn try_new(batch_size: usize, element_size: usize) -> Result<Self, TooLargeBufferSize> {
-        let len = element_size * batch_size;
-        let mut values = Vec::new();
-        values
-            .try_reserve_exact(len)
-            .map_err(|_| TooLargeBufferSize {
-                num_elements: batch_size,
-                element_size,
-            })?;
-        values.resize(len, 0);
+        let too_large = || TooLargeBufferSize {
+            num_elements: batch_size,
+            element_size,
+        };
+        let len = element_size.checked_mul(batch_size).ok_or_else(too_large)?;
+        let values = try_filled_vec(len, 0).map_err(|_| too_large())?;
+        let indicators = try_filled_vec(batch_size, 0).map_err(|_| too_large())?;
         Ok(BinColumn {
             max_len: element_size,
             values,
-            indicators: vec![0; batch_size],
+            indicators,
         })
     }
 
@@ -76,6 +74,15 @@ impl BinColumn {
         })
     }
 
+    /// Copy of the first `num_rows` elements, with the same maximum element length.
+    pub(crate) fn copy_rows(&self, num_rows: usize) -> Self {
+        Self {
+            max_len: self.max_len,
+            values: self.values[..num_rows * self.max_len].to_vec(),
+            indicators: self.indicators[..num_rows].to_vec(),
+        }
+    }
+
     /// Indicator value at the specified position. Useful to detect truncation of data.
     ///
     /// The column buffer does not know how many elements were in the last row group, and therefore
@@ -83,7 +90,7 @@ impl BinColumn {
     /// panic on accessing an undefined element. It will panic however if `row_index` is larger or
     /// equal to the maximum number of elements in the buffer.
     pub fn indicator_at(&self, row_index: usize) -> Indicator {
-        Indicator::from_isize(self.indicators[row_index])
+        Indicator::from_validated(self.indicators[row_index])
     }
 
     /// Length of value at the specified position. This is different from an indicator as it refers
@@ -111,12 +118,50 @@ impl BinColumn {
             .iter()
             .copied()
             .take(num_rows)
-            .any(|indicator| match Indicator::from_isize(indicator) {
+            .any(|indicator| match Indicator::from_validated(indicator) {
                 Indicator::Null | Indicator::NoTotal => true,
                 Indicator::Length(length) => self.max_len < length,
             })
     }
 
+    /// Fails with [`Error::InvalidDriverValue`] if the driver wrote an invalid length indicator
+    /// into the range [0, num_rows).
+    pub(crate) fn check_indicators(&self, num_rows: usize) -> Result<(), Error> {
+        check_indicators(&self.indicators[..num_rows.min(self.indicators.len())])
+    }
+
+    /// Length in bytes of the first value in the range [0, num_rows), which has been truncated.
+    /// `Some(None)` if the driver did not report its length.
+    pub(crate) fn first_truncated_len(&self, num_rows: usize) -> Option<Option<usize>> {
+        self.indicators
+            .iter()
+            .copied()
+            .take(num_rows)
+            .find_map(|indicator| match Indicator::from_validated(indicator) {
+                Indicator::Null => None,
+                Indicator::NoTotal => Some(None),
+                Indicator::Length(length) if self.max_len < length => Some(Some(length)),
+                Indicator::Length(_) => None,
+            })
+    }
+
+    /// Maximum element length required to hold the values in the range [0, num_rows) without
+    /// truncation. `None` if none of them is truncated. For values of unknown length twice the
+    /// current maximum element length is requested.
+    pub(crate) fn required_max_len(&self, num_rows: usize) -> Option<usize> {
+        self.indicators
+            .iter()
+            .copied()
+            .take(num_rows)
+            .filter_map(|indicator| match Indicator::from_validated(indicator) {
+                Indicator::Null => None,
+                Indicator::NoTotal => Some((self.max_len * 2).max(1)),
+                Indicator::Length(length) if self.max_len < length => Some(length),
+                Indicator::Length(_) => None,
+            })
+            .max()
+    }
+
     /// Changes the maximum element length the buffer can hold. This operation is useful if you find
     /// an unexpected large input during insertion. All values in the buffer will be set to NULL.
     ///
@@ -214,7 +259,7 @@ impl BinColumn {
             .zip(new_values.chunks_exact_mut(new_max_len))
             .take(num_rows)
         {
-            match Indicator::from_isize(indicator) {
+            match Indicator::from_validated(indicator) {
                 Indicator::Null => (),
                 Indicator::NoTotal => {
                     // There is no good choice here in case we are expanding the buffer. Since
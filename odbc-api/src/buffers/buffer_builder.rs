@@ -0,0 +1,125 @@
+use crate::{DataType, Error, ResultSetMetadata};
+
+use super::{BufferDesc, ColumnarAnyBuffer};
+
+/// Allocates a [`ColumnarAnyBuffer`] for the columns of a result set, after checking the buffer
+/// descriptions against the relational types of the columns. Fetching exact numeric values into
+/// floating point buffers loses precision silently, which is seldom acceptable for e.g. financial
+/// data. Therefore this builder refuses to bind:
+///
+/// * [`BufferDesc::F64`] or [`BufferDesc::F32`] to `DECIMAL` or `NUMERIC` columns, unless
+///   [`Self::allow_lossy_decimals`] is called.
+/// * [`BufferDesc::F32`] to `DOUBLE` or `FLOAT` columns with a precision of more than 24 bits,
+///   unless [`Self::allow_narrowing_floats`] is called.
+///
+/// ```no_run
+/// use odbc_api::{buffers::{BufferBuilder, BufferDesc}, Connection, Cursor, Error};
+///
+/// fn fetch_prices(conn: &Connection<'_>) -> Result<(), Error> {
+///     let mut cursor = conn.execute("SELECT id, price FROM Products", ())?.unwrap();
+///     let descs = [
+///         BufferDesc::I32 { nullable: false },
+///         BufferDesc::F64 { nullable: true },
+///     ];
+///     // `price` is a `DECIMAL(10,2)`. Approximating it is fine for our statistics.
+///     let buffer = BufferBuilder::new(1000)
+///         .allow_lossy_decimals()
+///         .build_for(&mut cursor, descs)?;
+///     let mut row_set_cursor = cursor.bind_buffer(buffer)?;
+///     // Fetch ...
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferBuilder {
+    capacity: usize,
+    allow_lossy_decimals: bool,
+    allow_narrowing_floats: bool,
+}
+
+impl BufferBuilder {
+    /// Buffers with room for `capacity` rows, rejecting lossy floating point buffers.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            allow_lossy_decimals: false,
+            allow_narrowing_floats: false,
+        }
+    }
+
+    /// Permit floating point buffers for `DECIMAL` and `NUMERIC` columns.
+    pub fn allow_lossy_decimals(mut self) -> Self {
+        self.allow_lossy_decimals = true;
+        self
+    }
+
+    /// Permit [`BufferDesc::F32`] for double precision columns.
+    pub fn allow_narrowing_floats(mut self) -> Self {
+        self.allow_narrowing_floats = true;
+        self
+    }
+
+    /// Allocates a buffer for `descs`. The first description refers to the first column of the
+    /// result set described by `metadata`, and so on. It fails with [`Error::LossyFloatBuffer`] if
+    /// a description would lose precision the builder has not been allowed to lose.
+    pub fn build_for(
+        &self,
+        metadata: &mut impl ResultSetMetadata,
+        descs: impl IntoIterator<Item = BufferDesc>,
+    ) -> Result<ColumnarAnyBuffer, Error> {
+        let descs: Vec<_> = descs.into_iter().collect();
+        for (index, desc) in descs.iter().enumerate() {
+            let column_number = index as u16 + 1;
+            let data_type = metadata.col_data_type(column_number)?;
+            if !self.permits(*desc, data_type) {
+                return Err(Error::LossyFloatBuffer {
+                    column_number,
+                    data_type,
+                    buffer_desc: *desc,
+                });
+            }
+        }
+        ColumnarAnyBuffer::try_from_descs(self.capacity, descs)
+    }
+
+    /// `false` if binding a buffer described by `desc` to a column of `data_type` loses precision
+    /// the builder has not been allowed to lose.
+    fn permits(&self, desc: BufferDesc, data_type: DataType) -> bool {
+        match (desc, data_type) {
+            (
+                BufferDesc::F64 { .. } | BufferDesc::F32 { .. },
+                DataType::Decimal { .. } | DataType::Numeric { .. },
+            ) => self.allow_lossy_decimals,
+            (BufferDesc::F32 { .. }, DataType::Double | DataType::Float { precision: 25.. }) => {
+                self.allow_narrowing_floats
+            }
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{buffers::BufferDesc, DataType};
+
+    use super::BufferBuilder;
+
+    #[test]
+    fn reject_lossy_floating_point_buffers_by_default() {
+        let f64 = BufferDesc::F64 { nullable: true };
+        let f32 = BufferDesc::F32 { nullable: true };
+        let decimal = DataType::Decimal {
+            precision: 10,
+            scale: 2,
+        };
+        let strict = BufferBuilder::new(10);
+        let lenient = strict.allow_lossy_decimals().allow_narrowing_floats();
+
+        assert!(!strict.permits(f64, decimal));
+        assert!(!strict.permits(f32, DataType::Double));
+        assert!(strict.permits(f64, DataType::Double));
+        assert!(strict.permits(f32, DataType::Real));
+        assert!(lenient.permits(f64, decimal));
+        assert!(lenient.permits(f32, DataType::Float { precision: 53 }));
+    }
+}
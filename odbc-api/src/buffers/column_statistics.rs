@@ -0,0 +1,315 @@
+use std::{cmp::Ordering, collections::hash_map::DefaultHasher, hash::Hasher};
+
+use crate::parameter::WithDataType;
+
+use super::{
+    row_comparison::compare, AnyBuffer, AnySlice, BinColumn, CellValue, CharColumn, ColumnBuffer,
+    OwnedCellValue, RowEq, WCharColumn,
+};
+
+/// Statistics about the values of one column, accumulated over all row sets fetched into a buffer
+/// since [`crate::buffers::ColumnarBuffer::with_column_statistics`] has been called. This allows
+/// profiling a data set while extracting it, rather than in a second pass over the data.
+#[derive(Debug, Clone, Default)]
+pub struct ColumnStatistics {
+    num_nulls: usize,
+    num_values: usize,
+    min: Option<OwnedCellValue>,
+    max: Option<OwnedCellValue>,
+    max_len: Option<usize>,
+    distinct: HyperLogLog,
+}
+
+impl ColumnStatistics {
+    /// Statistics without any values recorded.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of `NULL` values.
+    pub fn num_nulls(&self) -> usize {
+        self.num_nulls
+    }
+
+    /// Number of values other than `NULL`.
+    pub fn num_values(&self) -> usize {
+        self.num_values
+    }
+
+    /// Smallest value other than `NULL`. `None` if no such value has been fetched. Values are
+    /// ordered like by [`crate::buffers::RowOrd`]. Floating point values which are not a number are
+    /// ignored.
+    pub fn min(&self) -> Option<CellValue<'_>> {
+        self.min.as_ref().map(OwnedCellValue::as_cell)
+    }
+
+    /// Largest value other than `NULL`. `None` if no such value has been fetched.
+    pub fn max(&self) -> Option<CellValue<'_>> {
+        self.max.as_ref().map(OwnedCellValue::as_cell)
+    }
+
+    /// Length of the longest text or binary value. In bytes for narrow text and binary values, in
+    /// UTF-16 code units for wide text. `None` for columns of other types, or if no value other
+    /// than `NULL` has been fetched. Values truncated by the driver are only counted with the length
+    /// which has been fetched.
+    pub fn max_len(&self) -> Option<usize> {
+        self.max_len
+    }
+
+    /// Estimated number of distinct values other than `NULL`. The estimate is computed with a
+    /// HyperLogLog sketch, so it requires constant memory, with a typical error of about 3%.
+    pub fn estimated_distinct(&self) -> usize {
+        self.distinct.estimate()
+    }
+
+    fn record_all<'a, T>(
+        &mut self,
+        values: impl IntoIterator<Item = Option<T>>,
+        to_value: impl Fn(T) -> CellValue<'a>,
+    ) {
+        for value in values {
+            match value {
+                Some(value) => self.record(to_value(value)),
+                None => self.num_nulls += 1,
+            }
+        }
+    }
+
+    fn record(&mut self, value: CellValue<'_>) {
+        self.num_values += 1;
+        if let Some(len) = len(value) {
+            self.max_len = Some(self.max_len.map_or(len, |max_len| max_len.max(len)));
+        }
+        self.distinct.insert(value);
+        if matches!(value, CellValue::Float(value) if value.is_nan()) {
+            return;
+        }
+        let ordering = |known: &OwnedCellValue| compare(value, known.as_cell(), false);
+        if self
+            .min
+            .as_ref()
+            .is_none_or(|min| ordering(min) == Some(Ordering::Less))
+        {
+            self.min = Some(value.into_owned());
+        }
+        if self
+            .max
+            .as_ref()
+            .is_none_or(|max| ordering(max) == Some(Ordering::Greater))
+        {
+            self.max = Some(value.into_owned());
+        }
+    }
+}
+
+/// Length of text or binary values. See [`ColumnStatistics::max_len`].
+fn len(value: CellValue<'_>) -> Option<usize> {
+    match value {
+        CellValue::Text(text) | CellValue::Binary(text) => Some(text.len()),
+        CellValue::WideText(text) => Some(text.len()),
+        _ => None,
+    }
+}
+
+/// Number of bits of the hash used to choose a register of the [`HyperLogLog`] sketch.
+const PRECISION: u32 = 10;
+
+/// Sketch estimating the number of distinct values, see
+/// <https://en.wikipedia.org/wiki/HyperLogLog>.
+#[derive(Debug, Clone)]
+struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self {
+            registers: vec![0; 1 << PRECISION],
+        }
+    }
+}
+
+impl HyperLogLog {
+    fn insert(&mut self, value: CellValue<'_>) {
+        // The default hasher uses fixed keys, so equal values yield the same hash in every batch.
+        let mut hasher = DefaultHasher::new();
+        RowEq::new().hash_value(Some(value), &mut hasher);
+        let hash = hasher.finish();
+        let index = (hash >> (64 - PRECISION)) as usize;
+        // Position of the first set bit in the remaining bits. The sentinel bit limits the rank, in
+        // case all of them are zero.
+        let rank = ((hash << PRECISION) | (1 << (PRECISION - 1))).leading_zeros() as u8 + 1;
+        self.registers[index] = self.registers[index].max(rank);
+    }
+
+    fn estimate(&self) -> usize {
+        let m = self.registers.len() as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|&rank| 2f64.powi(-(rank as i32)))
+            .sum();
+        let estimate = alpha * m * m / sum;
+        let num_zeros = self.registers.iter().filter(|&&rank| rank == 0).count();
+        // Linear counting is more accurate for small cardinalities.
+        let estimate = if estimate <= 2.5 * m && num_zeros != 0 {
+            m * (m / num_zeros as f64).ln()
+        } else {
+            estimate
+        };
+        estimate.round() as usize
+    }
+}
+
+/// Column buffers able to record their values in [`ColumnStatistics`].
+pub trait Profile {
+    /// Records the values in the range [0, num_rows) in `statistics`.
+    fn profile(&self, num_rows: usize, statistics: &mut ColumnStatistics);
+}
+
+impl Profile for CharColumn {
+    fn profile(&self, num_rows: usize, statistics: &mut ColumnStatistics) {
+        statistics.record_all(self.view(num_rows).iter(), CellValue::Text)
+    }
+}
+
+impl Profile for WCharColumn {
+    fn profile(&self, num_rows: usize, statistics: &mut ColumnStatistics) {
+        statistics.record_all(self.view(num_rows).iter(), |text| {
+            CellValue::WideText(text.as_slice())
+        })
+    }
+}
+
+impl Profile for BinColumn {
+    fn profile(&self, num_rows: usize, statistics: &mut ColumnStatistics) {
+        statistics.record_all(self.view(num_rows).iter(), CellValue::Binary)
+    }
+}
+
+impl Profile for AnyBuffer {
+    fn profile(&self, num_rows: usize, statistics: &mut ColumnStatistics) {
+        fn int<T: Copy + Into<i128>>(value: &T) -> CellValue<'static> {
+            CellValue::Integer((*value).into())
+        }
+
+        match self.view(num_rows) {
+            AnySlice::Text(view) => statistics.record_all(view.iter(), CellValue::Text),
+            AnySlice::WText(view) => {
+                statistics.record_all(view.iter(), |text| CellValue::WideText(text.as_slice()))
+            }
+            AnySlice::Binary(view) => statistics.record_all(view.iter(), CellValue::Binary),
+            AnySlice::Date(values) => {
+                statistics.record_all(values.iter().map(Some), |&d| CellValue::Date(d))
+            }
+            AnySlice::Time(values) => {
+                statistics.record_all(values.iter().map(Some), |&t| CellValue::Time(t))
+            }
+            AnySlice::Timestamp(values) => {
+                statistics.record_all(values.iter().map(Some), |&ts| CellValue::Timestamp(ts))
+            }
+            AnySlice::F64(values) => {
+                statistics.record_all(values.iter().map(Some), |&f| CellValue::Float(f))
+            }
+            AnySlice::F32(values) => {
+                statistics.record_all(values.iter().map(Some), |&f| CellValue::Float(f.into()))
+            }
+            AnySlice::I8(values) => statistics.record_all(values.iter().map(Some), int),
+            AnySlice::I16(values) => statistics.record_all(values.iter().map(Some), int),
+            AnySlice::I32(values) => statistics.record_all(values.iter().map(Some), int),
+            AnySlice::I64(values) => statistics.record_all(values.iter().map(Some), int),
+            AnySlice::U8(values) => statistics.record_all(values.iter().map(Some), int),
+            AnySlice::U16(values) => statistics.record_all(values.iter().map(Some), int),
+            AnySlice::U32(values) => statistics.record_all(values.iter().map(Some), int),
+            AnySlice::U64(values) => statistics.record_all(values.iter().map(Some), int),
+            AnySlice::Bit(values) => {
+                statistics.record_all(values.iter().map(Some), |bit| int(&bit.0))
+            }
+            AnySlice::NullableDate(values) => {
+                statistics.record_all(values, |&d| CellValue::Date(d))
+            }
+            AnySlice::NullableTime(values) => {
+                statistics.record_all(values, |&t| CellValue::Time(t))
+            }
+            AnySlice::NullableTimestamp(values) => {
+                statistics.record_all(values, |&ts| CellValue::Timestamp(ts))
+            }
+            AnySlice::NullableF64(values) => {
+                statistics.record_all(values, |&f| CellValue::Float(f))
+            }
+            AnySlice::NullableF32(values) => {
+                statistics.record_all(values, |&f| CellValue::Float(f.into()))
+            }
+            AnySlice::NullableI8(values) => statistics.record_all(values, int),
+            AnySlice::NullableI16(values) => statistics.record_all(values, int),
+            AnySlice::NullableI32(values) => statistics.record_all(values, int),
+            AnySlice::NullableI64(values) => statistics.record_all(values, int),
+            AnySlice::NullableU8(values) => statistics.record_all(values, int),
+            AnySlice::NullableU16(values) => statistics.record_all(values, int),
+            AnySlice::NullableU32(values) => statistics.record_all(values, int),
+            AnySlice::NullableU64(values) => statistics.record_all(values, int),
+            AnySlice::NullableBit(values) => statistics.record_all(values, |bit| int(&bit.0)),
+        }
+    }
+}
+
+impl<T> Profile for WithDataType<T>
+where
+    T: Profile,
+{
+    fn profile(&self, num_rows: usize, statistics: &mut ColumnStatistics) {
+        self.value.profile(num_rows, statistics)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffers::{column_with_indicator::OptI32Column, AnyBuffer, TextColumn};
+
+    use crate::buffers::CellValue;
+
+    use super::{ColumnStatistics, Profile};
+
+    #[test]
+    fn accumulate_statistics_over_batches() {
+        let mut statistics = ColumnStatistics::new();
+        let mut first = OptI32Column::new(3);
+        let mut writer = first.writer_n(3);
+        writer.set_cell(0, Some(5));
+        writer.set_cell(1, None);
+        writer.set_cell(2, Some(-2));
+        let mut second = OptI32Column::new(2);
+        let mut writer = second.writer_n(2);
+        writer.set_cell(0, Some(5));
+        writer.set_cell(1, Some(7));
+
+        AnyBuffer::NullableI32(first).profile(3, &mut statistics);
+        AnyBuffer::NullableI32(second).profile(2, &mut statistics);
+
+        assert_eq!(1, statistics.num_nulls());
+        assert_eq!(4, statistics.num_values());
+        assert_eq!(Some(CellValue::Integer(-2)), statistics.min());
+        assert_eq!(Some(CellValue::Integer(7)), statistics.max());
+        assert_eq!(3, statistics.estimated_distinct());
+        assert_eq!(None, statistics.max_len());
+    }
+
+    #[test]
+    fn estimate_distinct_text_values() {
+        let mut statistics = ColumnStatistics::new();
+        let mut column = TextColumn::new(1000, 10);
+        for index in 0..1000 {
+            let value = format!("{}", index % 500);
+            column.set_value(index, Some(value.as_bytes()));
+        }
+
+        column.profile(1000, &mut statistics);
+
+        let estimate = statistics.estimated_distinct();
+        assert!((475..=525).contains(&estimate), "Estimate: {estimate}");
+        assert_eq!(Some(3), statistics.max_len());
+        assert_eq!(Some(CellValue::Text(b"0")), statistics.min());
+        assert_eq!(Some(CellValue::Text(b"99")), statistics.max());
+    }
+}
@@ -1,4 +1,6 @@
 use crate::{
+    buffers::try_filled_vec,
+    error::TooLargeBufferSize,
     fixed_sized::{Bit, Pod},
     handles::{CData, CDataMut},
 };
@@ -19,6 +21,9 @@ pub type OptI16Column = ColumnWithIndicator<i16>;
 pub type OptI32Column = ColumnWithIndicator<i32>;
 pub type OptI64Column = ColumnWithIndicator<i64>;
 pub type OptU8Column = ColumnWithIndicator<u8>;
+pub type OptU16Column = ColumnWithIndicator<u16>;
+pub type OptU32Column = ColumnWithIndicator<u32>;
+pub type OptU64Column = ColumnWithIndicator<u64>;
 pub type OptBitColumn = ColumnWithIndicator<Bit>;
 
 /// Column buffer for fixed sized type, also binding an indicator buffer to handle NULL.
@@ -39,6 +44,19 @@ where
         }
     }
 
+    /// Like [`Self::new`], but fails rather than aborting the process, if not enough memory is
+    /// available.
+    pub fn try_new(batch_size: usize) -> Result<Self, TooLargeBufferSize> {
+        let too_large = || TooLargeBufferSize {
+            num_elements: batch_size,
+            element_size: size_of::<T>() + size_of::<isize>(),
+        };
+        Ok(Self {
+            values: try_filled_vec(batch_size, T::default()).map_err(|_| too_large())?,
+            indicators: try_filled_vec(batch_size, NULL_DATA).map_err(|_| too_large())?,
+        })
+    }
+
     /// Access the value at a specific row index.
     ///
     /// The buffer size is not automatically adjusted to the size of the last row set. It is the
@@ -71,6 +89,14 @@ where
     pub fn capacity(&self) -> usize {
         self.indicators.len()
     }
+
+    /// Copy of the first `num_rows` elements.
+    pub(crate) fn copy_rows(&self, num_rows: usize) -> Self {
+        Self {
+            values: self.values[..num_rows].to_vec(),
+            indicators: self.indicators[..num_rows].to_vec(),
+        }
+    }
 }
 
 /// Iterates over the elements of a column buffer. Returned by
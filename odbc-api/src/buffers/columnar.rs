@@ -1,6 +1,7 @@
 use std::{
     cmp::min,
     collections::HashSet,
+    mem::size_of,
     str::{from_utf8, Utf8Error},
 };
 
@@ -10,10 +11,12 @@ use crate::{
     handles::{CDataMut, Statement, StatementRef},
     parameter::WithDataType,
     result_set_metadata::utf8_display_sizes,
-    Error, ResultSetMetadata, RowSetBuffer,
+    DataType, Error, ResultSetMetadata, RowSetBuffer,
 };
 
-use super::{Indicator, TextColumn};
+use super::{
+    CellError, CellErrorCause, ColumnStatistics, Indicator, Profile, Quarantine, TextColumn,
+};
 
 impl<C: ColumnBuffer> ColumnarBuffer<C> {
     /// Create a new instance from columns with unique indicies. Capacity of the buffer will be the
@@ -53,6 +56,11 @@ impl<C: ColumnBuffer> ColumnarBuffer<C> {
             num_rows: Box::new(0),
             row_capacity: capacity,
             columns,
+            transforms: Vec::new(),
+            quarantine: None,
+            cell_errors: Vec::new(),
+            profile: None,
+            column_statistics: Vec::new(),
         }
     }
 
@@ -66,6 +74,16 @@ impl<C: ColumnBuffer> ColumnarBuffer<C> {
         self.columns.len()
     }
 
+    /// Column indices and buffers.
+    pub(crate) fn columns(&self) -> &[(u16, C)] {
+        &self.columns
+    }
+
+    /// Mutable access to the column buffers, e.g. in order to reallocate them.
+    pub(crate) fn columns_mut(&mut self) -> impl Iterator<Item = &mut C> {
+        self.columns.iter_mut().map(|(_col_index, column)| column)
+    }
+
     /// Use this method to gain read access to the actual column data.
     ///
     /// # Parameters
@@ -79,6 +97,122 @@ impl<C: ColumnBuffer> ColumnarBuffer<C> {
     pub fn column(&self, buffer_index: usize) -> C::View<'_> {
         self.columns[buffer_index].1.view(*self.num_rows)
     }
+
+    /// Registers a transformation, which is applied to the column with the specified buffer index
+    /// each time after a row set has been fetched into this buffer by a block cursor. This allows
+    /// e.g. trimming padding, normalizing encodings or masking sensitive values, while the values
+    /// are still hot in the cache, rather than requiring a second pass over the data.
+    ///
+    /// `transform` is called with the column buffer and the number of valid rows in it. Multiple
+    /// transformations are applied in the order they have been registered.
+    ///
+    /// ```
+    /// use odbc_api::buffers::{TextColumn, TextRowSet};
+    ///
+    /// /// Masks all but the last four characters of the credit card numbers in the first column.
+    /// fn mask_card_numbers(buffer: TextRowSet) -> TextRowSet {
+    ///     buffer.with_transform(0, |column: &mut TextColumn<u8>, num_rows| {
+    ///         column.transform_values(num_rows, |value| {
+    ///             let num_masked = value.len().saturating_sub(4);
+    ///             value[..num_masked].fill(b'*');
+    ///             value.len()
+    ///         })
+    ///     })
+    /// }
+    /// ```
+    pub fn with_transform(
+        mut self,
+        buffer_index: usize,
+        transform: impl FnMut(&mut C, usize) + Send + Sync + 'static,
+    ) -> Self {
+        assert!(
+            buffer_index < self.columns.len(),
+            "Buffer index of transformation out of bounds."
+        );
+        self.transforms.push((buffer_index, Box::new(transform)));
+        self
+    }
+
+    /// Enables quarantine mode. Rather than failing, values which can not be decoded (e.g. text
+    /// which is not valid UTF-8, truncated values, or timestamps with fields out of range) are set
+    /// to `NULL` each time after a row set has been fetched by a block cursor. The affected cells
+    /// are reported by [`Self::cell_errors`]. This allows extracting data sets with a few dirty
+    /// values without aborting the entire extraction.
+    ///
+    /// Only values the driver did write into the buffer can be quarantined. Conversion errors
+    /// reported by the driver itself still cause the fetch to fail. Truncated values are only
+    /// quarantined if the fetch has not been configured to fail on truncation.
+    ///
+    /// ```
+    /// use odbc_api::{buffers::TextRowSet, Cursor, Error};
+    ///
+    /// fn count_dirty_cells(cursor: impl Cursor) -> Result<usize, Error> {
+    ///     let buffer = TextRowSet::from_max_str_lens(1000, [255, 255])?.with_quarantine();
+    ///     let mut block_cursor = cursor.bind_buffer(buffer)?;
+    ///     let mut num_dirty = 0;
+    ///     while let Some(batch) = block_cursor.fetch()? {
+    ///         for error in batch.cell_errors() {
+    ///             eprintln!("Discarded cell {}, {}: {:?}", error.row, error.col, error.cause);
+    ///         }
+    ///         num_dirty += batch.cell_errors().len();
+    ///     }
+    ///     Ok(num_dirty)
+    /// }
+    /// ```
+    pub fn with_quarantine(mut self) -> Self
+    where
+        C: Quarantine,
+    {
+        self.quarantine = Some(C::quarantine);
+        self
+    }
+
+    /// Cells which have been set to `NULL` after the last fetch, because they could not be
+    /// decoded. Always empty if [`Self::with_quarantine`] has not been called.
+    pub fn cell_errors(&self) -> &[CellError] {
+        &self.cell_errors
+    }
+
+    /// Enables column statistics. Each time after a row set has been fetched by a block cursor, its
+    /// values are recorded in one [`ColumnStatistics`] per column, so data sets can be profiled
+    /// while they are extracted. Statistics reflect the values after quarantine and
+    /// transformations have been applied. Enabling statistics again discards the statistics
+    /// recorded so far.
+    ///
+    /// ```
+    /// use odbc_api::{buffers::{BufferDesc, ColumnarAnyBuffer}, Cursor, Error};
+    ///
+    /// fn print_null_counts(cursor: impl Cursor) -> Result<(), Error> {
+    ///     let buffer = ColumnarAnyBuffer::from_descs(
+    ///         1000,
+    ///         [BufferDesc::Text { max_str_len: 255 }, BufferDesc::I64 { nullable: true }],
+    ///     )
+    ///     .with_column_statistics();
+    ///     let mut block_cursor = cursor.bind_buffer(buffer)?;
+    ///     while let Some(_batch) = block_cursor.fetch()? {
+    ///         // ... process batch ...
+    ///     }
+    ///     let (_cursor, buffer) = block_cursor.unbind()?;
+    ///     for (index, statistics) in buffer.column_statistics().iter().enumerate() {
+    ///         println!("Column {index}: {} NULLs", statistics.num_nulls());
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_column_statistics(mut self) -> Self
+    where
+        C: Profile,
+    {
+        self.profile = Some(C::profile);
+        self.column_statistics = vec![ColumnStatistics::new(); self.columns.len()];
+        self
+    }
+
+    /// Statistics of each column, accumulated over all row sets fetched since
+    /// [`Self::with_column_statistics`] has been called. Empty if it has not been called.
+    pub fn column_statistics(&self) -> &[ColumnStatistics] {
+        &self.column_statistics
+    }
 }
 
 unsafe impl<C> RowSetBuffer for ColumnarBuffer<C>
@@ -109,6 +243,44 @@ where
             .iter()
             .any(|col_buffer| col_buffer.1.has_truncated_values(*self.num_rows))
     }
+
+    fn check_indicators(&self) -> Result<(), Error> {
+        self.columns
+            .iter()
+            .try_for_each(|col_buffer| col_buffer.1.check_indicators(*self.num_rows))
+    }
+
+    fn payload_bytes(&self) -> usize {
+        self.columns
+            .iter()
+            .map(|col_buffer| col_buffer.1.payload_bytes(*self.num_rows))
+            .sum()
+    }
+
+    fn after_fetch(&mut self) {
+        if let Some(quarantine) = self.quarantine {
+            self.cell_errors.clear();
+            for (buffer_index, (_col_index, column)) in self.columns.iter_mut().enumerate() {
+                quarantine(column, *self.num_rows, &mut |row, cause| {
+                    self.cell_errors.push(CellError {
+                        row,
+                        col: buffer_index,
+                        cause,
+                    })
+                });
+            }
+        }
+        for (buffer_index, transform) in &mut self.transforms {
+            transform(&mut self.columns[*buffer_index].1, *self.num_rows)
+        }
+        if let Some(profile) = self.profile {
+            for ((_col_index, column), statistics) in
+                self.columns.iter().zip(&mut self.column_statistics)
+            {
+                profile(column, *self.num_rows, statistics);
+            }
+        }
+    }
 }
 
 /// A columnar buffer intended to be bound with [crate::Cursor::bind_buffer] in order to obtain
@@ -129,8 +301,25 @@ pub struct ColumnarBuffer<C> {
     row_capacity: usize,
     /// Column index and bound buffer
     columns: Vec<(u16, C)>,
+    /// Buffer index and transformation applied to the column after each fetch.
+    transforms: Vec<(usize, ColumnTransform<C>)>,
+    /// Set if quarantine mode is enabled. Discards values which can not be decoded after fetch.
+    quarantine: Option<QuarantineFn<C>>,
+    /// Cells quarantined during the last fetch.
+    cell_errors: Vec<CellError>,
+    /// Set if column statistics are enabled. Records the values of a column after fetch.
+    profile: Option<ProfileFn<C>>,
+    /// One element for each column, accumulated over all fetches since statistics were enabled.
+    column_statistics: Vec<ColumnStatistics>,
 }
 
+type QuarantineFn<C> = fn(&mut C, usize, &mut dyn FnMut(usize, CellErrorCause));
+
+type ProfileFn<C> = fn(&C, usize, &mut ColumnStatistics);
+
+/// Transformation applied to a column buffer after fetch. See [`ColumnarBuffer::with_transform`].
+type ColumnTransform<C> = Box<dyn FnMut(&mut C, usize) + Send + Sync>;
+
 /// A buffer for a single column intended to be used together with [`ColumnarBuffer`].
 ///
 /// # Safety
@@ -161,6 +350,19 @@ pub unsafe trait ColumnBuffer: CDataMut {
     /// not being able to hold elements of that size. This method checks the indicator buffer
     /// element wise.
     fn has_truncated_values(&self, num_rows: usize) -> bool;
+
+    /// Fails with [`Error::InvalidDriverValue`] if the driver wrote an invalid length indicator
+    /// into the range [0, num_rows). The default implementation does nothing.
+    fn check_indicators(&self, _num_rows: usize) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Number of payload bytes held by the values in the range [0, num_rows), excluding indicators
+    /// and terminating zeroes. Used to gather [`crate::CursorStatistics`]. The default
+    /// implementation returns `0`.
+    fn payload_bytes(&self, _num_rows: usize) -> usize {
+        0
+    }
 }
 
 unsafe impl<T> ColumnBuffer for WithDataType<T>
@@ -184,6 +386,14 @@ where
     fn has_truncated_values(&self, num_rows: usize) -> bool {
         self.value.has_truncated_values(num_rows)
     }
+
+    fn check_indicators(&self, num_rows: usize) -> Result<(), Error> {
+        self.value.check_indicators(num_rows)
+    }
+
+    fn payload_bytes(&self, num_rows: usize) -> usize {
+        self.value.payload_bytes(num_rows)
+    }
 }
 
 unsafe impl<'a, T> BoundInputSlice<'a> for WithDataType<T>
@@ -282,9 +492,9 @@ impl TextRowSet {
     /// The resulting text buffer is not in any way tied to the cursor, other than that its buffer
     /// sizes a tailor fitted to result set the cursor is iterating over.
     ///
-    /// This method performs faliable buffer allocations, if no upper bound is set, so you may see
-    /// a speedup, by setting an upper bound using `max_str_limit`.
-    ///
+    /// This method performs fallible buffer allocations, so it fails with
+    /// [`Error::TooLargeColumnBufferSize`] rather than aborting the process, if `batch_size` or the
+    /// reported column sizes are too large for the available memory.
     ///
     /// # Parameters
     ///
@@ -309,22 +519,13 @@ impl TextRowSet {
                 let buffer_index = buffer_index as u16;
                 let col_index = buffer_index + 1;
                 let max_str_len = reported_len?;
-                let buffer = if let Some(upper_bound) = max_str_limit {
-                    let max_str_len = if max_str_len == 0 {
-                        upper_bound
-                    } else {
-                        min(max_str_len, upper_bound)
-                    };
-                    TextColumn::new(batch_size, max_str_len)
-                } else {
-                    TextColumn::try_new(batch_size, max_str_len).map_err(|source| {
-                        Error::TooLargeColumnBufferSize {
-                            buffer_index,
-                            num_elements: source.num_elements,
-                            element_size: source.element_size,
-                        }
-                    })?
+                let max_str_len = match max_str_limit {
+                    Some(upper_bound) if max_str_len == 0 => upper_bound,
+                    Some(upper_bound) => min(max_str_len, upper_bound),
+                    None => max_str_len,
                 };
+                let buffer = TextColumn::try_new(batch_size, max_str_len)
+                    .map_err(|source| source.add_context(buffer_index))?;
 
                 Ok((col_index, buffer))
             })
@@ -333,6 +534,11 @@ impl TextRowSet {
             row_capacity: batch_size,
             num_rows: Box::new(0),
             columns: buffers,
+            transforms: Vec::new(),
+            quarantine: None,
+            cell_errors: Vec::new(),
+            profile: None,
+            column_statistics: Vec::new(),
         })
     }
 
@@ -357,9 +563,51 @@ impl TextRowSet {
             row_capacity,
             num_rows: Box::new(0),
             columns: buffers,
+            transforms: Vec::new(),
+            quarantine: None,
+            cell_errors: Vec::new(),
+            profile: None,
+            column_statistics: Vec::new(),
         })
     }
 
+    /// Strips the padding of fixed length character columns (i.e. `CHAR(n)` or `NCHAR(n)`) after
+    /// each fetch. Drivers pad these values with trailing spaces up to their declared length. The
+    /// data type of each bound column is queried from `cursor`, so it should be the same cursor this
+    /// buffer has been created for. Values of variadic columns like `VARCHAR` are left untouched.
+    ///
+    /// ```no_run
+    /// use odbc_api::{buffers::TextRowSet, Connection, Cursor, Error};
+    ///
+    /// fn print_names(conn: &Connection<'_>) -> Result<(), Error> {
+    ///     let mut cursor = conn.execute("SELECT name FROM Employees", ())?.unwrap();
+    ///     let buffer = TextRowSet::for_cursor(1000, &mut cursor, Some(4096))?
+    ///         .trim_char_padding(&mut cursor)?;
+    ///     let mut block_cursor = cursor.bind_buffer(buffer)?;
+    ///     while let Some(batch) = block_cursor.fetch()? {
+    ///         for row_index in 0..batch.num_rows() {
+    ///             println!("{:?}", batch.at_as_str(0, row_index));
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn trim_char_padding(mut self, cursor: &mut impl ResultSetMetadata) -> Result<Self, Error> {
+        for buffer_index in 0..self.columns.len() {
+            let col_index = self.columns[buffer_index].0;
+            if matches!(
+                cursor.col_data_type(col_index)?,
+                DataType::Char { .. } | DataType::WChar { .. }
+            ) {
+                self = self
+                    .with_transform(buffer_index, |column: &mut TextColumn<u8>, num_rows| {
+                        column.trim_trailing_spaces(num_rows)
+                    });
+            }
+        }
+        Ok(self)
+    }
+
     /// Access the element at the specified position in the row set.
     pub fn at(&self, buffer_index: usize, row_index: usize) -> Option<&[u8]> {
         assert!(row_index < *self.num_rows);
@@ -371,6 +619,20 @@ impl TextRowSet {
         self.at(col_index, row_index).map(from_utf8).transpose()
     }
 
+    /// Access the element at the specified position in the row set, converted from the narrow
+    /// `encoding` of the driver into UTF-8. Requires the `encoding` feature. See
+    /// [`crate::Connection::narrow_encoding`].
+    #[cfg(feature = "encoding")]
+    pub fn at_decoded(
+        &self,
+        col_index: usize,
+        row_index: usize,
+        encoding: &'static crate::encoding::Encoding,
+    ) -> Option<std::borrow::Cow<'_, str>> {
+        self.at(col_index, row_index)
+            .map(|bytes| crate::encoding::decode(encoding, bytes))
+    }
+
     /// Indicator value at the specified position. Useful to detect truncation of data.
     ///
     /// # Example
@@ -426,12 +688,52 @@ where
     fn has_truncated_values(&self, _num_rows: usize) -> bool {
         false
     }
+
+    fn payload_bytes(&self, num_rows: usize) -> usize {
+        num_rows * size_of::<T>()
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
-    use crate::buffers::{BufferDesc, ColumnarAnyBuffer};
+    use crate::{
+        buffers::{BufferDesc, ColumnarAnyBuffer, ColumnarBuffer, TextColumn},
+        RowSetBuffer,
+    };
+
+    #[test]
+    fn transformation_applied_after_fetch() {
+        let mut column = TextColumn::new(2, 10);
+        column.set_value(0, Some(b"abc  "));
+        column.set_value(1, None);
+        let mut buffer = ColumnarBuffer::new(vec![(1, column)]).with_transform(
+            0,
+            |column: &mut TextColumn<u8>, num_rows| {
+                column.transform_values(num_rows, |value| value.len() - 2)
+            },
+        );
+        *buffer.mut_num_fetch_rows() = 2;
+
+        buffer.after_fetch();
+
+        assert_eq!(Some(&b"abc"[..]), buffer.at(0, 0));
+        assert_eq!(None, buffer.at(0, 1));
+    }
+
+    #[test]
+    fn trim_trailing_spaces() {
+        let mut column = TextColumn::new(3, 5);
+        column.set_value(0, Some(b"ab   "));
+        column.set_value(1, Some(b"     "));
+        column.set_value(2, Some(b" a b"));
+
+        column.trim_trailing_spaces(3);
+
+        assert_eq!(Some(&b"ab"[..]), column.value_at(0));
+        assert_eq!(Some(&b""[..]), column.value_at(1));
+        assert_eq!(Some(&b" a b"[..]), column.value_at(2));
+    }
 
     #[test]
     #[should_panic(expected = "Column indices must be unique.")]
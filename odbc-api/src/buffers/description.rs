@@ -102,6 +102,24 @@ pub enum BufferDesc {
         /// cause an indicator buffer to be bound.
         nullable: bool,
     },
+    /// Unsigned 16 Bit integer
+    U16 {
+        /// This indicates whether or not the buffer will be able to represent NULL values. This will
+        /// cause an indicator buffer to be bound.
+        nullable: bool,
+    },
+    /// Unsigned 32 Bit integer
+    U32 {
+        /// This indicates whether or not the buffer will be able to represent NULL values. This will
+        /// cause an indicator buffer to be bound.
+        nullable: bool,
+    },
+    /// Unsigned 64 Bit integer
+    U64 {
+        /// This indicates whether or not the buffer will be able to represent NULL values. This will
+        /// cause an indicator buffer to be bound.
+        nullable: bool,
+    },
     /// Can either be zero or one
     Bit {
         /// This indicates whether or not the buffer will be able to represent NULL values. This will
@@ -167,6 +185,9 @@ impl BufferDesc {
             BufferDesc::I32 { nullable } => size_of::<i32>() + size_indicator(nullable),
             BufferDesc::I64 { nullable } => size_of::<i64>() + size_indicator(nullable),
             BufferDesc::U8 { nullable } => size_of::<u8>() + size_indicator(nullable),
+            BufferDesc::U16 { nullable } => size_of::<u16>() + size_indicator(nullable),
+            BufferDesc::U32 { nullable } => size_of::<u32>() + size_indicator(nullable),
+            BufferDesc::U64 { nullable } => size_of::<u64>() + size_indicator(nullable),
             BufferDesc::Bit { nullable } => size_of::<Bit>() + size_indicator(nullable),
         }
     }
@@ -204,5 +225,8 @@ mod tests {
         assert_eq!(4, BufferDesc::I32 { nullable: false }.bytes_per_row());
         assert_eq!(8, BufferDesc::I64 { nullable: false }.bytes_per_row());
         assert_eq!(1, BufferDesc::U8 { nullable: false }.bytes_per_row());
+        assert_eq!(2, BufferDesc::U16 { nullable: false }.bytes_per_row());
+        assert_eq!(4, BufferDesc::U32 { nullable: false }.bytes_per_row());
+        assert_eq!(8, BufferDesc::U64 { nullable: false }.bytes_per_row());
     }
 }
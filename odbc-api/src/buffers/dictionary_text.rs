@@ -0,0 +1,176 @@
+use std::{collections::HashMap, hash::Hash};
+
+use super::TextColumnView;
+
+/// Dictionary encoded copy of a batch of text values. Every distinct value is stored only once,
+/// each row holds a code referencing its value. Text columns extracted from a database are often
+/// highly repetitive (e.g. country codes or status strings). Buffers bound to a statement must
+/// reserve `max_str_len + 1` characters for every row, so keeping many fetched batches in memory
+/// this way is wasteful. Encoding a [`TextColumnView`] into a `DictionaryTextColumn` before
+/// fetching the next batch only requires memory for the distinct values and one code per row.
+///
+/// ```
+/// use odbc_api::{buffers::{DictionaryTextColumn, TextRowSet}, Cursor, Error};
+///
+/// fn fetch_status(mut cursor: impl Cursor) -> Result<Vec<DictionaryTextColumn<u8>>, Error> {
+///     let buffer = TextRowSet::for_cursor(10_000, &mut cursor, Some(4096))?;
+///     let mut row_set_cursor = cursor.bind_buffer(buffer)?;
+///     let mut batches = Vec::new();
+///     while let Some(batch) = row_set_cursor.fetch()? {
+///         batches.push(DictionaryTextColumn::from_view(batch.column(0)));
+///     }
+///     Ok(batches)
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DictionaryTextColumn<C> {
+    /// Concatenation of all distinct values.
+    values: Vec<C>,
+    /// Value with code `i` is stored in `values[offsets[i]..offsets[i + 1]]`.
+    offsets: Vec<usize>,
+    /// Code of the value for each row. `None` for `NULL`.
+    codes: Vec<Option<u32>>,
+}
+
+impl<C> DictionaryTextColumn<C> {
+    /// Copies the valid rows of `view`, storing each distinct value only once. Codes are assigned
+    /// in order of first occurrence.
+    ///
+    /// # Panics
+    ///
+    /// If the view holds more than `u32::MAX` distinct values.
+    pub fn from_view(view: TextColumnView<'_, C>) -> Self
+    where
+        C: Copy + Eq + Hash,
+    {
+        let mut values = Vec::new();
+        let mut offsets = vec![0];
+        let mut lookup = HashMap::new();
+        let codes = (0..view.len())
+            .map(|row_index| {
+                view.get(row_index).map(|value| {
+                    *lookup.entry(value).or_insert_with(|| {
+                        let code = u32::try_from(offsets.len() - 1)
+                            .expect("Number of distinct values must fit into 32 Bit.");
+                        values.extend_from_slice(value);
+                        offsets.push(values.len());
+                        code
+                    })
+                })
+            })
+            .collect();
+        Self {
+            values,
+            offsets,
+            codes,
+        }
+    }
+
+    /// Number of rows.
+    pub fn len(&self) -> usize {
+        self.codes.len()
+    }
+
+    /// `true` if there are no rows.
+    pub fn is_empty(&self) -> bool {
+        self.codes.is_empty()
+    }
+
+    /// Value at the specified row index. `None` for `NULL`. The returned slice is interned, i.e.
+    /// rows with equal values return the same slice.
+    ///
+    /// # Panics
+    ///
+    /// If `row_index` is out of bounds.
+    pub fn get(&self, row_index: usize) -> Option<&[C]> {
+        self.codes[row_index].map(|code| self.value_of(code))
+    }
+
+    /// Iterator over the values of all rows.
+    pub fn iter(&self) -> DictionaryTextIt<'_, C> {
+        DictionaryTextIt {
+            codes: self.codes.iter(),
+            column: self,
+        }
+    }
+
+    /// Number of distinct values, not counting `NULL`.
+    pub fn num_distinct(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    /// Code of the value for each row. `None` for `NULL`. Useful for converting into dictionary
+    /// encoded representations of other libraries without copying text.
+    pub fn codes(&self) -> &[Option<u32>] {
+        &self.codes
+    }
+
+    /// Distinct value referenced by `code`.
+    ///
+    /// # Panics
+    ///
+    /// If `code` is not smaller than [`Self::num_distinct`].
+    pub fn value_of(&self, code: u32) -> &[C] {
+        let code = code as usize;
+        &self.values[self.offsets[code]..self.offsets[code + 1]]
+    }
+}
+
+/// Iterator over a dictionary encoded text column. See [`DictionaryTextColumn::iter`].
+#[derive(Debug)]
+pub struct DictionaryTextIt<'c, C> {
+    codes: std::slice::Iter<'c, Option<u32>>,
+    column: &'c DictionaryTextColumn<C>,
+}
+
+impl<'c, C> Iterator for DictionaryTextIt<'c, C> {
+    type Item = Option<&'c [C]>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.codes
+            .next()
+            .map(|code| code.map(|code| self.column.value_of(code)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.codes.size_hint()
+    }
+}
+
+impl<'c, C> ExactSizeIterator for DictionaryTextIt<'c, C> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffers::{CharColumn, ColumnBuffer};
+
+    use super::DictionaryTextColumn;
+
+    #[test]
+    fn deduplicate_values() {
+        let mut column = CharColumn::new(5, 2);
+        for (index, value) in ["DE", "US", "DE", "", "US"].into_iter().enumerate() {
+            column.set_value(index, Some(value.as_bytes()));
+        }
+        column.set_value(3, None);
+        let view = column.view(5);
+
+        let encoded = DictionaryTextColumn::from_view(view);
+
+        assert_eq!(2, encoded.num_distinct());
+        assert_eq!(&[Some(0), Some(1), Some(0), None, Some(1)], encoded.codes());
+        assert_eq!(
+            vec![
+                Some(&b"DE"[..]),
+                Some(&b"US"[..]),
+                Some(&b"DE"[..]),
+                None,
+                Some(&b"US"[..])
+            ],
+            encoded.iter().collect::<Vec<_>>()
+        );
+        assert!(std::ptr::eq(
+            encoded.get(0).unwrap(),
+            encoded.get(2).unwrap()
+        ));
+    }
+}
@@ -0,0 +1,384 @@
+use std::{
+    any::type_name,
+    str::{from_utf8, Utf8Error},
+};
+
+use odbc_sys::{Date, Time, Timestamp, NULL_DATA};
+use thiserror::Error as ThisError;
+
+use crate::Bit;
+
+use super::{AnySlice, ColumnarAnyBuffer, NullableSlice};
+
+/// Error returned accessing a cell of an [`AnySlice`] as a specific type. See
+/// [`AnySlice::get`].
+#[derive(Debug, ThisError, Clone, PartialEq, Eq)]
+pub enum CellAccessError {
+    /// The column buffer holds values which can not be converted into the requested type.
+    #[error("A column buffer of type {column} can not be converted into {requested}.")]
+    TypeMismatch {
+        /// Variant of the [`AnySlice`] holding the column.
+        column: &'static str,
+        /// Name of the requested type.
+        requested: &'static str,
+    },
+    /// The cell is `NULL` but the requested type is not an `Option`.
+    #[error("Cell is NULL, yet a non optional value of type {requested} has been requested.")]
+    UnexpectedNull {
+        /// Name of the requested type.
+        requested: &'static str,
+    },
+    /// Narrow text is not valid UTF-8.
+    #[error("Text in cell is not valid UTF-8: {0}")]
+    InvalidUtf8(#[from] Utf8Error),
+    /// Wide text is not valid UTF-16.
+    #[error("Text in cell is not valid UTF-16.")]
+    InvalidUtf16,
+    /// The requested column buffer does not exist. See [`super::BatchExtractor`].
+    #[error("The batch has only {num_cols} column buffer(s).")]
+    NoSuchColumn {
+        /// Number of column buffers in the batch.
+        num_cols: usize,
+    },
+}
+
+/// Types a cell of an [`AnySlice`] can be converted into, without the need to match on the
+/// variant of the slice. Implemented for fixed sized types, strings and binary values. Numbers can
+/// also be read from columns of smaller types, as long as no value is lost (e.g. an `i64` can be
+/// read from a column of `i32`). `Option<T>` can be used for nullable columns.
+pub trait FromCell<'a>: Sized {
+    /// Reads the value in row `row_index` of `column`. `None` if the cell is `NULL`.
+    ///
+    /// # Panics
+    ///
+    /// If `row_index` is out of bounds.
+    fn from_cell(column: AnySlice<'a>, row_index: usize) -> Result<Option<Self>, CellAccessError>;
+}
+
+impl<'a, T> FromCell<'a> for Option<T>
+where
+    T: FromCell<'a>,
+{
+    fn from_cell(column: AnySlice<'a>, row_index: usize) -> Result<Option<Self>, CellAccessError> {
+        T::from_cell(column, row_index).map(Some)
+    }
+}
+
+impl<'a> AnySlice<'a> {
+    /// Value in row `row_index` converted into `T`. Use `Option<T>` to read nullable columns.
+    ///
+    /// ```
+    /// use odbc_api::buffers::{AnySlice, CellAccessError};
+    ///
+    /// fn print_names_and_ages(names: AnySlice, ages: AnySlice) -> Result<(), CellAccessError> {
+    ///     for row_index in 0..names.len() {
+    ///         let name: &str = names.get(row_index)?;
+    ///         let age: Option<i64> = ages.get(row_index)?;
+    ///         match age {
+    ///             Some(age) => println!("{name} is {age} years old."),
+    ///             None => println!("The age of {name} is unknown."),
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `row_index` is out of bounds.
+    pub fn get<T: FromCell<'a>>(self, row_index: usize) -> Result<T, CellAccessError> {
+        T::from_cell(self, row_index)?.ok_or(CellAccessError::UnexpectedNull {
+            requested: type_name::<T>(),
+        })
+    }
+
+    /// Number of valid rows in the slice.
+    pub fn len(&self) -> usize {
+        match self {
+            AnySlice::Text(view) => view.len(),
+            AnySlice::WText(view) => view.len(),
+            AnySlice::Binary(view) => view.len(),
+            AnySlice::Date(values) => values.len(),
+            AnySlice::Time(values) => values.len(),
+            AnySlice::Timestamp(values) => values.len(),
+            AnySlice::F64(values) => values.len(),
+            AnySlice::F32(values) => values.len(),
+            AnySlice::I8(values) => values.len(),
+            AnySlice::I16(values) => values.len(),
+            AnySlice::I32(values) => values.len(),
+            AnySlice::I64(values) => values.len(),
+            AnySlice::U8(values) => values.len(),
+            AnySlice::U16(values) => values.len(),
+            AnySlice::U32(values) => values.len(),
+            AnySlice::U64(values) => values.len(),
+            AnySlice::Bit(values) => values.len(),
+            AnySlice::NullableDate(values) => values.len(),
+            AnySlice::NullableTime(values) => values.len(),
+            AnySlice::NullableTimestamp(values) => values.len(),
+            AnySlice::NullableF64(values) => values.len(),
+            AnySlice::NullableF32(values) => values.len(),
+            AnySlice::NullableI8(values) => values.len(),
+            AnySlice::NullableI16(values) => values.len(),
+            AnySlice::NullableI32(values) => values.len(),
+            AnySlice::NullableI64(values) => values.len(),
+            AnySlice::NullableU8(values) => values.len(),
+            AnySlice::NullableU16(values) => values.len(),
+            AnySlice::NullableU32(values) => values.len(),
+            AnySlice::NullableU64(values) => values.len(),
+            AnySlice::NullableBit(values) => values.len(),
+        }
+    }
+
+    /// `true` if the slice does not contain any rows.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub(crate) fn variant_name(&self) -> &'static str {
+        match self {
+            AnySlice::Text(_) => "Text",
+            AnySlice::WText(_) => "WText",
+            AnySlice::Binary(_) => "Binary",
+            AnySlice::Date(_) => "Date",
+            AnySlice::Time(_) => "Time",
+            AnySlice::Timestamp(_) => "Timestamp",
+            AnySlice::F64(_) => "F64",
+            AnySlice::F32(_) => "F32",
+            AnySlice::I8(_) => "I8",
+            AnySlice::I16(_) => "I16",
+            AnySlice::I32(_) => "I32",
+            AnySlice::I64(_) => "I64",
+            AnySlice::U8(_) => "U8",
+            AnySlice::U16(_) => "U16",
+            AnySlice::U32(_) => "U32",
+            AnySlice::U64(_) => "U64",
+            AnySlice::Bit(_) => "Bit",
+            AnySlice::NullableDate(_) => "NullableDate",
+            AnySlice::NullableTime(_) => "NullableTime",
+            AnySlice::NullableTimestamp(_) => "NullableTimestamp",
+            AnySlice::NullableF64(_) => "NullableF64",
+            AnySlice::NullableF32(_) => "NullableF32",
+            AnySlice::NullableI8(_) => "NullableI8",
+            AnySlice::NullableI16(_) => "NullableI16",
+            AnySlice::NullableI32(_) => "NullableI32",
+            AnySlice::NullableI64(_) => "NullableI64",
+            AnySlice::NullableU8(_) => "NullableU8",
+            AnySlice::NullableU16(_) => "NullableU16",
+            AnySlice::NullableU32(_) => "NullableU32",
+            AnySlice::NullableU64(_) => "NullableU64",
+            AnySlice::NullableBit(_) => "NullableBit",
+        }
+    }
+}
+
+impl ColumnarAnyBuffer {
+    /// Value in row `row_index` of the column at `buffer_index` converted into `T`. Shorthand for
+    /// `self.column(buffer_index).get(row_index)`. Use `Option<T>` to read nullable columns.
+    ///
+    /// ```
+    /// use odbc_api::{buffers::ColumnarAnyBuffer, Cursor, Error};
+    ///
+    /// fn print_ids(cursor: impl Cursor, buffer: ColumnarAnyBuffer) -> Result<(), Error> {
+    ///     let mut block_cursor = cursor.bind_buffer(buffer)?;
+    ///     while let Some(batch) = block_cursor.fetch()? {
+    ///         for row_index in 0..batch.num_rows() {
+    ///             let id: i64 = batch.get(row_index, 0).expect("Id must be a non NULL integer.");
+    ///             println!("{id}");
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `row_index` or `buffer_index` is out of bounds.
+    pub fn get<'a, T: FromCell<'a>>(
+        &'a self,
+        row_index: usize,
+        buffer_index: usize,
+    ) -> Result<T, CellAccessError> {
+        self.column(buffer_index).get(row_index)
+    }
+}
+
+fn mismatch<T>(column: AnySlice<'_>) -> CellAccessError {
+    CellAccessError::TypeMismatch {
+        column: column.variant_name(),
+        requested: type_name::<T>(),
+    }
+}
+
+pub(super) fn nullable_at<T: Copy>(values: NullableSlice<'_, T>, row_index: usize) -> Option<T> {
+    let (values, indicators) = values.raw_values();
+    if indicators[row_index] == NULL_DATA {
+        None
+    } else {
+        Some(values[row_index])
+    }
+}
+
+/// Implements [`FromCell`] for a type, which can be read from the listed pairs of plain and
+/// nullable variants.
+macro_rules! impl_from_cell {
+    ($t:ty, $($plain:ident $nullable:ident),*) => {
+        impl<'a> FromCell<'a> for $t {
+            fn from_cell(
+                column: AnySlice<'a>,
+                row_index: usize,
+            ) -> Result<Option<Self>, CellAccessError> {
+                match column {
+                    $(
+                        AnySlice::$plain(values) => Ok(Some(values[row_index].into())),
+                        AnySlice::$nullable(values) => {
+                            Ok(nullable_at(values, row_index).map(Into::into))
+                        }
+                    )*
+                    other => Err(mismatch::<Self>(other)),
+                }
+            }
+        }
+    };
+}
+
+impl_from_cell!(u8, U8 NullableU8);
+impl_from_cell!(u16, U8 NullableU8, U16 NullableU16);
+impl_from_cell!(u32, U8 NullableU8, U16 NullableU16, U32 NullableU32);
+impl_from_cell!(u64, U8 NullableU8, U16 NullableU16, U32 NullableU32, U64 NullableU64);
+impl_from_cell!(i8, I8 NullableI8);
+impl_from_cell!(i16, I8 NullableI8, U8 NullableU8, I16 NullableI16);
+impl_from_cell!(
+    i32,
+    I8 NullableI8,
+    U8 NullableU8,
+    I16 NullableI16,
+    U16 NullableU16,
+    I32 NullableI32
+);
+impl_from_cell!(
+    i64,
+    I8 NullableI8,
+    U8 NullableU8,
+    I16 NullableI16,
+    U16 NullableU16,
+    I32 NullableI32,
+    U32 NullableU32,
+    I64 NullableI64
+);
+impl_from_cell!(f32, F32 NullableF32);
+impl_from_cell!(f64, F32 NullableF32, F64 NullableF64);
+impl_from_cell!(Bit, Bit NullableBit);
+impl_from_cell!(Date, Date NullableDate);
+impl_from_cell!(Time, Time NullableTime);
+impl_from_cell!(Timestamp, Timestamp NullableTimestamp);
+
+impl<'a> FromCell<'a> for bool {
+    fn from_cell(column: AnySlice<'a>, row_index: usize) -> Result<Option<Self>, CellAccessError> {
+        match column {
+            AnySlice::Bit(_) | AnySlice::NullableBit(_) => {
+                Bit::from_cell(column, row_index).map(|bit| bit.map(Bit::as_bool))
+            }
+            other => Err(mismatch::<Self>(other)),
+        }
+    }
+}
+
+impl<'a> FromCell<'a> for &'a str {
+    fn from_cell(column: AnySlice<'a>, row_index: usize) -> Result<Option<Self>, CellAccessError> {
+        match column {
+            AnySlice::Text(view) => Ok(view.get(row_index).map(from_utf8).transpose()?),
+            other => Err(mismatch::<Self>(other)),
+        }
+    }
+}
+
+impl<'a> FromCell<'a> for String {
+    fn from_cell(column: AnySlice<'a>, row_index: usize) -> Result<Option<Self>, CellAccessError> {
+        match column {
+            AnySlice::Text(_) => Ok(<&str>::from_cell(column, row_index)?.map(str::to_owned)),
+            AnySlice::WText(view) => view
+                .get(row_index)
+                .map(|text| String::from_utf16(text).map_err(|_| CellAccessError::InvalidUtf16))
+                .transpose(),
+            other => Err(mismatch::<Self>(other)),
+        }
+    }
+}
+
+impl<'a> FromCell<'a> for &'a [u8] {
+    fn from_cell(column: AnySlice<'a>, row_index: usize) -> Result<Option<Self>, CellAccessError> {
+        match column {
+            AnySlice::Binary(view) => Ok(view.get(row_index)),
+            other => Err(mismatch::<Self>(other)),
+        }
+    }
+}
+
+impl<'a> FromCell<'a> for Vec<u8> {
+    fn from_cell(column: AnySlice<'a>, row_index: usize) -> Result<Option<Self>, CellAccessError> {
+        Ok(<&[u8]>::from_cell(column, row_index)?.map(<[u8]>::to_vec))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        buffers::{AnyBuffer, BufferDesc, ColumnarAnyBuffer},
+        RowSetBuffer,
+    };
+
+    use super::CellAccessError;
+
+    #[test]
+    fn get_typed_values() {
+        let mut ids = AnyBuffer::from_desc(2, BufferDesc::I32 { nullable: true });
+        if let AnyBuffer::NullableI32(column) = &mut ids {
+            column.writer_n(2).write([Some(42), None].into_iter());
+        }
+        let mut names = AnyBuffer::from_desc(2, BufferDesc::Text { max_str_len: 5 });
+        if let AnyBuffer::Text(column) = &mut names {
+            column.set_value(0, Some(b"Hello"));
+            column.set_value(1, None);
+        }
+        let mut buffer = ColumnarAnyBuffer::new(vec![(1, ids), (2, names)]);
+        *buffer.mut_num_fetch_rows() = 2;
+
+        assert_eq!(Ok(42i64), buffer.get(0, 0));
+        assert_eq!(Ok(None::<i32>), buffer.get(1, 0));
+        assert_eq!(Ok("Hello"), buffer.get(0, 1));
+        assert_eq!(Ok(None::<String>), buffer.get(1, 1));
+        assert!(matches!(
+            buffer.get::<i32>(1, 0),
+            Err(CellAccessError::UnexpectedNull { .. })
+        ));
+        assert!(matches!(
+            buffer.get::<i8>(0, 0),
+            Err(CellAccessError::TypeMismatch {
+                column: "NullableI32",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn widen_unsigned_values() {
+        let mut column = AnyBuffer::from_desc(2, BufferDesc::U16 { nullable: true });
+        if let AnyBuffer::NullableU16(column) = &mut column {
+            column.writer_n(2).write([Some(u16::MAX), None].into_iter());
+        }
+        let mut buffer = ColumnarAnyBuffer::new(vec![(1, column)]);
+        *buffer.mut_num_fetch_rows() = 2;
+
+        assert_eq!(Ok(u16::MAX), buffer.get(0, 0));
+        assert_eq!(Ok(i32::from(u16::MAX)), buffer.get(0, 0));
+        assert_eq!(Ok(Some(u64::from(u16::MAX))), buffer.get(0, 0));
+        assert_eq!(Ok(None::<u32>), buffer.get(1, 0));
+        assert!(matches!(
+            buffer.get::<i16>(0, 0),
+            Err(CellAccessError::TypeMismatch {
+                column: "NullableU16",
+                ..
+            })
+        ));
+    }
+}
@@ -1,5 +1,7 @@
 use odbc_sys::{NO_TOTAL, NULL_DATA};
 
+use crate::Error;
+
 /// Indicates existence and length of a value.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Indicator {
@@ -12,21 +14,51 @@ pub enum Indicator {
     Length(usize),
 }
 
+/// Fails with [`Error::InvalidDriverValue`] if the driver wrote an invalid value into any of
+/// `indicators`.
+pub(crate) fn check_indicators(indicators: &[isize]) -> Result<(), Error> {
+    indicators
+        .iter()
+        .try_for_each(|&indicator| Indicator::try_from_isize(indicator).map(|_| ()))
+}
+
 impl Indicator {
     /// Creates an indicator from an `isize` indicator value returned by ODBC. Users of this crate
     /// have likely no need to call this method.
+    ///
+    /// # Panics
+    ///
+    /// If `indicator` is negative, but neither `NULL_DATA` nor `NO_TOTAL`. See
+    /// [`Self::try_from_isize`].
     pub fn from_isize(indicator: isize) -> Self {
+        Self::try_from_isize(indicator).expect("Length indicator must be non-negative.")
+    }
+
+    /// Like [`Self::from_isize`], but fails with [`Error::InvalidDriverValue`] rather than
+    /// panicking, if the driver wrote an invalid indicator.
+    pub fn try_from_isize(indicator: isize) -> Result<Self, Error> {
         match indicator {
-            NULL_DATA => Indicator::Null,
-            NO_TOTAL => Indicator::NoTotal,
-            other => Indicator::Length(
+            NULL_DATA => Ok(Indicator::Null),
+            NO_TOTAL => Ok(Indicator::NoTotal),
+            other => {
                 other
                     .try_into()
-                    .expect("Length indicator must be non-negative."),
-            ),
+                    .map(Indicator::Length)
+                    .map_err(|_| Error::InvalidDriverValue {
+                        description: "length indicator",
+                        value: other as i64,
+                    })
+            }
         }
     }
 
+    /// Like [`Self::from_isize`], but for indicators already validated with
+    /// [`Self::try_from_isize`], e.g. after a fetch. Never panics, invalid values are treated as
+    /// [`Self::NoTotal`].
+    pub(crate) fn from_validated(indicator: isize) -> Self {
+        Self::try_from_isize(indicator).unwrap_or(Indicator::NoTotal)
+    }
+
     /// Creates an indicator value as required by the ODBC C API.
     pub fn to_isize(self) -> isize {
         match self {
@@ -36,6 +68,16 @@ impl Indicator {
         }
     }
 
+    /// Number of bytes of a value with this indicator, which fit into a buffer of
+    /// `length_in_buffer` bytes. `0` for `NULL`.
+    pub(crate) fn length_in_buffer(self, length_in_buffer: usize) -> usize {
+        match self {
+            Indicator::Null => 0,
+            Indicator::NoTotal => length_in_buffer,
+            Indicator::Length(complete_length) => complete_length.min(length_in_buffer),
+        }
+    }
+
     /// Does this indicator imply truncation for a value of the given length?
     ///
     /// `length_in_buffer` is specified in bytes without terminating zeroes.
@@ -104,6 +104,9 @@ macro_rules! impl_item {
 impl_item!(f64, F64, NullableF64);
 impl_item!(f32, F32, NullableF32);
 impl_item!(u8, U8, NullableU8);
+impl_item!(u16, U16, NullableU16);
+impl_item!(u32, U32, NullableU32);
+impl_item!(u64, U64, NullableU64);
 impl_item!(i8, I8, NullableI8);
 impl_item!(i16, I16, NullableI16);
 impl_item!(i32, I32, NullableI32);
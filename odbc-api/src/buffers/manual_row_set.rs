@@ -0,0 +1,713 @@
+use std::{
+    ffi::c_void,
+    marker::PhantomData,
+    mem::{align_of, size_of, size_of_val},
+    ptr::null_mut,
+    slice,
+};
+
+use odbc_sys::CDataType;
+use thiserror::Error as ThisError;
+
+use crate::{
+    buffers::Indicator,
+    fixed_sized::Pod,
+    handles::{CData, CDataMut, Statement, StatementRef},
+    Error, RowSetBuffer,
+};
+
+/// Invariant violated by a layout described with [`ColumnWiseLayout`] or [`RowWiseLayout`].
+/// Columns are identified by their number, starting at 1 in the order they have been described.
+#[derive(Debug, ThisError, PartialEq, Eq, Clone, Copy)]
+pub enum LayoutError {
+    /// The layout does not describe any column.
+    #[error("The layout must describe at least one column.")]
+    NoColumns,
+    /// The layout has room for no row at all.
+    #[error("The layout must hold at least one row.")]
+    ZeroCapacity,
+    /// The memory of a column is too small to hold a value or indicator for each row.
+    #[error("Memory of column {column} is too small to hold all rows.")]
+    BufferTooSmall { column: u16 },
+    /// A value or indicator does not lie within a row of a row wise layout.
+    #[error("Value or indicator of column {column} exceeds the size of a row.")]
+    OutOfRow { column: u16 },
+    /// Values or indicators of two columns share the same memory.
+    #[error("Values or indicators of column {column} overlap with the ones of column {other}.")]
+    Overlap { column: u16, other: u16 },
+    /// A value or indicator is not aligned as required by its type, in at least one row.
+    #[error("Values or indicators of column {column} are not aligned as required by their type.")]
+    Misaligned { column: u16 },
+    /// The length of a value is zero, or not a whole number of characters for wide text.
+    #[error("Values of column {column} have an invalid length.")]
+    InvalidLength { column: u16 },
+    /// A column described as variadic has a C data type other than narrow text, wide text or
+    /// binary.
+    #[error("Column {column} has a fixed sized C data type, but has been described as variadic.")]
+    NotVariadic { column: u16 },
+    /// Indicators are missing for a variadic column. Without them the length of its values is
+    /// unknown.
+    #[error("Variadic column {column} requires indicators.")]
+    MissingIndicator { column: u16 },
+}
+
+/// Describes a row set buffer from caller owned memory laid out column wise, i.e. each column
+/// consists of an array of values and optionally an array of indicators. See
+/// [`ManualRowSet::column_wise`].
+pub struct ColumnWiseLayout<'b> {
+    capacity: usize,
+    columns: Vec<Described>,
+    _buffers: PhantomData<&'b mut [u8]>,
+}
+
+impl<'b> ColumnWiseLayout<'b> {
+    /// Appends a column of fixed sized values, e.g. integers or timestamps. Without indicators
+    /// fetching `NULL` causes an error.
+    pub fn fixed<T>(mut self, values: &'b mut [T], indicators: Option<&'b mut [isize]>) -> Self
+    where
+        T: Pod,
+    {
+        self.columns.push(Described::column_wise(
+            T::C_DATA_TYPE,
+            values.as_mut_ptr() as *mut u8,
+            size_of_val(values),
+            size_of::<T>(),
+            indicators,
+            Shape::Fixed {
+                alignment: align_of::<T>(),
+            },
+        ));
+        self
+    }
+
+    /// Appends a column of variadic values, i.e. narrow text ([`CDataType::Char`]), wide text
+    /// ([`CDataType::WChar`]) or binary ([`CDataType::Binary`]). Each value occupies `length`
+    /// bytes of `values`, including the terminating zero of text.
+    pub fn variadic(
+        mut self,
+        cdata_type: CDataType,
+        values: &'b mut [u8],
+        length: usize,
+        indicators: &'b mut [isize],
+    ) -> Self {
+        self.columns.push(Described::column_wise(
+            cdata_type,
+            values.as_mut_ptr(),
+            values.len(),
+            length,
+            Some(indicators),
+            Shape::Variadic,
+        ));
+        self
+    }
+
+    /// Validates the layout and creates a row set buffer, which can be bound to a cursor.
+    pub fn build(self) -> Result<ManualRowSet<'b>, LayoutError> {
+        if self.capacity == 0 {
+            return Err(LayoutError::ZeroCapacity);
+        }
+        if self.columns.is_empty() {
+            return Err(LayoutError::NoColumns);
+        }
+        for (column, described) in numbered(&self.columns) {
+            described.validate_type(column)?;
+            if !is_aligned(described.column.value_ptr as usize, described.alignment()) {
+                return Err(LayoutError::Misaligned { column });
+            }
+            let too_small = self
+                .capacity
+                .checked_mul(described.column.length)
+                .is_none_or(|required| described.value_memory < required)
+                || described.indicator_memory < self.capacity;
+            if too_small {
+                return Err(LayoutError::BufferTooSmall { column });
+            }
+        }
+        Ok(ManualRowSet::new(0, self.capacity, self.columns))
+    }
+}
+
+/// Describes a row set buffer from caller owned memory laid out row wise, i.e. the values and
+/// indicators of all columns of a row are stored together, at fixed offsets within the row. See
+/// [`ManualRowSet::row_wise`].
+pub struct RowWiseLayout<'b> {
+    rows: *mut u8,
+    row_size: usize,
+    capacity: usize,
+    columns: Vec<Described>,
+    _buffers: PhantomData<&'b mut [u8]>,
+}
+
+impl<'b> RowWiseLayout<'b> {
+    /// Appends a column of fixed sized values, e.g. integers or timestamps, stored at byte `offset`
+    /// within each row. Indicators are stored at byte `indicator_offset`, if specified. Without
+    /// indicators fetching `NULL` causes an error.
+    pub fn fixed<T>(self, offset: usize, indicator_offset: Option<usize>) -> Self
+    where
+        T: Pod,
+    {
+        let shape = Shape::Fixed {
+            alignment: align_of::<T>(),
+        };
+        self.field(
+            T::C_DATA_TYPE,
+            offset,
+            size_of::<T>(),
+            indicator_offset,
+            shape,
+        )
+    }
+
+    /// Appends a column of variadic values, i.e. narrow text ([`CDataType::Char`]), wide text
+    /// ([`CDataType::WChar`]) or binary ([`CDataType::Binary`]), stored at byte `offset` within
+    /// each row. Each value occupies `length` bytes, including the terminating zero of text. The
+    /// indicators are stored at byte `indicator_offset`.
+    pub fn variadic(
+        self,
+        cdata_type: CDataType,
+        offset: usize,
+        length: usize,
+        indicator_offset: usize,
+    ) -> Self {
+        self.field(
+            cdata_type,
+            offset,
+            length,
+            Some(indicator_offset),
+            Shape::Variadic,
+        )
+    }
+
+    fn field(
+        mut self,
+        cdata_type: CDataType,
+        offset: usize,
+        length: usize,
+        indicator_offset: Option<usize>,
+        shape: Shape,
+    ) -> Self {
+        self.columns.push(Described {
+            column: ManualColumn {
+                cdata_type,
+                length,
+                // Pointers are only dereferenced once the layout has been validated.
+                value_ptr: self.rows.wrapping_add(offset),
+                indicator_ptr: indicator_offset
+                    .map(|indicator_offset| self.rows.wrapping_add(indicator_offset) as *mut isize)
+                    .unwrap_or_else(null_mut),
+            },
+            value_memory: offset,
+            indicator_memory: indicator_offset.unwrap_or(usize::MAX),
+            shape,
+        });
+        self
+    }
+
+    /// Validates the layout and creates a row set buffer, which can be bound to a cursor.
+    pub fn build(self) -> Result<ManualRowSet<'b>, LayoutError> {
+        if self.capacity == 0 {
+            return Err(LayoutError::ZeroCapacity);
+        }
+        if self.columns.is_empty() {
+            return Err(LayoutError::NoColumns);
+        }
+        // Byte ranges occupied within each row, and the column they belong to.
+        let mut occupied = Vec::new();
+        for (column, described) in numbered(&self.columns) {
+            described.validate_type(column)?;
+            // For row wise layouts `value_memory` and `indicator_memory` hold the offsets.
+            let mut ranges = vec![(
+                described.value_memory,
+                described.column.length,
+                described.alignment(),
+            )];
+            if described.has_indicators() {
+                ranges.push((
+                    described.indicator_memory,
+                    size_of::<isize>(),
+                    align_of::<isize>(),
+                ));
+            }
+            for (offset, length, alignment) in ranges {
+                if offset
+                    .checked_add(length)
+                    .is_none_or(|end| end > self.row_size)
+                {
+                    return Err(LayoutError::OutOfRow { column });
+                }
+                // Every row must be aligned, not just the first one.
+                if !is_aligned(self.rows as usize + offset, alignment)
+                    || !is_aligned(self.row_size, alignment)
+                {
+                    return Err(LayoutError::Misaligned { column });
+                }
+                if let Some(&(_, _, other)) = occupied
+                    .iter()
+                    .find(|&&(start, end, _)| offset < end && start < offset + length)
+                {
+                    return Err(LayoutError::Overlap { column, other });
+                }
+                occupied.push((offset, offset + length, column));
+            }
+        }
+        Ok(ManualRowSet::new(
+            self.row_size,
+            self.capacity,
+            self.columns,
+        ))
+    }
+}
+
+/// A row set buffer in caller owned memory, with a layout described by the caller. Fills the gap
+/// between the buffers managed by this crate, like [`crate::buffers::ColumnarBuffer`], and binding
+/// raw pointers using [`crate::sys`]. Useful if fetched values should end up in a memory layout
+/// required by other code, e.g. an array of C structs.
+///
+/// The layout is validated before it can be bound: every value and indicator of every row must lie
+/// within the memory provided, must be aligned as required by its type, and must not overlap with
+/// values or indicators of other columns. The memory is borrowed mutably for the lifetime of the
+/// row set, so it can not be accessed, while the driver may write to it. Values can be accessed
+/// through the row set, or by the owner of the memory once the row set has been dropped.
+///
+/// Fetch rows into an array of C structs:
+///
+/// ```no_run
+/// use std::mem::{offset_of, size_of, size_of_val};
+/// use odbc_api::{buffers::ManualRowSet, sys::CDataType, Cursor, Error};
+///
+/// #[repr(C)]
+/// #[derive(Clone, Copy)]
+/// struct Employee {
+///     id: i32,
+///     name: [u8; 32],
+///     name_indicator: isize,
+/// }
+///
+/// fn fetch_employees(cursor: impl Cursor, employees: &mut [Employee]) -> Result<usize, Error> {
+///     // Safe, since `Employee` can hold any bit pattern.
+///     let memory = unsafe {
+///         let len = size_of_val(employees);
+///         std::slice::from_raw_parts_mut(employees.as_mut_ptr() as *mut u8, len)
+///     };
+///     let row_set = ManualRowSet::row_wise(memory, size_of::<Employee>())
+///         .fixed::<i32>(offset_of!(Employee, id), None)
+///         .variadic(
+///             CDataType::Char,
+///             offset_of!(Employee, name),
+///             32,
+///             offset_of!(Employee, name_indicator),
+///         )
+///         .build()
+///         .expect("Layout of Employee must be valid");
+///     let mut cursor = cursor.bind_buffer(row_set)?;
+///     let num_rows = cursor.fetch()?.map(|batch| batch.num_rows()).unwrap_or(0);
+///     Ok(num_rows)
+/// }
+/// ```
+pub struct ManualRowSet<'b> {
+    /// `0` for column wise binding, otherwise the size of a row in bytes.
+    row_size: usize,
+    capacity: usize,
+    columns: Vec<ManualColumn>,
+    num_rows: Box<usize>,
+    _buffers: PhantomData<&'b mut [u8]>,
+}
+
+impl<'b> ManualRowSet<'b> {
+    /// Starts describing a layout, in which each column consists of its own arrays of values and
+    /// indicators, holding `capacity` rows.
+    pub fn column_wise(capacity: usize) -> ColumnWiseLayout<'b> {
+        ColumnWiseLayout {
+            capacity,
+            columns: Vec::new(),
+            _buffers: PhantomData,
+        }
+    }
+
+    /// Starts describing a layout, in which `rows` is an array of rows, each one `row_size` bytes
+    /// long. The number of rows is the number of whole rows fitting into `rows`.
+    ///
+    /// # Panics
+    ///
+    /// If `row_size` is zero.
+    pub fn row_wise(rows: &'b mut [u8], row_size: usize) -> RowWiseLayout<'b> {
+        assert!(row_size > 0, "Size of a row must be at least one byte.");
+        RowWiseLayout {
+            rows: rows.as_mut_ptr(),
+            row_size,
+            capacity: rows.len() / row_size,
+            columns: Vec::new(),
+            _buffers: PhantomData,
+        }
+    }
+
+    fn new(row_size: usize, capacity: usize, described: Vec<Described>) -> Self {
+        Self {
+            row_size,
+            capacity,
+            columns: described
+                .into_iter()
+                .map(|described| described.column)
+                .collect(),
+            num_rows: Box::new(0),
+            _buffers: PhantomData,
+        }
+    }
+
+    /// Number of rows the buffer can hold.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of rows fetched with the last call to `fetch`.
+    pub fn num_rows(&self) -> usize {
+        *self.num_rows
+    }
+
+    /// Number of columns in the layout.
+    pub fn num_cols(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Bytes of the value in the column at `buffer_index` and row at `row_index`, both starting at
+    /// zero. Variadic values are cut at the reported length and exclude the terminating zero of
+    /// text. `None` if the value is `NULL`.
+    ///
+    /// # Panics
+    ///
+    /// If `row_index` is not smaller than [`Self::num_rows`].
+    pub fn value(&self, buffer_index: usize, row_index: usize) -> Option<&[u8]> {
+        assert!(
+            row_index < *self.num_rows,
+            "Row index must be smaller than the number of fetched rows."
+        );
+        let column = &self.columns[buffer_index];
+        let length = match self.indicator(column, row_index) {
+            Some(Indicator::Null) => return None,
+            Some(indicator) if column.is_variadic() => {
+                indicator.length_in_buffer(column.max_payload())
+            }
+            _ => column.length,
+        };
+        // Safe: The layout has been validated, and the memory is borrowed for the lifetime of
+        // `self`.
+        unsafe {
+            let value_ptr = column.value_ptr.add(row_index * self.value_stride(column));
+            Some(slice::from_raw_parts(value_ptr, length))
+        }
+    }
+
+    /// Fixed sized value in the column at `buffer_index` and row at `row_index`, both starting at
+    /// zero. `None` if the value is `NULL`.
+    ///
+    /// # Panics
+    ///
+    /// If `row_index` is not smaller than [`Self::num_rows`], or if the column has not been
+    /// described with the C data type of `T`.
+    pub fn fixed<T>(&self, buffer_index: usize, row_index: usize) -> Option<T>
+    where
+        T: Pod,
+    {
+        assert_eq!(
+            T::C_DATA_TYPE,
+            self.columns[buffer_index].cdata_type,
+            "C data type of the column must match the requested type."
+        );
+        // Values of row wise layouts are aligned, yet do not rely on it.
+        self.value(buffer_index, row_index)
+            .map(|bytes| unsafe { (bytes.as_ptr() as *const T).read_unaligned() })
+    }
+
+    fn value_stride(&self, column: &ManualColumn) -> usize {
+        if self.row_size == 0 {
+            column.length
+        } else {
+            self.row_size
+        }
+    }
+
+    fn indicator(&self, column: &ManualColumn, row_index: usize) -> Option<Indicator> {
+        self.raw_indicator(column, row_index)
+            .map(Indicator::from_validated)
+    }
+
+    fn raw_indicator(&self, column: &ManualColumn, row_index: usize) -> Option<isize> {
+        if column.indicator_ptr.is_null() {
+            return None;
+        }
+        let stride = if self.row_size == 0 {
+            size_of::<isize>()
+        } else {
+            self.row_size
+        };
+        // Safe: The layout has been validated, and indicators are aligned.
+        let indicator = unsafe {
+            *(column.indicator_ptr as *const u8)
+                .add(row_index * stride)
+                .cast::<isize>()
+        };
+        Some(indicator)
+    }
+}
+
+unsafe impl RowSetBuffer for ManualRowSet<'_> {
+    fn bind_type(&self) -> usize {
+        self.row_size
+    }
+
+    fn row_array_size(&self) -> usize {
+        self.capacity
+    }
+
+    fn mut_num_fetch_rows(&mut self) -> &mut usize {
+        self.num_rows.as_mut()
+    }
+
+    unsafe fn bind_colmuns_to_cursor(&mut self, mut cursor: StatementRef<'_>) -> Result<(), Error> {
+        for (column_number, column) in (1..).zip(&mut self.columns) {
+            cursor
+                .bind_col(column_number, column)
+                .into_result(&cursor)?;
+        }
+        Ok(())
+    }
+
+    fn has_truncated_values(&self) -> bool {
+        self.columns
+            .iter()
+            .filter(|column| column.is_variadic())
+            .any(|column| {
+                (0..*self.num_rows).any(|row_index| {
+                    self.indicator(column, row_index)
+                        .is_some_and(|indicator| indicator.is_truncated(column.max_payload()))
+                })
+            })
+    }
+
+    fn check_indicators(&self) -> Result<(), Error> {
+        for column in &self.columns {
+            for row_index in 0..*self.num_rows {
+                if let Some(indicator) = self.raw_indicator(column, row_index) {
+                    Indicator::try_from_isize(indicator)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A column bound to caller owned memory. Pointers refer to the first row.
+struct ManualColumn {
+    cdata_type: CDataType,
+    /// Length of a single value in bytes, including the terminating zero of text.
+    length: usize,
+    value_ptr: *mut u8,
+    /// Null, if the column has no indicators.
+    indicator_ptr: *mut isize,
+}
+
+impl ManualColumn {
+    fn is_variadic(&self) -> bool {
+        matches!(
+            self.cdata_type,
+            CDataType::Char | CDataType::WChar | CDataType::Binary
+        )
+    }
+
+    /// Maximum length of a value in bytes, excluding the terminating zero of text.
+    fn max_payload(&self) -> usize {
+        match self.cdata_type {
+            CDataType::Char => self.length.saturating_sub(1),
+            CDataType::WChar => self.length.saturating_sub(2),
+            _ => self.length,
+        }
+    }
+}
+
+unsafe impl CData for ManualColumn {
+    fn cdata_type(&self) -> CDataType {
+        self.cdata_type
+    }
+
+    fn indicator_ptr(&self) -> *const isize {
+        self.indicator_ptr
+    }
+
+    fn value_ptr(&self) -> *const c_void {
+        self.value_ptr as *const c_void
+    }
+
+    fn buffer_length(&self) -> isize {
+        self.length.try_into().unwrap()
+    }
+}
+
+unsafe impl CDataMut for ManualColumn {
+    fn mut_indicator_ptr(&mut self) -> *mut isize {
+        self.indicator_ptr
+    }
+
+    fn mut_value_ptr(&mut self) -> *mut c_void {
+        self.value_ptr as *mut c_void
+    }
+}
+
+/// A column described, but not yet validated.
+struct Described {
+    column: ManualColumn,
+    /// Column wise: Bytes of memory available for values. Row wise: Offset of the value in a row.
+    value_memory: usize,
+    /// Column wise: Number of indicators available. Row wise: Offset of the indicator in a row.
+    /// `usize::MAX` if the column has no indicators.
+    indicator_memory: usize,
+    shape: Shape,
+}
+
+/// How a column has been described.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    /// Using [`Pod`], so values have the size and alignment of the type.
+    Fixed { alignment: usize },
+    /// Using a C data type and a length.
+    Variadic,
+}
+
+impl Described {
+    fn column_wise(
+        cdata_type: CDataType,
+        value_ptr: *mut u8,
+        value_memory: usize,
+        length: usize,
+        indicators: Option<&mut [isize]>,
+        shape: Shape,
+    ) -> Self {
+        let (indicator_ptr, indicator_memory) = match indicators {
+            Some(indicators) => (indicators.as_mut_ptr(), indicators.len()),
+            None => (null_mut(), usize::MAX),
+        };
+        Self {
+            column: ManualColumn {
+                cdata_type,
+                length,
+                value_ptr,
+                indicator_ptr,
+            },
+            value_memory,
+            indicator_memory,
+            shape,
+        }
+    }
+
+    fn has_indicators(&self) -> bool {
+        !self.column.indicator_ptr.is_null()
+    }
+
+    /// Alignment required for values.
+    fn alignment(&self) -> usize {
+        match self.shape {
+            Shape::Fixed { alignment } => alignment,
+            Shape::Variadic if self.column.cdata_type == CDataType::WChar => align_of::<u16>(),
+            Shape::Variadic => 1,
+        }
+    }
+
+    fn validate_type(&self, column: u16) -> Result<(), LayoutError> {
+        let ManualColumn {
+            cdata_type, length, ..
+        } = self.column;
+        if self.shape != Shape::Variadic {
+            return Ok(());
+        }
+        if !self.column.is_variadic() {
+            return Err(LayoutError::NotVariadic { column });
+        }
+        if length == 0 || (cdata_type == CDataType::WChar && length % 2 != 0) {
+            return Err(LayoutError::InvalidLength { column });
+        }
+        if !self.has_indicators() {
+            return Err(LayoutError::MissingIndicator { column });
+        }
+        Ok(())
+    }
+}
+
+/// `true` if `value` is a multiple of `alignment`, which must be a power of two.
+fn is_aligned(value: usize, alignment: usize) -> bool {
+    value & (alignment - 1) == 0
+}
+
+/// Pairs described columns with their column number, starting at 1.
+fn numbered(columns: &[Described]) -> impl Iterator<Item = (u16, &Described)> {
+    (1..).zip(columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use odbc_sys::CDataType;
+
+    use super::{LayoutError, ManualRowSet};
+
+    #[test]
+    fn column_wise_memory_must_hold_all_rows() {
+        let mut ids = [0i32; 3];
+        let mut names = [0u8; 3 * 8];
+        let mut indicators = [0isize; 2];
+
+        let result = ManualRowSet::column_wise(3)
+            .fixed(&mut ids, None)
+            .variadic(CDataType::Char, &mut names, 8, &mut indicators)
+            .build();
+
+        assert_eq!(
+            LayoutError::BufferTooSmall { column: 2 },
+            result.err().unwrap()
+        );
+    }
+
+    #[test]
+    fn row_wise_fields_must_not_overlap() {
+        let mut rows = [0u64; 8];
+        let rows = unsafe { std::slice::from_raw_parts_mut(rows.as_mut_ptr() as *mut u8, 64) };
+
+        let result = ManualRowSet::row_wise(rows, 16)
+            .fixed::<i64>(0, None)
+            .fixed::<i32>(4, None)
+            .build();
+
+        assert_eq!(
+            LayoutError::Overlap {
+                column: 2,
+                other: 1
+            },
+            result.err().unwrap()
+        );
+    }
+
+    #[test]
+    fn row_wise_fields_must_be_aligned_in_every_row() {
+        let mut rows = [0u64; 8];
+        let rows = unsafe { std::slice::from_raw_parts_mut(rows.as_mut_ptr() as *mut u8, 64) };
+
+        // Fields are aligned in the first row, but not in the second one.
+        let result = ManualRowSet::row_wise(rows, 12)
+            .fixed::<i64>(0, None)
+            .build();
+
+        assert_eq!(LayoutError::Misaligned { column: 1 }, result.err().unwrap());
+    }
+
+    #[test]
+    fn valid_row_wise_layout() {
+        let mut rows = [0u64; 8];
+        let rows = unsafe { std::slice::from_raw_parts_mut(rows.as_mut_ptr() as *mut u8, 64) };
+
+        let row_set = ManualRowSet::row_wise(rows, 32)
+            .fixed::<i32>(0, None)
+            .variadic(CDataType::Char, 4, 12, 16)
+            .build()
+            .unwrap();
+
+        assert_eq!(2, row_set.capacity());
+        assert_eq!(2, row_set.num_cols());
+    }
+}
@@ -0,0 +1,131 @@
+use super::{AnyBuffer, AnySlice, ColumnBuffer, ColumnarAnyBuffer};
+
+/// A deep copy of the rows of a [`ColumnarAnyBuffer`], detached from the cursor it has been fetched
+/// with. Other than the buffer, it is not bound to a statement, so it can be sent to other threads
+/// or kept around while the next batch is fetched into the buffer. Offers the same read access as
+/// the buffer it has been copied from. Created with [`ColumnarAnyBuffer::to_owned_batch`].
+///
+/// Only the valid rows are copied, so a batch copied from a buffer which has not been filled
+/// completely, e.g. the last one of a result set, is smaller than the buffer.
+///
+/// ```no_run
+/// use std::{sync::mpsc, thread};
+/// use odbc_api::{buffers::{BufferDesc, ColumnarAnyBuffer}, Cursor, Error};
+///
+/// fn process_in_background(cursor: impl Cursor) -> Result<(), Error> {
+///     let (sender, receiver) = mpsc::sync_channel(2);
+///     let worker = thread::spawn(move || {
+///         for batch in receiver {
+///             let batch: odbc_api::buffers::OwnedBatch = batch;
+///             println!("Processing {} rows", batch.num_rows());
+///         }
+///     });
+///     let buffer = ColumnarAnyBuffer::from_descs(1000, [BufferDesc::I64 { nullable: true }]);
+///     let mut block_cursor = cursor.bind_buffer(buffer)?;
+///     while let Some(batch) = block_cursor.fetch()? {
+///         sender.send(batch.to_owned_batch()).unwrap();
+///     }
+///     drop(sender);
+///     worker.join().unwrap();
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct OwnedBatch {
+    /// Column index and buffer holding exactly `num_rows` elements.
+    columns: Vec<(u16, AnyBuffer)>,
+    num_rows: usize,
+}
+
+impl OwnedBatch {
+    /// Number of rows in the batch.
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    /// Number of columns in the batch.
+    pub fn num_cols(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// One based index of the column in the result set, the buffer at `buffer_index` has been
+    /// bound to.
+    pub fn column_number(&self, buffer_index: usize) -> u16 {
+        self.columns[buffer_index].0
+    }
+
+    /// Read access to the values of a column. `buffer_index` is zero based, like for
+    /// [`ColumnarAnyBuffer::column`].
+    pub fn column(&self, buffer_index: usize) -> AnySlice<'_> {
+        self.columns[buffer_index].1.view(self.num_rows)
+    }
+}
+
+impl ColumnarAnyBuffer {
+    /// Copies the valid rows of the buffer into an [`OwnedBatch`].
+    pub fn to_owned_batch(&self) -> OwnedBatch {
+        let num_rows = self.num_rows();
+        let columns = self
+            .columns()
+            .iter()
+            .map(|(column_number, buffer)| (*column_number, buffer.copy_rows(num_rows)))
+            .collect();
+        OwnedBatch { columns, num_rows }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        buffers::{AnyBuffer, BufferDesc, ColumnarAnyBuffer},
+        RowSetBuffer,
+    };
+
+    use super::OwnedBatch;
+
+    #[test]
+    fn copy_valid_rows_only() {
+        let mut buffer = ColumnarAnyBuffer::from_descs(
+            3,
+            [
+                BufferDesc::I32 { nullable: true },
+                BufferDesc::Text { max_str_len: 5 },
+            ],
+        );
+        for (index, column) in buffer.columns_mut().enumerate() {
+            match column {
+                AnyBuffer::NullableI32(column) => {
+                    let mut writer = column.writer_n(2);
+                    writer.set_cell(0, Some(42));
+                    writer.set_cell(1, None);
+                }
+                AnyBuffer::Text(column) => {
+                    column.set_value(0, Some(b"Hello"));
+                    column.set_value(1, None);
+                }
+                _ => panic!("Unexpected buffer in column {index}"),
+            }
+        }
+        *buffer.mut_num_fetch_rows() = 2;
+
+        let batch = buffer.to_owned_batch();
+
+        assert_eq!(2, batch.num_rows());
+        assert_eq!(2, batch.column_number(1));
+        let numbers: Vec<_> = batch
+            .column(0)
+            .as_nullable_slice::<i32>()
+            .unwrap()
+            .map(|value| value.copied())
+            .collect();
+        assert_eq!(vec![Some(42), None], numbers);
+        let texts: Vec<_> = batch.column(1).as_text_view().unwrap().iter().collect();
+        assert_eq!(vec![Some(&b"Hello"[..]), None], texts);
+    }
+
+    #[test]
+    fn owned_batch_is_send() {
+        fn assert_send<T: Send + 'static>() {}
+        assert_send::<OwnedBatch>();
+    }
+}
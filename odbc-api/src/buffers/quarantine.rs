@@ -0,0 +1,244 @@
+use std::str::{from_utf8, Utf8Error};
+
+use odbc_sys::{Date, Timestamp};
+
+use crate::parameter::WithDataType;
+
+use super::{
+    column_with_indicator::ColumnWithIndicator, AnyBuffer, BinColumn, CharColumn, Indicator,
+    WCharColumn,
+};
+
+/// A cell of a fetched row set, which could not be decoded and has been set to `NULL` instead. See
+/// [`crate::buffers::ColumnarBuffer::with_quarantine`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellError {
+    /// Zero based index of the row within the batch.
+    pub row: usize,
+    /// Zero based index of the column within the buffer.
+    pub col: usize,
+    /// Reason the value of the cell has been discarded.
+    pub cause: CellErrorCause,
+}
+
+/// Reason for the value of a cell being quarantined.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CellErrorCause {
+    /// Value did not fit into the buffer. Holds the length of the complete value in bytes, if
+    /// reported by the driver.
+    Truncated { length: Option<usize> },
+    /// Narrow text is not valid UTF-8.
+    InvalidUtf8(Utf8Error),
+    /// Wide text is not valid UTF-16.
+    InvalidUtf16,
+    /// Date or timestamp with fields out of range, e.g. a month of `13`.
+    InvalidTimestamp,
+}
+
+/// Column buffers able to detect and discard values, which can not be decoded by the application.
+pub trait Quarantine {
+    /// Sets all values in the range [0, num_rows) which can not be decoded to `NULL` and reports
+    /// their row index and the cause to `on_error`.
+    fn quarantine(&mut self, num_rows: usize, on_error: &mut dyn FnMut(usize, CellErrorCause));
+}
+
+/// `Some` cause if the indicator belongs to a truncated value.
+fn truncation(indicator: Indicator, max_len: usize) -> Option<CellErrorCause> {
+    match indicator {
+        Indicator::NoTotal => Some(CellErrorCause::Truncated { length: None }),
+        Indicator::Length(length) if length > max_len => Some(CellErrorCause::Truncated {
+            length: Some(length),
+        }),
+        _ => None,
+    }
+}
+
+impl Quarantine for CharColumn {
+    fn quarantine(&mut self, num_rows: usize, on_error: &mut dyn FnMut(usize, CellErrorCause)) {
+        for row_index in 0..num_rows {
+            let cause = truncation(self.indicator_at(row_index), self.max_len()).or_else(|| {
+                self.value_at(row_index)
+                    .and_then(|value| from_utf8(value).err())
+                    .map(CellErrorCause::InvalidUtf8)
+            });
+            if let Some(cause) = cause {
+                self.set_value(row_index, None);
+                on_error(row_index, cause);
+            }
+        }
+    }
+}
+
+impl Quarantine for WCharColumn {
+    fn quarantine(&mut self, num_rows: usize, on_error: &mut dyn FnMut(usize, CellErrorCause)) {
+        for row_index in 0..num_rows {
+            let max_len_in_bytes = self.max_len() * 2;
+            let cause = truncation(self.indicator_at(row_index), max_len_in_bytes).or_else(|| {
+                self.value_at(row_index)
+                    .filter(|value| char::decode_utf16(value.iter().copied()).any(|c| c.is_err()))
+                    .map(|_| CellErrorCause::InvalidUtf16)
+            });
+            if let Some(cause) = cause {
+                self.set_value(row_index, None);
+                on_error(row_index, cause);
+            }
+        }
+    }
+}
+
+impl Quarantine for BinColumn {
+    fn quarantine(&mut self, num_rows: usize, on_error: &mut dyn FnMut(usize, CellErrorCause)) {
+        for row_index in 0..num_rows {
+            if let Some(cause) = truncation(self.indicator_at(row_index), self.max_len()) {
+                self.set_value(row_index, None);
+                on_error(row_index, cause);
+            }
+        }
+    }
+}
+
+fn is_valid_date(year: i16, month: u16, day: u16) -> bool {
+    let is_leap_year = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=days_in_month).contains(&day)
+}
+
+fn is_valid_time(hour: u16, minute: u16, second: u16) -> bool {
+    // Allow for leap seconds
+    hour < 24 && minute < 60 && second <= 60
+}
+
+/// Sets all values in the range [0, num_rows) rejected by `is_valid` to `NULL`.
+fn quarantine_invalid_timestamps<T: Default + Clone>(
+    column: &mut ColumnWithIndicator<T>,
+    num_rows: usize,
+    is_valid: impl Fn(&T) -> bool,
+    on_error: &mut dyn FnMut(usize, CellErrorCause),
+) {
+    let invalid: Vec<usize> = column
+        .iter(num_rows)
+        .enumerate()
+        .filter(|(_, value)| value.map(|value| !is_valid(value)).unwrap_or(false))
+        .map(|(row_index, _)| row_index)
+        .collect();
+    if invalid.is_empty() {
+        return;
+    }
+    let mut writer = column.writer_n(num_rows);
+    for row_index in invalid {
+        writer.set_cell(row_index, None);
+        on_error(row_index, CellErrorCause::InvalidTimestamp);
+    }
+}
+
+impl Quarantine for ColumnWithIndicator<Date> {
+    fn quarantine(&mut self, num_rows: usize, on_error: &mut dyn FnMut(usize, CellErrorCause)) {
+        quarantine_invalid_timestamps(
+            self,
+            num_rows,
+            |d| is_valid_date(d.year, d.month, d.day),
+            on_error,
+        )
+    }
+}
+
+impl Quarantine for ColumnWithIndicator<Timestamp> {
+    fn quarantine(&mut self, num_rows: usize, on_error: &mut dyn FnMut(usize, CellErrorCause)) {
+        quarantine_invalid_timestamps(
+            self,
+            num_rows,
+            |ts| {
+                is_valid_date(ts.year, ts.month, ts.day)
+                    && is_valid_time(ts.hour, ts.minute, ts.second)
+                    && ts.fraction < 1_000_000_000
+            },
+            on_error,
+        )
+    }
+}
+
+impl Quarantine for AnyBuffer {
+    fn quarantine(&mut self, num_rows: usize, on_error: &mut dyn FnMut(usize, CellErrorCause)) {
+        match self {
+            AnyBuffer::Binary(col) => col.quarantine(num_rows, on_error),
+            AnyBuffer::Text(col) => col.quarantine(num_rows, on_error),
+            AnyBuffer::WText(col) => col.quarantine(num_rows, on_error),
+            AnyBuffer::NullableDate(col) => col.quarantine(num_rows, on_error),
+            AnyBuffer::NullableTimestamp(col) => col.quarantine(num_rows, on_error),
+            // Values of other types are not decoded by the application, or can not be set to
+            // `NULL`.
+            _ => (),
+        }
+    }
+}
+
+impl<T> Quarantine for WithDataType<T>
+where
+    T: Quarantine,
+{
+    fn quarantine(&mut self, num_rows: usize, on_error: &mut dyn FnMut(usize, CellErrorCause)) {
+        self.value.quarantine(num_rows, on_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use odbc_sys::Timestamp;
+
+    use crate::buffers::{column_with_indicator::OptTimestampColumn, TextColumn};
+
+    use super::{CellErrorCause, Quarantine};
+
+    #[test]
+    fn quarantine_invalid_utf8() {
+        let mut column = TextColumn::new(2, 4);
+        column.set_value(0, Some(b"ok"));
+        column.set_value(1, Some(&[0xff, 0xfe]));
+        let mut errors = Vec::new();
+
+        column.quarantine(2, &mut |row, cause| errors.push((row, cause)));
+
+        assert_eq!(Some(&b"ok"[..]), column.value_at(0));
+        assert_eq!(None, column.value_at(1));
+        assert_eq!(1, errors.len());
+        assert_eq!(1, errors[0].0);
+        assert!(matches!(errors[0].1, CellErrorCause::InvalidUtf8(_)));
+    }
+
+    #[test]
+    fn quarantine_invalid_timestamps() {
+        let mut column = OptTimestampColumn::new(2);
+        let mut writer = column.writer_n(2);
+        writer.set_cell(
+            0,
+            Some(Timestamp {
+                year: 2024,
+                month: 2,
+                day: 29,
+                ..Default::default()
+            }),
+        );
+        writer.set_cell(
+            1,
+            Some(Timestamp {
+                year: 2023,
+                month: 2,
+                day: 29,
+                ..Default::default()
+            }),
+        );
+        let mut errors = Vec::new();
+
+        column.quarantine(2, &mut |row, cause| errors.push((row, cause)));
+
+        assert_eq!(vec![(1, CellErrorCause::InvalidTimestamp)], errors);
+        let values: Vec<_> = column.iter(2).map(|value| value.is_some()).collect();
+        assert_eq!(vec![true, false], values);
+    }
+}
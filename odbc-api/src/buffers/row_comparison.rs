@@ -0,0 +1,514 @@
+use std::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+};
+
+use odbc_sys::{Date, Time, Timestamp};
+
+use super::{from_cell::nullable_at, AnySlice, CellAccessError, FromCell};
+
+/// Borrowed value of a cell in a column buffer of any type. Values are normalized into a few
+/// kinds, so cells of different buffer types can be compared with each other, e.g. an `i32` with
+/// an `i64`, or narrow with wide text. Use `Option<CellValue>` to read nullable cells, see
+/// [`AnySlice::get`]. See [`OwnedCellValue`] for values which outlive the buffer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CellValue<'a> {
+    /// Value of an integer or bit column.
+    Integer(i128),
+    /// Value of a floating point column.
+    Float(f64),
+    /// Narrow text.
+    Text(&'a [u8]),
+    /// Wide text.
+    WideText(&'a [u16]),
+    /// Binary value.
+    Binary(&'a [u8]),
+    Date(Date),
+    Time(Time),
+    Timestamp(Timestamp),
+}
+
+/// Owned counterpart of [`CellValue`], e.g. to keep a value across fetching several batches.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OwnedCellValue {
+    /// Value of an integer or bit column.
+    Integer(i128),
+    /// Value of a floating point column.
+    Float(f64),
+    /// Narrow text.
+    Text(Vec<u8>),
+    /// Wide text.
+    WideText(Vec<u16>),
+    /// Binary value.
+    Binary(Vec<u8>),
+    Date(Date),
+    Time(Time),
+    Timestamp(Timestamp),
+}
+
+impl OwnedCellValue {
+    /// Borrows the value, so it can be compared with [`RowEq`] or [`RowOrd`].
+    pub fn as_cell(&self) -> CellValue<'_> {
+        match self {
+            OwnedCellValue::Integer(value) => CellValue::Integer(*value),
+            OwnedCellValue::Float(value) => CellValue::Float(*value),
+            OwnedCellValue::Text(value) => CellValue::Text(value),
+            OwnedCellValue::WideText(value) => CellValue::WideText(value),
+            OwnedCellValue::Binary(value) => CellValue::Binary(value),
+            OwnedCellValue::Date(value) => CellValue::Date(*value),
+            OwnedCellValue::Time(value) => CellValue::Time(*value),
+            OwnedCellValue::Timestamp(value) => CellValue::Timestamp(*value),
+        }
+    }
+}
+
+impl<'a> FromCell<'a> for CellValue<'a> {
+    fn from_cell(column: AnySlice<'a>, row_index: usize) -> Result<Option<Self>, CellAccessError> {
+        fn int<T: Into<i128>>(value: T) -> CellValue<'static> {
+            CellValue::Integer(value.into())
+        }
+
+        let value = match column {
+            AnySlice::Text(view) => view.get(row_index).map(CellValue::Text),
+            AnySlice::WText(view) => view.get(row_index).map(CellValue::WideText),
+            AnySlice::Binary(view) => view.get(row_index).map(CellValue::Binary),
+            AnySlice::Date(values) => Some(CellValue::Date(values[row_index])),
+            AnySlice::Time(values) => Some(CellValue::Time(values[row_index])),
+            AnySlice::Timestamp(values) => Some(CellValue::Timestamp(values[row_index])),
+            AnySlice::F64(values) => Some(CellValue::Float(values[row_index])),
+            AnySlice::F32(values) => Some(CellValue::Float(values[row_index].into())),
+            AnySlice::I8(values) => Some(int(values[row_index])),
+            AnySlice::I16(values) => Some(int(values[row_index])),
+            AnySlice::I32(values) => Some(int(values[row_index])),
+            AnySlice::I64(values) => Some(int(values[row_index])),
+            AnySlice::U8(values) => Some(int(values[row_index])),
+            AnySlice::U16(values) => Some(int(values[row_index])),
+            AnySlice::U32(values) => Some(int(values[row_index])),
+            AnySlice::U64(values) => Some(int(values[row_index])),
+            AnySlice::Bit(values) => Some(int(values[row_index].0)),
+            AnySlice::NullableDate(values) => nullable_at(values, row_index).map(CellValue::Date),
+            AnySlice::NullableTime(values) => nullable_at(values, row_index).map(CellValue::Time),
+            AnySlice::NullableTimestamp(values) => {
+                nullable_at(values, row_index).map(CellValue::Timestamp)
+            }
+            AnySlice::NullableF64(values) => nullable_at(values, row_index).map(CellValue::Float),
+            AnySlice::NullableF32(values) => {
+                nullable_at(values, row_index).map(|value| CellValue::Float(value.into()))
+            }
+            AnySlice::NullableI8(values) => nullable_at(values, row_index).map(int),
+            AnySlice::NullableI16(values) => nullable_at(values, row_index).map(int),
+            AnySlice::NullableI32(values) => nullable_at(values, row_index).map(int),
+            AnySlice::NullableI64(values) => nullable_at(values, row_index).map(int),
+            AnySlice::NullableU8(values) => nullable_at(values, row_index).map(int),
+            AnySlice::NullableU16(values) => nullable_at(values, row_index).map(int),
+            AnySlice::NullableU32(values) => nullable_at(values, row_index).map(int),
+            AnySlice::NullableU64(values) => nullable_at(values, row_index).map(int),
+            AnySlice::NullableBit(values) => nullable_at(values, row_index).map(|bit| int(bit.0)),
+        };
+        Ok(value)
+    }
+}
+
+/// Decides whether fetched values are equal, following the semantics of SQL rather than the ones of
+/// the Rust types holding them. Rows are slices of cells, with `None` representing `NULL`.
+///
+/// * Integers and floating point numbers are compared by their numeric value, optionally with a
+///   tolerance. `NaN` equals `NaN`.
+/// * Narrow and wide text are compared by their characters, optionally ignoring trailing spaces.
+/// * Values of different kinds, e.g. text and numbers, are never equal.
+///
+/// ```
+/// use odbc_api::buffers::{CellValue, RowEq};
+///
+/// let row_eq = RowEq::new().with_trailing_spaces_ignored(true);
+/// let a = [Some(CellValue::Integer(1)), Some(CellValue::Text(b"ab  ")), None];
+/// let b = [Some(CellValue::Float(1.0)), Some(CellValue::Text(b"ab")), None];
+///
+/// assert!(row_eq.rows_eq(&a, &b));
+/// assert!(!row_eq.with_nulls_equal(false).rows_eq(&a, &b));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RowEq {
+    nulls_equal: bool,
+    trailing_spaces_ignored: bool,
+    numeric_tolerance: f64,
+}
+
+impl Default for RowEq {
+    fn default() -> Self {
+        Self {
+            nulls_equal: true,
+            trailing_spaces_ignored: false,
+            numeric_tolerance: 0.0,
+        }
+    }
+}
+
+impl RowEq {
+    /// `NULL` equals `NULL`, like `IS NOT DISTINCT FROM`, `DISTINCT` or `GROUP BY` treat it. Text is
+    /// compared exactly and numbers without tolerance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// If `false`, `NULL` is not equal to anything, not even to `NULL`, like with the `=` operator.
+    pub fn with_nulls_equal(mut self, nulls_equal: bool) -> Self {
+        self.nulls_equal = nulls_equal;
+        self
+    }
+
+    /// If `true`, trailing spaces are ignored comparing text. This is how `CHAR` columns are
+    /// compared by most data sources, which pad their values with spaces.
+    pub fn with_trailing_spaces_ignored(mut self, trailing_spaces_ignored: bool) -> Self {
+        self.trailing_spaces_ignored = trailing_spaces_ignored;
+        self
+    }
+
+    /// Numbers are considered equal if they differ by at most `tolerance`. Useful to compare
+    /// floating point values, which went through different conversions on their way.
+    pub fn with_numeric_tolerance(mut self, tolerance: f64) -> Self {
+        self.numeric_tolerance = tolerance;
+        self
+    }
+
+    /// `true` if `a` and `b` are equal. `None` represents `NULL`.
+    pub fn values_eq(&self, a: Option<CellValue<'_>>, b: Option<CellValue<'_>>) -> bool {
+        match (a, b) {
+            (None, None) => self.nulls_equal,
+            (Some(a), Some(b)) => {
+                if self.numeric_tolerance != 0.0 {
+                    if let (Some(a), Some(b)) = (a.as_f64(), b.as_f64()) {
+                        return (a - b).abs() <= self.numeric_tolerance
+                            || (a.is_nan() && b.is_nan());
+                    }
+                }
+                compare(a, b, self.trailing_spaces_ignored) == Some(Ordering::Equal)
+            }
+            _ => false,
+        }
+    }
+
+    /// `true` if both rows have the same number of cells and all of them are equal.
+    pub fn rows_eq(&self, a: &[Option<CellValue<'_>>], b: &[Option<CellValue<'_>>]) -> bool {
+        a.len() == b.len() && a.iter().zip(b).all(|(&a, &b)| self.values_eq(a, b))
+    }
+
+    /// Feeds `value` into `hasher`, so that values equal according to [`Self::values_eq`] yield the
+    /// same hash. The numeric tolerance can not be taken into account, so numbers within the
+    /// tolerance of each other may still hash differently.
+    pub fn hash_value(&self, value: Option<CellValue<'_>>, hasher: &mut impl Hasher) {
+        let value = match value {
+            Some(value) => value,
+            None => return 0u8.hash(hasher),
+        };
+        match value {
+            CellValue::Integer(_) | CellValue::Float(_) => {
+                1u8.hash(hasher);
+                match value {
+                    CellValue::Integer(value) => value.hash(hasher),
+                    // Integral values hash like the integer of the same value, so `1.0` and `1`
+                    // yield the same hash. This also covers `-0.0`.
+                    CellValue::Float(value) if value.fract() == 0.0 && value.abs() < 1e38 => {
+                        (value as i128).hash(hasher)
+                    }
+                    CellValue::Float(value) if value.is_nan() => f64::NAN.to_bits().hash(hasher),
+                    CellValue::Float(value) => value.to_bits().hash(hasher),
+                    _ => unreachable!(),
+                }
+            }
+            CellValue::Text(text) => {
+                2u8.hash(hasher);
+                hasher.write(self.trim(text, b' '));
+            }
+            CellValue::WideText(text) => {
+                // Hash the UTF-8 representation, so it matches the hash of equal narrow text.
+                2u8.hash(hasher);
+                let text = String::from_utf16_lossy(self.trim(text, 0x20));
+                hasher.write(text.as_bytes());
+            }
+            CellValue::Binary(bytes) => {
+                3u8.hash(hasher);
+                hasher.write(bytes);
+            }
+            CellValue::Date(date) => (4u8, date).hash(hasher),
+            CellValue::Time(time) => (5u8, time).hash(hasher),
+            CellValue::Timestamp(timestamp) => (6u8, timestamp).hash(hasher),
+        }
+    }
+
+    /// Feeds all cells of `row` into `hasher`. See [`Self::hash_value`].
+    pub fn hash_row(&self, row: &[Option<CellValue<'_>>], hasher: &mut impl Hasher) {
+        for &value in row {
+            self.hash_value(value, hasher);
+        }
+    }
+
+    fn trim<'t, C: PartialEq>(&self, text: &'t [C], space: C) -> &'t [C] {
+        if self.trailing_spaces_ignored {
+            trim_end(text, space)
+        } else {
+            text
+        }
+    }
+}
+
+/// Orders fetched values, following the semantics of SQL rather than the ones of the Rust types
+/// holding them. Values are compared like by [`RowEq`]. Rows are ordered lexicographically.
+///
+/// * `NULL` is ordered after all other values, unless [`Self::with_nulls_first`] is used.
+/// * `NaN` is ordered after all other numbers.
+/// * Values of different kinds, e.g. text and numbers, are ordered by their kind: numbers, text,
+///   binary, dates, times, timestamps.
+///
+/// ```
+/// use std::cmp::Ordering;
+/// use odbc_api::buffers::{CellValue, RowOrd};
+///
+/// let row_ord = RowOrd::new();
+///
+/// assert_eq!(
+///     Ordering::Less,
+///     row_ord.cmp_values(Some(CellValue::Integer(2)), Some(CellValue::Float(2.5)))
+/// );
+/// assert_eq!(Ordering::Greater, row_ord.cmp_values(None, Some(CellValue::Integer(2))));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RowOrd {
+    nulls_first: bool,
+    trailing_spaces_ignored: bool,
+}
+
+impl RowOrd {
+    /// `NULL` is ordered last and trailing spaces are relevant.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// If `true`, `NULL` is ordered before all other values, like `NULLS FIRST` does.
+    pub fn with_nulls_first(mut self, nulls_first: bool) -> Self {
+        self.nulls_first = nulls_first;
+        self
+    }
+
+    /// If `true`, trailing spaces are ignored comparing text. See
+    /// [`RowEq::with_trailing_spaces_ignored`].
+    pub fn with_trailing_spaces_ignored(mut self, trailing_spaces_ignored: bool) -> Self {
+        self.trailing_spaces_ignored = trailing_spaces_ignored;
+        self
+    }
+
+    /// Orders `a` relative to `b`. `None` represents `NULL`.
+    pub fn cmp_values(&self, a: Option<CellValue<'_>>, b: Option<CellValue<'_>>) -> Ordering {
+        match (a, b) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) if self.nulls_first => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) if self.nulls_first => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a), Some(b)) => compare(a, b, self.trailing_spaces_ignored)
+                .unwrap_or_else(|| a.kind().cmp(&b.kind())),
+        }
+    }
+
+    /// Orders the rows lexicographically. A row which is a prefix of the other is ordered first.
+    pub fn cmp_rows(&self, a: &[Option<CellValue<'_>>], b: &[Option<CellValue<'_>>]) -> Ordering {
+        a.iter()
+            .zip(b)
+            .map(|(&a, &b)| self.cmp_values(a, b))
+            .find(|&ordering| ordering != Ordering::Equal)
+            .unwrap_or_else(|| a.len().cmp(&b.len()))
+    }
+}
+
+impl CellValue<'_> {
+    /// Copies the value, so it outlives the buffer it has been read from.
+    pub fn into_owned(self) -> OwnedCellValue {
+        match self {
+            CellValue::Integer(value) => OwnedCellValue::Integer(value),
+            CellValue::Float(value) => OwnedCellValue::Float(value),
+            CellValue::Text(value) => OwnedCellValue::Text(value.to_vec()),
+            CellValue::WideText(value) => OwnedCellValue::WideText(value.to_vec()),
+            CellValue::Binary(value) => OwnedCellValue::Binary(value.to_vec()),
+            CellValue::Date(value) => OwnedCellValue::Date(value),
+            CellValue::Time(value) => OwnedCellValue::Time(value),
+            CellValue::Timestamp(value) => OwnedCellValue::Timestamp(value),
+        }
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        match *self {
+            CellValue::Integer(value) => Some(value as f64),
+            CellValue::Float(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Rank of the kind of value, used to order values of different kinds.
+    fn kind(&self) -> u8 {
+        match self {
+            CellValue::Integer(_) | CellValue::Float(_) => 0,
+            CellValue::Text(_) | CellValue::WideText(_) => 1,
+            CellValue::Binary(_) => 2,
+            CellValue::Date(_) => 3,
+            CellValue::Time(_) => 4,
+            CellValue::Timestamp(_) => 5,
+        }
+    }
+}
+
+/// `None` if the values are of different kinds and therefore not comparable.
+pub(super) fn compare(
+    a: CellValue<'_>,
+    b: CellValue<'_>,
+    ignore_trailing_spaces: bool,
+) -> Option<Ordering> {
+    let trim_narrow = |text| {
+        if ignore_trailing_spaces {
+            trim_end(text, b' ')
+        } else {
+            text
+        }
+    };
+    let trim_wide = |text| {
+        if ignore_trailing_spaces {
+            trim_end(text, 0x20)
+        } else {
+            text
+        }
+    };
+    let ordering = match (a, b) {
+        (CellValue::Integer(a), CellValue::Integer(b)) => a.cmp(&b),
+        (
+            CellValue::Integer(_) | CellValue::Float(_),
+            CellValue::Integer(_) | CellValue::Float(_),
+        ) => cmp_f64(a.as_f64().unwrap(), b.as_f64().unwrap()),
+        // UTF-8 preserves the order of code points, so narrow text can be compared byte wise.
+        (CellValue::Text(a), CellValue::Text(b)) => trim_narrow(a).cmp(trim_narrow(b)),
+        (CellValue::WideText(a), CellValue::WideText(b)) => {
+            wide_chars(trim_wide(a)).cmp(wide_chars(trim_wide(b)))
+        }
+        (CellValue::Text(a), CellValue::WideText(b)) => String::from_utf8_lossy(trim_narrow(a))
+            .chars()
+            .cmp(wide_chars(trim_wide(b))),
+        (CellValue::WideText(a), CellValue::Text(b)) => {
+            wide_chars(trim_wide(a)).cmp(String::from_utf8_lossy(trim_narrow(b)).chars())
+        }
+        (CellValue::Binary(a), CellValue::Binary(b)) => a.cmp(b),
+        (CellValue::Date(a), CellValue::Date(b)) => {
+            (a.year, a.month, a.day).cmp(&(b.year, b.month, b.day))
+        }
+        (CellValue::Time(a), CellValue::Time(b)) => {
+            (a.hour, a.minute, a.second).cmp(&(b.hour, b.minute, b.second))
+        }
+        (CellValue::Timestamp(a), CellValue::Timestamp(b)) => {
+            timestamp_key(&a).cmp(&timestamp_key(&b))
+        }
+        _ => return None,
+    };
+    Some(ordering)
+}
+
+/// Like `f64::partial_cmp`, but `NaN` equals `NaN` and is greater than any other number.
+fn cmp_f64(a: f64, b: f64) -> Ordering {
+    a.partial_cmp(&b)
+        .unwrap_or_else(|| a.is_nan().cmp(&b.is_nan()))
+}
+
+fn wide_chars(text: &[u16]) -> impl Iterator<Item = char> + '_ {
+    char::decode_utf16(text.iter().copied()).map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+}
+
+fn trim_end<C: PartialEq>(mut text: &[C], space: C) -> &[C] {
+    while let [rest @ .., last] = text {
+        if *last != space {
+            break;
+        }
+        text = rest;
+    }
+    text
+}
+
+fn timestamp_key(ts: &Timestamp) -> (i16, u16, u16, u16, u16, u16, u32) {
+    (
+        ts.year,
+        ts.month,
+        ts.day,
+        ts.hour,
+        ts.minute,
+        ts.second,
+        ts.fraction,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cmp::Ordering, collections::hash_map::DefaultHasher, hash::Hasher};
+
+    use crate::{
+        buffers::{AnyBuffer, BufferDesc, ColumnarAnyBuffer},
+        RowSetBuffer,
+    };
+
+    use super::{CellValue, RowEq, RowOrd};
+
+    fn hash(row_eq: &RowEq, value: Option<CellValue<'_>>) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        row_eq.hash_value(value, &mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn equal_values_hash_equal() {
+        let row_eq = RowEq::new().with_trailing_spaces_ignored(true);
+        let wide: Vec<u16> = "abc ".encode_utf16().collect();
+        let pairs = [
+            (CellValue::Integer(1), CellValue::Float(1.0)),
+            (CellValue::Float(f64::NAN), CellValue::Float(f64::NAN)),
+            (CellValue::Text(b"abc"), CellValue::WideText(&wide)),
+        ];
+
+        for (a, b) in pairs {
+            assert!(row_eq.values_eq(Some(a), Some(b)), "{a:?} != {b:?}");
+            assert_eq!(hash(&row_eq, Some(a)), hash(&row_eq, Some(b)));
+        }
+        assert!(!row_eq.values_eq(Some(CellValue::Text(b"1")), Some(CellValue::Integer(1))));
+    }
+
+    #[test]
+    fn numeric_tolerance() {
+        let row_eq = RowEq::new().with_numeric_tolerance(0.01);
+
+        assert!(row_eq.values_eq(
+            Some(CellValue::Float(0.1 + 0.2)),
+            Some(CellValue::Float(0.3))
+        ));
+        assert!(!row_eq.values_eq(Some(CellValue::Float(0.3)), Some(CellValue::Integer(1))));
+    }
+
+    #[test]
+    fn order_nulls_and_rows() {
+        let row_ord = RowOrd::new();
+        let a = [Some(CellValue::Integer(1)), None];
+        let b = [Some(CellValue::Integer(1)), Some(CellValue::Integer(0))];
+
+        assert_eq!(Ordering::Greater, row_ord.cmp_rows(&a, &b));
+        assert_eq!(
+            Ordering::Less,
+            row_ord.with_nulls_first(true).cmp_rows(&a, &b)
+        );
+        assert_eq!(Ordering::Less, row_ord.cmp_rows(&a[..1], &a));
+    }
+
+    #[test]
+    fn read_cell_values_from_buffer() {
+        let mut buffer = AnyBuffer::from_desc(2, BufferDesc::I32 { nullable: true });
+        if let AnyBuffer::NullableI32(column) = &mut buffer {
+            column.writer_n(2).write([Some(42), None].into_iter());
+        }
+        let mut buffer = ColumnarAnyBuffer::new(vec![(1, buffer)]);
+        *buffer.mut_num_fetch_rows() = 2;
+
+        assert_eq!(Ok(Some(CellValue::Integer(42))), buffer.get(0, 0));
+        assert_eq!(Ok(None::<CellValue>), buffer.get(1, 0));
+    }
+}
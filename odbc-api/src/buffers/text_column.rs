@@ -2,10 +2,10 @@ use crate::{
     columnar_bulk_inserter::BoundInputSlice,
     error::TooLargeBufferSize,
     handles::{CData, CDataMut, HasDataType, Statement, StatementRef},
-    DataType, Error,
+    trim_split_surrogate, DataType, Error,
 };
 
-use super::{ColumnBuffer, Indicator};
+use super::{indicator::check_indicators, try_filled_vec, ColumnBuffer, Indicator};
 
 use log::debug;
 use odbc_sys::{CDataType, NULL_DATA};
@@ -51,21 +51,19 @@ impl<C> TextColumn<C> {
         C: Default + Copy,
     {
         // Element size is +1 to account for terminating zero
-        let element_size = max_str_len + 1;
-        let len = element_size * batch_size;
-        let mut values = Vec::new();
-        values
-            .try_reserve_exact(len)
-            .map_err(|_| TooLargeBufferSize {
-                num_elements: batch_size,
-                // We want the element size in bytes
-                element_size: element_size * size_of::<C>(),
-            })?;
-        values.resize(len, C::default());
+        let element_size = max_str_len.saturating_add(1);
+        let too_large = || TooLargeBufferSize {
+            num_elements: batch_size,
+            // We want the element size in bytes
+            element_size: element_size.saturating_mul(size_of::<C>()),
+        };
+        let len = element_size.checked_mul(batch_size).ok_or_else(too_large)?;
+        let values = try_filled_vec(len, C::default()).map_err(|_| too_large())?;
+        let indicators = try_filled_vec(batch_size, 0).map_err(|_| too_large())?;
         Ok(TextColumn {
             max_str_len,
             values,
-            indicators: vec![0; batch_size],
+            indicators,
         })
     }
 
@@ -104,6 +102,18 @@ impl<C> TextColumn<C> {
         })
     }
 
+    /// Copy of the first `num_rows` elements, with the same maximum string length.
+    pub(crate) fn copy_rows(&self, num_rows: usize) -> Self
+    where
+        C: Clone,
+    {
+        Self {
+            max_str_len: self.max_str_len,
+            values: self.values[..num_rows * (self.max_str_len + 1)].to_vec(),
+            indicators: self.indicators[..num_rows].to_vec(),
+        }
+    }
+
     /// Maximum length of elements
     pub fn max_len(&self) -> usize {
         self.max_str_len
@@ -116,7 +126,7 @@ impl<C> TextColumn<C> {
     /// panic on accessing an undefined element. It will panic however if `row_index` is larger or
     /// equal to the maximum number of elements in the buffer.
     pub fn indicator_at(&self, row_index: usize) -> Indicator {
-        Indicator::from_isize(self.indicators[row_index])
+        Indicator::from_validated(self.indicators[row_index])
     }
 
     /// Length of value at the specified position. This is different from an indicator as it refers
@@ -135,6 +145,60 @@ impl<C> TextColumn<C> {
         }
     }
 
+    /// Transforms the values in the range [0, num_rows) in place. `transform` is called with each
+    /// value and returns its new length, which must not exceed the length of the original value.
+    /// This allows e.g. trimming, masking or normalizing without any extra allocation. `NULL`s and
+    /// truncated values are skipped.
+    ///
+    /// # Panics
+    ///
+    /// If `transform` returns a length exceeding the original one.
+    pub fn transform_values(
+        &mut self,
+        num_rows: usize,
+        mut transform: impl FnMut(&mut [C]) -> usize,
+    ) where
+        C: Default,
+    {
+        for row_index in 0..num_rows {
+            let length = match self.indicator_at(row_index) {
+                Indicator::Length(length_in_bytes)
+                    if length_in_bytes / size_of::<C>() <= self.max_str_len =>
+                {
+                    length_in_bytes / size_of::<C>()
+                }
+                // Null or truncated
+                _ => continue,
+            };
+            let offset = row_index * (self.max_str_len + 1);
+            let new_length = transform(&mut self.values[offset..offset + length]);
+            assert!(
+                new_length <= length,
+                "Transformation must not increase the length of a value."
+            );
+            // Keep values zero terminated
+            self.values[offset + new_length] = C::default();
+            self.indicators[row_index] = (new_length * size_of::<C>()).try_into().unwrap();
+        }
+    }
+
+    /// Removes trailing spaces from all values in the range [0, num_rows). Drivers pad values of
+    /// fixed length character columns (e.g. `CHAR(10)`) with spaces up to their declared length, yet
+    /// usually applications are only interested in the value without the padding.
+    pub fn trim_trailing_spaces(&mut self, num_rows: usize)
+    where
+        C: Default + PartialEq + From<u8>,
+    {
+        let space = C::from(b' ');
+        self.transform_values(num_rows, |value| {
+            value
+                .iter()
+                .rposition(|c| *c != space)
+                .map(|last| last + 1)
+                .unwrap_or(0)
+        })
+    }
+
     /// `true` if any value is truncated in the range [0, num_rows).
     ///
     /// After fetching data we may want to know if any value has been truncated due to the buffer
@@ -146,12 +210,50 @@ impl<C> TextColumn<C> {
             .iter()
             .copied()
             .take(num_rows)
-            .any(|indicator| match Indicator::from_isize(indicator) {
+            .any(|indicator| match Indicator::from_validated(indicator) {
                 Indicator::Null | Indicator::NoTotal => true,
                 Indicator::Length(length_in_bytes) => max_bin_length < length_in_bytes,
             })
     }
 
+    /// Length in bytes of the first value in the range [0, num_rows), which has been truncated.
+    /// `Some(None)` if the driver did not report its length.
+    pub(crate) fn first_truncated_len(&self, num_rows: usize) -> Option<Option<usize>> {
+        let max_bin_length = self.max_str_len * size_of::<C>();
+        self.indicators
+            .iter()
+            .copied()
+            .take(num_rows)
+            .find_map(|indicator| match Indicator::from_validated(indicator) {
+                Indicator::Null => None,
+                Indicator::NoTotal => Some(None),
+                Indicator::Length(length_in_bytes) if max_bin_length < length_in_bytes => {
+                    Some(Some(length_in_bytes))
+                }
+                Indicator::Length(_) => None,
+            })
+    }
+
+    /// Maximum string length required to hold the values in the range [0, num_rows) without
+    /// truncation. `None` if none of them is truncated. For values of unknown length twice the
+    /// current maximum string length is requested.
+    pub(crate) fn required_max_str_len(&self, num_rows: usize) -> Option<usize> {
+        let max_bin_length = self.max_str_len * size_of::<C>();
+        self.indicators
+            .iter()
+            .copied()
+            .take(num_rows)
+            .filter_map(|indicator| match Indicator::from_validated(indicator) {
+                Indicator::Null => None,
+                Indicator::NoTotal => Some((self.max_str_len * 2).max(1)),
+                Indicator::Length(length_in_bytes) if max_bin_length < length_in_bytes => {
+                    Some(length_in_bytes.div_ceil(size_of::<C>()))
+                }
+                Indicator::Length(_) => None,
+            })
+            .max()
+    }
+
     /// Changes the maximum string length the buffer can hold. This operation is useful if you find
     /// an unexpected large input string during insertion.
     ///
@@ -189,7 +291,7 @@ impl<C> TextColumn<C> {
             .zip(new_values.chunks_exact_mut(new_max_str_len + 1))
             .take(num_rows)
         {
-            match Indicator::from_isize(indicator) {
+            match Indicator::from_validated(indicator) {
                 Indicator::Null => (),
                 Indicator::NoTotal => {
                     // There is no good choice here in case we are expanding the buffer. Since
@@ -309,6 +411,25 @@ impl WCharColumn {
     pub unsafe fn ustr_at(&self, row_index: usize) -> Option<&U16Str> {
         self.value_at(row_index).map(U16Str::from_slice)
     }
+
+    /// Like [`Self::value_at`], but should the value be truncated in the middle of a surrogate
+    /// pair, the first half of the pair is not part of the returned slice. This way truncated
+    /// values can still be decoded without yielding a replacement character at their end.
+    pub fn value_at_char_boundary(&self, row_index: usize) -> Option<&[u16]> {
+        let max_len_in_bytes = self.max_str_len * 2;
+        let is_truncated = match self.indicator_at(row_index) {
+            Indicator::NoTotal => true,
+            Indicator::Length(length) => length > max_len_in_bytes,
+            Indicator::Null => false,
+        };
+        self.value_at(row_index).map(|value| {
+            if is_truncated {
+                trim_split_surrogate(value)
+            } else {
+                value
+            }
+        })
+    }
 }
 
 unsafe impl<C: 'static> ColumnBuffer for TextColumn<C>
@@ -339,11 +460,24 @@ where
             .iter()
             .copied()
             .take(num_rows)
-            .any(|indicator| match Indicator::from_isize(indicator) {
+            .any(|indicator| match Indicator::from_validated(indicator) {
                 Indicator::Null | Indicator::NoTotal => true,
                 Indicator::Length(length_in_bytes) => max_bin_length < length_in_bytes,
             })
     }
+
+    fn check_indicators(&self, num_rows: usize) -> Result<(), Error> {
+        check_indicators(&self.indicators[..num_rows.min(self.indicators.len())])
+    }
+
+    fn payload_bytes(&self, num_rows: usize) -> usize {
+        let max_bin_length = self.max_str_len * size_of::<C>();
+        self.indicators
+            .iter()
+            .take(num_rows)
+            .map(|&indicator| Indicator::from_validated(indicator).length_in_buffer(max_bin_length))
+            .sum()
+    }
 }
 
 /// Allows read only access to the valid part of a text column.
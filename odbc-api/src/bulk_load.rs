@@ -0,0 +1,211 @@
+use std::fmt::Write;
+
+use crate::{query_builder::quote_identifier, Connection, Error, IntoParameter};
+
+/// How [`Connection::bulk_load`] sends the rows to the data source.
+///
+/// # No `COPY`
+///
+/// None of the strategies uses PostgreSQL's `COPY ... FROM STDIN`. ODBC offers no way to stream
+/// copy data to the server, and `psqlODBC` rejects the statement. [`Self::RecordSet`] is used
+/// instead, which like `COPY` takes one statement execution per batch, rather than one per row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulkLoadStrategy {
+    /// PostgreSQL only. Each batch is sent as a single JSON document, which is expanded into rows
+    /// by the server using `json_populate_recordset`, converting values to the types of the
+    /// columns.
+    RecordSet,
+    /// Any other data source. Rows are bound as arrays of text parameters to a prepared `INSERT`
+    /// statement, `batch_size` rows at a time.
+    ArrayInsert,
+}
+
+/// Outcome of [`Connection::bulk_load`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BulkLoadReport {
+    /// Strategy chosen for the data source.
+    pub strategy: BulkLoadStrategy,
+    /// Number of rows inserted.
+    pub num_rows: usize,
+    /// Number of statement executions.
+    pub num_batches: usize,
+}
+
+/// Initial length of the text buffers of [`BulkLoadStrategy::ArrayInsert`]. Buffers grow if larger
+/// values are appended.
+const INITIAL_MAX_STR_LEN: usize = 256;
+
+/// Inserts `rows` into `columns` of `table`. See [`Connection::bulk_load`].
+pub(crate) fn bulk_load<'v, R>(
+    connection: &Connection<'_>,
+    table: &str,
+    columns: &[&str],
+    rows: impl IntoIterator<Item = R>,
+    batch_size: usize,
+) -> Result<BulkLoadReport, Error>
+where
+    R: IntoIterator<Item = Option<&'v str>>,
+{
+    if columns.is_empty() {
+        return Err(Error::InvalidArgument {
+            description: "at least one column is required",
+        });
+    }
+    if batch_size == 0 {
+        return Err(Error::InvalidArgument {
+            description: "batch size must be at least one",
+        });
+    }
+    let quote = connection.identifier_quote_char()?;
+    let strategy = if connection.database_management_system_name()? == "PostgreSQL" {
+        BulkLoadStrategy::RecordSet
+    } else {
+        BulkLoadStrategy::ArrayInsert
+    };
+    let table = quote_identifier(table, &quote)?;
+    let column_list = columns
+        .iter()
+        .map(|column| quote_identifier(column, &quote))
+        .collect::<Result<Vec<_>, _>>()?;
+    let column_list = column_list.join(", ");
+    let mut report = BulkLoadReport {
+        strategy,
+        num_rows: 0,
+        num_batches: 0,
+    };
+    match strategy {
+        BulkLoadStrategy::RecordSet => {
+            let sql = format!(
+                "INSERT INTO {table} ({column_list}) SELECT {column_list} \
+                FROM json_populate_recordset(NULL::{table}, ?::json)"
+            );
+            let mut prepared = connection.prepare(&sql)?;
+            let mut document = String::new();
+            let mut values = Vec::with_capacity(columns.len());
+            let mut num_pending = 0;
+            let mut rows = rows.into_iter().peekable();
+            while let Some(row) = rows.next() {
+                collect_row(row, columns.len(), &mut values)?;
+                document.push(if num_pending == 0 { '[' } else { ',' });
+                write_json_object(&mut document, columns, &values);
+                num_pending += 1;
+                if num_pending == batch_size || rows.peek().is_none() {
+                    document.push(']');
+                    prepared.execute(&document.as_str().into_parameter())?;
+                    report.num_rows += num_pending;
+                    report.num_batches += 1;
+                    document.clear();
+                    num_pending = 0;
+                }
+            }
+        }
+        BulkLoadStrategy::ArrayInsert => {
+            let placeholders = vec!["?"; columns.len()].join(", ");
+            let sql = format!("INSERT INTO {table} ({column_list}) VALUES ({placeholders})");
+            let mut inserter = connection
+                .prepare(&sql)?
+                .into_text_inserter(batch_size, vec![INITIAL_MAX_STR_LEN; columns.len()])?;
+            let mut values = Vec::with_capacity(columns.len());
+            for row in rows {
+                collect_row(row, columns.len(), &mut values)?;
+                inserter.append(values.iter().map(|value| value.map(str::as_bytes)))?;
+                if inserter.num_rows() == inserter.capacity() {
+                    inserter.execute()?;
+                    report.num_rows += inserter.num_rows();
+                    report.num_batches += 1;
+                    inserter.clear();
+                }
+            }
+            if inserter.num_rows() != 0 {
+                inserter.execute()?;
+                report.num_rows += inserter.num_rows();
+                report.num_batches += 1;
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Replaces `values` with the values of `row`. Fails with [`Error::InvalidArgument`] if their
+/// number differs from `num_columns`.
+fn collect_row<'v>(
+    row: impl IntoIterator<Item = Option<&'v str>>,
+    num_columns: usize,
+    values: &mut Vec<Option<&'v str>>,
+) -> Result<(), Error> {
+    values.clear();
+    values.extend(row);
+    if values.len() != num_columns {
+        return Err(Error::InvalidArgument {
+            description: "number of values in each row must match the number of columns",
+        });
+    }
+    Ok(())
+}
+
+/// Appends a row as a JSON object with the names of `columns` as keys and `values` as strings.
+fn write_json_object(out: &mut String, columns: &[&str], values: &[Option<&str>]) {
+    out.push('{');
+    for (index, (column, value)) in columns.iter().zip(values).enumerate() {
+        if index != 0 {
+            out.push(',');
+        }
+        write_json_string(out, column);
+        out.push(':');
+        match value {
+            Some(text) => write_json_string(out, text),
+            None => out.push_str("null"),
+        }
+    }
+    out.push('}');
+}
+
+fn write_json_string(out: &mut String, text: &str) {
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c < ' ' => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Error;
+
+    use super::{collect_row, write_json_object};
+
+    #[test]
+    fn rows_as_json_objects() {
+        let mut out = String::new();
+
+        write_json_object(
+            &mut out,
+            &["id", "name"],
+            &[Some("1"), Some("Say \"Hi\"\\\n\u{1}")],
+        );
+        write_json_object(&mut out, &["id", "name"], &[Some("2"), None]);
+
+        assert_eq!(
+            r#"{"id":"1","name":"Say \"Hi\"\\\n\u0001"}{"id":"2","name":null}"#,
+            out
+        );
+    }
+
+    #[test]
+    fn rows_must_match_columns() {
+        let mut values = Vec::new();
+
+        let result = collect_row([Some("1")], 2, &mut values);
+
+        assert!(matches!(result, Err(Error::InvalidArgument { .. })));
+        assert!(collect_row([Some("1"), None], 2, &mut values).is_ok());
+    }
+}
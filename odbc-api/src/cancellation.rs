@@ -0,0 +1,144 @@
+use std::{collections::HashMap, hash::Hash, sync::Mutex};
+
+use odbc_sys::HStmt;
+
+use crate::{
+    handles::{AsStatementRef, Statement, StatementRef},
+    Error,
+};
+
+/// Statements currently executing, registered under an id chosen by the application, so they can
+/// be cancelled from any thread. E.g. a query tool may register each query under the id of the
+/// user session which submitted it, and cancel it if the user clicks a "kill query" button.
+///
+/// Statements are registered for the duration of [`Self::run`] only and deregistered
+/// automatically once it returns. The registry is [`Sync`], so it can be shared between the
+/// threads executing statements and the ones cancelling them, e.g. using an
+/// [`std::sync::Arc`].
+///
+/// ```no_run
+/// use odbc_api::{CancellationRegistry, Connection, Cursor, Error};
+///
+/// fn count_large_orders(
+///     conn: &Connection<'_>,
+///     registry: &CancellationRegistry<u64>,
+///     session_id: u64,
+/// ) -> Result<usize, Error> {
+///     let mut statement = conn.preallocate()?;
+///     // Another thread may call `registry.cancel(&session_id)` while this is running.
+///     registry.run(session_id, &mut statement, |statement| {
+///         let mut num_rows = 0;
+///         if let Some(mut cursor) =
+///             statement.execute("SELECT id FROM Orders WHERE total > 1000", ())?
+///         {
+///             while cursor.next_row()?.is_some() {
+///                 num_rows += 1;
+///             }
+///         }
+///         Ok(num_rows)
+///     })?
+/// }
+/// ```
+pub struct CancellationRegistry<K> {
+    /// Statement handles stored as addresses, so the registry can be shared between threads.
+    statements: Mutex<HashMap<K, usize>>,
+}
+
+impl<K> CancellationRegistry<K>
+where
+    K: Eq + Hash,
+{
+    /// A registry without any statements.
+    pub fn new() -> Self {
+        Self {
+            statements: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Registers `statement` under `id` and invokes `f` with it. Once `f` returns, or panics, the
+    /// statement is deregistered again. Fails with [`Error::QueryIdInUse`] without invoking `f`, if
+    /// another statement is currently registered under `id`.
+    pub fn run<S, R>(
+        &self,
+        id: K,
+        statement: &mut S,
+        f: impl FnOnce(&mut S) -> R,
+    ) -> Result<R, Error>
+    where
+        S: AsStatementRef,
+    {
+        let handle = statement.as_stmt_ref().as_sys();
+        let _registration = Registration::new(self, id, handle)?;
+        // `statement` is borrowed until the registration has been dropped, so it can not be freed
+        // while it is registered.
+        Ok(f(statement))
+    }
+
+    /// Cancels the statement registered under `id` using `SQLCancelHandle`. `false` if no statement
+    /// is registered under `id`, e.g. because it already completed. The thread executing the
+    /// statement usually observes the cancellation as an error with SQLSTATE `HY008` (operation
+    /// canceled).
+    pub fn cancel(&self, id: &K) -> Result<bool, Error> {
+        // The lock is held while cancelling, so the statement can not be deregistered and freed in
+        // the meantime.
+        let statements = self.statements.lock().unwrap();
+        let handle = match statements.get(id) {
+            Some(&handle) => handle,
+            None => return Ok(false),
+        };
+        let stmt = unsafe { StatementRef::new(handle as HStmt) };
+        stmt.cancel().into_result(&stmt)?;
+        Ok(true)
+    }
+
+    /// `true` if a statement is currently registered under `id`.
+    pub fn is_running(&self, id: &K) -> bool {
+        self.statements.lock().unwrap().contains_key(id)
+    }
+
+    /// Number of statements currently registered.
+    pub fn num_running(&self) -> usize {
+        self.statements.lock().unwrap().len()
+    }
+}
+
+impl<K> Default for CancellationRegistry<K>
+where
+    K: Eq + Hash,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Removes a statement from the registry once dropped.
+struct Registration<'r, K> {
+    registry: &'r CancellationRegistry<K>,
+    handle: usize,
+}
+
+impl<'r, K> Registration<'r, K>
+where
+    K: Eq + Hash,
+{
+    fn new(registry: &'r CancellationRegistry<K>, id: K, handle: HStmt) -> Result<Self, Error> {
+        let handle = handle as usize;
+        let mut statements = registry.statements.lock().unwrap();
+        if statements.contains_key(&id) {
+            return Err(Error::QueryIdInUse);
+        }
+        statements.insert(id, handle);
+        Ok(Self { registry, handle })
+    }
+}
+
+impl<'r, K> Drop for Registration<'r, K> {
+    fn drop(&mut self) {
+        // Identify the entry by its handle. This way the id does not need to be cloned.
+        self.registry
+            .statements
+            .lock()
+            .unwrap()
+            .retain(|_, &mut handle| handle != self.handle);
+    }
+}
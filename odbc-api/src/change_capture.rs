@@ -0,0 +1,354 @@
+use std::{cmp::min, collections::VecDeque, thread, time::Duration};
+
+use crate::{
+    buffers::TextRowSet, result_set_metadata::utf8_display_sizes, Connection, Cursor, Error,
+    IncrementalExtraction, ResultSetMetadata, Select, WatermarkStore,
+};
+
+/// Whether a row captured by [`ChangeCapture`] has been inserted or updated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// The creation timestamp of the row equals its modification timestamp.
+    Inserted,
+    /// The creation timestamp of the row differs from its modification timestamp.
+    Updated,
+    /// Inserted or updated. No creation column has been configured with
+    /// [`ChangeCapture::with_created_column`] to tell them apart.
+    Changed,
+}
+
+/// A row inserted or updated since the previous poll of a [`ChangeCapture`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangedRow {
+    /// Whether the row has been inserted or updated.
+    pub kind: ChangeKind,
+    /// Value of the watermark column in its text representation.
+    pub watermark: String,
+    /// Values of the selected columns in their text representation, `None` for `NULL`. In the
+    /// order of [`ChangeCapture::column_names`].
+    pub values: Vec<Option<String>>,
+}
+
+/// Captures rows inserted or updated in a table by polling it, for data sources without access to
+/// log based change data capture. Relies on audit columns maintained by the application or
+/// triggers: a watermark column, which increases with every change of a row, like an `updated_at`
+/// timestamp or a table wide `version` (e.g. `rowversion` of Microsoft SQL Server), and optionally
+/// a `created_at` column, to tell inserts apart from updates.
+///
+/// The watermark is kept and persisted using an [`IncrementalExtraction`], so every poll fetches
+/// only the rows changed since the last one. Deleted rows can not be captured this way.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use odbc_api::{ChangeCapture, Connection, Error, FileWatermarkStore};
+///
+/// fn follow_orders(conn: &Connection<'_>) -> Result<(), Error> {
+///     let store = FileWatermarkStore::new("watermarks");
+///     let mut capture = ChangeCapture::new("orders", "Orders", "updated_at", store)?
+///         .with_columns(["id", "status"])
+///         .with_created_column("created_at")
+///         .with_interval(Duration::from_secs(1), Duration::from_secs(60));
+///     for row in capture.changes(conn) {
+///         let row = row?;
+///         println!("{:?} {:?}", row.kind, row.values);
+///     }
+///     Ok(())
+/// }
+/// ```
+///
+/// Watermarks are compared using `>`. If several rows share the same watermark, e.g. timestamps
+/// with a coarse resolution, rows committed while a poll is running may be missed, if they have
+/// the same watermark as the last row of that poll. Prefer watermarks which are unique and
+/// assigned in commit order.
+#[derive(Debug)]
+pub struct ChangeCapture<S> {
+    extraction: IncrementalExtraction<S>,
+    table: String,
+    columns: Vec<String>,
+    watermark_column: String,
+    created_column: Option<String>,
+    column_names: Vec<String>,
+    backoff: PollBackoff,
+    batch_size: usize,
+    max_str_len: Option<usize>,
+}
+
+impl<S> ChangeCapture<S>
+where
+    S: WatermarkStore,
+{
+    /// Captures changes of `table`, using `watermark_column` to detect them. The watermark of the
+    /// previous run is loaded from `store` for `key`. If none has been saved yet, every row of the
+    /// table is captured by the first poll.
+    ///
+    /// By default all columns are selected. Polls start every second, backing off up to a
+    /// minute while no changes are found.
+    pub fn new(
+        key: impl Into<String>,
+        table: impl Into<String>,
+        watermark_column: impl Into<String>,
+        store: S,
+    ) -> Result<Self, Error> {
+        let watermark_column = watermark_column.into();
+        let extraction = IncrementalExtraction::new(key, watermark_column.clone(), store)?;
+        Ok(Self {
+            extraction,
+            table: table.into(),
+            columns: Vec::new(),
+            watermark_column,
+            created_column: None,
+            column_names: Vec::new(),
+            backoff: PollBackoff::new(Duration::from_secs(1), Duration::from_secs(60)),
+            batch_size: 1000,
+            max_str_len: Some(4096),
+        })
+    }
+
+    /// Selects only these columns, instead of all. The watermark and creation columns are
+    /// appended, unless listed.
+    pub fn with_columns<C: Into<String>>(mut self, columns: impl IntoIterator<Item = C>) -> Self {
+        self.columns = columns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Column holding the creation timestamp of a row. Rows whose creation timestamp equals their
+    /// watermark are reported as [`ChangeKind::Inserted`], others as [`ChangeKind::Updated`]. This
+    /// matches the convention of setting `updated_at` to `created_at` on insert.
+    pub fn with_created_column(mut self, created_column: impl Into<String>) -> Self {
+        self.created_column = Some(created_column.into());
+        self
+    }
+
+    /// Delay between polls of [`Self::changes`]. Starts with `min` and doubles after every poll
+    /// which found no changes or failed, up to `max`. Once changes are found, the next poll starts
+    /// right away, to catch up.
+    pub fn with_interval(mut self, min: Duration, max: Duration) -> Self {
+        self.backoff = PollBackoff::new(min, max);
+        self
+    }
+
+    /// Number of rows fetched at once.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Upper bound for the length of fetched values in bytes. The size reported by the driver is
+    /// used if it is smaller. `None` to always use the size reported by the driver, which fails for
+    /// columns without an upper bound, like `VARCHAR(max)`. Longer values are not truncated, but
+    /// cause [`Self::poll`] to fail with [`Error::TooLargeValueForBuffer`]. The bound does not
+    /// apply to the watermark and creation columns, unless the driver does not report their size.
+    pub fn with_max_str_len(mut self, max_str_len: Option<usize>) -> Self {
+        self.max_str_len = max_str_len;
+        self
+    }
+
+    /// Names of the columns of [`ChangedRow::values`], as reported by the data source. Empty
+    /// before the first poll.
+    pub fn column_names(&self) -> &[String] {
+        &self.column_names
+    }
+
+    /// Watermark of the last captured row, or the one loaded from the store, if no row has been
+    /// captured yet.
+    pub fn high_water_mark(&self) -> Option<&str> {
+        self.extraction.high_water_mark()
+    }
+
+    /// Fetches the rows changed since the committed watermark, ordered by their watermark. The
+    /// watermark of the last row becomes the new high-water mark, but is neither persisted nor
+    /// used to restrict the next poll before [`Self::commit`]. Polling again without committing
+    /// fetches the same rows again, plus any changed in the meantime.
+    pub fn poll(&mut self, connection: &Connection<'_>) -> Result<Vec<ChangedRow>, Error> {
+        let mut select = Select::from(self.table.clone());
+        if !self.columns.is_empty() {
+            let audit_columns = Some(&self.watermark_column).into_iter();
+            let missing = audit_columns
+                .chain(self.created_column.as_ref())
+                .filter(|audit| !self.columns.iter().any(|column| column == *audit))
+                .cloned();
+            select = select.columns(self.columns.iter().cloned().chain(missing));
+        }
+        let Some(mut cursor) = self.extraction.execute(connection, select)? else {
+            return Ok(Vec::new());
+        };
+        self.column_names = cursor.column_names()?.collect::<Result<_, _>>()?;
+        let watermark_index = self.position_of(&self.watermark_column)?;
+        let created_index = self
+            .created_column
+            .as_deref()
+            .map(|created| self.position_of(created))
+            .transpose()?;
+        // Watermark and creation columns are sized from the metadata alone, since `max_str_len`
+        // must not cut them short.
+        let max_str_lens = utf8_display_sizes(&mut cursor)?
+            .enumerate()
+            .map(|(index, reported_len)| {
+                let reported_len = reported_len?;
+                let is_audit_column = index == watermark_index || Some(index) == created_index;
+                Ok(match self.max_str_len {
+                    Some(upper_bound) if reported_len == 0 => upper_bound,
+                    Some(upper_bound) if !is_audit_column => min(reported_len, upper_bound),
+                    _ => reported_len,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let buffer = TextRowSet::from_max_str_lens(self.batch_size, max_str_lens)?;
+        let mut row_set_cursor = cursor.bind_buffer(buffer)?;
+        let mut rows = Vec::new();
+        while let Some(batch) = row_set_cursor.fetch_with_truncation_check(true)? {
+            for row_index in 0..batch.num_rows() {
+                let values: Vec<_> = (0..batch.num_cols())
+                    .map(|col_index| {
+                        batch
+                            .at(col_index, row_index)
+                            .map(|value| String::from_utf8_lossy(value).into_owned())
+                    })
+                    .collect();
+                let watermark = values[watermark_index].clone().unwrap_or_default();
+                let kind = match created_index {
+                    None => ChangeKind::Changed,
+                    Some(index) if values[index].as_ref() == Some(&watermark) => {
+                        ChangeKind::Inserted
+                    }
+                    Some(_) => ChangeKind::Updated,
+                };
+                self.extraction.observe(&watermark);
+                rows.push(ChangedRow {
+                    kind,
+                    watermark,
+                    values,
+                });
+            }
+        }
+        Ok(rows)
+    }
+
+    /// Persists the high-water mark, so the next run continues after the rows captured so far.
+    /// Call this only after the captured rows are processed, otherwise they are lost if the
+    /// application fails in between. [`Self::changes`] does this for you.
+    pub fn commit(&mut self) -> Result<(), Error> {
+        self.extraction.commit()
+    }
+
+    /// Polls for changes, yielding the changed rows one by one. Blocks the thread while waiting
+    /// for the next poll. The iterator never ends, so break out of the loop to stop capturing.
+    ///
+    /// The watermark of a poll is committed once all of its rows have been consumed and the next
+    /// row is requested. So every row is delivered at least once: if the application fails while
+    /// processing a poll, its rows are captured again by the next run. Errors are yielded, too.
+    /// Calling `next` again after an error retries after the backoff delay.
+    pub fn changes<'a>(&'a mut self, connection: &'a Connection<'a>) -> Changes<'a, S> {
+        Changes {
+            capture: self,
+            connection,
+            pending: VecDeque::new(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// Releases the store, e.g. to reuse it for another capture.
+    pub fn into_store(self) -> S {
+        self.extraction.into_store()
+    }
+
+    /// Index of `column` within the result set. Names are compared case insensitive, since some
+    /// data sources report them in upper case.
+    fn position_of(&self, column: &str) -> Result<usize, Error> {
+        self.column_names
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(column))
+            .ok_or_else(|| Error::MissingAuditColumn {
+                name: column.to_owned(),
+            })
+    }
+}
+
+/// Rows changed in a table, polled by [`ChangeCapture::changes`].
+pub struct Changes<'a, S> {
+    capture: &'a mut ChangeCapture<S>,
+    connection: &'a Connection<'a>,
+    /// Rows of the last poll, not yet yielded.
+    pending: VecDeque<ChangedRow>,
+    /// Time to wait before the next poll.
+    delay: Duration,
+}
+
+impl<S> Iterator for Changes<'_, S>
+where
+    S: WatermarkStore,
+{
+    type Item = Result<ChangedRow, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(row) = self.pending.pop_front() {
+                return Some(Ok(row));
+            }
+            // All rows of the previous poll have been consumed.
+            if let Err(error) = self.capture.commit() {
+                return Some(Err(error));
+            }
+            thread::sleep(self.delay);
+            match self.capture.poll(self.connection) {
+                Ok(rows) => {
+                    self.delay = self.capture.backoff.after_poll(!rows.is_empty());
+                    self.pending = rows.into();
+                }
+                Err(error) => {
+                    self.delay = self.capture.backoff.after_poll(false);
+                    return Some(Err(error));
+                }
+            }
+        }
+    }
+}
+
+/// Delay between polls, doubling while nothing is found.
+#[derive(Debug, Clone, Copy)]
+struct PollBackoff {
+    min: Duration,
+    max: Duration,
+    current: Duration,
+}
+
+impl PollBackoff {
+    fn new(min: Duration, max: Duration) -> Self {
+        Self {
+            min,
+            max,
+            current: min,
+        }
+    }
+
+    /// Time to wait after a poll, which found changes if `found` is `true`.
+    fn after_poll(&mut self, found: bool) -> Duration {
+        if found {
+            self.current = self.min;
+            Duration::ZERO
+        } else {
+            let delay = self.current;
+            self.current = self.current.saturating_mul(2).min(self.max).max(self.min);
+            delay
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::PollBackoff;
+
+    #[test]
+    fn back_off_while_nothing_changes() {
+        let mut backoff = PollBackoff::new(Duration::from_secs(1), Duration::from_secs(3));
+
+        let delays: Vec<_> = [false, false, false, true, false]
+            .into_iter()
+            .map(|found| backoff.after_poll(found).as_secs())
+            .collect();
+
+        assert_eq!(vec![1, 2, 3, 0, 1], delays);
+    }
+}
@@ -0,0 +1,118 @@
+use crate::{escape_attribute_value, vendor::snowflake::query_tag_statement, Connection, Error};
+
+/// Identifies the application (and optionally the host) a connection is opened by, so database
+/// administrators can attribute load to services. How the identity is transported differs between
+/// database systems. Some only accept it as part of the connection string during login, others
+/// allow setting it on a session which is already open.
+///
+/// ```no_run
+/// use odbc_api::{ClientIdentity, Environment};
+///
+/// let env = Environment::new()?;
+/// let identity = ClientIdentity::new("invoice-service").with_workstation_id("worker-7");
+///
+/// // SQL Server accepts the identity as part of the connection string only.
+/// let connection_string = format!(
+///     "Driver={{ODBC Driver 17 for SQL Server}};Server=localhost;UID=SA;PWD=secret;{}",
+///     identity.mssql_connection_string_attributes()
+/// );
+/// let conn = env.connect_with_connection_string(&connection_string)?;
+///
+/// // Other systems, like PostgreSQL, allow us to set it after connecting.
+/// let conn = env.connect_with_connection_string("DSN=MyPostgres;")?;
+/// identity.apply_to(&conn)?;
+/// # Ok::<(), odbc_api::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientIdentity {
+    application_name: String,
+    workstation_id: Option<String>,
+}
+
+impl ClientIdentity {
+    /// Identity consisting of the application name only.
+    pub fn new(application_name: impl Into<String>) -> Self {
+        Self {
+            application_name: application_name.into(),
+            workstation_id: None,
+        }
+    }
+
+    /// Adds the name of the host the application runs on.
+    pub fn with_workstation_id(mut self, workstation_id: impl Into<String>) -> Self {
+        self.workstation_id = Some(workstation_id.into());
+        self
+    }
+
+    /// Name of the application.
+    pub fn application_name(&self) -> &str {
+        &self.application_name
+    }
+
+    /// Name of the host the application runs on, if any.
+    pub fn workstation_id(&self) -> Option<&str> {
+        self.workstation_id.as_deref()
+    }
+
+    /// Keywords for the connection string of the Microsoft ODBC Driver for SQL Server. The
+    /// application name is reported as `program_name` and the workstation id as `host_name` in
+    /// `sys.dm_exec_sessions`.
+    ///
+    /// ```
+    /// use odbc_api::ClientIdentity;
+    ///
+    /// let identity = ClientIdentity::new("importer").with_workstation_id("host;1");
+    /// assert_eq!(
+    ///     "APP=importer;WSID={host;1};",
+    ///     identity.mssql_connection_string_attributes()
+    /// );
+    /// ```
+    pub fn mssql_connection_string_attributes(&self) -> String {
+        let mut attributes = format!("APP={};", escape_attribute_value(&self.application_name));
+        if let Some(wsid) = &self.workstation_id {
+            attributes.push_str(&format!("WSID={};", escape_attribute_value(wsid)));
+        }
+        attributes
+    }
+
+    /// Keywords for the connection string of the PostgreSQL ODBC driver, setting the
+    /// `application_name` reported in `pg_stat_activity`. The workstation id is not transported.
+    ///
+    /// ```
+    /// use odbc_api::ClientIdentity;
+    ///
+    /// let identity = ClientIdentity::new("importer");
+    /// assert_eq!(
+    ///     "pqopt={application_name=importer};",
+    ///     identity.postgres_connection_string_attributes()
+    /// );
+    /// ```
+    pub fn postgres_connection_string_attributes(&self) -> String {
+        format!(
+            "pqopt={{application_name={}}};",
+            self.application_name.replace('}', "}}")
+        )
+    }
+
+    /// Stamps the identity on a session which is already open, dispatching on the name of the
+    /// database management system.
+    ///
+    /// * PostgreSQL: Sets `application_name`.
+    /// * Snowflake: Sets the `QUERY_TAG` session parameter to the application name.
+    ///
+    /// Returns `false` if the database management system does not support changing the identity
+    /// of an open session, e.g. Microsoft SQL Server. Use the connection string attributes in that
+    /// case.
+    pub fn apply_to(&self, connection: &Connection<'_>) -> Result<bool, Error> {
+        let statement = match connection.database_management_system_name()?.as_str() {
+            "PostgreSQL" => format!(
+                "SET application_name = '{}'",
+                self.application_name.replace('\'', "''")
+            ),
+            "Snowflake" => query_tag_statement(&self.application_name),
+            _ => return Ok(false),
+        };
+        connection.execute(&statement, ())?;
+        Ok(true)
+    }
+}
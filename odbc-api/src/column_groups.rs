@@ -0,0 +1,184 @@
+use crate::{
+    buffers::{AnySlice, CellAccessError, ColumnarAnyBuffer, FromCell},
+    handles::AsStatementRef,
+    BlockCursor, Cursor, Error,
+};
+
+/// Presents the result set of a block cursor as several logical result sets over disjoint groups
+/// of its columns. Each batch is fetched only once and shared by all groups. This is useful to write
+/// one wide result set into multiple sinks with different schemas, without executing the query once
+/// per sink.
+///
+/// ```
+/// use odbc_api::{
+///     buffers::{BufferDesc, ColumnarAnyBuffer}, ColumnGroups, Cursor, Error,
+/// };
+///
+/// fn split(cursor: impl Cursor) -> Result<(), Error> {
+///     // Columns: id, name, amount
+///     let descs = [
+///         BufferDesc::I64 { nullable: false },
+///         BufferDesc::Text { max_str_len: 255 },
+///         BufferDesc::F64 { nullable: true },
+///     ];
+///     let buffer = ColumnarAnyBuffer::from_descs(1000, descs);
+///     let block_cursor = cursor.bind_buffer(buffer)?;
+///     // One sink receives id and name, the other one the amount.
+///     let mut groups = ColumnGroups::new(block_cursor, vec![vec![0, 1], vec![2]]);
+///     while let Some(batch) = groups.fetch()? {
+///         let (customers, amounts) = (batch.group(0), batch.group(1));
+///         // ... write each group into its own sink ...
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct ColumnGroups<C: AsStatementRef> {
+    block_cursor: BlockCursor<C, ColumnarAnyBuffer>,
+    /// Buffer indices of the columns in each group.
+    groups: Vec<Vec<usize>>,
+}
+
+impl<C> ColumnGroups<C>
+where
+    C: Cursor,
+{
+    /// Splits the columns of the buffer bound to `block_cursor` into `groups`. Each group lists the
+    /// buffer indices of its columns, in the order they are presented within the group. Columns
+    /// which are not part of any group are fetched, but not presented.
+    ///
+    /// # Panics
+    ///
+    /// If a buffer index is out of bounds, or a column is part of more than one group.
+    pub fn new(block_cursor: BlockCursor<C, ColumnarAnyBuffer>, groups: Vec<Vec<usize>>) -> Self {
+        let num_cols = block_cursor.buffer().num_cols();
+        let mut assigned = vec![false; num_cols];
+        for &buffer_index in groups.iter().flatten() {
+            assert!(
+                buffer_index < num_cols,
+                "Buffer index {buffer_index} of column group is out of bounds. The buffer has \
+                {num_cols} columns."
+            );
+            assert!(
+                !assigned[buffer_index],
+                "Column groups must be disjoint, yet buffer index {buffer_index} is part of more \
+                than one group."
+            );
+            assigned[buffer_index] = true;
+        }
+        Self {
+            block_cursor,
+            groups,
+        }
+    }
+
+    /// Number of column groups.
+    pub fn num_groups(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Fills the bound buffer with the next row set. Fails with [`Error::TooLargeValueForBuffer`]
+    /// if a value has been truncated.
+    ///
+    /// # Return
+    ///
+    /// `None` if the result set is empty and all row sets have been extracted. `Some` with a view
+    /// of the batch, which can be split into its column groups, otherwise.
+    pub fn fetch(&mut self) -> Result<Option<GroupedBatch<'_>>, Error> {
+        let groups = &self.groups;
+        let batch = self
+            .block_cursor
+            .fetch_with_truncation_check(true)?
+            .map(|batch| GroupedBatch { batch, groups });
+        Ok(batch)
+    }
+
+    /// Unbinds the buffer and gives back ownership of the cursor and the buffer.
+    pub fn unbind(self) -> Result<(C, ColumnarAnyBuffer), Error> {
+        self.block_cursor.unbind()
+    }
+}
+
+/// A batch fetched by [`ColumnGroups`].
+#[derive(Clone, Copy)]
+pub struct GroupedBatch<'a> {
+    batch: &'a ColumnarAnyBuffer,
+    groups: &'a [Vec<usize>],
+}
+
+impl<'a> GroupedBatch<'a> {
+    /// Number of rows in the batch, which is the same for every group.
+    pub fn num_rows(&self) -> usize {
+        self.batch.num_rows()
+    }
+
+    /// The columns of the group at `group_index`, in the order passed to [`ColumnGroups::new`].
+    ///
+    /// # Panics
+    ///
+    /// If `group_index` is out of bounds.
+    pub fn group(&self, group_index: usize) -> ColumnGroupView<'a> {
+        ColumnGroupView {
+            batch: self.batch,
+            buffer_indices: &self.groups[group_index],
+        }
+    }
+
+    /// Views of all column groups.
+    pub fn groups(&self) -> impl Iterator<Item = ColumnGroupView<'a>> + 'a {
+        let batch = self.batch;
+        self.groups
+            .iter()
+            .map(move |buffer_indices| ColumnGroupView {
+                batch,
+                buffer_indices,
+            })
+    }
+}
+
+/// Rows of a batch restricted to the columns of one group. Columns are indexed relative to the
+/// group.
+#[derive(Clone, Copy)]
+pub struct ColumnGroupView<'a> {
+    batch: &'a ColumnarAnyBuffer,
+    buffer_indices: &'a [usize],
+}
+
+impl<'a> ColumnGroupView<'a> {
+    /// Number of rows in the batch.
+    pub fn num_rows(&self) -> usize {
+        self.batch.num_rows()
+    }
+
+    /// Number of columns in the group.
+    pub fn num_cols(&self) -> usize {
+        self.buffer_indices.len()
+    }
+
+    /// Buffer indices of the columns in the group, within the buffer bound to the cursor.
+    pub fn buffer_indices(&self) -> &'a [usize] {
+        self.buffer_indices
+    }
+
+    /// View of the column at `col_index` within the group.
+    ///
+    /// # Panics
+    ///
+    /// If `col_index` is out of bounds.
+    pub fn column(&self, col_index: usize) -> AnySlice<'a> {
+        self.batch.column(self.buffer_indices[col_index])
+    }
+
+    /// Value of the cell at `row_index` in the column at `col_index` within the group. See
+    /// [`AnySlice::get`].
+    ///
+    /// # Panics
+    ///
+    /// If `row_index` or `col_index` is out of bounds.
+    pub fn get<T: FromCell<'a>>(
+        &self,
+        row_index: usize,
+        col_index: usize,
+    ) -> Result<T, CellAccessError> {
+        self.column(col_index).get(row_index)
+    }
+}
@@ -2,6 +2,7 @@ use crate::{
     buffers::{ColumnBuffer, TextColumn},
     execute::execute,
     handles::{AsStatementRef, HasDataType, Statement, StatementRef},
+    result_set_metadata::reported,
     CursorImpl, Error,
 };
 
@@ -193,6 +194,19 @@ where
     pub fn capacity(&self) -> usize {
         self.capacity
     }
+
+    /// Number of rows affected by the last execution. May return `None` if row count is not
+    /// available.
+    pub fn row_count(&mut self) -> Result<Option<usize>, Error> {
+        let stmt = self.statement.as_stmt_ref();
+        let count = stmt.row_count().into_result(&stmt)?;
+        // ODBC returns -1 in case a row count is not available
+        if count == -1 {
+            Ok(None)
+        } else {
+            reported(count, "row count").map(Some)
+        }
+    }
 }
 
 /// You can obtain a mutable slice of a column buffer which allows you to change its contents.
@@ -1,18 +1,89 @@
 use crate::{
     buffers::BufferDesc,
+    bulk_load,
+    environment::ConnectionCountGuard,
     execute::{
         execute_columns, execute_tables, execute_with_parameters, execute_with_parameters_polling,
     },
-    handles::{self, slice_to_utf8, SqlText, State, Statement, StatementImpl},
+    handles::{
+        self, slice_to_utf8, sql_text_len, AsHandle, AuditRegistration, AuditedCall, CallAuditLog,
+        CallHookRegistration, ContextRegistration, ErrorContext, SqlText, State, Statement,
+        StatementImpl, StatementRegistration,
+    },
+    keyed_dml,
+    parameter::InputParameter,
+    query_builder::quote_identifier,
+    query_plan,
+    result_set_metadata::reported_text,
+    script,
+    serialized_connection::{AsyncMode, ThreadingCapability},
     statement_connection::StatementConnection,
-    CursorImpl, CursorPolling, Error, ParameterCollectionRef, Preallocated, Prepared, Sleep,
+    text_lengths, BulkLoadReport, ConnectionEvent, ConnectionObserver, Cursor, CursorImpl,
+    CursorPolling, Error, ExecuteManyReport, InListCursor, KeyRetrieval, Latency, Pages,
+    ParameterCollectionRef, ParameterRow, Preallocated, Prepared, QueryPlan, Quirks, ScriptReport,
+    Sleep, StatementOptions, TextLengths,
+};
+use odbc_sys::{HDbc, HStmt, SqlDataType};
+use std::{
+    borrow::Cow,
+    cell::OnceCell,
+    mem::ManuallyDrop,
+    str,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
 };
-use odbc_sys::HDbc;
-use std::{borrow::Cow, mem::ManuallyDrop, str, thread::panicking};
 
 impl<'conn> Drop for Connection<'conn> {
     fn drop(&mut self) {
-        match self.connection.disconnect().into_result(&self.connection) {
+        // Disconnecting frees statements still alive, so the environment must not cancel them.
+        self.unregister_statements();
+        let disposal = self.disposal;
+        if disposal == Disposal::Disconnect {
+            self.disconnect_on_drop();
+        }
+        if disposal != Disposal::Transfer {
+            self.notify(ConnectionEvent::Closed);
+        }
+        // Registrations are keyed by the handle, so they must end before it is freed.
+        self.audit = None;
+        self.context.take();
+        self.call_hook = None;
+        if matches!(disposal, Disposal::Disconnect | Disposal::Free) {
+            // Safety: The handle is not used after this.
+            unsafe { ManuallyDrop::drop(&mut self.connection) }
+        }
+    }
+}
+
+/// What the drop handler of a [`Connection`] does with the connection handle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Disposal {
+    /// Disconnect, rolling back an open transaction if required, and free the handle.
+    Disconnect,
+    /// Free the handle, which has already been disconnected.
+    Free,
+    /// Neither disconnect nor free the handle, because disconnecting failed.
+    Leak,
+    /// Like [`Self::Leak`], but ownership of the handle has been transferred to the application, so
+    /// the connection is not reported as closed either.
+    Transfer,
+}
+
+impl<'conn> Connection<'conn> {
+    /// Disconnects, rolling back an open transaction if required. Errors are logged, since there is
+    /// no one to return them to.
+    fn disconnect_on_drop(&mut self) {
+        let num_active = self.active_statement_count();
+        if num_active != 0 && self.statements.leak_warnings.load(Ordering::Relaxed) {
+            log::warn!(
+                "Connection dropped while {num_active} statement(s) allocated from it are still \
+                alive. Disconnecting frees them implicitly."
+            );
+        }
+        match self.connection.disconnect().into_result(&*self.connection) {
             Ok(()) => (),
             Err(Error::Diagnostics {
                 record,
@@ -20,31 +91,17 @@ impl<'conn> Drop for Connection<'conn> {
             }) if record.state == State::INVALID_STATE_TRANSACTION => {
                 // Invalid transaction state. Let's rollback the current transaction and try again.
                 if let Err(e) = self.rollback() {
-                    // Avoid panicking, if we already have a panic. We don't want to mask the original
-                    // error.
-                    if !panicking() {
-                        panic!(
-                            "Unexpected error rolling back transaction (In order to recover \
-                                from invalid transaction state during disconnect): {e:?}"
-                        )
-                    }
+                    log::error!(
+                        "Unexpected error rolling back transaction (In order to recover from \
+                        invalid transaction state during disconnect): {e}"
+                    )
                 }
                 // Transaction is rolled back. Now let's try again to disconnect.
-                if let Err(e) = self.connection.disconnect().into_result(&self.connection) {
-                    // Avoid panicking, if we already have a panic. We don't want to mask the original
-                    // error.
-                    if !panicking() {
-                        panic!("Unexpected error disconnecting): {e:?}")
-                    }
-                }
-            }
-            Err(e) => {
-                // Avoid panicking, if we already have a panic. We don't want to mask the original
-                // error.
-                if !panicking() {
-                    panic!("Unexpected error disconnecting: {e:?}")
+                if let Err(e) = self.connection.disconnect().into_result(&*self.connection) {
+                    log::error!("Unexpected error disconnecting: {e}")
                 }
             }
+            Err(e) => log::error!("Unexpected error disconnecting: {e}"),
         }
     }
 }
@@ -55,18 +112,183 @@ impl<'conn> Drop for Connection<'conn> {
 /// If you want to enable the connection pooling support build into the ODBC driver manager have a
 /// look at [`crate::Environment::set_connection_pooling`].
 pub struct Connection<'c> {
-    connection: handles::Connection<'c>,
+    /// Freed explicitly, unless disconnecting failed.
+    connection: ManuallyDrop<handles::Connection<'c>>,
+    /// Notified about lifecycle events of this connection.
+    observer: Option<Arc<dyn ConnectionObserver>>,
+    /// Counts this connection as alive at the environment it has been opened with.
+    count_guard: Option<ConnectionCountGuard>,
+    statements: StatementAccounting,
+    /// Most recent calls made on this connection and its statements. See
+    /// [`Self::enable_call_audit`].
+    audit: Option<(Arc<CallAuditLog>, AuditRegistration)>,
+    /// Known bugs of the driver, detected on first use. See [`Self::quirks`].
+    quirks: OnceCell<Quirks>,
+    /// Queried on first use. See [`Self::max_statement_len`].
+    max_statement_len: OnceCell<Option<usize>>,
+    /// Key value pairs attached to errors, created on first use. See [`Self::set_context`].
+    context: OnceCell<(Arc<ErrorContext>, ContextRegistration)>,
+    /// Hook of the environment, registered for this connection and its statements. See
+    /// [`crate::Environment::set_call_hook`].
+    call_hook: Option<CallHookRegistration>,
+    /// Encoding of narrow text exchanged with the driver. See [`Self::set_narrow_encoding`].
+    #[cfg(feature = "encoding")]
+    narrow_encoding: &'static crate::encoding::Encoding,
+    /// What happens to the handle once dropped. Set by methods consuming the connection.
+    disposal: Disposal,
+}
+
+/// Bookkeeping of the statements allocated from a connection.
+#[derive(Debug, Default)]
+struct StatementAccounting {
+    /// Statements currently alive. Shared with the statements, which decrement it when dropped.
+    active: Arc<AtomicUsize>,
+    /// Statements allocated over the lifetime of the connection.
+    allocated: AtomicUsize,
+    /// Log a warning, if statements are still alive when the connection is dropped.
+    leak_warnings: AtomicBool,
 }
 
 impl<'c> Connection<'c> {
-    pub(crate) fn new(connection: handles::Connection<'c>) -> Self {
-        Self { connection }
+    /// Wraps a freshly opened connection and notifies `observer` about it.
+    pub(crate) fn with_observer(
+        connection: handles::Connection<'c>,
+        observer: Option<Arc<dyn ConnectionObserver>>,
+        count_guard: ConnectionCountGuard,
+        call_hook: Option<CallHookRegistration>,
+    ) -> Self {
+        let connection = Self {
+            connection: ManuallyDrop::new(connection),
+            observer,
+            count_guard: Some(count_guard),
+            statements: StatementAccounting::default(),
+            audit: None,
+            quirks: OnceCell::new(),
+            max_statement_len: OnceCell::new(),
+            context: OnceCell::new(),
+            call_hook,
+            #[cfg(feature = "encoding")]
+            narrow_encoding: encoding_rs::UTF_8,
+            disposal: Disposal::Disconnect,
+        };
+        connection.notify(ConnectionEvent::Opened);
+        connection
+    }
+
+    /// Registers an observer notified about subsequent [`ConnectionEvent`]s of this connection,
+    /// replacing the one inherited from the environment (see
+    /// [`crate::Environment::set_connection_observer`]). Pass `None` to remove it.
+    pub fn set_observer(&mut self, observer: Option<Arc<dyn ConnectionObserver>>) {
+        self.observer = observer;
+    }
+
+    /// Records the most recent `capacity` calls to the ODBC C API made on this connection and the
+    /// statements subsequently allocated from it. The recorded calls are appended to the message
+    /// of [`Error::Diagnostics`] returned for calls on the connection or its statements, and can be
+    /// inspected using [`Self::call_history`]. Knowing the preceding calls helps to diagnose errors
+    /// like function sequence errors (`HY010`). Calling this again discards the recorded calls.
+    ///
+    /// ```no_run
+    /// use odbc_api::{Environment, Error};
+    ///
+    /// let env = Environment::new()?;
+    /// let mut conn = env.connect_with_connection_string("DSN=sales;")?;
+    /// conn.enable_call_audit(32);
+    /// if let Err(error) = conn.execute("SELECT * FROM Orders", ()) {
+    ///     // Message includes the calls preceding the error.
+    ///     eprintln!("{error}");
+    /// }
+    /// # Ok::<(), Error>(())
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `capacity` is zero.
+    pub fn enable_call_audit(&mut self, capacity: usize) {
+        assert!(
+            capacity > 0,
+            "Capacity of the call audit must be at least one."
+        );
+        // Drop the old registration first, so the handle is not registered twice.
+        self.audit = None;
+        let log = Arc::new(CallAuditLog::new(capacity));
+        let registration = AuditRegistration::new(self.connection.as_handle(), &log);
+        self.audit = Some((log, registration));
+    }
+
+    /// Calls recorded since [`Self::enable_call_audit`], oldest first. Empty if the call audit is
+    /// not enabled.
+    pub fn call_history(&self) -> Vec<AuditedCall> {
+        self.audit
+            .as_ref()
+            .map(|(log, _)| log.calls())
+            .unwrap_or_default()
+    }
+
+    /// Attaches a key value pair, e.g. a tenant or job id, to errors originating from this
+    /// connection and its statements. The pairs are part of the [`crate::handles::Record`] of
+    /// [`Error::Diagnostics`], appended to its message, and to warnings logged for diagnostics.
+    /// This allows correlating driver errors with business context, without wrapping every call
+    /// site. Setting a key again replaces its value.
+    ///
+    /// The context applies to statements allocated after the first call, including changes made to
+    /// it later on. Statements allocated before only carry entries of their own. Statements may add
+    /// entries of their own, e.g. with [`crate::Prepared::set_context`]. Contexts are only tracked
+    /// once set, so connections and statements without one do not pay for this feature.
+    ///
+    /// ```no_run
+    /// use odbc_api::{Environment, Error};
+    ///
+    /// let env = Environment::new()?;
+    /// let conn = env.connect_with_connection_string("DSN=sales;")?;
+    /// conn.set_context("tenant", "acme");
+    /// conn.set_context("job", "nightly-import");
+    /// if let Err(error) = conn.execute("SELECT * FROM Orders", ()) {
+    ///     // Message ends with "Context: tenant=acme, job=nightly-import"
+    ///     eprintln!("{error}");
+    /// }
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn set_context(&self, key: &str, value: &str) {
+        self.error_context().set(key, value)
+    }
+
+    /// Removes `key` from the context set with [`Self::set_context`].
+    pub fn remove_context(&self, key: &str) {
+        if let Some((context, _)) = self.context.get() {
+            context.remove(key)
+        }
+    }
+
+    /// Key value pairs set with [`Self::set_context`], in the order the keys have first been set.
+    pub fn context(&self) -> Vec<(String, String)> {
+        self.context
+            .get()
+            .map(|(context, _)| context.entries())
+            .unwrap_or_default()
+    }
+
+    fn error_context(&self) -> &Arc<ErrorContext> {
+        let (context, _) = self.context.get_or_init(|| {
+            let context = Arc::new(ErrorContext::default());
+            let registration = ContextRegistration::new(self.connection.as_handle(), &context);
+            (context, registration)
+        });
+        context
+    }
+
+    fn notify(&self, event: ConnectionEvent) {
+        if let Some(observer) = &self.observer {
+            observer.on_event(event)
+        }
     }
 
     /// Transfers ownership of the handle to this open connection to the raw ODBC pointer.
-    pub fn into_sys(self) -> HDbc {
-        // We do not want to run the drop handler, but transfer ownership instead.
-        ManuallyDrop::new(self).connection.as_sys()
+    pub fn into_sys(mut self) -> HDbc {
+        // The drop handler neither disconnects nor frees the handle, but transfers ownership.
+        self.unregister_statements();
+        self.disposal = Disposal::Transfer;
+        self.connection.as_sys()
     }
 
     /// Transfer ownership of this open connection to a wrapper around the raw ODBC pointer. The
@@ -77,7 +299,15 @@ impl<'c> Connection<'c> {
     /// but, in case it is not, this may help you to break out of the type structure which might be
     /// to rigid for you, while simultaniously abondoning its safeguards.
     pub fn into_handle(self) -> handles::Connection<'c> {
-        unsafe { handles::Connection::new(ManuallyDrop::new(self).connection.as_sys()) }
+        unsafe { handles::Connection::new(self.into_sys()) }
+    }
+
+    /// Access the raw connection handle, in order to call ODBC functions not covered by this crate,
+    /// without giving up ownership of the connection like [`Self::into_handle`] would. The guard
+    /// borrows the connection, so it can not be dropped while the raw handle is in use. See
+    /// [`handles::RawConnectionGuard`].
+    pub fn raw_handle_guard(&self) -> handles::RawConnectionGuard<'_> {
+        unsafe { handles::RawConnectionGuard::new(self.connection.as_sys()) }
     }
 
     /// Executes an SQL statement. This is the fastest way to submit an SQL statement for one-time
@@ -119,6 +349,393 @@ impl<'c> Connection<'c> {
         execute_with_parameters(lazy_statement, Some(&query), params)
     }
 
+    /// Executes the `queries` one after another and passes the cursors of those returning a result
+    /// set to `process`, together with the index of the query. If the driver supports more than
+    /// one active statement per connection, the next query is executed before the cursor of the
+    /// current one is processed. This way the data source is already busy with the next query,
+    /// while the application drains the current cursor, saving a round trip per query. This is
+    /// useful e.g. for dashboards issuing many small queries. At most two statements are active at
+    /// the same time.
+    ///
+    /// Should the driver support only one active statement (see
+    /// [`Self::max_concurrent_activities`]), the queries are executed serially. For Microsoft SQL
+    /// Server enable "Multiple Active Result Sets" using `MARS_Connection=Yes` in the connection
+    /// string, in order to benefit from pipelining.
+    ///
+    /// Since the next query is executed before the results of the current one are processed, the
+    /// queries should not depend on side effects of each other. Execution stops at the first error,
+    /// be it returned executing a query or by `process`.
+    ///
+    /// ```no_run
+    /// use odbc_api::{buffers::TextRowSet, Connection, Cursor, Error};
+    ///
+    /// fn print_all(conn: &Connection<'_>, queries: &[&str]) -> Result<(), Error> {
+    ///     conn.execute_pipelined(queries.iter().copied(), |index, mut cursor| {
+    ///         println!("Results of query {index}:");
+    ///         let buffer = TextRowSet::for_cursor(100, &mut cursor, Some(4096))?;
+    ///         let mut block_cursor = cursor.bind_buffer(buffer)?;
+    ///         while let Some(batch) = block_cursor.fetch()? {
+    ///             for row_index in 0..batch.num_rows() {
+    ///                 let first = batch.at_as_str(0, row_index).unwrap().unwrap_or("NULL");
+    ///                 println!("{first}");
+    ///             }
+    ///         }
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub fn execute_pipelined<'q>(
+        &self,
+        queries: impl IntoIterator<Item = &'q str>,
+        mut process: impl FnMut(usize, CursorImpl<StatementImpl<'_>>) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        let is_pipelining = self.max_concurrent_activities()? != 1;
+        // Cursor of the previous query, which has not been processed yet.
+        let mut pending = None;
+        for (index, query) in queries.into_iter().enumerate() {
+            if !is_pipelining {
+                if let Some((pending_index, cursor)) = pending.take() {
+                    process(pending_index, cursor)?;
+                }
+            }
+            let cursor = self.execute(query, ())?;
+            if let Some((pending_index, cursor)) = pending.take() {
+                process(pending_index, cursor)?;
+            }
+            pending = cursor.map(|cursor| (index, cursor));
+        }
+        if let Some((pending_index, cursor)) = pending {
+            process(pending_index, cursor)?;
+        }
+        Ok(())
+    }
+
+    /// Executes the statements of `script`, separated by semicolons, one after another. Semicolons
+    /// within literals, quoted identifiers and comments do not separate statements. Sending each
+    /// statement on its own keeps long scripts within [`Self::max_statement_len`] and works with
+    /// drivers which do not support batches of statements. Result sets are discarded. Execution
+    /// stops at the first error, statements executed before stay executed.
+    ///
+    /// Statements must not contain semicolons outside of literals, which rules out e.g. the bodies
+    /// of stored procedures written in T-SQL. Dollar quoted bodies of PostgreSQL are fine.
+    ///
+    /// ```no_run
+    /// use odbc_api::{Connection, Error};
+    ///
+    /// fn migrate(conn: &Connection<'_>) -> Result<(), Error> {
+    ///     let script = "CREATE TABLE Movies (id INTEGER, title VARCHAR(255));\n\
+    ///         INSERT INTO Movies VALUES (1, 'Jaws'), (2, 'Alien');";
+    ///     let report = conn.execute_script(script)?;
+    ///     println!("{} statements, {} rows", report.num_statements, report.rows_affected);
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// Fails with [`Error::StatementTooLong`] if a single statement exceeds
+    /// [`Self::max_statement_len`].
+    pub fn execute_script(&self, script: &str) -> Result<ScriptReport, Error> {
+        script::execute_script(self, script)
+    }
+
+    /// Executes an `INSERT` statement and returns the keys generated by the data source for the
+    /// inserted rows as a cursor, e.g. the values of an auto incremented `id` column. The mechanism
+    /// used to retrieve the keys is chosen based on the name of the database management system.
+    /// See [`KeyRetrieval`] for which systems are supported and the caveats of each mechanism.
+    ///
+    /// ```no_run
+    /// use odbc_api::{Connection, Cursor, Error, IntoParameter};
+    ///
+    /// fn insert_movie(conn: &Connection<'_>, title: &str) -> Result<Option<i64>, Error> {
+    ///     let cursor = conn.execute_returning_keys(
+    ///         "INSERT INTO Movies (title) VALUES (?)",
+    ///         &title.into_parameter(),
+    ///         &["id"],
+    ///     )?;
+    ///     let mut id = None;
+    ///     if let Some(mut cursor) = cursor {
+    ///         if let Some(mut row) = cursor.next_row()? {
+    ///             let mut value = 0i64;
+    ///             row.get_data(1, &mut value)?;
+    ///             id = Some(value);
+    ///         }
+    ///     }
+    ///     Ok(id)
+    /// }
+    /// ```
+    pub fn execute_returning_keys(
+        &self,
+        insert: &str,
+        params: impl ParameterCollectionRef,
+        key_columns: &[&str],
+    ) -> Result<Option<CursorImpl<StatementImpl<'_>>>, Error> {
+        let dbms_name = self.database_management_system_name()?;
+        let retrieval = KeyRetrieval::for_dbms_name(&dbms_name)
+            .ok_or(Error::UnsupportedKeyRetrieval { dbms_name })?;
+        self.execute_returning_keys_using(retrieval, insert, params, key_columns)
+    }
+
+    /// Like [`Self::execute_returning_keys`], but with an explicitly chosen mechanism to retrieve
+    /// the generated keys.
+    pub fn execute_returning_keys_using(
+        &self,
+        retrieval: KeyRetrieval,
+        insert: &str,
+        params: impl ParameterCollectionRef,
+        key_columns: &[&str],
+    ) -> Result<Option<CursorImpl<StatementImpl<'_>>>, Error> {
+        if let Some(query) = retrieval.rewrite(insert, key_columns) {
+            self.execute(&query, params)
+        } else {
+            self.execute(insert, params)?;
+            self.execute("SELECT LAST_INSERT_ID()", ())
+        }
+    }
+
+    /// Splits the result set of `query` into pages of up to `page_size` rows, using keyset
+    /// pagination. Rows are sorted by `key_columns`, which must identify a row uniquely and must not
+    /// contain `NULL`. `query` is used as a subquery and must therefore not contain an `ORDER BY`
+    /// clause. See [`Pages`] for details.
+    ///
+    /// ```no_run
+    /// use odbc_api::{Connection, Cursor, Error};
+    ///
+    /// fn print_in_pages(conn: &Connection<'_>) -> Result<(), Error> {
+    ///     for page in conn.paginate("SELECT id, title FROM Movies", &["id"], 1000)? {
+    ///         let mut page = page?;
+    ///         let mut title = Vec::new();
+    ///         while let Some(mut row) = page.next_row()? {
+    ///             row.get_text(2, &mut title)?;
+    ///             println!("{}", String::from_utf8_lossy(&title));
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// Fails with [`Error::InvalidArgument`] if `key_columns` is empty or `page_size` is zero.
+    pub fn paginate(
+        &self,
+        query: &str,
+        key_columns: &[&str],
+        page_size: usize,
+    ) -> Result<Pages<'_>, Error> {
+        Pages::new(self, query, key_columns, page_size)
+    }
+
+    /// Executes `query` with a list of values of variable length. The marker `?...` in `query` is
+    /// expanded into one placeholder per value, e.g. `WHERE id IN (?...)` into
+    /// `WHERE id IN (?, ?, ?)`. The values are bound as parameters, so they can not alter the
+    /// structure of the statement. Lists longer than `chunk_size` are sent in several executions,
+    /// whose rows are returned by a single cursor. An empty list executes no statement at all.
+    /// Clauses like `ORDER BY` apply to each chunk separately. See [`InListCursor`] for details.
+    ///
+    /// ```no_run
+    /// use odbc_api::{Connection, Cursor, Error};
+    ///
+    /// fn print_titles(conn: &Connection<'_>, ids: &[i32]) -> Result<(), Error> {
+    ///     let query = "SELECT title FROM Movies WHERE id IN (?...)";
+    ///     let mut cursor = conn.execute_in_list(query, ids, 2000)?;
+    ///     let mut title = Vec::new();
+    ///     while let Some(mut row) = cursor.next_row()? {
+    ///         row.get_text(1, &mut title)?;
+    ///         println!("{}", String::from_utf8_lossy(&title));
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// Fails with [`Error::InvalidArgument`] if `query` does not contain the marker `?...` exactly
+    /// once outside of literals and comments, or `chunk_size` is zero.
+    pub fn execute_in_list<'v, T>(
+        &self,
+        query: &str,
+        values: &'v [T],
+        chunk_size: usize,
+    ) -> Result<InListCursor<'_, 'v, T>, Error>
+    where
+        T: InputParameter,
+    {
+        InListCursor::new(self, query, values, chunk_size)
+    }
+
+    /// Deletes the rows of `table` whose `key_columns` match one of `keys`. Each key is a tuple
+    /// with one value per key column. The statement is prepared once and executed with array bound
+    /// parameters in batches of `batch_size` keys, so neither one roundtrip per key nor the limits
+    /// of `IN` lists apply. Identifiers are quoted. See [`crate::Prepared::execute_many`] for how
+    /// errors of individual batches are reported.
+    ///
+    /// ```no_run
+    /// use odbc_api::{Connection, Error};
+    ///
+    /// fn delete_order_lines(conn: &Connection<'_>, lines: &[(i32, i16)]) -> Result<(), Error> {
+    ///     let keys = lines.iter().copied();
+    ///     let report = conn.delete_by_keys("OrderLines", &["order_id", "line"], keys, 1000)?;
+    ///     println!("Deleted {} order lines", report.rows_affected);
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// Fails with [`Error::InvalidArgument`] if `key_columns` is empty, the number of values in a
+    /// key differs from the number of key columns, or `batch_size` is zero. `table` may be
+    /// qualified with a schema, e.g. `dbo.OrderLines`, whose parts are quoted separately.
+    pub fn delete_by_keys<R>(
+        &self,
+        table: &str,
+        key_columns: &[&str],
+        keys: impl IntoIterator<Item = R>,
+        batch_size: usize,
+    ) -> Result<ExecuteManyReport, Error>
+    where
+        R: ParameterRow,
+    {
+        keyed_dml::delete_by_keys(self, table, key_columns, keys, batch_size)
+    }
+
+    /// Updates `set_columns` of the rows of `table` identified by `key_columns`. Each of `rows` is
+    /// a tuple holding the new values of `set_columns`, followed by the values of the key columns.
+    /// Executed in batches of `batch_size` rows, like [`Self::delete_by_keys`].
+    ///
+    /// ```no_run
+    /// use odbc_api::{Connection, Error};
+    ///
+    /// fn archive_orders(conn: &Connection<'_>, ids: &[i32]) -> Result<usize, Error> {
+    ///     let rows = ids.iter().map(|&id| ("archived", id));
+    ///     let report = conn.update_by_keys("Orders", &["status"], &["id"], rows, 1000)?;
+    ///     Ok(report.rows_affected)
+    /// }
+    /// ```
+    ///
+    /// Fails with [`Error::InvalidArgument`] if `set_columns` or `key_columns` is empty, the number
+    /// of values in a row differs from the number of columns, or `batch_size` is zero.
+    pub fn update_by_keys<R>(
+        &self,
+        table: &str,
+        set_columns: &[&str],
+        key_columns: &[&str],
+        rows: impl IntoIterator<Item = R>,
+        batch_size: usize,
+    ) -> Result<ExecuteManyReport, Error>
+    where
+        R: ParameterRow,
+    {
+        keyed_dml::update_by_keys(self, table, set_columns, key_columns, rows, batch_size)
+    }
+
+    /// Inserts `rows` into `columns` of `table`, using the fastest strategy available for the
+    /// data source. Each row holds the text representation of one value per column, `None`
+    /// representing `NULL`. Values are converted to the types of the columns by the data source.
+    /// Rows are sent in batches of `batch_size`. Identifiers are quoted.
+    ///
+    /// For PostgreSQL each batch is expanded into rows by the server, which is several times faster
+    /// than inserting through array bound parameters. All other data sources fall back to array
+    /// bound parameters. See [`crate::BulkLoadStrategy`].
+    ///
+    /// Note that PostgreSQL's `COPY ... FROM STDIN` is **not** used, even though it would be faster
+    /// still. ODBC offers no way to stream copy data to the server.
+    ///
+    /// ```no_run
+    /// use odbc_api::{Connection, Error};
+    ///
+    /// fn load_cities(conn: &Connection<'_>, cities: &[(String, Option<String>)]) -> Result<(), Error> {
+    ///     let rows = cities
+    ///         .iter()
+    ///         .map(|(name, country)| [Some(name.as_str()), country.as_deref()]);
+    ///     let report = conn.bulk_load("Cities", &["name", "country"], rows, 10_000)?;
+    ///     println!("Inserted {} rows using {:?}", report.num_rows, report.strategy);
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// Fails with [`Error::InvalidArgument`] if `columns` is empty, the number of values in a row
+    /// differs from the number of columns, or `batch_size` is zero. Batches sent before a row with
+    /// the wrong number of values is encountered stay inserted.
+    pub fn bulk_load<'v, R>(
+        &self,
+        table: &str,
+        columns: &[&str],
+        rows: impl IntoIterator<Item = R>,
+        batch_size: usize,
+    ) -> Result<BulkLoadReport, Error>
+    where
+        R: IntoIterator<Item = Option<&'v str>>,
+    {
+        bulk_load::bulk_load(self, table, columns, rows, batch_size)
+    }
+
+    /// Discovers the maximum length of the values in `columns` of the result set of `query`, by
+    /// letting the data source aggregate them. `query` is used as a subquery. The result can be
+    /// used to size text buffers tightly before executing `query`, see [`TextLengths`] for an
+    /// example. The length function is chosen depending on the database management system.
+    pub fn probe_text_lengths(&self, query: &str, columns: &[&str]) -> Result<TextLengths, Error> {
+        text_lengths::probe_text_lengths(self, query, columns)
+    }
+
+    /// Captures the execution plan the data source chooses for `sql`, without the application
+    /// having to know the dialect of the database management system. Depending on it the statement
+    /// is prefixed with `EXPLAIN`, compiled with `SET SHOWPLAN_XML ON` or explained into the plan
+    /// table with `EXPLAIN PLAN FOR`. `params` are bound to the placeholders of `sql`.
+    ///
+    /// ```no_run
+    /// use odbc_api::{Connection, Error};
+    ///
+    /// fn print_plan(conn: &Connection<'_>) -> Result<(), Error> {
+    ///     let plan = conn.explain("SELECT title FROM Movies WHERE year > ?", &1990)?;
+    ///     println!("{}", plan.text());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn explain(
+        &self,
+        sql: &str,
+        params: impl ParameterCollectionRef,
+    ) -> Result<QueryPlan, Error> {
+        query_plan::explain(self, sql, params)
+    }
+
+    /// Like [`Self::execute`], but limits the number of rows in the result set to `max_rows`. The
+    /// limit is enforced by the driver or the data source, rather than by stopping to fetch on the
+    /// client side. This is useful e.g. for previewing the results of ad-hoc queries, which may
+    /// return arbitrarily large result sets.
+    ///
+    /// ```no_run
+    /// use odbc_api::{Connection, Error};
+    ///
+    /// fn preview(conn: &Connection<'_>, query: &str) -> Result<(), Error> {
+    ///     if let Some(cursor) = conn.execute_with_max_rows(query, (), 100)? {
+    ///         // ... fetch at most 100 rows ...
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn execute_with_max_rows(
+        &self,
+        query: &str,
+        params: impl ParameterCollectionRef,
+        max_rows: usize,
+    ) -> Result<Option<CursorImpl<StatementImpl<'_>>>, Error> {
+        let options = StatementOptions::new().with_max_rows(max_rows);
+        self.execute_with_options(query, params, &options)
+    }
+
+    /// Like [`Self::execute`], but sets the statement attributes specified by `options` before
+    /// executing the query. Fails without executing the query, if the driver reports not to
+    /// support the options. See [`StatementOptions`].
+    pub fn execute_with_options(
+        &self,
+        query: &str,
+        params: impl ParameterCollectionRef,
+        options: &StatementOptions,
+    ) -> Result<Option<CursorImpl<StatementImpl<'_>>>, Error> {
+        options.validate(self)?;
+        let query = SqlText::new(query);
+        let lazy_statement = move || {
+            let mut statement = self.allocate_statement()?;
+            options.apply_to(&mut statement)?;
+            Ok(statement)
+        };
+        execute_with_parameters(lazy_statement, Some(&query), params)
+    }
+
     /// Asynchronous sibling of [`Self::execute`]. Uses polling mode to be asynchronous. `sleep`
     /// does govern the behaviour of polling, by waiting for the future in between polling. Sleep
     /// should not be implemented using a sleep which blocks the system thread, but rather utilize
@@ -188,10 +805,9 @@ impl<'c> Connection<'c> {
             Ok(None) => return Ok(None),
             Err(e) => return Err(e),
         };
-        // The rust compiler needs some help here. It assumes otherwise that the lifetime of the
+        // Only keep the raw handle. Otherwise the compiler assumes that the lifetime of the
         // resulting cursor would depend on the lifetime of `params`.
-        let mut cursor = ManuallyDrop::new(cursor);
-        let handle = cursor.as_sys();
+        let handle = cursor.into_statement().into_sys();
         // Safe: `handle` is a valid statement, and we are giving up ownership of `self`.
         let statement = unsafe { StatementConnection::new(handle, self) };
         // Safe: `statement is in the cursor state`.
@@ -236,10 +852,50 @@ impl<'c> Connection<'c> {
     ///   may be used as a placeholder in the statement text, to be replaced with parameters during
     ///   execution.
     pub fn prepare(&self, query: &str) -> Result<Prepared<StatementImpl<'_>>, Error> {
-        let query = SqlText::new(query);
+        let sql = SqlText::new(query);
         let mut stmt = self.allocate_statement()?;
-        stmt.prepare(&query).into_result(&stmt)?;
-        Ok(Prepared::new(stmt))
+        stmt.prepare(&sql).into_result(&stmt)?;
+        Ok(Prepared::new(stmt, query, self.quirks()))
+    }
+
+    /// Prepares `query` and describes its result set, without executing it. This allows to set up
+    /// buffers and target tables, or to validate the schema, before any data is moved. Execute the
+    /// returned statement later on with [`Prepared::execute`]. See
+    /// [`Prepared::result_set_schema`].
+    ///
+    /// ```no_run
+    /// use odbc_api::{Connection, Error};
+    ///
+    /// fn plan_extraction(conn: &Connection<'_>) -> Result<(), Error> {
+    ///     let mut prepared = conn.prepare_with_metadata("SELECT id, title FROM Movies")?;
+    ///     for column in &prepared.result_set_schema()?.columns {
+    ///         println!("{}: {:?}", column.name, column.data_type);
+    ///     }
+    ///     // ... create target table and buffers ...
+    ///     let cursor = prepared.execute(())?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn prepare_with_metadata(&self, query: &str) -> Result<Prepared<StatementImpl<'_>>, Error> {
+        let mut prepared = self.prepare(query)?;
+        prepared.result_set_schema()?;
+        Ok(prepared)
+    }
+
+    /// Like [`Self::prepare`], but sets the statement attributes specified by `options` before
+    /// preparing the query. Fails if the driver reports not to support the options. See
+    /// [`StatementOptions`].
+    pub fn prepare_with_options(
+        &self,
+        query: &str,
+        options: &StatementOptions,
+    ) -> Result<Prepared<StatementImpl<'_>>, Error> {
+        options.validate(self)?;
+        let sql = SqlText::new(query);
+        let mut stmt = self.allocate_statement()?;
+        options.apply_to(&mut stmt)?;
+        stmt.prepare(&sql).into_result(&stmt)?;
+        Ok(Prepared::new(stmt, query, self.quirks()))
     }
 
     /// Prepares an SQL statement which takes ownership of the connection. The advantage over
@@ -287,12 +943,13 @@ impl<'c> Connection<'c> {
     /// }
     /// ```
     pub fn into_prepared(self, query: &str) -> Result<Prepared<StatementConnection<'c>>, Error> {
-        let query = SqlText::new(query);
+        let sql = SqlText::new(query);
+        let quirks = self.quirks();
         let mut stmt = self.allocate_statement()?;
-        stmt.prepare(&query).into_result(&stmt)?;
+        stmt.prepare(&sql).into_result(&stmt)?;
         // Safe: `handle` is a valid statement, and we are giving up ownership of `self`.
         let stmt = unsafe { StatementConnection::new(stmt.into_sys(), self) };
-        Ok(Prepared::new(stmt))
+        Ok(Prepared::new(stmt, query, quirks))
     }
 
     /// Allocates an SQL statement handle. This is recommended if you want to sequentially execute
@@ -343,23 +1000,130 @@ impl<'c> Connection<'c> {
     pub fn set_autocommit(&self, enabled: bool) -> Result<(), Error> {
         self.connection
             .set_autocommit(enabled)
-            .into_result(&self.connection)
+            .into_result(&*self.connection)?;
+        self.notify(ConnectionEvent::AutocommitChanged(enabled));
+        Ok(())
     }
 
     /// To commit a transaction in manual-commit mode.
     pub fn commit(&self) -> Result<(), Error> {
-        self.connection.commit().into_result(&self.connection)
+        self.connection.commit().into_result(&*self.connection)?;
+        self.notify(ConnectionEvent::Committed);
+        Ok(())
     }
 
     /// To rollback a transaction in manual-commit mode.
     pub fn rollback(&self) -> Result<(), Error> {
-        self.connection.rollback().into_result(&self.connection)
+        self.connection.rollback().into_result(&*self.connection)?;
+        self.notify(ConnectionEvent::RolledBack);
+        Ok(())
+    }
+
+    /// Resets the session state, so the connection can be handed to another user of a connection
+    /// pool. Intended to be called at pool check-in. In order:
+    ///
+    /// 1. Rolls back any open transaction.
+    /// 2. Switches back to auto-commit mode.
+    /// 3. Executes a statement discarding session state, depending on the database management
+    ///    system, e.g. `DISCARD ALL` for PostgreSQL, which drops temporary tables, prepared
+    ///    statements and session settings.
+    /// 4. Sets `SQL_ATTR_RESET_CONNECTION`, which makes drivers supporting it, like the Microsoft
+    ///    ODBC Driver for SQL Server, reset the session before the next operation. Drivers not
+    ///    supporting the attribute are tolerated.
+    ///
+    /// Statements of this connection should not be alive while it is reset.
+    pub fn reset(&self) -> Result<(), Error> {
+        self.rollback()?;
+        self.set_autocommit(true)?;
+        if let Some(statement) = reset_statement(&self.database_management_system_name()?) {
+            self.execute(statement, ())?;
+        }
+        if let Err(error) = self
+            .connection
+            .reset_connection()
+            .into_result(&*self.connection)
+        {
+            log::debug!("Driver does not support resetting the connection: {error}");
+        }
+        Ok(())
     }
 
     /// Indicates the state of the connection. If `true` the connection has been lost. If `false`,
     /// the connection is still active.
     pub fn is_dead(&self) -> Result<bool, Error> {
-        self.connection.is_dead().into_result(&self.connection)
+        let is_dead = self.connection.is_dead().into_result(&*self.connection)?;
+        if is_dead {
+            self.notify(ConnectionEvent::Broken);
+        }
+        Ok(is_dead)
+    }
+
+    /// Checks whether the connection is still usable. Cheap enough to be used as health check of
+    /// pooled connections. Executes a trivial probe query (e.g. `SELECT 1`, or `SELECT 1 FROM DUAL`
+    /// for Oracle) with a timeout of five seconds. Other than [`Self::is_dead`], which only reports
+    /// the state the driver has cached since the last operation, this always performs a round trip
+    /// to the data source. Use [`Self::ping_with_probe`] to specify the statement and timeout of
+    /// the probe.
+    pub fn ping(&self) -> Result<bool, Error> {
+        let probe = probe_statement(&self.database_management_system_name()?);
+        self.ping_with_probe(probe, Duration::from_secs(5))
+    }
+
+    /// Executes `probe` to check whether the connection is still usable. `true` if the probe has
+    /// been executed successfully within `timeout`. Any error executing the probe is considered a
+    /// sign of a broken connection and yields `false`, so pick a statement which always succeeds
+    /// on a healthy connection. The timeout is rounded up to whole seconds and may be ignored by
+    /// drivers not supporting `SQL_ATTR_QUERY_TIMEOUT`.
+    pub fn ping_with_probe(&self, probe: &str, timeout: Duration) -> Result<bool, Error> {
+        let timeout_sec = timeout.as_secs() + u64::from(timeout.subsec_nanos() != 0);
+        let query = SqlText::new(probe);
+        let lazy_statement = move || {
+            let mut statement = self.allocate_statement()?;
+            statement
+                .set_query_timeout_sec(timeout_sec.max(1) as usize)
+                .into_result(&statement)?;
+            Ok(statement)
+        };
+        let is_alive = execute_with_parameters(lazy_statement, Some(&query), ()).is_ok();
+        if !is_alive {
+            self.notify(ConnectionEvent::Broken);
+        }
+        Ok(is_alive)
+    }
+
+    /// Measures the round trip time to the data source, by executing a trivial probe query (see
+    /// [`Self::ping`]) `samples` times and fetching its result. The statement is prepared once, so
+    /// the time to compile it is not part of the measurement.
+    ///
+    /// ```no_run
+    /// use odbc_api::{Connection, Error};
+    ///
+    /// fn report_latency(conn: &Connection<'_>) -> Result<(), Error> {
+    ///     let latency = conn.measure_latency(5)?;
+    ///     println!("Median round trip: {:?}", latency.median);
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// Fails with [`Error::InvalidArgument`] if `samples` is `0`.
+    pub fn measure_latency(&self, samples: usize) -> Result<Latency, Error> {
+        let no_samples = || Error::InvalidArgument {
+            description: "at least one sample is required to measure latency",
+        };
+        if samples == 0 {
+            return Err(no_samples());
+        }
+        let probe = probe_statement(&self.database_management_system_name()?);
+        let mut prepared = self.prepare(probe)?;
+        let mut round_trips = Vec::with_capacity(samples);
+        for _ in 0..samples {
+            let start = Instant::now();
+            if let Some(mut cursor) = prepared.execute(())? {
+                while cursor.next_row()?.is_some() {}
+            }
+            round_trips.push(start.elapsed());
+        }
+        Latency::from_samples(&round_trips).ok_or_else(no_samples)
     }
 
     /// Allows sending this connection to different threads. This Connection will still be only be
@@ -422,37 +1186,207 @@ impl<'c> Connection<'c> {
         let mut buf = Vec::new();
         self.connection
             .fetch_database_management_system_name(&mut buf)
-            .into_result(&self.connection)?;
-        let name = slice_to_utf8(&buf).unwrap();
-        Ok(name)
+            .into_result(&*self.connection)?;
+        reported_text(&buf, "a database management system name")
+    }
+
+    /// The character(s) the data source uses to quote identifiers, e.g. `"` for most databases or
+    /// `` ` `` for MySQL. A single space is returned if quoting identifiers is not supported.
+    pub fn identifier_quote_char(&self) -> Result<String, Error> {
+        let mut buf = Vec::new();
+        self.connection
+            .fetch_identifier_quote_char(&mut buf)
+            .into_result(&*self.connection)?;
+        reported_text(&buf, "an identifier quote character")
     }
 
     /// Maximum length of catalog names.
     pub fn max_catalog_name_len(&self) -> Result<u16, Error> {
         self.connection
             .max_catalog_name_len()
-            .into_result(&self.connection)
+            .into_result(&*self.connection)
     }
 
     /// Maximum length of schema names.
     pub fn max_schema_name_len(&self) -> Result<u16, Error> {
         self.connection
             .max_schema_name_len()
-            .into_result(&self.connection)
+            .into_result(&*self.connection)
     }
 
     /// Maximum length of table names.
     pub fn max_table_name_len(&self) -> Result<u16, Error> {
         self.connection
             .max_table_name_len()
-            .into_result(&self.connection)
+            .into_result(&*self.connection)
     }
 
     /// Maximum length of column names.
     pub fn max_column_name_len(&self) -> Result<u16, Error> {
         self.connection
             .max_column_name_len()
-            .into_result(&self.connection)
+            .into_result(&*self.connection)
+    }
+
+    /// Maximum number of statements, which can be active at the same time on this connection. A
+    /// statement is active, if it has pending results, e.g. an open cursor. `0` if there is no
+    /// specified limit or the limit is unknown.
+    ///
+    /// Microsoft SQL Server reports `1`, unless "Multiple Active Result Sets" are enabled with
+    /// `MARS_Connection=Yes` in the connection string.
+    pub fn max_concurrent_activities(&self) -> Result<u16, Error> {
+        self.connection
+            .max_concurrent_activities()
+            .into_result(&*self.connection)
+    }
+
+    /// Maximum length of a statement text in characters (`SQL_MAX_STATEMENT_LEN`). `None` if there
+    /// is no limit or the driver does not report it. Queried once and cached. Statements generated
+    /// by this crate, like the ones of [`Self::execute_in_list`] or [`Self::execute_script`], are
+    /// split to stay within this limit, rather than being truncated by the driver. Statement texts
+    /// passed by the application are not checked. The length is counted in the character type of
+    /// the driver, i.e. in UTF-16 code units, or in bytes if the `narrow` feature is enabled.
+    pub fn max_statement_len(&self) -> Option<usize> {
+        *self.max_statement_len.get_or_init(|| {
+            // `SQL_MAX_STATEMENT_LEN`
+            match self.info_integer(105) {
+                Ok(0) => None,
+                Ok(max_len) => Some(max_len as usize),
+                Err(error) => {
+                    log::debug!("Assuming no limit for the length of statements: {error}");
+                    None
+                }
+            }
+        })
+    }
+
+    /// Fails with [`Error::StatementTooLong`] if `query` exceeds [`Self::max_statement_len`]. Only
+    /// applied to statements generated by this crate, e.g. the ones of a split script.
+    pub(crate) fn check_statement_len(&self, query: &str) -> Result<(), Error> {
+        let Some(max_length) = self.max_statement_len() else {
+            return Ok(());
+        };
+        // Each character takes at least as many bytes as code units of the driver, so most
+        // statements are not even counted.
+        if query.len() <= max_length {
+            return Ok(());
+        }
+        let length = sql_text_len(query);
+        if length > max_length {
+            return Err(Error::StatementTooLong { length, max_length });
+        }
+        Ok(())
+    }
+
+    /// Value of an information type with an `SQLUINTEGER` value or bitmask, identified by its
+    /// numeric value.
+    pub(crate) fn info_integer(&self, info_type: u16) -> Result<u32, Error> {
+        self.connection
+            .info_integer(info_type)
+            .into_result(&*self.connection)
+    }
+
+    /// Hints whether the driver can be trusted to handle calls on this connection from several
+    /// threads, based on the name of the driver. See
+    /// [`ThreadingCapability`] and [`crate::SerializedConnection`].
+    pub fn threading_capability(&self) -> Result<ThreadingCapability, Error> {
+        // `SQL_DRIVER_NAME` and `SQL_ASYNC_MODE`.
+        let mut buf = Vec::new();
+        self.connection
+            .fetch_info_string(6, &mut buf)
+            .into_result(&*self.connection)?;
+        let async_mode = self
+            .connection
+            .info_integer(10021)
+            .into_result(&*self.connection)?;
+        Ok(ThreadingCapability {
+            driver_name: reported_text(&buf, "a driver name")?,
+            async_mode: AsyncMode::from_info(async_mode),
+        })
+    }
+
+    /// Known bugs of the driver, which are worked around for statements prepared with this
+    /// connection. Detected from the name and version of the driver (`SQL_DRIVER_NAME` and
+    /// `SQL_DRIVER_VER`) on first use, unless set with [`Self::set_quirks`]. If the driver can not
+    /// be identified, no quirks are assumed.
+    pub fn quirks(&self) -> Quirks {
+        *self.quirks.get_or_init(|| {
+            let mut name = Vec::new();
+            let mut version = Vec::new();
+            let identified = self
+                .connection
+                .fetch_info_string(6, &mut name)
+                .into_result(&*self.connection)
+                .and_then(|()| {
+                    self.connection
+                        .fetch_info_string(7, &mut version)
+                        .into_result(&*self.connection)
+                });
+            match identified {
+                Ok(()) => Quirks::for_driver(
+                    &slice_to_utf8(&name).unwrap_or_default(),
+                    &slice_to_utf8(&version).unwrap_or_default(),
+                ),
+                Err(error) => {
+                    log::warn!(
+                        "Assuming driver without quirks, since it can not be identified: {error}"
+                    );
+                    Quirks::default()
+                }
+            }
+        })
+    }
+
+    /// Overrides the quirks detected by [`Self::quirks`]. Useful to work around bugs of a driver
+    /// not known to this crate, or to disable workarounds no longer required by a newer version.
+    /// Only affects statements prepared afterwards.
+    ///
+    /// ```no_run
+    /// use odbc_api::{Environment, Error, Quirks};
+    ///
+    /// let env = Environment::new()?;
+    /// let mut conn = env.connect_with_connection_string("DSN=legacy;")?;
+    /// conn.set_quirks(Quirks {
+    ///     zero_length_unsupported: true,
+    ///     ..conn.quirks()
+    /// });
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn set_quirks(&mut self, quirks: Quirks) {
+        self.quirks = OnceCell::from(quirks);
+    }
+
+    /// Declares the encoding the driver uses for narrow text, e.g. `VARCHAR` values fetched as
+    /// `SQL_C_CHAR`. This crate assumes UTF-8 by default, which does not hold true for e.g. many
+    /// older clients of Oracle or Informix on unixODBC. Requires the `encoding` feature.
+    ///
+    /// The driver is not affected by this setting. It only determines the conversions performed by
+    /// [`Self::encode_narrow`] and [`Self::decode_narrow`].
+    #[cfg(feature = "encoding")]
+    pub fn set_narrow_encoding(&mut self, encoding: &'static crate::encoding::Encoding) {
+        self.narrow_encoding = encoding;
+    }
+
+    /// Encoding of narrow text declared with [`Self::set_narrow_encoding`]. UTF-8 by default.
+    #[cfg(feature = "encoding")]
+    pub fn narrow_encoding(&self) -> &'static crate::encoding::Encoding {
+        self.narrow_encoding
+    }
+
+    /// Converts `text` into the narrow encoding of the driver and wraps it in a parameter, so it
+    /// can be bound to a statement. Fails if `text` contains characters which can not be
+    /// represented in the encoding. Requires the `encoding` feature.
+    #[cfg(feature = "encoding")]
+    pub fn encode_narrow(&self, text: &str) -> Result<crate::parameter::VarCharBox, Error> {
+        let bytes = crate::encoding::encode(self.narrow_encoding, text)?;
+        Ok(crate::parameter::VarCharBox::from_vec(bytes.into_owned()))
+    }
+
+    /// Converts narrow text fetched from the driver into UTF-8. Malformed sequences are replaced
+    /// with `U+FFFD REPLACEMENT CHARACTER`. Requires the `encoding` feature.
+    #[cfg(feature = "encoding")]
+    pub fn decode_narrow<'a>(&self, bytes: &'a [u8]) -> Cow<'a, str> {
+        crate::encoding::decode(self.narrow_encoding, bytes)
     }
 
     /// Get the name of the current catalog being used by the connection.
@@ -460,9 +1394,64 @@ impl<'c> Connection<'c> {
         let mut buf = Vec::new();
         self.connection
             .fetch_current_catalog(&mut buf)
-            .into_result(&self.connection)?;
-        let name = slice_to_utf8(&buf).expect("Return catalog must be correctly encoded");
-        Ok(name)
+            .into_result(&*self.connection)?;
+        reported_text(&buf, "a catalog name")
+    }
+
+    /// Switches the catalog (called database by some systems) used by the connection. Uses the
+    /// connection attribute `SQL_ATTR_CURRENT_CATALOG`, so it works with every driver supporting
+    /// catalogs, without knowing the SQL dialect of the data source.
+    pub fn set_current_catalog(&self, catalog_name: &str) -> Result<(), Error> {
+        let catalog_name = SqlText::new(catalog_name);
+        self.connection
+            .set_current_catalog(&catalog_name)
+            .into_result(&*self.connection)
+    }
+
+    /// Name of the default schema, i.e. the schema of tables referenced without a schema name.
+    /// `None` if there is no default schema, e.g. for MySQL connections without selected database.
+    ///
+    /// ODBC offers no way to query the default schema, so a statement specific to the database
+    /// management system is executed. Fails with [`Error::UnsupportedSchemaSwitching`] for
+    /// unknown systems.
+    pub fn current_schema(&self) -> Result<Option<String>, Error> {
+        let dbms_name = self.database_management_system_name()?;
+        let query = if let Some((query, _)) = schema_statements(&dbms_name) {
+            query
+        } else {
+            return Err(Error::UnsupportedSchemaSwitching { dbms_name });
+        };
+        let mut cursor = self.execute(query, ())?.ok_or_else(|| Error::NoResultSet {
+            statement: query.to_owned(),
+        })?;
+        let mut schema = Vec::new();
+        let is_some = if let Some(mut row) = cursor.next_row()? {
+            row.get_text(1, &mut schema)?
+        } else {
+            false
+        };
+        Ok(is_some.then(|| String::from_utf8_lossy(&schema).into_owned()))
+    }
+
+    /// Switches the default schema of the connection, using the statement appropriate for the
+    /// database management system, e.g. `SET search_path TO ...` for PostgreSQL, or `USE ...` for
+    /// MySQL and MariaDB, where schemas are databases. The name is quoted, so it is case sensitive
+    /// for systems which are case sensitive for quoted identifiers.
+    ///
+    /// Fails with [`Error::UnsupportedSchemaSwitching`] for systems which do not allow switching
+    /// the default schema per session, like Microsoft SQL Server, or which are unknown. Fails with
+    /// [`Error::UnquotableIdentifier`] if the data source does not support quoting identifiers and
+    /// `schema_name` is not a plain name.
+    pub fn set_current_schema(&self, schema_name: &str) -> Result<(), Error> {
+        let dbms_name = self.database_management_system_name()?;
+        let statement = if let Some((_, Some(statement))) = schema_statements(&dbms_name) {
+            statement
+        } else {
+            return Err(Error::UnsupportedSchemaSwitching { dbms_name });
+        };
+        let quoted = quote_identifier(schema_name, &self.identifier_quote_char()?)?;
+        self.execute(&format!("{statement} {quoted}"), ())?;
+        Ok(())
     }
 
     /// A cursor describing columns of all tables matching the patterns. Patterns support as
@@ -565,6 +1554,19 @@ impl<'c> Connection<'c> {
         )
     }
 
+    /// A cursor over the data types supported by the data source, as returned by `SQLGetTypeInfo`.
+    /// Each row describes one type, starting with its name (`TYPE_NAME`), its SQL data type
+    /// (`DATA_TYPE`) and its maximum column size (`COLUMN_SIZE`). See:
+    /// <https://learn.microsoft.com/sql/odbc/reference/syntax/sqlgettypeinfo-function>
+    pub fn type_info(&self) -> Result<CursorImpl<StatementImpl<'_>>, Error> {
+        let mut statement = self.allocate_statement()?;
+        statement
+            .type_info(SqlDataType::UNKNOWN_TYPE)
+            .into_result(&statement)?;
+        // Safe: `statement` is in cursor state.
+        Ok(unsafe { CursorImpl::new(statement) })
+    }
+
     /// The buffer descriptions for all standard buffers (not including extensions) returned in the
     /// columns query (e.g. [`Connection::columns`]).
     ///
@@ -657,11 +1659,183 @@ impl<'c> Connection<'c> {
         ])
     }
 
+    /// The underlying connection handle. Allows modules of this crate to call ODBC functions not
+    /// exposed by [`Connection`].
+    pub(crate) fn as_handle(&self) -> &handles::Connection<'c> {
+        &self.connection
+    }
+
+    /// Number of statements allocated from this connection, which are still alive. This includes
+    /// prepared statements and statements with open cursors. Many drivers support only one active
+    /// statement per connection (see [`Self::max_concurrent_activities`]) and fail with a
+    /// "Function sequence error" (`HY010`) or similar, if another statement is executed while a
+    /// cursor is still open. This count helps to track down the statement holding on to it.
+    ///
+    /// Statements transferred into raw handles (see [`StatementImpl::into_sys`]) are no longer
+    /// counted.
+    pub fn active_statement_count(&self) -> usize {
+        self.statements.active.load(Ordering::SeqCst)
+    }
+
+    /// Number of statements allocated from this connection over its lifetime, including the ones
+    /// already freed.
+    pub fn allocated_statement_count(&self) -> usize {
+        self.statements.allocated.load(Ordering::SeqCst)
+    }
+
+    /// If enabled, a warning is logged when the connection is dropped while statements allocated
+    /// from it are still alive. This can only happen if statements are leaked, e.g. using
+    /// [`std::mem::forget`], or handed out as raw handles. Disabled by default.
+    pub fn set_leak_warnings(&self, enabled: bool) {
+        self.statements
+            .leak_warnings
+            .store(enabled, Ordering::Relaxed);
+    }
+
+    /// Disconnects from the data source and frees the connection handle. In contrast to dropping
+    /// the connection, errors are returned rather than causing a panic. The connection is given
+    /// back together with the error, so e.g. an open transaction can be rolled back before trying
+    /// again. Both are boxed, to keep the size of the result small.
+    ///
+    /// Statements, prepared queries and cursors borrow the connection they have been allocated
+    /// from, or own it (see [`StatementConnection`]). So the connection can not be disconnected
+    /// while they are alive:
+    ///
+    /// ```compile_fail
+    /// use odbc_api::{Connection, Error};
+    ///
+    /// fn disconnect_with_open_cursor(conn: Connection<'_>) -> Result<(), Error> {
+    ///     let cursor = conn.execute("SELECT title FROM Movies", ())?;
+    ///     conn.disconnect().map_err(|failed| failed.1)?;
+    ///     drop(cursor);
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// Statements may still be alive if they have been leaked, e.g. using [`std::mem::forget`]. In
+    /// this case [`Error::ActiveStatements`] is returned, without disconnecting. See
+    /// [`Self::disconnect_force`] for shutdown paths, which can not recover from errors.
+    pub fn disconnect(mut self) -> Result<(), Box<(Self, Error)>> {
+        let num_active = self.active_statement_count();
+        if num_active != 0 {
+            return Err(Box::new((self, Error::ActiveStatements { num_active })));
+        }
+        self.unregister_statements();
+        if let Err(error) = self.connection.disconnect().into_result(&*self.connection) {
+            return Err(Box::new((self, error)));
+        }
+        self.disposal = Disposal::Free;
+        Ok(())
+    }
+
+    /// Disconnects from the data source, even if statements allocated from it are still alive or
+    /// a transaction is open. Open transactions are rolled back and statements are freed
+    /// implicitly by the driver. Intended for shutdown paths, this never panics. Should
+    /// disconnecting fail nonetheless, the error is returned and the connection handle is leaked
+    /// rather than freed, since freeing a handle which is still connected fails.
+    pub fn disconnect_force(mut self) -> Result<(), Error> {
+        let num_active = self.active_statement_count();
+        if num_active != 0 {
+            log::warn!(
+                "Forcefully disconnecting while {num_active} statement(s) allocated from the \
+                connection are still alive."
+            );
+        }
+        self.unregister_statements();
+        let mut result = self.connection.disconnect().into_result(&*self.connection);
+        if let Err(Error::Diagnostics { record, .. }) = &result {
+            if record.state == State::INVALID_STATE_TRANSACTION {
+                result = self
+                    .connection
+                    .rollback()
+                    .into_result(&*self.connection)
+                    .and_then(|()| self.connection.disconnect().into_result(&*self.connection));
+            }
+        }
+        self.disposal = if result.is_ok() {
+            Disposal::Free
+        } else {
+            Disposal::Leak
+        };
+        result
+    }
+
     fn allocate_statement(&self) -> Result<StatementImpl<'_>, Error> {
-        self.connection
+        let statement = self
+            .connection
             .allocate_statement()
-            .into_result(&self.connection)
+            .into_result(&*self.connection)?;
+        self.statements.allocated.fetch_add(1, Ordering::SeqCst);
+        let registration = self.register_statement(statement.as_sys());
+        Ok(statement
+            .counted_by(&self.statements.active)
+            .registered(registration)
+            .audited(self.audit.as_ref().map(|(log, _)| log))
+            .with_context(self.context.get().map(|(context, _)| context))
+            .hooked(self.call_hook.as_ref().map(CallHookRegistration::hook)))
     }
+
+    /// Tracks `statement` at the environment, so it can be cancelled by
+    /// [`crate::Environment::shutdown`]. `None` for connections which are not tracked by an
+    /// environment.
+    pub(crate) fn register_statement(&self, statement: HStmt) -> Option<StatementRegistration> {
+        self.count_guard.as_ref().map(|guard| {
+            guard
+                .statements()
+                .register(self.connection.as_sys(), statement)
+        })
+    }
+
+    /// Stops tracking the statements of this connection at the environment. Disconnecting frees
+    /// all statements of the connection, including leaked ones, so this must be called before.
+    fn unregister_statements(&self) {
+        if let Some(guard) = &self.count_guard {
+            guard
+                .statements()
+                .unregister_connection(self.connection.as_sys());
+        }
+    }
+}
+
+/// Trivial query used by [`Connection::ping`] and [`crate::KeepAlive`]. Some systems require a
+/// `FROM` clause.
+pub(crate) fn probe_statement(dbms_name: &str) -> &'static str {
+    match dbms_name {
+        "Oracle" => "SELECT 1 FROM DUAL",
+        name if name.starts_with("DB2") => "SELECT 1 FROM SYSIBM.SYSDUMMY1",
+        "Firebird" => "SELECT 1 FROM RDB$DATABASE",
+        _ => "SELECT 1",
+    }
+}
+
+/// Statement discarding the session state, used by [`Connection::reset`].
+fn reset_statement(dbms_name: &str) -> Option<&'static str> {
+    match dbms_name {
+        "PostgreSQL" => Some("DISCARD ALL"),
+        "Oracle" => Some("BEGIN DBMS_SESSION.RESET_PACKAGE; END;"),
+        _ => None,
+    }
+}
+
+/// Query for the default schema and, if the schema can be switched per session, the statement
+/// switching it, to be followed by the quoted schema name.
+fn schema_statements(dbms_name: &str) -> Option<(&'static str, Option<&'static str>)> {
+    let statements = match dbms_name {
+        "PostgreSQL" => ("SELECT current_schema()", Some("SET search_path TO")),
+        "MySQL" | "MariaDB" => ("SELECT DATABASE()", Some("USE")),
+        "Oracle" => (
+            "SELECT SYS_CONTEXT('USERENV', 'CURRENT_SCHEMA') FROM DUAL",
+            Some("ALTER SESSION SET CURRENT_SCHEMA ="),
+        ),
+        name if name.starts_with("DB2") => (
+            "SELECT CURRENT SCHEMA FROM SYSIBM.SYSDUMMY1",
+            Some("SET SCHEMA"),
+        ),
+        "Snowflake" => ("SELECT CURRENT_SCHEMA()", Some("USE SCHEMA")),
+        "Microsoft SQL Server" => ("SELECT SCHEMA_NAME()", None),
+        _ => return None,
+    };
+    Some(statements)
 }
 
 /// You can use this method to escape a password so it is suitable to be appended to an ODBC
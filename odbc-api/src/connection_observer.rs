@@ -0,0 +1,50 @@
+/// Events in the lifecycle of a [`crate::Connection`], reported to a [`ConnectionObserver`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionEvent {
+    /// A connection to a data source has been established.
+    Opened,
+    /// The connection has been closed, because it went out of scope.
+    Closed,
+    /// [`crate::Connection::is_dead`] detected that the connection has been lost.
+    Broken,
+    /// The transaction mode has been set using [`crate::Connection::set_autocommit`]. Holds `true`
+    /// if autocommit has been enabled.
+    AutocommitChanged(bool),
+    /// A transaction has been committed using [`crate::Connection::commit`].
+    Committed,
+    /// A transaction has been rolled back using [`crate::Connection::rollback`].
+    RolledBack,
+}
+
+/// Notified about [`ConnectionEvent`]s, e.g. to update health metrics or to warm caches once a
+/// connection is opened. Register an observer for all connections opened with an environment using
+/// [`crate::Environment::set_connection_observer`], or for a single connection using
+/// [`crate::Connection::set_observer`]. Implemented for closures.
+///
+/// ```
+/// use std::sync::{Arc, atomic::{AtomicUsize, Ordering}};
+/// use odbc_api::{ConnectionEvent, ConnectionObserver};
+///
+/// let open_connections = Arc::new(AtomicUsize::new(0));
+/// let counter = open_connections.clone();
+/// let observer: Arc<dyn ConnectionObserver> = Arc::new(move |event| match event {
+///     ConnectionEvent::Opened => { counter.fetch_add(1, Ordering::Relaxed); },
+///     ConnectionEvent::Closed => { counter.fetch_sub(1, Ordering::Relaxed); },
+///     _ => (),
+/// });
+/// ```
+pub trait ConnectionObserver: Send + Sync {
+    /// Invoked after the event happened. Since this is called from within the operation which
+    /// caused the event (including `Drop` of the connection), implementations should be quick and
+    /// must not panic.
+    fn on_event(&self, event: ConnectionEvent);
+}
+
+impl<F> ConnectionObserver for F
+where
+    F: Fn(ConnectionEvent) + Send + Sync,
+{
+    fn on_event(&self, event: ConnectionEvent) {
+        self(event)
+    }
+}
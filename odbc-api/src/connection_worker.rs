@@ -0,0 +1,313 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{mpsc, Arc, Mutex},
+    task::{Context, Poll, Waker},
+    thread,
+};
+
+use crate::{
+    handles::StatementImpl, parameter::InputParameter, Connection, Cursor, CursorImpl, Error,
+    RowSetBuffer,
+};
+
+/// Work sent to the worker thread. Executed with the connection and the cursor of the last query,
+/// if it is still open.
+type Job = Box<
+    dyn for<'c> FnOnce(&'c Connection<'static>, &mut Option<CursorImpl<StatementImpl<'c>>>) + Send,
+>;
+
+enum Command {
+    Run(Job),
+    /// Closes the cursor and disconnects, ending the worker thread.
+    Close(ReplySender<Result<(), Error>>),
+}
+
+/// Owns a connection on a dedicated thread, and exposes it to asynchronous code. Every call to the
+/// driver happens on the worker thread, so the executor is never blocked by the driver, even if it
+/// does not support asynchronous execution. Use this rather than [`Connection::execute_polling`],
+/// if `SQL_ATTR_ASYNC_ENABLE` is not supported by the driver.
+///
+/// Since [`Connection`] is not `Send`, the connection is opened on the worker thread, too. Commands
+/// are processed one at a time in the order they are sent.
+///
+/// ```no_run
+/// use std::sync::OnceLock;
+/// use odbc_api::{buffers::TextRowSet, ConnectionWorker, Environment, Error};
+///
+/// static ENV: OnceLock<Environment> = OnceLock::new();
+///
+/// async fn print_titles() -> Result<(), Error> {
+///     let mut worker = ConnectionWorker::connect(|| {
+///         let env = ENV.get_or_init(|| Environment::new().unwrap());
+///         env.connect_with_connection_string("DSN=movies;")
+///     })
+///     .await?;
+///     if let Some(mut cursor) = worker.execute("SELECT title FROM Movies", Vec::new()).await? {
+///         let mut buffer = TextRowSet::from_max_str_lens(100, [255])?;
+///         while let Some(batch) = cursor.fetch(buffer).await? {
+///             for row_index in 0..batch.num_rows() {
+///                 println!("{:?}", batch.at_as_str(0, row_index).unwrap());
+///             }
+///             buffer = batch;
+///         }
+///     }
+///     worker.close().await
+/// }
+/// ```
+pub struct ConnectionWorker {
+    commands: mpsc::Sender<Command>,
+    /// Not joined on drop, to avoid blocking the executor while the driver disconnects.
+    thread: thread::JoinHandle<()>,
+}
+
+impl ConnectionWorker {
+    /// Spawns the worker thread and opens the connection on it using `connect`. Fails with the
+    /// error returned by `connect`.
+    pub async fn connect(
+        connect: impl FnOnce() -> Result<Connection<'static>, Error> + Send + 'static,
+    ) -> Result<Self, Error> {
+        let (commands, receiver) = mpsc::channel();
+        let (connected, reply) = reply_channel();
+        let thread = thread::Builder::new()
+            .name("odbc-connection-worker".to_owned())
+            .spawn(move || match connect() {
+                Ok(connection) => {
+                    connected.send(Ok(()));
+                    serve(connection, receiver)
+                }
+                Err(error) => connected.send(Err(error)),
+            })
+            .map_err(Error::FailedSpawningWorker)?;
+        let worker = Self { commands, thread };
+        reply.await.ok_or(Error::WorkerTerminated)??;
+        Ok(worker)
+    }
+
+    /// Runs `f` with the connection on the worker thread, e.g. to query metadata or to change
+    /// attributes of the connection. The cursor of the last query, if still open, stays open.
+    pub async fn run<T>(
+        &mut self,
+        f: impl FnOnce(&Connection<'static>) -> T + Send + 'static,
+    ) -> Result<T, Error>
+    where
+        T: Send + 'static,
+    {
+        self.submit(move |connection, _cursor| f(connection)).await
+    }
+
+    /// Executes `query` with `params` on the worker thread. Returns a [`WorkerCursor`] if the
+    /// statement produced a result set. A cursor still open from a previous query is closed first.
+    pub async fn execute(
+        &mut self,
+        query: impl Into<String>,
+        params: Vec<Box<dyn InputParameter + Send>>,
+    ) -> Result<Option<WorkerCursor<'_>>, Error> {
+        let query = query.into();
+        let has_cursor = self
+            .submit(move |connection, cursor| {
+                *cursor = None;
+                let params: Vec<Box<dyn InputParameter>> = params
+                    .into_iter()
+                    .map(|param| param as Box<dyn InputParameter>)
+                    .collect();
+                *cursor = connection.execute(&query, &params[..])?;
+                Ok(cursor.is_some())
+            })
+            .await??;
+        Ok(has_cursor.then_some(WorkerCursor { worker: self }))
+    }
+
+    /// Closes any open cursor and disconnects from the data source. Waits for the worker thread to
+    /// finish. Dropping the worker disconnects, too, but in the background and ignoring errors.
+    pub async fn close(self) -> Result<(), Error> {
+        let (sender, reply) = reply_channel();
+        self.commands
+            .send(Command::Close(sender))
+            .map_err(|_| Error::WorkerTerminated)?;
+        let result = reply.await.ok_or(Error::WorkerTerminated)?;
+        // The worker thread has already replied and is about to finish.
+        self.thread.join().map_err(|_| Error::WorkerTerminated)?;
+        result
+    }
+
+    /// Sends `job` to the worker thread and waits for its result.
+    async fn submit<T, F>(&mut self, job: F) -> Result<T, Error>
+    where
+        T: Send + 'static,
+        F: for<'c> FnOnce(&'c Connection<'static>, &mut Option<CursorImpl<StatementImpl<'c>>>) -> T,
+        F: Send + 'static,
+    {
+        let (sender, reply) = reply_channel();
+        let job: Job = Box::new(move |connection, cursor| sender.send(job(connection, cursor)));
+        self.commands
+            .send(Command::Run(job))
+            .map_err(|_| Error::WorkerTerminated)?;
+        reply.await.ok_or(Error::WorkerTerminated)
+    }
+}
+
+/// Processes commands until [`Command::Close`] or until the [`ConnectionWorker`] is dropped.
+fn serve(connection: Connection<'static>, commands: mpsc::Receiver<Command>) {
+    let mut cursor = None;
+    let close = loop {
+        match commands.recv() {
+            Ok(Command::Run(job)) => job(&connection, &mut cursor),
+            Ok(Command::Close(reply)) => break Some(reply),
+            Err(mpsc::RecvError) => break None,
+        }
+    };
+    drop(cursor);
+    let result = connection.disconnect_force();
+    match close {
+        Some(reply) => reply.send(result),
+        None => {
+            if let Err(error) = result {
+                log::error!("Failed to disconnect connection of worker thread: {error}");
+            }
+        }
+    }
+}
+
+/// Cursor of a query executed by a [`ConnectionWorker`]. The result set lives on the worker
+/// thread. Batches are fetched into buffers sent to the worker and back.
+pub struct WorkerCursor<'w> {
+    worker: &'w mut ConnectionWorker,
+}
+
+impl WorkerCursor<'_> {
+    /// Binds `buffer` to the cursor and fetches the next batch into it. Returns the filled buffer,
+    /// to be passed to the next call, or `None` once the result set is consumed. The buffer may
+    /// differ between calls. If fetching fails, the cursor is closed.
+    pub async fn fetch<B>(&mut self, buffer: B) -> Result<Option<B>, Error>
+    where
+        B: RowSetBuffer + Send + 'static,
+    {
+        self.worker
+            .submit(move |_connection, cursor| {
+                let open = cursor.take().ok_or(Error::WorkerCursorClosed)?;
+                let mut block_cursor = open.bind_buffer(buffer)?;
+                let has_batch = block_cursor.fetch()?.is_some();
+                let (open, buffer) = block_cursor.unbind()?;
+                *cursor = Some(open);
+                Ok(has_batch.then_some(buffer))
+            })
+            .await?
+    }
+
+    /// Runs `f` with the cursor on the worker thread, e.g. to query the names and types of the
+    /// columns in order to allocate a buffer.
+    pub async fn run<T>(
+        &mut self,
+        f: impl FnOnce(&mut CursorImpl<StatementImpl<'_>>) -> Result<T, Error> + Send + 'static,
+    ) -> Result<T, Error>
+    where
+        T: Send + 'static,
+    {
+        self.worker
+            .submit(move |_connection, cursor| match cursor {
+                Some(cursor) => f(cursor),
+                None => Err(Error::WorkerCursorClosed),
+            })
+            .await?
+    }
+
+    /// Closes the cursor on the worker thread, so the connection can be used for other
+    /// statements. Dropping the cursor without closing it keeps the result set open until the
+    /// next call to [`ConnectionWorker::execute`].
+    pub async fn close(self) -> Result<(), Error> {
+        self.worker
+            .submit(|_connection, cursor| *cursor = None)
+            .await
+    }
+}
+
+/// Creates a channel for a single reply of the worker thread.
+fn reply_channel<T>() -> (ReplySender<T>, Reply<T>) {
+    let slot = Arc::new(Mutex::new(ReplySlot {
+        value: None,
+        sender_alive: true,
+        waker: None,
+    }));
+    (ReplySender { slot: slot.clone() }, Reply { slot })
+}
+
+struct ReplySlot<T> {
+    value: Option<T>,
+    sender_alive: bool,
+    waker: Option<Waker>,
+}
+
+/// Sends the reply. If dropped without sending, e.g. because a job panicked, the [`Reply`] yields
+/// `None`.
+struct ReplySender<T> {
+    slot: Arc<Mutex<ReplySlot<T>>>,
+}
+
+impl<T> ReplySender<T> {
+    fn send(self, value: T) {
+        self.slot.lock().unwrap().value = Some(value);
+        // Drop wakes the receiver.
+    }
+}
+
+impl<T> Drop for ReplySender<T> {
+    fn drop(&mut self) {
+        let mut slot = self
+            .slot
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        slot.sender_alive = false;
+        if let Some(waker) = slot.waker.take() {
+            waker.wake()
+        }
+    }
+}
+
+/// Resolves to the value sent by the [`ReplySender`], or to `None` if it has been dropped without
+/// sending.
+struct Reply<T> {
+    slot: Arc<Mutex<ReplySlot<T>>>,
+}
+
+impl<T> Future for Reply<T> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let mut slot = self.slot.lock().unwrap();
+        if let Some(value) = slot.value.take() {
+            Poll::Ready(Some(value))
+        } else if !slot.sender_alive {
+            Poll::Ready(None)
+        } else {
+            slot.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        future::Future,
+        pin::pin,
+        task::{Context, Poll, Waker},
+    };
+
+    use super::reply_channel;
+
+    #[test]
+    fn reply_is_none_if_sender_dropped_without_sending() {
+        let mut cx = Context::from_waker(Waker::noop());
+
+        let (sender, reply) = reply_channel::<i32>();
+        let mut reply = pin!(reply);
+        assert!(reply.as_mut().poll(&mut cx).is_pending());
+        sender.send(42);
+        assert_eq!(Poll::Ready(Some(42)), reply.poll(&mut cx));
+
+        let (sender, reply) = reply_channel::<i32>();
+        drop(sender);
+        assert_eq!(Poll::Ready(None), pin!(reply).poll(&mut cx));
+    }
+}
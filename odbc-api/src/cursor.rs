@@ -1,15 +1,15 @@
-use odbc_sys::HStmt;
-
 use crate::{
-    buffers::Indicator,
+    buffers::{BatchExtractor, ColumnarAnyBuffer, Indicator},
+    cursor_statistics::StatisticsTracker,
     error::ExtendResult,
     handles::{AsStatementRef, CDataMut, SqlResult, State, Statement, StatementRef},
-    parameter::{Binary, CElement, Text, VarCell, VarKind},
+    parameter::{Binary, CElement, Text, VarCell, VarKind, WideText},
     sleep::{wait_for, Sleep},
-    Error, ResultSetMetadata,
+    CursorStatistics, Error, NoTotalPolicy, ResultSetMetadata, VariadicFetch,
 };
 
-use std::thread::panicking;
+use odbc_sys::FetchOrientation;
+use std::{mem::ManuallyDrop, ptr};
 
 /// Cursors are used to process and iterate the result sets returned by executing queries.
 ///
@@ -69,6 +69,60 @@ pub trait Cursor: ResultSetMetadata {
     where
         Self: Sized,
         B: RowSetBuffer;
+
+    /// Fetches all rows and writes them to `writer` as delimited text, e.g. CSV. Returns the number
+    /// of rows written. See [`crate::delimited`].
+    fn write_delimited(
+        self,
+        writer: impl std::io::Write,
+        options: &crate::delimited::DelimitedOptions,
+    ) -> Result<usize, Error>
+    where
+        Self: Sized,
+    {
+        crate::delimited::write_delimited(self, writer, options)
+    }
+
+    /// Fetches the rows and writes them to `writer` as an ASCII, markdown or HTML table. Returns
+    /// the number of rows rendered. See [`crate::render`].
+    fn write_table(
+        self,
+        writer: impl std::io::Write,
+        options: &crate::render::RenderOptions,
+    ) -> Result<usize, Error>
+    where
+        Self: Sized,
+    {
+        crate::render::write_table(self, writer, options)
+    }
+
+    /// Fetches all rows and writes them to `writer` as an Avro object container file. Returns the
+    /// number of rows written. See [`crate::avro`] for how data types are mapped.
+    #[cfg(feature = "avro")]
+    fn write_avro(
+        self,
+        writer: impl std::io::Write,
+        options: &crate::avro::AvroOptions,
+    ) -> Result<usize, Error>
+    where
+        Self: Sized,
+    {
+        crate::avro::write_avro(self, writer, options)
+    }
+
+    /// Fetches all rows and writes them to `writer` as an Arrow IPC stream. Returns the number of
+    /// rows written. See [`crate::arrow`] for how data types are mapped.
+    #[cfg(feature = "arrow")]
+    fn write_arrow_ipc(
+        self,
+        writer: impl std::io::Write,
+        options: &crate::arrow::ArrowIpcOptions,
+    ) -> Result<usize, Error>
+    where
+        Self: Sized,
+    {
+        crate::arrow::write_arrow_ipc(self, writer, options)
+    }
 }
 
 /// An individual row of an result set. See [`crate::Cursor::next_row`].
@@ -80,7 +134,7 @@ impl<'s> CursorRow<'s> {
     /// # Safety
     ///
     /// `statement` must be in a cursor state.
-    unsafe fn new(statement: StatementRef<'s>) -> Self {
+    pub(crate) unsafe fn new(statement: StatementRef<'s>) -> Self {
         CursorRow { statement }
     }
 }
@@ -129,11 +183,115 @@ impl<'s> CursorRow<'s> {
         self.get_variadic::<Binary>(col_or_param_num, buf)
     }
 
+    /// Retrieves arbitrary large character data from the row as UTF-16 and stores it in the buffer.
+    /// Column index starts at `1`. Since the value is fetched in its entirety, surrogate pairs split
+    /// between individual calls to `SQLGetData` are joined again. If you want to process the value
+    /// in chunks instead, see [`crate::Utf16ChunkJoiner`].
+    ///
+    /// # Return
+    ///
+    /// `true` indicates that the value has not been `NULL` and the value has been placed in `buf`.
+    /// `false` indicates that the value is `NULL`. The buffer is cleared in that case.
+    pub fn get_wide_text(
+        &mut self,
+        col_or_param_num: u16,
+        buf: &mut Vec<u16>,
+    ) -> Result<bool, Error> {
+        let mut bytes = Vec::with_capacity(buf.capacity() * 2);
+        let not_null = self.get_variadic::<WideText>(col_or_param_num, &mut bytes)?;
+        buf.clear();
+        buf.extend(
+            bytes
+                .chunks_exact(2)
+                .map(|pair| u16::from_ne_bytes([pair[0], pair[1]])),
+        );
+        Ok(not_null)
+    }
+
     pub fn get_variadic<K: VarKind>(
         &mut self,
         col_or_param_num: u16,
         buf: &mut Vec<u8>,
     ) -> Result<bool, Error> {
+        let fetch =
+            self.get_variadic_with_policy::<K>(col_or_param_num, buf, NoTotalPolicy::ReadToEnd)?;
+        Ok(fetch.not_null)
+    }
+
+    /// Like [`Self::get_variadic`], but `policy` decides what happens if the driver does not report
+    /// the total length of the value. The returned [`VariadicFetch`] tells whether this has been the
+    /// case and whether the value has been truncated as a consequence.
+    ///
+    /// ```
+    /// use odbc_api::{parameter::Binary, CursorRow, Error, NoTotalPolicy};
+    ///
+    /// /// Fetches at most 1 MiB of a binary value, even if the driver can not tell its length.
+    /// fn get_thumbnail(row: &mut CursorRow<'_>, buf: &mut Vec<u8>) -> Result<bool, Error> {
+    ///     let policy = NoTotalPolicy::Cap { max_bytes: 1 << 20 };
+    ///     let fetch = row.get_variadic_with_policy::<Binary>(1, buf, policy)?;
+    ///     if fetch.truncated {
+    ///         eprintln!("Thumbnail truncated to {} bytes.", buf.len());
+    ///     }
+    ///     Ok(fetch.not_null)
+    /// }
+    /// ```
+    pub fn get_variadic_with_policy<K: VarKind>(
+        &mut self,
+        col_or_param_num: u16,
+        buf: &mut Vec<u8>,
+        policy: NoTotalPolicy,
+    ) -> Result<VariadicFetch, Error> {
+        self.get_variadic_impl::<K>(col_or_param_num, buf, policy, None)
+    }
+
+    /// Like [`Self::get_variadic`], but fails with [`Error::ValueTooLarge`] if the value is longer
+    /// than `cap` bytes, rather than allocating memory for it. Protects services from values of
+    /// unexpected size, e.g. a cell holding gigabytes of text. No more than `cap` bytes, plus room
+    /// for the terminating zero, are allocated, even if the driver does not report the length of
+    /// the value. `buf` is left in an unspecified state if the value is too large.
+    ///
+    /// ```
+    /// use odbc_api::{parameter::Text, CursorRow, Error};
+    ///
+    /// /// Fetches a comment, refusing to hold more than 64 KiB of it in memory.
+    /// fn get_comment(row: &mut CursorRow<'_>, buf: &mut Vec<u8>) -> Result<bool, Error> {
+    ///     row.get_variadic_capped::<Text>(1, buf, 64 * 1024)
+    /// }
+    /// ```
+    pub fn get_variadic_capped<K: VarKind>(
+        &mut self,
+        col_or_param_num: u16,
+        buf: &mut Vec<u8>,
+        cap: usize,
+    ) -> Result<bool, Error> {
+        let fetch = self.get_variadic_impl::<K>(
+            col_or_param_num,
+            buf,
+            NoTotalPolicy::ReadToEnd,
+            Some(cap),
+        )?;
+        Ok(fetch.not_null)
+    }
+
+    /// Fetches a variadic value in chunks, growing `buf` as required. `cap` is the maximum
+    /// length of the value in bytes, if any.
+    fn get_variadic_impl<K: VarKind>(
+        &mut self,
+        col_or_param_num: u16,
+        buf: &mut Vec<u8>,
+        policy: NoTotalPolicy,
+        cap: Option<usize>,
+    ) -> Result<VariadicFetch, Error> {
+        let too_large = |size| Error::ValueTooLarge {
+            column: col_or_param_num,
+            size,
+            cap: cap.unwrap_or_default(),
+        };
+        let mut fetch = VariadicFetch {
+            not_null: true,
+            no_total: false,
+            truncated: false,
+        };
         if buf.capacity() == 0 {
             // User did just provide an empty buffer. So it is fair to assume not much domain
             // knowledge has been used to decide its size. We just default to 256 to increase the
@@ -144,53 +302,98 @@ impl<'s> CursorRow<'s> {
             // This also implicitly makes sure that we can at least hold one terminting zero.
             buf.reserve(256);
         }
-        // Utilize all of the allocated buffer.
+        // Utilize all of the allocated buffer. Previous contents are discarded, so the terminating
+        // zero required for truncated text is in place.
+        buf.clear();
         buf.resize(buf.capacity(), 0);
 
-        // Did we learn how much capacity we need in the last iteration? We use this only to panic
-        // on erroneous implementations of get_data and avoid endless looping until we run out of
-        // memory.
+        // Did we learn how much capacity we need in the last iteration? We use this only to return
+        // an error on erroneous implementations of get_data and avoid endless looping until we run
+        // out of memory.
         let mut remaining_length_known = false;
         // We repeatedly fetch data and add it to the buffer. The buffer length is therefore the
         // accumulated value size. The target always points to the last window in buf which is going
         // to contain the **next** part of the data, thereas buf contains the entire accumulated
         // value so far.
-        let mut target = VarCell::<&mut [u8], K>::from_buffer(buf.as_mut_slice(), Indicator::NoTotal);
+        let mut target =
+            VarCell::<&mut [u8], K>::try_from_buffer(buf.as_mut_slice(), Indicator::NoTotal)?;
+        // Position of the target window within `buf`, i.e. the number of bytes fetched by previous
+        // calls to get_data.
+        let mut window_start = 0;
         self.get_data(col_or_param_num, &mut target)?;
         while !target.is_complete() {
-            // Amount of payload bytes (excluding terminating zeros) fetched with the last call to
-            // get_data.
-            let fetched = target.as_bytes().unwrap().len();
-            match target.indicator() {
-                // If Null the value would be complete
-                Indicator::Null => unreachable!(),
+            match target.try_indicator()? {
+                // Not reached, since NULL values are complete. Handled after the loop.
+                Indicator::Null => break,
                 // We do not know how large the value is. Let's fetch the data with repeated calls
                 // to get_data.
                 Indicator::NoTotal => {
+                    fetch.no_total = true;
                     let old_len = buf.len();
+                    // The value is longer than the payload fetched so far.
+                    let fetched_so_far = old_len - K::TERMINATING_ZEROES;
+                    if cap.is_some_and(|cap| fetched_so_far >= cap) {
+                        return Err(too_large(None));
+                    }
                     // Use an exponential strategy for increasing buffer size.
-                    buf.resize(old_len * 2, 0);
-                    let buf_extend = &mut buf[(old_len - K::TERMINATING_ZEROES)..];
-                    target = VarCell::<&mut [u8], K>::from_buffer(buf_extend, Indicator::NoTotal);
+                    let new_len = match policy {
+                        NoTotalPolicy::ReadToEnd => old_len * 2,
+                        NoTotalPolicy::Cap { max_bytes } => {
+                            // Round down to whole elements, e.g. UTF-16 code units for wide text.
+                            let element_size = K::TERMINATING_ZEROES.max(1);
+                            let max_bytes = max_bytes - max_bytes % element_size;
+                            // All of the buffer is payload, except for the terminating zero.
+                            if old_len - K::TERMINATING_ZEROES >= max_bytes {
+                                buf.truncate(max_bytes);
+                                fetch.truncated = true;
+                                return Ok(fetch);
+                            }
+                            (old_len * 2).min(max_bytes + K::TERMINATING_ZEROES)
+                        }
+                        NoTotalPolicy::Error => {
+                            return Err(Error::NoTotal {
+                                column_number: col_or_param_num,
+                            })
+                        }
+                    };
+                    // Allocate at most one byte more than the cap, to tell whether it is exceeded.
+                    let new_len =
+                        cap.map_or(new_len, |cap| new_len.min(cap + 1 + K::TERMINATING_ZEROES));
+                    buf.resize(new_len, 0);
+                    window_start = old_len - K::TERMINATING_ZEROES;
+                    let buf_extend = &mut buf[window_start..];
+                    target =
+                        VarCell::<&mut [u8], K>::try_from_buffer(buf_extend, Indicator::NoTotal)?;
                 }
                 // We did not get all of the value in one go, but the data source has been friendly
                 // enough to tell us how much is missing.
                 Indicator::Length(len) => {
+                    // SQLGetData has been unable to fetch all data, even though the capacity of
+                    // the target buffer has been adapted to hold the entire payload based on the
+                    // indicator of the last part. This is a bug in the ODBC driver.
+                    let invalid_length = || Error::InvalidDriverValue {
+                        description: "remaining length of a value",
+                        value: len as i64,
+                    };
                     if remaining_length_known {
-                        panic!(
-                            "SQLGetData has been unable to fetch all data, even though the \
-                            capacity of the target buffer has been adapted to hold the entire \
-                            payload based on the indicator of the last part. You may consider \
-                            filing a bug with the ODBC driver you are using.")
+                        return Err(invalid_length());
+                    }
+                    if cap.is_some_and(|cap| window_start + len > cap) {
+                        return Err(too_large(Some(window_start + len)));
                     }
                     remaining_length_known = true;
+                    // Amount of payload bytes (excluding terminating zeros) fetched with the last
+                    // call to get_data.
+                    let fetched = target.as_bytes().map_or(0, <[u8]>::len);
                     // Amount of bytes missing from the value using get_data, excluding terminating
                     // zero.
-                    let still_missing = len - fetched;
+                    let still_missing = len.checked_sub(fetched).ok_or_else(invalid_length)?;
                     let old_len = buf.len();
                     buf.resize(old_len + still_missing, 0);
-                    let buf_extend = &mut buf[(old_len - K::TERMINATING_ZEROES)..];
-                    target = VarCell::<&mut [u8], K>::from_buffer(buf_extend, Indicator::NoTotal);
+                    window_start = old_len - K::TERMINATING_ZEROES;
+                    let buf_extend = &mut buf[window_start..];
+                    target =
+                        VarCell::<&mut [u8], K>::try_from_buffer(buf_extend, Indicator::NoTotal)?;
                 }
             }
             // Fetch binary data into buffer.
@@ -198,17 +401,63 @@ impl<'s> CursorRow<'s> {
         }
         // We did get the complete value, including the terminating zero. Let's resize the buffer to
         // match the retrieved value exactly (excluding terminating zero).
-        if let Some(len) = target.indicator().value_len() {
+        if let Some(len) = target.try_indicator()?.value_len() {
+            // The value may have fit into the initial buffer, yet exceed the cap.
+            if cap.is_some_and(|cap| window_start + len > cap) {
+                return Err(too_large(Some(window_start + len)));
+            }
             // Since the indicator refers to value length without terminating zero, and capacity is
             // including the terminating zero this also implicitly drops the terminating zero at the
             // end of the buffer.
             let shrink_by = target.capacity() - len;
             buf.resize(buf.len() - shrink_by, 0);
-            Ok(true)
         } else {
             // value is NULL
             buf.clear();
-            Ok(false)
+            fetch.not_null = false;
+        }
+        Ok(fetch)
+    }
+
+    /// Hashes arbitrary large binary data from the row, without holding the entire value in memory.
+    /// The value is fetched in chunks of 8 KiB, each passed to the hash function `D` before the
+    /// next one is fetched. Column index starts at `1`. Requires the `digest` feature.
+    ///
+    /// ```
+    /// use digest::Digest;
+    /// use odbc_api::{CursorRow, Error};
+    ///
+    /// /// Hex encoded hash of the document in the first column. `None` if it is `NULL`.
+    /// fn document_hash<D: Digest>(row: &mut CursorRow<'_>) -> Result<Option<String>, Error> {
+    ///     let hash = row.get_digest::<D>(1)?;
+    ///     Ok(hash.map(|hash| format!("{hash:x}")))
+    /// }
+    /// ```
+    ///
+    /// # Return
+    ///
+    /// The hash of the value. `None` if the value is `NULL`.
+    #[cfg(feature = "digest")]
+    pub fn get_digest<D: digest::Digest>(
+        &mut self,
+        col_or_param_num: u16,
+    ) -> Result<Option<digest::Output<D>>, Error> {
+        let mut chunk = vec![0; 8192];
+        let mut hasher = D::new();
+        loop {
+            let mut target = VarCell::<&mut [u8], Binary>::try_from_buffer(
+                chunk.as_mut_slice(),
+                Indicator::NoTotal,
+            )?;
+            self.get_data(col_or_param_num, &mut target)?;
+            target.try_indicator()?;
+            match target.as_bytes() {
+                Some(bytes) => hasher.update(bytes),
+                None => return Ok(None),
+            }
+            if target.is_complete() {
+                return Ok(Some(hasher.finalize()));
+            }
         }
     }
 }
@@ -219,6 +468,7 @@ impl<'s> CursorRow<'s> {
 pub struct CursorImpl<Stmt: AsStatementRef> {
     /// A statement handle in cursor mode.
     statement: Stmt,
+    statistics: StatisticsTracker,
 }
 
 impl<S> Drop for CursorImpl<S>
@@ -228,11 +478,8 @@ where
     fn drop(&mut self) {
         let mut stmt = self.statement.as_stmt_ref();
         if let Err(e) = stmt.close_cursor().into_result(&stmt) {
-            // Avoid panicking, if we already have a panic. We don't want to mask the original
-            // error.
-            if !panicking() {
-                panic!("Unexpected error closing cursor: {e:?}")
-            }
+            // There is no one to return the error to, so we log it rather than panicking.
+            log::error!("Unexpected error closing cursor: {e}")
         }
     }
 }
@@ -252,6 +499,21 @@ impl<S> Cursor for CursorImpl<S>
 where
     S: AsStatementRef,
 {
+    fn next_row(&mut self) -> Result<Option<CursorRow<'_>>, Error> {
+        let mut stmt = self.statement.as_stmt_ref();
+        let row_available = self
+            .statistics
+            .time_odbc_call(|| unsafe { stmt.fetch() })
+            .into_result_bool(&stmt)?;
+        let ret = if row_available {
+            self.statistics.record_batch(1, 0);
+            Some(unsafe { CursorRow::new(stmt) })
+        } else {
+            None
+        };
+        Ok(ret)
+    }
+
     fn bind_buffer<B>(mut self, mut row_set_buffer: B) -> Result<BlockCursor<Self, B>, Error>
     where
         B: RowSetBuffer,
@@ -279,11 +541,24 @@ where
     ///
     /// `statement` must be in Cursor state, for the invariants of this type to hold.
     pub unsafe fn new(statement: S) -> Self {
-        Self { statement }
+        Self {
+            statement,
+            statistics: StatisticsTracker::new(),
+        }
+    }
+
+    /// Statistics about the rows fetched so far using [`Cursor::next_row`]. Rows fetched using a
+    /// block cursor are reported by [`BlockCursor::statistics`] instead.
+    pub fn statistics(&self) -> CursorStatistics {
+        self.statistics.snapshot()
     }
 
-    pub(crate) fn as_sys(&mut self) -> HStmt {
-        self.as_stmt_ref().as_sys()
+    /// Gives back ownership of the statement, without closing the cursor. The statement remains
+    /// in cursor state.
+    pub(crate) fn into_statement(self) -> S {
+        // `CursorImpl` implements `Drop`, which would close the cursor.
+        let this = ManuallyDrop::new(self);
+        unsafe { ptr::read(&this.statement) }
     }
 }
 
@@ -322,6 +597,25 @@ pub unsafe trait RowSetBuffer {
 
     /// Check if the buffer contains any truncated values for variadic sized columns.
     fn has_truncated_values(&self) -> bool;
+
+    /// Invoked by block cursors after each fetch, before any indicator is interpreted. Fails with
+    /// [`Error::InvalidDriverValue`] if the driver wrote an invalid length indicator into the rows
+    /// of the last fetched row set. The default implementation does nothing.
+    fn check_indicators(&self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Invoked by block cursors each time after a row set has been fetched successfully into the
+    /// buffer, before it is handed to the application. Allows implementations to post process the
+    /// fetched values in place. The default implementation does nothing.
+    fn after_fetch(&mut self) {}
+
+    /// Number of payload bytes held by the rows of the last fetched row set, excluding indicators
+    /// and terminating zeroes. Used to gather [`crate::CursorStatistics`]. The default
+    /// implementation returns `0`.
+    fn payload_bytes(&self) -> usize {
+        0
+    }
 }
 
 unsafe impl<T: RowSetBuffer> RowSetBuffer for &mut T {
@@ -344,6 +638,18 @@ unsafe impl<T: RowSetBuffer> RowSetBuffer for &mut T {
     fn has_truncated_values(&self) -> bool {
         (**self).has_truncated_values()
     }
+
+    fn check_indicators(&self) -> Result<(), Error> {
+        (**self).check_indicators()
+    }
+
+    fn after_fetch(&mut self) {
+        (*self).after_fetch()
+    }
+
+    fn payload_bytes(&self) -> usize {
+        (**self).payload_bytes()
+    }
 }
 
 /// In order to safe on network overhead, it is recommended to use block cursors instead of fetching
@@ -368,14 +674,19 @@ unsafe impl<T: RowSetBuffer> RowSetBuffer for &mut T {
 pub struct BlockCursor<C: AsStatementRef, B> {
     buffer: B,
     cursor: C,
+    statistics: StatisticsTracker,
 }
 
 impl<C, B> BlockCursor<C, B>
 where
     C: Cursor,
 {
-    fn new(buffer: B, cursor: C) -> Self {
-        Self { buffer, cursor }
+    pub(crate) fn new(buffer: B, cursor: C) -> Self {
+        Self {
+            buffer,
+            cursor,
+            statistics: StatisticsTracker::new(),
+        }
     }
 
     /// Fills the bound buffer with the next row set.
@@ -440,12 +751,199 @@ where
     {
         let mut stmt = self.cursor.as_stmt_ref();
         unsafe {
-            let result = stmt.fetch();
+            let result = self.statistics.time_odbc_call(|| stmt.fetch());
             let has_row =
                 error_handling_for_fetch(result, stmt, &self.buffer, error_for_truncation)?;
+            if has_row {
+                self.buffer.after_fetch();
+                let num_rows = *self.buffer.mut_num_fetch_rows();
+                self.statistics
+                    .record_batch(num_rows, self.buffer.payload_bytes());
+            }
             Ok(has_row.then_some(&self.buffer))
         }
     }
+
+    /// Statistics about the row sets fetched so far, since the buffer has been bound.
+    ///
+    /// ```
+    /// use odbc_api::{buffers::TextRowSet, Cursor, Error};
+    ///
+    /// fn report_throughput(mut cursor: impl Cursor) -> Result<(), Error> {
+    ///     let buffer = TextRowSet::for_cursor(1000, &mut cursor, Some(4096))?;
+    ///     let mut block_cursor = cursor.bind_buffer(buffer)?;
+    ///     while let Some(_batch) = block_cursor.fetch()? {
+    ///         // ... process batch ...
+    ///     }
+    ///     let stats = block_cursor.statistics();
+    ///     println!(
+    ///         "Fetched {} rows in {} batches ({} bytes). {:?} in ODBC, {:?} processing.",
+    ///         stats.rows_fetched,
+    ///         stats.batches_fetched,
+    ///         stats.bytes_fetched,
+    ///         stats.time_in_odbc,
+    ///         stats.time_outside_odbc()
+    ///     );
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn statistics(&self) -> CursorStatistics {
+        self.statistics.snapshot()
+    }
+
+    /// The bound buffer, holding the row set of the last successful fetch.
+    pub(crate) fn buffer(&self) -> &B {
+        &self.buffer
+    }
+
+    /// Unbinds the buffer from the underlying statement handle and gives back ownership of both the
+    /// cursor and the buffer. This allows e.g. to bind a buffer with a different row capacity and
+    /// continue fetching the remaining rows of the result set.
+    pub fn unbind(self) -> Result<(C, B), Error> {
+        // We want to move the cursor and the buffer out of self, yet `BlockCursor` implements
+        // `Drop`. The drop handler is replaced by the unbinding below.
+        let this = ManuallyDrop::new(self);
+        let buffer = unsafe { ptr::read(&this.buffer) };
+        // Declared after the buffer, so in case of an error the cursor is dropped before the
+        // buffer bound to it.
+        let mut cursor = unsafe { ptr::read(&this.cursor) };
+        let mut stmt = cursor.as_stmt_ref();
+        stmt.unbind_cols().into_result(&stmt)?;
+        unsafe { stmt.set_num_rows_fetched(None) }.into_result(&stmt)?;
+        Ok((cursor, buffer))
+    }
+}
+
+/// How [`BlockCursor::fetch_with_truncation_policy`] handles text and binary values, which are too
+/// large for the buffer bound to their column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TruncationPolicy {
+    /// Fail with [`Error::TooLargeValueForBuffer`].
+    Error,
+    /// Hand the truncated values to the application. Their indicators still reveal the truncation.
+    Ignore,
+    /// Grow the buffers of the affected columns, so all values of the row set fit, and fetch the
+    /// row set again. This requires a scrollable cursor, e.g. a [`crate::CursorType::Static`] one
+    /// requested with [`crate::StatementOptions::with_cursor_type`]. Forward only cursors can not be
+    /// repositioned, and cause the fetch to fail with [`Error::TruncationNotRecoverable`].
+    Grow,
+    /// Treat the maximum length of each text and binary buffer as a hard cap for the values of its
+    /// column. Fail with [`Error::ValueTooLarge`], naming the column and the size of the first
+    /// value exceeding it. This protects services from values of unexpected size, without
+    /// allocating memory for them.
+    Cap,
+}
+
+impl<C> BlockCursor<C, ColumnarAnyBuffer>
+where
+    C: Cursor,
+{
+    /// Fills the bound buffer with the next row set and handles truncated text and binary values
+    /// according to `policy`.
+    ///
+    /// Using [`TruncationPolicy::Grow`] the buffers of columns with truncated values are enlarged
+    /// to the length reported by the driver (or doubled, if the driver does not know it), bound
+    /// again and the current row set is fetched once more, until no value is truncated. The grown
+    /// buffers are kept for the following row sets.
+    ///
+    /// ```no_run
+    /// use odbc_api::{
+    ///     buffers::{BufferDesc, ColumnarAnyBuffer}, Connection, CursorType, Cursor, Error,
+    ///     StatementOptions, TruncationPolicy,
+    /// };
+    ///
+    /// fn print_comments(conn: &Connection<'_>) -> Result<(), Error> {
+    ///     let options = StatementOptions::new().with_cursor_type(CursorType::Static);
+    ///     let cursor = conn
+    ///         .execute_with_options("SELECT comment FROM Reviews", (), &options)?
+    ///         .unwrap();
+    ///     // Most comments are short, so start with a small buffer.
+    ///     let buffer = ColumnarAnyBuffer::from_descs(1000, [BufferDesc::Text { max_str_len: 50 }]);
+    ///     let mut block_cursor = cursor.bind_buffer(buffer)?;
+    ///     while let Some(batch) = block_cursor.fetch_with_truncation_policy(TruncationPolicy::Grow)?
+    ///     {
+    ///         for comment in batch.column(0).as_text_view().unwrap().iter().flatten() {
+    ///             println!("{}", String::from_utf8_lossy(comment));
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn fetch_with_truncation_policy(
+        &mut self,
+        policy: TruncationPolicy,
+    ) -> Result<Option<&ColumnarAnyBuffer>, Error> {
+        match policy {
+            TruncationPolicy::Error => return self.fetch_with_truncation_check(true),
+            TruncationPolicy::Ignore => return self.fetch_with_truncation_check(false),
+            TruncationPolicy::Grow => (),
+            TruncationPolicy::Cap => {
+                let has_row = self.fetch_with_truncation_check(false)?.is_some();
+                if let Some(error) = self.buffer.value_too_large() {
+                    return Err(error);
+                }
+                return Ok(has_row.then_some(&self.buffer));
+            }
+        }
+        let mut stmt = self.cursor.as_stmt_ref();
+        let mut result = self.statistics.time_odbc_call(|| unsafe { stmt.fetch() });
+        let has_row = loop {
+            let with_info = result == SqlResult::SuccessWithInfo(());
+            let stmt = self.cursor.as_stmt_ref();
+            let has_row = error_handling_for_fetch(result, stmt, &self.buffer, false)?;
+            if !(has_row && with_info && self.buffer.grow_truncated_columns()) {
+                break has_row;
+            }
+            result = self.refetch_row_set()?;
+        };
+        if has_row {
+            self.buffer.after_fetch();
+            let num_rows = *self.buffer.mut_num_fetch_rows();
+            self.statistics
+                .record_batch(num_rows, self.buffer.payload_bytes());
+        }
+        Ok(has_row.then_some(&self.buffer))
+    }
+
+    /// Binds the buffer again, after it has been reallocated, and fetches the current row set into
+    /// it once more.
+    fn refetch_row_set(&mut self) -> Result<SqlResult<()>, Error> {
+        unsafe { bind_row_set_buffer_to_statement(self.cursor.as_stmt_ref(), &mut self.buffer)? };
+        let mut stmt = self.cursor.as_stmt_ref();
+        let result = self
+            .statistics
+            .time_odbc_call(|| unsafe { stmt.fetch_scroll(FetchOrientation::Relative, 0) });
+        if let SqlResult::Error { .. } = result {
+            result
+                .into_result(&stmt)
+                .provide_context_for_diagnostic(|record, _function| {
+                    Error::TruncationNotRecoverable { record }
+                })?;
+        }
+        Ok(result)
+    }
+
+    /// Fetches the next row set and appends its rows to `rows`, using `extractor` to convert them.
+    /// `false` if all row sets have been extracted, in which case `rows` is left untouched. Fails
+    /// with [`Error::TooLargeValueForBuffer`] if a value has been truncated, rather than appending
+    /// rows with truncated values.
+    ///
+    /// See [`BatchExtractor`] for an example.
+    pub fn fetch_into<T>(
+        &mut self,
+        rows: &mut Vec<T>,
+        extractor: &BatchExtractor<T>,
+    ) -> Result<bool, Error>
+    where
+        T: Default,
+    {
+        let batch = match self.fetch_with_truncation_check(true)? {
+            Some(batch) => batch,
+            None => return Ok(false),
+        };
+        extractor.extract(batch, rows)?;
+        Ok(true)
+    }
 }
 
 impl<C, B> Drop for BlockCursor<C, B>
@@ -460,11 +958,8 @@ where
                 .into_result(&stmt)
                 .and_then(|()| stmt.set_num_rows_fetched(None).into_result(&stmt))
             {
-                // Avoid panicking, if we already have a panic. We don't want to mask the original
-                // error.
-                if !panicking() {
-                    panic!("Unexpected error unbinding columns: {e:?}")
-                }
+                // There is no one to return the error to, so we log it rather than panicking.
+                log::error!("Unexpected error unbinding columns: {e}")
             }
         }
     }
@@ -529,11 +1024,8 @@ where
     fn drop(&mut self) {
         let mut stmt = self.statement.as_stmt_ref();
         if let Err(e) = stmt.close_cursor().into_result(&stmt) {
-            // Avoid panicking, if we already have a panic. We don't want to mask the original
-            // error.
-            if !panicking() {
-                panic!("Unexpected error closing cursor: {e:?}")
-            }
+            // There is no one to return the error to, so we log it rather than panicking.
+            log::error!("Unexpected error closing cursor: {e}")
         }
     }
 }
@@ -591,13 +1083,16 @@ where
         let mut stmt = self.cursor.as_stmt_ref();
         let result = unsafe { wait_for(|| stmt.fetch(), &mut sleep).await };
         let has_row = error_handling_for_fetch(result, stmt, &self.buffer, error_for_truncation)?;
+        if has_row {
+            self.buffer.after_fetch();
+        }
         Ok(has_row.then_some(&self.buffer))
     }
 }
 
 /// Binds a row set buffer to a statment. Implementation is shared between synchronous and
 /// asynchronous cursors.
-unsafe fn bind_row_set_buffer_to_statement(
+pub(crate) unsafe fn bind_row_set_buffer_to_statement(
     mut stmt: StatementRef<'_>,
     row_set_buffer: &mut impl RowSetBuffer,
 ) -> Result<(), Error> {
@@ -629,6 +1124,9 @@ fn error_handling_for_fetch(
     buffer: &impl RowSetBuffer,
     error_for_truncation: bool,
 ) -> Result<bool, Error> {
+    if matches!(result, SqlResult::Success(()) | SqlResult::SuccessWithInfo(())) {
+        buffer.check_indicators()?;
+    }
     if error_for_truncation
         && result == SqlResult::SuccessWithInfo(())
         && buffer.has_truncated_values()
@@ -665,11 +1163,8 @@ where
                 .into_result(&stmt)
                 .and_then(|()| stmt.set_num_rows_fetched(None).into_result(&stmt))
             {
-                // Avoid panicking, if we already have a panic. We don't want to mask the original
-                // error.
-                if !panicking() {
-                    panic!("Unexpected error unbinding columns: {e:?}")
-                }
+                // There is no one to return the error to, so we log it rather than panicking.
+                log::error!("Unexpected error unbinding columns: {e}")
             }
         }
     }
@@ -0,0 +1,66 @@
+use std::time::{Duration, Instant};
+
+/// Statistics about fetching a result set, as gathered by [`crate::CursorImpl`] and
+/// [`crate::BlockCursor`]. Allows pipelines to report throughput without wrapping each fetch into
+/// timers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CursorStatistics {
+    /// Number of rows fetched so far.
+    pub rows_fetched: usize,
+    /// Number of calls to fetch which did yield rows. Equals `rows_fetched` for cursors fetching
+    /// row by row.
+    pub batches_fetched: usize,
+    /// Payload bytes fetched into bound buffers, excluding indicators and terminating zeroes. Only
+    /// gathered by block cursors, with buffers reporting their payload. See
+    /// [`crate::RowSetBuffer::payload_bytes`].
+    pub bytes_fetched: usize,
+    /// Time spent inside calls to ODBC functions for fetching data.
+    pub time_in_odbc: Duration,
+    /// Time passed since the statistics started to be gathered. For cursors this is when they have
+    /// been created, for block cursors when the buffer has been bound.
+    pub elapsed: Duration,
+}
+
+impl CursorStatistics {
+    /// Time spent outside of fetching data, i.e. processing the data in the application.
+    pub fn time_outside_odbc(&self) -> Duration {
+        self.elapsed.saturating_sub(self.time_in_odbc)
+    }
+}
+
+/// Gathers [`CursorStatistics`].
+#[derive(Debug)]
+pub(crate) struct StatisticsTracker {
+    start: Instant,
+    statistics: CursorStatistics,
+}
+
+impl StatisticsTracker {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            statistics: CursorStatistics::default(),
+        }
+    }
+
+    /// Executes `odbc_call` and accounts the time spent to [`CursorStatistics::time_in_odbc`].
+    pub fn time_odbc_call<T>(&mut self, odbc_call: impl FnOnce() -> T) -> T {
+        let before = Instant::now();
+        let result = odbc_call();
+        self.statistics.time_in_odbc += before.elapsed();
+        result
+    }
+
+    pub fn record_batch(&mut self, num_rows: usize, num_bytes: usize) {
+        self.statistics.rows_fetched += num_rows;
+        self.statistics.batches_fetched += 1;
+        self.statistics.bytes_fetched += num_bytes;
+    }
+
+    pub fn snapshot(&self) -> CursorStatistics {
+        CursorStatistics {
+            elapsed: self.start.elapsed(),
+            ..self.statistics
+        }
+    }
+}
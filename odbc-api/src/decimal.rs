@@ -0,0 +1,191 @@
+use thiserror::Error as ThisError;
+
+/// Error returned if the text representation of a decimal fetched from a data source can not be
+/// normalized.
+#[derive(Debug, ThisError, PartialEq, Eq, Clone, Copy)]
+pub enum DecimalTextError {
+    /// Text is not a decimal number in any of the formats emitted by drivers.
+    #[error("Text is not a valid decimal number.")]
+    Invalid,
+    /// Value has more significant fractional digits than the requested scale.
+    #[error("Decimal can not be represented with the requested scale without loss of precision.")]
+    Inexact,
+    /// Scaled value does not fit into a 128 Bit integer.
+    #[error("Decimal is out of range for a 128 Bit integer.")]
+    Overflow,
+}
+
+/// Normalizes the text representation of a decimal, as emitted by different drivers, into a
+/// canonical one. This allows to compare values fetched as text, regardless of the driver or locale
+/// of the data source.
+///
+/// Accepted are an optional sign, `.` or `,` as decimal separator and an optional exponent. Thousand
+/// separators are rejected, since in combination with a `,` as decimal separator they are
+/// ambiguous. The canonical form has no `+` sign, no leading zeros in the integer part, no trailing
+/// zeros in the fractional part, `.` as decimal separator and no exponent. Zero is always
+/// represented as `0`.
+///
+/// ```
+/// use odbc_api::canonical_decimal;
+///
+/// assert_eq!("1.5", canonical_decimal("1,5").unwrap());
+/// assert_eq!("1.5", canonical_decimal("+1.50").unwrap());
+/// assert_eq!("1.5", canonical_decimal("1.5E0").unwrap());
+/// assert_eq!("-0.015", canonical_decimal("-1.5e-2").unwrap());
+/// ```
+pub fn canonical_decimal(text: &str) -> Result<String, DecimalTextError> {
+    let decimal = ParsedDecimal::parse(text)?;
+    let digits = decimal.digits.as_str();
+    if digits.is_empty() {
+        return Ok("0".to_owned());
+    }
+    let mut canonical = String::with_capacity(digits.len() + 2);
+    if decimal.negative {
+        canonical.push('-');
+    }
+    if decimal.exponent >= 0 {
+        canonical.push_str(digits);
+        canonical.extend((0..decimal.exponent).map(|_| '0'));
+    } else {
+        let num_fractional = decimal.exponent.unsigned_abs() as usize;
+        if num_fractional >= digits.len() {
+            canonical.push_str("0.");
+            canonical.extend((digits.len()..num_fractional).map(|_| '0'));
+            canonical.push_str(digits);
+        } else {
+            let (integer, fraction) = digits.split_at(digits.len() - num_fractional);
+            canonical.push_str(integer);
+            canonical.push('.');
+            canonical.push_str(fraction);
+        }
+    }
+    Ok(canonical)
+}
+
+/// Parses the text representation of a decimal, as emitted by different drivers, into an integer
+/// holding the value multiplied by `10^scale`. E.g. `1,5` with a scale of `2` yields `150`. Accepts
+/// the same formats as [`canonical_decimal`].
+///
+/// ```
+/// use odbc_api::{decimal_to_scaled_integer, DecimalTextError};
+///
+/// assert_eq!(Ok(150), decimal_to_scaled_integer("+1,50", 2));
+/// assert_eq!(Ok(-1500), decimal_to_scaled_integer("-1.5E1", 2));
+/// assert_eq!(Err(DecimalTextError::Inexact), decimal_to_scaled_integer("1.555", 2));
+/// ```
+pub fn decimal_to_scaled_integer(text: &str, scale: u8) -> Result<i128, DecimalTextError> {
+    let decimal = ParsedDecimal::parse(text)?;
+    let exponent = decimal.exponent + i32::from(scale);
+    if exponent < 0 {
+        // Trailing zeros have been removed, so we would discard significant digits.
+        return Err(DecimalTextError::Inexact);
+    }
+    let mut value: i128 = 0;
+    for digit in decimal.digits.bytes() {
+        value = value
+            .checked_mul(10)
+            .and_then(|value| value.checked_add(i128::from(digit - b'0')))
+            .ok_or(DecimalTextError::Overflow)?;
+    }
+    for _ in 0..exponent {
+        value = value.checked_mul(10).ok_or(DecimalTextError::Overflow)?;
+    }
+    Ok(if decimal.negative { -value } else { value })
+}
+
+/// Decimal with value `digits * 10^exponent`.
+struct ParsedDecimal {
+    negative: bool,
+    /// Significant digits without leading or trailing zeros. Empty for zero.
+    digits: String,
+    exponent: i32,
+}
+
+impl ParsedDecimal {
+    fn parse(text: &str) -> Result<Self, DecimalTextError> {
+        let text = text.trim();
+        let (negative, rest) = match text.as_bytes().first() {
+            Some(b'-') => (true, &text[1..]),
+            Some(b'+') => (false, &text[1..]),
+            _ => (false, text),
+        };
+        let (mantissa, exponent) = match rest.find(['e', 'E']) {
+            Some(pos) => (&rest[..pos], Some(&rest[pos + 1..])),
+            None => (rest, None),
+        };
+        let (integer, fraction) = match mantissa.find(['.', ',']) {
+            Some(pos) => (&mantissa[..pos], &mantissa[pos + 1..]),
+            None => (mantissa, ""),
+        };
+        let is_digits = |s: &str| s.bytes().all(|c| c.is_ascii_digit());
+        if integer.is_empty() && fraction.is_empty() || !is_digits(integer) || !is_digits(fraction)
+        {
+            return Err(DecimalTextError::Invalid);
+        }
+        let exponent = match exponent {
+            // Limit the exponent, so canonical representations stay reasonably sized.
+            Some(exponent) => exponent
+                .parse::<i16>()
+                .map_err(|_| DecimalTextError::Invalid)?,
+            None => 0,
+        };
+
+        let digits = format!("{integer}{fraction}");
+        let digits = digits.trim_start_matches('0');
+        let significant = digits.trim_end_matches('0');
+        if significant.is_empty() {
+            return Ok(ParsedDecimal {
+                negative: false,
+                digits: String::new(),
+                exponent: 0,
+            });
+        }
+        let exponent =
+            i32::from(exponent) - fraction.len() as i32 + (digits.len() - significant.len()) as i32;
+        Ok(ParsedDecimal {
+            negative,
+            digits: significant.to_owned(),
+            exponent,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{canonical_decimal, decimal_to_scaled_integer, DecimalTextError};
+
+    #[test]
+    fn canonical_representations() {
+        assert_eq!("1.5", canonical_decimal("1,5").unwrap());
+        assert_eq!("1.5", canonical_decimal(" +001.500 ").unwrap());
+        assert_eq!("1.5", canonical_decimal("1.5E0").unwrap());
+        assert_eq!("1500", canonical_decimal("1.5e3").unwrap());
+        assert_eq!("-0.0015", canonical_decimal("-1.5E-3").unwrap());
+        assert_eq!("0.5", canonical_decimal(".5").unwrap());
+        assert_eq!("5", canonical_decimal("5.").unwrap());
+        assert_eq!("0", canonical_decimal("-0.000").unwrap());
+        assert_eq!("100", canonical_decimal("100").unwrap());
+    }
+
+    #[test]
+    fn reject_invalid_decimals() {
+        for text in ["", "-", ".", "1.000,5", "1e", "1.5x", "abc", "1e99999"] {
+            assert_eq!(Err(DecimalTextError::Invalid), canonical_decimal(text));
+        }
+    }
+
+    #[test]
+    fn scaled_integers() {
+        assert_eq!(Ok(150), decimal_to_scaled_integer("1,5", 2));
+        assert_eq!(Ok(-15), decimal_to_scaled_integer("-1.50", 1));
+        assert_eq!(Ok(0), decimal_to_scaled_integer("0.00", 0));
+        assert_eq!(
+            Err(DecimalTextError::Inexact),
+            decimal_to_scaled_integer("1.05", 1)
+        );
+        assert_eq!(
+            Err(DecimalTextError::Overflow),
+            decimal_to_scaled_integer("1e100", 0)
+        );
+    }
+}
@@ -0,0 +1,337 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashSet},
+    env,
+    fs::{self, File, OpenOptions},
+    hash::Hasher,
+    io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::PathBuf,
+    process,
+    sync::atomic::Ordering,
+};
+
+use crate::{
+    buffers::{CellValue, ColumnarAnyBuffer, RowEq},
+    handles::AsStatementRef,
+    spooling_cursor::NUM_SPILL_FILES,
+    BlockCursor, Cursor, Error,
+};
+
+/// Eliminates duplicate rows from the batches fetched by a block cursor, emitting only the first
+/// occurrence of each key. This is an alternative to `SELECT DISTINCT` for data sources, for which
+/// deduplicating on the server is too expensive, or which emit duplicates e.g. due to replication.
+///
+/// Rows are identified by the values of their key columns, compared with the semantics of a
+/// [`RowEq`]. Instead of the keys themselves, a 128 bit digest of each key is remembered, so the
+/// memory used does not depend on the size of the keys. Two distinct keys sharing a digest would
+/// cause the second one to be dropped, yet this is astronomically unlikely. Once more digests than
+/// set with [`Self::with_memory_limit`] are held in memory, they are spilled into a sorted
+/// temporary file, which is looked up for later rows.
+///
+/// ```
+/// use odbc_api::{
+///     buffers::{BufferDesc, ColumnarAnyBuffer}, Cursor, DeduplicatingCursor, Error,
+/// };
+///
+/// fn count_distinct_customers(cursor: impl Cursor) -> Result<usize, Error> {
+///     // Columns: customer_id, name
+///     let descs = [
+///         BufferDesc::I64 { nullable: false },
+///         BufferDesc::Text { max_str_len: 255 },
+///     ];
+///     let buffer = ColumnarAnyBuffer::from_descs(1000, descs);
+///     // Identify rows by `customer_id` only.
+///     let mut deduplicated = DeduplicatingCursor::new(cursor.bind_buffer(buffer)?, vec![0])
+///         .with_memory_limit(1_000_000);
+///     let mut num_distinct = 0;
+///     while let Some(rows) = deduplicated.fetch()? {
+///         num_distinct += rows.num_rows();
+///     }
+///     Ok(num_distinct)
+/// }
+/// ```
+pub struct DeduplicatingCursor<C: AsStatementRef> {
+    block_cursor: BlockCursor<C, ColumnarAnyBuffer>,
+    /// Buffer indices of the key columns. Empty if all columns form the key.
+    key_columns: Vec<usize>,
+    row_eq: RowEq,
+    seen: DigestSet,
+    /// Indices of the rows within the current batch, which are first occurrences.
+    first_occurrences: Vec<usize>,
+    num_duplicates: usize,
+}
+
+impl<C> DeduplicatingCursor<C>
+where
+    C: Cursor,
+{
+    /// Deduplicates the rows fetched by `block_cursor` by the columns with the buffer indices in
+    /// `key_columns`. If `key_columns` is empty, all columns of the buffer form the key. Keys are
+    /// compared with [`RowEq::new`] and all digests are kept in memory.
+    pub fn new(block_cursor: BlockCursor<C, ColumnarAnyBuffer>, key_columns: Vec<usize>) -> Self {
+        Self {
+            block_cursor,
+            key_columns,
+            row_eq: RowEq::new(),
+            seen: DigestSet::new(None),
+            first_occurrences: Vec::new(),
+            num_duplicates: 0,
+        }
+    }
+
+    /// Determines which keys are considered equal, e.g. to ignore trailing spaces of `CHAR`
+    /// columns. The numeric tolerance is not taken into account, since keys are compared by their
+    /// digest.
+    pub fn with_row_eq(mut self, row_eq: RowEq) -> Self {
+        self.row_eq = row_eq;
+        self
+    }
+
+    /// Holds at most `max_digests` digests in memory, which take 16 bytes each, not accounting for
+    /// the overhead of the hash set. Beyond that, digests are spilled into a temporary file in
+    /// [`std::env::temp_dir`], which is deleted once the cursor is dropped.
+    pub fn with_memory_limit(mut self, max_digests: usize) -> Self {
+        self.seen.max_in_memory = Some(max_digests);
+        self
+    }
+
+    /// Fetches batches until one of them contains at least one key which has not been seen before.
+    /// Fails with [`Error::TooLargeValueForBuffer`] if a value has been truncated, since truncated
+    /// keys could be mistaken for duplicates.
+    ///
+    /// # Return
+    ///
+    /// `None` if the result set is consumed. Otherwise the first occurrences within the fetched
+    /// batch.
+    pub fn fetch(&mut self) -> Result<Option<DistinctRows<'_>>, Error> {
+        loop {
+            self.first_occurrences.clear();
+            let batch = match self.block_cursor.fetch_with_truncation_check(true)? {
+                Some(batch) => batch,
+                None => return Ok(None),
+            };
+            let num_cols = batch.num_cols();
+            let mut key = Vec::new();
+            for row_index in 0..batch.num_rows() {
+                key.clear();
+                if self.key_columns.is_empty() {
+                    key.extend(
+                        (0..num_cols).map(|buffer_index| cell(batch, row_index, buffer_index)),
+                    );
+                } else {
+                    key.extend(
+                        self.key_columns
+                            .iter()
+                            .map(|&buffer_index| cell(batch, row_index, buffer_index)),
+                    );
+                }
+                let digest = digest(&self.row_eq, &key);
+                if self.seen.insert(digest).map_err(Error::SpillFile)? {
+                    self.first_occurrences.push(row_index);
+                } else {
+                    self.num_duplicates += 1;
+                }
+            }
+            if !self.first_occurrences.is_empty() {
+                break;
+            }
+        }
+        // Fetch succeeded in the loop above, so the buffer holds the current batch.
+        Ok(Some(DistinctRows {
+            batch: self.block_cursor.buffer(),
+            row_indices: &self.first_occurrences,
+        }))
+    }
+
+    /// Number of rows dropped so far, because their key has been seen before.
+    pub fn num_duplicates(&self) -> usize {
+        self.num_duplicates
+    }
+
+    /// `true` if digests have been spilled to disk.
+    pub fn has_spilled(&self) -> bool {
+        self.seen.spill.is_some()
+    }
+
+    /// Unbinds the buffer and gives back ownership of the cursor and the buffer.
+    pub fn unbind(self) -> Result<(C, ColumnarAnyBuffer), Error> {
+        self.block_cursor.unbind()
+    }
+}
+
+/// First occurrences of keys within a batch fetched by a [`DeduplicatingCursor`].
+#[derive(Clone, Copy)]
+pub struct DistinctRows<'a> {
+    batch: &'a ColumnarAnyBuffer,
+    row_indices: &'a [usize],
+}
+
+impl<'a> DistinctRows<'a> {
+    /// The whole batch, including the duplicates. Only the rows at [`Self::row_indices`] are
+    /// first occurrences.
+    pub fn batch(&self) -> &'a ColumnarAnyBuffer {
+        self.batch
+    }
+
+    /// Indices of the rows within [`Self::batch`] which are first occurrences, in ascending order.
+    pub fn row_indices(&self) -> &'a [usize] {
+        self.row_indices
+    }
+
+    /// Number of first occurrences in this batch.
+    pub fn num_rows(&self) -> usize {
+        self.row_indices.len()
+    }
+}
+
+fn cell(batch: &ColumnarAnyBuffer, row_index: usize, buffer_index: usize) -> Option<CellValue<'_>> {
+    batch
+        .get(row_index, buffer_index)
+        .expect("Every column buffer can be read as `CellValue`.")
+}
+
+/// 128 bit digest of `key`, combining two independently seeded hashes.
+fn digest(row_eq: &RowEq, key: &[Option<CellValue<'_>>]) -> u128 {
+    let mut low = DefaultHasher::new();
+    let mut high = DefaultHasher::new();
+    high.write_u8(0xff);
+    row_eq.hash_row(key, &mut low);
+    row_eq.hash_row(key, &mut high);
+    (u128::from(high.finish()) << 64) | u128::from(low.finish())
+}
+
+/// Set of the digests seen so far. Held in memory, up to the limit. Beyond that all digests held
+/// in memory are merged into a sorted spill file.
+struct DigestSet {
+    in_memory: HashSet<u128>,
+    max_in_memory: Option<usize>,
+    spill: Option<SpillFile>,
+}
+
+/// Sorted digests, 16 bytes each in big endian.
+struct SpillFile {
+    file: File,
+    path: PathBuf,
+    num_digests: u64,
+}
+
+impl DigestSet {
+    fn new(max_in_memory: Option<usize>) -> Self {
+        Self {
+            in_memory: HashSet::new(),
+            max_in_memory,
+            spill: None,
+        }
+    }
+
+    /// `true` if the digest has not been seen before.
+    fn insert(&mut self, digest: u128) -> io::Result<bool> {
+        if self.in_memory.contains(&digest) {
+            return Ok(false);
+        }
+        if let Some(spill) = &mut self.spill {
+            if spill.contains(digest)? {
+                return Ok(false);
+            }
+        }
+        self.in_memory.insert(digest);
+        if self
+            .max_in_memory
+            .is_some_and(|max| self.in_memory.len() > max)
+        {
+            self.spill()?;
+        }
+        Ok(true)
+    }
+
+    /// Merges the digests in memory with the ones already spilled into a new spill file.
+    fn spill(&mut self) -> io::Result<()> {
+        let mut digests: Vec<u128> = self.in_memory.drain().collect();
+        digests.sort_unstable();
+        let path = env::temp_dir().join(format!(
+            "odbc-api-dedup-{}-{}",
+            process::id(),
+            NUM_SPILL_FILES.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        let mut merged = SpillFile {
+            file,
+            path,
+            num_digests: 0,
+        };
+        let mut writer = BufWriter::new(&merged.file);
+        let mut pending = digests.into_iter().peekable();
+        if let Some(previous) = &mut self.spill {
+            previous.file.rewind()?;
+            let mut reader = BufReader::new(&previous.file);
+            for _ in 0..previous.num_digests {
+                let spilled = read_digest(&mut reader)?;
+                while let Some(digest) = pending.next_if(|&digest| digest < spilled) {
+                    writer.write_all(&digest.to_be_bytes())?;
+                    merged.num_digests += 1;
+                }
+                writer.write_all(&spilled.to_be_bytes())?;
+                merged.num_digests += 1;
+            }
+        }
+        for digest in pending {
+            writer.write_all(&digest.to_be_bytes())?;
+            merged.num_digests += 1;
+        }
+        writer.flush()?;
+        drop(writer);
+        // Dropping the previous spill file deletes it.
+        self.spill = Some(merged);
+        Ok(())
+    }
+}
+
+impl SpillFile {
+    /// Binary search for `digest` within the file.
+    fn contains(&mut self, digest: u128) -> io::Result<bool> {
+        let (mut low, mut high) = (0, self.num_digests);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            self.file.seek(SeekFrom::Start(mid * 16))?;
+            let candidate = read_digest(&mut self.file)?;
+            match candidate.cmp(&digest) {
+                std::cmp::Ordering::Less => low = mid + 1,
+                std::cmp::Ordering::Greater => high = mid,
+                std::cmp::Ordering::Equal => return Ok(true),
+            }
+        }
+        Ok(false)
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn read_digest(reader: &mut impl Read) -> io::Result<u128> {
+    let mut bytes = [0; 16];
+    reader.read_exact(&mut bytes)?;
+    Ok(u128::from_be_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DigestSet;
+
+    #[test]
+    fn spilled_digests_are_remembered() {
+        let mut set = DigestSet::new(Some(2));
+
+        let first: Vec<bool> = [5, 1, 3, 9, 7].map(|d| set.insert(d).unwrap()).into();
+        let second: Vec<bool> = [1, 9, 7, 4, 5].map(|d| set.insert(d).unwrap()).into();
+
+        assert!(set.spill.is_some());
+        assert_eq!(vec![true; 5], first);
+        assert_eq!(vec![false, false, false, true, false], second);
+    }
+}
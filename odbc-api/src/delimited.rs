@@ -0,0 +1,339 @@
+//! Writes result sets as delimited text, e.g. CSV, or files consumable by bulk loaders like `bcp`
+//! or `COPY`. See [`crate::Cursor::write_delimited`].
+//!
+//! All columns are fetched as text, so the values are formatted by the driver, in the canonical
+//! text representation of ODBC. Values are written straight from the bound buffer into the writer.
+
+use std::io::{self, BufWriter, Write};
+
+use crate::{buffers::TextRowSet, Cursor, Error};
+
+/// When values are surrounded by quotes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quoting {
+    /// Only values containing the delimiter, the quote, a line break or which would be mistaken
+    /// for `NULL` are quoted. Quotes within values are doubled.
+    Necessary,
+    /// Every value other than `NULL` is quoted. Quotes within values are doubled.
+    Always,
+    /// Values are never quoted. Use [`DelimitedOptions::with_backslash_escapes`] to tell special
+    /// characters in values apart from delimiters.
+    Never,
+}
+
+/// Encoding of the written text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEncoding {
+    /// UTF-8, e.g. for `COPY` or `bcp -c -C 65001`.
+    Utf8,
+    /// UTF-16 little endian, without byte order mark, e.g. for `bcp -w`.
+    Utf16Le,
+}
+
+/// Options for writing result sets as delimited text. See [`crate::Cursor::write_delimited`].
+///
+/// ```
+/// use odbc_api::delimited::{DelimitedOptions, Quoting};
+///
+/// // Text format of PostgreSQL `COPY`.
+/// let copy = DelimitedOptions::new()
+///     .with_delimiter(b'\t')
+///     .with_quoting(Quoting::Never)
+///     .with_backslash_escapes(true)
+///     .with_null("\\N")
+///     .with_header(false);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DelimitedOptions {
+    delimiter: u8,
+    record_terminator: String,
+    quote: u8,
+    quoting: Quoting,
+    backslash_escapes: bool,
+    null: String,
+    header: bool,
+    encoding: TextEncoding,
+    batch_size: usize,
+    max_str_len: Option<usize>,
+}
+
+impl Default for DelimitedOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            record_terminator: "\n".to_owned(),
+            quote: b'"',
+            quoting: Quoting::Necessary,
+            backslash_escapes: false,
+            null: String::new(),
+            header: true,
+            encoding: TextEncoding::Utf8,
+            batch_size: 1000,
+            max_str_len: Some(4096),
+        }
+    }
+}
+
+impl DelimitedOptions {
+    /// CSV as described in RFC 4180, except for records being terminated with `\n`. Starts with a
+    /// header of column names and writes `NULL` as an empty field. Rows are fetched in batches of
+    /// 1000. Values hold at most 4096 bytes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Separates the fields of a record. `,` by default. Must be an ASCII character.
+    pub fn with_delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Terminates each record, including the last one. `\n` by default.
+    pub fn with_record_terminator(mut self, record_terminator: impl Into<String>) -> Self {
+        self.record_terminator = record_terminator.into();
+        self
+    }
+
+    /// Character values are quoted with. `"` by default. Must be an ASCII character.
+    pub fn with_quote(mut self, quote: u8) -> Self {
+        self.quote = quote;
+        self
+    }
+
+    /// When values are quoted. [`Quoting::Necessary`] by default.
+    pub fn with_quoting(mut self, quoting: Quoting) -> Self {
+        self.quoting = quoting;
+        self
+    }
+
+    /// Escapes backslashes, delimiters, tabs and line breaks within values with a backslash, like
+    /// the text format of `COPY` expects it. `false` by default.
+    pub fn with_backslash_escapes(mut self, backslash_escapes: bool) -> Self {
+        self.backslash_escapes = backslash_escapes;
+        self
+    }
+
+    /// Written in place of `NULL`. Empty by default.
+    pub fn with_null(mut self, null: impl Into<String>) -> Self {
+        self.null = null.into();
+        self
+    }
+
+    /// Whether the first record holds the names of the columns. `true` by default.
+    pub fn with_header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Encoding of the written text. UTF-8 by default.
+    pub fn with_encoding(mut self, encoding: TextEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Number of rows fetched at once.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Upper bound for the length of values in bytes. The size reported by the driver is used if it
+    /// is smaller. `None` to always use the size reported by the driver, which fails for columns
+    /// without an upper bound, like `VARCHAR(max)`. Longer values are not truncated, but cause
+    /// [`crate::Cursor::write_delimited`] to fail with [`Error::TooLargeValueForBuffer`].
+    pub fn with_max_str_len(mut self, max_str_len: Option<usize>) -> Self {
+        self.max_str_len = max_str_len;
+        self
+    }
+}
+
+/// Fetches all rows of `cursor` and writes them to `writer` as delimited text. Returns the number of
+/// rows written, excluding the header. See [`crate::Cursor::write_delimited`].
+///
+/// ```no_run
+/// use std::{fs::File, io::BufWriter};
+/// use odbc_api::{delimited::{write_delimited, DelimitedOptions}, Connection, Error};
+///
+/// fn export(conn: &Connection<'_>) -> Result<usize, Error> {
+///     let file = File::create("movies.csv").unwrap();
+///     let cursor = conn.execute("SELECT title, year FROM Movies", ())?.unwrap();
+///     write_delimited(cursor, BufWriter::new(file), &DelimitedOptions::new())
+/// }
+/// ```
+pub fn write_delimited(
+    mut cursor: impl Cursor,
+    writer: impl Write,
+    options: &DelimitedOptions,
+) -> Result<usize, Error> {
+    let mut out = Output::new(writer, options);
+    if options.header {
+        let names = cursor.column_names()?.collect::<Result<Vec<_>, _>>()?;
+        out.write_record(names.iter().map(|name| Some(name.as_bytes())))
+            .map_err(Error::FailedWritingDelimited)?;
+    }
+    let buffer = TextRowSet::for_cursor(options.batch_size, &mut cursor, options.max_str_len)?;
+    let mut row_set_cursor = cursor.bind_buffer(buffer)?;
+    let mut num_rows = 0;
+    while let Some(batch) = row_set_cursor.fetch_with_truncation_check(true)? {
+        for row_index in 0..batch.num_rows() {
+            let values = (0..batch.num_cols()).map(|col_index| batch.at(col_index, row_index));
+            out.write_record(values)
+                .map_err(Error::FailedWritingDelimited)?;
+        }
+        num_rows += batch.num_rows();
+    }
+    out.flush().map_err(Error::FailedWritingDelimited)?;
+    Ok(num_rows)
+}
+
+/// Writes records to the underlying writer in the configured format and encoding.
+struct Output<'o, W: Write> {
+    writer: BufWriter<W>,
+    options: &'o DelimitedOptions,
+    /// Holds the encoded text, if it is transcoded to UTF-16.
+    wide: Vec<u8>,
+}
+
+impl<'o, W> Output<'o, W>
+where
+    W: Write,
+{
+    fn new(writer: W, options: &'o DelimitedOptions) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+            options,
+            wide: Vec::new(),
+        }
+    }
+
+    fn write_record<'v>(
+        &mut self,
+        values: impl Iterator<Item = Option<&'v [u8]>>,
+    ) -> io::Result<()> {
+        let options = self.options;
+        for (index, value) in values.enumerate() {
+            if index != 0 {
+                self.put(&[options.delimiter])?;
+            }
+            match value {
+                None => self.put(options.null.as_bytes())?,
+                Some(value) => self.write_value(value)?,
+            }
+        }
+        self.put(options.record_terminator.as_bytes())
+    }
+
+    fn write_value(&mut self, value: &[u8]) -> io::Result<()> {
+        let options = self.options;
+        let quoted = match options.quoting {
+            Quoting::Always => true,
+            Quoting::Never => false,
+            Quoting::Necessary => {
+                value == options.null.as_bytes()
+                    || value.iter().any(|&byte| {
+                        byte == options.delimiter
+                            || byte == options.quote
+                            || byte == b'\n'
+                            || byte == b'\r'
+                    })
+            }
+        };
+        if quoted {
+            self.put(&[options.quote])?;
+        }
+        let mut start = 0;
+        for (pos, &byte) in value.iter().enumerate() {
+            let escaped: Option<&[u8]> = if quoted && byte == options.quote {
+                Some(&[options.quote, options.quote])
+            } else if options.backslash_escapes {
+                match byte {
+                    b'\\' => Some(b"\\\\"),
+                    b'\n' => Some(b"\\n"),
+                    b'\r' => Some(b"\\r"),
+                    b'\t' => Some(b"\\t"),
+                    _ if byte == options.delimiter => Some(&[b'\\', options.delimiter]),
+                    _ => None,
+                }
+            } else {
+                None
+            };
+            if let Some(escaped) = escaped {
+                self.put(&value[start..pos])?;
+                self.put(escaped)?;
+                start = pos + 1;
+            }
+        }
+        self.put(&value[start..])?;
+        if quoted {
+            self.put(&[options.quote])?;
+        }
+        Ok(())
+    }
+
+    /// Writes `text` in the configured encoding.
+    fn put(&mut self, text: &[u8]) -> io::Result<()> {
+        match self.options.encoding {
+            TextEncoding::Utf8 => self.writer.write_all(text),
+            TextEncoding::Utf16Le => {
+                self.wide.clear();
+                for unit in String::from_utf8_lossy(text).encode_utf16() {
+                    self.wide.extend_from_slice(&unit.to_le_bytes());
+                }
+                self.writer.write_all(&self.wide)
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DelimitedOptions, Output, Quoting, TextEncoding};
+
+    fn render(options: &DelimitedOptions, values: &[Option<&str>]) -> Vec<u8> {
+        let mut text = Vec::new();
+        let mut out = Output::new(&mut text, options);
+        out.write_record(values.iter().map(|value| value.map(str::as_bytes)))
+            .unwrap();
+        out.flush().unwrap();
+        drop(out);
+        text
+    }
+
+    #[test]
+    fn quote_only_where_necessary() {
+        let options = DelimitedOptions::new();
+
+        let text = render(&options, &[Some("a,b"), None, Some(""), Some("say \"hi\"")]);
+
+        assert_eq!(
+            "\"a,b\",,\"\",\"say \"\"hi\"\"\"\n",
+            String::from_utf8(text).unwrap()
+        );
+    }
+
+    #[test]
+    fn copy_text_format() {
+        let options = DelimitedOptions::new()
+            .with_delimiter(b'\t')
+            .with_quoting(Quoting::Never)
+            .with_backslash_escapes(true)
+            .with_null("\\N");
+
+        let text = render(&options, &[Some("a\tb\\c\nd"), None]);
+
+        assert_eq!("a\\tb\\\\c\\nd\t\\N\n", String::from_utf8(text).unwrap());
+    }
+
+    #[test]
+    fn utf16_encoding() {
+        let options = DelimitedOptions::new().with_encoding(TextEncoding::Utf16Le);
+
+        let text = render(&options, &[Some("ä"), Some("b")]);
+
+        assert_eq!(vec![0xe4, 0, b',', 0, b'b', 0, b'\n', 0], text);
+    }
+}
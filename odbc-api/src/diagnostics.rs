@@ -0,0 +1,342 @@
+//! Reports about the capabilities of ODBC drivers and data sources, to help triaging driver
+//! specific issues. See [`capability_report`].
+
+use std::fmt;
+
+use crate::{
+    handles::{slice_to_cow_utf8, SqlResult},
+    Connection, Cursor, Error, Nullable,
+};
+
+/// Everything the driver reports about itself and the data source, equivalent to the output of
+/// tools like `odbcinfo`. Created by [`capability_report`].
+///
+/// With the `serde` feature enabled the report implements `Serialize` and `Deserialize`, so it can
+/// e.g. be rendered as JSON and attached to bug reports. [`fmt::Display`] renders it as plain text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CapabilityReport {
+    /// Values returned by `SQLGetInfo`, including names, versions and limits.
+    pub info: Vec<InfoEntry>,
+    /// Support for ODBC functions, as returned by `SQLGetFunctions`. Empty if the driver manager
+    /// could not report it.
+    pub functions: Vec<FunctionSupport>,
+    /// Data types supported by the data source, as returned by `SQLGetTypeInfo`.
+    pub types: Vec<TypeInfo>,
+    /// Errors encountered while creating the report, e.g. because the driver does not support
+    /// `SQLGetTypeInfo`. Information types the driver does not know are reported with a value of
+    /// `None` instead.
+    pub errors: Vec<String>,
+}
+
+/// One value returned by `SQLGetInfo`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InfoEntry {
+    /// Name of the information type as defined by the ODBC standard, e.g. `SQL_DRIVER_NAME`.
+    pub name: String,
+    /// Numeric value of the information type.
+    pub info_type: u16,
+    /// `None` if the driver failed to report the value.
+    pub value: Option<InfoValue>,
+}
+
+/// Value of an information type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InfoValue {
+    /// Character string, e.g. a name or version, or `Y` and `N` for flags.
+    Text(String),
+    /// Number or bitmask.
+    Number(u32),
+}
+
+/// Whether the driver supports an ODBC function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FunctionSupport {
+    /// Name of the function, e.g. `SQLBulkOperations`.
+    pub name: String,
+    /// `true` if the driver supports the function.
+    pub supported: bool,
+}
+
+/// One row of the result set of `SQLGetTypeInfo`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TypeInfo {
+    /// Name of the type used by the data source, e.g. `varchar`.
+    pub type_name: String,
+    /// SQL data type the type maps to, e.g. `12` for `SQL_VARCHAR`.
+    pub data_type: i16,
+    /// Maximum column size. `None` if not applicable.
+    pub column_size: Option<i32>,
+    /// Parameters used in `CREATE TABLE` statements for this type, e.g. `max length`.
+    pub create_params: Option<String>,
+}
+
+/// How the value of an information type is returned by `SQLGetInfo`.
+#[derive(Clone, Copy)]
+enum InfoKind {
+    Text,
+    SmallInt,
+    Integer,
+}
+
+/// Information types included in the report.
+const INFO_TYPES: &[(&str, u16, InfoKind)] = &[
+    ("SQL_DRIVER_NAME", 6, InfoKind::Text),
+    ("SQL_DRIVER_VER", 7, InfoKind::Text),
+    ("SQL_DRIVER_ODBC_VER", 77, InfoKind::Text),
+    ("SQL_ODBC_VER", 10, InfoKind::Text),
+    ("SQL_DBMS_NAME", 17, InfoKind::Text),
+    ("SQL_DBMS_VER", 18, InfoKind::Text),
+    ("SQL_DATA_SOURCE_NAME", 2, InfoKind::Text),
+    ("SQL_SERVER_NAME", 13, InfoKind::Text),
+    ("SQL_DATA_SOURCE_READ_ONLY", 25, InfoKind::Text),
+    ("SQL_IDENTIFIER_QUOTE_CHAR", 29, InfoKind::Text),
+    ("SQL_SEARCH_PATTERN_ESCAPE", 14, InfoKind::Text),
+    ("SQL_SPECIAL_CHARACTERS", 94, InfoKind::Text),
+    ("SQL_CATALOG_NAME", 10003, InfoKind::Text),
+    ("SQL_COLLATION_SEQ", 10004, InfoKind::Text),
+    ("SQL_DESCRIBE_PARAMETER", 10002, InfoKind::Text),
+    ("SQL_MULT_RESULT_SETS", 36, InfoKind::Text),
+    ("SQL_ORDER_BY_COLUMNS_IN_SELECT", 90, InfoKind::Text),
+    ("SQL_IDENTIFIER_CASE", 28, InfoKind::SmallInt),
+    ("SQL_NULL_COLLATION", 85, InfoKind::SmallInt),
+    ("SQL_TXN_CAPABLE", 46, InfoKind::SmallInt),
+    ("SQL_CURSOR_COMMIT_BEHAVIOR", 23, InfoKind::SmallInt),
+    ("SQL_DEFAULT_TXN_ISOLATION", 26, InfoKind::Integer),
+    ("SQL_TXN_ISOLATION_OPTION", 72, InfoKind::Integer),
+    ("SQL_GETDATA_EXTENSIONS", 81, InfoKind::Integer),
+    ("SQL_SCROLL_OPTIONS", 44, InfoKind::Integer),
+    ("SQL_BATCH_ROW_COUNT", 120, InfoKind::Integer),
+    ("SQL_BATCH_SUPPORT", 121, InfoKind::Integer),
+    ("SQL_PARAM_ARRAY_ROW_COUNTS", 153, InfoKind::Integer),
+    ("SQL_PARAM_ARRAY_SELECTS", 154, InfoKind::Integer),
+    ("SQL_ASYNC_MODE", 10021, InfoKind::Integer),
+    (
+        "SQL_MAX_ASYNC_CONCURRENT_STATEMENTS",
+        10022,
+        InfoKind::Integer,
+    ),
+    ("SQL_MAX_DRIVER_CONNECTIONS", 0, InfoKind::SmallInt),
+    ("SQL_MAX_CONCURRENT_ACTIVITIES", 1, InfoKind::SmallInt),
+    ("SQL_MAX_IDENTIFIER_LEN", 10005, InfoKind::SmallInt),
+    ("SQL_MAX_CATALOG_NAME_LEN", 34, InfoKind::SmallInt),
+    ("SQL_MAX_SCHEMA_NAME_LEN", 32, InfoKind::SmallInt),
+    ("SQL_MAX_TABLE_NAME_LEN", 35, InfoKind::SmallInt),
+    ("SQL_MAX_COLUMN_NAME_LEN", 30, InfoKind::SmallInt),
+    ("SQL_MAX_CURSOR_NAME_LEN", 31, InfoKind::SmallInt),
+    ("SQL_MAX_USER_NAME_LEN", 107, InfoKind::SmallInt),
+    ("SQL_MAX_COLUMNS_IN_GROUP_BY", 97, InfoKind::SmallInt),
+    ("SQL_MAX_COLUMNS_IN_INDEX", 98, InfoKind::SmallInt),
+    ("SQL_MAX_COLUMNS_IN_ORDER_BY", 99, InfoKind::SmallInt),
+    ("SQL_MAX_COLUMNS_IN_SELECT", 100, InfoKind::SmallInt),
+    ("SQL_MAX_COLUMNS_IN_TABLE", 101, InfoKind::SmallInt),
+    ("SQL_MAX_TABLES_IN_SELECT", 106, InfoKind::SmallInt),
+    ("SQL_MAX_INDEX_SIZE", 102, InfoKind::Integer),
+    ("SQL_MAX_ROW_SIZE", 104, InfoKind::Integer),
+    ("SQL_MAX_STATEMENT_LEN", 105, InfoKind::Integer),
+];
+
+/// Functions included in the report, together with their `SQL_API_*` identifiers.
+const FUNCTIONS: &[(&str, u16)] = &[
+    ("SQLAllocHandle", 1001),
+    ("SQLBindCol", 4),
+    ("SQLBindParameter", 72),
+    ("SQLBulkOperations", 24),
+    ("SQLCancel", 5),
+    ("SQLCancelHandle", 1550),
+    ("SQLCloseCursor", 1003),
+    ("SQLColAttribute", 6),
+    ("SQLColumnPrivileges", 56),
+    ("SQLColumns", 40),
+    ("SQLCompleteAsync", 1551),
+    ("SQLDescribeCol", 8),
+    ("SQLDescribeParam", 58),
+    ("SQLDriverConnect", 41),
+    ("SQLEndTran", 1005),
+    ("SQLExecDirect", 11),
+    ("SQLExecute", 12),
+    ("SQLFetch", 13),
+    ("SQLFetchScroll", 1021),
+    ("SQLForeignKeys", 60),
+    ("SQLFreeStmt", 16),
+    ("SQLGetConnectAttr", 1007),
+    ("SQLGetData", 43),
+    ("SQLGetDiagRec", 1010),
+    ("SQLGetInfo", 45),
+    ("SQLGetStmtAttr", 1014),
+    ("SQLGetTypeInfo", 47),
+    ("SQLMoreResults", 61),
+    ("SQLNativeSql", 62),
+    ("SQLNumParams", 63),
+    ("SQLNumResultCols", 18),
+    ("SQLParamData", 48),
+    ("SQLPrepare", 19),
+    ("SQLPrimaryKeys", 65),
+    ("SQLProcedureColumns", 66),
+    ("SQLProcedures", 67),
+    ("SQLPutData", 49),
+    ("SQLRowCount", 20),
+    ("SQLSetConnectAttr", 1016),
+    ("SQLSetPos", 68),
+    ("SQLSetStmtAttr", 1020),
+    ("SQLSpecialColumns", 52),
+    ("SQLStatistics", 53),
+    ("SQLTablePrivileges", 70),
+    ("SQLTables", 54),
+];
+
+/// Queries everything the driver reports about itself and the data source `connection` is
+/// connected to. Failing to obtain individual parts of the report does not fail the entire report,
+/// since triaging broken drivers is the main purpose of this function. Such failures are listed in
+/// [`CapabilityReport::errors`] instead.
+///
+/// ```no_run
+/// use odbc_api::{diagnostics::capability_report, Environment};
+///
+/// let env = Environment::new()?;
+/// let conn = env.connect_with_connection_string("DSN=MyDatabase;")?;
+/// println!("{}", capability_report(&conn));
+/// # Ok::<(), odbc_api::Error>(())
+/// ```
+pub fn capability_report(connection: &Connection<'_>) -> CapabilityReport {
+    let handle = connection.as_handle();
+    let mut errors = Vec::new();
+    let mut buf = Vec::new();
+    let info = INFO_TYPES
+        .iter()
+        .map(|&(name, info_type, kind)| {
+            let value = match kind {
+                InfoKind::Text => ok(handle.fetch_info_string(info_type, &mut buf))
+                    .map(|()| InfoValue::Text(slice_to_cow_utf8(&buf).into_owned())),
+                InfoKind::SmallInt => {
+                    ok(handle.info_small_int(info_type)).map(|v| InfoValue::Number(v.into()))
+                }
+                InfoKind::Integer => ok(handle.info_integer(info_type)).map(InfoValue::Number),
+            };
+            InfoEntry {
+                name: name.to_owned(),
+                info_type,
+                value,
+            }
+        })
+        .collect();
+    let functions = match handle.supported_functions().into_result(handle) {
+        Ok(bitmap) => FUNCTIONS
+            .iter()
+            .map(|&(name, id)| FunctionSupport {
+                name: name.to_owned(),
+                supported: is_supported(&bitmap, id),
+            })
+            .collect(),
+        Err(error) => {
+            errors.push(format!("Failed to query supported functions: {error}"));
+            Vec::new()
+        }
+    };
+    let types = type_info(connection).unwrap_or_else(|error| {
+        errors.push(format!("Failed to query type info: {error}"));
+        Vec::new()
+    });
+    CapabilityReport {
+        info,
+        functions,
+        types,
+        errors,
+    }
+}
+
+/// `Some` for successful calls. Diagnostics of failed ones are not of interest, the value is simply
+/// reported as unknown.
+fn ok<T>(result: SqlResult<T>) -> Option<T> {
+    match result {
+        SqlResult::Success(value) | SqlResult::SuccessWithInfo(value) => Some(value),
+        _ => None,
+    }
+}
+
+/// Looks up `function_id` in the bitmap returned by `SQLGetFunctions` for `SQL_API_ODBC3_ALL_FUNCTIONS`.
+fn is_supported(bitmap: &[u16], function_id: u16) -> bool {
+    bitmap[usize::from(function_id >> 4)] & (1 << (function_id & 0xF)) != 0
+}
+
+fn type_info(connection: &Connection<'_>) -> Result<Vec<TypeInfo>, Error> {
+    let mut cursor = connection.type_info()?;
+    let mut types = Vec::new();
+    let mut text = Vec::new();
+    while let Some(mut row) = cursor.next_row()? {
+        row.get_text(1, &mut text)?;
+        let type_name = String::from_utf8_lossy(&text).into_owned();
+        let mut data_type = Nullable::<i16>::null();
+        row.get_data(2, &mut data_type)?;
+        let mut column_size = Nullable::<i32>::null();
+        row.get_data(3, &mut column_size)?;
+        let create_params = if row.get_text(6, &mut text)? {
+            Some(String::from_utf8_lossy(&text).into_owned())
+        } else {
+            None
+        };
+        types.push(TypeInfo {
+            type_name,
+            data_type: data_type.into_opt().unwrap_or_default(),
+            column_size: column_size.into_opt(),
+            create_params,
+        });
+    }
+    Ok(types)
+}
+
+impl fmt::Display for CapabilityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Info:")?;
+        for entry in &self.info {
+            match &entry.value {
+                Some(InfoValue::Text(text)) => writeln!(f, "  {}: {text}", entry.name)?,
+                Some(InfoValue::Number(number)) => writeln!(f, "  {}: {number}", entry.name)?,
+                None => writeln!(f, "  {}: <unknown>", entry.name)?,
+            }
+        }
+        writeln!(f, "Functions:")?;
+        for function in &self.functions {
+            let support = if function.supported { "yes" } else { "no" };
+            writeln!(f, "  {}: {support}", function.name)?;
+        }
+        writeln!(f, "Types:")?;
+        for type_info in &self.types {
+            write!(f, "  {} ({})", type_info.type_name, type_info.data_type)?;
+            if let Some(column_size) = type_info.column_size {
+                write!(f, ", column size {column_size}")?;
+            }
+            if let Some(create_params) = &type_info.create_params {
+                write!(f, ", parameters: {create_params}")?;
+            }
+            writeln!(f)?;
+        }
+        if !self.errors.is_empty() {
+            writeln!(f, "Errors:")?;
+            for error in &self.errors {
+                writeln!(f, "  {error}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_supported;
+
+    #[test]
+    fn look_up_function_in_bitmap() {
+        let mut bitmap = vec![0u16; 250];
+        // SQLFetchScroll (1021) is bit 13 of element 63
+        bitmap[63] = 1 << 13;
+
+        assert!(is_supported(&bitmap, 1021));
+        assert!(!is_supported(&bitmap, 1020));
+        assert!(!is_supported(&bitmap, 4));
+    }
+}
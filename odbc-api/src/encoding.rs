@@ -0,0 +1,100 @@
+//! Converts text between the narrow character encoding of a driver and UTF-8. Requires the
+//! `encoding` feature.
+//!
+//! Narrow text, i.e. values fetched into [`crate::buffers::TextRowSet`] or bound as
+//! [`crate::parameter::VarCharBox`], is exchanged with the driver as bytes, which this crate
+//! assumes to be UTF-8. Some setups, e.g. unixODBC with older Oracle or Informix clients, use a
+//! different encoding like Latin-1 or Shift-JIS, though. Declare it for a connection with
+//! [`crate::Connection::set_narrow_encoding`] and convert text using the functions of this module.
+//!
+//! ```no_run
+//! use odbc_api::{
+//!     buffers::TextRowSet, encoding::encoding_rs::SHIFT_JIS, Cursor, Environment, IntoParameter,
+//! };
+//!
+//! let env = Environment::new()?;
+//! let mut conn = env.connect_with_connection_string("DSN=legacy;")?;
+//! conn.set_narrow_encoding(SHIFT_JIS);
+//!
+//! let city = conn.encode_narrow("東京")?;
+//! let cursor = conn.execute("SELECT name FROM Shops WHERE city = ?", &city)?.unwrap();
+//! let mut block_cursor = cursor.bind_buffer(TextRowSet::from_max_str_lens(100, [255])?)?;
+//! while let Some(batch) = block_cursor.fetch()? {
+//!     for row_index in 0..batch.num_rows() {
+//!         println!("{:?}", batch.at_decoded(0, row_index, conn.narrow_encoding()));
+//!     }
+//! }
+//! # Ok::<(), odbc_api::Error>(())
+//! ```
+
+use std::borrow::Cow;
+
+use crate::Error;
+
+pub use encoding_rs::{self, Encoding};
+
+/// Converts `bytes` in `encoding` to UTF-8. Malformed sequences are replaced with
+/// `U+FFFD REPLACEMENT CHARACTER`, like [`String::from_utf8_lossy`] does. Borrows `bytes` if no
+/// conversion is required.
+pub fn decode<'a>(encoding: &'static Encoding, bytes: &'a [u8]) -> Cow<'a, str> {
+    let (text, _had_errors) = encoding.decode_without_bom_handling(bytes);
+    text
+}
+
+/// Converts `text` to `encoding`. Borrows `text` if no conversion is required.
+///
+/// Fails with [`Error::UnmappableCharacter`] if `text` contains characters which can not be
+/// represented in `encoding`, rather than silently altering the value sent to the data source.
+pub fn encode<'a>(encoding: &'static Encoding, text: &'a str) -> Result<Cow<'a, [u8]>, Error> {
+    let (bytes, _actual_encoding, had_errors) = encoding.encode(text);
+    if had_errors {
+        Err(Error::UnmappableCharacter {
+            encoding: encoding.name(),
+        })
+    } else {
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use encoding_rs::{UTF_8, WINDOWS_1252};
+
+    use crate::Error;
+
+    use super::{decode, encode};
+
+    #[test]
+    fn round_trip_latin_1() {
+        let bytes = encode(WINDOWS_1252, "Grüße").unwrap();
+
+        assert_eq!(&b"Gr\xFC\xDFe"[..], &bytes[..]);
+        assert_eq!("Grüße", decode(WINDOWS_1252, &bytes));
+    }
+
+    #[test]
+    fn borrow_ascii_and_utf_8() {
+        assert!(matches!(
+            encode(WINDOWS_1252, "Hello"),
+            Ok(Cow::Borrowed(_))
+        ));
+        assert!(matches!(
+            decode(UTF_8, "Grüße".as_bytes()),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn unmappable_character() {
+        let result = encode(WINDOWS_1252, "東京");
+
+        assert!(matches!(
+            result,
+            Err(Error::UnmappableCharacter {
+                encoding: "windows-1252"
+            })
+        ));
+    }
+}
@@ -1,11 +1,27 @@
-use std::{cmp::max, collections::HashMap, ptr::null_mut, sync::Mutex};
+use std::{
+    cmp::max,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    ptr::null_mut,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex, OnceLock, RwLock,
+    },
+    thread,
+    time::{Duration, Instant},
+};
 
 use crate::{
     error::ExtendResult,
-    handles::{self, log_diagnostics, OutputStringBuffer, SqlResult, SqlText, State, SzBuffer},
-    Connection, DriverCompleteOption, Error,
+    file_dsn::{self, SaveFileWatch},
+    handles::{
+        self, log_diagnostics, AsHandle, CallHookRegistration, OutputStringBuffer, SqlResult,
+        SqlText, State, StatementRegistry, SzBuffer,
+    },
+    result_set_metadata::reported,
+    Connection, ConnectionObserver, ConnectionTarget, DriverCompleteOption, Error, FailoverOptions,
 };
-use log::debug;
+use log::{debug, warn};
 use odbc_sys::{AttrCpMatch, AttrOdbcVersion, FetchOrientation, HWnd};
 
 #[cfg(target_os = "windows")]
@@ -28,7 +44,20 @@ const ODBC_API_VERSION: AttrOdbcVersion = AttrOdbcVersion::Odbc3;
 /// * The current stetting of each environment attribute
 ///
 /// Creating the environment is the first applications do, then interacting with an ODBC driver
-/// manager. There must only be one environment in the entire process.
+/// manager. Usually there is only one environment in the entire process.
+///
+/// # Multiple environments
+///
+/// ODBC allows several environments to exist at the same time. E.g. plugins loaded by a host
+/// application may each create an environment of their own. Each environment has its own ODBC
+/// version (see [`Self::with_odbc_version`]), connection pool matching
+/// ([`Self::set_connection_pooling_matching`]), connection observer
+/// ([`Self::set_connection_observer`]) and call hook ([`Self::set_call_hook`]). Yet enabling
+/// connection pooling ([`Self::set_connection_pooling`]) is process wide and affects every
+/// environment. To prevent one user of this crate from changing the connection pooling of
+/// environments already in use by another, use [`Self::set_connection_pooling_checked`], which
+/// fails while any environment exists. Use [`Self::num_environments`] and [`Self::num_connections`] to inspect how
+/// many handles are currently allocated.
 pub struct Environment {
     environment: handles::Environment,
     /// ODBC environments use interior mutability to maintain iterator state then iterating over
@@ -40,10 +69,63 @@ pub struct Environment {
     /// If multiple fallible operations are executed in parallel, we need the mutex to ensure the
     /// errors are fetched by the correct thread.
     internal_state: Mutex<()>,
+    /// Passed to each connection opened with this environment.
+    connection_observer: RwLock<Option<Arc<dyn ConnectionObserver>>>,
+    /// Invoked for calls made with the environment handle, and registered for each connection
+    /// opened with this environment. See [`Self::set_call_hook`].
+    call_hook: RwLock<Option<CallHookRegistration>>,
+    /// Executed on each connection opened with this environment.
+    session_initialization: RwLock<Vec<String>>,
+    /// Number of connections opened with this environment, which are still alive.
+    num_connections: Arc<AtomicUsize>,
+    /// Statements allocated on connections of this environment, which are still alive.
+    statements: Arc<StatementRegistry>,
+    /// Set by [`Self::shutdown`]. No further connections are opened.
+    shut_down: AtomicBool,
 }
 
 unsafe impl Sync for Environment {}
 
+/// Number of environments created by this crate, which are still alive.
+static NUM_ENVIRONMENTS: AtomicUsize = AtomicUsize::new(0);
+
+impl Drop for Environment {
+    fn drop(&mut self) {
+        // Unregister before the handle is freed.
+        *self.call_hook.get_mut().unwrap() = None;
+        NUM_ENVIRONMENTS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Counts a connection as alive, until it is dropped. See [`Environment::num_connections`]. Also
+/// gives the connection access to the statement registry of its environment.
+#[derive(Debug)]
+pub(crate) struct ConnectionCountGuard {
+    count: Arc<AtomicUsize>,
+    statements: Arc<StatementRegistry>,
+}
+
+impl ConnectionCountGuard {
+    fn new(count: &Arc<AtomicUsize>, statements: &Arc<StatementRegistry>) -> Self {
+        count.fetch_add(1, Ordering::SeqCst);
+        Self {
+            count: count.clone(),
+            statements: statements.clone(),
+        }
+    }
+
+    /// Statements allocated on connections of the environment, which are still alive.
+    pub fn statements(&self) -> &Arc<StatementRegistry> {
+        &self.statements
+    }
+}
+
+impl Drop for ConnectionCountGuard {
+    fn drop(&mut self) {
+        self.count.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
 impl Environment {
     /// Enable or disable (default) connection pooling for ODBC connections. Call this function
     /// before creating the ODBC environment for which you want to enable connection pooling.
@@ -91,18 +173,136 @@ impl Environment {
     /// > support connection pooling. This means the driver is able to handle a call on any thread
     /// > at any time and is able to connect on one thread, to use the connection on another thread,
     /// > and to disconnect on a third thread.
+    ///
+    /// Connection pooling is process wide state and also affects environments which already exist.
+    /// See [`Self::set_connection_pooling_checked`] for a variant refusing to change it while any
+    /// environment is alive.
     pub unsafe fn set_connection_pooling(
         scheme: odbc_sys::AttrConnectionPooling,
     ) -> Result<(), Error> {
         match handles::Environment::set_connection_pooling(scheme) {
-            SqlResult::Error { .. } => Err(Error::FailedSettingConnectionPooling),
             SqlResult::Success(()) | SqlResult::SuccessWithInfo(()) => Ok(()),
             other => {
-                panic!("Unexpected return value `{other:?}`.")
+                log::error!("Unexpected result setting connection pooling: {other:?}");
+                Err(Error::FailedSettingConnectionPooling)
             }
         }
     }
 
+    /// Like [`Self::set_connection_pooling`], but fails with
+    /// [`Error::ConnectionPoolingWithLiveEnvironments`] if any environment created by this crate
+    /// still exists. Prevents changing the connection pooling of environments in use by other parts
+    /// of the application, e.g. other libraries.
+    ///
+    /// # Safety
+    ///
+    /// See [`Self::set_connection_pooling`].
+    pub unsafe fn set_connection_pooling_checked(
+        scheme: odbc_sys::AttrConnectionPooling,
+    ) -> Result<(), Error> {
+        let num_environments = Self::num_environments();
+        if num_environments != 0 {
+            return Err(Error::ConnectionPoolingWithLiveEnvironments { num_environments });
+        }
+        Self::set_connection_pooling(scheme)
+    }
+
+    /// Installs a hook which is invoked after every call to the ODBC C API this crate makes with
+    /// the handle of this environment, the connections subsequently opened with it and their
+    /// statements. Passing `None` removes the hook. Connections which are already open are not
+    /// affected, just like with [`Self::set_connection_observer`]. See [`handles::CallHook`] for
+    /// details. Useful to trace ODBC calls, or to inject faults while testing error handling of an
+    /// application.
+    ///
+    /// The hook is scoped to this environment, so other environments in the same process, e.g.
+    /// created by other libraries, are not affected.
+    pub fn set_call_hook(&self, hook: Option<Arc<dyn handles::CallHook>>) {
+        let mut call_hook = self.call_hook.write().unwrap();
+        // Drop the old registration first, so the handle is not registered twice.
+        *call_hook = None;
+        *call_hook =
+            hook.map(|hook| CallHookRegistration::new(self.environment.as_handle(), &hook));
+    }
+
+    /// Registers an observer which is passed to every connection opened with this environment
+    /// from now on, or removes it by passing `None`. The observer is notified about the opening of
+    /// the connection and subsequent [`crate::ConnectionEvent`]s. Connections which are already open
+    /// are not affected.
+    pub fn set_connection_observer(&self, observer: Option<Arc<dyn ConnectionObserver>>) {
+        *self.connection_observer.write().unwrap() = observer;
+    }
+
+    /// Registers statements executed on every connection opened with this environment from now on,
+    /// right after it has been established. Useful to configure the session consistently, e.g.
+    /// `SET NOCOUNT ON`, `ALTER SESSION SET NLS_DATE_FORMAT = 'YYYY-MM-DD'` or
+    /// `SET search_path TO reporting`. The statements are executed in order. Should one of them
+    /// fail, the connection is closed and the error is returned by the method used to connect.
+    /// Replaces any statements registered before. Connections which are already open are not
+    /// affected.
+    ///
+    /// With connection pooling enabled every connect checks a connection out of the pool, so the
+    /// statements are executed on each checkout. They should therefore be idempotent.
+    ///
+    /// ```no_run
+    /// use odbc_api::Environment;
+    ///
+    /// let env = Environment::new()?;
+    /// env.set_session_initialization(["SET NOCOUNT ON", "SET ANSI_WARNINGS ON"]);
+    /// let conn = env.connect("YourDatabase", "SA", "My@Test@Password1")?;
+    /// # Ok::<(), odbc_api::Error>(())
+    /// ```
+    pub fn set_session_initialization(
+        &self,
+        statements: impl IntoIterator<Item = impl Into<String>>,
+    ) {
+        *self.session_initialization.write().unwrap() =
+            statements.into_iter().map(Into::into).collect();
+    }
+
+    /// Commits the transactions of all connections opened with this environment, which are in
+    /// manual-commit mode. The driver manager calls `SQLEndTran` for each of these connections.
+    ///
+    /// This is a convenience, not a distributed transaction. ODBC offers no two phase commit, so
+    /// should committing the transaction of one connection fail, the transactions of others may
+    /// already have been committed. An error is returned in this case, yet it does not tell which
+    /// connections have been committed.
+    ///
+    /// Whether this is supported depends on the driver manager and the drivers. E.g. the Microsoft
+    /// driver manager refuses to end the transactions of an environment shared between
+    /// applications by connection pooling. Fall back to [`Connection::commit`] for each
+    /// connection if this fails.
+    ///
+    /// ```no_run
+    /// use odbc_api::{Environment, Error};
+    ///
+    /// fn transfer(env: &Environment) -> Result<(), Error> {
+    ///     let orders = env.connect("Orders", "SA", "My@Test@Password1")?;
+    ///     let billing = env.connect("Billing", "SA", "My@Test@Password1")?;
+    ///     orders.set_autocommit(false)?;
+    ///     billing.set_autocommit(false)?;
+    ///     orders.execute("UPDATE Orders SET billed = 1 WHERE id = 42", ())?;
+    ///     billing.execute("INSERT INTO Invoices (order_id) VALUES (42)", ())?;
+    ///     match env.commit_all() {
+    ///         Ok(()) => Ok(()),
+    ///         Err(error) => {
+    ///             env.rollback_all()?;
+    ///             Err(error)
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn commit_all(&self) -> Result<(), Error> {
+        self.environment.commit_all().into_result(&self.environment)
+    }
+
+    /// Rolls back the transactions of all connections opened with this environment, which are in
+    /// manual-commit mode. See [`Self::commit_all`] for caveats.
+    pub fn rollback_all(&self) -> Result<(), Error> {
+        self.environment
+            .rollback_all()
+            .into_result(&self.environment)
+    }
+
     /// Determines how a connection is chosen from a connection pool. When [`Self::connect`],
     /// [`Self::connect_with_connection_string`] or [`Self::driver_connect`] is called, the Driver
     /// Manager determines which connection is reused from the pool. The Driver Manager tries to
@@ -124,18 +324,28 @@ impl Environment {
     }
 
     /// Entry point into this API. Allocates a new ODBC Environment and declares to the driver
-    /// manager that the Application wants to use ODBC version 3.8.
+    /// manager that the Application wants to use ODBC version 3.8 (or 3.5 if the
+    /// `odbc_version_3_5` feature is active).
     ///
-    /// # Safety
+    /// # Multiple environments
     ///
-    /// There may only be one ODBC environment in any process at any time. Take care using this
-    /// function in unit tests, as these run in parallel by default in Rust. Also no library should
-    /// probably wrap the creation of an odbc environment into a safe function call. This is because
-    /// using two of these "safe" libraries at the same time in different parts of your program may
-    /// lead to race condition thus violating Rust's safety guarantees.
+    /// Usually there is only one ODBC environment in a process. Take care using this function in
+    /// unit tests, as these run in parallel by default in Rust. Several environments may exist at
+    /// the same time, but they share process wide settings. See the section about multiple
+    /// environments in the documentation of [`Environment`].
     ///
-    /// Creating one environment in your binary is safe however.
+    /// Libraries and applications which want to share a single environment may use
+    /// [`Self::global`] instead.
     pub fn new() -> Result<Self, Error> {
+        Self::with_odbc_version(ODBC_API_VERSION)
+    }
+
+    /// Like [`Self::new`], but declares `version` as the ODBC version used by the application,
+    /// independent of the `odbc_version_3_5` feature. Drivers and driver managers behave
+    /// differently depending on the declared version, e.g. regarding the state codes they return.
+    /// Since the version is declared per environment, independent parts of an application may
+    /// choose different versions, by using environments of their own.
+    pub fn with_odbc_version(version: AttrOdbcVersion) -> Result<Self, Error> {
         let result = handles::Environment::new();
 
         let environment = match result {
@@ -144,14 +354,16 @@ impl Environment {
                 log_diagnostics(&env);
                 env
             }
-            SqlResult::Error { .. } => return Err(Error::FailedAllocatingEnvironment),
-            other => panic!("Unexpected return value '{other:?}'"),
+            other => {
+                log::error!("Unexpected result allocating environment: {other:?}");
+                return Err(Error::FailedAllocatingEnvironment);
+            }
         };
 
         debug!("ODBC Environment created.");
 
         let result = environment
-            .declare_version(ODBC_API_VERSION)
+            .declare_version(version)
             .into_result(&environment);
 
         // Translate invalid attribute into a more meaningful error, provided the additional
@@ -168,12 +380,133 @@ impl Environment {
             _ => Error::Diagnostics { record, function },
         })?;
 
+        NUM_ENVIRONMENTS.fetch_add(1, Ordering::SeqCst);
         Ok(Self {
             environment,
             internal_state: Mutex::new(()),
+            connection_observer: RwLock::new(None),
+            call_hook: RwLock::new(None),
+            session_initialization: RwLock::new(Vec::new()),
+            num_connections: Arc::new(AtomicUsize::new(0)),
+            statements: Arc::new(StatementRegistry::default()),
+            shut_down: AtomicBool::new(false),
         })
     }
 
+    /// Number of environments created by this crate in this process, which are still alive,
+    /// including the one returned by [`Self::global`]. Environments created by other versions of
+    /// this crate or by other libraries are not counted.
+    pub fn num_environments() -> usize {
+        NUM_ENVIRONMENTS.load(Ordering::SeqCst)
+    }
+
+    /// Number of connections opened with this environment, which are still alive. Connections
+    /// released using [`Connection::into_sys`] or [`Connection::into_handle`] are not counted.
+    pub fn num_connections(&self) -> usize {
+        self.num_connections.load(Ordering::SeqCst)
+    }
+
+    /// Prepares the environment for the application to exit. Connecting fails with
+    /// [`Error::EnvironmentShutDown`] afterwards. This includes connections opened by e.g.
+    /// [`crate::ReplicaSet::connect`]. Statements executing on connections of this environment,
+    /// e.g. running a query or fetching, are given until `timeout` to finish. Any statements still
+    /// executing after that are cancelled using `SQLCancel`, so the calls return with an error.
+    ///
+    /// Handles are not freed by the shutdown. They are owned by their connections and statements,
+    /// which free them once dropped. Unloading the driver at process exit, while handles are still
+    /// alive, causes crashes in some drivers. This is especially relevant for [`Self::global`],
+    /// which is never dropped. Call this before exiting, and drop the connections of the
+    /// environment afterwards. [`ShutdownReport::num_connections`] tells how many of them are still
+    /// alive.
+    ///
+    /// Connections and statements handed out as raw handles (see [`Connection::into_sys`] and
+    /// [`handles::StatementImpl::into_sys`]) are not tracked and therefore neither awaited nor
+    /// cancelled.
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use odbc_api::Environment;
+    ///
+    /// let env = Environment::global()?;
+    /// // ... serve requests on other threads ...
+    /// let report = env.shutdown(Duration::from_secs(10));
+    /// if report.num_cancelled != 0 {
+    ///     eprintln!("Cancelled {} statement(s) during shutdown.", report.num_cancelled);
+    /// }
+    /// # Ok::<(), odbc_api::Error>(())
+    /// ```
+    pub fn shutdown(&self, timeout: Duration) -> ShutdownReport {
+        self.shut_down.store(true, Ordering::SeqCst);
+        let deadline = Instant::now() + timeout;
+        while self.statements.num_executing() != 0 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let num_cancelled = self.statements.cancel_executing();
+        // Give cancelled statements a chance to return.
+        let grace_deadline = Instant::now() + Duration::from_millis(100);
+        while self.statements.num_executing() != 0 && Instant::now() < grace_deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        ShutdownReport {
+            num_cancelled,
+            num_connections: self.num_connections(),
+        }
+    }
+
+    /// `true` if [`Self::shutdown`] has been called.
+    pub fn is_shut_down(&self) -> bool {
+        self.shut_down.load(Ordering::SeqCst)
+    }
+
+    /// A process wide environment, which is lazily created by the first call to this function.
+    /// All subsequent calls return a reference to the same environment. Use this in case several
+    /// independent libraries within the same application want to use ODBC, without every one of
+    /// them allocating an environment of its own.
+    ///
+    /// The environment is created with the same settings as [`Self::new`]. If creating the
+    /// environment fails, the error is returned and the next call tries again.
+    ///
+    /// # ODBC environment semantics
+    ///
+    /// There should only be one ODBC environment in the entire process. This function can only
+    /// uphold this, if it is the only way your application creates an environment. Mixing calls to
+    /// this function with calls to [`Self::new`] still causes two environments to exist at the same
+    /// time. Settings which must happen before an environment exists, like
+    /// [`Self::set_connection_pooling`], must be applied before the first call to this function.
+    ///
+    /// Since the global environment lives in a `static` it is never dropped. Its handle is
+    /// therefore not freed before the process exits. This also implies that the driver manager is
+    /// not unloaded while the process is still running.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use odbc_api::Environment;
+    ///
+    /// let env = Environment::global()?;
+    /// let conn = env.connect_with_connection_string("DSN=MyDatabase;")?;
+    /// // Any other part of the application obtains the same environment.
+    /// assert!(std::ptr::eq(env, Environment::global()?));
+    /// # Ok::<(), odbc_api::Error>(())
+    /// ```
+    pub fn global() -> Result<&'static Environment, Error> {
+        // `Environment` is `Sync`, but not `Send`. Access to the environment is synchronized by
+        // its internal state, and it is never moved out of the static.
+        static GLOBAL: OnceLock<force_send_sync::Send<Environment>> = OnceLock::new();
+        // Serializes the fallible initialization, so we never create two environments at once.
+        static INIT: Mutex<()> = Mutex::new(());
+
+        if let Some(env) = GLOBAL.get() {
+            return Ok(env);
+        }
+        let _lock = INIT.lock().unwrap();
+        if let Some(env) = GLOBAL.get() {
+            return Ok(env);
+        }
+        let env = Environment::new()?;
+        Ok(GLOBAL.get_or_init(|| unsafe { force_send_sync::Send::new(env) }))
+    }
+
     /// Allocates a connection handle and establishes connections to a driver and a data source.
     ///
     /// * See [Connecting with SQLConnect][1]
@@ -209,11 +542,11 @@ impl Environment {
         let user = SqlText::new(user);
         let pwd = SqlText::new(pwd);
 
-        let mut connection = self.allocate_connection()?;
+        let (mut connection, call_hook) = self.allocate_connection()?;
         connection
             .connect(&data_source_name, &user, &pwd)
             .into_result(&connection)?;
-        Ok(Connection::new(connection))
+        self.opened(connection, call_hook)
     }
 
     /// Allocates a connection handle and establishes connections to a driver and a data source.
@@ -246,11 +579,161 @@ impl Environment {
         connection_string: &str,
     ) -> Result<Connection<'_>, Error> {
         let connection_string = SqlText::new(connection_string);
-        let mut connection = self.allocate_connection()?;
+        let (mut connection, call_hook) = self.allocate_connection()?;
+        connection
+            .connect_with_connection_string(&connection_string)
+            .into_result(&connection)?;
+        self.opened(connection, call_hook)
+    }
+
+    /// Like [`Self::connect_with_connection_string`], but enables the bulk copy functions of the
+    /// Microsoft ODBC Driver for SQL Server before connecting, so the connection can be used with
+    /// [`crate::mssql_bcp::BulkCopy`]. Requires the `mssql-bcp` feature.
+    #[cfg(feature = "mssql-bcp")]
+    pub fn connect_with_bulk_copy(&self, connection_string: &str) -> Result<Connection<'_>, Error> {
+        let connection_string = SqlText::new(connection_string);
+        let (mut connection, call_hook) = self.allocate_connection()?;
+        connection.set_bulk_copy(true).into_result(&connection)?;
         connection
             .connect_with_connection_string(&connection_string)
             .into_result(&connection)?;
-        Ok(Connection::new(connection))
+        self.opened(connection, call_hook)
+    }
+
+    /// Connects using a file DSN, i.e. a file holding the attributes of a connection string, as
+    /// saved by [`Self::connect_and_save_file_dsn`] or the ODBC Data Source Administrator.
+    /// `attributes` are appended to the connection string, e.g. to provide a password, which is
+    /// never saved in the file. Pass an empty string if the file is sufficient.
+    ///
+    /// ```no_run
+    /// use odbc_api::Environment;
+    ///
+    /// let env = Environment::new()?;
+    ///
+    /// let mut conn = env.connect_with_file_dsn("C:\\Users\\me\\sales.dsn", "PWD=secret;")?;
+    /// # Ok::<(), odbc_api::Error>(())
+    /// ```
+    pub fn connect_with_file_dsn(
+        &self,
+        path: impl AsRef<Path>,
+        attributes: &str,
+    ) -> Result<Connection<'_>, Error> {
+        let file_dsn = file_dsn::path_attribute_value(path.as_ref());
+        self.connect_with_connection_string(&format!("FILEDSN={file_dsn};{attributes}"))
+    }
+
+    /// Connects using `connection_string` and asks the driver manager to save its attributes as a
+    /// file DSN to `path`, using the `SAVEFILE` keyword. The file can be used to connect again
+    /// with [`Self::connect_with_file_dsn`]. Passwords are not saved.
+    ///
+    /// Returns the connection together with the path of the saved file. It may differ from `path`,
+    /// since the driver manager appends `.dsn` to file names without an extension. `None` if no
+    /// file has been saved, e.g. because the driver manager does not support `SAVEFILE`. Pass an
+    /// absolute path, since driver managers resolve relative ones differently.
+    ///
+    /// ```no_run
+    /// use odbc_api::Environment;
+    ///
+    /// let env = Environment::new()?;
+    ///
+    /// let connection_string = "Driver={ODBC Driver 18 for SQL Server};Server=localhost;UID=SA;";
+    /// let (conn, saved) =
+    ///     env.connect_and_save_file_dsn(connection_string, "C:\\Users\\me\\sales")?;
+    /// match saved {
+    ///     Some(path) => println!("Connection settings saved to {}", path.display()),
+    ///     None => println!("Driver manager did not save the connection settings."),
+    /// }
+    /// # Ok::<(), odbc_api::Error>(())
+    /// ```
+    pub fn connect_and_save_file_dsn(
+        &self,
+        connection_string: &str,
+        path: impl AsRef<Path>,
+    ) -> Result<(Connection<'_>, Option<PathBuf>), Error> {
+        let path = path.as_ref();
+        let watch = SaveFileWatch::new(path);
+        let connection_string = file_dsn::with_save_file(connection_string, path);
+        let connection = self.connect_with_connection_string(&connection_string)?;
+        let saved = watch.saved();
+        if saved.is_none() {
+            warn!(
+                "Driver manager did not save the file DSN to '{}'.",
+                path.display()
+            );
+        }
+        Ok((connection, saved))
+    }
+
+    /// Connects to the first of `targets` which is available. Useful e.g. for active / passive
+    /// database pairs. Uses the default [`FailoverOptions`], see
+    /// [`Self::connect_first_available_with_options`].
+    ///
+    /// ```no_run
+    /// use odbc_api::{ConnectionTarget, Environment};
+    ///
+    /// let env = Environment::new()?;
+    ///
+    /// let targets = [
+    ///     ConnectionTarget::connection_string("Driver={ODBC Driver 17 for SQL Server};\
+    ///         Server=primary;UID=SA;PWD=My@Test@Password1;"),
+    ///     ConnectionTarget::connection_string("Driver={ODBC Driver 17 for SQL Server};\
+    ///         Server=secondary;UID=SA;PWD=My@Test@Password1;"),
+    /// ];
+    /// let (conn, index) = env.connect_first_available(&targets)?;
+    /// if index != 0 {
+    ///     eprintln!("Primary is down. Connected to the secondary.");
+    /// }
+    /// # Ok::<(), odbc_api::Error>(())
+    /// ```
+    pub fn connect_first_available(
+        &self,
+        targets: &[ConnectionTarget],
+    ) -> Result<(Connection<'_>, usize), Error> {
+        self.connect_first_available_with_options(targets, &FailoverOptions::new())
+    }
+
+    /// Tries to connect to each of `targets` in order, until a connection is established. Should
+    /// all of them fail, this is repeated for the number of rounds specified in `options`, waiting
+    /// in between. Returns the connection together with the index of the target it has been
+    /// established to.
+    ///
+    /// Failed attempts are logged as warnings. If no target is available
+    /// [`Error::NoConnectionTargetAvailable`] is returned, holding the error of the last attempt.
+    ///
+    /// # Panics
+    ///
+    /// If `targets` is empty.
+    pub fn connect_first_available_with_options(
+        &self,
+        targets: &[ConnectionTarget],
+        options: &FailoverOptions,
+    ) -> Result<(Connection<'_>, usize), Error> {
+        assert!(
+            !targets.is_empty(),
+            "At least one connection target required."
+        );
+        let mut num_attempts = 0;
+        let mut last = None;
+        for round in 0..options.rounds() {
+            if round != 0 {
+                thread::sleep(options.delay_after(round - 1));
+            }
+            for (index, target) in targets.iter().enumerate() {
+                num_attempts += 1;
+                match self.connect_to_target(target, options.login_timeout_sec()) {
+                    Ok(connection) => return Ok((connection, index)),
+                    Err(error) => {
+                        warn!("Connection target {index} is unavailable: {error}");
+                        last = Some(error);
+                    }
+                }
+            }
+        }
+        Err(Error::NoConnectionTargetAvailable {
+            num_targets: targets.len(),
+            num_attempts,
+            last: Box::new(last.expect("At least one attempt must have been made")),
+        })
     }
 
     /// Allocates a connection handle and establishes connections to a driver and a data source.
@@ -406,7 +889,7 @@ impl Environment {
         driver_completion: DriverCompleteOption,
         parent_window: HWnd,
     ) -> Result<Connection<'_>, Error> {
-        let mut connection = self.allocate_connection()?;
+        let (mut connection, call_hook) = self.allocate_connection()?;
         let connection_string = SqlText::new(connection_string);
 
         let connection_string_is_complete = connection
@@ -420,7 +903,7 @@ impl Environment {
         if !connection_string_is_complete {
             return Err(Error::AbortedConnectionStringCompletion);
         }
-        Ok(Connection::new(connection))
+        self.opened(connection, call_hook)
     }
 
     /// Get information about available drivers. Only 32 or 64 Bit drivers will be listed, depending
@@ -470,8 +953,12 @@ impl Environment {
             }
 
             // Allocate +1 character extra for terminating zero
-            let mut desc_buf = SzBuffer::with_capacity(desc_len as usize);
-            let mut attr_buf = SzBuffer::with_capacity(attr_len as usize);
+            let mut desc_buf = SzBuffer::with_capacity(reported(
+                desc_len.into(),
+                "length of a driver description",
+            )?);
+            let mut attr_buf =
+                SzBuffer::with_capacity(reported(attr_len.into(), "length of driver attributes")?);
 
             while self
                 .environment
@@ -581,8 +1068,12 @@ impl Environment {
                 driver_len = max(candidate_decs_len, driver_len);
             }
 
-            let mut server_name_buf = SzBuffer::with_capacity(server_name_len as usize);
-            let mut driver_buf = SzBuffer::with_capacity(driver_len as usize);
+            let mut server_name_buf = SzBuffer::with_capacity(reported(
+                server_name_len.into(),
+                "length of a data source name",
+            )?);
+            let mut driver_buf =
+                SzBuffer::with_capacity(reported(driver_len.into(), "length of a driver name")?);
 
             let mut not_empty = self
                 .environment
@@ -611,15 +1102,83 @@ impl Environment {
         Ok(data_source_info)
     }
 
-    fn allocate_connection(&self) -> Result<handles::Connection, Error> {
+    /// Wraps a freshly opened connection, notifies the connection observer, if any, and executes
+    /// the session initialization statements.
+    fn opened<'c>(
+        &'c self,
+        connection: handles::Connection<'c>,
+        call_hook: Option<CallHookRegistration>,
+    ) -> Result<Connection<'c>, Error> {
+        let observer = self.connection_observer.read().unwrap().clone();
+        let count_guard = ConnectionCountGuard::new(&self.num_connections, &self.statements);
+        let connection = Connection::with_observer(connection, observer, count_guard, call_hook);
+        for statement in self.session_initialization.read().unwrap().iter() {
+            connection.execute(statement, ())?;
+        }
+        Ok(connection)
+    }
+
+    pub(crate) fn connect_to_target(
+        &self,
+        target: &ConnectionTarget,
+        login_timeout_sec: Option<u32>,
+    ) -> Result<Connection<'_>, Error> {
+        let (mut connection, call_hook) = self.allocate_connection()?;
+        if let Some(timeout) = login_timeout_sec {
+            connection
+                .set_login_timeout_sec(timeout)
+                .into_result(&connection)?;
+        }
+        match target {
+            ConnectionTarget::ConnectionString(connection_string) => connection
+                .connect_with_connection_string(&SqlText::new(connection_string))
+                .into_result(&connection)?,
+            ConnectionTarget::DataSource {
+                name,
+                user,
+                password,
+            } => connection
+                .connect(
+                    &SqlText::new(name),
+                    &SqlText::new(user),
+                    &SqlText::new(password),
+                )
+                .into_result(&connection)?,
+        }
+        self.opened(connection, call_hook)
+    }
+
+    /// Allocates a connection handle. The call hook of the environment, if any, is registered for
+    /// it, so it observes connecting as well.
+    fn allocate_connection(
+        &self,
+    ) -> Result<(handles::Connection, Option<CallHookRegistration>), Error> {
+        if self.shut_down.load(Ordering::SeqCst) {
+            return Err(Error::EnvironmentShutDown);
+        }
         // Hold lock diagnostics errors are consumed in this thread.
         let _lock = self.internal_state.lock().unwrap();
-        self.environment
+        let connection = self
+            .environment
             .allocate_connection()
-            .into_result(&self.environment)
+            .into_result(&self.environment)?;
+        let call_hook = self.call_hook.read().unwrap().as_ref().map(|registration| {
+            CallHookRegistration::new(connection.as_handle(), registration.hook())
+        });
+        Ok((connection, call_hook))
     }
 }
 
+/// Outcome of [`Environment::shutdown`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ShutdownReport {
+    /// Number of statements still executing after the timeout, which have been cancelled.
+    pub num_cancelled: usize,
+    /// Number of connections of the environment still alive at the end of the shutdown. These
+    /// should be dropped before the application exits.
+    pub num_connections: usize,
+}
+
 /// Struct holding information available on a driver. Can be obtained via [`Environment::drivers`].
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct DriverInfo {
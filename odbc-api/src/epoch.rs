@@ -0,0 +1,49 @@
+//! Converts dates and times fetched from a data source into offsets from the Unix epoch, as used by
+//! the file formats written with the `avro` and `arrow` features.
+
+use crate::sys::{Date, Time, Timestamp};
+
+/// Days since 1970-01-01 in the proleptic Gregorian calendar.
+pub fn days_since_epoch(date: &Date) -> i64 {
+    let year = i64::from(date.year) - i64::from(date.month <= 2);
+    let month = i64::from(date.month);
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + i64::from(date.day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+pub fn micros_since_midnight(time: &Time) -> i64 {
+    (i64::from(time.hour) * 3600 + i64::from(time.minute) * 60 + i64::from(time.second)) * 1_000_000
+}
+
+/// Whole seconds since the epoch of a timestamp without time zone, interpreted as UTC. The
+/// fraction of the timestamp is ignored.
+pub fn seconds_since_epoch(timestamp: &Timestamp) -> i64 {
+    let date = Date {
+        year: timestamp.year,
+        month: timestamp.month,
+        day: timestamp.day,
+    };
+    days_since_epoch(&date) * 86_400
+        + i64::from(timestamp.hour) * 3600
+        + i64::from(timestamp.minute) * 60
+        + i64::from(timestamp.second)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sys::Date;
+
+    use super::days_since_epoch;
+
+    #[test]
+    fn dates_as_days_since_epoch() {
+        let days = |year, month, day| days_since_epoch(&Date { year, month, day });
+
+        assert_eq!(0, days(1970, 1, 1));
+        assert_eq!(-1, days(1969, 12, 31));
+        assert_eq!(19_782, days(2024, 2, 29));
+    }
+}
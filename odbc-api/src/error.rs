@@ -2,7 +2,11 @@ use std::io;
 
 use thiserror::Error as ThisError;
 
-use crate::handles::{log_diagnostics, Diagnostics, Record as DiagnosticRecord, SqlResult};
+use crate::{
+    buffers::{BufferDesc, CellAccessError},
+    handles::{log_diagnostics, Diagnostics, Record as DiagnosticRecord, SqlResult},
+    DataType, FetchPlanViolation,
+};
 
 /// Error indicating a failed allocation for a column buffer
 #[derive(Debug)]
@@ -14,6 +18,19 @@ pub struct TooLargeBufferSize {
 }
 
 impl TooLargeBufferSize {
+    /// Total number of bytes requested for the buffer.
+    pub fn requested_bytes(&self) -> usize {
+        self.num_elements.saturating_mul(self.element_size)
+    }
+
+    /// Map the allocation error to [`crate::Error::TooLargeAllocation`]. Used for buffers which
+    /// are not associated with a column of a result set, e.g. array parameters.
+    pub fn into_allocation_error(self) -> Error {
+        Error::TooLargeAllocation {
+            requested_bytes: self.requested_bytes(),
+        }
+    }
+
     /// Map the column allocation error to an [`crate::Error`] adding the context of which
     /// column caused the allocation error.
     pub fn add_context(self, buffer_index: u16) -> Error {
@@ -116,11 +133,299 @@ pub enum Error {
         num_elements: usize,
         element_size: usize,
     },
+    /// Allocating a buffer failed, because not enough memory is available. Returned by fallible
+    /// allocations, e.g. of array parameter buffers, rather than aborting the process.
+    #[error(
+        "There is not enough memory to allocate a buffer. Requested bytes: {requested_bytes}. \
+        Consider reducing the batch size."
+    )]
+    TooLargeAllocation { requested_bytes: usize },
     #[error(
         "A value (at least one) is too large to be written into the allocated buffer without
         truncation."
     )]
     TooLargeValueForBuffer,
+    /// A value is longer than the maximum size allowed for it. See
+    /// [`crate::CursorRow::get_variadic_capped`] and [`crate::TruncationPolicy::Cap`].
+    #[error(
+        "The value in column {column} is too large. Size: {}; Maximum size: {cap} bytes.",
+        describe_size(*size)
+    )]
+    ValueTooLarge {
+        /// One based index of the column.
+        column: u16,
+        /// Size of the value in bytes, as reported by the driver. `None` if the driver does not
+        /// report it.
+        size: Option<usize>,
+        /// Maximum size of the value in bytes.
+        cap: usize,
+    },
+    #[error(
+        "Returning generated keys is not supported for the database management system \
+        '{dbms_name}'. Consider specifying how to retrieve the keys explicitly."
+    )]
+    UnsupportedKeyRetrieval {
+        /// Name of the database management system, as reported by the driver.
+        dbms_name: String,
+    },
+    /// Loading or saving the watermark of an [`crate::IncrementalExtraction`] failed.
+    #[error("Failed to load or save the watermark of an incremental extraction:\n{0}")]
+    WatermarkStore(io::Error),
+    /// Querying or switching the default schema is not supported for the database management
+    /// system. See [`crate::Connection::set_current_schema`].
+    #[error(
+        "Querying or switching the default schema is not supported for the database management \
+        system '{dbms_name}'."
+    )]
+    UnsupportedSchemaSwitching {
+        /// Name of the database management system, as reported by the driver.
+        dbms_name: String,
+    },
+    /// A statement issued by this crate in order to query information, e.g. the default schema,
+    /// did not return a result set.
+    #[error("The statement '{statement}' did not return a result set.")]
+    NoResultSet {
+        /// Text of the statement.
+        statement: String,
+    },
+    /// A key column used for pagination (see [`crate::Connection::paginate`]) contained `NULL`.
+    #[error(
+        "A key column used for pagination contained NULL. Keyset pagination requires key columns \
+        without NULL values."
+    )]
+    NullPaginationKey,
+    /// Connection pooling could not be changed, because environments created by this crate are
+    /// still alive. See [`crate::Environment::set_connection_pooling_checked`].
+    #[error(
+        "Connection pooling is a process wide setting and has not been changed, since ODBC \
+        environments are still in use. Currently {num_environments} environment(s) are alive."
+    )]
+    ConnectionPoolingWithLiveEnvironments {
+        /// Number of environments alive at the time of the call.
+        num_environments: usize,
+    },
+    /// Writing or reading the spill file of a [`crate::SpoolingCursor`] or a
+    /// [`crate::DeduplicatingCursor`] failed.
+    #[error("Failed to write or read a spill file:\n{0}")]
+    SpillFile(io::Error),
+    /// The driver reports no upper bound for the length of a column, e.g. `VARCHAR(max)`, and the
+    /// application did not specify one either.
+    #[error(
+        "The driver reports no upper bound for the length of column {column_number}. Specify a \
+        maximum string length."
+    )]
+    UnboundedColumnLength {
+        /// One based index of the column.
+        column_number: u16,
+    },
+    /// Writing the result set as an Arrow IPC stream failed. See
+    /// [`crate::Cursor::write_arrow_ipc`].
+    #[cfg(feature = "arrow")]
+    #[error("Failed to write the result set as Arrow IPC stream:\n{0}")]
+    FailedWritingArrow(io::Error),
+    /// A floating point buffer would lose precision of a column. See
+    /// [`crate::buffers::BufferBuilder`].
+    #[error(
+        "Refusing to fetch column {column_number} of type {data_type:?} into a {buffer_desc:?} \
+        buffer, since this may silently lose precision. Bind a text or integer buffer instead, or \
+        explicitly allow the loss of precision."
+    )]
+    LossyFloatBuffer {
+        column_number: u16,
+        data_type: DataType,
+        buffer_desc: BufferDesc,
+    },
+    /// None of the targets passed to [`crate::Environment::connect_first_available`] could be
+    /// connected to.
+    #[error(
+        "Failed to connect to any of {num_targets} connection target(s) in {num_attempts} \
+        attempt(s). Last error:\n{last}"
+    )]
+    NoConnectionTargetAvailable {
+        num_targets: usize,
+        num_attempts: usize,
+        /// Error of the last attempt.
+        last: Box<Error>,
+    },
+    /// The result set has no column with the name of a field registered with
+    /// [`crate::RowMapper::field`].
+    #[error("The result set has no column named '{name}' to map a field from.")]
+    RowMapperUnknownColumn {
+        /// Name of the field, as passed to [`crate::RowMapper::field`].
+        name: String,
+    },
+    /// The result set polled by a [`crate::ChangeCapture`] lacks its watermark or creation column.
+    #[error("The result set has no audit column named '{name}' to capture changes with.")]
+    MissingAuditColumn {
+        /// Name of the watermark or creation column.
+        name: String,
+    },
+    /// A [`crate::RowMapper`] fetched `NULL` into a field, which is not an `Option`.
+    #[error(
+        "Column {column_number} contained NULL, but the field it is mapped to can not represent \
+        NULL. Use an `Option` for fields mapped from nullable columns."
+    )]
+    RowMapperUnexpectedNull { column_number: u16 },
+    /// A closure registered with a [`crate::buffers::BatchExtractor`] could not read the column
+    /// buffer it has been registered for.
+    #[error("Failed to extract the values of column buffer {buffer_index}: {source}")]
+    BatchExtraction {
+        buffer_index: usize,
+        source: CellAccessError,
+    },
+    /// [`crate::Connection::disconnect`] has been called while statements allocated from the
+    /// connection are still alive.
+    #[error(
+        "Refusing to disconnect, since {num_active} statement(s) allocated from the connection are \
+        still alive. Statements are usually leaked using `std::mem::forget`, if this happens. Use \
+        `Connection::disconnect_force` to disconnect anyway."
+    )]
+    ActiveStatements { num_active: usize },
+    /// Connecting failed, because [`crate::Environment::shutdown`] has been called.
+    #[error("The ODBC environment has been shut down. No further connections can be opened.")]
+    EnvironmentShutDown,
+    /// [`crate::CancellationRegistry::run`] has been called with an id, under which another
+    /// statement is currently registered.
+    #[error(
+        "Another statement is currently registered under the same id for cancellation. Ids must be \
+        unique among the statements running at the same time."
+    )]
+    QueryIdInUse,
+    /// Values have been truncated, and the cursor could not be positioned on the current row set
+    /// again, in order to fetch them into grown buffers. See [`crate::TruncationPolicy::Grow`].
+    #[error(
+        "Values have been truncated and could not be fetched again into larger buffers, since the \
+        cursor could not be repositioned. Growing buffers on truncation requires a scrollable \
+        cursor.\nDiagnostic record returned by SQLFetchScroll:\n{record}"
+    )]
+    TruncationNotRecoverable { record: DiagnosticRecord },
+    /// Writing the result set as delimited text failed. See [`crate::Cursor::write_delimited`].
+    #[error("Failed to write the result set as delimited text:\n{0}")]
+    FailedWritingDelimited(io::Error),
+    /// Rendering the result set as a table failed. See [`crate::Cursor::write_table`].
+    #[error("Failed to render the result set as a table:\n{0}")]
+    FailedRenderingTable(io::Error),
+    /// The thread of a [`crate::ConnectionWorker`] could not be spawned.
+    #[error("Failed to spawn the thread of the connection worker:\n{0}")]
+    FailedSpawningWorker(io::Error),
+    /// The thread of a [`crate::ConnectionWorker`] terminated, e.g. because a function passed to
+    /// [`crate::ConnectionWorker::run`] panicked.
+    #[error("The thread of the connection worker terminated unexpectedly.")]
+    WorkerTerminated,
+    /// The cursor of a [`crate::WorkerCursor`] has been closed, because fetching from it failed.
+    #[error(
+        "The cursor of the connection worker has been closed, because fetching from it failed."
+    )]
+    WorkerCursorClosed,
+    /// The driver reports not to support an option requested with [`crate::StatementOptions`].
+    #[error("The driver does not support {option}, requested in the statement options.")]
+    UnsupportedStatementOption {
+        /// Description of the option, e.g. `static cursors`.
+        option: &'static str,
+    },
+    /// An argument passed by the application is out of range, e.g. a page size of zero. Returned
+    /// rather than panicking, since such arguments are often derived from configuration or user
+    /// input.
+    #[error("Invalid argument: {description}.")]
+    InvalidArgument {
+        /// Describes the violated requirement, e.g. `page size must be at least one`.
+        description: &'static str,
+    },
+    /// A value set for a placeholder of a [`crate::SqlTemplate`] does not match the placeholders of
+    /// the template, or a placeholder has been rendered without a value.
+    #[error("Invalid argument for SQL template: {description}")]
+    InvalidTemplateArgument {
+        /// Names the placeholder and the problem with its value.
+        description: String,
+    },
+    /// The data source does not support quoted identifiers and the identifier is not a plain name
+    /// of ASCII letters, digits and underscores. It can therefore not be embedded into a statement
+    /// safely.
+    #[error(
+        "The identifier `{identifier}` can not be used, since the data source does not support \
+        quoting identifiers. Without quotes only ASCII letters, digits and underscores are allowed."
+    )]
+    UnquotableIdentifier { identifier: String },
+    /// The driver reported a value violating the ODBC specification, e.g. a negative length of a
+    /// column. Returned rather than panicking, see the panic policy in the crate documentation.
+    #[error("The driver reported an invalid {description}: {value}")]
+    InvalidDriverValue {
+        /// What the value describes, e.g. `display size`.
+        description: &'static str,
+        value: i64,
+    },
+    /// The driver reported text which is not correctly encoded, i.e. not UTF-16 (or UTF-8 if the
+    /// `narrow` feature is enabled). Returned rather than panicking, see the panic policy in the
+    /// crate documentation.
+    #[error("The driver reported {description} which is not correctly encoded.")]
+    InvalidDriverText {
+        /// What the text describes, e.g. `an identifier quote character`.
+        description: &'static str,
+    },
+    /// A statement generated by this crate exceeds the maximum statement length reported by the
+    /// driver (see [`crate::Connection::max_statement_len`]), even though it has been split as far
+    /// as possible, e.g. a single statement of [`crate::Connection::execute_script`], or an
+    /// [`crate::Connection::execute_in_list`] statement with a single value. Returned rather than
+    /// having the driver silently truncate the statement text.
+    #[error(
+        "The statement text is too long for the driver. Length: {length} characters; Maximum \
+        length: {max_length} characters."
+    )]
+    StatementTooLong { length: usize, max_length: usize },
+    /// A truncated value passed to [`crate::parameter::VarCell::try_from_buffer`] is not
+    /// terminated with zero.
+    #[error("Truncated value must be terminated with zero.")]
+    UnterminatedTruncatedValue,
+    /// A [`crate::FetchPlan`] does not match the result set it is validated against.
+    #[error("Fetch plan does not match the result set. Column '{name}': {violation}")]
+    FetchPlanMismatch {
+        /// Name of the column, as given in the plan.
+        name: String,
+        violation: FetchPlanViolation,
+    },
+    /// The driver did not report the length of a variadic value and [`crate::NoTotalPolicy::Error`]
+    /// has been chosen.
+    #[error(
+        "The driver did not report the total length of the value in column {column_number}, while \
+        fetching it with `SQLGetData`."
+    )]
+    NoTotal { column_number: u16 },
+    /// Saving or loading a recorded result set failed. See [`crate::mock::MockResultSet::save`].
+    #[cfg(feature = "mock")]
+    #[error("Failed to save or load a recorded result set:\n{0}")]
+    RecordedResultSet(io::Error),
+    /// Writing the result set as Avro failed. See [`crate::Cursor::write_avro`].
+    #[cfg(feature = "avro")]
+    #[error("Failed to write the result set as Avro:\n{0}")]
+    FailedWritingAvro(io::Error),
+    /// Text could not be converted to the narrow encoding of the driver, since it contains
+    /// characters which can not be represented in it. See [`crate::encoding::encode`].
+    #[cfg(feature = "encoding")]
+    #[error("The text contains characters which can not be represented in {encoding}.")]
+    UnmappableCharacter {
+        /// Name of the encoding, e.g. `windows-1252`.
+        encoding: &'static str,
+    },
+    /// A function of the installer API failed. See [`crate::installer`].
+    #[cfg(feature = "installer")]
+    #[error(
+        "The ODBC installer function '{function}' failed:\n{}",
+        crate::installer::join_messages(messages)
+    )]
+    Installer {
+        /// Installer function which failed, e.g. `SQLConfigDataSource`.
+        function: &'static str,
+        /// Errors reported by `SQLInstallerError`.
+        messages: Vec<crate::installer::InstallerMessage>,
+    },
+}
+
+/// Size of a value in the message of [`Error::ValueTooLarge`].
+fn describe_size(size: Option<usize>) -> String {
+    match size {
+        Some(size) => format!("{size} bytes"),
+        None => "unknown".to_owned(),
+    }
 }
 
 impl Error {
@@ -212,6 +517,8 @@ impl<T> SqlResult<T> {
                 let mut record = DiagnosticRecord::with_capacity(512);
                 if record.fill_from(handle, 1) {
                     log_diagnostics(handle);
+                    record.call_history = handle.call_history();
+                    record.context = handle.context();
                     Err(Error::Diagnostics { record, function })
                 } else {
                     // Anecdotal ways to reach this code paths:
@@ -230,6 +537,9 @@ impl<T> SqlResult<T> {
             SqlResult::StillExecuting => panic!(
                 "SqlResult must not be converted to result while the function is still executing."
             ),
+            SqlResult::InvalidDriverValue { description, value } => {
+                Err(Error::InvalidDriverValue { description, value })
+            }
         }
     }
 }
@@ -1,10 +1,12 @@
-use std::intrinsics::transmute;
+use std::{intrinsics::transmute, io, thread, time::Duration};
 
 use crate::{
-    handles::{AsStatementRef, SqlText, Statement},
+    handles::{AsStatementRef, SqlText, Statement, StatementRef},
     parameter::Blob,
+    parameter_types::RetypingStatement,
+    re_prepare::RePreparePolicy,
     sleep::wait_for,
-    CursorImpl, CursorPolling, Error, ParameterCollectionRef, Sleep,
+    CursorImpl, CursorPolling, DataType, Error, ParameterCollectionRef, Quirks, Sleep,
 };
 
 /// Shared implementation for executing a query with parameters between [`crate::Connection`],
@@ -27,7 +29,7 @@ where
     S: AsStatementRef,
 {
     unsafe {
-        if let Some(statement) = bind_parameters(lazy_statement, params)? {
+        if let Some(statement) = bind_parameters(lazy_statement, params, &[])? {
             execute(statement, query)
         } else {
             Ok(None)
@@ -35,6 +37,50 @@ where
     }
 }
 
+/// Like [`execute_with_parameters`] for a prepared query, but binds the parameters with the SQL
+/// data types in `parameter_types`, where specified. See [`crate::Prepared::with_parameter_types`].
+/// If the execution fails with an error `re_prepare` applies to, `query` is prepared again, the
+/// execution is retried once and `on_re_prepare` is invoked, e.g. to drop a cached schema. See
+/// [`crate::RePreparePolicy`]. Parameters are bound working around `quirks` of the driver.
+pub fn execute_prepared<S>(
+    mut statement: S,
+    mut params: impl ParameterCollectionRef,
+    parameter_types: &[Option<DataType>],
+    query: &str,
+    re_prepare: &RePreparePolicy,
+    quirks: &Quirks,
+    on_re_prepare: impl FnOnce(),
+) -> Result<Option<CursorImpl<S>>, Error>
+where
+    S: AsStatementRef,
+{
+    if params.parameter_set_size() == 0 {
+        return Ok(None);
+    }
+    let mut stmt = statement.as_stmt_ref();
+    let has_result_set = unsafe {
+        bind_parameters_to(&mut stmt, &mut params, parameter_types, quirks)?;
+        match execute_stmt(stmt.as_stmt_ref(), None) {
+            Err(error) if re_prepare.applies_to(&error) => {
+                log::debug!("Preparing statement again after execution failed: {error}");
+                stmt.prepare(&SqlText::new(query)).into_result(&stmt)?;
+                on_re_prepare();
+                // Bind again, in case preparing the statement discarded the bindings.
+                bind_parameters_to(&mut stmt, &mut params, parameter_types, quirks)?;
+                execute_stmt(stmt.as_stmt_ref(), None)?
+            }
+            result => result?,
+        }
+    };
+    if has_result_set {
+        // Safe: `statement` is in cursor state.
+        let cursor = unsafe { CursorImpl::new(statement) };
+        Ok(Some(cursor))
+    } else {
+        Ok(None)
+    }
+}
+
 /// Asynchronous sibiling of [`execute_with_parameters`]
 pub async fn execute_with_parameters_polling<S>(
     lazy_statement: impl FnOnce() -> Result<S, Error>,
@@ -46,7 +92,7 @@ where
     S: AsStatementRef,
 {
     unsafe {
-        if let Some(statement) = bind_parameters(lazy_statement, params)? {
+        if let Some(statement) = bind_parameters(lazy_statement, params, &[])? {
             execute_polling(statement, query, sleep).await
         } else {
             Ok(None)
@@ -57,6 +103,7 @@ where
 unsafe fn bind_parameters<S>(
     lazy_statement: impl FnOnce() -> Result<S, Error>,
     mut params: impl ParameterCollectionRef,
+    parameter_types: &[Option<DataType>],
 ) -> Result<Option<S>, Error>
 where
     S: AsStatementRef,
@@ -68,15 +115,34 @@ where
 
     // Only allocate the statement, if we know we are going to execute something.
     let mut statement = lazy_statement()?;
-    let mut stmt = statement.as_stmt_ref();
+    bind_parameters_to(
+        &mut statement.as_stmt_ref(),
+        &mut params,
+        parameter_types,
+        &Quirks::default(),
+    )?;
+    Ok(Some(statement))
+}
+
+unsafe fn bind_parameters_to(
+    stmt: &mut StatementRef<'_>,
+    params: &mut impl ParameterCollectionRef,
+    parameter_types: &[Option<DataType>],
+    quirks: &Quirks,
+) -> Result<(), Error> {
     // Reset parameters so we do not dereference stale once by mistake if we call
     // `exec_direct`.
-    stmt.reset_parameters().into_result(&stmt)?;
-    stmt.set_paramset_size(parameter_set_size)
-        .into_result(&stmt)?;
+    stmt.reset_parameters().into_result(stmt)?;
+    stmt.set_paramset_size(params.parameter_set_size())
+        .into_result(stmt)?;
     // Bind new parameters passed by caller.
-    params.bind_parameters_to(&mut stmt)?;
-    Ok(Some(statement))
+    if parameter_types.is_empty() && !quirks.affects_parameters() {
+        params.bind_parameters_to(stmt)?;
+    } else {
+        let mut retyping = RetypingStatement::new(stmt, parameter_types, *quirks);
+        params.bind_parameters_to(&mut retyping)?;
+    }
+    Ok(())
 }
 
 /// # Safety
@@ -91,7 +157,21 @@ pub unsafe fn execute<S>(
 where
     S: AsStatementRef,
 {
-    let mut stmt = statement.as_stmt_ref();
+    if execute_stmt(statement.as_stmt_ref(), query)? {
+        // Safe: `statement` is in cursor state.
+        let cursor = CursorImpl::new(statement);
+        Ok(Some(cursor))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Executes the statement and puts the data of delayed parameters. Returns `true` if a result set
+/// has been created. See [`execute`] for safety.
+unsafe fn execute_stmt(
+    mut stmt: StatementRef<'_>,
+    query: Option<&SqlText<'_>>,
+) -> Result<bool, Error> {
     let result = if let Some(sql) = query {
         // We execute an unprepared "one shot query"
         stmt.exec_direct(sql)
@@ -114,20 +194,22 @@ where
             let blob_ptr: *mut &mut dyn Blob = transmute(blob_ptr);
             let blob_ref = &mut *blob_ptr;
             // Loop over all batches within each blob
-            while let Some(batch) = blob_ref.next_batch().map_err(Error::FailedReadingInput)? {
-                stmt.put_binary_batch(batch).into_result(&stmt)?;
+            loop {
+                match blob_ref.next_batch() {
+                    Ok(Some(batch)) => stmt.put_binary_batch(batch).into_result(&stmt)?,
+                    Ok(None) => break,
+                    // The source is not ready yet. Pause, rather than spinning.
+                    Err(error) if error.kind() == io::ErrorKind::WouldBlock => {
+                        thread::sleep(Duration::from_millis(1))
+                    }
+                    Err(error) => return Err(Error::FailedReadingInput(error)),
+                }
             }
         }
     }
 
     // Check if a result set has been created.
-    if stmt.num_result_cols().into_result(&stmt)? == 0 {
-        Ok(None)
-    } else {
-        // Safe: `statement` is in cursor state.
-        let cursor = CursorImpl::new(statement);
-        Ok(Some(cursor))
-    }
+    Ok(stmt.num_result_cols().into_result(&stmt)? != 0)
 }
 
 /// # Safety
@@ -166,9 +248,19 @@ where
             let blob_ptr: *mut &mut dyn Blob = transmute(blob_ptr);
             let blob_ref = &mut *blob_ptr;
             // Loop over all batches within each blob
-            while let Some(batch) = blob_ref.next_batch().map_err(Error::FailedReadingInput)? {
-                let result = wait_for(|| stmt.put_binary_batch(batch), &mut sleep).await;
-                result.into_result(&stmt)?;
+            loop {
+                match blob_ref.next_batch() {
+                    Ok(Some(batch)) => {
+                        let result = wait_for(|| stmt.put_binary_batch(batch), &mut sleep).await;
+                        result.into_result(&stmt)?;
+                    }
+                    Ok(None) => break,
+                    // The source is not ready yet. Give it a chance to catch up.
+                    Err(error) if error.kind() == io::ErrorKind::WouldBlock => {
+                        sleep.next_poll().await;
+                    }
+                    Err(error) => return Err(Error::FailedReadingInput(error)),
+                }
             }
         }
     }
@@ -0,0 +1,317 @@
+use crate::{
+    buffers::{AnyBuffer, AnySliceMut, BufferDesc, Item},
+    handles::StatementRef,
+    ColumnarBulkInserter, Error,
+};
+
+/// Values which can be copied into an array bound parameter buffer by
+/// [`crate::Prepared::execute_many`].
+pub trait ArrayElement {
+    /// Describes a buffer able to hold the value. `max_len` is the largest
+    /// [`ArrayElement::len_in_buffer`] of all values within the batch.
+    fn buffer_desc(max_len: usize) -> BufferDesc;
+
+    /// Length of variable sized values in buffer elements, `0` for fixed sized values. Used to
+    /// determine the element size of text and binary buffers.
+    fn len_in_buffer(&self) -> usize {
+        0
+    }
+
+    /// Copies the value into the cell at `row_index` of `column`.
+    ///
+    /// # Panics
+    ///
+    /// If `column` is not of the type described by [`ArrayElement::buffer_desc`].
+    fn write_into(&self, column: AnySliceMut<'_>, row_index: usize);
+}
+
+impl<T> ArrayElement for T
+where
+    T: Item,
+{
+    fn buffer_desc(_max_len: usize) -> BufferDesc {
+        T::buffer_desc(false)
+    }
+
+    fn write_into(&self, column: AnySliceMut<'_>, row_index: usize) {
+        T::as_slice_mut(column).expect("Column buffer must match the element type.")[row_index] =
+            *self;
+    }
+}
+
+impl<T> ArrayElement for Option<T>
+where
+    T: Item,
+{
+    fn buffer_desc(_max_len: usize) -> BufferDesc {
+        T::buffer_desc(true)
+    }
+
+    fn write_into(&self, column: AnySliceMut<'_>, row_index: usize) {
+        T::as_nullable_slice_mut(column)
+            .expect("Column buffer must match the element type.")
+            .set_cell(row_index, *self)
+    }
+}
+
+fn text_desc(max_len: usize) -> BufferDesc {
+    // Some drivers reject binding parameters with a column size of zero.
+    BufferDesc::Text {
+        max_str_len: max_len.max(1),
+    }
+}
+
+fn write_text(column: AnySliceMut<'_>, row_index: usize, text: Option<&str>) {
+    column
+        .as_text_view()
+        .expect("Column buffer must hold text.")
+        .set_cell(row_index, text.map(str::as_bytes))
+}
+
+fn binary_desc(max_len: usize) -> BufferDesc {
+    BufferDesc::Binary {
+        length: max_len.max(1),
+    }
+}
+
+fn write_binary(column: AnySliceMut<'_>, row_index: usize, bytes: Option<&[u8]>) {
+    column
+        .as_bin_view()
+        .expect("Column buffer must hold binary data.")
+        .set_cell(row_index, bytes)
+}
+
+impl ArrayElement for &str {
+    fn buffer_desc(max_len: usize) -> BufferDesc {
+        text_desc(max_len)
+    }
+
+    fn len_in_buffer(&self) -> usize {
+        self.len()
+    }
+
+    fn write_into(&self, column: AnySliceMut<'_>, row_index: usize) {
+        write_text(column, row_index, Some(self))
+    }
+}
+
+impl ArrayElement for String {
+    fn buffer_desc(max_len: usize) -> BufferDesc {
+        text_desc(max_len)
+    }
+
+    fn len_in_buffer(&self) -> usize {
+        self.len()
+    }
+
+    fn write_into(&self, column: AnySliceMut<'_>, row_index: usize) {
+        write_text(column, row_index, Some(self))
+    }
+}
+
+impl ArrayElement for Option<&str> {
+    fn buffer_desc(max_len: usize) -> BufferDesc {
+        text_desc(max_len)
+    }
+
+    fn len_in_buffer(&self) -> usize {
+        self.map_or(0, str::len)
+    }
+
+    fn write_into(&self, column: AnySliceMut<'_>, row_index: usize) {
+        write_text(column, row_index, *self)
+    }
+}
+
+impl ArrayElement for Option<String> {
+    fn buffer_desc(max_len: usize) -> BufferDesc {
+        text_desc(max_len)
+    }
+
+    fn len_in_buffer(&self) -> usize {
+        self.as_ref().map_or(0, String::len)
+    }
+
+    fn write_into(&self, column: AnySliceMut<'_>, row_index: usize) {
+        write_text(column, row_index, self.as_deref())
+    }
+}
+
+impl ArrayElement for &[u8] {
+    fn buffer_desc(max_len: usize) -> BufferDesc {
+        binary_desc(max_len)
+    }
+
+    fn len_in_buffer(&self) -> usize {
+        self.len()
+    }
+
+    fn write_into(&self, column: AnySliceMut<'_>, row_index: usize) {
+        write_binary(column, row_index, Some(self))
+    }
+}
+
+impl ArrayElement for Vec<u8> {
+    fn buffer_desc(max_len: usize) -> BufferDesc {
+        binary_desc(max_len)
+    }
+
+    fn len_in_buffer(&self) -> usize {
+        self.len()
+    }
+
+    fn write_into(&self, column: AnySliceMut<'_>, row_index: usize) {
+        write_binary(column, row_index, Some(self))
+    }
+}
+
+impl ArrayElement for Option<&[u8]> {
+    fn buffer_desc(max_len: usize) -> BufferDesc {
+        binary_desc(max_len)
+    }
+
+    fn len_in_buffer(&self) -> usize {
+        self.map_or(0, <[u8]>::len)
+    }
+
+    fn write_into(&self, column: AnySliceMut<'_>, row_index: usize) {
+        write_binary(column, row_index, *self)
+    }
+}
+
+impl ArrayElement for Option<Vec<u8>> {
+    fn buffer_desc(max_len: usize) -> BufferDesc {
+        binary_desc(max_len)
+    }
+
+    fn len_in_buffer(&self) -> usize {
+        self.as_ref().map_or(0, Vec::len)
+    }
+
+    fn write_into(&self, column: AnySliceMut<'_>, row_index: usize) {
+        write_binary(column, row_index, self.as_deref())
+    }
+}
+
+/// A tuple of parameters, which can be copied into the array bound parameter buffers of a
+/// [`ColumnarBulkInserter`]. Implemented for tuples of up to ten [`ArrayElement`]s.
+pub trait ParameterRow: Sized {
+    /// Describes buffers able to hold every row in `rows`.
+    fn buffer_descs(rows: &[Self]) -> Vec<BufferDesc>;
+
+    /// Copies the parameters into the row at `row_index` of `inserter`.
+    fn write_into(
+        &self,
+        inserter: &mut ColumnarBulkInserter<StatementRef<'_>, AnyBuffer>,
+        row_index: usize,
+    );
+}
+
+macro_rules! impl_parameter_row_for_tuple {
+    ($($t:ident $index:tt)*) => (
+        impl<$($t: ArrayElement,)*> ParameterRow for ($($t,)*) {
+            fn buffer_descs(rows: &[Self]) -> Vec<BufferDesc> {
+                vec![$(
+                    $t::buffer_desc(
+                        rows.iter().map(|row| row.$index.len_in_buffer()).max().unwrap_or(0)
+                    ),
+                )*]
+            }
+
+            fn write_into(
+                &self,
+                inserter: &mut ColumnarBulkInserter<StatementRef<'_>, AnyBuffer>,
+                row_index: usize,
+            ) {
+                $(self.$index.write_into(inserter.column_mut($index), row_index);)*
+            }
+        }
+    );
+}
+
+impl_parameter_row_for_tuple! { A 0 }
+impl_parameter_row_for_tuple! { A 0 B 1 }
+impl_parameter_row_for_tuple! { A 0 B 1 C 2 }
+impl_parameter_row_for_tuple! { A 0 B 1 C 2 D 3 }
+impl_parameter_row_for_tuple! { A 0 B 1 C 2 D 3 E 4 }
+impl_parameter_row_for_tuple! { A 0 B 1 C 2 D 3 E 4 F 5 }
+impl_parameter_row_for_tuple! { A 0 B 1 C 2 D 3 E 4 F 5 G 6 }
+impl_parameter_row_for_tuple! { A 0 B 1 C 2 D 3 E 4 F 5 G 6 H 7 }
+impl_parameter_row_for_tuple! { A 0 B 1 C 2 D 3 E 4 F 5 G 6 H 7 I 8 }
+impl_parameter_row_for_tuple! { A 0 B 1 C 2 D 3 E 4 F 5 G 6 H 7 I 8 J 9 }
+
+/// Outcome of [`crate::Prepared::execute_many`].
+#[derive(Debug, Default)]
+pub struct ExecuteManyReport {
+    /// Sum of the rows affected by each successfully executed batch, as far as reported by the
+    /// driver.
+    pub rows_affected: usize,
+    /// Batches which could not be executed. Execution continues with the next batch after an error.
+    pub batch_errors: Vec<BatchError>,
+}
+
+/// A batch of rows which could not be executed by [`crate::Prepared::execute_many`].
+#[derive(Debug)]
+pub struct BatchError {
+    /// Zero based index of the first row of the batch within the input.
+    pub first_row: usize,
+    /// Number of rows in the batch.
+    pub num_rows: usize,
+    /// Error returned executing the batch, or retrieving the number of rows affected by it. In the
+    /// latter case the rows of the batch may still have been inserted.
+    pub error: Error,
+}
+
+/// Descriptions of buffers large enough to hold the values described by either `a` or `b`.
+pub(crate) fn merge_buffer_descs(a: &[BufferDesc], b: &[BufferDesc]) -> Vec<BufferDesc> {
+    if a.is_empty() {
+        return b.to_vec();
+    }
+    a.iter()
+        .zip(b)
+        .map(|(&a, &b)| match (a, b) {
+            (BufferDesc::Text { max_str_len: a }, BufferDesc::Text { max_str_len: b }) => {
+                BufferDesc::Text {
+                    max_str_len: a.max(b),
+                }
+            }
+            (BufferDesc::Binary { length: a }, BufferDesc::Binary { length: b }) => {
+                BufferDesc::Binary { length: a.max(b) }
+            }
+            (a, _) => a,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffers::BufferDesc;
+
+    use super::{merge_buffer_descs, ParameterRow};
+
+    #[test]
+    fn buffer_descs_fit_longest_value() {
+        let rows = [(1i32, Some("a"), "abc"), (2, None, "abcde")];
+
+        let descs = <(i32, Option<&str>, &str)>::buffer_descs(&rows);
+
+        assert_eq!(
+            vec![
+                BufferDesc::I32 { nullable: false },
+                BufferDesc::Text { max_str_len: 1 },
+                BufferDesc::Text { max_str_len: 5 }
+            ],
+            descs
+        );
+        let merged = merge_buffer_descs(
+            &descs,
+            &[
+                BufferDesc::I32 { nullable: false },
+                BufferDesc::Text { max_str_len: 3 },
+                BufferDesc::Text { max_str_len: 2 },
+            ],
+        );
+        assert_eq!(BufferDesc::Text { max_str_len: 3 }, merged[1]);
+        assert_eq!(BufferDesc::Text { max_str_len: 5 }, merged[2]);
+    }
+}
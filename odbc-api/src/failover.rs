@@ -0,0 +1,571 @@
+use std::{
+    collections::hash_map::RandomState,
+    fmt,
+    hash::{BuildHasher, Hasher},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use log::warn;
+
+use crate::{Connection, Environment, Error, Latency};
+
+/// A data source to connect to. See [`crate::Environment::connect_first_available`].
+///
+/// The `Debug` representation does not show passwords, so targets can be logged safely.
+#[derive(Clone, PartialEq, Eq)]
+pub enum ConnectionTarget {
+    /// Connect using [`crate::Environment::connect_with_connection_string`].
+    ConnectionString(String),
+    /// Connect using [`crate::Environment::connect`].
+    DataSource {
+        /// Data source name
+        name: String,
+        /// User identifier
+        user: String,
+        /// Authentication string (typically the password)
+        password: String,
+    },
+}
+
+impl ConnectionTarget {
+    /// Target connected to using a connection string.
+    pub fn connection_string(connection_string: impl Into<String>) -> Self {
+        ConnectionTarget::ConnectionString(connection_string.into())
+    }
+
+    /// Target connected to using a data source name and credentials.
+    pub fn data_source(
+        name: impl Into<String>,
+        user: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        ConnectionTarget::DataSource {
+            name: name.into(),
+            user: user.into(),
+            password: password.into(),
+        }
+    }
+}
+
+impl fmt::Debug for ConnectionTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConnectionTarget::ConnectionString(connection_string) => f
+                .debug_tuple("ConnectionString")
+                .field(&redact_password(connection_string))
+                .finish(),
+            ConnectionTarget::DataSource { name, user, .. } => f
+                .debug_struct("DataSource")
+                .field("name", name)
+                .field("user", user)
+                .field("password", &REDACTED)
+                .finish(),
+        }
+    }
+}
+
+/// Shown instead of passwords.
+const REDACTED: &str = "***";
+
+/// Replaces the value of the `PWD` attribute of a connection string. Values may be enclosed in
+/// braces, in which case they can contain `;`, and `}` is escaped as `}}`.
+fn redact_password(connection_string: &str) -> String {
+    let mut redacted = String::with_capacity(connection_string.len());
+    let mut rest = connection_string;
+    while let Some(equals) = rest.find('=') {
+        let key = &rest[..equals];
+        let value = &rest[equals + 1..];
+        let value_len = if value.starts_with('{') {
+            braced_len(value)
+        } else {
+            value.find(';').unwrap_or(value.len())
+        };
+        redacted.push_str(key);
+        redacted.push('=');
+        if key.trim().eq_ignore_ascii_case("PWD") {
+            redacted.push_str(REDACTED);
+        } else {
+            redacted.push_str(&value[..value_len]);
+        }
+        rest = &value[value_len..];
+        if let Some(after_separator) = rest.strip_prefix(';') {
+            redacted.push(';');
+            rest = after_separator;
+        }
+    }
+    redacted.push_str(rest);
+    redacted
+}
+
+/// Length of a value enclosed in braces, including the braces. The entire text, if the closing
+/// brace is missing.
+fn braced_len(value: &str) -> usize {
+    let bytes = value.as_bytes();
+    let mut index = 1;
+    while index < bytes.len() {
+        if bytes[index] == b'}' {
+            if bytes.get(index + 1) == Some(&b'}') {
+                index += 2;
+                continue;
+            }
+            return index + 1;
+        }
+        index += 1;
+    }
+    bytes.len()
+}
+
+/// Controls timeouts and retries of [`crate::Environment::connect_first_available_with_options`].
+///
+/// Each round tries every target once, in order. Between rounds the thread sleeps for the retry
+/// delay, which doubles every round. A random jitter of up to half the delay is added, so clients
+/// which lost their connection at the same time do not retry in lockstep.
+///
+/// ```
+/// use std::time::Duration;
+/// use odbc_api::FailoverOptions;
+///
+/// let options = FailoverOptions::new()
+///     .with_login_timeout(Some(Duration::from_secs(5)))
+///     .with_rounds(5)
+///     .with_retry_delay(Duration::from_millis(200));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FailoverOptions {
+    login_timeout: Option<Duration>,
+    rounds: u32,
+    retry_delay: Duration,
+}
+
+impl Default for FailoverOptions {
+    fn default() -> Self {
+        Self {
+            login_timeout: Some(Duration::from_secs(15)),
+            rounds: 3,
+            retry_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+impl FailoverOptions {
+    /// Login timeout of 15 seconds, three rounds and a retry delay of one second.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Time a single connection attempt may take, before trying the next target. Set as
+    /// `SQL_ATTR_LOGIN_TIMEOUT` and enforced by the driver, which only supports whole seconds.
+    /// Fractions are rounded up. `None` leaves the default of the driver in place.
+    pub fn with_login_timeout(mut self, login_timeout: Option<Duration>) -> Self {
+        self.login_timeout = login_timeout;
+        self
+    }
+
+    /// How often each target is tried, at most. At least `1`.
+    pub fn with_rounds(mut self, rounds: u32) -> Self {
+        self.rounds = rounds.max(1);
+        self
+    }
+
+    /// Time to wait after the first round failed. Doubles after each further round.
+    pub fn with_retry_delay(mut self, retry_delay: Duration) -> Self {
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    pub(crate) fn rounds(&self) -> u32 {
+        self.rounds
+    }
+
+    /// Login timeout in seconds, as understood by `SQL_ATTR_LOGIN_TIMEOUT`.
+    pub(crate) fn login_timeout_sec(&self) -> Option<u32> {
+        self.login_timeout.map(whole_seconds)
+    }
+
+    /// Time to wait after `round` (zero based) failed, including jitter.
+    pub(crate) fn delay_after(&self, round: u32) -> Duration {
+        let delay = self
+            .retry_delay
+            .saturating_mul(2u32.saturating_pow(round.min(16)));
+        let jitter = RandomState::new().build_hasher().finish() % 1000;
+        delay.saturating_add((delay / 2).mul_f64(jitter as f64 / 1000.0))
+    }
+}
+
+/// How [`ReplicaSet::connect`] picks among the healthy replicas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplicaSelection {
+    /// Cycle through the replicas in order.
+    RoundRobin,
+    /// Pick the replica which has not been connected to for the longest time.
+    LeastRecentlyUsed,
+    /// Pick the replica with the lowest median round trip time, as measured by
+    /// [`ReplicaSet::measure_latencies`] or reported via [`ReplicaSet::report_latency`]. Replicas
+    /// without a measurement come last, in order.
+    LowestLatency,
+}
+
+/// Spreads read only connections across a set of replicas of the same database.
+///
+/// Each call to [`Self::connect`] picks a replica according to the [`ReplicaSelection`]. A replica
+/// which fails to connect, or is reported via [`Self::report_failure`], is considered unhealthy
+/// and not picked again until its quarantine has passed. Should all replicas be unhealthy, they are
+/// tried anyway, starting with the one whose quarantine ends first.
+///
+/// A `ReplicaSet` is shared between threads by reference. Connections are opened through the
+/// environment, so they are taken from and returned to the pool of the driver manager, if
+/// connection pooling is enabled (see [`Environment::set_connection_pooling`]).
+///
+/// ```no_run
+/// use odbc_api::{ConnectionTarget, Environment, ReplicaSelection, ReplicaSet};
+///
+/// let env = Environment::new()?;
+/// let replicas = ReplicaSet::new(vec![
+///     ConnectionTarget::data_source("replica_a", "reader", "My@Test@Password1"),
+///     ConnectionTarget::data_source("replica_b", "reader", "My@Test@Password1"),
+/// ])
+/// .with_selection(ReplicaSelection::LeastRecentlyUsed);
+///
+/// let (conn, replica) = replicas.connect(&env)?;
+/// if conn.execute("SELECT * FROM Movies", ()).is_err() {
+///     replicas.report_failure(replica);
+/// }
+/// # Ok::<(), odbc_api::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct ReplicaSet {
+    replicas: Vec<ConnectionTarget>,
+    selection: ReplicaSelection,
+    quarantine: Duration,
+    login_timeout: Option<Duration>,
+    health: Mutex<ReplicaHealth>,
+}
+
+impl ReplicaSet {
+    /// Round robin selection among `replicas`, which are quarantined for 30 seconds after a
+    /// failure. Connection attempts time out after 5 seconds.
+    ///
+    /// # Panics
+    ///
+    /// If `replicas` is empty.
+    pub fn new(replicas: Vec<ConnectionTarget>) -> Self {
+        assert!(!replicas.is_empty(), "At least one replica required.");
+        let health = ReplicaHealth {
+            next: 0,
+            last_used: vec![None; replicas.len()],
+            unhealthy_until: vec![None; replicas.len()],
+            latency: vec![None; replicas.len()],
+        };
+        Self {
+            replicas,
+            selection: ReplicaSelection::RoundRobin,
+            quarantine: Duration::from_secs(30),
+            login_timeout: Some(Duration::from_secs(5)),
+            health: Mutex::new(health),
+        }
+    }
+
+    /// How replicas are picked among the healthy ones.
+    pub fn with_selection(mut self, selection: ReplicaSelection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// How long a failing replica is not picked.
+    pub fn with_quarantine(mut self, quarantine: Duration) -> Self {
+        self.quarantine = quarantine;
+        self
+    }
+
+    /// Time a single connection attempt may take, see [`FailoverOptions::with_login_timeout`].
+    pub fn with_login_timeout(mut self, login_timeout: Option<Duration>) -> Self {
+        self.login_timeout = login_timeout;
+        self
+    }
+
+    /// The replicas, in the order they have been passed to [`Self::new`].
+    pub fn replicas(&self) -> &[ConnectionTarget] {
+        &self.replicas
+    }
+
+    /// Connects to a healthy replica. Returns the connection and the index of the replica. Tries
+    /// further replicas, if the connection fails. [`Error::NoConnectionTargetAvailable`] is
+    /// returned if connecting to every replica failed.
+    pub fn connect<'e>(
+        &self,
+        environment: &'e Environment,
+    ) -> Result<(Connection<'e>, usize), Error> {
+        let candidates = self
+            .health
+            .lock()
+            .unwrap()
+            .candidates(self.selection, Instant::now());
+        let login_timeout_sec = self.login_timeout.map(whole_seconds);
+        let mut last = None;
+        for &index in &candidates {
+            match environment.connect_to_target(&self.replicas[index], login_timeout_sec) {
+                Ok(connection) => {
+                    self.health.lock().unwrap().used(index, Instant::now());
+                    return Ok((connection, index));
+                }
+                Err(error) => {
+                    warn!("Replica {index} is unavailable: {error}");
+                    self.report_failure(index);
+                    last = Some(error);
+                }
+            }
+        }
+        Err(Error::NoConnectionTargetAvailable {
+            num_targets: self.replicas.len(),
+            num_attempts: candidates.len(),
+            last: Box::new(last.expect("At least one attempt must have been made")),
+        })
+    }
+
+    /// Quarantines the replica at `index`, e.g. because a query executed on a connection to it
+    /// failed in a way indicating the replica is unavailable.
+    pub fn report_failure(&self, index: usize) {
+        let until = Instant::now() + self.quarantine;
+        self.health.lock().unwrap().unhealthy_until[index] = Some(until);
+    }
+
+    /// Records the round trip times measured on a connection to the replica at `index`, e.g. by
+    /// [`Connection::measure_latency`]. Used by [`ReplicaSelection::LowestLatency`].
+    pub fn report_latency(&self, index: usize, latency: Latency) {
+        self.health.lock().unwrap().latency[index] = Some(latency);
+    }
+
+    /// Round trip times last reported for the replica at `index`. `None` if none have been
+    /// reported yet.
+    pub fn latency(&self, index: usize) -> Option<Latency> {
+        self.health.lock().unwrap().latency[index]
+    }
+
+    /// Connects to each replica in turn and measures its round trip time with `samples` probe
+    /// queries (see [`Connection::measure_latency`]). Replicas which fail to connect, or to answer
+    /// the probes, are quarantined. Returns the measurements by replica index.
+    pub fn measure_latencies(
+        &self,
+        environment: &Environment,
+        samples: usize,
+    ) -> Vec<Option<Latency>> {
+        let login_timeout_sec = self.login_timeout.map(whole_seconds);
+        (0..self.replicas.len())
+            .map(|index| {
+                let measured = environment
+                    .connect_to_target(&self.replicas[index], login_timeout_sec)
+                    .and_then(|connection| connection.measure_latency(samples));
+                match measured {
+                    Ok(latency) => {
+                        self.report_latency(index, latency);
+                        Some(latency)
+                    }
+                    Err(error) => {
+                        warn!("Failed to measure latency of replica {index}: {error}");
+                        self.report_failure(index);
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// `false` if the replica at `index` is in quarantine.
+    pub fn is_healthy(&self, index: usize) -> bool {
+        self.health
+            .lock()
+            .unwrap()
+            .is_healthy(index, Instant::now())
+    }
+
+    /// Number of replicas not in quarantine.
+    pub fn num_healthy(&self) -> usize {
+        let health = self.health.lock().unwrap();
+        let now = Instant::now();
+        (0..self.replicas.len())
+            .filter(|&index| health.is_healthy(index, now))
+            .count()
+    }
+}
+
+#[derive(Debug)]
+struct ReplicaHealth {
+    /// Next replica to consider for round robin selection.
+    next: usize,
+    last_used: Vec<Option<Instant>>,
+    unhealthy_until: Vec<Option<Instant>>,
+    latency: Vec<Option<Latency>>,
+}
+
+impl ReplicaHealth {
+    fn is_healthy(&self, index: usize, now: Instant) -> bool {
+        self.unhealthy_until[index].is_none_or(|until| until <= now)
+    }
+
+    /// Indices of the replicas in the order they should be tried. Healthy replicas come first, in
+    /// the order given by `selection`, unhealthy ones last, ordered by the end of their quarantine.
+    fn candidates(&mut self, selection: ReplicaSelection, now: Instant) -> Vec<usize> {
+        let num_replicas = self.last_used.len();
+        let mut candidates: Vec<usize> = match selection {
+            ReplicaSelection::RoundRobin => {
+                let start = self.next;
+                self.next = (self.next + 1) % num_replicas;
+                (0..num_replicas)
+                    .map(|offset| (start + offset) % num_replicas)
+                    .collect()
+            }
+            ReplicaSelection::LeastRecentlyUsed => {
+                let mut indices: Vec<usize> = (0..num_replicas).collect();
+                // `None` (never used) sorts before any point in time
+                indices.sort_by_key(|&index| self.last_used[index]);
+                indices
+            }
+            ReplicaSelection::LowestLatency => {
+                let mut indices: Vec<usize> = (0..num_replicas).collect();
+                indices.sort_by_key(|&index| match self.latency[index] {
+                    Some(latency) => (false, latency.median),
+                    None => (true, Duration::ZERO),
+                });
+                indices
+            }
+        };
+        // Stable sort keeps the order among the healthy replicas
+        candidates.sort_by_key(|&index| {
+            if self.is_healthy(index, now) {
+                None
+            } else {
+                self.unhealthy_until[index]
+            }
+        });
+        candidates
+    }
+
+    fn used(&mut self, index: usize, now: Instant) {
+        self.last_used[index] = Some(now);
+        self.unhealthy_until[index] = None;
+    }
+}
+
+/// Rounds up to whole seconds. At least `1`, since `0` disables the login timeout entirely.
+fn whole_seconds(timeout: Duration) -> u32 {
+    let secs = timeout.as_secs() + u64::from(timeout.subsec_nanos() != 0);
+    secs.clamp(1, u32::MAX as u64) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::Latency;
+
+    use super::{ConnectionTarget, FailoverOptions, ReplicaHealth, ReplicaSelection};
+
+    fn health(num_replicas: usize) -> ReplicaHealth {
+        ReplicaHealth {
+            next: 0,
+            last_used: vec![None; num_replicas],
+            unhealthy_until: vec![None; num_replicas],
+            latency: vec![None; num_replicas],
+        }
+    }
+
+    #[test]
+    fn debug_representation_hides_passwords() {
+        let connection_string = ConnectionTarget::connection_string(
+            "Driver={ODBC Driver 17 for SQL Server};UID=SA;pwd={se;cr}}et};Server=primary",
+        );
+        let data_source = ConnectionTarget::data_source("sales", "SA", "secret");
+
+        assert_eq!(
+            r#"ConnectionString("Driver={ODBC Driver 17 for SQL Server};UID=SA;pwd=***;Server=primary")"#,
+            format!("{connection_string:?}")
+        );
+        assert_eq!(
+            r#"DataSource { name: "sales", user: "SA", password: "***" }"#,
+            format!("{data_source:?}")
+        );
+    }
+
+    #[test]
+    fn login_timeout_is_rounded_up_to_whole_seconds() {
+        let timeout = |duration| {
+            FailoverOptions::new()
+                .with_login_timeout(duration)
+                .login_timeout_sec()
+        };
+
+        assert_eq!(Some(2), timeout(Some(Duration::from_millis(1500))));
+        assert_eq!(Some(1), timeout(Some(Duration::ZERO)));
+        assert_eq!(None, timeout(None));
+    }
+
+    #[test]
+    fn delay_doubles_each_round_with_bounded_jitter() {
+        let options = FailoverOptions::new().with_retry_delay(Duration::from_millis(100));
+
+        for round in 0..3 {
+            let base = Duration::from_millis(100 * 2u64.pow(round));
+            let delay = options.delay_after(round);
+            assert!(delay >= base && delay <= base * 3 / 2, "{delay:?}");
+        }
+    }
+
+    #[test]
+    fn round_robin_skips_unhealthy_replicas() {
+        let now = Instant::now();
+        let mut health = health(3);
+        health.unhealthy_until[1] = Some(now + Duration::from_secs(1));
+
+        assert_eq!(
+            vec![0, 2, 1],
+            health.candidates(ReplicaSelection::RoundRobin, now)
+        );
+        assert_eq!(
+            vec![2, 0, 1],
+            health.candidates(ReplicaSelection::RoundRobin, now)
+        );
+        // Quarantine is over
+        let later = now + Duration::from_secs(2);
+        assert_eq!(
+            vec![2, 0, 1],
+            health.candidates(ReplicaSelection::RoundRobin, later)
+        );
+    }
+
+    #[test]
+    fn least_recently_used_replica_comes_first() {
+        let now = Instant::now();
+        let mut health = health(3);
+        health.used(0, now);
+        health.used(2, now + Duration::from_secs(1));
+
+        assert_eq!(
+            vec![1, 0, 2],
+            health.candidates(ReplicaSelection::LeastRecentlyUsed, now)
+        );
+    }
+
+    #[test]
+    fn lowest_latency_replica_comes_first() {
+        let now = Instant::now();
+        let mut health = health(4);
+        let latency = |millis| {
+            let median = Duration::from_millis(millis);
+            Some(Latency {
+                min: median,
+                median,
+                max: median,
+            })
+        };
+        health.latency[1] = latency(20);
+        health.latency[2] = latency(5);
+        health.latency[3] = latency(1);
+        health.unhealthy_until[3] = Some(now + Duration::from_secs(1));
+
+        assert_eq!(
+            vec![2, 1, 0, 3],
+            health.candidates(ReplicaSelection::LowestLatency, now)
+        );
+    }
+}
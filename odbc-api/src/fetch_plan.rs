@@ -0,0 +1,346 @@
+use thiserror::Error as ThisError;
+
+use crate::{
+    buffers::{BufferDesc, ColumnarAnyBuffer},
+    DataType, Error, Nullability, ResultSetMetadata, ResultSetSchema,
+};
+
+/// Declares which Rust type each column of a result set is fetched into, by column name. A plan is
+/// validated against the metadata of a cursor before anything is fetched, so a changed schema is
+/// reported as an error upfront, rather than as garbled values later on. Once validated it is
+/// compiled into a [`ColumnarAnyBuffer`], which lets the driver convert the values into the target
+/// types as they are fetched. The buffer at index `0` belongs to the first column of the plan, and
+/// so on, regardless of the position of the column in the result set.
+///
+/// With the `serde` feature enabled, plans implement `Serialize` and `Deserialize`, so they can be
+/// stored in configuration files. In YAML a plan looks like this:
+///
+/// ```yaml
+/// columns:
+///   - name: id
+///     type: i64
+///   - name: title
+///     type: text
+///     max_len: 255
+///     nullable: true
+/// ```
+///
+/// ```no_run
+/// use odbc_api::{Connection, Cursor, Error, FetchPlan, TargetType};
+///
+/// fn fetch_movies(conn: &Connection<'_>) -> Result<(), Error> {
+///     let plan = FetchPlan::new()
+///         .column("id", TargetType::I64, false)
+///         .column("title", TargetType::Text { max_len: 255 }, true);
+///     let mut cursor = conn.execute("SELECT title, year, id FROM Movies", ())?.unwrap();
+///     let buffer = plan.compile(&mut cursor, 1000)?;
+///     let mut row_set_cursor = cursor.bind_buffer(buffer)?;
+///     while let Some(batch) = row_set_cursor.fetch()? {
+///         let ids = batch.column(0).as_slice::<i64>().unwrap();
+///         let titles = batch.column(1).as_text_view().unwrap();
+///         // ...
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FetchPlan {
+    /// Columns to fetch, in the order of the buffers they are fetched into.
+    pub columns: Vec<PlannedColumn>,
+}
+
+/// A single column of a [`FetchPlan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlannedColumn {
+    /// Name of the column in the result set. Compared ignoring ASCII case.
+    pub name: String,
+    /// Type the values of the column are fetched into.
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub target: TargetType,
+    /// Whether the column may contain `NULL`. If `false`, columns reported as nullable by the
+    /// driver are rejected.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub nullable: bool,
+}
+
+/// Rust type the values of a column are fetched into. See [`FetchPlan`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
+pub enum TargetType {
+    /// [`crate::Bit`]. Accepts `BIT` columns.
+    Bool,
+    /// `i8`. Accepts integer columns with values fitting into it.
+    I8,
+    /// `i16`. Accepts integer columns with values fitting into it.
+    I16,
+    /// `i32`. Accepts integer columns with values fitting into it.
+    I32,
+    /// `i64`. Accepts integer columns, as well as `DECIMAL` and `NUMERIC` columns without
+    /// fractional digits and a precision of up to 18.
+    I64,
+    /// `f32`. Accepts single precision floating point and small integer columns.
+    F32,
+    /// `f64`. Accepts floating point columns and integer columns of up to 32 bits.
+    F64,
+    /// UTF-8 text of up to `max_len` bytes. Accepts columns of any type.
+    Text { max_len: usize },
+    /// Bytes of up to `max_len`. Accepts columns of any type.
+    Binary { max_len: usize },
+    /// [`crate::sys::Date`]. Accepts `DATE` columns.
+    Date,
+    /// [`crate::sys::Time`]. Accepts `TIME` columns.
+    Time,
+    /// [`crate::sys::Timestamp`]. Accepts `TIMESTAMP` and `DATE` columns.
+    Timestamp,
+}
+
+/// Reason a [`FetchPlan`] does not match a result set. See [`Error::FetchPlanMismatch`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ThisError)]
+pub enum FetchPlanViolation {
+    /// The result set has no such column.
+    #[error("The result set has no such column.")]
+    UnknownColumn,
+    /// The plan lists the column more than once.
+    #[error("The column is listed more than once in the fetch plan.")]
+    DuplicateColumn,
+    /// Values of the column can not be fetched into the target type without losing information.
+    #[error("Values of type {data_type:?} can not be fetched into the target type without loss.")]
+    IncompatibleType { data_type: DataType },
+    /// The column is nullable, but the plan does not allow `NULL` for it.
+    #[error("The column is nullable, but not declared as such in the fetch plan.")]
+    NullableColumn,
+}
+
+impl FetchPlan {
+    /// A plan without any columns.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a column to the plan.
+    pub fn column(mut self, name: impl Into<String>, target: TargetType, nullable: bool) -> Self {
+        self.columns.push(PlannedColumn {
+            name: name.into(),
+            target,
+            nullable,
+        });
+        self
+    }
+
+    /// Checks the plan against the result set described by `metadata`, without allocating any
+    /// buffers. Fails with [`Error::FetchPlanMismatch`] for the first column violating the plan.
+    pub fn validate(&self, metadata: &mut impl ResultSetMetadata) -> Result<(), Error> {
+        self.column_numbers(&ResultSetSchema::for_cursor(metadata)?)
+            .map(|_| ())
+    }
+
+    /// Validates the plan like [`Self::validate`] and allocates a buffer with room for `capacity`
+    /// rows, fetching each column of the plan into its target type.
+    pub fn compile(
+        &self,
+        metadata: &mut impl ResultSetMetadata,
+        capacity: usize,
+    ) -> Result<ColumnarAnyBuffer, Error> {
+        let column_numbers = self.column_numbers(&ResultSetSchema::for_cursor(metadata)?)?;
+        let descs = column_numbers
+            .into_iter()
+            .zip(&self.columns)
+            .map(|(column_number, column)| (column_number, column.buffer_desc()));
+        ColumnarAnyBuffer::try_from_descs_and_indices(capacity, descs)
+    }
+
+    /// Column numbers of the planned columns in the result set described by `schema`.
+    fn column_numbers(&self, schema: &ResultSetSchema) -> Result<Vec<u16>, Error> {
+        let mut column_numbers = Vec::with_capacity(self.columns.len());
+        for column in &self.columns {
+            let mismatch = |violation| Error::FetchPlanMismatch {
+                name: column.name.clone(),
+                violation,
+            };
+            let index = schema
+                .columns
+                .iter()
+                .position(|actual| actual.name.eq_ignore_ascii_case(&column.name))
+                .ok_or_else(|| mismatch(FetchPlanViolation::UnknownColumn))?;
+            let column_number = index as u16 + 1;
+            if column_numbers.contains(&column_number) {
+                return Err(mismatch(FetchPlanViolation::DuplicateColumn));
+            }
+            let actual = &schema.columns[index];
+            if !column.target.accepts(actual.data_type) {
+                return Err(mismatch(FetchPlanViolation::IncompatibleType {
+                    data_type: actual.data_type,
+                }));
+            }
+            if !column.nullable && actual.nullability == Nullability::Nullable {
+                return Err(mismatch(FetchPlanViolation::NullableColumn));
+            }
+            column_numbers.push(column_number);
+        }
+        Ok(column_numbers)
+    }
+}
+
+impl PlannedColumn {
+    fn buffer_desc(&self) -> BufferDesc {
+        let nullable = self.nullable;
+        match self.target {
+            TargetType::Bool => BufferDesc::Bit { nullable },
+            TargetType::I8 => BufferDesc::I8 { nullable },
+            TargetType::I16 => BufferDesc::I16 { nullable },
+            TargetType::I32 => BufferDesc::I32 { nullable },
+            TargetType::I64 => BufferDesc::I64 { nullable },
+            TargetType::F32 => BufferDesc::F32 { nullable },
+            TargetType::F64 => BufferDesc::F64 { nullable },
+            TargetType::Text { max_len } => BufferDesc::Text {
+                max_str_len: max_len,
+            },
+            TargetType::Binary { max_len } => BufferDesc::Binary { length: max_len },
+            TargetType::Date => BufferDesc::Date { nullable },
+            TargetType::Time => BufferDesc::Time { nullable },
+            TargetType::Timestamp => BufferDesc::Timestamp { nullable },
+        }
+    }
+}
+
+impl TargetType {
+    /// `true` if values of a column of `data_type` can be fetched into this type without losing
+    /// information.
+    fn accepts(self, data_type: DataType) -> bool {
+        let bits = integer_bits(data_type);
+        match self {
+            TargetType::Bool => data_type == DataType::Bit,
+            TargetType::I8 => bits.is_some_and(|bits| bits <= 8),
+            TargetType::I16 => bits.is_some_and(|bits| bits <= 16),
+            TargetType::I32 => bits.is_some_and(|bits| bits <= 32),
+            TargetType::I64 => bits.is_some_and(|bits| bits <= 64),
+            TargetType::F32 => {
+                matches!(
+                    data_type,
+                    DataType::Real | DataType::Float { precision: 0..=24 }
+                ) || bits.is_some_and(|bits| bits <= 16)
+            }
+            TargetType::F64 => {
+                matches!(
+                    data_type,
+                    DataType::Real | DataType::Float { .. } | DataType::Double
+                ) || bits.is_some_and(|bits| bits <= 32)
+            }
+            TargetType::Text { .. } | TargetType::Binary { .. } => true,
+            TargetType::Date => data_type == DataType::Date,
+            TargetType::Time => matches!(data_type, DataType::Time { .. }),
+            TargetType::Timestamp => {
+                matches!(data_type, DataType::Timestamp { .. } | DataType::Date)
+            }
+        }
+    }
+}
+
+/// Number of bits of the smallest signed integer holding any value of a column of `data_type`.
+/// `None` for non integer types.
+fn integer_bits(data_type: DataType) -> Option<u8> {
+    let bits = match data_type {
+        DataType::Bit | DataType::TinyInt => 8,
+        DataType::SmallInt => 16,
+        DataType::Integer => 32,
+        DataType::BigInt => 64,
+        DataType::Numeric {
+            precision,
+            scale: 0,
+        }
+        | DataType::Decimal {
+            precision,
+            scale: 0,
+        } => match precision {
+            0..=2 => 8,
+            3..=4 => 16,
+            5..=9 => 32,
+            10..=18 => 64,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ColumnSchema, DataType, Error, Nullability, ResultSetSchema};
+
+    use super::{FetchPlan, FetchPlanViolation, TargetType};
+
+    fn schema() -> ResultSetSchema {
+        ResultSetSchema {
+            columns: vec![
+                ColumnSchema::new(
+                    "title",
+                    DataType::Varchar { length: 50 },
+                    Nullability::Nullable,
+                ),
+                ColumnSchema::new("ID", DataType::BigInt, Nullability::NoNulls),
+            ],
+        }
+    }
+
+    #[test]
+    fn resolve_columns_by_name() {
+        let plan = FetchPlan::new()
+            .column("id", TargetType::I64, false)
+            .column("title", TargetType::Text { max_len: 50 }, true);
+
+        assert_eq!(vec![2, 1], plan.column_numbers(&schema()).unwrap());
+    }
+
+    #[test]
+    fn reject_narrowing_integer_target() {
+        let plan = FetchPlan::new().column("id", TargetType::I32, false);
+
+        let error = plan.column_numbers(&schema()).unwrap_err();
+
+        assert!(matches!(
+            error,
+            Error::FetchPlanMismatch {
+                violation: FetchPlanViolation::IncompatibleType {
+                    data_type: DataType::BigInt
+                },
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn reject_undeclared_nullable_column() {
+        let plan = FetchPlan::new().column("title", TargetType::Text { max_len: 50 }, false);
+
+        let error = plan.column_numbers(&schema()).unwrap_err();
+
+        assert!(matches!(
+            error,
+            Error::FetchPlanMismatch {
+                violation: FetchPlanViolation::NullableColumn,
+                ..
+            }
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_plan() {
+        let json = r#"{"columns": [
+            {"name": "id", "type": "i64"},
+            {"name": "title", "type": "text", "max_len": 255, "nullable": true}
+        ]}"#;
+
+        let plan: FetchPlan = serde_json::from_str(json).unwrap();
+
+        assert_eq!(
+            FetchPlan::new()
+                .column("id", TargetType::I64, false)
+                .column("title", TargetType::Text { max_len: 255 }, true),
+            plan
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::escape_attribute_value;
+
+/// Value of a `FILEDSN` or `SAVEFILE` attribute in a connection string, pointing to `path`.
+pub(crate) fn path_attribute_value(path: &Path) -> String {
+    escape_attribute_value(&path.to_string_lossy()).into_owned()
+}
+
+/// Appends `SAVEFILE` to `connection_string`, so the driver manager saves the attributes of the
+/// connection to `path`.
+pub(crate) fn with_save_file(connection_string: &str, path: &Path) -> String {
+    let attributes = connection_string.trim_end().trim_end_matches(';');
+    let save_file = path_attribute_value(path);
+    if attributes.is_empty() {
+        format!("SAVEFILE={save_file};")
+    } else {
+        format!("{attributes};SAVEFILE={save_file};")
+    }
+}
+
+/// Detects whether the driver manager saved a file DSN, by comparing the files it may have
+/// written before and after connecting.
+pub(crate) struct SaveFileWatch {
+    /// Files the driver manager may write to, and their state before connecting.
+    candidates: Vec<(PathBuf, Option<FileState>)>,
+}
+
+/// Modification time and size of a file, to tell whether it has been written.
+type FileState = (Option<SystemTime>, u64);
+
+impl SaveFileWatch {
+    /// Watches `path`. The driver manager appends the extension `.dsn` to file names without one,
+    /// so this is watched, too.
+    pub fn new(path: &Path) -> Self {
+        let mut paths = vec![path.to_owned()];
+        if path.extension().is_none() {
+            paths.push(path.with_extension("dsn"));
+        }
+        let candidates = paths
+            .into_iter()
+            .map(|path| {
+                let state = file_state(&path);
+                (path, state)
+            })
+            .collect();
+        Self { candidates }
+    }
+
+    /// Path of the file written since the watch has been created, if any.
+    pub fn saved(&self) -> Option<PathBuf> {
+        self.candidates
+            .iter()
+            .find(|(path, before)| {
+                let after = file_state(path);
+                after.is_some() && after != *before
+            })
+            .map(|(path, _)| path.clone())
+    }
+}
+
+fn file_state(path: &Path) -> Option<FileState> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok(), metadata.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::Path};
+
+    use super::{with_save_file, SaveFileWatch};
+
+    #[test]
+    fn append_save_file_attribute() {
+        let path = Path::new("/home/me/my;db.dsn");
+
+        assert_eq!(
+            "DSN=db;SAVEFILE={/home/me/my;db.dsn};",
+            with_save_file("DSN=db; ", path)
+        );
+        assert_eq!("SAVEFILE={/home/me/my;db.dsn};", with_save_file("", path));
+    }
+
+    #[test]
+    fn detect_saved_file_with_appended_extension() {
+        let path = env::temp_dir().join(format!("odbc_api_save_file_{}", std::process::id()));
+        let written = path.with_extension("dsn");
+        let _ = fs::remove_file(&written);
+        let watch = SaveFileWatch::new(&path);
+        assert_eq!(None, watch.saved());
+
+        fs::write(&written, "[ODBC]\nDRIVER=SQLite3\n").unwrap();
+        let saved = watch.saved();
+        fs::remove_file(&written).unwrap();
+
+        assert_eq!(Some(written), saved);
+    }
+}
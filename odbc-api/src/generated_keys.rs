@@ -0,0 +1,130 @@
+/// Mechanism used by [`crate::Connection::execute_returning_keys`] to obtain the keys generated by
+/// the data source for inserted rows. The appropriate mechanism depends on the SQL dialect of the
+/// data source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyRetrieval {
+    /// Adds an `OUTPUT INSERTED.<key>` clause to the `INSERT` statement. Returns the keys of all
+    /// inserted rows. Used for Microsoft SQL Server. Fails for tables with triggers.
+    Output,
+    /// Appends a `RETURNING <key>` clause to the `INSERT` statement. Returns the keys of all
+    /// inserted rows. Used for PostgreSQL and SQLite (requires version 3.35 or later).
+    Returning,
+    /// Executes `SELECT SCOPE_IDENTITY()` within the same batch as the `INSERT` statement. Returns
+    /// a single value, the identity of the last inserted row. An alternative to
+    /// [`KeyRetrieval::Output`] for Microsoft SQL Server, e.g. for tables with triggers.
+    ScopeIdentity,
+    /// Executes `SELECT LAST_INSERT_ID()` after the `INSERT` statement. Returns a single value, the
+    /// key of the first inserted row. Used for MySQL and MariaDB.
+    LastInsertId,
+}
+
+impl KeyRetrieval {
+    /// Chooses the mechanism based on the name of the database management system, as returned by
+    /// [`crate::Connection::database_management_system_name`]. `None` if the DBMS is not known.
+    pub fn for_dbms_name(dbms_name: &str) -> Option<Self> {
+        match dbms_name {
+            "Microsoft SQL Server" => Some(KeyRetrieval::Output),
+            "PostgreSQL" | "SQLite" => Some(KeyRetrieval::Returning),
+            "MySQL" | "MariaDB" => Some(KeyRetrieval::LastInsertId),
+            _ => None,
+        }
+    }
+
+    /// Statement to execute instead of `insert`. `None` if the statement has to be executed
+    /// unchanged. Falls back to [`KeyRetrieval::ScopeIdentity`] for [`KeyRetrieval::Output`], if
+    /// the `INSERT` statement contains neither a `VALUES`, nor a `SELECT`, nor a `DEFAULT` clause.
+    pub(crate) fn rewrite(self, insert: &str, key_columns: &[&str]) -> Option<String> {
+        let insert = insert.trim_end().trim_end_matches(';');
+        match self {
+            KeyRetrieval::Output => {
+                if let Some(position) = find_values_clause(insert) {
+                    let output = key_columns
+                        .iter()
+                        .map(|key| format!("INSERTED.{key}"))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    Some(format!(
+                        "{} OUTPUT {output} {}",
+                        insert[..position].trim_end(),
+                        &insert[position..]
+                    ))
+                } else {
+                    KeyRetrieval::ScopeIdentity.rewrite(insert, key_columns)
+                }
+            }
+            KeyRetrieval::Returning => {
+                Some(format!("{insert} RETURNING {}", key_columns.join(", ")))
+            }
+            KeyRetrieval::ScopeIdentity => {
+                Some(format!("SET NOCOUNT ON; {insert}; SELECT SCOPE_IDENTITY()"))
+            }
+            KeyRetrieval::LastInsertId => None,
+        }
+    }
+}
+
+/// Byte position of the first `VALUES`, `SELECT` or `DEFAULT` keyword, which is neither part of a
+/// quoted string or identifier, nor enclosed in parentheses.
+fn find_values_clause(insert: &str) -> Option<usize> {
+    let bytes = insert.as_bytes();
+    let mut depth = 0;
+    let mut quote = None;
+    for (position, &byte) in bytes.iter().enumerate() {
+        match (quote, byte) {
+            (Some(q), _) if byte == q => quote = None,
+            (Some(_), _) => (),
+            (None, b'\'' | b'"' | b'`') => quote = Some(byte),
+            (None, b'[') => quote = Some(b']'),
+            (None, b'(') => depth += 1,
+            (None, b')') => depth -= 1,
+            (None, _) if depth == 0 => {
+                let is_word_start = position == 0 || !is_identifier_char(bytes[position - 1]);
+                let rest = &bytes[position..];
+                let starts_keyword = ["VALUES", "SELECT", "DEFAULT"].iter().any(|keyword| {
+                    rest.len() >= keyword.len()
+                        && rest[..keyword.len()].eq_ignore_ascii_case(keyword.as_bytes())
+                        && rest
+                            .get(keyword.len())
+                            .filter(|&&next| is_identifier_char(next))
+                            .is_none()
+                });
+                if is_word_start && starts_keyword {
+                    return Some(position);
+                }
+            }
+            (None, _) => (),
+        }
+    }
+    None
+}
+
+fn is_identifier_char(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KeyRetrieval;
+
+    #[test]
+    fn rewrite_insert_statements() {
+        let insert = "INSERT INTO Movies (title, year) VALUES ('SELECT (VALUES)', 1999);";
+
+        assert_eq!(
+            Some(
+                "INSERT INTO Movies (title, year) OUTPUT INSERTED.id VALUES ('SELECT (VALUES)', \
+                1999)"
+                    .to_owned()
+            ),
+            KeyRetrieval::Output.rewrite(insert, &["id"])
+        );
+        assert_eq!(
+            Some(
+                "INSERT INTO Movies (title, year) VALUES ('SELECT (VALUES)', 1999) RETURNING id"
+                    .to_owned()
+            ),
+            KeyRetrieval::Returning.rewrite(insert, &["id"])
+        );
+        assert_eq!(None, KeyRetrieval::LastInsertId.rewrite(insert, &["id"]));
+    }
+}
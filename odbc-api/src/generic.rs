@@ -0,0 +1,154 @@
+//! Object safe traits abstracting over the most common operations on connections, prepared
+//! statements and cursors. Application code written against these traits can be unit tested using
+//! the in memory fakes provided in the `mock` module (requires the `mock` feature), rather than a
+//! live ODBC driver.
+
+use crate::{
+    handles::AsStatementRef, parameter::InputParameter, result_set_metadata::reported,
+    ColumnSchema, Connection, Cursor, CursorImpl, DataType, Error, Nullability, Prepared,
+    ResultSetMetadata, ResultSetSchema,
+};
+
+/// Object safe subset of the operations on [`crate::Connection`].
+///
+/// ```
+/// use odbc_api::{parameter::InputParameter, Error, GenericConnection, IntoParameter};
+///
+/// /// Works with a real `odbc_api::Connection` as well as with a mock.
+/// fn count_birthdays(conn: &dyn GenericConnection, year: i32) -> Result<usize, Error> {
+///     let params: [Box<dyn InputParameter>; 1] = [Box::new(year)];
+///     let mut count = 0;
+///     if let Some(mut cursor) = conn.execute("SELECT name FROM Birthdays WHERE year=?", &params)? {
+///         while cursor.next_text_row()?.is_some() {
+///             count += 1;
+///         }
+///     }
+///     Ok(count)
+/// }
+/// ```
+pub trait GenericConnection {
+    /// Executes an SQL statement, see [`crate::Connection::execute`]. Parameters are bound in the
+    /// order of the slice, i.e. `params[0]` is bound to the first placeholder.
+    fn execute(
+        &self,
+        query: &str,
+        params: &[Box<dyn InputParameter>],
+    ) -> Result<Option<Box<dyn GenericCursor + '_>>, Error>;
+
+    /// Prepares an SQL statement, see [`crate::Connection::prepare`].
+    fn prepare(&self, query: &str) -> Result<Box<dyn GenericPrepared + '_>, Error>;
+
+    /// Specify the transaction mode, see [`crate::Connection::set_autocommit`].
+    fn set_autocommit(&self, enabled: bool) -> Result<(), Error>;
+
+    /// To commit a transaction in manual-commit mode.
+    fn commit(&self) -> Result<(), Error>;
+
+    /// To rollback a transaction in manual-commit mode.
+    fn rollback(&self) -> Result<(), Error>;
+}
+
+/// Object safe subset of the operations on [`crate::Prepared`].
+pub trait GenericPrepared {
+    /// Execute the prepared statement, see [`crate::Prepared::execute`].
+    fn execute(
+        &mut self,
+        params: &[Box<dyn InputParameter>],
+    ) -> Result<Option<Box<dyn GenericCursor + '_>>, Error>;
+}
+
+/// Object safe subset of the operations on a [`crate::Cursor`]. Values are fetched row by row as
+/// text. Like [`crate::Cursor::next_row`] this is convenient, but slow. Code which needs high
+/// throughput should bind buffers to a [`crate::Cursor`] directly.
+pub trait GenericCursor {
+    /// Names of the columns in the result set.
+    fn column_names(&mut self) -> Result<Vec<String>, Error>;
+
+    /// Names, types and nullability of the columns in the result set, as reported by the driver.
+    /// The default implementation reports the column names, with unknown types and nullability.
+    fn result_set_schema(&mut self) -> Result<ResultSetSchema, Error> {
+        let columns = self
+            .column_names()?
+            .into_iter()
+            .map(|name| ColumnSchema::new(name, DataType::Unknown, Nullability::Unknown))
+            .collect();
+        Ok(ResultSetSchema { columns })
+    }
+
+    /// Advances the cursor to the next row and returns all its fields formatted as text. `NULL`
+    /// is represented as `None`. Returns `None` after the last row of the result set.
+    fn next_text_row(&mut self) -> Result<Option<Vec<Option<String>>>, Error>;
+}
+
+impl<'c> GenericConnection for Connection<'c> {
+    fn execute(
+        &self,
+        query: &str,
+        params: &[Box<dyn InputParameter>],
+    ) -> Result<Option<Box<dyn GenericCursor + '_>>, Error> {
+        let cursor = Connection::execute(self, query, params)?;
+        Ok(cursor.map(|c| Box::new(c) as Box<dyn GenericCursor>))
+    }
+
+    fn prepare(&self, query: &str) -> Result<Box<dyn GenericPrepared + '_>, Error> {
+        let prepared = Connection::prepare(self, query)?;
+        Ok(Box::new(prepared))
+    }
+
+    fn set_autocommit(&self, enabled: bool) -> Result<(), Error> {
+        Connection::set_autocommit(self, enabled)
+    }
+
+    fn commit(&self) -> Result<(), Error> {
+        Connection::commit(self)
+    }
+
+    fn rollback(&self) -> Result<(), Error> {
+        Connection::rollback(self)
+    }
+}
+
+impl<S> GenericPrepared for Prepared<S>
+where
+    S: AsStatementRef,
+{
+    fn execute(
+        &mut self,
+        params: &[Box<dyn InputParameter>],
+    ) -> Result<Option<Box<dyn GenericCursor + '_>>, Error> {
+        let cursor = Prepared::execute(self, params)?;
+        Ok(cursor.map(|c| Box::new(c) as Box<dyn GenericCursor>))
+    }
+}
+
+impl<S> GenericCursor for CursorImpl<S>
+where
+    S: AsStatementRef,
+{
+    fn column_names(&mut self) -> Result<Vec<String>, Error> {
+        ResultSetMetadata::column_names(self)?.collect()
+    }
+
+    fn result_set_schema(&mut self) -> Result<ResultSetSchema, Error> {
+        ResultSetSchema::for_cursor(self)
+    }
+
+    fn next_text_row(&mut self) -> Result<Option<Vec<Option<String>>>, Error> {
+        let num_cols: u16 = reported(self.num_result_cols()?.into(), "number of columns")?;
+        let mut row = match self.next_row()? {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+        let mut buf = Vec::new();
+        let mut fields = Vec::with_capacity(num_cols.into());
+        for col_index in 1..=num_cols {
+            let field = if row.get_text(col_index, &mut buf)? {
+                Some(String::from_utf8_lossy(&buf).into_owned())
+            } else {
+                None
+            };
+            fields.push(field);
+        }
+        Ok(Some(fields))
+    }
+}
@@ -0,0 +1,147 @@
+use crate::{
+    buffers::{CellValue, OwnedCellValue, RowEq},
+    handles::AsStatementRef,
+    peekable_cursor::BufferedRow,
+    Cursor, Error, PeekableCursor,
+};
+
+/// Groups consecutive rows sharing the same values in a set of key columns. Intended for result
+/// sets ordered by these columns, e.g. a join of orders with their order lines, from which one
+/// document per order is assembled. Groups may span several batches, so rows are visited one at a
+/// time, rather than as slices of a batch.
+///
+/// ```
+/// use odbc_api::{
+///     buffers::{BufferDesc, ColumnarAnyBuffer}, Cursor, Error, GroupedRows, PeekableCursor,
+/// };
+///
+/// /// Prints each order with its lines. Expects the result of a query like
+/// /// `SELECT o.id, l.product FROM orders o JOIN lines l ON l.order_id = o.id ORDER BY o.id`.
+/// fn print_orders(cursor: impl Cursor) -> Result<(), Error> {
+///     let descs = [
+///         BufferDesc::I64 { nullable: false },
+///         BufferDesc::Text { max_str_len: 255 },
+///     ];
+///     let buffer = ColumnarAnyBuffer::from_descs(1000, descs);
+///     let rows = PeekableCursor::new(cursor.bind_buffer(buffer)?);
+///     let mut orders = GroupedRows::new(rows, vec![0]);
+///     while orders.next_group()? {
+///         println!("Order:");
+///         while let Some(line) = orders.next_row()? {
+///             let product: String = line.get(1).unwrap();
+///             println!("  {product}");
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct GroupedRows<C: AsStatementRef> {
+    rows: PeekableCursor<C>,
+    /// Buffer indices of the key columns. Empty if all columns form the key.
+    key_columns: Vec<usize>,
+    row_eq: RowEq,
+    /// Key of the current group. Copied, since the group may outlive the batch of its first row.
+    key: Vec<Option<OwnedCellValue>>,
+    /// `true` if the current group may still have rows which have not been consumed.
+    in_group: bool,
+}
+
+impl<C> GroupedRows<C>
+where
+    C: Cursor,
+{
+    /// Groups the rows of `rows` by the columns with the buffer indices in `key_columns`. If
+    /// `key_columns` is empty, all columns of the buffer form the key. Keys are compared with
+    /// [`RowEq::new`].
+    pub fn new(rows: PeekableCursor<C>, key_columns: Vec<usize>) -> Self {
+        Self {
+            rows,
+            key_columns,
+            row_eq: RowEq::new(),
+            key: Vec::new(),
+            in_group: false,
+        }
+    }
+
+    /// Determines which keys are considered equal, e.g. to ignore trailing spaces of `CHAR`
+    /// columns. If `NULL` is not equal to `NULL`, each row with a `NULL` key forms a group of its
+    /// own.
+    pub fn with_row_eq(mut self, row_eq: RowEq) -> Self {
+        self.row_eq = row_eq;
+        self
+    }
+
+    /// Advances to the next group. Rows of the current group which have not been consumed are
+    /// skipped.
+    ///
+    /// # Return
+    ///
+    /// `false` if the result set is consumed.
+    pub fn next_group(&mut self) -> Result<bool, Error> {
+        while self.next_row()?.is_some() {}
+        let key = match self.rows.peek_row()? {
+            Some(row) => key_of(&self.key_columns, row)
+                .into_iter()
+                .map(|cell| cell.map(CellValue::into_owned))
+                .collect(),
+            None => return Ok(false),
+        };
+        self.key = key;
+        self.in_group = true;
+        Ok(true)
+    }
+
+    /// Consumes the next row of the current group.
+    ///
+    /// # Return
+    ///
+    /// `None` if all rows of the current group have been consumed, or [`Self::next_group`] has not
+    /// been called yet.
+    pub fn next_row(&mut self) -> Result<Option<BufferedRow<'_>>, Error> {
+        if !self.in_group {
+            return Ok(None);
+        }
+        let is_same_group = match self.rows.peek_row()? {
+            Some(row) => key_of(&self.key_columns, row)
+                .into_iter()
+                .zip(&self.key)
+                .all(|(a, b)| {
+                    self.row_eq
+                        .values_eq(a, b.as_ref().map(OwnedCellValue::as_cell))
+                }),
+            None => false,
+        };
+        if !is_same_group {
+            self.in_group = false;
+            return Ok(None);
+        }
+        self.rows.next_row()
+    }
+
+    /// Values of the key columns of the current group. `None` represents `NULL`. Empty before
+    /// the first call to [`Self::next_group`].
+    pub fn key(&self) -> Vec<Option<CellValue<'_>>> {
+        self.key
+            .iter()
+            .map(|cell| cell.as_ref().map(OwnedCellValue::as_cell))
+            .collect()
+    }
+
+    /// Gives back the rows, positioned at the next row which has not been consumed.
+    pub fn into_inner(self) -> PeekableCursor<C> {
+        self.rows
+    }
+}
+
+/// Values of the key columns of `row`. All columns, if `key_columns` is empty.
+fn key_of<'a>(key_columns: &[usize], row: BufferedRow<'a>) -> Vec<Option<CellValue<'a>>> {
+    let cell = |buffer_index| {
+        row.get(buffer_index)
+            .expect("Every column buffer can be read as `CellValue`.")
+    };
+    if key_columns.is_empty() {
+        (0..row.num_cols()).map(cell).collect()
+    } else {
+        key_columns.iter().copied().map(cell).collect()
+    }
+}
@@ -8,35 +8,59 @@
 mod as_handle;
 mod bind;
 mod buffer;
+mod call_audit;
+mod call_hook;
 mod column_description;
 mod connection;
+mod cursor_type;
 mod data_type;
 mod diagnostics;
 mod environment;
+mod error_context;
+mod handle_registry;
 mod logging;
+mod raw_guard;
 mod sql_char;
 mod sql_result;
 mod statement;
+mod statement_registry;
 
 pub use {
     as_handle::AsHandle,
     bind::{CData, CDataMut, DelayedInput, HasDataType},
+    call_audit::AuditedCall,
+    call_hook::CallHook,
     column_description::{ColumnDescription, Nullability},
     connection::Connection,
+    cursor_type::{Concurrency, CursorType},
     data_type::DataType,
     diagnostics::{Diagnostics, Record, State},
     environment::Environment,
     logging::log_diagnostics,
+    raw_guard::{RawConnectionGuard, RawStatementGuard},
     sql_char::{slice_to_cow_utf8, slice_to_utf8, OutputStringBuffer, SqlChar, SqlText, SzBuffer},
     sql_result::SqlResult,
     statement::{AsStatementRef, ParameterDescription, Statement, StatementImpl, StatementRef},
+    statement_registry::ExecutionStatus,
+};
+
+#[cfg(any(feature = "installer", feature = "mssql-bcp"))]
+pub(crate) use sql_char::nul_terminated;
+
+pub(crate) use {
+    call_audit::{AuditRegistration, CallAuditLog},
+    call_hook::CallHookRegistration,
+    error_context::{
+        detach_statement_context, registered_context, statement_context, ContextRegistration,
+        ErrorContext,
+    },
+    sql_char::sql_text_len,
+    statement_registry::{StatementRegistration, StatementRegistry},
 };
 
 use odbc_sys::{Handle, HandleType, SQLFreeHandle, SqlReturn};
-use std::thread::panicking;
 
-/// Helper function freeing a handle and panicking on errors. Yet if the drop is triggered during
-/// another panic, the function will simply ignore errors from failed drops.
+/// Helper function freeing a handle. Errors are logged, rather than causing a panic.
 ///
 /// # Safety
 ///
@@ -45,11 +69,8 @@ pub unsafe fn drop_handle(handle: Handle, handle_type: HandleType) {
     match SQLFreeHandle(handle_type, handle) {
         SqlReturn::SUCCESS => (),
         other => {
-            // Avoid panicking, if we already have a panic. We don't want to mask the
-            // original error.
-            if !panicking() {
-                panic!("SQLFreeHandle failed with error code: {:?}", other.0)
-            }
+            // Called from `Drop` implementations, so there is no one to return the error to.
+            log::error!("SQLFreeHandle failed with error code: {:?}", other.0)
         }
     }
 }
@@ -8,6 +8,11 @@ pub fn clamp_small_int(n: usize) -> i16 {
     min(n, i16::MAX as usize) as i16
 }
 
+/// Clamps a usize between `0` and `i32::MAX`.
+pub fn clamp_int(n: usize) -> i32 {
+    min(n, i32::MAX as usize) as i32
+}
+
 /// Returns a pointer suitable to be passed as an output buffer to ODBC functions. Most notably it
 /// will return NULL for empty buffers.
 pub fn mut_buf_ptr<T>(buffer: &mut [T]) -> *mut T {
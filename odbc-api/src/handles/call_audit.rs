@@ -0,0 +1,172 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use odbc_sys::{Handle, HandleType, SqlReturn};
+
+use super::{
+    handle_registry::{HandleRegistration, HandleRegistry},
+    AsHandle, Diagnostics, State,
+};
+
+/// A call to the ODBC C API recorded by the call audit of a connection. See
+/// [`crate::Connection::enable_call_audit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuditedCall {
+    /// Name of the ODBC function, e.g. `"SQLExecDirect"`.
+    pub function: &'static str,
+    /// Type of the handle the function has been called with, i.e. the connection or one of its
+    /// statements.
+    pub handle_type: HandleType,
+    /// Return code of the function, after it has been passed to the installed
+    /// [`super::CallHook`], if any.
+    pub return_code: SqlReturn,
+    /// State of the first diagnostic record, if the function returned an error or a warning.
+    pub state: Option<State>,
+}
+
+impl fmt::Display for AuditedCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} on {:?} handle returned {}",
+            self.function, self.handle_type, self.return_code.0
+        )?;
+        if let Some(state) = &self.state {
+            write!(f, " (State: {})", state.as_str())?;
+        }
+        Ok(())
+    }
+}
+
+/// Ring buffer holding the most recent calls made on a connection and its statements.
+#[derive(Debug)]
+pub(crate) struct CallAuditLog {
+    capacity: usize,
+    calls: Mutex<VecDeque<AuditedCall>>,
+}
+
+impl CallAuditLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            calls: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    fn push(&self, call: AuditedCall) {
+        let mut calls = self.calls.lock().unwrap();
+        if calls.len() == self.capacity {
+            calls.pop_front();
+        }
+        calls.push_back(call);
+    }
+
+    /// Recorded calls, oldest first.
+    pub fn calls(&self) -> Vec<AuditedCall> {
+        self.calls.lock().unwrap().iter().copied().collect()
+    }
+}
+
+/// Audited handles and the log their calls are recorded in.
+static AUDITED: HandleRegistry<CallAuditLog> = HandleRegistry::new();
+
+/// Records calls made with a handle into a [`CallAuditLog`], until dropped. Must be dropped before
+/// the handle is freed.
+pub(crate) struct AuditRegistration {
+    _registration: HandleRegistration<CallAuditLog>,
+}
+
+impl AuditRegistration {
+    pub fn new(handle: Handle, log: &Arc<CallAuditLog>) -> Self {
+        Self {
+            _registration: AUDITED.register(handle, log),
+        }
+    }
+}
+
+/// Log the calls made with `handle` are recorded in, if any.
+fn log_of(handle: Handle) -> Option<Arc<CallAuditLog>> {
+    AUDITED.get(handle)
+}
+
+/// Records the call, if `handle` is audited.
+pub(super) fn record_call(
+    function: &'static str,
+    handle_type: HandleType,
+    handle: Handle,
+    ret: SqlReturn,
+) {
+    // Most applications do not audit calls. Skip looking up the handle then.
+    if AUDITED.is_empty() {
+        return;
+    }
+    let log = match log_of(handle) {
+        Some(log) => log,
+        None => return,
+    };
+    let state = match ret {
+        SqlReturn::ERROR | SqlReturn::SUCCESS_WITH_INFO => (RawHandle {
+            handle,
+            handle_type,
+        })
+        .diagnostic_record(1, &mut [])
+        .map(|result| result.state),
+        _ => None,
+    };
+    log.push(AuditedCall {
+        function,
+        handle_type,
+        return_code: ret,
+        state,
+    });
+}
+
+/// Calls recorded for the connection `handle` belongs to, oldest first. Empty if `handle` is not
+/// audited.
+pub(super) fn history_of(handle: Handle) -> Vec<AuditedCall> {
+    log_of(handle).map(|log| log.calls()).unwrap_or_default()
+}
+
+/// Handle passed to [`record_call`], used to look up the state of its diagnostics.
+struct RawHandle {
+    handle: Handle,
+    handle_type: HandleType,
+}
+
+unsafe impl AsHandle for RawHandle {
+    fn as_handle(&self) -> Handle {
+        self.handle
+    }
+
+    fn handle_type(&self) -> HandleType {
+        self.handle_type
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use odbc_sys::{HandleType, SqlReturn};
+
+    use super::{AuditedCall, CallAuditLog};
+
+    #[test]
+    fn keep_most_recent_calls() {
+        let log = CallAuditLog::new(2);
+        let call = |function| AuditedCall {
+            function,
+            handle_type: HandleType::Stmt,
+            return_code: SqlReturn::SUCCESS,
+            state: None,
+        };
+
+        log.push(call("SQLPrepare"));
+        log.push(call("SQLExecute"));
+        log.push(call("SQLFetch"));
+
+        let functions: Vec<_> = log.calls().iter().map(|call| call.function).collect();
+        assert_eq!(["SQLExecute", "SQLFetch"], functions.as_slice());
+    }
+}
@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+use odbc_sys::{Handle, HandleType, SqlReturn};
+
+use super::handle_registry::{HandleRegistration, HandleRegistry};
+
+/// Observes, and may alter, the return code of every call to the ODBC C API made by this crate
+/// with the handles of an environment. Install it using [`crate::Environment::set_call_hook`].
+///
+/// Use cases are low level tracing of ODBC calls, or fault injection in tests. Altering the return
+/// code allows to test error paths of an application (e.g. a return code of
+/// [`SqlReturn::SUCCESS_WITH_INFO`] or [`SqlReturn::ERROR`]) which are hard to provoke with real
+/// drivers.
+///
+/// The hook is not invoked for calls retrieving diagnostic records or freeing handles.
+pub trait CallHook: Send + Sync {
+    /// Invoked after the ODBC function `function` returned. The value returned by this method is
+    /// treated as if it had been returned by the ODBC function call itself.
+    ///
+    /// # Parameters
+    ///
+    /// * `function`: Name of the ODBC function which has been called, e.g. `"SQLExecDirect"`.
+    /// * `handle_type`: Type of `handle`.
+    /// * `handle`: The handle the function has been called with. May be `NULL` for functions which
+    ///   are not invoked on a handle, e.g. allocating the environment.
+    /// * `ret`: The return code of the ODBC function. Return it unchanged for pure observation.
+    ///
+    /// Note that returning an error without the function having pushed a diagnostic record onto
+    /// the handle results in [`crate::Error::NoDiagnostics`] for the caller. The same is true for
+    /// return codes not defined by ODBC. Also calls which are executed synchronously, must not
+    /// return [`SqlReturn::STILL_EXECUTING`].
+    fn after_call(
+        &self,
+        function: &'static str,
+        handle_type: HandleType,
+        handle: Handle,
+        ret: SqlReturn,
+    ) -> SqlReturn;
+}
+
+/// Handles and the hook installed on the environment they have been allocated from.
+static HOOKS: HandleRegistry<dyn CallHook> = HandleRegistry::new();
+
+/// Invokes a [`CallHook`] for the calls made with a handle, until dropped. Must be dropped before
+/// the handle is freed.
+pub(crate) struct CallHookRegistration {
+    hook: Arc<dyn CallHook>,
+    _registration: HandleRegistration<dyn CallHook>,
+}
+
+impl CallHookRegistration {
+    pub fn new(handle: Handle, hook: &Arc<dyn CallHook>) -> Self {
+        Self {
+            hook: hook.clone(),
+            _registration: HOOKS.register(handle, hook),
+        }
+    }
+
+    /// The registered hook, so it can be registered for the handles allocated from this one, too.
+    pub fn hook(&self) -> &Arc<dyn CallHook> {
+        &self.hook
+    }
+}
+
+/// Passes the return value to the hook registered for `handle`, if any.
+pub(super) fn invoke_call_hook(
+    function: &'static str,
+    handle_type: HandleType,
+    handle: Handle,
+    ret: SqlReturn,
+) -> SqlReturn {
+    // Most applications do not install call hooks. Skip looking up the handle then.
+    if HOOKS.is_empty() {
+        return ret;
+    }
+    match HOOKS.get(handle) {
+        Some(hook) => hook.after_call(function, handle_type, handle, ret),
+        None => ret,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use odbc_sys::{Handle, HandleType, SqlReturn};
+
+    use crate::handles::{sql_result::ExtSqlReturn, SqlResult};
+
+    use super::{CallHook, CallHookRegistration};
+
+    /// Fails every call to a made up function name.
+    struct FailFakeFunction;
+
+    impl CallHook for FailFakeFunction {
+        fn after_call(
+            &self,
+            function: &'static str,
+            _handle_type: HandleType,
+            _handle: odbc_sys::Handle,
+            ret: SqlReturn,
+        ) -> SqlReturn {
+            if function == "SQLFakeFunction" {
+                SqlReturn::ERROR
+            } else {
+                ret
+            }
+        }
+    }
+
+    /// Made up handle, so the hook does not interfere with other tests running in parallel.
+    fn fake_handle(address: usize) -> Handle {
+        address as Handle
+    }
+
+    #[test]
+    fn hook_alters_return_code() {
+        let hook: Arc<dyn CallHook> = Arc::new(FailFakeFunction);
+        let registration = CallHookRegistration::new(fake_handle(8), &hook);
+        let result = SqlReturn::SUCCESS.into_sql_result_for(
+            HandleType::Stmt,
+            fake_handle(8),
+            "SQLFakeFunction",
+        );
+        drop(registration);
+
+        assert_eq!(
+            SqlResult::Error {
+                function: "SQLFakeFunction"
+            },
+            result
+        );
+    }
+
+    #[test]
+    fn hook_is_scoped_to_registered_handles() {
+        let hook: Arc<dyn CallHook> = Arc::new(FailFakeFunction);
+        let _registration = CallHookRegistration::new(fake_handle(16), &hook);
+
+        let result = SqlReturn::SUCCESS.into_sql_result_for(
+            HandleType::Stmt,
+            fake_handle(24),
+            "SQLFakeFunction",
+        );
+
+        assert_eq!(SqlResult::Success(()), result);
+    }
+
+    /// Hooks may return codes not defined by ODBC. These must not cause a panic.
+    #[test]
+    fn unexpected_return_code_is_an_error() {
+        let result =
+            SqlReturn(42).into_sql_result_for(HandleType::Stmt, fake_handle(32), "SQLFakeFunction");
+
+        assert_eq!(
+            SqlResult::Error {
+                function: "SQLFakeFunction"
+            },
+            result
+        );
+    }
+}
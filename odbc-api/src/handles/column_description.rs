@@ -5,6 +5,7 @@ use super::{
 
 /// Indication of whether a column is nullable or not.
 #[derive(Clone, Copy, Hash, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Nullability {
     /// Indicates that we do not know whether the column is Nullable or not.
     Unknown,
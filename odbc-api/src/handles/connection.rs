@@ -1,6 +1,6 @@
 use super::{
     as_handle::AsHandle,
-    buffer::mut_buf_ptr,
+    buffer::{clamp_int, clamp_small_int, mut_buf_ptr},
     drop_handle,
     sql_char::{
         binary_length, is_truncated_bin, resize_to_fit_with_tz, resize_to_fit_without_tz, SqlChar,
@@ -10,26 +10,71 @@ use super::{
     statement::StatementImpl,
     OutputStringBuffer, SqlResult,
 };
+#[cfg(feature = "mssql-bcp")]
+use odbc_sys::IS_INTEGER;
 use odbc_sys::{
     CompletionType, ConnectionAttribute, DriverConnectOption, HDbc, HEnv, HStmt, HWnd, Handle,
-    HandleType, InfoType, Pointer, SQLAllocHandle, SQLDisconnect, SQLEndTran, IS_UINTEGER,
+    HandleType, InfoType, Pointer, SQLAllocHandle, SQLDisconnect, SQLEndTran, SqlReturn,
+    IS_UINTEGER,
 };
 use std::{ffi::c_void, marker::PhantomData, mem::size_of, ptr::null_mut};
 
 #[cfg(feature = "narrow")]
 use odbc_sys::{
     SQLConnect as sql_connect, SQLDriverConnect as sql_driver_connect,
-    SQLGetConnectAttr as sql_get_connect_attr, SQLGetInfo as sql_get_info,
-    SQLSetConnectAttr as sql_set_connect_attr,
+    SQLGetConnectAttr as sql_get_connect_attr, SQLSetConnectAttr as sql_set_connect_attr,
 };
 
 #[cfg(not(feature = "narrow"))]
 use odbc_sys::{
     SQLConnectW as sql_connect, SQLDriverConnectW as sql_driver_connect,
-    SQLGetConnectAttrW as sql_get_connect_attr, SQLGetInfoW as sql_get_info,
-    SQLSetConnectAttrW as sql_set_connect_attr,
+    SQLGetConnectAttrW as sql_get_connect_attr, SQLSetConnectAttrW as sql_set_connect_attr,
 };
 
+// `odbc_sys` declares the info type parameter of `SQLGetInfo` as `InfoType`, which lacks many of
+// the values defined by the standard. So we declare our own binding to the same symbol, just like
+// for driver specific statement attributes. The same goes for the attribute parameter of
+// `SQLSetConnectAttr`. `SQLGetFunctions` is not declared by `odbc_sys` at all.
+extern "system" {
+    #[cfg_attr(not(feature = "narrow"), link_name = "SQLSetConnectAttrW")]
+    #[cfg_attr(feature = "narrow", link_name = "SQLSetConnectAttr")]
+    fn sql_set_connect_attr_by_id(
+        hdbc: HDbc,
+        attr: i32,
+        value: Pointer,
+        str_length: i32,
+    ) -> SqlReturn;
+
+    #[cfg_attr(not(feature = "narrow"), link_name = "SQLGetInfoW")]
+    #[cfg_attr(feature = "narrow", link_name = "SQLGetInfo")]
+    fn sql_get_info_by_id(
+        hdbc: HDbc,
+        info_type: u16,
+        info_value: Pointer,
+        buffer_length: i16,
+        string_length: *mut i16,
+    ) -> SqlReturn;
+
+    #[link_name = "SQLGetFunctions"]
+    fn sql_get_functions(hdbc: HDbc, function_id: u16, supported: *mut u16) -> SqlReturn;
+}
+
+/// `SQL_API_ODBC3_ALL_FUNCTIONS`. Queries support for all functions at once, as a bitmap.
+const ALL_FUNCTIONS: u16 = 999;
+
+/// `SQL_API_ODBC3_ALL_FUNCTIONS_SIZE`. Number of elements in the bitmap of supported functions.
+const ALL_FUNCTIONS_SIZE: usize = 250;
+
+/// `SQL_ATTR_RESET_CONNECTION`. Not declared by `odbc_sys`.
+const RESET_CONNECTION: i32 = 116;
+
+/// `SQL_RESET_CONNECTION_YES`. The only valid value for [`RESET_CONNECTION`].
+const RESET_CONNECTION_YES: usize = 1;
+
+/// `SQL_COPT_SS_BCP`. Driver specific attribute of the Microsoft ODBC Driver for SQL Server.
+#[cfg(feature = "mssql-bcp")]
+const SS_BCP: i32 = 1219;
+
 /// The connection handle references storage of all information about the connection to the data
 /// source, including status, transaction state, and error information.
 pub struct Connection<'c> {
@@ -101,7 +146,7 @@ impl<'c> Connection<'c> {
                 pwd.ptr(),
                 pwd.len_char().try_into().unwrap(),
             )
-            .into_sql_result("SQLConnect")
+            .into_sql_result(self, "SQLConnect")
         }
     }
 
@@ -151,12 +196,12 @@ impl<'c> Connection<'c> {
             completed_connection_string.mut_actual_len_ptr(),
             driver_completion,
         )
-        .into_sql_result("SQLDriverConnect")
+        .into_sql_result(self, "SQLDriverConnect")
     }
 
     /// Disconnect from an ODBC data source.
     pub fn disconnect(&mut self) -> SqlResult<()> {
-        unsafe { SQLDisconnect(self.handle).into_sql_result("SQLDisconnect") }
+        unsafe { SQLDisconnect(self.handle).into_sql_result(self, "SQLDisconnect") }
     }
 
     /// Allocate a new statement handle. The `Statement` must not outlive the `Connection`.
@@ -164,7 +209,7 @@ impl<'c> Connection<'c> {
         let mut out = null_mut();
         unsafe {
             SQLAllocHandle(HandleType::Stmt, self.as_handle(), &mut out)
-                .into_sql_result("SQLAllocHandle")
+                .into_sql_result(self, "SQLAllocHandle")
                 .on_success(|| StatementImpl::new(out as HStmt))
         }
     }
@@ -182,7 +227,46 @@ impl<'c> Connection<'c> {
                 val as Pointer,
                 0, // will be ignored according to ODBC spec
             )
-            .into_sql_result("SQLSetConnectAttr")
+            .into_sql_result(self, "SQLSetConnectAttr")
+        }
+    }
+
+    /// Sets `SQL_ATTR_RESET_CONNECTION`, which asks the driver to reset the session state on the
+    /// data source, e.g. temporary tables and session settings, before the next operation on the
+    /// connection. Only supported by some drivers, e.g. the Microsoft ODBC Driver for SQL Server.
+    pub fn reset_connection(&self) -> SqlResult<()> {
+        unsafe {
+            sql_set_connect_attr_by_id(
+                self.handle,
+                RESET_CONNECTION,
+                RESET_CONNECTION_YES as Pointer,
+                IS_UINTEGER,
+            )
+            .into_sql_result(self, "SQLSetConnectAttr")
+        }
+    }
+
+    /// Sets `SQL_COPT_SS_BCP`, which enables the bulk copy functions of the Microsoft ODBC Driver
+    /// for SQL Server on this connection. Must be set before connecting.
+    #[cfg(feature = "mssql-bcp")]
+    pub fn set_bulk_copy(&self, enabled: bool) -> SqlResult<()> {
+        unsafe {
+            sql_set_connect_attr_by_id(self.handle, SS_BCP, enabled as usize as Pointer, IS_INTEGER)
+                .into_sql_result(self, "SQLSetConnectAttr")
+        }
+    }
+
+    /// Number of seconds to wait for a login request to complete before returning to the
+    /// application. `0` disables the timeout and waits indefinitely. Must be set before connecting.
+    pub fn set_login_timeout_sec(&self, timeout: u32) -> SqlResult<()> {
+        unsafe {
+            sql_set_connect_attr(
+                self.handle,
+                ConnectionAttribute::LoginTimeout,
+                timeout as Pointer,
+                0, // will be ignored according to ODBC spec
+            )
+            .into_sql_result(self, "SQLSetConnectAttr")
         }
     }
 
@@ -190,7 +274,7 @@ impl<'c> Connection<'c> {
     pub fn commit(&self) -> SqlResult<()> {
         unsafe {
             SQLEndTran(HandleType::Dbc, self.as_handle(), CompletionType::Commit)
-                .into_sql_result("SQLEndTran")
+                .into_sql_result(self, "SQLEndTran")
         }
     }
 
@@ -198,60 +282,95 @@ impl<'c> Connection<'c> {
     pub fn rollback(&self) -> SqlResult<()> {
         unsafe {
             SQLEndTran(HandleType::Dbc, self.as_handle(), CompletionType::Rollback)
-                .into_sql_result("SQLEndTran")
+                .into_sql_result(self, "SQLEndTran")
         }
     }
 
     /// Fetch the name of the database management system used by the connection and store it into
     /// the provided `buf`.
     pub fn fetch_database_management_system_name(&self, buf: &mut Vec<SqlChar>) -> SqlResult<()> {
+        self.info_string(InfoType::DbmsName, buf)
+    }
+
+    /// Fetch the character(s) used to quote identifiers, e.g. `"` or `` ` `` and store it into the
+    /// provided `buf`. A single space is reported if quoting identifiers is not supported.
+    pub fn fetch_identifier_quote_char(&self, buf: &mut Vec<SqlChar>) -> SqlResult<()> {
+        self.info_string(InfoType::IdentifierQuoteChar, buf)
+    }
+
+    fn info_string(&self, info_type: InfoType, buf: &mut Vec<SqlChar>) -> SqlResult<()> {
+        self.fetch_info_string(info_type as u16, buf)
+    }
+
+    /// Fetches the text value of an arbitrary information type, identified by its numeric value
+    /// (e.g. `6` for `SQL_DRIVER_NAME`) and stores it into `buf`. It is the callers responsibility
+    /// to use an information type with a character string value.
+    pub fn fetch_info_string(&self, info_type: u16, buf: &mut Vec<SqlChar>) -> SqlResult<()> {
         // String length in bytes, not characters. Terminating zero is excluded.
         let mut string_length_in_bytes: i16 = 0;
         // Let's utilize all of `buf`s capacity.
         buf.resize(buf.capacity(), 0);
 
         unsafe {
-            let mut res = sql_get_info(
+            let mut res = sql_get_info_by_id(
                 self.handle,
-                InfoType::DbmsName,
+                info_type,
                 mut_buf_ptr(buf) as Pointer,
-                binary_length(buf).try_into().unwrap(),
+                clamp_small_int(binary_length(buf)),
                 &mut string_length_in_bytes as *mut i16,
             )
-            .into_sql_result("SQLGetInfo");
+            .into_sql_result(self, "SQLGetInfo");
 
             if res.is_err() {
                 return res;
             }
+            let Ok(length) = usize::try_from(string_length_in_bytes) else {
+                return SqlResult::invalid_driver_value(
+                    "length of an info string",
+                    string_length_in_bytes,
+                );
+            };
 
             // Call has been a success but let's check if the buffer had been large enough.
-            if is_truncated_bin(buf, string_length_in_bytes.try_into().unwrap()) {
+            if is_truncated_bin(buf, length) {
                 // It seems we must try again with a large enough buffer.
-                resize_to_fit_with_tz(buf, string_length_in_bytes.try_into().unwrap());
-                res = sql_get_info(
+                resize_to_fit_with_tz(buf, length);
+                res = sql_get_info_by_id(
                     self.handle,
-                    InfoType::DbmsName,
+                    info_type,
                     mut_buf_ptr(buf) as Pointer,
-                    binary_length(buf).try_into().unwrap(),
+                    clamp_small_int(binary_length(buf)),
                     &mut string_length_in_bytes as *mut i16,
                 )
-                .into_sql_result("SQLGetInfo");
+                .into_sql_result(self, "SQLGetInfo");
 
                 if res.is_err() {
                     return res;
                 }
             }
+            let Ok(length) = usize::try_from(string_length_in_bytes) else {
+                return SqlResult::invalid_driver_value(
+                    "length of an info string",
+                    string_length_in_bytes,
+                );
+            };
 
             // Resize buffer to exact string length without terminal zero
-            resize_to_fit_without_tz(buf, string_length_in_bytes.try_into().unwrap());
+            resize_to_fit_without_tz(buf, length);
             res
         }
     }
 
     fn info_u16(&self, info_type: InfoType) -> SqlResult<u16> {
+        self.info_small_int(info_type as u16)
+    }
+
+    /// Value of an arbitrary information type with an `SQLUSMALLINT` value, identified by its
+    /// numeric value (e.g. `30` for `SQL_MAX_COLUMN_NAME_LEN`).
+    pub fn info_small_int(&self, info_type: u16) -> SqlResult<u16> {
         unsafe {
             let mut value = 0u16;
-            sql_get_info(
+            sql_get_info_by_id(
                 self.handle,
                 info_type,
                 &mut value as *mut u16 as Pointer,
@@ -262,11 +381,40 @@ impl<'c> Connection<'c> {
                 size_of::<*mut u16>() as i16,
                 null_mut(),
             )
-            .into_sql_result("SQLGetInfo")
+            .into_sql_result(self, "SQLGetInfo")
             .on_success(|| value)
         }
     }
 
+    /// Value of an arbitrary information type with an `SQLUINTEGER` value or bitmask, identified
+    /// by its numeric value (e.g. `81` for `SQL_GETDATA_EXTENSIONS`).
+    pub fn info_integer(&self, info_type: u16) -> SqlResult<u32> {
+        unsafe {
+            let mut value = 0u32;
+            sql_get_info_by_id(
+                self.handle,
+                info_type,
+                &mut value as *mut u32 as Pointer,
+                size_of::<u32>() as i16,
+                null_mut(),
+            )
+            .into_sql_result(self, "SQLGetInfo")
+            .on_success(|| value)
+        }
+    }
+
+    /// Bitmap of the ODBC functions supported by the driver, as returned by `SQLGetFunctions` for
+    /// `SQL_API_ODBC3_ALL_FUNCTIONS`. Function `id` is supported if bit `id % 16` of element
+    /// `id / 16` is set.
+    pub fn supported_functions(&self) -> SqlResult<Vec<u16>> {
+        let mut bitmap = vec![0u16; ALL_FUNCTIONS_SIZE];
+        unsafe {
+            sql_get_functions(self.handle, ALL_FUNCTIONS, bitmap.as_mut_ptr())
+                .into_sql_result(self, "SQLGetFunctions")
+                .on_success(|| bitmap)
+        }
+    }
+
     /// Maximum length of catalog names.
     pub fn max_catalog_name_len(&self) -> SqlResult<u16> {
         self.info_u16(InfoType::MaxCatalogNameLen)
@@ -287,6 +435,12 @@ impl<'c> Connection<'c> {
         self.info_u16(InfoType::MaxColumnNameLen)
     }
 
+    /// Maximum number of active statements the driver can support for a connection. `0` if there
+    /// is no specified limit or the limit is unknown.
+    pub fn max_concurrent_activities(&self) -> SqlResult<u16> {
+        self.info_u16(InfoType::MaxConcurrentActivities)
+    }
+
     /// Fetch the name of the current catalog being used by the connection and store it into the
     /// provided `buf`.
     pub fn fetch_current_catalog(&self, buffer: &mut Vec<SqlChar>) -> SqlResult<()> {
@@ -300,46 +454,73 @@ impl<'c> Connection<'c> {
                 self.handle,
                 ConnectionAttribute::CurrentCatalog,
                 mut_buf_ptr(buffer) as Pointer,
-                binary_length(buffer).try_into().unwrap(),
+                clamp_int(binary_length(buffer)),
                 &mut string_length_in_bytes as *mut i32,
             )
-            .into_sql_result("SQLGetConnectAttr");
+            .into_sql_result(self, "SQLGetConnectAttr");
 
             if res.is_err() {
                 return res;
             }
-
-            if is_truncated_bin(buffer, string_length_in_bytes.try_into().unwrap()) {
-                resize_to_fit_with_tz(buffer, string_length_in_bytes.try_into().unwrap());
+            let Ok(length) = usize::try_from(string_length_in_bytes) else {
+                return SqlResult::invalid_driver_value(
+                    "length of a catalog name",
+                    string_length_in_bytes,
+                );
+            };
+
+            if is_truncated_bin(buffer, length) {
+                resize_to_fit_with_tz(buffer, length);
                 res = sql_get_connect_attr(
                     self.handle,
                     ConnectionAttribute::CurrentCatalog,
                     mut_buf_ptr(buffer) as Pointer,
-                    binary_length(buffer).try_into().unwrap(),
+                    clamp_int(binary_length(buffer)),
                     &mut string_length_in_bytes as *mut i32,
                 )
-                .into_sql_result("SQLGetConnectAttr");
+                .into_sql_result(self, "SQLGetConnectAttr");
             }
 
             if res.is_err() {
                 return res;
             }
+            let Ok(length) = usize::try_from(string_length_in_bytes) else {
+                return SqlResult::invalid_driver_value(
+                    "length of a catalog name",
+                    string_length_in_bytes,
+                );
+            };
 
             // Resize buffer to exact string length without terminal zero
-            resize_to_fit_without_tz(buffer, string_length_in_bytes.try_into().unwrap());
+            resize_to_fit_without_tz(buffer, length);
             res
         }
     }
 
+    /// Sets the catalog (called database by some systems) used by the connection.
+    pub fn set_current_catalog(&self, catalog_name: &SqlText) -> SqlResult<()> {
+        unsafe {
+            sql_set_connect_attr(
+                self.handle,
+                ConnectionAttribute::CurrentCatalog,
+                catalog_name.ptr() as Pointer,
+                (catalog_name.len_char() * size_of::<SqlChar>())
+                    .try_into()
+                    .unwrap(),
+            )
+            .into_sql_result(self, "SQLSetConnectAttr")
+        }
+    }
+
     /// Indicates the state of the connection. If `true` the connection has been lost. If `false`,
     /// the connection is still active.
     pub fn is_dead(&self) -> SqlResult<bool> {
         unsafe {
             self.attribute_u32(ConnectionAttribute::ConnectionDead)
-                .map(|v| match v {
-                    0 => false,
-                    1 => true,
-                    other => panic!("Unexpected result value from SQLGetConnectAttr: {other}"),
+                .and_then(|v| match v {
+                    0 => SqlResult::Success(false),
+                    1 => SqlResult::Success(true),
+                    other => SqlResult::invalid_driver_value("connection dead attribute", other),
                 })
         }
     }
@@ -356,7 +537,7 @@ impl<'c> Connection<'c> {
             IS_UINTEGER,
             null_mut(),
         )
-        .into_sql_result("SQLGetConnectAttr")
+        .into_sql_result(self, "SQLGetConnectAttr")
         .on_success(|| out)
     }
 }
@@ -0,0 +1,58 @@
+/// Kind of cursor the driver uses to fetch a result set. Corresponds to `SQL_ATTR_CURSOR_TYPE`.
+/// Drivers may substitute a cursor type they do not support with a similar one.
+///
+/// See: <https://learn.microsoft.com/sql/odbc/reference/develop-app/cursor-types>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorType {
+    /// Rows can only be fetched in order from first to last. The default, and usually the fastest.
+    ForwardOnly,
+    /// Membership and order of the result set is fixed once it is opened. Changes to the values of
+    /// the rows by other statements are visible.
+    KeysetDriven,
+    /// Changes to membership, order and values of the result set are visible while scrolling.
+    Dynamic,
+    /// The result set is not affected by changes of other statements after it is opened.
+    Static,
+}
+
+impl CursorType {
+    /// Value of the `SQL_ATTR_CURSOR_TYPE` statement attribute.
+    pub(crate) fn as_attribute_value(self) -> usize {
+        match self {
+            CursorType::ForwardOnly => 0,
+            CursorType::KeysetDriven => 1,
+            CursorType::Dynamic => 2,
+            CursorType::Static => 3,
+        }
+    }
+}
+
+/// How a cursor protects the rows it fetches against concurrent updates. Corresponds to
+/// `SQL_ATTR_CONCURRENCY`.
+///
+/// See: <https://learn.microsoft.com/sql/odbc/reference/develop-app/concurrency-control>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Concurrency {
+    /// The cursor is not used to update rows. Allows drivers to choose cheaper execution plans,
+    /// e.g. fast forward cursors for Microsoft SQL Server in combination with
+    /// [`CursorType::ForwardOnly`].
+    ReadOnly,
+    /// Rows are locked to allow updating them through the cursor.
+    Lock,
+    /// Optimistic concurrency control comparing row versions.
+    RowVersions,
+    /// Optimistic concurrency control comparing values.
+    Values,
+}
+
+impl Concurrency {
+    /// Value of the `SQL_ATTR_CONCURRENCY` statement attribute.
+    pub(crate) fn as_attribute_value(self) -> usize {
+        match self {
+            Concurrency::ReadOnly => 1,
+            Concurrency::Lock => 2,
+            Concurrency::RowVersions => 3,
+            Concurrency::Values => 4,
+        }
+    }
+}
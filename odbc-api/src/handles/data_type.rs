@@ -1,3 +1,5 @@
+use std::hash::{Hash, Hasher};
+
 use odbc_sys::SqlDataType;
 
 /// The relational type of the column. Think of it as the type used in the `CREATE TABLE` statement
@@ -9,6 +11,7 @@ use odbc_sys::SqlDataType;
 /// Microsoft SQL Server return a custom type, with its meaning specific to that driver. PostgreSQL
 /// identifies that column as an ordinary ODBC timestamp.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Enumeration over valid SQL Data Types supported by ODBC
 pub enum DataType {
     /// The type is not known.
@@ -123,6 +126,7 @@ pub enum DataType {
     /// non-standard types.
     Other {
         /// Type of the column
+        #[cfg_attr(feature = "serde", serde(with = "serde_sql_data_type"))]
         data_type: SqlDataType,
         /// Size of column element
         column_size: usize,
@@ -132,6 +136,35 @@ pub enum DataType {
     },
 }
 
+// `SqlDataType` does not implement `Hash`, so we can not derive it.
+impl Hash for DataType {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.data_type().0.hash(state);
+        self.column_size().hash(state);
+        self.decimal_digits().hash(state);
+    }
+}
+
+/// `SqlDataType` does not implement serde traits, so we represent it by its `i16` value.
+#[cfg(feature = "serde")]
+mod serde_sql_data_type {
+    use odbc_sys::SqlDataType;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        data_type: &SqlDataType,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        data_type.0.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<SqlDataType, D::Error> {
+        i16::deserialize(deserializer).map(SqlDataType)
+    }
+}
+
 impl DataType {
     /// This constructor is useful to create an instance of the enumeration using values returned by
     /// ODBC Api calls like `SQLDescribeCol`, rather than just initializing a variant directly.
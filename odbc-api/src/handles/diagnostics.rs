@@ -3,6 +3,8 @@ use crate::handles::slice_to_cow_utf8;
 use super::{
     as_handle::AsHandle,
     buffer::{clamp_small_int, mut_buf_ptr},
+    call_audit::{history_of, AuditedCall},
+    error_context::context_of,
     SqlChar,
 };
 use odbc_sys::{SqlReturn, SQLSTATE_SIZE};
@@ -41,15 +43,20 @@ impl State {
 
         let mut ascii = [0; SQLSTATE_SIZE];
         for (index, letter) in code[..SQLSTATE_SIZE].iter().copied().enumerate() {
-            ascii[index] = letter as u8;
+            // ODBC status codes always consist of ASCII characters. Replace anything else a buggy
+            // driver may report.
+            ascii[index] = u8::try_from(letter)
+                .ok()
+                .filter(u8::is_ascii)
+                .unwrap_or(b'?');
         }
         State(ascii)
     }
 
-    /// View status code as string slice for displaying. Must always succeed as ODBC status code
-    /// always consist of ASCII characters.
+    /// View status code as string slice for displaying. Empty, if the status code is not valid
+    /// UTF-8, which can only happen if it has not been reported by a driver.
     pub fn as_str(&self) -> &str {
-        std::str::from_utf8(&self.0).unwrap()
+        std::str::from_utf8(&self.0).unwrap_or_default()
     }
 }
 
@@ -136,8 +143,9 @@ pub trait Diagnostics {
         message_text.resize(cap, 0);
 
         self.diagnostic_record(rec_number, message_text)
-            .map(|mut result| {
-                let mut text_length = result.text_length.try_into().unwrap();
+            .and_then(|mut result| {
+                // A negative length violates the specification. Treat it like an empty message.
+                let mut text_length = result.text_length.try_into().unwrap_or(0);
 
                 // Check if the buffer has been large enough to hold the message.
                 if text_length > message_text.len() {
@@ -151,7 +159,7 @@ pub trait Diagnostics {
 
                     // Call diagnostics again with the larger buffer. Should be a success this time if
                     // driver isn't buggy.
-                    result = self.diagnostic_record(rec_number, message_text).unwrap();
+                    result = self.diagnostic_record(rec_number, message_text)?;
                 }
                 // Now `message_text` has been large enough to hold the entire message.
 
@@ -163,9 +171,21 @@ pub trait Diagnostics {
                 // Resize Vec to hold exactly the message.
                 message_text.resize(text_length, 0);
 
-                result
+                Some(result)
             })
     }
+
+    /// Calls recorded by the call audit of the connection this handle belongs to, oldest first.
+    /// Empty, unless [`crate::Connection::enable_call_audit`] has been called.
+    fn call_history(&self) -> Vec<AuditedCall> {
+        Vec::new()
+    }
+
+    /// Key value pairs attached to the connection this handle belongs to. Empty, unless
+    /// [`crate::Connection::set_context`] has been called.
+    fn context(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
 }
 
 impl<T: AsHandle + ?Sized> Diagnostics for T {
@@ -204,10 +224,22 @@ impl<T: AsHandle + ?Sized> Diagnostics for T {
         match ret {
             SqlReturn::SUCCESS | SqlReturn::SUCCESS_WITH_INFO => Some(result),
             SqlReturn::NO_DATA => None,
-            SqlReturn::ERROR => panic!("rec_number argument of diagnostics must be > 0."),
-            unexpected => panic!("SQLGetDiagRec returned: {unexpected:?}"),
+            // `rec_number` is positive, so the driver has no excuse for either of these. There is
+            // no further diagnostic to report them with, so log them.
+            unexpected => {
+                log::error!("SQLGetDiagRec returned: {unexpected:?}");
+                None
+            }
         }
     }
+
+    fn call_history(&self) -> Vec<AuditedCall> {
+        history_of(self.as_handle())
+    }
+
+    fn context(&self) -> Vec<(String, String)> {
+        context_of(self.as_handle())
+    }
 }
 
 /// ODBC Diagnostic Record
@@ -223,6 +255,12 @@ pub struct Record {
     /// Buffer containing the error message. The buffer already has the correct size, and there is
     /// no terminating zero at the end.
     pub message: Vec<SqlChar>,
+    /// Calls preceding the error on the same connection, if its call audit is enabled. See
+    /// [`crate::Connection::enable_call_audit`].
+    pub call_history: Vec<AuditedCall>,
+    /// Key value pairs attached to the connection the error originated from, e.g. a tenant or job
+    /// id. See [`crate::Connection::set_context`].
+    pub context: Vec<(String, String)>,
 }
 
 impl Record {
@@ -263,7 +301,21 @@ impl fmt::Display for Record {
             self.state.as_str(),
             self.native_error,
             message,
-        )
+        )?;
+        if !self.context.is_empty() {
+            write!(f, "\nContext:")?;
+            for (index, (key, value)) in self.context.iter().enumerate() {
+                let separator = if index == 0 { " " } else { ", " };
+                write!(f, "{separator}{key}={value}")?;
+            }
+        }
+        if !self.call_history.is_empty() {
+            write!(f, "\nPreceding ODBC calls, oldest first:")?;
+            for call in &self.call_history {
+                write!(f, "\n  {call}")?;
+            }
+        }
+        Ok(())
     }
 }
 
@@ -276,7 +328,7 @@ impl fmt::Debug for Record {
 #[cfg(test)]
 mod tests {
 
-    use crate::handles::diagnostics::State;
+    use crate::handles::{diagnostics::State, SqlChar};
 
     use super::Record;
 
@@ -307,4 +359,12 @@ mod tests {
              Function sequence error"
         );
     }
+
+    #[test]
+    fn non_ascii_state_is_replaced() {
+        // `0xE9` is not ASCII, neither as wide nor as narrow character.
+        let code = [b'H', b'Y', 0xE9, b'1', b'0', 0].map(SqlChar::from);
+
+        assert_eq!("HY?10", State::from_chars_with_nul(&code).as_str());
+    }
 }
@@ -1,13 +1,14 @@
 use super::{
     as_handle::AsHandle,
+    buffer::clamp_small_int,
     drop_handle,
     sql_char::SqlChar,
     sql_result::{ExtSqlReturn, SqlResult},
     Connection,
 };
 use odbc_sys::{
-    AttrCpMatch, AttrOdbcVersion, EnvironmentAttribute, FetchOrientation, HDbc, HEnv, Handle,
-    HandleType, SQLAllocHandle, SQLSetEnvAttr,
+    AttrCpMatch, AttrOdbcVersion, CompletionType, EnvironmentAttribute, FetchOrientation, HDbc,
+    HEnv, Handle, HandleType, SQLAllocHandle, SQLEndTran, SQLSetEnvAttr,
 };
 use std::ptr::null_mut;
 
@@ -75,7 +76,7 @@ impl Environment {
             scheme.into(),
             odbc_sys::IS_INTEGER,
         )
-        .into_sql_result("SQLSetEnvAttr")
+        .into_sql_result_for(HandleType::Env, null_mut(), "SQLSetEnvAttr")
     }
 
     pub fn set_connection_pooling_matching(&mut self, matching: AttrCpMatch) -> SqlResult<()> {
@@ -87,7 +88,7 @@ impl Environment {
                 odbc_sys::IS_INTEGER,
             )
         }
-        .into_sql_result("SQLSetEnvAttr")
+        .into_sql_result(self, "SQLSetEnvAttr")
     }
 
     /// An allocated ODBC Environment handle
@@ -102,7 +103,7 @@ impl Environment {
         unsafe {
             let mut handle = null_mut();
             let result: SqlResult<()> = SQLAllocHandle(HandleType::Env, null_mut(), &mut handle)
-                .into_sql_result("SQLAllocHandle");
+                .into_sql_result_for(HandleType::Env, null_mut(), "SQLAllocHandle");
             result.on_success(|| Environment {
                 handle: handle as HEnv,
             })
@@ -119,7 +120,7 @@ impl Environment {
                 version.into(),
                 0,
             )
-            .into_sql_result("SQLSetEnvAttr")
+            .into_sql_result(self, "SQLSetEnvAttr")
         }
     }
 
@@ -128,11 +129,27 @@ impl Environment {
         let mut handle = null_mut();
         unsafe {
             SQLAllocHandle(HandleType::Dbc, self.as_handle(), &mut handle)
-                .into_sql_result("SQLAllocHandle")
+                .into_sql_result(self, "SQLAllocHandle")
                 .on_success(|| Connection::new(handle as HDbc))
         }
     }
 
+    /// Commits the transactions of all connections allocated on this environment.
+    pub fn commit_all(&self) -> SqlResult<()> {
+        unsafe {
+            SQLEndTran(HandleType::Env, self.as_handle(), CompletionType::Commit)
+                .into_sql_result(self, "SQLEndTran")
+        }
+    }
+
+    /// Rolls back the transactions of all connections allocated on this environment.
+    pub fn rollback_all(&self) -> SqlResult<()> {
+        unsafe {
+            SQLEndTran(HandleType::Env, self.as_handle(), CompletionType::Rollback)
+                .into_sql_result(self, "SQLEndTran")
+        }
+    }
+
     /// Provides access to the raw ODBC environment handle.
     pub fn as_raw(&self) -> HEnv {
         self.handle
@@ -173,13 +190,13 @@ impl Environment {
             self.handle,
             direction,
             buffer_description.as_mut_ptr(),
-            buffer_description.len().try_into().unwrap(),
+            clamp_small_int(buffer_description.len()),
             null_mut(),
             buffer_attributes.as_mut_ptr(),
-            buffer_attributes.len().try_into().unwrap(),
+            clamp_small_int(buffer_attributes.len()),
             null_mut(),
         )
-        .into_sql_result("SQLDrivers")
+        .into_sql_result(self, "SQLDrivers")
     }
 
     /// Use together with [`Environment::drivers_buffer_fill`] to list drivers descriptions and
@@ -221,7 +238,7 @@ impl Environment {
             0,
             &mut length_attributes,
         )
-        .into_sql_result("SQLDrivers")
+        .into_sql_result(self, "SQLDrivers")
         .on_success(|| (length_description, length_attributes))
     }
 
@@ -263,7 +280,7 @@ impl Environment {
             0,
             &mut length_description,
         )
-        .into_sql_result("SQLDataSources")
+        .into_sql_result(self, "SQLDataSources")
         .on_success(|| (length_name, length_description))
     }
 
@@ -296,12 +313,12 @@ impl Environment {
             self.handle,
             direction,
             buffer_name.as_mut_ptr(),
-            buffer_name.len().try_into().unwrap(),
+            clamp_small_int(buffer_name.len()),
             null_mut(),
             buffer_description.as_mut_ptr(),
-            buffer_description.len().try_into().unwrap(),
+            clamp_small_int(buffer_description.len()),
             null_mut(),
         )
-        .into_sql_result("SQLDataSources")
+        .into_sql_result(self, "SQLDataSources")
     }
 }
@@ -0,0 +1,182 @@
+use std::sync::{Arc, RwLock};
+
+use odbc_sys::Handle;
+
+use super::handle_registry::{HandleRegistration, HandleRegistry};
+
+/// User defined key value pairs attached to a connection and its statements. See
+/// [`crate::Connection::set_context`].
+#[derive(Debug, Default)]
+pub(crate) struct ErrorContext {
+    entries: RwLock<Vec<(String, String)>>,
+    /// Context of the connection a statement has been allocated from. Its entries are read each
+    /// time, so changes to it reach statements allocated earlier.
+    parent: Option<Arc<ErrorContext>>,
+}
+
+impl ErrorContext {
+    /// Empty context, which inherits the entries of `parent`.
+    pub fn with_parent(parent: Arc<ErrorContext>) -> Self {
+        Self {
+            entries: RwLock::default(),
+            parent: Some(parent),
+        }
+    }
+
+    /// Sets `key` to `value`, replacing the previous value of `key`, if any. Keys keep the order
+    /// in which they have been set first.
+    pub fn set(&self, key: &str, value: &str) {
+        upsert(&mut self.entries.write().unwrap(), key, value)
+    }
+
+    /// Removes `key`, if present.
+    pub fn remove(&self, key: &str) {
+        self.entries
+            .write()
+            .unwrap()
+            .retain(|(existing, _)| existing != key);
+    }
+
+    /// Entries of the parent followed by the own ones. Own values take precedence over the ones
+    /// of the parent.
+    pub fn entries(&self) -> Vec<(String, String)> {
+        let mut entries = self
+            .parent
+            .as_ref()
+            .map(|parent| parent.entries())
+            .unwrap_or_default();
+        for (key, value) in self.entries.read().unwrap().iter() {
+            upsert(&mut entries, key, value);
+        }
+        entries
+    }
+}
+
+fn upsert(entries: &mut Vec<(String, String)>, key: &str, value: &str) {
+    match entries.iter_mut().find(|(existing, _)| existing == key) {
+        Some((_, existing)) => *existing = value.to_owned(),
+        None => entries.push((key.to_owned(), value.to_owned())),
+    }
+}
+
+/// Handles and the context attached to them.
+static CONTEXTS: HandleRegistry<ErrorContext> = HandleRegistry::new();
+
+/// Attaches an [`ErrorContext`] to a handle, until dropped. Must be dropped before the handle is
+/// freed.
+pub(crate) struct ContextRegistration {
+    _registration: HandleRegistration<ErrorContext>,
+}
+
+impl ContextRegistration {
+    pub fn new(handle: Handle, context: &Arc<ErrorContext>) -> Self {
+        Self {
+            _registration: CONTEXTS.register(handle, context),
+        }
+    }
+}
+
+/// Context registered for `handle`, if any.
+pub(crate) fn registered_context(handle: Handle) -> Option<Arc<ErrorContext>> {
+    CONTEXTS.get(handle)
+}
+
+/// Context of the statement `handle`. An empty one is attached on first use, which must be
+/// detached using [`detach_statement_context`] before the handle is freed.
+pub(crate) fn statement_context(handle: Handle) -> Arc<ErrorContext> {
+    CONTEXTS.get(handle).unwrap_or_else(|| {
+        let context = Arc::new(ErrorContext::default());
+        CONTEXTS.insert(handle, context.clone());
+        context
+    })
+}
+
+/// Attaches a context inheriting the entries of `parent` to the statement `handle`. Must be
+/// detached using [`detach_statement_context`] before the handle is freed.
+pub(crate) fn attach_statement_context(handle: Handle, parent: &Arc<ErrorContext>) {
+    CONTEXTS.insert(handle, Arc::new(ErrorContext::with_parent(parent.clone())))
+}
+
+/// Detaches the context attached to the statement `handle`, if any.
+pub(crate) fn detach_statement_context(handle: Handle) {
+    CONTEXTS.remove(handle)
+}
+
+/// Context attached to `handle`. Empty, if there is none.
+pub(super) fn context_of(handle: Handle) -> Vec<(String, String)> {
+    CONTEXTS
+        .get(handle)
+        .map(|context| context.entries())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use odbc_sys::Handle;
+
+    use super::{
+        attach_statement_context, detach_statement_context, registered_context, statement_context,
+        ErrorContext,
+    };
+
+    #[test]
+    fn replace_value_of_existing_key() {
+        let context = ErrorContext::default();
+
+        context.set("tenant", "a");
+        context.set("job", "42");
+        context.set("tenant", "b");
+        context.remove("job");
+
+        assert_eq!(
+            vec![("tenant".to_owned(), "b".to_owned())],
+            context.entries()
+        );
+    }
+
+    #[test]
+    fn own_entries_take_precedence_over_parent() {
+        let parent = Arc::new(ErrorContext::default());
+        let child = ErrorContext::with_parent(parent.clone());
+
+        child.set("job", "child");
+        // Set after the child has been created, yet still visible.
+        parent.set("tenant", "a");
+        parent.set("job", "parent");
+
+        assert_eq!(
+            vec![
+                ("tenant".to_owned(), "a".to_owned()),
+                ("job".to_owned(), "child".to_owned())
+            ],
+            child.entries()
+        );
+    }
+
+    #[test]
+    fn attach_statement_context_on_first_use() {
+        // Made up handles, so the contexts do not interfere with other tests running in parallel.
+        let (inheriting, lazy) = (0x5001 as Handle, 0x5002 as Handle);
+        let parent = Arc::new(ErrorContext::default());
+        parent.set("tenant", "a");
+
+        attach_statement_context(inheriting, &parent);
+        assert!(registered_context(lazy).is_none());
+        statement_context(lazy).set("job", "42");
+
+        assert_eq!(
+            vec![("tenant".to_owned(), "a".to_owned())],
+            registered_context(inheriting).unwrap().entries()
+        );
+        assert_eq!(
+            vec![("job".to_owned(), "42".to_owned())],
+            registered_context(lazy).unwrap().entries()
+        );
+        detach_statement_context(inheriting);
+        detach_statement_context(lazy);
+        assert!(registered_context(inheriting).is_none());
+        assert!(registered_context(lazy).is_none());
+    }
+}
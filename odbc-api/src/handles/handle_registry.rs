@@ -0,0 +1,99 @@
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, RwLock,
+    },
+};
+
+use odbc_sys::Handle;
+
+/// Associates values with handles, so they can be looked up, given nothing but a handle. Allows
+/// state shared by a connection and its statements to be found, then a function is called with any
+/// of their handles.
+pub(crate) struct HandleRegistry<T: ?Sized> {
+    /// Number of registered handles. Allows us to avoid taking the lock in the common case there
+    /// no handle is registered.
+    num_registered: AtomicUsize,
+    /// Registered handles, stored as addresses, and their values.
+    entries: RwLock<BTreeMap<usize, Arc<T>>>,
+}
+
+impl<T: ?Sized> HandleRegistry<T> {
+    pub const fn new() -> Self {
+        Self {
+            num_registered: AtomicUsize::new(0),
+            entries: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    /// Associates `value` with `handle`, until the returned registration is dropped.
+    pub fn register(&'static self, handle: Handle, value: &Arc<T>) -> HandleRegistration<T> {
+        self.insert(handle, value.clone());
+        HandleRegistration {
+            registry: self,
+            handle: handle as usize,
+        }
+    }
+
+    /// Associates `value` with `handle`, until [`Self::remove`] is called for `handle`. Replaces
+    /// the value associated with `handle` before, if any.
+    pub fn insert(&self, handle: Handle, value: Arc<T>) {
+        if self
+            .entries
+            .write()
+            .unwrap()
+            .insert(handle as usize, value)
+            .is_none()
+        {
+            self.num_registered.fetch_add(1, Ordering::Release);
+        }
+    }
+
+    /// Removes the value associated with `handle`, if any.
+    pub fn remove(&self, handle: Handle) {
+        if self.is_empty() {
+            return;
+        }
+        if self
+            .entries
+            .write()
+            .unwrap()
+            .remove(&(handle as usize))
+            .is_some()
+        {
+            self.num_registered.fetch_sub(1, Ordering::Release);
+        }
+    }
+
+    /// `true` if no handle is registered. Cheap enough to be checked on every call to an ODBC
+    /// function.
+    pub fn is_empty(&self) -> bool {
+        self.num_registered.load(Ordering::Acquire) == 0
+    }
+
+    /// Value associated with `handle`, if any.
+    pub fn get(&self, handle: Handle) -> Option<Arc<T>> {
+        if self.is_empty() {
+            return None;
+        }
+        self.entries
+            .read()
+            .unwrap()
+            .get(&(handle as usize))
+            .cloned()
+    }
+}
+
+/// Keeps a value associated with a handle until dropped. Must be dropped before the handle is
+/// freed.
+pub(crate) struct HandleRegistration<T: ?Sized + 'static> {
+    registry: &'static HandleRegistry<T>,
+    handle: usize,
+}
+
+impl<T: ?Sized> Drop for HandleRegistration<T> {
+    fn drop(&mut self) {
+        self.registry.remove(self.handle as Handle)
+    }
+}
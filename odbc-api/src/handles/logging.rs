@@ -11,6 +11,7 @@ pub fn log_diagnostics(handle: &(impl Diagnostics + ?Sized)) {
     }
 
     let mut rec = Record::with_capacity(512);
+    rec.context = handle.context();
     let mut rec_number = 1;
 
     // Log results, while there are diagnostic records
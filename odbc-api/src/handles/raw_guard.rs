@@ -0,0 +1,116 @@
+use std::marker::PhantomData;
+
+use odbc_sys::{HDbc, HStmt, Handle, HandleType, SqlReturn};
+
+use super::{sql_result::ExtSqlReturn, AsHandle, SqlResult};
+
+/// Grants access to the raw handle of a [`crate::Connection`], in order to call functions of the
+/// ODBC C API (via [`crate::sys`]) which are not covered by this crate. The guard borrows the
+/// connection, so the handle can not be freed, while the guard is alive.
+///
+/// Created by [`crate::Connection::raw_handle_guard`].
+///
+/// ```no_run
+/// use odbc_api::{sys, Connection, Error};
+///
+/// /// Sets the network packet size in bytes.
+/// fn set_packet_size(conn: &Connection, packet_size: usize) -> Result<(), Error> {
+///     let guard = conn.raw_handle_guard();
+///     let ret = unsafe {
+///         sys::SQLSetConnectAttrW(
+///             guard.as_sys(),
+///             sys::ConnectionAttribute::PacketSize,
+///             packet_size as sys::Pointer,
+///             0,
+///         )
+///     };
+///     guard.sql_result(ret, "SQLSetConnectAttrW").into_result(&guard)
+/// }
+/// ```
+pub struct RawConnectionGuard<'a> {
+    handle: HDbc,
+    parent: PhantomData<&'a HDbc>,
+}
+
+impl<'a> RawConnectionGuard<'a> {
+    /// # Safety
+    ///
+    /// `handle` must be a valid connection handle, which outlives `'a`.
+    pub(crate) unsafe fn new(handle: HDbc) -> Self {
+        Self {
+            handle,
+            parent: PhantomData,
+        }
+    }
+
+    /// The raw connection handle. It is valid as long as the guard is alive. Calling ODBC
+    /// functions with it may change the state of the connection in ways the safe abstractions of
+    /// this crate are not aware of, e.g. disconnecting it.
+    pub fn as_sys(&self) -> HDbc {
+        self.handle
+    }
+
+    /// Converts the return code of an ODBC function called with the raw handle into an
+    /// [`SqlResult`]. Use [`SqlResult::into_result`] with the guard to obtain diagnostics in case
+    /// of an error.
+    pub fn sql_result(&self, ret: SqlReturn, function: &'static str) -> SqlResult<()> {
+        ret.into_sql_result(self, function)
+    }
+}
+
+unsafe impl<'a> AsHandle for RawConnectionGuard<'a> {
+    fn as_handle(&self) -> Handle {
+        self.handle as Handle
+    }
+
+    fn handle_type(&self) -> HandleType {
+        HandleType::Dbc
+    }
+}
+
+/// Grants access to the raw handle of a statement, in order to call functions of the ODBC C API
+/// (via [`crate::sys`]) which are not covered by this crate. The guard borrows the statement
+/// exclusively, so the handle can neither be freed, nor used by the safe abstractions while the
+/// guard is alive.
+///
+/// Created by [`super::StatementRef::raw`].
+pub struct RawStatementGuard<'a> {
+    handle: HStmt,
+    parent: PhantomData<&'a mut HStmt>,
+}
+
+impl<'a> RawStatementGuard<'a> {
+    /// # Safety
+    ///
+    /// `handle` must be a valid statement handle, which outlives `'a`.
+    pub(crate) unsafe fn new(handle: HStmt) -> Self {
+        Self {
+            handle,
+            parent: PhantomData,
+        }
+    }
+
+    /// The raw statement handle. It is valid as long as the guard is alive. Calling ODBC
+    /// functions with it may change the state of the statement in ways the safe abstractions of
+    /// this crate are not aware of, e.g. binding buffers or closing a cursor.
+    pub fn as_sys(&self) -> HStmt {
+        self.handle
+    }
+
+    /// Converts the return code of an ODBC function called with the raw handle into an
+    /// [`SqlResult`]. Use [`SqlResult::into_result`] with the guard to obtain diagnostics in case
+    /// of an error.
+    pub fn sql_result(&self, ret: SqlReturn, function: &'static str) -> SqlResult<()> {
+        ret.into_sql_result(self, function)
+    }
+}
+
+unsafe impl<'a> AsHandle for RawStatementGuard<'a> {
+    fn as_handle(&self) -> Handle {
+        self.handle as Handle
+    }
+
+    fn handle_type(&self) -> HandleType {
+        HandleType::Stmt
+    }
+}
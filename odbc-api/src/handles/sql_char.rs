@@ -5,7 +5,7 @@ use super::buffer::{buf_ptr, mut_buf_ptr};
 use std::{borrow::Cow, mem::size_of};
 
 #[cfg(feature = "narrow")]
-use std::{ffi::CStr, string::FromUtf8Error};
+use std::string::FromUtf8Error;
 
 #[cfg(not(feature = "narrow"))]
 use std::{
@@ -14,7 +14,7 @@ use std::{
 };
 
 #[cfg(not(feature = "narrow"))]
-use widestring::{U16CStr, U16String};
+use widestring::U16String;
 
 #[cfg(feature = "narrow")]
 pub type SqlChar = u8;
@@ -41,26 +41,42 @@ pub fn slice_to_cow_utf8(text: &[u8]) -> Cow<str> {
 }
 #[cfg(not(feature = "narrow"))]
 pub fn slice_to_cow_utf8(text: &[u16]) -> Cow<str> {
-    let text: Result<String, _> = decode_utf16(text.iter().copied()).collect();
-    text.unwrap().into()
+    decode_utf16(text.iter().copied())
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect::<String>()
+        .into()
 }
 
+/// Decodes the string up to the first terminating zero, or the entire buffer if it is missing.
+fn sz_to_utf8(buffer: &[SqlChar]) -> String {
+    let end = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+    slice_to_cow_utf8(&buffer[..end]).into_owned()
+}
+
+/// NUL terminated copy of `text` in the encoding of [`SqlChar`]. `None` if `text` contains a NUL
+/// character, which would end the string early.
+#[cfg(any(feature = "installer", feature = "mssql-bcp"))]
+pub(crate) fn nul_terminated(text: &str) -> Option<Vec<SqlChar>> {
+    if text.contains('\0') {
+        return None;
+    }
+    #[cfg(not(feature = "narrow"))]
+    let mut chars: Vec<SqlChar> = text.encode_utf16().collect();
+    #[cfg(feature = "narrow")]
+    let mut chars: Vec<SqlChar> = text.as_bytes().to_vec();
+    chars.push(0);
+    Some(chars)
+}
+
+/// Length of `text` once converted to [`SqlChar`]s, i.e. in UTF-16 code units, or in bytes if the
+/// `narrow` feature is enabled. This is the unit drivers use to count the length of statements.
 #[cfg(not(feature = "narrow"))]
-fn sz_to_utf8(buffer: &[u16]) -> String {
-    let c_str = U16CStr::from_slice_truncate(buffer).unwrap();
-    c_str.to_string_lossy()
+pub(crate) fn sql_text_len(text: &str) -> usize {
+    text.encode_utf16().count()
 }
 #[cfg(feature = "narrow")]
-fn sz_to_utf8(buffer: &[u8]) -> String {
-    // Truncate slice at first zero.
-    let end = buffer
-        .iter()
-        .enumerate()
-        .find(|(_index, &character)| character == b'\0')
-        .expect("Buffer must contain terminating zero.")
-        .0;
-    let c_str = unsafe { CStr::from_bytes_with_nul_unchecked(&buffer[..=end]) };
-    c_str.to_string_lossy().into_owned()
+pub(crate) fn sql_text_len(text: &str) -> usize {
+    text.len()
 }
 
 /// Buffer length in bytes, not characters
@@ -215,7 +231,8 @@ impl OutputStringBuffer {
         &mut self.actual_length as *mut i16
     }
 
-    /// Call this method to extract string from buffer after ODBC has filled it.
+    /// Call this method to extract string from buffer after ODBC has filled it. Invalid characters
+    /// are replaced with `U+FFFD`.
     pub fn to_utf8(&self) -> String {
         if self.buffer.is_empty() {
             return String::new();
@@ -224,17 +241,19 @@ impl OutputStringBuffer {
         if self.is_truncated() {
             // If the string is truncated we return the entire buffer excluding the terminating
             // zero.
-            slice_to_utf8(&self.buffer[0..(self.buffer.len() - 1)]).unwrap()
-        } else {
+            slice_to_cow_utf8(&self.buffer[0..(self.buffer.len() - 1)]).into_owned()
+        } else if let Ok(actual_length) = usize::try_from(self.actual_length) {
             // If the string is not truncated, we return not the entire buffer, but only the slice
             // containing the actual string.
-            let actual_length: usize = self.actual_length.try_into().unwrap();
-            slice_to_utf8(&self.buffer[0..actual_length]).unwrap()
+            slice_to_cow_utf8(&self.buffer[0..actual_length]).into_owned()
+        } else {
+            // A negative length violates the specification. Rely on the terminating zero instead.
+            sz_to_utf8(&self.buffer)
         }
     }
 
     /// True if the buffer had not been large enough to hold the string.
     pub fn is_truncated(&self) -> bool {
-        self.actual_length >= self.buffer.len().try_into().unwrap()
+        usize::try_from(self.actual_length).is_ok_and(|length| length >= self.buffer.len())
     }
 }
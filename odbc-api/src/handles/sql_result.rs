@@ -1,8 +1,10 @@
-use odbc_sys::SqlReturn;
+use odbc_sys::{Handle, HandleType, SqlReturn};
+
+use super::{as_handle::AsHandle, call_audit::record_call, call_hook::invoke_call_hook};
 
 /// Result of an ODBC function call. Variants hold the same meaning as the constants associated with
-/// [`SqlReturn`]. This type may hold results, but it is still the responsibility of the user to
-/// fetch and handle the diagnostics in case of an Error.
+/// [`SqlReturn`], except for [`Self::InvalidDriverValue`]. This type may hold results, but it is
+/// still the responsibility of the user to fetch and handle the diagnostics in case of an Error.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum SqlResult<T> {
     /// The function has been executed successfully.
@@ -23,6 +25,14 @@ pub enum SqlResult<T> {
         /// end users output, but the context is lost.
         function: &'static str,
     },
+    /// The function has been executed successfully, but the driver reported a value violating the
+    /// ODBC specification, e.g. a negative string length. Not an ODBC return code, but emitted by
+    /// the wrappers in this module rather than panicking.
+    InvalidDriverValue {
+        /// What the value describes, e.g. `length of a column name`.
+        description: &'static str,
+        value: i64,
+    },
 }
 
 impl SqlResult<()> {
@@ -53,6 +63,56 @@ impl<T> SqlResult<T> {
             SqlResult::StillExecuting => SqlResult::StillExecuting,
             SqlResult::NoData => SqlResult::NoData,
             SqlResult::NeedData => SqlResult::NeedData,
+            SqlResult::InvalidDriverValue { description, value } => {
+                SqlResult::InvalidDriverValue { description, value }
+            }
+        }
+    }
+
+    /// Like [`Self::map`], but `f` may fail, e.g. because the value violates the ODBC
+    /// specification. Warnings are kept if `f` succeeds.
+    pub fn and_then<U, F>(self, f: F) -> SqlResult<U>
+    where
+        F: FnOnce(T) -> SqlResult<U>,
+    {
+        match self {
+            SqlResult::Success(v) => f(v),
+            SqlResult::SuccessWithInfo(v) => match f(v) {
+                SqlResult::Success(u) => SqlResult::SuccessWithInfo(u),
+                other => other,
+            },
+            SqlResult::Error { function } => SqlResult::Error { function },
+            SqlResult::StillExecuting => SqlResult::StillExecuting,
+            SqlResult::NoData => SqlResult::NoData,
+            SqlResult::NeedData => SqlResult::NeedData,
+            SqlResult::InvalidDriverValue { description, value } => {
+                SqlResult::InvalidDriverValue { description, value }
+            }
+        }
+    }
+
+    /// Converts a value reported by the driver, e.g. a length or a count, into `U`. Yields
+    /// [`Self::InvalidDriverValue`] if it is out of range for `U`.
+    pub fn reported<U>(self, description: &'static str) -> SqlResult<U>
+    where
+        T: Copy,
+        U: TryFrom<T>,
+        i64: TryFrom<T>,
+    {
+        self.and_then(|value| match U::try_from(value) {
+            Ok(converted) => SqlResult::Success(converted),
+            Err(_) => SqlResult::invalid_driver_value(description, value),
+        })
+    }
+
+    /// [`Self::InvalidDriverValue`] for `value`.
+    pub fn invalid_driver_value<V>(description: &'static str, value: V) -> Self
+    where
+        i64: TryFrom<V>,
+    {
+        SqlResult::InvalidDriverValue {
+            description,
+            value: i64::try_from(value).unwrap_or(i64::MAX),
         }
     }
 
@@ -65,21 +125,54 @@ impl<T> SqlResult<T> {
 }
 
 pub trait ExtSqlReturn {
-    fn into_sql_result(self, function_name: &'static str) -> SqlResult<()>;
+    /// Converts the return code into an [`SqlResult`], after passing it to the installed
+    /// [`super::CallHook`], if any.
+    fn into_sql_result(
+        self,
+        handle: &(impl AsHandle + ?Sized),
+        function_name: &'static str,
+    ) -> SqlResult<()>;
+
+    /// Like [`Self::into_sql_result`], but for functions which may not be called on a valid
+    /// handle, e.g. `SQLAllocHandle` for an environment.
+    fn into_sql_result_for(
+        self,
+        handle_type: HandleType,
+        handle: Handle,
+        function_name: &'static str,
+    ) -> SqlResult<()>;
 }
 
 impl ExtSqlReturn for SqlReturn {
-    fn into_sql_result(self, function: &'static str) -> SqlResult<()> {
-        match self {
+    fn into_sql_result(
+        self,
+        handle: &(impl AsHandle + ?Sized),
+        function: &'static str,
+    ) -> SqlResult<()> {
+        self.into_sql_result_for(handle.handle_type(), handle.as_handle(), function)
+    }
+
+    fn into_sql_result_for(
+        self,
+        handle_type: HandleType,
+        handle: Handle,
+        function: &'static str,
+    ) -> SqlResult<()> {
+        let ret = invoke_call_hook(function, handle_type, handle, self);
+        record_call(function, handle_type, handle, ret);
+        match ret {
             SqlReturn::SUCCESS => SqlResult::Success(()),
             SqlReturn::SUCCESS_WITH_INFO => SqlResult::SuccessWithInfo(()),
             SqlReturn::ERROR => SqlResult::Error { function },
             SqlReturn::NO_DATA => SqlResult::NoData,
             SqlReturn::NEED_DATA => SqlResult::NeedData,
             SqlReturn::STILL_EXECUTING => SqlResult::StillExecuting,
-            r => panic!(
-                "Unexpected return value '{r:?}' for ODBC function '{function}'"
-            ),
+            r => {
+                // Could also be returned by a `CallHook`. Treat it as an error rather than
+                // panicking. There are no diagnostics for it, so report it here.
+                log::error!("Unexpected return value '{r:?}' for ODBC function '{function}'");
+                SqlResult::Error { function }
+            }
         }
     }
 }
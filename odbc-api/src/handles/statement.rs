@@ -2,20 +2,36 @@ use super::{
     as_handle::AsHandle,
     bind::{CDataMut, DelayedInput, HasDataType},
     buffer::{clamp_small_int, mut_buf_ptr},
+    call_audit::{AuditRegistration, CallAuditLog},
+    call_hook::{CallHook, CallHookRegistration},
     column_description::{ColumnDescription, Nullability},
+    cursor_type::{Concurrency, CursorType},
     data_type::DataType,
     drop_handle,
+    error_context::{attach_statement_context, detach_statement_context, ErrorContext},
+    raw_guard::RawStatementGuard,
     sql_char::{binary_length, is_truncated_bin, resize_to_fit_without_tz},
     sql_result::ExtSqlReturn,
+    statement_registry::{executing, ExecutionStatus, StatementRegistration},
     CData, SqlChar, SqlResult, SqlText,
 };
 use odbc_sys::{
-    Desc, FreeStmtOption, HDbc, HStmt, Handle, HandleType, Len, ParamType, Pointer, SQLBindCol,
-    SQLBindParameter, SQLCloseCursor, SQLCompleteAsync, SQLDescribeParam, SQLExecute, SQLFetch,
-    SQLFreeStmt, SQLGetData, SQLNumParams, SQLNumResultCols, SQLParamData, SQLPutData, SQLRowCount,
-    SqlDataType, SqlReturn, StatementAttribute, IS_POINTER,
+    CDataType, Desc, FetchOrientation, FreeStmtOption, HDbc, HStmt, Handle, HandleType, Len,
+    ParamType, Pointer, SQLBindCol, SQLBindParameter, SQLCancelHandle, SQLCloseCursor,
+    SQLCompleteAsync, SQLDescribeParam, SQLExecute, SQLFetch, SQLFetchScroll, SQLFreeStmt,
+    SQLGetData, SQLGetTypeInfo, SQLNumParams, SQLNumResultCols, SQLParamData, SQLPutData,
+    SQLRowCount, SqlDataType, SqlReturn, StatementAttribute, IS_INTEGER, IS_POINTER, NTS,
+};
+use std::{
+    ffi::c_void,
+    marker::PhantomData,
+    mem::{size_of, ManuallyDrop},
+    ptr::null_mut,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
 };
-use std::{ffi::c_void, marker::PhantomData, mem::ManuallyDrop, ptr::null_mut};
 
 #[cfg(feature = "narrow")]
 use odbc_sys::{
@@ -31,10 +47,33 @@ use odbc_sys::{
     SQLPrepareW as sql_prepare, SQLSetStmtAttrW as sql_set_stmt_attr, SQLTablesW as sql_tables,
 };
 
+// `odbc_sys` declares the attribute parameter of `SQLSetStmtAttr` as `StatementAttribute`, which
+// can not represent driver specific attributes. So we declare our own binding to the same symbol.
+// Linking against the driver manager is already taken care of by `odbc_sys`.
+extern "system" {
+    #[cfg_attr(not(feature = "narrow"), link_name = "SQLSetStmtAttrW")]
+    #[cfg_attr(feature = "narrow", link_name = "SQLSetStmtAttr")]
+    fn sql_set_stmt_vendor_attr(
+        hstmt: HStmt,
+        attr: i32,
+        value: Pointer,
+        str_length: i32,
+    ) -> SqlReturn;
+}
+
 /// An owned valid (i.e. successfully allocated) ODBC statement handle.
 pub struct StatementImpl<'s> {
     parent: PhantomData<&'s HDbc>,
     handle: HStmt,
+    /// Count of alive statements of the parent connection, decremented when this statement is
+    /// dropped.
+    count: Option<Arc<AtomicUsize>>,
+    /// Allows the environment to cancel this statement during shutdown.
+    registration: Option<StatementRegistration>,
+    /// Records calls made with this statement in the call audit of its connection.
+    audit: Option<AuditRegistration>,
+    /// Invokes the call hook of its environment for calls made with this statement.
+    call_hook: Option<CallHookRegistration>,
 }
 
 unsafe impl<'c> AsHandle for StatementImpl<'c> {
@@ -49,9 +88,17 @@ unsafe impl<'c> AsHandle for StatementImpl<'c> {
 
 impl<'s> Drop for StatementImpl<'s> {
     fn drop(&mut self) {
+        // Unregister before freeing, so the handle is not cancelled after it has been freed.
+        self.registration = None;
+        self.audit = None;
+        detach_statement_context(self.handle as Handle);
+        self.call_hook = None;
         unsafe {
             drop_handle(self.handle as Handle, HandleType::Stmt);
         }
+        if let Some(count) = &self.count {
+            count.fetch_sub(1, Ordering::SeqCst);
+        }
     }
 }
 
@@ -63,14 +110,65 @@ impl<'s> StatementImpl<'s> {
         Self {
             handle,
             parent: PhantomData,
+            count: None,
+            registration: None,
+            audit: None,
+            call_hook: None,
+        }
+    }
+
+    /// Increments `count` and decrements it again once this statement is dropped, or transferred
+    /// into a raw handle.
+    pub(crate) fn counted_by(mut self, count: &Arc<AtomicUsize>) -> Self {
+        count.fetch_add(1, Ordering::SeqCst);
+        self.count = Some(count.clone());
+        self
+    }
+
+    /// Keeps `registration` alive until this statement is dropped, or transferred into a raw
+    /// handle.
+    pub(crate) fn registered(mut self, registration: Option<StatementRegistration>) -> Self {
+        self.registration = registration;
+        self
+    }
+
+    /// Records calls made with this statement in `log`, until it is dropped, or transferred into a
+    /// raw handle.
+    pub(crate) fn audited(mut self, log: Option<&Arc<CallAuditLog>>) -> Self {
+        self.audit = log.map(|log| AuditRegistration::new(self.handle as Handle, log));
+        self
+    }
+
+    /// Attaches a context of its own to errors of this statement, until it is dropped, or
+    /// transferred into a raw handle. It inherits the entries of `parent`. Without a `parent`, a
+    /// context is only attached once the statement sets one itself.
+    pub(crate) fn with_context(self, parent: Option<&Arc<ErrorContext>>) -> Self {
+        if let Some(parent) = parent {
+            attach_statement_context(self.handle as Handle, parent);
         }
+        self
+    }
+
+    /// Invokes `hook` for calls made with this statement, until it is dropped, or transferred into
+    /// a raw handle.
+    pub(crate) fn hooked(mut self, hook: Option<&Arc<dyn CallHook>>) -> Self {
+        self.call_hook = hook.map(|hook| CallHookRegistration::new(self.handle as Handle, hook));
+        self
     }
 
     /// Transfer ownership of this statement to a raw system handle. It is the users responsibility
     /// to call [`crate::sys::SQLFreeHandle`].
     pub fn into_sys(self) -> HStmt {
         // We do not want to run the drop handler, but transfer ownership instead.
-        ManuallyDrop::new(self).handle
+        let mut this = ManuallyDrop::new(self);
+        if let Some(count) = this.count.take() {
+            count.fetch_sub(1, Ordering::SeqCst);
+        }
+        this.registration = None;
+        this.audit = None;
+        detach_statement_context(this.handle as Handle);
+        this.call_hook = None;
+        this.handle
     }
 
     /// Special wrapper to a borrowed statement. Acts like a mutable reference to an owned
@@ -79,6 +177,10 @@ impl<'s> StatementImpl<'s> {
         StatementRef {
             parent: self.parent,
             handle: self.handle,
+            status: self
+                .registration
+                .as_ref()
+                .map(StatementRegistration::status),
         }
     }
 }
@@ -90,21 +192,40 @@ impl<'s> StatementImpl<'s> {
 pub struct StatementRef<'s> {
     parent: PhantomData<&'s HDbc>,
     handle: HStmt,
+    /// Execution status of the owning statement, if it is tracked by its environment.
+    status: Option<&'s ExecutionStatus>,
 }
 
 impl<'s> StatementRef<'s> {
     pub(crate) unsafe fn new(handle: HStmt) -> Self {
+        Self::with_status(handle, None)
+    }
+
+    /// Like [`Self::new`], but marks the owning statement as executing during calls, so
+    /// [`crate::Environment::shutdown`] can wait for them.
+    pub(crate) unsafe fn with_status(handle: HStmt, status: Option<&'s ExecutionStatus>) -> Self {
         Self {
             handle,
             parent: PhantomData,
+            status,
         }
     }
+
+    /// Access the raw statement handle, in order to call ODBC functions not covered by this crate.
+    /// The statement is borrowed exclusively for the lifetime of the guard.
+    pub fn raw(&mut self) -> RawStatementGuard<'_> {
+        unsafe { RawStatementGuard::new(self.handle) }
+    }
 }
 
 impl<'s> Statement for StatementRef<'s> {
     fn as_sys(&self) -> HStmt {
         self.handle
     }
+
+    fn execution_status(&self) -> Option<&ExecutionStatus> {
+        self.status
+    }
 }
 
 unsafe impl<'c> AsHandle for StatementRef<'c> {
@@ -139,7 +260,7 @@ impl<'o> AsStatementRef for &mut StatementImpl<'o> {
 
 impl<'s> AsStatementRef for StatementRef<'s> {
     fn as_stmt_ref(&mut self) -> StatementRef<'_> {
-        unsafe { StatementRef::new(self.handle) }
+        unsafe { StatementRef::with_status(self.handle, self.status) }
     }
 }
 
@@ -155,6 +276,12 @@ pub trait Statement: AsHandle {
     /// Gain access to the underlying statement handle without transferring ownership to it.
     fn as_sys(&self) -> HStmt;
 
+    /// Tells [`crate::Environment::shutdown`] whether a function is currently called with this
+    /// statement. `None` if the statement is not tracked by an environment.
+    fn execution_status(&self) -> Option<&ExecutionStatus> {
+        None
+    }
+
     /// Binds application data buffers to columns in the result set.
     ///
     /// * `column_number`: `0` is the bookmark column. It is not included in some result sets. All
@@ -182,7 +309,7 @@ pub trait Statement: AsHandle {
             target.buffer_length(),
             target.mut_indicator_ptr(),
         )
-        .into_sql_result("SQLBindCol")
+        .into_sql_result(self, "SQLBindCol")
     }
 
     /// Returns the next row set in the result set.
@@ -197,11 +324,29 @@ pub trait Statement: AsHandle {
     ///
     /// Fetch dereferences bound column pointers.
     unsafe fn fetch(&mut self) -> SqlResult<()> {
-        SQLFetch(self.as_sys()).into_sql_result("SQLFetch")
+        let _executing = executing(self.execution_status());
+        SQLFetch(self.as_sys()).into_sql_result(self, "SQLFetch")
+    }
+
+    /// Fetches the row set at `offset` relative to the position indicated by `orientation`. E.g.
+    /// [`FetchOrientation::Relative`] with an offset of `0` fetches the current row set again. All
+    /// orientations other than [`FetchOrientation::Next`] require a scrollable cursor.
+    ///
+    /// # Safety
+    ///
+    /// Fetch dereferences bound column pointers.
+    unsafe fn fetch_scroll(
+        &mut self,
+        orientation: FetchOrientation,
+        offset: isize,
+    ) -> SqlResult<()> {
+        let _executing = executing(self.execution_status());
+        SQLFetchScroll(self.as_sys(), orientation, offset).into_sql_result(self, "SQLFetchScroll")
     }
 
     /// Retrieves data for a single column in the result set or for a single parameter.
     fn get_data(&mut self, col_or_param_num: u16, target: &mut impl CDataMut) -> SqlResult<()> {
+        let _executing = executing(self.execution_status());
         unsafe {
             SQLGetData(
                 self.as_sys(),
@@ -212,12 +357,13 @@ pub trait Statement: AsHandle {
                 target.mut_indicator_ptr(),
             )
         }
-        .into_sql_result("SQLGetData")
+        .into_sql_result(self, "SQLGetData")
     }
 
     /// Release all column buffers bound by `bind_col`. Except bookmark column.
     fn unbind_cols(&mut self) -> SqlResult<()> {
-        unsafe { SQLFreeStmt(self.as_sys(), FreeStmtOption::Unbind) }.into_sql_result("SQLFreeStmt")
+        unsafe { SQLFreeStmt(self.as_sys(), FreeStmtOption::Unbind) }
+            .into_sql_result(self, "SQLFreeStmt")
     }
 
     /// Bind an integer to hold the number of rows retrieved with fetch in the current row set.
@@ -236,7 +382,7 @@ pub trait Statement: AsHandle {
             value,
             IS_POINTER,
         )
-        .into_sql_result("SQLSetStmtAttr")
+        .into_sql_result(self, "SQLSetStmtAttr")
     }
 
     /// Fetch a column description using the column index.
@@ -274,7 +420,7 @@ pub trait Statement: AsHandle {
                 &mut decimal_digits,
                 &mut nullable,
             )
-            .into_sql_result("SQLDescribeCol")
+            .into_sql_result(self, "SQLDescribeCol")
         };
 
         if res.is_err() {
@@ -311,23 +457,40 @@ pub trait Statement: AsHandle {
     /// * [`SqlResult::NoData`] if a searched update or delete statement did not affect any rows at
     ///   the data source.
     unsafe fn exec_direct(&mut self, statement: &SqlText) -> SqlResult<()> {
+        let _executing = executing(self.execution_status());
         sql_exec_direc(
             self.as_sys(),
             statement.ptr(),
             statement.len_char().try_into().unwrap(),
         )
-        .into_sql_result("SQLExecDirect")
+        .into_sql_result(self, "SQLExecDirect")
     }
 
     /// Close an open cursor.
     fn close_cursor(&mut self) -> SqlResult<()> {
-        unsafe { SQLCloseCursor(self.as_sys()) }.into_sql_result("SQLCloseCursor")
+        unsafe { SQLCloseCursor(self.as_sys()) }.into_sql_result(self, "SQLCloseCursor")
+    }
+
+    /// Cancels the processing on the statement, e.g. of a query executed on another thread. In
+    /// contrast to most other functions, this may be called concurrently with another function
+    /// operating on the same statement.
+    fn cancel(&self) -> SqlResult<()> {
+        unsafe { SQLCancelHandle(HandleType::Stmt, self.as_sys() as Handle) }
+            .into_sql_result(self, "SQLCancelHandle")
+    }
+
+    /// Closes the cursor associated with the statement, if any, discarding all pending results.
+    /// Contrary to [`Self::close_cursor`] it is not an error to call this if no cursor is open.
+    fn discard_results(&mut self) -> SqlResult<()> {
+        unsafe { SQLFreeStmt(self.as_sys(), FreeStmtOption::Close) }
+            .into_sql_result(self, "SQLFreeStmt")
     }
 
     /// Send an SQL statement to the data source for preparation. The application can include one or
     /// more parameter markers in the SQL statement. To include a parameter marker, the application
     /// embeds a question mark (?) into the SQL string at the appropriate position.
     fn prepare(&mut self, statement: &SqlText) -> SqlResult<()> {
+        let _executing = executing(self.execution_status());
         unsafe {
             sql_prepare(
                 self.as_sys(),
@@ -335,7 +498,7 @@ pub trait Statement: AsHandle {
                 statement.len_char().try_into().unwrap(),
             )
         }
-        .into_sql_result("SQLPrepare")
+        .into_sql_result(self, "SQLPrepare")
     }
 
     /// Executes a statement prepared by `prepare`. After the application processes or discards the
@@ -355,7 +518,8 @@ pub trait Statement: AsHandle {
     /// * [`SqlResult::NoData`] if a searched update or delete statement did not affect any rows at
     ///   the data source.
     unsafe fn execute(&mut self) -> SqlResult<()> {
-        SQLExecute(self.as_sys()).into_sql_result("SQLExecute")
+        let _executing = executing(self.execution_status());
+        SQLExecute(self.as_sys()).into_sql_result(self, "SQLExecute")
     }
 
     /// Number of columns in result set.
@@ -364,7 +528,7 @@ pub trait Statement: AsHandle {
     fn num_result_cols(&self) -> SqlResult<i16> {
         let mut out: i16 = 0;
         unsafe { SQLNumResultCols(self.as_sys(), &mut out) }
-            .into_sql_result("SQLNumResultCols")
+            .into_sql_result(self, "SQLNumResultCols")
             .on_success(|| out)
     }
 
@@ -372,8 +536,9 @@ pub trait Statement: AsHandle {
     fn num_params(&self) -> SqlResult<u16> {
         let mut out: i16 = 0;
         unsafe { SQLNumParams(self.as_sys(), &mut out) }
-            .into_sql_result("SQLNumParams")
-            .on_success(|| out.try_into().unwrap())
+            .into_sql_result(self, "SQLNumParams")
+            .on_success(|| out)
+            .reported("number of parameters")
     }
 
     /// Sets the batch size for bulk cursors, if retrieving many rows at once.
@@ -390,7 +555,7 @@ pub trait Statement: AsHandle {
             size as Pointer,
             0,
         )
-        .into_sql_result("SQLSetStmtAttr")
+        .into_sql_result(self, "SQLSetStmtAttr")
     }
 
     /// Specifies the number of values for each parameter. If it is greater than 1, the data and
@@ -409,7 +574,7 @@ pub trait Statement: AsHandle {
             size as Pointer,
             0,
         )
-        .into_sql_result("SQLSetStmtAttr")
+        .into_sql_result(self, "SQLSetStmtAttr")
     }
 
     /// Sets the binding type to columnar binding for batch cursors.
@@ -428,7 +593,74 @@ pub trait Statement: AsHandle {
             row_size as Pointer,
             0,
         )
-        .into_sql_result("SQLSetStmtAttr")
+        .into_sql_result(self, "SQLSetStmtAttr")
+    }
+
+    /// Binds an offset in bytes, which the driver adds to the value, indicator and length pointers
+    /// of all bound columns then fetching. Allows fetching consecutive row sets into one large
+    /// allocation, which is bound only once, by changing the offset between calls to `fetch`,
+    /// rather than binding the buffers again. Passing `None` for `offset` is going to unbind the
+    /// offset from the statement.
+    ///
+    /// Since the same offset is added to every pointer, this is mostly useful for row wise binding
+    /// (see [`Self::set_row_bind_type`]), or if all bound buffers have elements of the same size.
+    ///
+    /// # Safety
+    ///
+    /// `offset` must not be moved and remain valid, as long as it remains bound to the statement.
+    /// The bound buffers must be large enough to hold the row set at any offset in effect then
+    /// fetching.
+    unsafe fn set_row_bind_offset(&mut self, offset: Option<&mut usize>) -> SqlResult<()> {
+        let value = offset
+            .map(|offset| offset as *mut usize as Pointer)
+            .unwrap_or_else(null_mut);
+        sql_set_stmt_attr(
+            self.as_sys(),
+            StatementAttribute::RowBindOffsetPtr,
+            value,
+            IS_POINTER,
+        )
+        .into_sql_result(self, "SQLSetStmtAttr")
+    }
+
+    /// Sets the binding type of parameters. Like [`Self::set_row_bind_type`], any positive number
+    /// indicates a row wise binding with that row length. `0` indicates a columnar binding, which
+    /// is the default.
+    ///
+    /// # Safety
+    ///
+    /// It is the callers responsibility to ensure that the bound buffers match the memory layout
+    /// specified by this function.
+    unsafe fn set_param_bind_type(&mut self, row_size: usize) -> SqlResult<()> {
+        sql_set_stmt_attr(
+            self.as_sys(),
+            StatementAttribute::ParamBindType,
+            row_size as Pointer,
+            0,
+        )
+        .into_sql_result(self, "SQLSetStmtAttr")
+    }
+
+    /// Binds an offset in bytes, which the driver adds to the value, indicator and length pointers
+    /// of all bound parameters then executing. Like [`Self::set_row_bind_offset`], this allows
+    /// executing a statement for consecutive batches of one large parameter buffer, which is bound
+    /// only once. Passing `None` for `offset` is going to unbind the offset from the statement.
+    ///
+    /// # Safety
+    ///
+    /// `offset` must not be moved and remain valid, as long as it remains bound to the statement.
+    /// The bound buffers must hold the parameter set at any offset in effect then executing.
+    unsafe fn set_param_bind_offset(&mut self, offset: Option<&mut usize>) -> SqlResult<()> {
+        let value = offset
+            .map(|offset| offset as *mut usize as Pointer)
+            .unwrap_or_else(null_mut);
+        sql_set_stmt_attr(
+            self.as_sys(),
+            StatementAttribute::ParamBindOffsetPtr,
+            value,
+            IS_POINTER,
+        )
+        .into_sql_result(self, "SQLSetStmtAttr")
     }
 
     fn set_metadata_id(&mut self, metadata_id: bool) -> SqlResult<()> {
@@ -439,7 +671,7 @@ pub trait Statement: AsHandle {
                 metadata_id as usize as Pointer,
                 0,
             )
-            .into_sql_result("SQLSetStmtAttr")
+            .into_sql_result(self, "SQLSetStmtAttr")
         }
     }
 
@@ -459,10 +691,161 @@ pub trait Statement: AsHandle {
                 on as usize as Pointer,
                 0,
             )
-            .into_sql_result("SQLSetStmtAttr")
+            .into_sql_result(self, "SQLSetStmtAttr")
         }
     }
 
+    /// Limits the number of rows returned by result sets generated by subsequent executions of this
+    /// statement. `0` means no limit, which is the default. Drivers may not support this. They
+    /// then return `SuccessWithInfo` and substitute the value (`01S02`).
+    ///
+    /// This is equivalent to setting `SQL_ATTR_MAX_ROWS` in the bare C API.
+    fn set_max_rows(&mut self, max_rows: usize) -> SqlResult<()> {
+        unsafe {
+            sql_set_stmt_attr(
+                self.as_sys(),
+                StatementAttribute::MaxRows,
+                max_rows as Pointer,
+                0,
+            )
+            .into_sql_result(self, "SQLSetStmtAttr")
+        }
+    }
+
+    /// Limits the number of bytes returned for a single character or binary value. Longer values
+    /// are silently cut off by the driver. `0` means no limit, which is the default.
+    ///
+    /// This is equivalent to setting `SQL_ATTR_MAX_LENGTH` in the bare C API.
+    fn set_max_length(&mut self, max_length: usize) -> SqlResult<()> {
+        unsafe {
+            sql_set_stmt_attr(
+                self.as_sys(),
+                StatementAttribute::MaxLength,
+                max_length as Pointer,
+                0,
+            )
+            .into_sql_result(self, "SQLSetStmtAttr")
+        }
+    }
+
+    /// Number of seconds to wait for a statement to execute before returning to the application.
+    /// `0` means no timeout, which is the default. Drivers may not support this, or substitute
+    /// the value and return `SuccessWithInfo` (`01S02`).
+    ///
+    /// This is equivalent to setting `SQL_ATTR_QUERY_TIMEOUT` in the bare C API.
+    fn set_query_timeout_sec(&mut self, timeout_sec: usize) -> SqlResult<()> {
+        unsafe {
+            sql_set_stmt_attr(
+                self.as_sys(),
+                StatementAttribute::QueryTimeout,
+                timeout_sec as Pointer,
+                0,
+            )
+            .into_sql_result(self, "SQLSetStmtAttr")
+        }
+    }
+
+    /// Chooses the kind of cursor used for result sets of subsequent executions. Must be set
+    /// before the statement is prepared or executed. Drivers may substitute the cursor type and
+    /// return `SuccessWithInfo` (`01S02`).
+    ///
+    /// This is equivalent to setting `SQL_ATTR_CURSOR_TYPE` in the bare C API.
+    fn set_cursor_type(&mut self, cursor_type: CursorType) -> SqlResult<()> {
+        unsafe {
+            sql_set_stmt_attr(
+                self.as_sys(),
+                StatementAttribute::CursorType,
+                cursor_type.as_attribute_value() as Pointer,
+                0,
+            )
+            .into_sql_result(self, "SQLSetStmtAttr")
+        }
+    }
+
+    /// Chooses the concurrency control of cursors for subsequent executions. Must be set before the
+    /// statement is prepared or executed. Drivers may substitute the concurrency and return
+    /// `SuccessWithInfo` (`01S02`).
+    ///
+    /// This is equivalent to setting `SQL_ATTR_CONCURRENCY` in the bare C API.
+    fn set_concurrency(&mut self, concurrency: Concurrency) -> SqlResult<()> {
+        unsafe {
+            sql_set_stmt_attr(
+                self.as_sys(),
+                StatementAttribute::Concurrency,
+                concurrency.as_attribute_value() as Pointer,
+                0,
+            )
+            .into_sql_result(self, "SQLSetStmtAttr")
+        }
+    }
+
+    /// Enables or disables variable length bookmarks for result sets of subsequent executions.
+    /// Bookmarks are returned in column `0`. Must be set before the statement is prepared or
+    /// executed.
+    ///
+    /// This is equivalent to setting `SQL_ATTR_USE_BOOKMARKS` to `SQL_UB_VARIABLE` or `SQL_UB_OFF`
+    /// in the bare C API.
+    fn set_use_bookmarks(&mut self, use_bookmarks: bool) -> SqlResult<()> {
+        let value: usize = if use_bookmarks { 2 } else { 0 };
+        unsafe {
+            sql_set_stmt_attr(
+                self.as_sys(),
+                StatementAttribute::UseBookmarks,
+                value as Pointer,
+                0,
+            )
+            .into_sql_result(self, "SQLSetStmtAttr")
+        }
+    }
+
+    /// Sets a driver specific statement attribute holding an integer. See [`crate::vendor`] for
+    /// identifiers of some common vendor attributes.
+    ///
+    /// # Safety
+    ///
+    /// The meaning of the attribute is up to the driver. It is the callers responsibility to ensure
+    /// that `attribute` expects an integer value and that setting it, does not invalidate the
+    /// assumptions of any buffers bound to the statement.
+    unsafe fn set_vendor_attr_int(&mut self, attribute: i32, value: i32) -> SqlResult<()> {
+        sql_set_stmt_vendor_attr(
+            self.as_sys(),
+            attribute,
+            value as isize as Pointer,
+            IS_INTEGER,
+        )
+        .into_sql_result(self, "SQLSetStmtAttr")
+    }
+
+    /// Sets a driver specific statement attribute holding a pointer.
+    ///
+    /// # Safety
+    ///
+    /// In addition to the requirements of [`Self::set_vendor_attr_int`], `value` must point to
+    /// memory of the type the driver expects for `attribute`. It must stay valid for as long as
+    /// the driver may access it, which may well be the lifetime of the statement.
+    unsafe fn set_vendor_attr_ptr(&mut self, attribute: i32, value: Pointer) -> SqlResult<()> {
+        sql_set_stmt_vendor_attr(self.as_sys(), attribute, value, IS_POINTER)
+            .into_sql_result(self, "SQLSetStmtAttr")
+    }
+
+    /// Sets a driver specific statement attribute holding a string.
+    ///
+    /// # Safety
+    ///
+    /// Same requirements as for [`Self::set_vendor_attr_int`], but `attribute` must expect a
+    /// string value. The driver is expected to copy the string.
+    unsafe fn set_vendor_attr_str(&mut self, attribute: i32, value: &SqlText) -> SqlResult<()> {
+        sql_set_stmt_vendor_attr(
+            self.as_sys(),
+            attribute,
+            value.ptr() as Pointer,
+            (value.len_char() * size_of::<SqlChar>())
+                .try_into()
+                .unwrap(),
+        )
+        .into_sql_result(self, "SQLSetStmtAttr")
+    }
+
     /// Binds a buffer holding an input parameter to a parameter marker in an SQL statement. This
     /// specialized version takes a constant reference to parameter, but is therefore limited to
     /// binding input parameters. See [`Statement::bind_parameter`] for the version which can bind
@@ -494,7 +877,7 @@ pub trait Statement: AsHandle {
             // We cast const to mut here, but we specify the input_output_type as input.
             parameter.indicator_ptr() as *mut isize,
         )
-        .into_sql_result("SQLBindParameter")
+        .into_sql_result(self, "SQLBindParameter")
     }
 
     /// Binds a buffer holding a single parameter to a parameter marker in an SQL statement. To bind
@@ -525,7 +908,46 @@ pub trait Statement: AsHandle {
             parameter.buffer_length(),
             parameter.mut_indicator_ptr(),
         )
-        .into_sql_result("SQLBindParameter")
+        .into_sql_result(self, "SQLBindParameter")
+    }
+
+    /// Binds a table-valued parameter of Microsoft SQL Server to a parameter marker. The columns
+    /// of the table are bound afterwards as parameters of their own, while the statement attribute
+    /// [`crate::vendor::mssql::SOPT_SS_PARAM_FOCUS`] is set to `parameter_number`.
+    ///
+    /// See <https://learn.microsoft.com/en-us/sql/relational-databases/native-client-odbc-table-valued-parameters/binding-and-data-transfer-of-table-valued-parameters-and-column-values>.
+    ///
+    /// # Safety
+    ///
+    /// * `type_name` must either be empty, or hold the name of the table type terminated by a zero.
+    /// * `type_name` and `num_rows` must stay valid until the statement is executed.
+    /// * Only call this for statements allocated on a connection to Microsoft SQL Server.
+    unsafe fn bind_table_valued_parameter(
+        &mut self,
+        parameter_number: u16,
+        type_name: &[SqlChar],
+        max_rows: usize,
+        num_rows: &isize,
+    ) -> SqlResult<()> {
+        let type_name_ptr = if type_name.is_empty() {
+            null_mut()
+        } else {
+            type_name.as_ptr() as *mut c_void
+        };
+        SQLBindParameter(
+            self.as_sys(),
+            parameter_number,
+            ParamType::Input,
+            CDataType::Default,
+            SqlDataType(crate::vendor::mssql::SS_TABLE),
+            max_rows,
+            0,
+            type_name_ptr,
+            NTS,
+            // We cast const to mut here, but we specify the input_output_type as input.
+            num_rows as *const isize as *mut isize,
+        )
+        .into_sql_result(self, "SQLBindParameter")
     }
 
     /// Binds an input stream to a parameter marker in an SQL statement. Use this to stream large
@@ -557,7 +979,7 @@ pub trait Statement: AsHandle {
             // We cast const to mut here, but we specify the input_output_type as input.
             parameter.indicator_ptr() as *mut isize,
         )
-        .into_sql_result("SQLBindParameter")
+        .into_sql_result(self, "SQLBindParameter")
     }
 
     /// `true` if a given column in a result set is unsigned or not a numeric type, `false`
@@ -565,10 +987,12 @@ pub trait Statement: AsHandle {
     ///
     /// `column_number`: Index of the column, starting at 1.
     fn is_unsigned_column(&self, column_number: u16) -> SqlResult<bool> {
-        unsafe { self.numeric_col_attribute(Desc::Unsigned, column_number) }.map(|out| match out {
-            0 => false,
-            1 => true,
-            _ => panic!("Unsigned column attribute must be either 0 or 1."),
+        unsafe { self.numeric_col_attribute(Desc::Unsigned, column_number) }.and_then(|out| {
+            match out {
+                0 => SqlResult::Success(false),
+                1 => SqlResult::Success(true),
+                other => SqlResult::invalid_driver_value("unsigned column attribute", other),
+            }
         })
     }
 
@@ -577,7 +1001,8 @@ pub trait Statement: AsHandle {
     /// `column_number`: Index of the column, starting at 1.
     fn col_type(&self, column_number: u16) -> SqlResult<SqlDataType> {
         unsafe { self.numeric_col_attribute(Desc::Type, column_number) }
-            .map(|ret| SqlDataType(ret.try_into().unwrap()))
+            .reported("SQL data type")
+            .map(SqlDataType)
     }
 
     /// The concise data type. For the datetime and interval data types, this field returns the
@@ -586,7 +1011,8 @@ pub trait Statement: AsHandle {
     /// `column_number`: Index of the column, starting at 1.
     fn col_concise_type(&self, column_number: u16) -> SqlResult<SqlDataType> {
         unsafe { self.numeric_col_attribute(Desc::ConciseType, column_number) }
-            .map(|ret| SqlDataType(ret.try_into().unwrap()))
+            .reported("SQL data type")
+            .map(SqlDataType)
     }
 
     /// Returns the size in bytes of the columns. For variable sized types the maximum size is
@@ -632,37 +1058,49 @@ pub trait Statement: AsHandle {
                 column_number,
                 Desc::Name,
                 mut_buf_ptr(buffer) as Pointer,
-                binary_length(buffer).try_into().unwrap(),
+                clamp_small_int(binary_length(buffer)),
                 &mut string_length_in_bytes as *mut i16,
                 null_mut(),
             )
-            .into_sql_result("SQLColAttribute");
+            .into_sql_result(self, "SQLColAttribute");
 
             if res.is_err() {
                 return res;
             }
-
-            if is_truncated_bin(buffer, string_length_in_bytes.try_into().unwrap()) {
+            let Ok(length) = usize::try_from(string_length_in_bytes) else {
+                return SqlResult::invalid_driver_value(
+                    "length of a column name",
+                    string_length_in_bytes,
+                );
+            };
+
+            if is_truncated_bin(buffer, length) {
                 // If we could rely on every ODBC driver sticking to the specifcation it would
                 // probably best to resize by `string_length_in_bytes / 2 + 1`. Yet e.g. SQLite
                 // seems to report the length in characters, so to work with a wide range of DB
                 // systems, and since buffers for names are not expected to become super large we
                 // ommit the division by two here.
-                buffer.resize((string_length_in_bytes + 1).try_into().unwrap(), 0);
+                buffer.resize(length + 1, 0);
 
                 res = sql_col_attribute(
                     self.as_sys(),
                     column_number,
                     Desc::Name,
                     mut_buf_ptr(buffer) as Pointer,
-                    binary_length(buffer).try_into().unwrap(),
+                    clamp_small_int(binary_length(buffer)),
                     &mut string_length_in_bytes as *mut i16,
                     null_mut(),
                 )
-                .into_sql_result("SQLColAttribute");
+                .into_sql_result(self, "SQLColAttribute");
             }
+            let Ok(length) = usize::try_from(string_length_in_bytes) else {
+                return SqlResult::invalid_driver_value(
+                    "length of a column name",
+                    string_length_in_bytes,
+                );
+            };
             // Resize buffer to exact string length without terminal zero
-            resize_to_fit_without_tz(buffer, string_length_in_bytes.try_into().unwrap());
+            resize_to_fit_without_tz(buffer, length);
 
             res
         }
@@ -682,7 +1120,7 @@ pub trait Statement: AsHandle {
             null_mut(),
             &mut out as *mut Len,
         )
-        .into_sql_result("SQLColAttribute")
+        .into_sql_result(self, "SQLColAttribute")
         .on_success(|| out)
     }
 
@@ -690,7 +1128,8 @@ pub trait Statement: AsHandle {
     /// given StatementHandle.
     fn reset_parameters(&mut self) -> SqlResult<()> {
         unsafe {
-            SQLFreeStmt(self.as_sys(), FreeStmtOption::ResetParams).into_sql_result("SQLFreeStmt")
+            SQLFreeStmt(self.as_sys(), FreeStmtOption::ResetParams)
+                .into_sql_result(self, "SQLFreeStmt")
         }
     }
 
@@ -715,7 +1154,7 @@ pub trait Statement: AsHandle {
                 &mut nullable,
             )
         }
-        .into_sql_result("SQLDescribeParam")
+        .into_sql_result(self, "SQLDescribeParam")
         .on_success(|| ParameterDescription {
             data_type: DataType::new(data_type, parameter_size, decimal_digits),
             nullable: Nullability::new(nullable),
@@ -728,12 +1167,15 @@ pub trait Statement: AsHandle {
     ///
     /// Return value contains a parameter identifier passed to bind parameter as a value pointer.
     fn param_data(&mut self) -> SqlResult<Option<Pointer>> {
+        let _executing = executing(self.execution_status());
         unsafe {
             let mut param_id: Pointer = null_mut();
             // Use cases for `PARAM_DATA_AVAILABLE` and `NO_DATA` not implemented yet.
-            match SQLParamData(self.as_sys(), &mut param_id as *mut Pointer) {
-                SqlReturn::NEED_DATA => SqlResult::Success(Some(param_id)),
-                other => other.into_sql_result("SQLParamData").on_success(|| None),
+            match SQLParamData(self.as_sys(), &mut param_id as *mut Pointer)
+                .into_sql_result(self, "SQLParamData")
+            {
+                SqlResult::NeedData => SqlResult::Success(Some(param_id)),
+                other => other.on_success(|| None),
             }
         }
     }
@@ -746,6 +1188,7 @@ pub trait Statement: AsHandle {
         table_name: &SqlText,
         column_name: &SqlText,
     ) -> SqlResult<()> {
+        let _executing = executing(self.execution_status());
         unsafe {
             sql_columns(
                 self.as_sys(),
@@ -758,7 +1201,7 @@ pub trait Statement: AsHandle {
                 column_name.ptr(),
                 column_name.len_char().try_into().unwrap(),
             )
-            .into_sql_result("SQLColumns")
+            .into_sql_result(self, "SQLColumns")
         }
     }
 
@@ -775,6 +1218,7 @@ pub trait Statement: AsHandle {
         table_name: &SqlText,
         table_type: &SqlText,
     ) -> SqlResult<()> {
+        let _executing = executing(self.execution_status());
         unsafe {
             sql_tables(
                 self.as_sys(),
@@ -787,15 +1231,24 @@ pub trait Statement: AsHandle {
                 table_type.ptr(),
                 table_type.len_char().try_into().unwrap(),
             )
-            .into_sql_result("SQLTables")
+            .into_sql_result(self, "SQLTables")
         }
     }
 
+    /// Creates a result set describing the data types supported by the data source. Pass
+    /// [`SqlDataType::UNKNOWN_TYPE`] (`SQL_ALL_TYPES`) to describe all of them. See:
+    /// <https://learn.microsoft.com/sql/odbc/reference/syntax/sqlgettypeinfo-function>
+    fn type_info(&mut self, data_type: SqlDataType) -> SqlResult<()> {
+        let _executing = executing(self.execution_status());
+        unsafe { SQLGetTypeInfo(self.as_sys(), data_type).into_sql_result(self, "SQLGetTypeInfo") }
+    }
+
     /// To put a batch of binary data into the data source at statement execution time. May return
     /// [`SqlResult::NeedData`]
     ///
     /// Panics if batch is empty.
     fn put_binary_batch(&mut self, batch: &[u8]) -> SqlResult<()> {
+        let _executing = executing(self.execution_status());
         // Probably not strictly necessary. MSSQL returns an error than inserting empty batches.
         // Still strikes me as a programming error. Maybe we could also do nothing instead.
         if batch.is_empty() {
@@ -808,7 +1261,7 @@ pub trait Statement: AsHandle {
                 batch.as_ptr() as Pointer,
                 batch.len().try_into().unwrap(),
             )
-            .into_sql_result("SQLPutData")
+            .into_sql_result(self, "SQLPutData")
         }
     }
 
@@ -822,7 +1275,7 @@ pub trait Statement: AsHandle {
         let mut ret = 0isize;
         unsafe {
             SQLRowCount(self.as_sys(), &mut ret as *mut isize)
-                .into_sql_result("SQLRowCount")
+                .into_sql_result(self, "SQLRowCount")
                 .on_success(|| ret)
         }
     }
@@ -845,9 +1298,9 @@ pub trait Statement: AsHandle {
             //   Driver Manager has not notified the application. In polling mode, an asynchronous
             //   operation is not in progress.
             SQLCompleteAsync(self.handle_type(), self.as_handle(), &mut ret.0 as *mut _)
-                .into_sql_result("SQLCompleteAsync")
+                .into_sql_result(self, "SQLCompleteAsync")
         }
-        .on_success(|| ret.into_sql_result(function_name))
+        .on_success(|| ret.into_sql_result(self, function_name))
     }
 }
 
@@ -856,6 +1309,12 @@ impl<'o> Statement for StatementImpl<'o> {
     fn as_sys(&self) -> HStmt {
         self.handle
     }
+
+    fn execution_status(&self) -> Option<&ExecutionStatus> {
+        self.registration
+            .as_ref()
+            .map(StatementRegistration::status)
+    }
 }
 
 /// Description of a parameter associated with a parameter marker in a prepared statement. Returned
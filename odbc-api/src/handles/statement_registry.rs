@@ -0,0 +1,171 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+
+use log::warn;
+use odbc_sys::{HDbc, HStmt, SQLCancel, SqlReturn};
+
+/// Statements allocated with an environment, which are still alive. Allows
+/// [`crate::Environment::shutdown`] to wait for statements executing on other threads and cancel
+/// them. Handles are stored as addresses, so the registry can be shared between threads.
+#[derive(Debug, Default)]
+pub(crate) struct StatementRegistry {
+    /// Connection handle, statement handle and status of each statement.
+    statements: Mutex<Vec<(usize, usize, Arc<ExecutionStatus>)>>,
+}
+
+/// Whether a function is currently called with a statement handle. Owned by the statement and
+/// shared with the registry of its environment, so it can be updated without looking up the
+/// handle. See [`crate::Environment::shutdown`].
+#[derive(Debug, Default)]
+pub struct ExecutionStatus(AtomicBool);
+
+impl ExecutionStatus {
+    fn is_executing(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+impl StatementRegistry {
+    /// Tracks `statement` until the returned registration is dropped, or the statements of
+    /// `connection` are unregistered.
+    pub fn register(self: &Arc<Self>, connection: HDbc, statement: HStmt) -> StatementRegistration {
+        let status = Arc::new(ExecutionStatus::default());
+        let entry = (connection as usize, statement as usize, status.clone());
+        self.statements.lock().unwrap().push(entry);
+        StatementRegistration {
+            registry: self.clone(),
+            statement: statement as usize,
+            status,
+        }
+    }
+
+    /// Stops tracking any statement allocated on `connection`. Must be called before disconnecting,
+    /// since this frees the statements, even if they have been leaked.
+    pub fn unregister_connection(&self, connection: HDbc) {
+        self.statements
+            .lock()
+            .unwrap()
+            .retain(|&(conn, _, _)| conn != connection as usize);
+    }
+
+    /// Number of statements currently executing a function, e.g. a query or a fetch.
+    pub fn num_executing(&self) -> usize {
+        self.statements
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, _, status)| status.is_executing())
+            .count()
+    }
+
+    /// Calls `SQLCancel` on every statement currently executing and returns the number of
+    /// statements cancelled successfully. The lock is held while cancelling, so none of the
+    /// statements can be freed in the meantime.
+    pub fn cancel_executing(&self) -> usize {
+        let statements = self.statements.lock().unwrap();
+        let mut num_cancelled = 0;
+        for (_, statement, status) in statements.iter() {
+            if !status.is_executing() {
+                continue;
+            }
+            match unsafe { SQLCancel(*statement as HStmt) } {
+                SqlReturn::SUCCESS | SqlReturn::SUCCESS_WITH_INFO => num_cancelled += 1,
+                other => warn!("SQLCancel failed with return code {:?}.", other.0),
+            }
+        }
+        num_cancelled
+    }
+
+    fn unregister(&self, statement: usize) {
+        let mut statements = self.statements.lock().unwrap();
+        if let Some(index) = statements
+            .iter()
+            .position(|&(_, stmt, _)| stmt == statement)
+        {
+            statements.swap_remove(index);
+        }
+    }
+}
+
+/// Marks the statement as executing, until dropped. See [`executing`].
+pub(crate) struct Executing<'s>(&'s ExecutionStatus);
+
+impl Drop for Executing<'_> {
+    fn drop(&mut self) {
+        self.0 .0.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Marks a statement as executing, so [`crate::Environment::shutdown`] waits for the call to
+/// finish, or cancels it. `None` if the statement is not tracked, or already executing, e.g. while
+/// fetching parameter data during execution.
+pub(crate) fn executing(status: Option<&ExecutionStatus>) -> Option<Executing<'_>> {
+    let status = status?;
+    if status.0.swap(true, Ordering::SeqCst) {
+        return None;
+    }
+    Some(Executing(status))
+}
+
+/// Removes a statement from a [`StatementRegistry`] once dropped. Must be dropped before the
+/// statement handle is freed.
+pub(crate) struct StatementRegistration {
+    registry: Arc<StatementRegistry>,
+    statement: usize,
+    status: Arc<ExecutionStatus>,
+}
+
+impl StatementRegistration {
+    /// Tells the registry whether the statement is currently executing.
+    pub fn status(&self) -> &ExecutionStatus {
+        &self.status
+    }
+}
+
+impl Drop for StatementRegistration {
+    fn drop(&mut self) {
+        self.registry.unregister(self.statement)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use odbc_sys::{HDbc, HStmt};
+
+    use super::{executing, StatementRegistry};
+
+    #[test]
+    fn unregister_statements_of_dropped_and_disconnected_connections() {
+        let registry = Arc::new(StatementRegistry::default());
+        let (first, second) = (1 as HDbc, 2 as HDbc);
+
+        let dropped = registry.register(first, 10 as HStmt);
+        let _alive = registry.register(first, 11 as HStmt);
+        let leaked = registry.register(second, 20 as HStmt);
+        std::mem::forget(leaked);
+        drop(dropped);
+        assert_eq!(2, registry.statements.lock().unwrap().len());
+        registry.unregister_connection(second);
+
+        assert_eq!(1, registry.statements.lock().unwrap().len());
+    }
+
+    #[test]
+    fn only_statements_within_calls_are_executing() {
+        let registry = Arc::new(StatementRegistry::default());
+        let _idle = registry.register(1 as HDbc, 30 as HStmt);
+        let busy = registry.register(1 as HDbc, 31 as HStmt);
+
+        let call = executing(Some(busy.status()));
+        let nested = executing(Some(busy.status()));
+        assert_eq!(1, registry.num_executing());
+        assert!(nested.is_none());
+        drop(call);
+
+        assert_eq!(0, registry.num_executing());
+    }
+}
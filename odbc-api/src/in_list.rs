@@ -0,0 +1,338 @@
+use crate::{
+    handles::{sql_text_len, AsStatementRef, StatementImpl},
+    parameter::InputParameter,
+    statement_kind::{Lexer, Token},
+    BlockCursor, Connection, Cursor, CursorImpl, CursorRow, Error, RowSetBuffer,
+};
+
+/// Marker in a statement text, which is expanded into one placeholder per value of the list.
+const MARKER: &str = "?...";
+
+/// Cursor over the result of a statement with an `IN` list of variable length. Created by
+/// [`Connection::execute_in_list`].
+///
+/// Data sources limit the number of parameters of a statement, e.g. Microsoft SQL Server to 2100
+/// and older versions of SQLite to 999. Lists exceeding `chunk_size` values are therefore split
+/// into several executions. Chunks are made smaller still, if the expanded statement text would
+/// otherwise exceed [`Connection::max_statement_len`]. See [`Self::chunk_size`] and
+/// [`Self::num_chunks`]. The result sets of the chunks are merged into this one cursor, which
+/// executes the next chunk once all rows of the previous one have been fetched. Only one statement
+/// is active at a time, so this works with drivers supporting a single active statement per
+/// connection (see [`Connection::max_concurrent_activities`]).
+///
+/// Merging concatenates the result sets, so the cursor holds the result of the statement as long
+/// as the condition can be evaluated for each chunk separately. This is true for `IN`, but not for
+/// `NOT IN`, and rows may be counted several times by aggregates. Clauses acting on the whole result
+/// set, like `ORDER BY`, `DISTINCT` or `LIMIT`, are applied to the result of each chunk separately.
+/// E.g. the rows of each chunk are ordered, but the rows of one chunk are not ordered relative to
+/// the ones of the next.
+pub struct InListCursor<'c, 'v, T> {
+    chunks: Chunks<'c, 'v, T>,
+    /// Cursor of the chunk currently fetched from. `None` once all chunks have been fetched.
+    current: Option<CursorImpl<StatementImpl<'c>>>,
+}
+
+impl<'c, 'v, T> InListCursor<'c, 'v, T>
+where
+    T: InputParameter,
+{
+    /// Executes chunks until the first one creating a result set, so statements without one, e.g.
+    /// `DELETE`, are executed for all values right away.
+    pub(crate) fn new(
+        connection: &'c Connection<'c>,
+        query: &str,
+        values: &'v [T],
+        chunk_size: usize,
+    ) -> Result<Self, Error> {
+        let mut chunks = Chunks::new(connection, query, values, chunk_size)?;
+        let current = chunks.execute_next()?;
+        Ok(Self { chunks, current })
+    }
+
+    /// Maximum number of values sent with each execution. Smaller than the requested chunk size, if
+    /// the statement text would otherwise exceed the maximum statement length of the driver.
+    pub fn chunk_size(&self) -> usize {
+        self.chunks.chunk_size
+    }
+
+    /// Number of executions required for the values not yet sent to the data source. Values which
+    /// do not fit into the maximum statement length at all count as a single chunk, which fails
+    /// with [`Error::StatementTooLong`].
+    pub fn num_chunks(&self) -> usize {
+        self.chunks.num_chunks()
+    }
+
+    /// Advances the cursor to the next row, executing the next chunk if the rows of the current one
+    /// are exhausted. Like [`Cursor::next_row`] this is slow. Bind a buffer using
+    /// [`Self::bind_buffer`] for good performance.
+    pub fn next_row(&mut self) -> Result<Option<CursorRow<'_>>, Error> {
+        loop {
+            match &mut self.current {
+                None => return Ok(None),
+                Some(cursor) => {
+                    if cursor.next_row()?.is_some() {
+                        break;
+                    }
+                }
+            }
+            // Close the exhausted cursor, before the next chunk is executed.
+            self.current = None;
+            self.current = self.chunks.execute_next()?;
+        }
+        // Safety: The row has just been fetched, so the statement is in cursor state.
+        Ok(self
+            .current
+            .as_mut()
+            .map(|cursor| unsafe { CursorRow::new(cursor.as_stmt_ref()) }))
+    }
+
+    /// Binds `buffer` to the cursor, to fetch the merged result set in row sets. The buffer is bound
+    /// to the cursor of each chunk in turn.
+    pub fn bind_buffer<B>(self, buffer: B) -> Result<InListBlockCursor<'c, 'v, T, B>, Error>
+    where
+        B: RowSetBuffer,
+    {
+        let current = self
+            .current
+            .map(|cursor| cursor.bind_buffer(buffer))
+            .transpose()?;
+        Ok(InListBlockCursor {
+            chunks: self.chunks,
+            current,
+        })
+    }
+}
+
+/// An [`InListCursor`] with a buffer bound to it. Created by [`InListCursor::bind_buffer`].
+pub struct InListBlockCursor<'c, 'v, T, B> {
+    chunks: Chunks<'c, 'v, T>,
+    /// Cursor of the chunk currently fetched from, with the buffer bound to it. `None` once all
+    /// chunks have been fetched.
+    current: Option<BlockCursor<CursorImpl<StatementImpl<'c>>, B>>,
+}
+
+impl<'c, 'v, T, B> InListBlockCursor<'c, 'v, T, B>
+where
+    T: InputParameter,
+    B: RowSetBuffer,
+{
+    /// Fills the bound buffer with the next row set, executing the next chunk if the rows of the
+    /// current one are exhausted. Row sets never span several chunks. `None` once the rows of all
+    /// chunks have been fetched.
+    pub fn fetch(&mut self) -> Result<Option<&B>, Error> {
+        self.fetch_with_truncation_check(false)
+    }
+
+    /// Like [`Self::fetch`], but fails if `error_for_truncation` is `true` and any value has been
+    /// truncated. See [`BlockCursor::fetch_with_truncation_check`].
+    pub fn fetch_with_truncation_check(
+        &mut self,
+        error_for_truncation: bool,
+    ) -> Result<Option<&B>, Error> {
+        loop {
+            let Some(mut block_cursor) = self.current.take() else {
+                return Ok(None);
+            };
+            if block_cursor
+                .fetch_with_truncation_check(error_for_truncation)?
+                .is_some()
+            {
+                self.current = Some(block_cursor);
+                break;
+            }
+            // Close the exhausted cursor, before the next chunk is executed.
+            let (cursor, buffer) = block_cursor.unbind()?;
+            drop(cursor);
+            self.current = self
+                .chunks
+                .execute_next()?
+                .map(|cursor| cursor.bind_buffer(buffer))
+                .transpose()?;
+        }
+        Ok(self.current.as_ref().map(BlockCursor::buffer))
+    }
+}
+
+/// Executes a statement with an `IN` list once for each chunk of values.
+struct Chunks<'c, 'v, T> {
+    connection: &'c Connection<'c>,
+    /// Statement text, split at the marker.
+    head: String,
+    tail: String,
+    /// Values which have not been sent to the data source yet.
+    values: &'v [T],
+    /// Number of values sent with each execution. `0` if not even a single value fits into the
+    /// maximum statement length.
+    chunk_size: usize,
+    max_statement_len: Option<usize>,
+}
+
+impl<'c, 'v, T> Chunks<'c, 'v, T>
+where
+    T: InputParameter,
+{
+    fn new(
+        connection: &'c Connection<'c>,
+        query: &str,
+        values: &'v [T],
+        chunk_size: usize,
+    ) -> Result<Self, Error> {
+        if chunk_size == 0 {
+            return Err(Error::InvalidArgument {
+                description: "chunk size of an IN list must be at least one",
+            });
+        }
+        let (head, tail) = split_at_marker(query)?;
+        let max_statement_len = connection.max_statement_len();
+        let chunk_size = match max_statement_len {
+            Some(max_len) => chunk_size.min(max_placeholders(head, tail, max_len)),
+            None => chunk_size,
+        };
+        Ok(Self {
+            connection,
+            head: head.to_owned(),
+            tail: tail.to_owned(),
+            values,
+            chunk_size,
+            max_statement_len,
+        })
+    }
+
+    fn num_chunks(&self) -> usize {
+        if self.chunk_size == 0 {
+            usize::from(!self.values.is_empty())
+        } else {
+            self.values.len().div_ceil(self.chunk_size)
+        }
+    }
+
+    /// Executes chunks until one of them creates a result set. `None` once all values have been
+    /// sent. No further chunks are executed after an error.
+    fn execute_next(&mut self) -> Result<Option<CursorImpl<StatementImpl<'c>>>, Error> {
+        if self.chunk_size == 0 && !self.values.is_empty() {
+            self.values = &[];
+            return Err(Error::StatementTooLong {
+                length: statement_len(&self.head, &self.tail, 1),
+                max_length: self.max_statement_len.unwrap_or(0),
+            });
+        }
+        // Statements which do not create a result set are still executed for every chunk.
+        while !self.values.is_empty() {
+            let (chunk, rest) = self.values.split_at(self.chunk_size.min(self.values.len()));
+            self.values = rest;
+            let sql = expand(&self.head, &self.tail, chunk.len());
+            match self.connection.execute(&sql, chunk) {
+                Ok(Some(cursor)) => return Ok(Some(cursor)),
+                Ok(None) => (),
+                Err(error) => {
+                    self.values = &[];
+                    return Err(error);
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Splits `query` at the only occurrence of the marker `?...`. Fails with
+/// [`Error::InvalidArgument`] if `query` does not contain the marker exactly once.
+fn split_at_marker(query: &str) -> Result<(&str, &str), Error> {
+    match marker_positions(query).as_slice() {
+        &[pos] => Ok((&query[..pos], &query[pos + MARKER.len()..])),
+        _ => Err(Error::InvalidArgument {
+            description: "statement text must contain the marker `?...` of the IN list exactly \
+                once, outside of literals and comments",
+        }),
+    }
+}
+
+/// Byte offsets of the markers in `query`, ignoring string literals, quoted identifiers and
+/// comments.
+fn marker_positions(query: &str) -> Vec<usize> {
+    let mut tokens = Lexer::new(query);
+    let mut positions = Vec::new();
+    while let Some(token) = tokens.next() {
+        // The question mark is a single byte in front of the current position.
+        let start = tokens.position - 1;
+        if token == Token::Punct(b'?') && query[start..].starts_with(MARKER) {
+            positions.push(start);
+        }
+    }
+    positions
+}
+
+/// Statement text with the marker replaced by `num_values` placeholders.
+fn expand(head: &str, tail: &str, num_values: usize) -> String {
+    let placeholders = vec!["?"; num_values].join(", ");
+    format!("{head}{placeholders}{tail}")
+}
+
+/// Length of the statement text with the marker replaced by `num_values` placeholders, counted like
+/// [`Connection::max_statement_len`]. Consistent with [`expand`].
+fn statement_len(head: &str, tail: &str, num_values: usize) -> usize {
+    // `?` for the first placeholder and `, ?` for each further one. Each is a single code unit.
+    sql_text_len(head) + sql_text_len(tail) + (num_values * 3).saturating_sub(2)
+}
+
+/// Maximum number of placeholders the marker can be expanded into, without the statement text
+/// exceeding `max_len`.
+fn max_placeholders(head: &str, tail: &str, max_len: usize) -> usize {
+    let fixed = statement_len(head, tail, 0);
+    max_len.saturating_sub(fixed).div_ceil(3)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Error;
+
+    use super::{expand, max_placeholders, split_at_marker, statement_len};
+
+    #[test]
+    fn expand_marker_into_placeholders() {
+        let (head, tail) =
+            split_at_marker("SELECT a FROM t WHERE id IN (?...) ORDER BY a").unwrap();
+
+        assert_eq!(
+            "SELECT a FROM t WHERE id IN (?, ?, ?) ORDER BY a",
+            expand(head, tail, 3)
+        );
+        assert_eq!(
+            "SELECT a FROM t WHERE id IN (?) ORDER BY a",
+            expand(head, tail, 1)
+        );
+    }
+
+    #[test]
+    fn fit_placeholders_into_max_statement_len() {
+        let (head, tail) = split_at_marker("SELECT a FROM t WHERE id IN (?...)").unwrap();
+        let len_of_two = expand(head, tail, 2).len();
+
+        assert_eq!(len_of_two, statement_len(head, tail, 2));
+        assert_eq!(2, max_placeholders(head, tail, len_of_two));
+        assert_eq!(2, max_placeholders(head, tail, len_of_two + 2));
+        assert_eq!(3, max_placeholders(head, tail, len_of_two + 3));
+        assert_eq!(0, max_placeholders(head, tail, len_of_two - 4));
+    }
+
+    #[test]
+    fn marker_must_occur_exactly_once() {
+        let twice = split_at_marker("SELECT a FROM t WHERE id IN (?...) OR b IN (?...)");
+        let missing = split_at_marker("SELECT a FROM t WHERE id IN (?)");
+
+        assert!(matches!(twice, Err(Error::InvalidArgument { .. })));
+        assert!(matches!(missing, Err(Error::InvalidArgument { .. })));
+    }
+
+    #[test]
+    fn ignore_markers_in_literals_and_comments() {
+        let query = "SELECT '?...', \"?...\" FROM t -- ?...\nWHERE id IN (?...) /* ?... */";
+
+        let (head, tail) = split_at_marker(query).unwrap();
+
+        assert_eq!(
+            "SELECT '?...', \"?...\" FROM t -- ?...\nWHERE id IN (",
+            head
+        );
+        assert_eq!(") /* ?... */", tail);
+    }
+}
@@ -0,0 +1,245 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{handles::StatementImpl, Connection, CursorImpl, Error, IntoParameter, Select};
+
+/// Persists the watermarks of incremental extractions between runs. Watermarks are stored in their
+/// text representation, identified by the key of the extraction.
+pub trait WatermarkStore {
+    /// Watermark saved for `key`. `None` if no watermark has been saved yet.
+    fn load(&mut self, key: &str) -> io::Result<Option<String>>;
+
+    /// Replaces the watermark saved for `key`.
+    fn save(&mut self, key: &str, watermark: &str) -> io::Result<()>;
+}
+
+/// Keeps watermarks in memory. Useful for tests, or if the application persists the watermarks
+/// itself, e.g. along with the extracted data.
+#[derive(Debug, Default, Clone)]
+pub struct InMemoryWatermarkStore {
+    watermarks: HashMap<String, String>,
+}
+
+impl InMemoryWatermarkStore {
+    /// An empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl WatermarkStore for InMemoryWatermarkStore {
+    fn load(&mut self, key: &str) -> io::Result<Option<String>> {
+        Ok(self.watermarks.get(key).cloned())
+    }
+
+    fn save(&mut self, key: &str, watermark: &str) -> io::Result<()> {
+        self.watermarks.insert(key.to_owned(), watermark.to_owned());
+        Ok(())
+    }
+}
+
+/// Stores each watermark in a file named after its key within a directory. The key must be a valid
+/// file name. Files are replaced atomically, so an interrupted run never leaves a partially written
+/// watermark behind.
+#[derive(Debug, Clone)]
+pub struct FileWatermarkStore {
+    directory: PathBuf,
+}
+
+impl FileWatermarkStore {
+    /// Stores watermarks in `directory`, which must exist.
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    /// Directory holding the watermark files.
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+}
+
+impl WatermarkStore for FileWatermarkStore {
+    fn load(&mut self, key: &str) -> io::Result<Option<String>> {
+        match fs::read_to_string(self.directory.join(key)) {
+            Ok(watermark) => Ok(Some(watermark)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn save(&mut self, key: &str, watermark: &str) -> io::Result<()> {
+        let path = self.directory.join(key);
+        let temporary = self.directory.join(format!("{key}.tmp"));
+        fs::write(&temporary, watermark)?;
+        fs::rename(temporary, path)
+    }
+}
+
+/// Extracts only the rows added or changed since the last run, based on a watermark column, i.e. a
+/// column whose value increases with every change, like an auto incremented id or a modification
+/// timestamp.
+///
+/// The highest watermark seen by the previous run is loaded from a [`WatermarkStore`]. Queries are
+/// restricted to rows with a greater watermark and sorted by it. While fetching, the application
+/// reports the watermark of the rows it processed using [`Self::observe`]. Once the extracted data
+/// is safely stored, [`Self::commit`] persists the new high-water mark for the next run.
+///
+/// The watermark is bound as text. Drivers convert it to the type of the watermark column, so pick
+/// a text representation the data source understands, e.g. `2024-01-31 12:00:00` for timestamps.
+///
+/// ```no_run
+/// use odbc_api::{Connection, Cursor, Error, FileWatermarkStore, IncrementalExtraction, Select};
+///
+/// fn extract_orders(conn: &Connection<'_>) -> Result<(), Error> {
+///     let store = FileWatermarkStore::new("watermarks");
+///     let mut extraction = IncrementalExtraction::new("orders", "modified", store)?;
+///     let select = Select::from("Orders").columns(["id", "modified"]);
+///     if let Some(mut cursor) = extraction.execute(conn, select)? {
+///         let mut modified = Vec::new();
+///         while let Some(mut row) = cursor.next_row()? {
+///             // Process the row ...
+///             row.get_text(2, &mut modified)?;
+///             extraction.observe(&String::from_utf8_lossy(&modified));
+///         }
+///     }
+///     extraction.commit()
+/// }
+/// ```
+#[derive(Debug)]
+pub struct IncrementalExtraction<S> {
+    key: String,
+    watermark_column: String,
+    store: S,
+    /// Watermark loaded from the store. Only rows above it are extracted.
+    saved: Option<String>,
+    /// Last watermark passed to `observe`.
+    observed: Option<String>,
+}
+
+impl<S> IncrementalExtraction<S>
+where
+    S: WatermarkStore,
+{
+    /// Loads the watermark saved for `key` from `store`. `watermark_column` is the name of the
+    /// column compared against the watermark. If no watermark has been saved yet, all rows are
+    /// extracted.
+    pub fn new(
+        key: impl Into<String>,
+        watermark_column: impl Into<String>,
+        mut store: S,
+    ) -> Result<Self, Error> {
+        let key = key.into();
+        let saved = store.load(&key).map_err(Error::WatermarkStore)?;
+        Ok(Self {
+            key,
+            watermark_column: watermark_column.into(),
+            store,
+            saved,
+            observed: None,
+        })
+    }
+
+    /// Watermark saved by the previous run. `None` during the first run.
+    pub fn saved_watermark(&self) -> Option<&str> {
+        self.saved.as_deref()
+    }
+
+    /// Highest watermark known to this run. The last observed watermark, or the saved one if
+    /// nothing has been observed yet.
+    pub fn high_water_mark(&self) -> Option<&str> {
+        self.observed.as_deref().or(self.saved.as_deref())
+    }
+
+    /// Restricts `select` to rows with a watermark greater than the saved one and sorts them by
+    /// the watermark column.
+    pub fn restrict(&self, select: Select) -> Select {
+        let select = if let Some(saved) = &self.saved {
+            select.filter_greater_than(
+                self.watermark_column.clone(),
+                saved.clone().into_parameter(),
+            )
+        } else {
+            select
+        };
+        select.order_by(self.watermark_column.clone())
+    }
+
+    /// Executes `select` restricted to the rows added or changed since the last run. See
+    /// [`Self::restrict`].
+    pub fn execute<'c>(
+        &self,
+        connection: &'c Connection<'_>,
+        select: Select,
+    ) -> Result<Option<CursorImpl<StatementImpl<'c>>>, Error> {
+        self.restrict(select).execute(connection)
+    }
+
+    /// Reports the watermark of a processed row. Since rows are fetched sorted by the watermark,
+    /// the last value observed is the highest. Observe rows in the order they are fetched.
+    pub fn observe(&mut self, watermark: &str) {
+        if let Some(observed) = &mut self.observed {
+            observed.clear();
+            observed.push_str(watermark);
+        } else {
+            self.observed = Some(watermark.to_owned());
+        }
+    }
+
+    /// Persists the watermark last observed, so the next run continues after it. Does nothing if
+    /// no rows have been observed. Call this only after the extracted rows are safely stored,
+    /// otherwise they are missing if the application fails in between.
+    pub fn commit(&mut self) -> Result<(), Error> {
+        if let Some(observed) = self.observed.take() {
+            self.store
+                .save(&self.key, &observed)
+                .map_err(Error::WatermarkStore)?;
+            self.saved = Some(observed);
+        }
+        Ok(())
+    }
+
+    /// Releases the store, e.g. to reuse it for the next extraction.
+    pub fn into_store(self) -> S {
+        self.store
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Select;
+
+    use super::{InMemoryWatermarkStore, IncrementalExtraction, WatermarkStore};
+
+    #[test]
+    fn continue_after_committed_watermark() {
+        let mut extraction =
+            IncrementalExtraction::new("orders", "id", InMemoryWatermarkStore::new()).unwrap();
+        assert_eq!(
+            r#"SELECT * FROM "Orders" ORDER BY "id""#,
+            extraction
+                .restrict(Select::from("Orders"))
+                .to_sql("\"")
+                .unwrap()
+        );
+
+        extraction.observe("41");
+        extraction.observe("42");
+        extraction.commit().unwrap();
+        let mut store = extraction.into_store();
+        assert_eq!(Some("42".to_owned()), store.load("orders").unwrap());
+
+        let extraction = IncrementalExtraction::new("orders", "id", store).unwrap();
+        assert_eq!(
+            r#"SELECT * FROM "Orders" WHERE "id" > ? ORDER BY "id""#,
+            extraction
+                .restrict(Select::from("Orders"))
+                .to_sql("\"")
+                .unwrap()
+        );
+    }
+}
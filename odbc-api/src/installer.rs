@@ -0,0 +1,320 @@
+//! Registers drivers and data sources with the driver manager, using its installer API. This is
+//! `odbccp32.dll` on Windows, `libodbcinst` for `unixODBC` and `libiodbcinst` for `iODBC`. Requires
+//! the `installer` feature.
+//!
+//! ```no_run
+//! use odbc_api::installer::{self, DataSourceRequest};
+//!
+//! // Register the driver, copying no files.
+//! installer::install_driver(
+//!     "PostgreSQL Unicode",
+//!     &[
+//!         ("Driver", "/usr/lib/x86_64-linux-gnu/odbc/psqlodbcw.so"),
+//!         ("Setup", "/usr/lib/x86_64-linux-gnu/odbc/libodbcpsqlS.so"),
+//!     ],
+//!     None,
+//! )?;
+//! // Create a system data source using it.
+//! installer::config_data_source(
+//!     DataSourceRequest::AddSystem,
+//!     "PostgreSQL Unicode",
+//!     &[("DSN", "sales"), ("Servername", "localhost"), ("Database", "sales")],
+//! )?;
+//! # Ok::<(), odbc_api::Error>(())
+//! ```
+
+use std::{
+    fmt,
+    ptr::{null, null_mut},
+};
+
+use crate::{
+    handles::{nul_terminated, SqlChar},
+    Error,
+};
+
+// Request types of `SQLInstallDriverEx`.
+const ODBC_INSTALL_COMPLETE: u16 = 2;
+
+/// Maximum length of the path returned by `SQLInstallDriverEx`, as defined by Windows.
+const MAX_PATH: usize = 260;
+
+const SQL_NO_DATA: i16 = 100;
+const SQL_SUCCESS_WITH_INFO: i16 = 1;
+
+#[cfg_attr(windows, link(name = "odbccp32"))]
+#[cfg_attr(all(not(windows), not(feature = "iodbc")), link(name = "odbcinst"))]
+#[cfg_attr(all(not(windows), feature = "iodbc"), link(name = "iodbcinst"))]
+extern "system" {
+    #[cfg_attr(not(feature = "narrow"), link_name = "SQLInstallDriverExW")]
+    #[cfg_attr(feature = "narrow", link_name = "SQLInstallDriverEx")]
+    fn sql_install_driver_ex(
+        driver: *const SqlChar,
+        path_in: *const SqlChar,
+        path_out: *mut SqlChar,
+        path_out_max: u16,
+        path_out_len: *mut u16,
+        request: u16,
+        usage_count: *mut u32,
+    ) -> i32;
+
+    #[cfg_attr(not(feature = "narrow"), link_name = "SQLRemoveDriverW")]
+    #[cfg_attr(feature = "narrow", link_name = "SQLRemoveDriver")]
+    fn sql_remove_driver(driver: *const SqlChar, remove_dsn: i32, usage_count: *mut u32) -> i32;
+
+    #[cfg_attr(not(feature = "narrow"), link_name = "SQLConfigDataSourceW")]
+    #[cfg_attr(feature = "narrow", link_name = "SQLConfigDataSource")]
+    fn sql_config_data_source(
+        parent_window: *mut std::ffi::c_void,
+        request: u16,
+        driver: *const SqlChar,
+        attributes: *const SqlChar,
+    ) -> i32;
+
+    #[cfg_attr(not(feature = "narrow"), link_name = "SQLWriteDSNToIniW")]
+    #[cfg_attr(feature = "narrow", link_name = "SQLWriteDSNToIni")]
+    fn sql_write_dsn_to_ini(dsn: *const SqlChar, driver: *const SqlChar) -> i32;
+
+    #[cfg_attr(not(feature = "narrow"), link_name = "SQLInstallerErrorW")]
+    #[cfg_attr(feature = "narrow", link_name = "SQLInstallerError")]
+    fn sql_installer_error(
+        error_index: u16,
+        error_code: *mut u32,
+        message: *mut SqlChar,
+        message_max: u16,
+        message_len: *mut u16,
+    ) -> i16;
+}
+
+/// What [`config_data_source`] should do with the data source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataSourceRequest {
+    /// Add a user data source.
+    Add = 1,
+    /// Change the attributes of an existing user data source.
+    Configure = 2,
+    /// Remove a user data source.
+    Remove = 3,
+    /// Add a system data source.
+    AddSystem = 4,
+    /// Change the attributes of an existing system data source.
+    ConfigureSystem = 5,
+    /// Remove a system data source.
+    RemoveSystem = 6,
+    /// Remove the default data source specification section from the system information.
+    RemoveDefault = 7,
+}
+
+/// A driver registered with [`install_driver`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DriverInstallation {
+    /// Directory the driver files should be copied into. Registering a driver does not copy any
+    /// files, this is left to the caller.
+    pub path: String,
+    /// Number of times the driver has been installed, including this installation.
+    pub usage_count: u32,
+}
+
+/// An error reported by `SQLInstallerError`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstallerMessage {
+    /// Installer error code, e.g. `6` for `ODBC_ERROR_COMPONENT_NOT_FOUND`.
+    pub code: u32,
+    pub message: String,
+}
+
+impl fmt::Display for InstallerMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Installer error {}: {}", self.code, self.message)
+    }
+}
+
+/// Registers the driver `name` with the driver manager, i.e. adds it to `odbcinst.ini` or the
+/// `ODBCINST.INI` registry key. `attributes` are the keywords of the driver, e.g. `Driver` and
+/// `Setup` holding the paths to its shared libraries. `path_in` is the directory the driver should
+/// be installed into. If `None`, the default directory of the driver manager is chosen. Wraps
+/// `SQLInstallDriverEx`.
+/// Fails with [`Error::InvalidArgument`] if `name` or any key or value in `attributes` contains a
+/// NUL character.
+pub fn install_driver(
+    name: &str,
+    attributes: &[(&str, &str)],
+    path_in: Option<&str>,
+) -> Result<DriverInstallation, Error> {
+    let mut driver = sz(name)?;
+    driver.extend(attribute_list(attributes)?);
+    let path_in = path_in.map(sz).transpose()?;
+    let mut path_out = vec![0; MAX_PATH];
+    let mut path_out_len = 0;
+    let mut usage_count = 0;
+    let success = unsafe {
+        sql_install_driver_ex(
+            driver.as_ptr(),
+            path_in.as_ref().map_or(null(), |path| path.as_ptr()),
+            path_out.as_mut_ptr(),
+            path_out.len() as u16,
+            &mut path_out_len,
+            ODBC_INSTALL_COMPLETE,
+            &mut usage_count,
+        )
+    };
+    check(success, "SQLInstallDriverEx")?;
+    path_out.truncate((path_out_len as usize).min(MAX_PATH));
+    Ok(DriverInstallation {
+        path: to_utf8(&path_out),
+        usage_count,
+    })
+}
+
+/// Decrements the usage count of the driver `name` and removes it from `odbcinst.ini` or the
+/// `ODBCINST.INI` registry key, once it reaches zero. If `remove_dsns` is `true`, data sources
+/// using the driver are removed as well. Returns the usage count after the removal. Wraps
+/// `SQLRemoveDriver`.
+/// Fails with [`Error::InvalidArgument`] if `name` contains a NUL character.
+pub fn remove_driver(name: &str, remove_dsns: bool) -> Result<u32, Error> {
+    let name = sz(name)?;
+    let mut usage_count = 0;
+    let success = unsafe { sql_remove_driver(name.as_ptr(), remove_dsns as i32, &mut usage_count) };
+    check(success, "SQLRemoveDriver")?;
+    Ok(usage_count)
+}
+
+/// Adds, changes or removes a data source using the setup library of `driver`, without showing any
+/// dialogs. `attributes` are the keywords of the data source, including its name as `DSN`. Wraps
+/// `SQLConfigDataSource`.
+/// Fails with [`Error::InvalidArgument`] if `driver` or any key or value in `attributes` contains a
+/// NUL character.
+pub fn config_data_source(
+    request: DataSourceRequest,
+    driver: &str,
+    attributes: &[(&str, &str)],
+) -> Result<(), Error> {
+    let driver = sz(driver)?;
+    let attributes = attribute_list(attributes)?;
+    let success = unsafe {
+        sql_config_data_source(
+            null_mut(),
+            request as u16,
+            driver.as_ptr(),
+            attributes.as_ptr(),
+        )
+    };
+    check(success, "SQLConfigDataSource")
+}
+
+/// Adds the data source `dsn` using `driver` to `odbc.ini` or the `ODBC.INI` registry key, without
+/// involving the setup library of the driver. Attributes other than the driver must be written by
+/// the caller. Wraps `SQLWriteDSNToIni`.
+/// Fails with [`Error::InvalidArgument`] if `dsn` or `driver` contains a NUL character.
+pub fn write_dsn_to_ini(dsn: &str, driver: &str) -> Result<(), Error> {
+    let dsn = sz(dsn)?;
+    let driver = sz(driver)?;
+    let success = unsafe { sql_write_dsn_to_ini(dsn.as_ptr(), driver.as_ptr()) };
+    check(success, "SQLWriteDSNToIni")
+}
+
+/// Maps the `BOOL` returned by installer functions to a result, collecting the messages of
+/// `SQLInstallerError` in case of failure.
+fn check(success: i32, function: &'static str) -> Result<(), Error> {
+    if success != 0 {
+        return Ok(());
+    }
+    let mut messages = Vec::new();
+    // The installer keeps up to eight errors.
+    for error_index in 1..=8 {
+        let mut code = 0;
+        let mut message = vec![0; 512];
+        let mut message_len = 0;
+        let ret = unsafe {
+            sql_installer_error(
+                error_index,
+                &mut code,
+                message.as_mut_ptr(),
+                message.len() as u16,
+                &mut message_len,
+            )
+        };
+        if ret == SQL_NO_DATA || ret < 0 {
+            break;
+        }
+        if ret == SQL_SUCCESS_WITH_INFO {
+            // Message has been truncated. Keep what fits.
+            message_len = message.len() as u16 - 1;
+        }
+        message.truncate(message_len as usize);
+        messages.push(InstallerMessage {
+            code,
+            message: to_utf8(&message),
+        });
+    }
+    Err(Error::Installer { function, messages })
+}
+
+/// Renders the messages of [`Error::Installer`].
+pub(crate) fn join_messages(messages: &[InstallerMessage]) -> String {
+    if messages.is_empty() {
+        return "No installer errors have been reported.".to_owned();
+    }
+    messages
+        .iter()
+        .map(InstallerMessage::to_string)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `keyword=value` pairs, each terminated by a NUL character. The list is terminated by another
+/// NUL character.
+fn attribute_list(attributes: &[(&str, &str)]) -> Result<Vec<SqlChar>, Error> {
+    let mut list = Vec::new();
+    for (key, value) in attributes {
+        list.extend(sz(&format!("{key}={value}"))?);
+    }
+    list.push(0);
+    Ok(list)
+}
+
+/// NUL terminated string in the encoding of the installer functions called.
+fn sz(text: &str) -> Result<Vec<SqlChar>, Error> {
+    nul_terminated(text).ok_or(Error::InvalidArgument {
+        description: "strings passed to the ODBC installer must not contain NUL characters",
+    })
+}
+
+/// Decodes text returned by installer functions, stopping at the first NUL character.
+fn to_utf8(text: &[SqlChar]) -> String {
+    let end = text.iter().position(|&c| c == 0).unwrap_or(text.len());
+    #[cfg(not(feature = "narrow"))]
+    let text = String::from_utf16_lossy(&text[..end]);
+    #[cfg(feature = "narrow")]
+    let text = String::from_utf8_lossy(&text[..end]).into_owned();
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Error;
+
+    use super::{attribute_list, sz};
+
+    #[test]
+    fn attribute_list_is_double_nul_terminated() {
+        let list = attribute_list(&[("DSN", "sales"), ("Database", "db")]).unwrap();
+
+        let mut expected = sz("DSN=sales").unwrap();
+        expected.extend(sz("Database=db").unwrap());
+        expected.push(0);
+        assert_eq!(expected, list);
+    }
+
+    #[test]
+    fn empty_attribute_list() {
+        assert_eq!(vec![0], attribute_list(&[]).unwrap());
+    }
+
+    #[test]
+    fn nul_character_in_attribute() {
+        let result = attribute_list(&[("DSN", "sa\0les")]);
+
+        assert!(matches!(result, Err(Error::InvalidArgument { .. })));
+    }
+}
@@ -1,7 +1,9 @@
 use crate::{
     fixed_sized::Pod,
-    parameter::{InputParameter, VarBinaryBox, VarBinarySlice, VarCharBox, VarCharSlice},
-    Nullable,
+    parameter::{
+        InputParameter, VarBinaryBox, VarBinarySlice, VarCharBox, VarCharSlice, WithDataType,
+    },
+    DataType, Nullable,
 };
 
 /// An instance can be consumed and to create a parameter which can be bound to a statement during
@@ -16,6 +18,28 @@ pub trait IntoParameter {
     type Parameter: InputParameter;
 
     fn into_parameter(self) -> Self::Parameter;
+
+    /// Converts into a parameter bound as `data_type`, rather than the default SQL type of the
+    /// parameter. The driver converts the value to `data_type`. See
+    /// [`crate::parameter::WithDataType`].
+    ///
+    /// ```no_run
+    /// use odbc_api::{Connection, DataType, Error, IntoParameter};
+    ///
+    /// fn insert_event(conn: &Connection<'_>) -> Result<(), Error> {
+    ///     // Bind the text as a timestamp, rather than as VARCHAR
+    ///     let happened_at = "2023-01-31 12:00:00.000000"
+    ///         .with_data_type(DataType::Timestamp { precision: 6 });
+    ///     conn.execute("INSERT INTO Events (happened_at) VALUES (?)", &happened_at)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    fn with_data_type(self, data_type: DataType) -> WithDataType<Self::Parameter>
+    where
+        Self: Sized,
+    {
+        WithDataType::new(self.into_parameter(), data_type)
+    }
 }
 
 impl<T> IntoParameter for T
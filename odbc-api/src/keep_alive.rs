@@ -0,0 +1,130 @@
+use std::time::{Duration, Instant};
+
+use crate::{connection::probe_statement, Connection, Error, Sleep};
+
+/// Wraps the [`Sleep`] of asynchronous execution and fetching, to keep the connection from being
+/// closed by firewalls or proxies which drop TCP sessions idle for too long. While the data source
+/// is busy computing the result of a long running query, nothing is sent over the connection, so
+/// such intermediaries may consider it idle.
+///
+/// Whenever at least `interval` has passed since the last time, polling issues a trivial probe
+/// query (e.g. `SELECT 1`) on a separate statement of the same connection. If the connection is
+/// busy, i.e. it already holds as many active statements as the data source supports (see
+/// [`Connection::max_concurrent_activities`]), the probe is skipped, since it would fail. This is
+/// always the case for data sources supporting only one active statement per connection, e.g.
+/// Microsoft SQL Server without "Multiple Active Result Sets". Failing probes do not interrupt
+/// polling. They are logged as warnings and the last one can be inspected with
+/// [`Self::take_error`].
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use odbc_api::{Connection, Error, KeepAlive};
+///
+/// async fn long_running_update(conn: &Connection<'_>) -> Result<(), Error> {
+///     let sleep = || tokio::time::sleep(Duration::from_millis(500));
+///     let keep_alive = KeepAlive::new(conn, Duration::from_secs(5 * 60), sleep)?;
+///     conn.execute_polling("UPDATE Orders SET status = 'archived'", (), keep_alive).await?;
+///     Ok(())
+/// }
+/// ```
+///
+/// To keep the connection alive while fetching in batches, pass a mutable reference to each call
+/// of [`crate::BlockCursorPolling::fetch`].
+pub struct KeepAlive<'c, S> {
+    connection: &'c Connection<'c>,
+    sleep: S,
+    interval: Duration,
+    /// Probe query, e.g. `SELECT 1`.
+    probe: &'static str,
+    /// Maximum number of active statements of the connection. `0` if there is no limit.
+    max_activities: u16,
+    last_touch: Instant,
+    num_touches: u64,
+    num_skipped: u64,
+    /// Error of the last probe which failed, and has not been taken yet.
+    error: Option<Error>,
+}
+
+impl<'c, S> KeepAlive<'c, S> {
+    /// Touches `connection` every `interval` while polling. `sleep` governs the polling intervals,
+    /// which should be considerably shorter than `interval`.
+    pub fn new(
+        connection: &'c Connection<'c>,
+        interval: Duration,
+        sleep: S,
+    ) -> Result<Self, Error> {
+        let max_activities = connection.max_concurrent_activities()?;
+        let probe = probe_statement(&connection.database_management_system_name()?);
+        Ok(Self {
+            connection,
+            sleep,
+            interval,
+            probe,
+            max_activities,
+            last_touch: Instant::now(),
+            num_touches: 0,
+            num_skipped: 0,
+            error: None,
+        })
+    }
+
+    /// Number of probes which have been executed successfully so far.
+    pub fn num_touches(&self) -> u64 {
+        self.num_touches
+    }
+
+    /// Number of probes which have been skipped so far, because the connection has been busy.
+    pub fn num_skipped(&self) -> u64 {
+        self.num_skipped
+    }
+
+    /// Error of the last probe which failed, if any. Subsequent calls return `None` until another
+    /// probe fails.
+    pub fn take_error(&mut self) -> Option<Error> {
+        self.error.take()
+    }
+
+    fn touch_if_due(&mut self) {
+        if self.last_touch.elapsed() < self.interval {
+            return;
+        }
+        self.last_touch = Instant::now();
+        // The statement being polled is active itself. Another one may exceed the limit.
+        if self.max_activities != 0
+            && self.connection.active_statement_count() >= usize::from(self.max_activities)
+        {
+            self.num_skipped += 1;
+            return;
+        }
+        match self.connection.execute(self.probe, ()) {
+            Ok(_) => self.num_touches += 1,
+            Err(error) => {
+                log::warn!("Probe keeping the connection alive failed: {error}");
+                self.error = Some(error);
+            }
+        }
+    }
+}
+
+impl<'c, S> Sleep for KeepAlive<'c, S>
+where
+    S: Sleep,
+{
+    type Poll = S::Poll;
+
+    fn next_poll(&mut self) -> Self::Poll {
+        self.touch_if_due();
+        self.sleep.next_poll()
+    }
+}
+
+impl<'a, 'c, S> Sleep for &'a mut KeepAlive<'c, S>
+where
+    S: Sleep,
+{
+    type Poll = S::Poll;
+
+    fn next_poll(&mut self) -> Self::Poll {
+        (**self).next_poll()
+    }
+}
@@ -0,0 +1,161 @@
+use crate::{
+    query_builder::{quote_identifier, quote_qualified},
+    Connection, Error, ExecuteManyReport, ParameterRow,
+};
+
+/// Deletes the rows of `table` identified by `keys`. See [`Connection::delete_by_keys`].
+pub(crate) fn delete_by_keys<R>(
+    connection: &Connection<'_>,
+    table: &str,
+    key_columns: &[&str],
+    keys: impl IntoIterator<Item = R>,
+    batch_size: usize,
+) -> Result<ExecuteManyReport, Error>
+where
+    R: ParameterRow,
+{
+    check_arity::<R>(key_columns.len(), batch_size)?;
+    let quote = connection.identifier_quote_char()?;
+    let sql = delete_sql(table, key_columns, &quote)?;
+    execute_batched(connection, &sql, keys, batch_size)
+}
+
+/// Updates the rows of `table` identified by the key values at the end of each of `rows`. See
+/// [`Connection::update_by_keys`].
+pub(crate) fn update_by_keys<R>(
+    connection: &Connection<'_>,
+    table: &str,
+    set_columns: &[&str],
+    key_columns: &[&str],
+    rows: impl IntoIterator<Item = R>,
+    batch_size: usize,
+) -> Result<ExecuteManyReport, Error>
+where
+    R: ParameterRow,
+{
+    if set_columns.is_empty() {
+        return Err(Error::InvalidArgument {
+            description: "at least one column to update is required",
+        });
+    }
+    check_arity::<R>(set_columns.len() + key_columns.len(), batch_size)?;
+    let quote = connection.identifier_quote_char()?;
+    let sql = update_sql(table, set_columns, key_columns, &quote)?;
+    execute_batched(connection, &sql, rows, batch_size)
+}
+
+/// Prepares `sql` and executes it for each of `rows`. Nothing is prepared if there are no rows.
+fn execute_batched<R>(
+    connection: &Connection<'_>,
+    sql: &str,
+    rows: impl IntoIterator<Item = R>,
+    batch_size: usize,
+) -> Result<ExecuteManyReport, Error>
+where
+    R: ParameterRow,
+{
+    let mut rows = rows.into_iter().peekable();
+    if rows.peek().is_none() {
+        return Ok(ExecuteManyReport::default());
+    }
+    let mut prepared = connection.prepare(sql)?;
+    prepared.execute_many(batch_size, rows)
+}
+
+/// Checks that each parameter row holds `num_params` values, at least one of them a key, and that
+/// batches hold at least one row. Fails with [`Error::InvalidArgument`] otherwise.
+fn check_arity<R>(num_params: usize, batch_size: usize) -> Result<(), Error>
+where
+    R: ParameterRow,
+{
+    let description = if num_params == 0 {
+        // Rows without any key columns would match every row of the table.
+        "at least one key column is required"
+    } else if num_params != R::buffer_descs(&[]).len() {
+        "the number of values in each row must match the number of columns"
+    } else if batch_size == 0 {
+        "the batch size must be at least one"
+    } else {
+        return Ok(());
+    };
+    Err(Error::InvalidArgument { description })
+}
+
+/// Condition matching a row by all of `key_columns`, e.g. `"a" = ? AND "b" = ?`.
+fn key_condition(key_columns: &[&str], quote: &str) -> Result<String, Error> {
+    Ok(assignments(key_columns, quote)?.join(" AND "))
+}
+
+/// `column = ?` for each of `columns`.
+fn assignments(columns: &[&str], quote: &str) -> Result<Vec<String>, Error> {
+    columns
+        .iter()
+        .map(|column| Ok(format!("{} = ?", quote_identifier(column, quote)?)))
+        .collect()
+}
+
+fn delete_sql(table: &str, key_columns: &[&str], quote: &str) -> Result<String, Error> {
+    Ok(format!(
+        "DELETE FROM {} WHERE {}",
+        quote_qualified(table, quote)?,
+        key_condition(key_columns, quote)?
+    ))
+}
+
+fn update_sql(
+    table: &str,
+    set_columns: &[&str],
+    key_columns: &[&str],
+    quote: &str,
+) -> Result<String, Error> {
+    Ok(format!(
+        "UPDATE {} SET {} WHERE {}",
+        quote_qualified(table, quote)?,
+        assignments(set_columns, quote)?.join(", "),
+        key_condition(key_columns, quote)?
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Error;
+
+    use super::{check_arity, delete_sql, update_sql};
+
+    #[test]
+    fn statements_match_by_all_key_columns() {
+        assert_eq!(
+            r#"DELETE FROM "t" WHERE "a" = ? AND "b" = ?"#,
+            delete_sql("t", &["a", "b"], "\"").unwrap()
+        );
+        assert_eq!(
+            "UPDATE `t` SET `c` = ?, `d` = ? WHERE `a` = ?",
+            update_sql("t", &["c", "d"], &["a"], "`").unwrap()
+        );
+    }
+
+    #[test]
+    fn quote_parts_of_qualified_table_names() {
+        assert_eq!(
+            r#"DELETE FROM "dbo"."t" WHERE "a" = ?"#,
+            delete_sql("dbo.t", &["a"], "\"").unwrap()
+        );
+    }
+
+    #[test]
+    fn rows_must_match_key_columns() {
+        assert!(matches!(
+            check_arity::<(i32, i32)>(1, 100),
+            Err(Error::InvalidArgument { .. })
+        ));
+        assert!(matches!(
+            check_arity::<(i32,)>(0, 100),
+            Err(Error::InvalidArgument { .. })
+        ));
+        assert!(matches!(
+            check_arity::<(i32,)>(1, 0),
+            Err(Error::InvalidArgument { .. })
+        ));
+        assert!(check_arity::<(i32,)>(1, 100).is_ok());
+    }
+}
@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+/// Round trip times to the data source, measured by [`crate::Connection::measure_latency`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Latency {
+    /// Fastest round trip.
+    pub min: Duration,
+    /// Median of the round trips. For an even number of samples, the mean of the two middle ones.
+    pub median: Duration,
+    /// Slowest round trip.
+    pub max: Duration,
+}
+
+impl Latency {
+    /// Summarizes the round trip times in `samples`. `None` if `samples` is empty.
+    pub fn from_samples(samples: &[Duration]) -> Option<Self> {
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+        let (&min, &max) = (sorted.first()?, sorted.last()?);
+        // Both indices point to the middle sample, if the number of samples is odd.
+        let median = (sorted[(sorted.len() - 1) / 2] + sorted[sorted.len() / 2]) / 2;
+        Some(Self { min, median, max })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Latency;
+
+    #[test]
+    fn summarize_odd_and_even_number_of_samples() {
+        let ms = Duration::from_millis;
+
+        let odd = Latency::from_samples(&[ms(5), ms(1), ms(3)]).unwrap();
+        let even = Latency::from_samples(&[ms(4), ms(1), ms(2), ms(8)]).unwrap();
+
+        assert_eq!(
+            Latency {
+                min: ms(1),
+                median: ms(3),
+                max: ms(5)
+            },
+            odd
+        );
+        assert_eq!(ms(3), even.median);
+        assert_eq!(None, Latency::from_samples(&[]));
+    }
+}
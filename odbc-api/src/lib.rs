@@ -3,49 +3,171 @@
 //! `odbc-api` enables you to write applications which utilize ODBC (Open Database Connectivity)
 //! standard to access databases. See the [`guide`] for more information and code
 //! examples.
+//!
+//! # Panics
+//!
+//! Panics are reserved for violations of preconditions by the calling code, which are documented
+//! in a `# Panics` section of the function in question, e.g. indexing a buffer out of bounds. Values
+//! reported by drivers, data sources or the file system never cause a panic. They surface as an
+//! [`Error`] instead, e.g. [`Error::InvalidDriverValue`]. Errors in `Drop` implementations, e.g.
+//! failing to close a cursor or to disconnect, are logged, since there is no one to return them
+//! to. Where a precondition may depend on data received at runtime, a fallible alternative is
+//! offered, like [`parameter::VarCell::try_from_buffer`] or [`buffers::Indicator::try_from_isize`].
+//! This allows server applications to handle any failure of the ODBC layer within the worker it
+//! occurs in.
 
+mod adaptive_block_cursor;
+mod bulk_load;
+mod cancellation;
+mod change_capture;
+mod client_identity;
+mod column_groups;
 mod columnar_bulk_inserter;
 mod connection;
+mod connection_observer;
+mod connection_worker;
 mod cursor;
+mod cursor_statistics;
+mod decimal;
+mod deduplicating_cursor;
 mod driver_complete_option;
 mod environment;
+#[cfg(any(feature = "avro", feature = "arrow"))]
+mod epoch;
 mod error;
 mod execute;
+mod execute_many;
+mod failover;
+mod fetch_plan;
+mod file_dsn;
 mod fixed_sized;
+mod generated_keys;
+mod generic;
+mod grouped_rows;
+mod in_list;
+mod incremental;
 mod into_parameter;
+mod keep_alive;
+mod keyed_dml;
+mod latency;
+mod no_total;
 mod nullable;
+mod pagination;
 mod parameter_collection;
+mod parameter_types;
+mod peekable_cursor;
 mod preallocated;
 mod prepared;
+mod query_builder;
+mod query_plan;
+mod quirks;
+mod re_prepare;
 mod result_set_metadata;
+mod result_set_schema;
+mod row_mapper;
+mod script;
+mod serialized_connection;
 mod sleep;
+mod spooling_cursor;
+mod sql_template;
 mod statement_connection;
+mod statement_kind;
+mod statement_options;
+mod table_valued_param;
+mod text_lengths;
+mod utf16;
 
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "avro")]
+pub mod avro;
 pub mod buffers;
+pub mod delimited;
+pub mod diagnostics;
+#[cfg(feature = "encoding")]
+pub mod encoding;
 pub mod guide;
 pub mod handles;
+#[cfg(feature = "installer")]
+pub mod installer;
+#[cfg(feature = "mock")]
+pub mod mock;
+#[cfg(feature = "mssql-bcp")]
+pub mod mssql_bcp;
+#[cfg(feature = "ndjson")]
+pub mod ndjson;
+pub mod odbc_config;
+#[cfg(feature = "rayon")]
+pub mod parallel;
 pub mod parameter;
+pub mod render;
+#[cfg(feature = "test_support")]
+pub mod test_support;
+pub mod typestate;
+pub mod vendor;
 
 pub use self::{
+    adaptive_block_cursor::{AdaptiveBatchSize, AdaptiveBlockCursor},
+    bulk_load::{BulkLoadReport, BulkLoadStrategy},
+    cancellation::CancellationRegistry,
+    change_capture::{ChangeCapture, ChangeKind, ChangedRow, Changes},
+    client_identity::ClientIdentity,
+    column_groups::{ColumnGroupView, ColumnGroups, GroupedBatch},
     columnar_bulk_inserter::{BoundInputSlice, ColumnarBulkInserter},
     connection::{escape_attribute_value, Connection},
+    connection_observer::{ConnectionEvent, ConnectionObserver},
+    connection_worker::{ConnectionWorker, WorkerCursor},
     cursor::{
-        BlockCursor, BlockCursorPolling, Cursor, CursorImpl, CursorPolling, CursorRow, RowSetBuffer,
+        BlockCursor, BlockCursorPolling, Cursor, CursorImpl, CursorPolling, CursorRow,
+        RowSetBuffer, TruncationPolicy,
     },
+    cursor_statistics::CursorStatistics,
+    decimal::{canonical_decimal, decimal_to_scaled_integer, DecimalTextError},
+    deduplicating_cursor::{DeduplicatingCursor, DistinctRows},
     driver_complete_option::DriverCompleteOption,
-    environment::{DataSourceInfo, DriverInfo, Environment},
+    environment::{DataSourceInfo, DriverInfo, Environment, ShutdownReport},
     error::{Error, TooLargeBufferSize},
+    execute_many::{ArrayElement, BatchError, ExecuteManyReport, ParameterRow},
+    failover::{ConnectionTarget, FailoverOptions, ReplicaSelection, ReplicaSet},
+    fetch_plan::{FetchPlan, FetchPlanViolation, PlannedColumn, TargetType},
     fixed_sized::Bit,
-    handles::{ColumnDescription, DataType, Nullability},
+    generated_keys::KeyRetrieval,
+    generic::{GenericConnection, GenericCursor, GenericPrepared},
+    grouped_rows::GroupedRows,
+    handles::{ColumnDescription, Concurrency, CursorType, DataType, Nullability},
+    in_list::{InListBlockCursor, InListCursor},
+    incremental::{
+        FileWatermarkStore, InMemoryWatermarkStore, IncrementalExtraction, WatermarkStore,
+    },
     into_parameter::IntoParameter,
+    keep_alive::KeepAlive,
+    latency::Latency,
+    no_total::{NoTotalPolicy, VariadicFetch},
     nullable::Nullable,
+    pagination::Pages,
     parameter::{InOut, Out, OutputParameter},
     parameter_collection::{ParameterCollection, ParameterCollectionRef, ParameterTupleElement},
+    peekable_cursor::{BufferedRow, PeekableCursor},
     preallocated::{Preallocated, PreallocatedPolling},
     prepared::Prepared,
+    query_builder::Select,
+    query_plan::{PlanFormat, QueryPlan},
+    quirks::Quirks,
+    re_prepare::RePreparePolicy,
     result_set_metadata::ResultSetMetadata,
+    result_set_schema::{ColumnSchema, ResultSetSchema, SchemaChange},
+    row_mapper::{ColumnValue, RowMapper},
+    script::ScriptReport,
+    serialized_connection::{AsyncMode, SerializedConnection, ThreadingCapability},
     sleep::Sleep,
+    spooling_cursor::{SpooledBatch, SpooledBatches, SpoolingCursor},
+    sql_template::{SqlTemplate, TemplateValue},
     statement_connection::StatementConnection,
+    statement_kind::{classify_statement, StatementKind},
+    statement_options::StatementOptions,
+    table_valued_param::TableValuedParam,
+    text_lengths::TextLengths,
+    utf16::{trim_split_surrogate, Utf16ChunkJoiner},
 };
 // Reexports
 pub use force_send_sync;
@@ -0,0 +1,510 @@
+//! In memory fakes implementing [`GenericConnection`], [`GenericPrepared`] and
+//! [`GenericCursor`]. They serve canned result sets and allow unit testing data access code
+//! without an ODBC driver. Requires the `mock` feature.
+//!
+//! ```
+//! use odbc_api::{mock::{MockConnection, MockResultSet}, GenericConnection};
+//!
+//! let query = "SELECT name, year FROM Birthdays";
+//! let conn = MockConnection::new().with_result_set(
+//!     query,
+//!     MockResultSet::new(["name", "year"])
+//!         .row([Some("Peter"), Some("1980")])
+//!         .row([Some("Anna"), None]),
+//! );
+//!
+//! let mut cursor = conn.execute(query, &[])?.unwrap();
+//! let row = cursor.next_text_row()?.unwrap();
+//! assert_eq!([Some("Peter".to_string()), Some("1980".to_string())], row.as_slice());
+//! let row = cursor.next_text_row()?.unwrap();
+//! assert_eq!([Some("Anna".to_string()), None], row.as_slice());
+//! assert_eq!(None, cursor.next_text_row()?);
+//! assert_eq!([query], conn.executed_queries().as_slice());
+//! # Ok::<(), odbc_api::Error>(())
+//! ```
+//!
+//! Rather than writing result sets by hand, they can be recorded from a real data source with
+//! [`MockResultSet::record`] and saved to a file. This captures the types reported by the driver
+//! as well, see [`GenericCursor::result_set_schema`].
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    io::{self, Read, Write},
+    rc::Rc,
+};
+
+use crate::{
+    buffers::TextRowSet,
+    parameter::InputParameter,
+    spooling_cursor::{read_u64, NULL},
+    sys::{Nullability as SysNullability, SqlDataType},
+    ColumnSchema, Cursor, DataType, Error, GenericConnection, GenericCursor, GenericPrepared,
+    Nullability, ResultSetSchema,
+};
+
+/// Identifies files written by [`MockResultSet::save`], followed by the version of the format.
+const MAGIC: &[u8; 8] = b"ODBCRS\0\x01";
+
+/// A canned result set, served by [`MockConnection`] and [`MockPrepared`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MockResultSet {
+    columns: Vec<ColumnSchema>,
+    rows: Vec<Vec<Option<String>>>,
+}
+
+impl MockResultSet {
+    /// An empty result set with the specified column names. Types and nullability of the columns
+    /// are unknown.
+    pub fn new<S: Into<String>>(column_names: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            columns: column_names
+                .into_iter()
+                .map(|name| ColumnSchema::new(name, DataType::Unknown, Nullability::Unknown))
+                .collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Fetches all rows of `cursor` as text in batches of `batch_size` rows, together with the
+    /// schema of the result set as reported by the driver. `max_str_len` limits the size of the
+    /// text buffers, see [`TextRowSet::for_cursor`]. Fails with [`Error::TooLargeValueForBuffer`]
+    /// if a value exceeds `max_str_len`, and with [`Error::InvalidDriverText`] if a value is not
+    /// valid UTF-8, rather than recording altered values. Save the result with [`Self::save`], in
+    /// order to replay it in tests without a database.
+    ///
+    /// ```no_run
+    /// use std::{fs::File, io::BufWriter};
+    /// use odbc_api::{mock::MockResultSet, Connection};
+    ///
+    /// fn snapshot_birthdays(conn: &Connection<'_>) -> Result<(), Box<dyn std::error::Error>> {
+    ///     let cursor = conn.execute("SELECT name, year FROM Birthdays", ())?.unwrap();
+    ///     let recorded = MockResultSet::record(cursor, 1000, Some(4096))?;
+    ///     recorded.save(BufWriter::new(File::create("tests/snapshots/birthdays.odbcrs")?))?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn record(
+        mut cursor: impl Cursor,
+        batch_size: usize,
+        max_str_len: Option<usize>,
+    ) -> Result<Self, Error> {
+        let schema = ResultSetSchema::for_cursor(&mut cursor)?;
+        let buffer = TextRowSet::for_cursor(batch_size, &mut cursor, max_str_len)?;
+        let mut block_cursor = cursor.bind_buffer(buffer)?;
+        let mut rows = Vec::new();
+        while let Some(batch) = block_cursor.fetch_with_truncation_check(true)? {
+            for row_index in 0..batch.num_rows() {
+                let row = (0..batch.num_cols())
+                    .map(|col_index| {
+                        batch
+                            .at(col_index, row_index)
+                            .map(|value| {
+                                String::from_utf8(value.to_vec()).map_err(|_| {
+                                    Error::InvalidDriverText {
+                                        description: "a value",
+                                    }
+                                })
+                            })
+                            .transpose()
+                    })
+                    .collect::<Result<_, _>>()?;
+                rows.push(row);
+            }
+        }
+        Ok(Self {
+            columns: schema.columns,
+            rows,
+        })
+    }
+
+    /// Writes the result set, including its schema, in a compact binary format private to this
+    /// crate. Read it again with [`Self::load`].
+    pub fn save(&self, mut writer: impl Write) -> Result<(), Error> {
+        self.write(&mut writer)
+            .and_then(|()| writer.flush())
+            .map_err(Error::RecordedResultSet)
+    }
+
+    /// Reads a result set written by [`Self::save`].
+    ///
+    /// ```no_run
+    /// use std::fs::File;
+    /// use odbc_api::mock::{MockConnection, MockResultSet};
+    ///
+    /// let recorded = MockResultSet::load(File::open("tests/snapshots/birthdays.odbcrs")?)?;
+    /// let conn = MockConnection::new()
+    ///     .with_result_set("SELECT name, year FROM Birthdays", recorded);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn load(mut reader: impl Read) -> Result<Self, Error> {
+        Self::read(&mut reader).map_err(Error::RecordedResultSet)
+    }
+
+    /// Names, types and nullability of the columns.
+    pub fn schema(&self) -> ResultSetSchema {
+        ResultSetSchema {
+            columns: self.columns.clone(),
+        }
+    }
+
+    fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&(self.columns.len() as u64).to_le_bytes())?;
+        for column in &self.columns {
+            write_value(writer, Some(&column.name))?;
+            let data_type = &column.data_type;
+            writer.write_all(&data_type.data_type().0.to_le_bytes())?;
+            writer.write_all(&(data_type.column_size() as u64).to_le_bytes())?;
+            writer.write_all(&data_type.decimal_digits().to_le_bytes())?;
+            let nullability: i16 = match column.nullability {
+                Nullability::NoNulls => 0,
+                Nullability::Nullable => 1,
+                Nullability::Unknown => 2,
+            };
+            writer.write_all(&nullability.to_le_bytes())?;
+        }
+        writer.write_all(&(self.rows.len() as u64).to_le_bytes())?;
+        for field in self.rows.iter().flatten() {
+            write_value(writer, field.as_deref())?;
+        }
+        Ok(())
+    }
+
+    fn read(reader: &mut impl Read) -> io::Result<Self> {
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("Not a recorded result set."));
+        }
+        let num_cols = read_u64(reader)?;
+        let mut columns = Vec::new();
+        for _ in 0..num_cols {
+            let name = read_value(reader)?.ok_or_else(|| invalid_data("Column without name."))?;
+            let sql_type = SqlDataType(read_i16(reader)?);
+            let column_size = read_u64(reader)? as usize;
+            let decimal_digits = read_i16(reader)?;
+            let nullability = match read_i16(reader)? {
+                nullability @ 0..=2 => Nullability::new(SysNullability(nullability)),
+                _ => return Err(invalid_data("Invalid nullability.")),
+            };
+            let data_type = DataType::new(sql_type, column_size, decimal_digits);
+            columns.push(ColumnSchema::new(name, data_type, nullability));
+        }
+        let num_rows = read_u64(reader)?;
+        let mut rows = Vec::new();
+        for _ in 0..num_rows {
+            let row = columns
+                .iter()
+                .map(|_| read_value(reader))
+                .collect::<io::Result<_>>()?;
+            rows.push(row);
+        }
+        Ok(Self { columns, rows })
+    }
+
+    /// Appends a row to the result set. `None` represents `NULL`.
+    ///
+    /// # Panics
+    ///
+    /// If the number of fields does not match the number of columns.
+    pub fn row<S: Into<String>>(mut self, fields: impl IntoIterator<Item = Option<S>>) -> Self {
+        let row: Vec<_> = fields
+            .into_iter()
+            .map(|field| field.map(Into::into))
+            .collect();
+        assert_eq!(
+            self.columns.len(),
+            row.len(),
+            "Number of fields in row must match number of columns."
+        );
+        self.rows.push(row);
+        self
+    }
+}
+
+/// Fake connection serving canned result sets for registered queries. Queries without registered
+/// result set behave like statements which do not produce one, e.g. an `INSERT`. Keeps track of
+/// all executed and prepared statements, so tests can make assertions about them.
+#[derive(Debug, Default)]
+pub struct MockConnection {
+    result_sets: HashMap<String, MockResultSet>,
+    executed: Rc<RefCell<Vec<String>>>,
+    autocommit: RefCell<bool>,
+    commits: RefCell<usize>,
+    rollbacks: RefCell<usize>,
+}
+
+impl MockConnection {
+    /// A mock connection without any registered result sets. Autocommit is enabled, just like for
+    /// a real connection.
+    pub fn new() -> Self {
+        Self {
+            autocommit: RefCell::new(true),
+            ..Self::default()
+        }
+    }
+
+    /// Register `result_set` to be returned each time `query` is executed. `query` must match the
+    /// statement text exactly.
+    pub fn with_result_set(mut self, query: impl Into<String>, result_set: MockResultSet) -> Self {
+        self.result_sets.insert(query.into(), result_set);
+        self
+    }
+
+    /// Statement texts in the order they have been executed. Executions of prepared statements
+    /// are reported with the text they have been prepared with.
+    pub fn executed_queries(&self) -> Vec<String> {
+        self.executed.borrow().clone()
+    }
+
+    /// Current transaction mode, as set by [`GenericConnection::set_autocommit`].
+    pub fn is_autocommit(&self) -> bool {
+        *self.autocommit.borrow()
+    }
+
+    /// Number of calls to [`GenericConnection::commit`].
+    pub fn num_commits(&self) -> usize {
+        *self.commits.borrow()
+    }
+
+    /// Number of calls to [`GenericConnection::rollback`].
+    pub fn num_rollbacks(&self) -> usize {
+        *self.rollbacks.borrow()
+    }
+}
+
+impl GenericConnection for MockConnection {
+    fn execute(
+        &self,
+        query: &str,
+        _params: &[Box<dyn InputParameter>],
+    ) -> Result<Option<Box<dyn GenericCursor + '_>>, Error> {
+        self.executed.borrow_mut().push(query.to_owned());
+        Ok(self
+            .result_sets
+            .get(query)
+            .map(|rs| Box::new(MockCursor::new(rs.clone())) as Box<dyn GenericCursor>))
+    }
+
+    fn prepare(&self, query: &str) -> Result<Box<dyn GenericPrepared + '_>, Error> {
+        Ok(Box::new(MockPrepared {
+            query: query.to_owned(),
+            result_set: self.result_sets.get(query).cloned(),
+            executed: self.executed.clone(),
+        }))
+    }
+
+    fn set_autocommit(&self, enabled: bool) -> Result<(), Error> {
+        *self.autocommit.borrow_mut() = enabled;
+        Ok(())
+    }
+
+    fn commit(&self) -> Result<(), Error> {
+        *self.commits.borrow_mut() += 1;
+        Ok(())
+    }
+
+    fn rollback(&self) -> Result<(), Error> {
+        *self.rollbacks.borrow_mut() += 1;
+        Ok(())
+    }
+}
+
+/// Fake prepared statement created by [`MockConnection`].
+#[derive(Debug)]
+pub struct MockPrepared {
+    query: String,
+    result_set: Option<MockResultSet>,
+    executed: Rc<RefCell<Vec<String>>>,
+}
+
+impl GenericPrepared for MockPrepared {
+    fn execute(
+        &mut self,
+        _params: &[Box<dyn InputParameter>],
+    ) -> Result<Option<Box<dyn GenericCursor + '_>>, Error> {
+        self.executed.borrow_mut().push(self.query.clone());
+        Ok(self
+            .result_set
+            .clone()
+            .map(|rs| Box::new(MockCursor::new(rs)) as Box<dyn GenericCursor>))
+    }
+}
+
+/// Fake cursor iterating over the rows of a [`MockResultSet`].
+#[derive(Debug)]
+pub struct MockCursor {
+    columns: Vec<ColumnSchema>,
+    rows: std::vec::IntoIter<Vec<Option<String>>>,
+}
+
+impl MockCursor {
+    /// A cursor positioned before the first row of `result_set`.
+    pub fn new(result_set: MockResultSet) -> Self {
+        Self {
+            columns: result_set.columns,
+            rows: result_set.rows.into_iter(),
+        }
+    }
+}
+
+impl GenericCursor for MockCursor {
+    fn column_names(&mut self) -> Result<Vec<String>, Error> {
+        Ok(self
+            .columns
+            .iter()
+            .map(|column| column.name.clone())
+            .collect())
+    }
+
+    fn result_set_schema(&mut self) -> Result<ResultSetSchema, Error> {
+        Ok(ResultSetSchema {
+            columns: self.columns.clone(),
+        })
+    }
+
+    fn next_text_row(&mut self) -> Result<Option<Vec<Option<String>>>, Error> {
+        Ok(self.rows.next())
+    }
+}
+
+/// Writes a length prefixed value. `None` is written as [`NULL`].
+fn write_value(writer: &mut impl Write, value: Option<&str>) -> io::Result<()> {
+    match value {
+        Some(value) => {
+            writer.write_all(&(value.len() as u64).to_le_bytes())?;
+            writer.write_all(value.as_bytes())
+        }
+        None => writer.write_all(&NULL.to_le_bytes()),
+    }
+}
+
+fn read_value(reader: &mut impl Read) -> io::Result<Option<String>> {
+    let len = read_u64(reader)?;
+    if len == NULL {
+        return Ok(None);
+    }
+    // Do not trust the length for allocating, in case the file is corrupted.
+    let mut value = Vec::new();
+    reader.take(len).read_to_end(&mut value)?;
+    if value.len() as u64 != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    String::from_utf8(value)
+        .map(Some)
+        .map_err(|_| invalid_data("Value is not valid UTF-8."))
+}
+
+fn read_i16(reader: &mut impl Read) -> io::Result<i16> {
+    let mut bytes = [0; 2];
+    reader.read_exact(&mut bytes)?;
+    Ok(i16::from_le_bytes(bytes))
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parameter::InputParameter, ColumnSchema, DataType, Error, GenericConnection, GenericCursor,
+        Nullability,
+    };
+
+    use super::{MockConnection, MockResultSet};
+
+    #[test]
+    fn prepared_statement_serves_result_set_on_each_execution() {
+        let conn = MockConnection::new().with_result_set(
+            "SELECT a FROM t WHERE b=?",
+            MockResultSet::new(["a"]).row([Some("1")]),
+        );
+        let params: [Box<dyn InputParameter>; 1] = [Box::new(42i32)];
+
+        let mut prepared = conn.prepare("SELECT a FROM t WHERE b=?").unwrap();
+        for _ in 0..2 {
+            let mut cursor = prepared.execute(&params).unwrap().unwrap();
+            assert_eq!(vec!["a".to_string()], cursor.column_names().unwrap());
+            assert_eq!(
+                Some(vec![Some("1".to_string())]),
+                cursor.next_text_row().unwrap()
+            );
+            assert_eq!(None, cursor.next_text_row().unwrap());
+        }
+        drop(prepared);
+
+        assert_eq!(2, conn.executed_queries().len());
+    }
+
+    #[test]
+    fn unregistered_query_yields_no_cursor() {
+        let conn = MockConnection::new();
+        conn.set_autocommit(false).unwrap();
+
+        let cursor = conn.execute("INSERT INTO t (a) VALUES (1)", &[]).unwrap();
+        conn.commit().unwrap();
+
+        assert!(cursor.is_none());
+        assert!(!conn.is_autocommit());
+        assert_eq!(1, conn.num_commits());
+    }
+
+    #[test]
+    fn save_and_load_recorded_result_set() {
+        let mut recorded = MockResultSet::new(["name", "year"])
+            .row([Some("Peter"), Some("1980")])
+            .row([Some("Anna"), None]);
+        recorded.columns[0] = ColumnSchema::new(
+            "name",
+            DataType::Varchar { length: 255 },
+            Nullability::NoNulls,
+        );
+        recorded.columns[1] = ColumnSchema::new(
+            "year",
+            DataType::Numeric {
+                precision: 4,
+                scale: 0,
+            },
+            Nullability::Nullable,
+        );
+        let mut file = Vec::new();
+
+        recorded.save(&mut file).unwrap();
+        let loaded = MockResultSet::load(file.as_slice()).unwrap();
+
+        assert_eq!(recorded, loaded);
+    }
+
+    #[test]
+    fn reject_truncated_recording() {
+        let mut file = Vec::new();
+        MockResultSet::new(["a"])
+            .row([Some("value")])
+            .save(&mut file)
+            .unwrap();
+
+        let result = MockResultSet::load(&file[..file.len() - 1]);
+
+        assert!(matches!(result, Err(Error::RecordedResultSet(_))));
+    }
+
+    #[test]
+    fn schema_of_cursor_without_types() {
+        struct NamesOnly;
+        impl GenericCursor for NamesOnly {
+            fn column_names(&mut self) -> Result<Vec<String>, Error> {
+                Ok(vec!["a".to_string()])
+            }
+
+            fn next_text_row(&mut self) -> Result<Option<Vec<Option<String>>>, Error> {
+                Ok(None)
+            }
+        }
+
+        let schema = NamesOnly.result_set_schema().unwrap();
+
+        assert_eq!(MockResultSet::new(["a"]).schema(), schema);
+    }
+}
@@ -0,0 +1,391 @@
+//! Bulk loads rows into Microsoft SQL Server, using the bulk copy program (BCP) API of the Microsoft
+//! ODBC Driver for SQL Server. Requires the `mssql-bcp` feature.
+//!
+//! BCP streams rows to the server using its native bulk load protocol, which is considerably faster
+//! than inserting arrays of parameters. The BCP functions are exported by the driver itself, rather
+//! than the driver manager, so the application has to link against the driver library, e.g. by
+//! emitting `cargo:rustc-link-lib=msodbcsql18` from its build script. The connection must have been
+//! opened with [`crate::Environment::connect_with_bulk_copy`].
+//!
+//! ```no_run
+//! use odbc_api::{
+//!     mssql_bcp::{BcpColumn, BcpValue, BulkCopy},
+//!     Environment,
+//! };
+//!
+//! let env = Environment::new()?;
+//! let conn = env.connect_with_bulk_copy(
+//!     "Driver={ODBC Driver 18 for SQL Server};Server=localhost;UID=SA;PWD=<password>;",
+//! )?;
+//! let mut bcp = BulkCopy::new(
+//!     &conn,
+//!     "dbo.Birthdays",
+//!     &[BcpColumn::Text { max_str_len: 255 }, BcpColumn::I32],
+//! )?
+//! .with_batch_size(10_000);
+//! for (name, year) in [("Hamilton", 1985), ("Vettel", 1987)] {
+//!     bcp.send_row(&[BcpValue::Text(name), BcpValue::I32(year)])?;
+//! }
+//! let num_rows = bcp.finish()?;
+//! # Ok::<(), odbc_api::Error>(())
+//! ```
+
+use std::{mem::size_of, ptr::null};
+
+use odbc_sys::HDbc;
+
+use crate::{
+    handles::{self, nul_terminated, SqlChar, SqlResult},
+    Connection, Error,
+};
+
+/// `DB_IN`. Direction of a bulk copy into a table.
+const DB_IN: i32 = 1;
+
+/// `FAIL`. Returned by the BCP functions in case of an error.
+const FAIL: i16 = 0;
+
+/// `SQL_NULL_DATA`. Length passed to `bcp_collen` for `NULL` values.
+const NULL_DATA: i32 = -1;
+
+// Program variable types of `bcp_bind`.
+const SQLINT4: i32 = 0x38;
+const SQLINT8: i32 = 0x7f;
+const SQLFLT8: i32 = 0x3e;
+const SQLNCHAR: i32 = 0xef;
+const SQLBIGVARBINARY: i32 = 0xa5;
+
+extern "system" {
+    #[cfg_attr(not(feature = "narrow"), link_name = "bcp_initW")]
+    #[cfg_attr(feature = "narrow", link_name = "bcp_initA")]
+    fn bcp_init(
+        hdbc: HDbc,
+        table: *const SqlChar,
+        data_file: *const SqlChar,
+        error_file: *const SqlChar,
+        direction: i32,
+    ) -> i16;
+
+    fn bcp_bind(
+        hdbc: HDbc,
+        data: *const u8,
+        indicator_len: i32,
+        data_len: i32,
+        terminator: *const u8,
+        terminator_len: i32,
+        data_type: i32,
+        server_column: i32,
+    ) -> i16;
+
+    fn bcp_collen(hdbc: HDbc, data_len: i32, server_column: i32) -> i16;
+
+    fn bcp_sendrow(hdbc: HDbc) -> i16;
+
+    fn bcp_batch(hdbc: HDbc) -> i32;
+
+    fn bcp_done(hdbc: HDbc) -> i32;
+}
+
+/// Type of a column bound to a [`BulkCopy`]. The server converts the values to the type of the
+/// column in the target table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BcpColumn {
+    /// 32 Bit integers, sent as [`BcpValue::I32`].
+    I32,
+    /// 64 Bit integers, sent as [`BcpValue::I64`].
+    I64,
+    /// Double precision floating points, sent as [`BcpValue::F64`].
+    F64,
+    /// Text holding up to `max_str_len` UTF-16 code units, sent as [`BcpValue::Text`].
+    Text { max_str_len: usize },
+    /// Binary values of up to `max_len` bytes, sent as [`BcpValue::Binary`].
+    Binary { max_len: usize },
+}
+
+impl BcpColumn {
+    /// Program variable type and size of the buffer in bytes.
+    fn bind_type_and_len(self) -> (i32, usize) {
+        match self {
+            BcpColumn::I32 => (SQLINT4, size_of::<i32>()),
+            BcpColumn::I64 => (SQLINT8, size_of::<i64>()),
+            BcpColumn::F64 => (SQLFLT8, size_of::<f64>()),
+            BcpColumn::Text { max_str_len } => (SQLNCHAR, max_str_len * size_of::<u16>()),
+            BcpColumn::Binary { max_len } => (SQLBIGVARBINARY, max_len),
+        }
+    }
+}
+
+/// A value of a row sent with [`BulkCopy::send_row`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BcpValue<'a> {
+    Null,
+    I32(i32),
+    I64(i64),
+    F64(f64),
+    Text(&'a str),
+    Binary(&'a [u8]),
+}
+
+/// An ongoing bulk copy into a table. Rows are sent one at a time, yet transferred to the server in
+/// batches by the driver. Each column is bound to a buffer owned by the bulk copy, which holds the
+/// value of the current row.
+///
+/// Until it is finished, no other statements can be executed on the connection. Dropping the bulk
+/// copy without calling [`Self::finish`] ends it as well, yet any error is ignored.
+pub struct BulkCopy<'c> {
+    connection: &'c handles::Connection<'c>,
+    columns: Vec<BoundColumn>,
+    /// Number of rows after which the rows sent so far are committed. `0` commits all rows at once,
+    /// once the bulk copy is finished.
+    batch_size: usize,
+    num_pending: usize,
+    num_committed: usize,
+    done: bool,
+}
+
+struct BoundColumn {
+    kind: BcpColumn,
+    /// Aligned for the largest fixed size value. Never reallocated, since the driver holds a
+    /// pointer to it.
+    buffer: Box<[u64]>,
+}
+
+impl BoundColumn {
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        let len = self.buffer.len() * size_of::<u64>();
+        // Safe: Any bit pattern is a valid `u8` and the slice borrows the buffer mutably.
+        unsafe { std::slice::from_raw_parts_mut(self.buffer.as_mut_ptr() as *mut u8, len) }
+    }
+}
+
+impl<'c> BulkCopy<'c> {
+    /// Starts a bulk copy into `table`, sending the values of `columns` to the columns of the table
+    /// with the same position. Wraps `bcp_init` and `bcp_bind`.
+    pub fn new(
+        connection: &'c Connection<'_>,
+        table: &str,
+        columns: &[BcpColumn],
+    ) -> Result<Self, Error> {
+        let handle = connection.as_handle();
+        let table = nul_terminated(table).ok_or(Error::InvalidArgument {
+            description: "table names passed to bcp_init must not contain NUL characters",
+        })?;
+        let ret = unsafe { bcp_init(handle.as_sys(), table.as_ptr(), null(), null(), DB_IN) };
+        check(handle, ret, "bcp_init")?;
+        let mut bcp = BulkCopy {
+            connection: handle,
+            columns: Vec::with_capacity(columns.len()),
+            batch_size: 0,
+            num_pending: 0,
+            num_committed: 0,
+            done: false,
+        };
+        for (index, &kind) in columns.iter().enumerate() {
+            let (data_type, len) = kind.bind_type_and_len();
+            let mut column = BoundColumn {
+                kind,
+                buffer: vec![0; len.div_ceil(size_of::<u64>())].into_boxed_slice(),
+            };
+            let ret = unsafe {
+                bcp_bind(
+                    handle.as_sys(),
+                    column.bytes_mut().as_ptr(),
+                    0,
+                    len.try_into().unwrap_or(i32::MAX),
+                    null(),
+                    0,
+                    data_type,
+                    index as i32 + 1,
+                )
+            };
+            check(handle, ret, "bcp_bind")?;
+            bcp.columns.push(column);
+        }
+        Ok(bcp)
+    }
+
+    /// Commits the rows sent so far, every `batch_size` rows, using `bcp_batch`. This bounds the
+    /// size of the transaction log, yet rows of already committed batches stay in the table, if
+    /// the bulk copy fails later on. `0` (the default) commits all rows at once, once the bulk copy
+    /// is finished.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Copies `values` into the bound buffers and sends them to the server. Wraps `bcp_collen` and
+    /// `bcp_sendrow`. Fails with [`Error::ValueTooLarge`] if a value exceeds the maximum length of
+    /// its column, and with [`Error::InvalidArgument`] if the number of values does not match the
+    /// number of columns, or a value does not match the type of its column. Nothing is sent in
+    /// either case.
+    pub fn send_row(&mut self, values: &[BcpValue<'_>]) -> Result<(), Error> {
+        if self.columns.len() != values.len() {
+            return Err(Error::InvalidArgument {
+                description: "number of values in a row must match the number of columns",
+            });
+        }
+        let handle = self.connection;
+        for (index, (column, value)) in self.columns.iter_mut().zip(values).enumerate() {
+            let len = column.set_value(index as u16 + 1, value)?;
+            let ret = unsafe { bcp_collen(handle.as_sys(), len, index as i32 + 1) };
+            check(handle, ret, "bcp_collen")?;
+        }
+        let ret = unsafe { bcp_sendrow(handle.as_sys()) };
+        check(handle, ret, "bcp_sendrow")?;
+        self.num_pending += 1;
+        if self.num_pending == self.batch_size {
+            let num_rows = unsafe { bcp_batch(handle.as_sys()) };
+            self.committed(num_rows, "bcp_batch")?;
+        }
+        Ok(())
+    }
+
+    /// Commits all rows not yet committed and ends the bulk copy. Returns the total number of rows
+    /// copied. Wraps `bcp_done`.
+    pub fn finish(mut self) -> Result<usize, Error> {
+        self.done = true;
+        let num_rows = unsafe { bcp_done(self.connection.as_sys()) };
+        self.committed(num_rows, "bcp_done")?;
+        Ok(self.num_committed)
+    }
+
+    /// Accounts for the rows reported as committed by `bcp_batch` or `bcp_done`. `-1` indicates an
+    /// error.
+    fn committed(&mut self, num_rows: i32, function: &'static str) -> Result<(), Error> {
+        let Ok(num_rows) = usize::try_from(num_rows) else {
+            return SqlResult::Error { function }.into_result(self.connection);
+        };
+        self.num_committed += num_rows;
+        self.num_pending = 0;
+        Ok(())
+    }
+}
+
+impl BoundColumn {
+    /// Writes `value` into the buffer and returns the length to announce to `bcp_collen`.
+    fn set_value(&mut self, column: u16, value: &BcpValue<'_>) -> Result<i32, Error> {
+        let kind = self.kind;
+        let bytes = self.bytes_mut();
+        let len = match (kind, value) {
+            (_, BcpValue::Null) => return Ok(NULL_DATA),
+            (BcpColumn::I32, BcpValue::I32(v)) => put(bytes, &v.to_ne_bytes()),
+            (BcpColumn::I64, BcpValue::I64(v)) => put(bytes, &v.to_ne_bytes()),
+            (BcpColumn::F64, BcpValue::F64(v)) => put(bytes, &v.to_ne_bytes()),
+            (BcpColumn::Text { max_str_len }, BcpValue::Text(text)) => {
+                let mut len = 0;
+                for (index, unit) in text.encode_utf16().enumerate() {
+                    if index == max_str_len {
+                        return Err(Error::ValueTooLarge {
+                            column,
+                            size: Some(text.encode_utf16().count() * size_of::<u16>()),
+                            cap: max_str_len * size_of::<u16>(),
+                        });
+                    }
+                    len += put(&mut bytes[len..], &unit.to_ne_bytes());
+                }
+                len
+            }
+            (BcpColumn::Binary { max_len }, BcpValue::Binary(data)) => {
+                if data.len() > max_len {
+                    return Err(Error::ValueTooLarge {
+                        column,
+                        size: Some(data.len()),
+                        cap: max_len,
+                    });
+                }
+                put(bytes, data)
+            }
+            _ => {
+                return Err(Error::InvalidArgument {
+                    description: "values sent with bulk copy must match the type of their column",
+                })
+            }
+        };
+        Ok(len as i32)
+    }
+}
+
+impl Drop for BulkCopy<'_> {
+    fn drop(&mut self) {
+        if !self.done {
+            let num_rows = unsafe { bcp_done(self.connection.as_sys()) };
+            if num_rows < 0 {
+                log::warn!("Ending a bulk copy failed while dropping it.");
+            }
+        }
+    }
+}
+
+/// Copies `value` to the start of `bytes` and returns its length.
+fn put(bytes: &mut [u8], value: &[u8]) -> usize {
+    bytes[..value.len()].copy_from_slice(value);
+    value.len()
+}
+
+/// Maps the `RETCODE` of a BCP function to a result. BCP functions report errors as diagnostics of
+/// the connection.
+fn check(
+    connection: &handles::Connection<'_>,
+    ret: i16,
+    function: &'static str,
+) -> Result<(), Error> {
+    if ret == FAIL {
+        SqlResult::Error { function }.into_result(connection)
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Error;
+
+    use super::{BcpColumn, BcpValue, BoundColumn, NULL_DATA};
+
+    fn bound(kind: BcpColumn, len: usize) -> BoundColumn {
+        BoundColumn {
+            kind,
+            buffer: vec![0; len.div_ceil(8)].into_boxed_slice(),
+        }
+    }
+
+    #[test]
+    fn write_text_as_utf_16() {
+        let mut column = bound(BcpColumn::Text { max_str_len: 3 }, 6);
+
+        let len = column.set_value(1, &BcpValue::Text("Grü")).unwrap();
+
+        assert_eq!(6, len);
+        let units: Vec<u16> = column.bytes_mut()[..6]
+            .chunks(2)
+            .map(|pair| u16::from_ne_bytes([pair[0], pair[1]]))
+            .collect();
+        assert_eq!("Grü".encode_utf16().collect::<Vec<_>>(), units);
+        assert_eq!(NULL_DATA, column.set_value(1, &BcpValue::Null).unwrap());
+    }
+
+    #[test]
+    fn reject_too_long_text() {
+        let mut column = bound(BcpColumn::Text { max_str_len: 2 }, 4);
+
+        let result = column.set_value(2, &BcpValue::Text("abc"));
+
+        assert!(matches!(
+            result,
+            Err(Error::ValueTooLarge {
+                column: 2,
+                size: Some(6),
+                cap: 4
+            })
+        ));
+    }
+
+    #[test]
+    fn reject_value_of_other_type() {
+        let mut column = bound(BcpColumn::I32, 4);
+
+        let result = column.set_value(1, &BcpValue::Text("42"));
+
+        assert!(matches!(result, Err(Error::InvalidArgument { .. })));
+    }
+}
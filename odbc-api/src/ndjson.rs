@@ -0,0 +1,237 @@
+//! Bulk inserts newline delimited JSON into tables. Requires the `ndjson` feature. See
+//! [`NdjsonLoader`].
+
+use std::io::BufRead;
+
+use serde_json::{Map, Value};
+
+use crate::{query_builder::quote_identifier, Connection, Error, ResultSetMetadata};
+
+/// Reads newline delimited JSON (one JSON object per line) and inserts each object as one row into
+/// a table.
+///
+/// The columns of the table are determined by preparing a query for the table and inspecting the
+/// metadata of its result set. Fields of the objects are matched to columns by name, ignoring
+/// ASCII case. Fields without a matching column are ignored, missing fields and `null` are
+/// inserted as `NULL`. All values are bound as text and converted by the driver: strings are bound
+/// as is, numbers in their JSON representation, booleans as `1` or `0`, and arrays or nested
+/// objects as JSON text.
+///
+/// Lines which are not valid JSON, or do not hold an object, do not abort the load. They are
+/// quarantined, i.e. skipped and reported in [`LoadReport::quarantined`]. Empty lines are ignored.
+///
+/// ```no_run
+/// use std::{fs::File, io::BufReader};
+/// use odbc_api::{ndjson::NdjsonLoader, Connection};
+///
+/// fn load_events(conn: &Connection<'_>) -> Result<(), Box<dyn std::error::Error>> {
+///     let input = BufReader::new(File::open("events.ndjson")?);
+///     let report = NdjsonLoader::new().load(conn, "Events", input)?;
+///     for line in &report.quarantined {
+///         eprintln!("Skipped line {}: {}", line.line_number, line.reason);
+///     }
+///     println!("Inserted {} rows.", report.num_inserted);
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NdjsonLoader {
+    batch_size: usize,
+    max_str_len: usize,
+    columns: Option<Vec<String>>,
+}
+
+impl Default for NdjsonLoader {
+    fn default() -> Self {
+        Self {
+            batch_size: 1000,
+            max_str_len: 4096,
+            columns: None,
+        }
+    }
+}
+
+impl NdjsonLoader {
+    /// Inserts batches of 1000 rows.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of rows sent to the data source at once.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Initial size of the text buffers for columns, whose size is unknown or larger than this.
+    /// Buffers grow if larger values are encountered.
+    pub fn with_max_str_len(mut self, max_str_len: usize) -> Self {
+        self.max_str_len = max_str_len;
+        self
+    }
+
+    /// Only insert into these columns, rather than all columns of the table. Useful to exclude
+    /// columns populated by the data source, like auto incremented ids.
+    pub fn with_columns(mut self, columns: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.columns = Some(columns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Inserts every object read from `input` as a row into `table`.
+    pub fn load(
+        &self,
+        connection: &Connection<'_>,
+        table: &str,
+        input: impl BufRead,
+    ) -> Result<LoadReport, Error> {
+        let quote = connection.identifier_quote_char()?;
+        let table = quote_identifier(table, &quote)?;
+        let selection = if let Some(columns) = &self.columns {
+            let columns = columns
+                .iter()
+                .map(|name| quote_identifier(name, &quote))
+                .collect::<Result<Vec<_>, _>>()?;
+            columns.join(", ")
+        } else {
+            "*".to_owned()
+        };
+        let mut metadata = connection.prepare(&format!("SELECT {selection} FROM {table}"))?;
+        let num_cols = metadata.num_result_cols()? as u16;
+        let mut column_names = Vec::with_capacity(num_cols as usize);
+        let mut max_str_lens = Vec::with_capacity(num_cols as usize);
+        for column_number in 1..=num_cols {
+            column_names.push(metadata.col_name(column_number)?);
+            let len = metadata
+                .col_data_type(column_number)?
+                .utf8_len()
+                .unwrap_or(0);
+            max_str_lens.push(if len == 0 || len > self.max_str_len {
+                self.max_str_len
+            } else {
+                len
+            });
+        }
+        drop(metadata);
+
+        let columns = column_names
+            .iter()
+            .map(|name| quote_identifier(name, &quote))
+            .collect::<Result<Vec<_>, _>>()?;
+        let placeholders = vec!["?"; columns.len()].join(", ");
+        let insert = format!(
+            "INSERT INTO {table} ({}) VALUES ({placeholders})",
+            columns.join(", ")
+        );
+        let mut inserter = connection
+            .prepare(&insert)?
+            .into_text_inserter(self.batch_size, max_str_lens)?;
+
+        let mut report = LoadReport::default();
+        let mut row = vec![None; column_names.len()];
+        for (index, line) in input.lines().enumerate() {
+            let line = line.map_err(Error::FailedReadingInput)?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let object = match serde_json::from_str::<Value>(&line) {
+                Ok(Value::Object(object)) => object,
+                Ok(_) => {
+                    report.quarantine(index, line, "Line does not hold a JSON object.".to_owned());
+                    continue;
+                }
+                Err(error) => {
+                    report.quarantine(index, line, format!("Invalid JSON: {error}"));
+                    continue;
+                }
+            };
+            fill_row(&object, &column_names, &mut row);
+            inserter.append(row.iter().map(|value| value.as_deref().map(str::as_bytes)))?;
+            if inserter.num_rows() == inserter.capacity() {
+                inserter.execute()?;
+                report.num_inserted += inserter.num_rows();
+                inserter.clear();
+            }
+        }
+        if inserter.num_rows() != 0 {
+            inserter.execute()?;
+            report.num_inserted += inserter.num_rows();
+        }
+        Ok(report)
+    }
+}
+
+/// Outcome of [`NdjsonLoader::load`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LoadReport {
+    /// Number of rows inserted.
+    pub num_inserted: usize,
+    /// Lines skipped, because they could not be mapped to a row.
+    pub quarantined: Vec<QuarantinedLine>,
+}
+
+impl LoadReport {
+    fn quarantine(&mut self, index: usize, line: String, reason: String) {
+        self.quarantined.push(QuarantinedLine {
+            line_number: index + 1,
+            line,
+            reason,
+        });
+    }
+}
+
+/// A line of input, which has not been inserted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuarantinedLine {
+    /// One based line number within the input.
+    pub line_number: usize,
+    /// Content of the line.
+    pub line: String,
+    /// Why the line has been skipped.
+    pub reason: String,
+}
+
+/// Sets `row` to the text representation of the fields matching `column_names`.
+fn fill_row(object: &Map<String, Value>, column_names: &[String], row: &mut [Option<String>]) {
+    for (cell, column_name) in row.iter_mut().zip(column_names) {
+        let value = object.get(column_name).or_else(|| {
+            object
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(column_name))
+                .map(|(_, value)| value)
+        });
+        *cell = match value {
+            None | Some(Value::Null) => None,
+            Some(Value::String(text)) => Some(text.clone()),
+            Some(Value::Bool(boolean)) => Some(if *boolean { "1" } else { "0" }.to_owned()),
+            Some(other) => Some(other.to_string()),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::fill_row;
+
+    #[test]
+    fn map_fields_to_columns_by_name() {
+        let object = json!({"ID": 42, "name": "Jo", "active": true, "tags": ["a"], "extra": 1});
+        let object = object.as_object().unwrap();
+        let columns = ["id", "name", "active", "tags", "missing"].map(str::to_owned);
+        let mut row = vec![None; columns.len()];
+
+        fill_row(object, &columns, &mut row);
+
+        assert_eq!(
+            vec![
+                Some("42".to_owned()),
+                Some("Jo".to_owned()),
+                Some("1".to_owned()),
+                Some(r#"["a"]"#.to_owned()),
+                None
+            ],
+            row
+        );
+    }
+}
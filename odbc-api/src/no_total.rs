@@ -0,0 +1,31 @@
+/// What to do, if the driver reports [`crate::buffers::Indicator::NoTotal`] while streaming a
+/// variadic value using `SQLGetData`, i.e. it does not know how many bytes are still to come. See
+/// [`crate::CursorRow::get_variadic_with_policy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NoTotalPolicy {
+    /// Keep growing the buffer and calling `SQLGetData` until the driver returns the final chunk of
+    /// the value. This is the behavior of [`crate::CursorRow::get_text`] and
+    /// [`crate::CursorRow::get_binary`].
+    #[default]
+    ReadToEnd,
+    /// Like [`Self::ReadToEnd`], but stop after `max_bytes` bytes and truncate the value. The rest
+    /// of the value is not fetched. Values whose length is reported by the driver are fetched in
+    /// their entirety. For wide text `max_bytes` is rounded down to an even number, so no code unit
+    /// is split.
+    Cap { max_bytes: usize },
+    /// Fail with [`crate::Error::NoTotal`].
+    Error,
+}
+
+/// Describes how a variadic value has been fetched with
+/// [`crate::CursorRow::get_variadic_with_policy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VariadicFetch {
+    /// `false` if the value is `NULL`.
+    pub not_null: bool,
+    /// `true` if the driver reported [`crate::buffers::Indicator::NoTotal`] for at least one chunk
+    /// of the value.
+    pub no_total: bool,
+    /// `true` if the value has been cut short due to [`NoTotalPolicy::Cap`].
+    pub truncated: bool,
+}
@@ -0,0 +1,625 @@
+//! Inspects the configuration of the driver manager, i.e. the `odbc.ini` and `odbcinst.ini` files
+//! or their equivalents in the Windows registry. Unlike [`crate::Environment::data_sources`] and
+//! [`crate::Environment::drivers`] this also tells where each data source and driver is configured,
+//! which is what is needed to help users fixing their setup. See [`inspect`].
+//!
+//! ```no_run
+//! use odbc_api::odbc_config;
+//!
+//! let config = odbc_config::inspect()?;
+//! for dsn in &config.data_sources {
+//!     println!(
+//!         "{} ({:?}) uses driver {:?}, configured in {}",
+//!         dsn.name,
+//!         dsn.scope,
+//!         dsn.driver(),
+//!         dsn.location
+//!     );
+//! }
+//! for searched in config.locations.iter().filter(|searched| !searched.found) {
+//!     println!("Not found: {}", searched.location);
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::{collections::HashMap, fmt, io, path::PathBuf};
+
+#[cfg(not(target_os = "windows"))]
+use std::{fs, path::Path};
+
+/// Names of sections, which hold settings of the driver manager, rather than a data source or
+/// driver.
+const RESERVED_SECTIONS: &[&str] = &[
+    "ODBC",
+    "ODBC Data Sources",
+    "ODBC Drivers",
+    "ODBC File DSN",
+    "ODBC Translators",
+    "ODBC Core",
+];
+
+/// Environment variables changing where `unixODBC` or `iODBC` look for their configuration files.
+#[cfg(not(target_os = "windows"))]
+const ENVIRONMENT_VARIABLES: &[&str] = &[
+    "ODBCSYSINI",
+    "ODBCINSTINI",
+    "ODBCINI",
+    "SYSODBCINI",
+    "ODBCSEARCH",
+];
+
+/// Data sources and drivers configured for the driver manager, together with the locations they
+/// have been read from. Created by [`inspect`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OdbcConfig {
+    /// Data sources, user data sources first.
+    pub data_sources: Vec<ConfigEntry>,
+    /// Installed drivers, user drivers first.
+    pub drivers: Vec<ConfigEntry>,
+    /// Every location which has been searched for configuration, whether it exists or not.
+    pub locations: Vec<SearchedLocation>,
+    /// Environment variables which change where the driver manager looks for its configuration
+    /// files, with their values. `None` if a variable is not set. Empty on Windows.
+    pub environment: Vec<(String, Option<String>)>,
+}
+
+/// A data source or driver, as configured in a section of an ini file, or a registry key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigEntry {
+    /// Name of the data source or driver.
+    pub name: String,
+    /// Attributes by key, e.g. `Driver`, `Server` or `Database`.
+    pub attributes: HashMap<String, String>,
+    /// Whether the entry is visible to the current user only, or to every user.
+    pub scope: ConfigScope,
+    /// Where the entry is configured.
+    pub location: ConfigLocation,
+}
+
+impl ConfigEntry {
+    /// Value of the `Driver` attribute. For data sources this is the name of the driver or the path
+    /// to its shared library, for drivers the path to its shared library.
+    pub fn driver(&self) -> Option<&str> {
+        self.attribute("Driver")
+    }
+
+    /// Value of the attribute named `key`, ignoring the case of the key, like the driver manager
+    /// does.
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(candidate, _)| candidate.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// A location searched for configuration by [`inspect`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchedLocation {
+    pub location: ConfigLocation,
+    /// Whether the location holds data sources or drivers.
+    pub kind: ConfigKind,
+    pub scope: ConfigScope,
+    /// `false` if the file or registry key does not exist.
+    pub found: bool,
+}
+
+/// Where data sources or drivers are configured.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigLocation {
+    /// Path to an ini file, e.g. `/etc/odbcinst.ini`.
+    File(PathBuf),
+    /// Path of a registry key, e.g. `HKEY_LOCAL_MACHINE\SOFTWARE\ODBC\ODBCINST.INI`.
+    Registry(String),
+}
+
+impl fmt::Display for ConfigLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigLocation::File(path) => write!(f, "{}", path.display()),
+            ConfigLocation::Registry(key) => write!(f, "{key}"),
+        }
+    }
+}
+
+/// Whether a location holds data sources (`odbc.ini`) or drivers (`odbcinst.ini`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigKind {
+    DataSources,
+    Drivers,
+}
+
+/// Whether configuration is visible to the current user only, or to every user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigScope {
+    User,
+    System,
+}
+
+/// A section of an ini file, e.g. `[PostgreSQL]`, with its key value pairs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IniSection {
+    /// Name of the section, without the brackets.
+    pub name: String,
+    /// Attributes of the section by key. If a key is repeated, the last value wins.
+    pub attributes: HashMap<String, String>,
+}
+
+/// Parses ini files in the dialect understood by `unixODBC` and `iODBC`. Lines starting with `;` or
+/// `#` are comments. Keys and values are separated by `=` and stripped of surrounding whitespace.
+/// Lines before the first section and lines without `=` are ignored.
+///
+/// ```
+/// use odbc_api::odbc_config::parse_ini;
+///
+/// let text = "[PostgreSQL]\nDriver = /usr/lib/psqlodbcw.so\n; A comment\n";
+/// let sections = parse_ini(text);
+///
+/// assert_eq!("PostgreSQL", sections[0].name);
+/// assert_eq!("/usr/lib/psqlodbcw.so", sections[0].attributes["Driver"]);
+/// ```
+pub fn parse_ini(text: &str) -> Vec<IniSection> {
+    let mut sections: Vec<IniSection> = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            sections.push(IniSection {
+                name: name.trim().to_owned(),
+                attributes: HashMap::new(),
+            });
+        } else if let (Some(section), Some((key, value))) =
+            (sections.last_mut(), line.split_once('='))
+        {
+            section
+                .attributes
+                .insert(key.trim().to_owned(), value.trim().to_owned());
+        }
+    }
+    sections
+}
+
+/// Reads the data sources and drivers configured for the driver manager.
+///
+/// On Windows these are read from the `ODBC.INI` and `ODBCINST.INI` registry keys below
+/// `HKEY_CURRENT_USER` and `HKEY_LOCAL_MACHINE`. Elsewhere the ini files are located the way
+/// `unixODBC` does, honoring `ODBCSYSINI`, `ODBCINSTINI` and `ODBCINI`, with fallbacks to the
+/// locations used by `iODBC`. Locations which do not exist are reported as not found, rather than
+/// failing.
+pub fn inspect() -> io::Result<OdbcConfig> {
+    let mut config = OdbcConfig {
+        data_sources: Vec::new(),
+        drivers: Vec::new(),
+        locations: Vec::new(),
+        environment: Vec::new(),
+    };
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        config.environment = ENVIRONMENT_VARIABLES
+            .iter()
+            .map(|&name| (name.to_owned(), std::env::var(name).ok()))
+            .collect();
+        let locations = file_locations(|name| std::env::var(name).ok());
+        for (path, kind, scope) in locations {
+            let sections = read_ini_file(&path)?;
+            config.add(ConfigLocation::File(path), kind, scope, sections);
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    for (root, root_name, path, kind, scope) in registry::LOCATIONS {
+        let sections = registry::read_sections(root(), path)?;
+        let location = ConfigLocation::Registry(format!("{root_name}\\{path}"));
+        config.add(location, kind, scope, sections);
+    }
+
+    Ok(config)
+}
+
+impl OdbcConfig {
+    /// Records a searched location. `sections` is `None` if the location does not exist.
+    fn add(
+        &mut self,
+        location: ConfigLocation,
+        kind: ConfigKind,
+        scope: ConfigScope,
+        sections: Option<Vec<IniSection>>,
+    ) {
+        let found = sections.is_some();
+        let entries = sections
+            .into_iter()
+            .flatten()
+            .filter(|section| {
+                !RESERVED_SECTIONS
+                    .iter()
+                    .any(|reserved| reserved.eq_ignore_ascii_case(&section.name))
+            })
+            .map(|section| ConfigEntry {
+                name: section.name,
+                attributes: section.attributes,
+                scope,
+                location: location.clone(),
+            });
+        match kind {
+            ConfigKind::DataSources => self.data_sources.extend(entries),
+            ConfigKind::Drivers => self.drivers.extend(entries),
+        }
+        self.locations.push(SearchedLocation {
+            location,
+            kind,
+            scope,
+            found,
+        });
+    }
+}
+
+/// Paths of the ini files, user files first. `var` looks up environment variables.
+#[cfg(not(target_os = "windows"))]
+fn file_locations(var: impl Fn(&str) -> Option<String>) -> Vec<(PathBuf, ConfigKind, ConfigScope)> {
+    let home = var("HOME").map(PathBuf::from);
+    let sys_dir = var("ODBCSYSINI")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("/etc"));
+    let mut locations = Vec::new();
+
+    // `unixODBC` and `iODBC` both use `ODBCINI` as path to the user data sources.
+    if let Some(user_dsns) = var("ODBCINI")
+        .map(PathBuf::from)
+        .or_else(|| home.as_ref().map(|home| home.join(".odbc.ini")))
+    {
+        locations.push((user_dsns, ConfigKind::DataSources, ConfigScope::User));
+    }
+    // `iODBC` uses `SYSODBCINI` as path to the system data sources.
+    let system_dsns = var("SYSODBCINI")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| sys_dir.join("odbc.ini"));
+    locations.push((system_dsns, ConfigKind::DataSources, ConfigScope::System));
+
+    if let Some(home) = &home {
+        locations.push((
+            home.join(".odbcinst.ini"),
+            ConfigKind::Drivers,
+            ConfigScope::User,
+        ));
+    }
+    // `unixODBC` expects a file name within `ODBCSYSINI`, `iODBC` a path.
+    let system_drivers = match var("ODBCINSTINI") {
+        Some(name) if Path::new(&name).is_absolute() => PathBuf::from(name),
+        Some(name) => sys_dir.join(name),
+        None => sys_dir.join("odbcinst.ini"),
+    };
+    locations.push((system_drivers, ConfigKind::Drivers, ConfigScope::System));
+
+    locations
+}
+
+/// Reads and parses the ini file at `path`. `None` if it does not exist.
+#[cfg(not(target_os = "windows"))]
+fn read_ini_file(path: &Path) -> io::Result<Option<Vec<IniSection>>> {
+    match fs::read(path) {
+        Ok(bytes) => Ok(Some(parse_ini(&String::from_utf8_lossy(&bytes)))),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+/// Reads the registry using the functions of `advapi32`.
+#[cfg(target_os = "windows")]
+mod registry {
+    use std::{collections::HashMap, ffi::c_void, io, ptr::null_mut};
+
+    use widestring::U16CString;
+
+    use super::{ConfigKind, ConfigScope, IniSection};
+
+    type Hkey = *mut c_void;
+
+    // Predefined keys are sign extended 32 bit values.
+    const HKEY_CURRENT_USER: isize = 0x8000_0001_u32 as i32 as isize;
+    const HKEY_LOCAL_MACHINE: isize = 0x8000_0002_u32 as i32 as isize;
+    const KEY_READ: u32 = 0x0002_0019;
+    const ERROR_SUCCESS: i32 = 0;
+    const ERROR_FILE_NOT_FOUND: i32 = 2;
+    const ERROR_MORE_DATA: i32 = 234;
+    const ERROR_NO_MORE_ITEMS: i32 = 259;
+    const REG_SZ: u32 = 1;
+    const REG_EXPAND_SZ: u32 = 2;
+    /// Maximum length of key and value names in characters, including the terminating zero.
+    const MAX_NAME_LEN: usize = 16384;
+
+    #[link(name = "advapi32")]
+    extern "system" {
+        fn RegOpenKeyExW(
+            key: Hkey,
+            sub_key: *const u16,
+            options: u32,
+            sam_desired: u32,
+            result: *mut Hkey,
+        ) -> i32;
+        fn RegEnumKeyExW(
+            key: Hkey,
+            index: u32,
+            name: *mut u16,
+            name_len: *mut u32,
+            reserved: *mut u32,
+            class: *mut u16,
+            class_len: *mut u32,
+            last_write_time: *mut c_void,
+        ) -> i32;
+        fn RegEnumValueW(
+            key: Hkey,
+            index: u32,
+            value_name: *mut u16,
+            value_name_len: *mut u32,
+            reserved: *mut u32,
+            value_type: *mut u32,
+            data: *mut u8,
+            data_len: *mut u32,
+        ) -> i32;
+        fn RegCloseKey(key: Hkey) -> i32;
+    }
+
+    /// Registry keys holding data sources and drivers, user keys first.
+    #[allow(clippy::type_complexity)]
+    pub const LOCATIONS: [(fn() -> Hkey, &str, &str, ConfigKind, ConfigScope); 4] = [
+        (
+            current_user,
+            "HKEY_CURRENT_USER",
+            "Software\\ODBC\\ODBC.INI",
+            ConfigKind::DataSources,
+            ConfigScope::User,
+        ),
+        (
+            local_machine,
+            "HKEY_LOCAL_MACHINE",
+            "SOFTWARE\\ODBC\\ODBC.INI",
+            ConfigKind::DataSources,
+            ConfigScope::System,
+        ),
+        (
+            current_user,
+            "HKEY_CURRENT_USER",
+            "Software\\ODBC\\ODBCINST.INI",
+            ConfigKind::Drivers,
+            ConfigScope::User,
+        ),
+        (
+            local_machine,
+            "HKEY_LOCAL_MACHINE",
+            "SOFTWARE\\ODBC\\ODBCINST.INI",
+            ConfigKind::Drivers,
+            ConfigScope::System,
+        ),
+    ];
+
+    fn current_user() -> Hkey {
+        HKEY_CURRENT_USER as Hkey
+    }
+
+    fn local_machine() -> Hkey {
+        HKEY_LOCAL_MACHINE as Hkey
+    }
+
+    /// Open registry key, closed then dropped.
+    struct Key(Hkey);
+
+    impl Key {
+        /// `None` if the key does not exist.
+        fn open(parent: Hkey, path: &str) -> io::Result<Option<Key>> {
+            let path = U16CString::from_str(path).unwrap();
+            let mut key = null_mut();
+            let result = unsafe { RegOpenKeyExW(parent, path.as_ptr(), 0, KEY_READ, &mut key) };
+            match result {
+                ERROR_SUCCESS => Ok(Some(Key(key))),
+                ERROR_FILE_NOT_FOUND => Ok(None),
+                code => Err(io::Error::from_raw_os_error(code)),
+            }
+        }
+
+        fn sub_key_names(&self) -> io::Result<Vec<String>> {
+            let mut names = Vec::new();
+            let mut name = vec![0u16; MAX_NAME_LEN];
+            for index in 0.. {
+                let mut name_len = name.len() as u32;
+                let result = unsafe {
+                    RegEnumKeyExW(
+                        self.0,
+                        index,
+                        name.as_mut_ptr(),
+                        &mut name_len,
+                        null_mut(),
+                        null_mut(),
+                        null_mut(),
+                        null_mut(),
+                    )
+                };
+                match result {
+                    ERROR_SUCCESS => {
+                        names.push(String::from_utf16_lossy(&name[..name_len as usize]))
+                    }
+                    ERROR_NO_MORE_ITEMS => break,
+                    code => return Err(io::Error::from_raw_os_error(code)),
+                }
+            }
+            Ok(names)
+        }
+
+        /// Values of type `REG_SZ` or `REG_EXPAND_SZ` by name. Values of other types are ignored.
+        fn string_values(&self) -> io::Result<HashMap<String, String>> {
+            let mut values = HashMap::new();
+            let mut name = vec![0u16; MAX_NAME_LEN];
+            let mut data = vec![0u8; 1024];
+            let mut index = 0;
+            loop {
+                let mut name_len = name.len() as u32;
+                let mut data_len = data.len() as u32;
+                let mut value_type = 0;
+                let result = unsafe {
+                    RegEnumValueW(
+                        self.0,
+                        index,
+                        name.as_mut_ptr(),
+                        &mut name_len,
+                        null_mut(),
+                        &mut value_type,
+                        data.as_mut_ptr(),
+                        &mut data_len,
+                    )
+                };
+                match result {
+                    ERROR_SUCCESS => (),
+                    // Data did not fit. `data_len` holds the required size. Try again.
+                    ERROR_MORE_DATA => {
+                        data.resize(data_len as usize, 0);
+                        continue;
+                    }
+                    ERROR_NO_MORE_ITEMS => break,
+                    code => return Err(io::Error::from_raw_os_error(code)),
+                }
+                index += 1;
+                if value_type != REG_SZ && value_type != REG_EXPAND_SZ {
+                    continue;
+                }
+                let text: Vec<u16> = data[..data_len as usize]
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_ne_bytes([pair[0], pair[1]]))
+                    .take_while(|&unit| unit != 0)
+                    .collect();
+                values.insert(
+                    String::from_utf16_lossy(&name[..name_len as usize]),
+                    String::from_utf16_lossy(&text),
+                );
+            }
+            Ok(values)
+        }
+    }
+
+    impl Drop for Key {
+        fn drop(&mut self) {
+            unsafe {
+                RegCloseKey(self.0);
+            }
+        }
+    }
+
+    /// Sub keys of the key at `path` below `root` as sections. `None` if the key does not exist.
+    pub fn read_sections(root: Hkey, path: &str) -> io::Result<Option<Vec<IniSection>>> {
+        let key = match Key::open(root, path)? {
+            Some(key) => key,
+            None => return Ok(None),
+        };
+        let mut sections = Vec::new();
+        for name in key.sub_key_names()? {
+            // The sub key may have been deleted since enumerating it.
+            if let Some(sub_key) = Key::open(key.0, &name)? {
+                sections.push(IniSection {
+                    attributes: sub_key.string_values()?,
+                    name,
+                });
+            }
+        }
+        Ok(Some(sections))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_ini, ConfigKind, ConfigLocation, ConfigScope, OdbcConfig};
+
+    #[test]
+    fn parse_sections_comments_and_whitespace() {
+        let text = "\
+            ignored = before any section\n\
+            [ODBC Drivers]\n\
+            PostgreSQL = Installed\n\
+            \n\
+            ; comment\n\
+            # another comment\n\
+            [ PostgreSQL ]\n\
+            Driver=/usr/lib/psqlodbcw.so\n\
+            Setup = /usr/lib/libodbcpsqlS.so \n\
+            no equals sign\n\
+            Driver = /usr/local/lib/psqlodbcw.so\n";
+
+        let sections = parse_ini(text);
+
+        assert_eq!(2, sections.len());
+        assert_eq!("ODBC Drivers", sections[0].name);
+        assert_eq!("Installed", sections[0].attributes["PostgreSQL"]);
+        assert_eq!("PostgreSQL", sections[1].name);
+        assert_eq!(2, sections[1].attributes.len());
+        assert_eq!(
+            "/usr/local/lib/psqlodbcw.so",
+            sections[1].attributes["Driver"]
+        );
+        assert_eq!("/usr/lib/libodbcpsqlS.so", sections[1].attributes["Setup"]);
+    }
+
+    #[test]
+    fn reserved_sections_are_not_data_sources() {
+        let mut config = OdbcConfig {
+            data_sources: Vec::new(),
+            drivers: Vec::new(),
+            locations: Vec::new(),
+            environment: Vec::new(),
+        };
+        let sections =
+            parse_ini("[ODBC Data Sources]\nsales = PostgreSQL\n[sales]\nDRIVER = PostgreSQL\n");
+        let location = ConfigLocation::File("/etc/odbc.ini".into());
+
+        config.add(
+            location.clone(),
+            ConfigKind::DataSources,
+            ConfigScope::System,
+            Some(sections),
+        );
+        config.add(
+            ConfigLocation::File("/home/user/.odbc.ini".into()),
+            ConfigKind::DataSources,
+            ConfigScope::User,
+            None,
+        );
+
+        assert_eq!(1, config.data_sources.len());
+        assert_eq!("sales", config.data_sources[0].name);
+        assert_eq!(Some("PostgreSQL"), config.data_sources[0].driver());
+        assert_eq!(location, config.data_sources[0].location);
+        assert!(config.locations[0].found);
+        assert!(!config.locations[1].found);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn file_locations_honor_environment() {
+        use super::file_locations;
+        use std::path::PathBuf;
+
+        let var = |name: &str| match name {
+            "HOME" => Some("/home/user".to_owned()),
+            "ODBCSYSINI" => Some("/opt/odbc".to_owned()),
+            "ODBCINSTINI" => Some("drivers.ini".to_owned()),
+            _ => None,
+        };
+
+        let paths: Vec<PathBuf> = file_locations(var)
+            .into_iter()
+            .map(|(path, _, _)| path)
+            .collect();
+
+        let expected: Vec<PathBuf> = [
+            "/home/user/.odbc.ini",
+            "/opt/odbc/odbc.ini",
+            "/home/user/.odbcinst.ini",
+            "/opt/odbc/drivers.ini",
+        ]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+        assert_eq!(expected, paths);
+    }
+}
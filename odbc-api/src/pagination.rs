@@ -0,0 +1,289 @@
+use odbc_sys::{Date, Time, Timestamp};
+
+use crate::{
+    fixed_sized::Pod,
+    handles::StatementImpl,
+    parameter::{InputParameter, VarBinaryBox, VarCharBox, WithDataType},
+    query_builder::quote_identifier,
+    result_set_metadata::reported,
+    Connection, Cursor, CursorImpl, CursorRow, DataType, Error, Nullable, ResultSetMetadata,
+};
+
+/// Iterator over the pages of a result set, each page being a cursor over up to `page_size` rows.
+/// Created by [`Connection::paginate`].
+///
+/// Pages are determined by keyset (also called seek) pagination. Rows are sorted by the key columns
+/// and each page continues after the last key of the previous one. Other than `OFFSET` based
+/// paging, the data source does not need to skip all rows of the preceding pages, so fetching a
+/// page stays fast for large tables, provided the key columns are indexed.
+///
+/// Each page takes two queries, both seeking past the last key of the previous page and limited to
+/// `page_size` rows (see [`Connection::execute_with_max_rows`]). The first one fetches only the key
+/// columns to determine the last key of the page, the second one selects the rows of the page by
+/// their range of keys. Rows inserted or deleted while iterating may therefore change the number of
+/// rows within a page. An empty result set yields no pages at all. Consume or drop each page before
+/// requesting the next one, since many drivers support only one active statement per connection
+/// (see [`Connection::max_concurrent_activities`]).
+///
+/// Key values are bound as parameters of the same type as the key columns. Integer, floating point,
+/// date, time and binary keys are bound as such, all others (e.g. `DECIMAL` or `VARCHAR`) as text.
+pub struct Pages<'c> {
+    connection: &'c Connection<'c>,
+    /// The query to paginate, used as a subquery.
+    query: String,
+    /// Quoted names of the key columns.
+    keys: Vec<String>,
+    page_size: usize,
+    /// Last key of the previous page. `None` for the first page.
+    last_key: Option<Vec<KeyValue>>,
+    /// `true` once the last page has been returned.
+    done: bool,
+}
+
+impl<'c> Pages<'c> {
+    pub(crate) fn new(
+        connection: &'c Connection<'c>,
+        query: &str,
+        key_columns: &[&str],
+        page_size: usize,
+    ) -> Result<Self, Error> {
+        if key_columns.is_empty() {
+            return Err(Error::InvalidArgument {
+                description: "pagination requires at least one key column",
+            });
+        }
+        if page_size == 0 {
+            return Err(Error::InvalidArgument {
+                description: "page size must be at least one",
+            });
+        }
+        let quote = connection.identifier_quote_char()?;
+        Ok(Self {
+            connection,
+            query: query.trim_end().trim_end_matches(';').to_owned(),
+            keys: key_columns
+                .iter()
+                .map(|key| quote_identifier(key, &quote))
+                .collect::<Result<_, _>>()?,
+            page_size,
+            last_key: None,
+            done: false,
+        })
+    }
+
+    /// Selects `select_list` from the rows following the previous page, with `bound` as an upper
+    /// limit for the keys, if specified. The result set is sorted by the keys and limited to
+    /// `page_size` rows.
+    fn execute_page_query(
+        &self,
+        select_list: &str,
+        bound: Option<&[KeyValue]>,
+    ) -> Result<Option<CursorImpl<StatementImpl<'c>>>, Error> {
+        let mut params = Vec::new();
+        let mut conditions = Vec::new();
+        if let Some(last_key) = &self.last_key {
+            conditions.push(range_condition(&self.keys, last_key, ">", &mut params));
+        }
+        if let Some(bound) = bound {
+            conditions.push(range_condition(&self.keys, bound, "<", &mut params));
+        }
+        let mut sql = format!("SELECT {select_list} FROM ({}) paginated", self.query);
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        sql.push_str(" ORDER BY ");
+        sql.push_str(&self.keys.join(", "));
+        self.connection
+            .execute_with_max_rows(&sql, &params[..], self.page_size)
+    }
+
+    /// Fetches the keys of the next page and returns the last one, together with the number of
+    /// rows in the page.
+    fn fetch_bound(&self) -> Result<(Option<Vec<KeyValue>>, usize), Error> {
+        let mut bound = None;
+        let mut num_rows = 0;
+        if let Some(mut cursor) = self.execute_page_query(&self.keys.join(", "), None)? {
+            let num_keys: u16 = reported(self.keys.len() as isize, "number of key columns")?;
+            let data_types = (1..=num_keys)
+                .map(|col_index| cursor.col_data_type(col_index))
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut buf = Vec::new();
+            // Drivers may ignore the limit, so do not rely on it for the number of rows.
+            while num_rows < self.page_size {
+                let Some(mut row) = cursor.next_row()? else {
+                    break;
+                };
+                num_rows += 1;
+                bound = Some(
+                    (1..=num_keys)
+                        .zip(&data_types)
+                        .map(|(col_index, &data_type)| {
+                            KeyValue::fetch(&mut row, col_index, data_type, &mut buf)
+                        })
+                        .collect::<Result<_, _>>()?,
+                );
+            }
+        }
+        Ok((bound, num_rows))
+    }
+
+    fn next_page(&mut self) -> Result<Option<CursorImpl<StatementImpl<'c>>>, Error> {
+        let (bound, num_rows) = self.fetch_bound()?;
+        let Some(bound) = bound else {
+            // All rows have been returned with the previous page.
+            return Ok(None);
+        };
+        let cursor = self.execute_page_query("*", Some(&bound))?;
+        // A page with fewer rows than the page size is the last one.
+        self.done = num_rows < self.page_size;
+        self.last_key = Some(bound);
+        Ok(cursor)
+    }
+}
+
+/// Value of a key column, bound as a parameter of the same type as the column.
+#[derive(Clone, Debug)]
+enum KeyValue {
+    Integer(i64, DataType),
+    Float(f64, DataType),
+    Date(Date),
+    Time(Time, DataType),
+    Timestamp(Timestamp, DataType),
+    Binary(Vec<u8>),
+    /// Any other type is fetched as text, and converted back by the driver.
+    Text(Vec<u8>),
+}
+
+impl KeyValue {
+    /// Fetches the key in column `col_index` of `row`. Fails with [`Error::NullPaginationKey`] if
+    /// it is `NULL`.
+    fn fetch(
+        row: &mut CursorRow<'_>,
+        col_index: u16,
+        data_type: DataType,
+        buf: &mut Vec<u8>,
+    ) -> Result<Self, Error> {
+        let value = match data_type {
+            DataType::TinyInt | DataType::SmallInt | DataType::Integer | DataType::BigInt => {
+                fetch_pod(row, col_index)?.map(|value| Self::Integer(value, data_type))
+            }
+            DataType::Real | DataType::Float { .. } | DataType::Double => {
+                fetch_pod(row, col_index)?.map(|value| Self::Float(value, data_type))
+            }
+            DataType::Date => fetch_pod(row, col_index)?.map(Self::Date),
+            DataType::Time { .. } => {
+                fetch_pod(row, col_index)?.map(|value| Self::Time(value, data_type))
+            }
+            DataType::Timestamp { .. } => {
+                fetch_pod(row, col_index)?.map(|value| Self::Timestamp(value, data_type))
+            }
+            DataType::Binary { .. }
+            | DataType::Varbinary { .. }
+            | DataType::LongVarbinary { .. } => row
+                .get_binary(col_index, buf)?
+                .then(|| Self::Binary(buf.clone())),
+            _ => row
+                .get_text(col_index, buf)?
+                .then(|| Self::Text(buf.clone())),
+        };
+        value.ok_or(Error::NullPaginationKey)
+    }
+
+    fn to_parameter(&self) -> Box<dyn InputParameter> {
+        match self {
+            &KeyValue::Integer(value, data_type) => Box::new(WithDataType { value, data_type }),
+            &KeyValue::Float(value, data_type) => Box::new(WithDataType { value, data_type }),
+            &KeyValue::Date(value) => Box::new(value),
+            &KeyValue::Time(value, data_type) => Box::new(WithDataType { value, data_type }),
+            &KeyValue::Timestamp(value, data_type) => Box::new(WithDataType { value, data_type }),
+            KeyValue::Binary(bytes) => Box::new(VarBinaryBox::from_vec(bytes.clone())),
+            KeyValue::Text(text) => Box::new(VarCharBox::from_vec(text.clone())),
+        }
+    }
+}
+
+fn fetch_pod<T>(row: &mut CursorRow<'_>, col_index: u16) -> Result<Option<T>, Error>
+where
+    T: Pod,
+{
+    let mut value = Nullable::<T>::null();
+    row.get_data(col_index, &mut value)?;
+    Ok(value.into_opt())
+}
+
+/// Lexicographic comparison of the `columns` with `key`, using `op` (`<` or `>`). The comparison
+/// of the last column includes equality for `<`. Row value comparisons (`(k1, k2) > (?, ?)`) would
+/// be shorter, but are not supported by every data source. Pushes the values of `key` to `params`
+/// in the order of their placeholders.
+fn range_condition(
+    columns: &[String],
+    key: &[KeyValue],
+    op: &str,
+    params: &mut Vec<Box<dyn InputParameter>>,
+) -> String {
+    let disjunction: Vec<_> = (0..columns.len())
+        .map(|position| {
+            let mut conjunction = Vec::new();
+            for (column, value) in columns.iter().zip(key).take(position) {
+                conjunction.push(format!("{column} = ?"));
+                params.push(value.to_parameter());
+            }
+            let op = if position + 1 == columns.len() && op == "<" {
+                "<="
+            } else {
+                op
+            };
+            conjunction.push(format!("{} {op} ?", columns[position]));
+            params.push(key[position].to_parameter());
+            format!("({})", conjunction.join(" AND "))
+        })
+        .collect();
+    format!("({})", disjunction.join(" OR "))
+}
+
+impl<'c> Iterator for Pages<'c> {
+    type Item = Result<CursorImpl<StatementImpl<'c>>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.next_page() {
+            Ok(Some(cursor)) => Some(Ok(cursor)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(error) => {
+                self.done = true;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::DataType;
+
+    use super::{range_condition, KeyValue};
+
+    #[test]
+    fn compare_keys_lexicographically() {
+        let columns = ["a".to_owned(), "b".to_owned()];
+        let key = [
+            KeyValue::Integer(1, DataType::Integer),
+            KeyValue::Text(b"2".to_vec()),
+        ];
+        let mut params = Vec::new();
+
+        let seek = range_condition(&columns, &key, ">", &mut params);
+        let bound = range_condition(&columns, &key, "<", &mut params);
+
+        assert_eq!("((a > ?) OR (a = ? AND b > ?))", seek);
+        assert_eq!("((a < ?) OR (a = ? AND b <= ?))", bound);
+        assert_eq!(6, params.len());
+        assert_eq!(DataType::Integer, params[0].data_type());
+    }
+}
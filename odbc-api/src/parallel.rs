@@ -0,0 +1,224 @@
+//! Processes fetched batches in parallel on a rayon thread pool. Requires the `rayon` feature. See
+//! [`ParallelFetch`].
+
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+use rayon::{Scope, ThreadPool, Yield};
+
+use crate::{Cursor, Error, RowSetBuffer};
+
+/// Hands each batch fetched from a cursor to a rayon thread pool, while the next batch is fetched.
+/// Useful if processing the rows is CPU heavy and would otherwise be the bottleneck of an
+/// extraction.
+///
+/// Every batch is fetched into a buffer of its own, which is owned by the task processing it and
+/// reused once the task is done. At most [`Self::with_max_in_flight`] buffers are allocated, so
+/// fetching pauses as long as all of them are being processed. Fetching happens on the calling
+/// thread, since cursors can not be sent to other threads.
+///
+/// ```no_run
+/// use odbc_api::{buffers::TextRowSet, parallel::ParallelFetch, Connection, Error};
+///
+/// fn count_words(conn: &Connection<'_>) -> Result<usize, Error> {
+///     let cursor = conn.execute("SELECT body FROM Articles", ())?.unwrap();
+///     let counts = ParallelFetch::new().with_max_in_flight(8).run(
+///         cursor,
+///         |cursor| TextRowSet::for_cursor(10_000, cursor, Some(65_536)),
+///         |batch| {
+///             (0..batch.num_rows())
+///                 .filter_map(|row| batch.at_as_str(0, row).ok().flatten())
+///                 .map(|body| body.split_whitespace().count())
+///                 .sum::<usize>()
+///         },
+///     )?;
+///     Ok(counts.into_iter().sum())
+/// }
+/// ```
+#[derive(Clone, Copy)]
+pub struct ParallelFetch<'p> {
+    max_in_flight: usize,
+    pool: Option<&'p ThreadPool>,
+}
+
+impl Default for ParallelFetch<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'p> ParallelFetch<'p> {
+    /// Processes up to four batches at once on the global thread pool of rayon.
+    pub fn new() -> Self {
+        Self {
+            max_in_flight: 4,
+            pool: None,
+        }
+    }
+
+    /// Maximum number of batches fetched, but not yet processed. Equals the number of buffers
+    /// allocated. [`Self::run`] fails with [`Error::InvalidArgument`] if it is zero.
+    pub fn with_max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = max_in_flight;
+        self
+    }
+
+    /// Processes the batches on `pool`, rather than the global thread pool.
+    pub fn with_thread_pool(mut self, pool: &'p ThreadPool) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+
+    /// Fetches all batches of `cursor` and calls `process` for each of them on the thread pool.
+    /// Buffers are allocated with `make_buffer` as required. Returns the results of `process` in
+    /// the order of the batches. Waits for all batches to be processed, even if fetching fails.
+    /// Fails with [`Error::TooLargeValueForBuffer`] rather than processing truncated values.
+    ///
+    /// If `process` panics, the panic is propagated to the caller once all other tasks finished.
+    /// Called from within a thread of the pool, the thread processes pending batches itself while
+    /// waiting for a free buffer.
+    pub fn run<C, B, R>(
+        &self,
+        cursor: C,
+        make_buffer: impl FnMut(&mut C) -> Result<B, Error>,
+        process: impl Fn(&B) -> R + Sync,
+    ) -> Result<Vec<R>, Error>
+    where
+        C: Cursor,
+        B: RowSetBuffer + Send,
+        R: Send,
+    {
+        if self.max_in_flight == 0 {
+            return Err(Error::InvalidArgument {
+                description: "at least one batch must be in flight",
+            });
+        }
+        let mut run = Run {
+            max_in_flight: self.max_in_flight,
+            cursor: Some(cursor),
+            make_buffer,
+            num_buffers: 0,
+            results: Vec::new(),
+        };
+        let (done, finished) = mpsc::channel();
+        let fetched = match self.pool {
+            Some(pool) => {
+                pool.in_place_scope(|scope| run.fetch_all(scope, &process, &done, &finished))
+            }
+            None => rayon::in_place_scope(|scope| run.fetch_all(scope, &process, &done, &finished)),
+        };
+        // All tasks have finished, once the scope ends.
+        drop(done);
+        for (index, _buffer, result) in finished.try_iter() {
+            run.record(index, result);
+        }
+        fetched?;
+        Ok(run
+            .results
+            .into_iter()
+            .map(|result| result.expect("Every batch must have been processed."))
+            .collect())
+    }
+}
+
+/// Waits for the next batch to be processed, while every buffer is in flight. A thread of the
+/// pool must not block, since the pending tasks may be queued to it, so it executes them instead.
+fn wait_for<T>(finished: &mpsc::Receiver<T>) -> T {
+    loop {
+        // The senders of the tasks keep the channel open.
+        let timeout = match rayon::yield_now() {
+            // Not a thread of a pool. The tasks are processed by other threads.
+            None => return finished.recv().unwrap(),
+            Some(Yield::Executed) => Duration::ZERO,
+            // Pending tasks are being processed by other threads.
+            Some(Yield::Idle) => Duration::from_millis(1),
+        };
+        match finished.recv_timeout(timeout) {
+            Ok(processed) => return processed,
+            Err(RecvTimeoutError::Timeout) => (),
+            Err(RecvTimeoutError::Disconnected) => unreachable!("Sender outlives the tasks."),
+        }
+    }
+}
+
+/// A processed batch: its index, the buffer to reuse and the outcome of processing it.
+type Finished<B, R> = (usize, B, thread::Result<R>);
+
+/// State of a single call to [`ParallelFetch::run`].
+struct Run<C, F, R> {
+    max_in_flight: usize,
+    /// `None` only while a buffer is bound to it.
+    cursor: Option<C>,
+    make_buffer: F,
+    num_buffers: usize,
+    /// Results of processing, by batch index. `None` while the batch is in flight.
+    results: Vec<Option<R>>,
+}
+
+impl<C, F, R> Run<C, F, R>
+where
+    C: Cursor,
+    R: Send,
+{
+    fn fetch_all<'s, B>(
+        &mut self,
+        scope: &Scope<'s>,
+        process: &'s (impl Fn(&B) -> R + Sync),
+        done: &mpsc::Sender<Finished<B, R>>,
+        finished: &mpsc::Receiver<Finished<B, R>>,
+    ) -> Result<(), Error>
+    where
+        F: FnMut(&mut C) -> Result<B, Error>,
+        B: RowSetBuffer + Send + 's,
+        R: 's,
+    {
+        let mut free = Vec::new();
+        loop {
+            // Collect the buffers of all batches processed in the meantime.
+            for (index, buffer, result) in finished.try_iter() {
+                self.record(index, result);
+                free.push(buffer);
+            }
+            let buffer = match free.pop() {
+                Some(buffer) => buffer,
+                None if self.num_buffers < self.max_in_flight => {
+                    self.num_buffers += 1;
+                    (self.make_buffer)(self.cursor.as_mut().unwrap())?
+                }
+                None => {
+                    let (index, buffer, result) = wait_for(finished);
+                    self.record(index, result);
+                    buffer
+                }
+            };
+            let mut block_cursor = self.cursor.take().unwrap().bind_buffer(buffer)?;
+            let has_batch = block_cursor.fetch_with_truncation_check(true)?.is_some();
+            let (cursor, buffer) = block_cursor.unbind()?;
+            self.cursor = Some(cursor);
+            if !has_batch {
+                return Ok(());
+            }
+            let index = self.results.len();
+            self.results.push(None);
+            let done = done.clone();
+            scope.spawn(move |_| {
+                let result = panic::catch_unwind(AssertUnwindSafe(|| process(&buffer)));
+                // The receiver lives until all tasks of the scope are finished.
+                done.send((index, buffer, result)).unwrap();
+            });
+        }
+    }
+
+    /// Stores the result of processing the batch at `index`. Resumes the panic of the task, if
+    /// processing panicked.
+    fn record(&mut self, index: usize, result: thread::Result<R>) {
+        match result {
+            Ok(result) => self.results[index] = Some(result),
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    }
+}
@@ -57,6 +57,10 @@
 //! # Ok::<(), odbc_api::Error>(())
 //! ```
 //!
+//! Any input parameter can be annotated this way. [`crate::IntoParameter::with_data_type`] is a
+//! shorthand, which also works for types like `&str`, which need to be converted into a parameter
+//! first, e.g. `"1980-01-01".with_data_type(DataType::Date)`.
+//!
 //! In that case it is likely that the driver manager converts our annotated year into a string
 //! which is most likely being converted back into an integer by the driver. All this converting can
 //! be confusing, but it is helpful if we do not know what types the parameters actually have (i.e.
@@ -314,14 +318,17 @@
 //! implemented entirely in safe code, and is a suitable spot to enable support for your custom
 //! types.
 mod blob;
+mod blob_stream;
 mod c_string;
 mod varcell;
 
 pub use self::{
     blob::{Blob, BlobParam, BlobRead, BlobSlice},
+    blob_stream::{blob_channel, BlobProgress, BlobReceiver, BlobSender, ChunkedBlob},
     varcell::{
         VarBinary, VarBinaryArray, VarBinaryBox, VarBinarySlice, VarBinarySliceMut, VarCell,
-        VarChar, VarCharArray, VarCharBox, VarCharSlice, VarCharSliceMut, VarKind, Binary, Text
+        VarChar, VarCharArray, VarCharBox, VarCharSlice, VarCharSliceMut, VarKind, VarWChar,
+        VarWCharArray, VarWCharSliceMut, Binary, Text, WideText
     },
 };
 
@@ -453,6 +460,14 @@ pub struct WithDataType<T> {
     pub data_type: DataType,
 }
 
+impl<T> WithDataType<T> {
+    /// Binds `value` as `data_type`. Any input parameter can be wrapped, see also
+    /// [`crate::IntoParameter::with_data_type`].
+    pub fn new(value: T, data_type: DataType) -> Self {
+        Self { value, data_type }
+    }
+}
+
 unsafe impl<T> CData for WithDataType<T>
 where
     T: CData,
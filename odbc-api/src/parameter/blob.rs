@@ -32,6 +32,11 @@ pub unsafe trait Blob: HasDataType {
 
     /// Retrieve the netxt batch of data from the source. Batches may not be empty. `None` indicates
     /// the last batch has been reached.
+    ///
+    /// Fail with [`io::ErrorKind::WouldBlock`] if the next batch is not available yet, e.g. because
+    /// it is still being received over the network. Execution pauses and asks again later, for
+    /// asynchronous execution after awaiting the next poll of its [`crate::Sleep`], for blocking
+    /// execution after sleeping for a millisecond. See [`super::blob_channel`].
     fn next_batch(&mut self) -> io::Result<Option<&[u8]>>;
 
     /// Convinience function. Same as calling [`self::BlobParam::new`].
@@ -334,7 +339,12 @@ impl BlobRead<BufReader<File>> {
     /// ```
     pub fn from_path(path: &Path) -> io::Result<Self> {
         let file = File::open(path)?;
-        let size = file.metadata()?.len().try_into().unwrap();
+        let size = file.metadata()?.len().try_into().map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "File is too large to be addressed on this platform.",
+            )
+        })?;
         let buf_read = BufReader::new(file);
         Ok(Self {
             consume: 0,
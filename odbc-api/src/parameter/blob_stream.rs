@@ -0,0 +1,376 @@
+use std::{
+    collections::VecDeque,
+    future::poll_fn,
+    io,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    task::{Poll, Waker},
+};
+
+use odbc_sys::CDataType;
+
+use crate::{handles::HasDataType, DataType};
+
+use super::Blob;
+
+/// Counts the data put to the driver by a [`ChunkedBlob`]. Shared, so progress can be observed
+/// from another thread while the statement is executing.
+#[derive(Debug, Default)]
+pub struct BlobProgress {
+    bytes: AtomicU64,
+    chunks: AtomicU64,
+}
+
+impl BlobProgress {
+    /// Number of bytes the driver accepted so far.
+    pub fn bytes_pushed(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    /// Number of calls to `SQLPutData` which succeeded so far.
+    pub fn chunks_pushed(&self) -> u64 {
+        self.chunks.load(Ordering::Relaxed)
+    }
+
+    fn record(&self, chunk_len: usize) {
+        self.bytes.fetch_add(chunk_len as u64, Ordering::Relaxed);
+        self.chunks.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Puts the data of another [`Blob`] to the driver in chunks of a fixed size, regardless of the
+/// size of the batches it yields. Large batches are split and small ones are combined, so each
+/// call to `SQLPutData` transfers exactly `chunk_size` bytes, except for the last one. Keeps
+/// count of the data the driver accepted in a [`BlobProgress`].
+///
+/// ```no_run
+/// use std::{fs::File, io::BufReader};
+/// use odbc_api::{Connection, Error, parameter::{Blob, BlobRead, ChunkedBlob}};
+///
+/// fn insert_archive(conn: &Connection<'_>, file: File) -> Result<(), Error> {
+///     let blob = BlobRead::with_upper_bound(BufReader::new(file), usize::MAX);
+///     let mut chunked = ChunkedBlob::new(blob, 4 * 1024 * 1024);
+///     let progress = chunked.progress();
+///     conn.execute("INSERT INTO Archives (data) VALUES (?)", &mut chunked.as_blob_param())?;
+///     println!("Transferred {} bytes", progress.bytes_pushed());
+///     Ok(())
+/// }
+/// ```
+pub struct ChunkedBlob<B> {
+    blob: B,
+    chunk_size: usize,
+    /// Chunk handed to the driver by the last call to `next_batch`.
+    chunk: Vec<u8>,
+    /// Rest of the last batch of `blob`, which did not fit into the chunk.
+    carry: Vec<u8>,
+    /// Position of the first byte in `carry`, which has not been put into a chunk yet.
+    carry_pos: usize,
+    progress: Arc<BlobProgress>,
+}
+
+impl<B> ChunkedBlob<B> {
+    /// Puts the data of `blob` in chunks of `chunk_size` bytes.
+    ///
+    /// # Panics
+    ///
+    /// If `chunk_size` is zero.
+    pub fn new(blob: B, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "Chunk size must be at least one byte.");
+        Self {
+            blob,
+            chunk_size,
+            chunk: Vec::new(),
+            carry: Vec::new(),
+            carry_pos: 0,
+            progress: Arc::new(BlobProgress::default()),
+        }
+    }
+
+    /// Progress of putting the data, shared with this blob.
+    pub fn progress(&self) -> Arc<BlobProgress> {
+        self.progress.clone()
+    }
+
+    /// Releases the wrapped blob.
+    pub fn into_inner(self) -> B {
+        self.blob
+    }
+}
+
+impl<B> HasDataType for ChunkedBlob<B>
+where
+    B: Blob,
+{
+    fn data_type(&self) -> DataType {
+        self.blob.data_type()
+    }
+}
+
+unsafe impl<B> Blob for ChunkedBlob<B>
+where
+    B: Blob,
+{
+    fn c_data_type(&self) -> CDataType {
+        self.blob.c_data_type()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.blob.size_hint()
+    }
+
+    fn next_batch(&mut self) -> io::Result<Option<&[u8]>> {
+        // The driver asks for the next chunk only after it accepted the previous one.
+        if !self.chunk.is_empty() {
+            self.progress.record(self.chunk.len());
+            self.chunk.clear();
+        }
+        while self.chunk.len() < self.chunk_size {
+            let space = self.chunk_size - self.chunk.len();
+            if self.carry_pos < self.carry.len() {
+                let end = self.carry.len().min(self.carry_pos + space);
+                self.chunk
+                    .extend_from_slice(&self.carry[self.carry_pos..end]);
+                self.carry_pos = end;
+                continue;
+            }
+            match self.blob.next_batch() {
+                Ok(Some(batch)) => {
+                    let (head, tail) = batch.split_at(batch.len().min(space));
+                    self.chunk.extend_from_slice(head);
+                    self.carry.clear();
+                    self.carry.extend_from_slice(tail);
+                    self.carry_pos = 0;
+                }
+                Ok(None) => break,
+                // Put what we have, rather than waiting for the source to fill the chunk.
+                Err(error)
+                    if error.kind() == io::ErrorKind::WouldBlock && !self.chunk.is_empty() =>
+                {
+                    break
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        if self.chunk.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(&self.chunk))
+        }
+    }
+}
+
+/// Creates a [`Blob`] fed asynchronously, e.g. from a network stream, and the sender feeding it.
+/// The channel holds at most `capacity` chunks. Once it is full, [`BlobSender::send`] waits until
+/// the driver accepted a chunk, so the source is never read faster than the data source can take
+/// the data. While the channel is empty, execution pauses between the calls to `SQLPutData`.
+/// `upper_bound` is used to describe the parameter as `LONGVARBINARY` to the driver.
+///
+/// Combine this with [`crate::Connection::execute_polling`], so neither side blocks the thread
+/// of the other. Blocking execution works, too, if the sender runs on another thread. It checks
+/// for new chunks every millisecond, while the channel is empty.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use odbc_api::{Connection, Error, parameter::{blob_channel, Blob}};
+///
+/// async fn upload(conn: &Connection<'_>, parts: Vec<Vec<u8>>) -> Result<(), Error> {
+///     let (mut sender, mut receiver) = blob_channel(4, 1024 * 1024 * 1024);
+///     let feed = async move {
+///         for part in parts {
+///             sender.send(part).await.unwrap();
+///         }
+///         // Dropping the sender marks the end of the blob.
+///     };
+///     let sleep = || tokio::time::sleep(Duration::from_millis(10));
+///     let mut param = receiver.as_blob_param();
+///     let insert = conn.execute_polling("INSERT INTO Uploads (data) VALUES (?)", &mut param, sleep);
+///     let (result, ()) = tokio::join!(insert, feed);
+///     result?;
+///     Ok(())
+/// }
+/// ```
+pub fn blob_channel(capacity: usize, upper_bound: usize) -> (BlobSender, BlobReceiver) {
+    assert!(
+        capacity > 0,
+        "Capacity of the blob channel must be at least one."
+    );
+    let shared = Arc::new(Mutex::new(ChannelState {
+        chunks: VecDeque::with_capacity(capacity),
+        capacity,
+        sender_alive: true,
+        receiver_alive: true,
+        sender_waker: None,
+    }));
+    let sender = BlobSender {
+        shared: shared.clone(),
+    };
+    let receiver = BlobReceiver {
+        shared,
+        upper_bound,
+        current: Vec::new(),
+    };
+    (sender, receiver)
+}
+
+/// State shared between the [`BlobSender`] and [`BlobReceiver`] of a [`blob_channel`].
+struct ChannelState {
+    chunks: VecDeque<Vec<u8>>,
+    capacity: usize,
+    sender_alive: bool,
+    receiver_alive: bool,
+    /// Wakes the sender, once there is space in the channel.
+    sender_waker: Option<Waker>,
+}
+
+impl ChannelState {
+    fn wake_sender(&mut self) {
+        if let Some(waker) = self.sender_waker.take() {
+            waker.wake()
+        }
+    }
+}
+
+/// Feeds chunks to the [`BlobReceiver`] of a [`blob_channel`]. Dropping it marks the end of the
+/// blob.
+pub struct BlobSender {
+    shared: Arc<Mutex<ChannelState>>,
+}
+
+impl BlobSender {
+    /// Appends `chunk` to the blob, waiting for space in the channel, if it is full. Empty chunks
+    /// are ignored. Fails with [`io::ErrorKind::BrokenPipe`] if the receiver has been dropped, e.g.
+    /// because the execution failed.
+    pub async fn send(&mut self, chunk: Vec<u8>) -> io::Result<()> {
+        if chunk.is_empty() {
+            return Ok(());
+        }
+        let mut chunk = Some(chunk);
+        poll_fn(|cx| {
+            let mut state = self.shared.lock().unwrap();
+            if !state.receiver_alive {
+                return Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::BrokenPipe,
+                    "Receiver of the blob channel has been dropped.",
+                )));
+            }
+            if state.chunks.len() < state.capacity {
+                state.chunks.push_back(chunk.take().unwrap());
+                Poll::Ready(Ok(()))
+            } else {
+                state.sender_waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+impl Drop for BlobSender {
+    fn drop(&mut self) {
+        self.shared.lock().unwrap().sender_alive = false;
+    }
+}
+
+/// A [`Blob`] streaming the chunks sent by the [`BlobSender`] of a [`blob_channel`].
+pub struct BlobReceiver {
+    shared: Arc<Mutex<ChannelState>>,
+    upper_bound: usize,
+    /// Chunk handed to the driver by the last call to `next_batch`.
+    current: Vec<u8>,
+}
+
+impl Drop for BlobReceiver {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock().unwrap();
+        state.receiver_alive = false;
+        state.wake_sender();
+    }
+}
+
+impl HasDataType for BlobReceiver {
+    fn data_type(&self) -> DataType {
+        DataType::LongVarbinary {
+            length: self.upper_bound,
+        }
+    }
+}
+
+unsafe impl Blob for BlobReceiver {
+    fn c_data_type(&self) -> CDataType {
+        CDataType::Binary
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        None
+    }
+
+    fn next_batch(&mut self) -> io::Result<Option<&[u8]>> {
+        let mut state = self.shared.lock().unwrap();
+        match state.chunks.pop_front() {
+            Some(chunk) => {
+                state.wake_sender();
+                self.current = chunk;
+                Ok(Some(&self.current))
+            }
+            None if state.sender_alive => Err(io::ErrorKind::WouldBlock.into()),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        future::Future,
+        io,
+        pin::pin,
+        task::{Context, Poll, Waker},
+    };
+
+    use crate::parameter::{Blob, BlobSlice};
+
+    use super::{blob_channel, ChunkedBlob};
+
+    fn batches(blob: &mut impl Blob) -> Vec<Vec<u8>> {
+        let mut batches = Vec::new();
+        while let Some(batch) = blob.next_batch().unwrap() {
+            batches.push(batch.to_vec());
+        }
+        batches
+    }
+
+    #[test]
+    fn split_and_combine_batches() {
+        let mut slice = BlobSlice::from_byte_slice(b"abcdefgh");
+        slice.batch_size = 3;
+        let mut chunked = ChunkedBlob::new(slice, 4);
+        let progress = chunked.progress();
+
+        let batches = batches(&mut chunked);
+
+        assert_eq!(vec![b"abcd".to_vec(), b"efgh".to_vec()], batches);
+        assert_eq!(8, progress.bytes_pushed());
+        assert_eq!(2, progress.chunks_pushed());
+    }
+
+    #[test]
+    fn sender_waits_for_space_in_channel() {
+        let (mut sender, mut receiver) = blob_channel(1, 100);
+        let mut cx = Context::from_waker(Waker::noop());
+
+        assert!(matches!(
+            pin!(sender.send(b"a".to_vec())).poll(&mut cx),
+            Poll::Ready(Ok(()))
+        ));
+        assert!(pin!(sender.send(b"b".to_vec())).poll(&mut cx).is_pending());
+        assert_eq!(Some(&b"a"[..]), receiver.next_batch().unwrap());
+        assert_eq!(
+            io::ErrorKind::WouldBlock,
+            receiver.next_batch().unwrap_err().kind()
+        );
+        drop(sender);
+        assert_eq!(None, receiver.next_batch().unwrap());
+    }
+}
@@ -9,7 +9,7 @@ use odbc_sys::{CDataType, NULL_DATA};
 use crate::{
     buffers::Indicator,
     handles::{CData, CDataMut, HasDataType},
-    DataType, OutputParameter,
+    DataType, Error, OutputParameter,
 };
 
 use super::CElement;
@@ -56,6 +56,21 @@ unsafe impl VarKind for Binary {
     }
 }
 
+/// Intended to be used as a generic argument for [`VariadicCell`] to declare that this buffer is
+/// used to hold wide UTF-16 text. The buffer is still a byte buffer, two bytes make up one UTF-16
+/// code unit in platform endianness. Use [`crate::Utf16ChunkJoiner`] to stream text in chunks
+/// without splitting surrogate pairs.
+pub struct WideText;
+
+unsafe impl VarKind for WideText {
+    const TERMINATING_ZEROES: usize = 2;
+    const C_DATA_TYPE: CDataType = CDataType::WChar;
+
+    fn relational_type(length: usize) -> DataType {
+        DataType::WVarchar { length: length / 2 }
+    }
+}
+
 /// Binds a byte array as Variadic sized character data. It can not be used for columnar bulk
 /// fetches, but if the buffer type is stack allocated it can be utilized in row wise bulk fetches.
 ///
@@ -88,6 +103,7 @@ pub struct VarCell<B, K> {
 
 pub type VarBinary<B> = VarCell<B, Binary>;
 pub type VarChar<B> = VarCell<B, Text>;
+pub type VarWChar<B> = VarCell<B, WideText>;
 
 /// Parameter type for owned, variable sized character data.
 ///
@@ -133,21 +149,35 @@ where
 {
     /// Creates a new instance from an existing buffer. For text should the indicator be `NoTotal`
     /// or indicate a length longer than buffer, the last element in the buffer must be nul (`\0`).
+    ///
+    /// # Panics
+    ///
+    /// If the value is truncated, but not terminated with zero. See [`Self::try_from_buffer`].
     pub fn from_buffer(buffer: B, indicator: Indicator) -> Self {
+        match Self::try_from_buffer(buffer, indicator) {
+            Ok(cell) => cell,
+            Err(error) => panic!("{error}"),
+        }
+    }
+
+    /// Like [`Self::from_buffer`], but fails with [`Error::UnterminatedTruncatedValue`] rather
+    /// than panicking, if the indicator implies truncation and the buffer does not end with the
+    /// terminating zero. Useful if buffer and indicator originate from outside the application.
+    pub fn try_from_buffer(buffer: B, indicator: Indicator) -> Result<Self, Error> {
         let buf = buffer.borrow();
         if indicator.is_truncated(buf.len()) {
             // Value is truncated. Let's check that all required terminating zeroes are at the end
             // of the buffer.
             if !ends_in_zeroes(buf, K::TERMINATING_ZEROES) {
-                panic!("Truncated value must be terminated with zero.")
+                return Err(Error::UnterminatedTruncatedValue);
             }
         }
 
-        Self {
+        Ok(Self {
             buffer,
             indicator: indicator.to_isize(),
             kind: PhantomData,
-        }
+        })
     }
 
     /// Valid payload of the buffer (excluding terminating zeroes) returned as slice or `None` in
@@ -229,7 +259,13 @@ where
     /// [`Indicator::NoTotal`] implies that the content of the current buffer is valid up to its
     /// maximum capacity.
     pub fn indicator(&self) -> Indicator {
-        Indicator::from_isize(self.indicator)
+        Indicator::from_validated(self.indicator)
+    }
+
+    /// Like [`Self::indicator`], but fails with [`Error::InvalidDriverValue`] if the driver wrote
+    /// an invalid indicator value.
+    pub(crate) fn try_indicator(&self) -> Result<Indicator, Error> {
+        Indicator::try_from_isize(self.indicator)
     }
 
     /// The payload in bytes the buffer can hold including terminating zeroes
@@ -372,6 +408,9 @@ pub type VarCharSliceMut<'a> = VarChar<&'a mut [u8]>;
 /// Wraps a slice so it can be used as an output parameter for binary data.
 pub type VarBinarySliceMut<'a> = VarBinary<&'a mut [u8]>;
 
+/// Wraps a slice so it can be used as an output parameter for wide character data.
+pub type VarWCharSliceMut<'a> = VarWChar<&'a mut [u8]>;
+
 /// A stack allocated VARCHAR type.
 ///
 /// Due to its memory layout this type can be bound either as a single parameter, or as a column of
@@ -384,6 +423,12 @@ pub type VarCharArray<const LENGTH: usize> = VarChar<[u8; LENGTH]>;
 /// a row-by-row output, but not be used in columnar parameter arrays or output buffers.
 pub type VarBinaryArray<const LENGTH: usize> = VarBinary<[u8; LENGTH]>;
 
+/// A stack allocated wide character VARCHAR type. `LENGTH` is in bytes.
+///
+/// Due to its memory layout this type can be bound either as a single parameter, or as a column of
+/// a row-by-row output, but not be used in columnar parameter arrays or output buffers.
+pub type VarWCharArray<const LENGTH: usize> = VarWChar<[u8; LENGTH]>;
+
 impl<const LENGTH: usize, K: VarKind> VarCell<[u8; LENGTH], K> {
     /// Indicates a missing value.
     pub const NULL: Self = Self {
@@ -440,6 +485,8 @@ unsafe impl<K: VarKind> OutputParameter for VarCell<Box<[u8]>, K> {}
 #[cfg(test)]
 mod tests {
 
+    use crate::Error;
+
     use super::{Indicator, VarCharSlice};
 
     #[test]
@@ -456,4 +503,25 @@ mod tests {
         // Not fine, value is too long, but not terminated by zero
         VarCharSlice::from_buffer(b"12345", Indicator::Length(10));
     }
+
+    #[test]
+    fn try_from_buffer_reports_unterminated_truncated_values() {
+        let result = VarCharSlice::try_from_buffer(b"12345", Indicator::Length(10));
+
+        assert!(matches!(result, Err(Error::UnterminatedTruncatedValue)));
+        assert!(VarCharSlice::try_from_buffer(b"1234\0", Indicator::NoTotal).is_ok());
+    }
+
+    #[test]
+    fn invalid_indicator_from_driver() {
+        let result = Indicator::try_from_isize(-2);
+
+        assert!(matches!(
+            result,
+            Err(Error::InvalidDriverValue {
+                description: "length indicator",
+                value: -2
+            })
+        ));
+    }
 }
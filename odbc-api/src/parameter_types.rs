@@ -0,0 +1,192 @@
+use std::ffi::c_void;
+
+use odbc_sys::{CDataType, HStmt, Handle, HandleType, ParamType};
+
+use crate::{
+    handles::{
+        AsHandle, CData, CDataMut, DelayedInput, ExecutionStatus, HasDataType, SqlResult, Statement,
+    },
+    DataType, Quirks,
+};
+
+/// Binds parameters with the SQL data types specified in `overrides`, rather than the ones
+/// reported by the parameters themselves. The element at index `0` applies to the first parameter.
+/// Parameters without an override (`None`, or beyond the end of `overrides`) keep their own type.
+/// See [`crate::Prepared::with_parameter_types`]. Types are adjusted to work around `quirks` of
+/// the driver, too.
+pub struct RetypingStatement<'a, S> {
+    statement: &'a mut S,
+    overrides: &'a [Option<DataType>],
+    quirks: Quirks,
+}
+
+impl<'a, S> RetypingStatement<'a, S> {
+    pub fn new(statement: &'a mut S, overrides: &'a [Option<DataType>], quirks: Quirks) -> Self {
+        Self {
+            statement,
+            overrides,
+            quirks,
+        }
+    }
+
+    fn data_type(&self, parameter_number: u16, own: DataType) -> DataType {
+        let data_type = self
+            .overrides
+            .get(parameter_number as usize - 1)
+            .copied()
+            .flatten()
+            .unwrap_or(own);
+        self.quirks.parameter_type(data_type)
+    }
+}
+
+unsafe impl<'a, S> AsHandle for RetypingStatement<'a, S>
+where
+    S: AsHandle,
+{
+    fn as_handle(&self) -> Handle {
+        self.statement.as_handle()
+    }
+
+    fn handle_type(&self) -> HandleType {
+        self.statement.handle_type()
+    }
+}
+
+impl<'a, S> Statement for RetypingStatement<'a, S>
+where
+    S: Statement,
+{
+    fn as_sys(&self) -> HStmt {
+        self.statement.as_sys()
+    }
+
+    fn execution_status(&self) -> Option<&ExecutionStatus> {
+        self.statement.execution_status()
+    }
+
+    unsafe fn bind_input_parameter(
+        &mut self,
+        parameter_number: u16,
+        parameter: &(impl HasDataType + CData + ?Sized),
+    ) -> SqlResult<()> {
+        let parameter = Retyped {
+            data_type: self.data_type(parameter_number, parameter.data_type()),
+            parameter,
+        };
+        self.statement
+            .bind_input_parameter(parameter_number, &parameter)
+    }
+
+    unsafe fn bind_parameter(
+        &mut self,
+        parameter_number: u16,
+        input_output_type: ParamType,
+        parameter: &mut (impl CDataMut + HasDataType),
+    ) -> SqlResult<()> {
+        let mut parameter = Retyped {
+            data_type: self.data_type(parameter_number, parameter.data_type()),
+            parameter,
+        };
+        self.statement
+            .bind_parameter(parameter_number, input_output_type, &mut parameter)
+    }
+
+    unsafe fn bind_delayed_input_parameter(
+        &mut self,
+        parameter_number: u16,
+        parameter: &mut (impl DelayedInput + HasDataType),
+    ) -> SqlResult<()> {
+        let mut parameter = Retyped {
+            data_type: self.data_type(parameter_number, parameter.data_type()),
+            parameter,
+        };
+        self.statement
+            .bind_delayed_input_parameter(parameter_number, &mut parameter)
+    }
+}
+
+/// A parameter reporting `data_type`, but otherwise forwarding to `parameter`. The pointers to the
+/// buffers of `parameter` are bound, so `Retyped` itself does not need to outlive the binding.
+struct Retyped<P> {
+    parameter: P,
+    data_type: DataType,
+}
+
+impl<P> HasDataType for Retyped<P> {
+    fn data_type(&self) -> DataType {
+        self.data_type
+    }
+}
+
+unsafe impl<P> CData for Retyped<&P>
+where
+    P: CData + ?Sized,
+{
+    fn cdata_type(&self) -> CDataType {
+        self.parameter.cdata_type()
+    }
+
+    fn indicator_ptr(&self) -> *const isize {
+        self.parameter.indicator_ptr()
+    }
+
+    fn value_ptr(&self) -> *const c_void {
+        self.parameter.value_ptr()
+    }
+
+    fn buffer_length(&self) -> isize {
+        self.parameter.buffer_length()
+    }
+}
+
+unsafe impl<P> CData for Retyped<&mut P>
+where
+    P: CData,
+{
+    fn cdata_type(&self) -> CDataType {
+        self.parameter.cdata_type()
+    }
+
+    fn indicator_ptr(&self) -> *const isize {
+        self.parameter.indicator_ptr()
+    }
+
+    fn value_ptr(&self) -> *const c_void {
+        self.parameter.value_ptr()
+    }
+
+    fn buffer_length(&self) -> isize {
+        self.parameter.buffer_length()
+    }
+}
+
+unsafe impl<P> CDataMut for Retyped<&mut P>
+where
+    P: CDataMut,
+{
+    fn mut_indicator_ptr(&mut self) -> *mut isize {
+        self.parameter.mut_indicator_ptr()
+    }
+
+    fn mut_value_ptr(&mut self) -> *mut c_void {
+        self.parameter.mut_value_ptr()
+    }
+}
+
+unsafe impl<P> DelayedInput for Retyped<&mut P>
+where
+    P: DelayedInput,
+{
+    fn cdata_type(&self) -> CDataType {
+        self.parameter.cdata_type()
+    }
+
+    fn indicator_ptr(&self) -> *const isize {
+        self.parameter.indicator_ptr()
+    }
+
+    fn stream_ptr(&mut self) -> *mut c_void {
+        self.parameter.stream_ptr()
+    }
+}
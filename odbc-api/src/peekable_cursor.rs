@@ -0,0 +1,173 @@
+use crate::{
+    buffers::{CellAccessError, ColumnarAnyBuffer, FromCell},
+    handles::AsStatementRef,
+    BlockCursor, Cursor, Error,
+};
+
+/// Iterates the rows fetched by a block cursor one at a time, while allowing to look at the next
+/// row without consuming it. The last row consumed can also be pushed back. This is what run
+/// length grouping algorithms over ordered result sets require, e.g. to flatten master detail
+/// relations, without copying whole batches.
+///
+/// Only the batch the current row is part of is held. Peeking beyond the last row of a batch
+/// fetches the next one. Fetching fails with [`Error::TooLargeValueForBuffer`] if a value has been
+/// truncated.
+///
+/// ```
+/// use odbc_api::{
+///     buffers::{BufferDesc, ColumnarAnyBuffer}, Cursor, Error, PeekableCursor,
+/// };
+///
+/// /// Prints the number of consecutive rows sharing the same value in the first column.
+/// fn print_runs(cursor: impl Cursor) -> Result<(), Error> {
+///     let descs = [BufferDesc::I64 { nullable: false }];
+///     let buffer = ColumnarAnyBuffer::from_descs(1000, descs);
+///     let mut rows = PeekableCursor::new(cursor.bind_buffer(buffer)?);
+///     while let Some(row) = rows.next_row()? {
+///         let key: i64 = row.get(0).unwrap();
+///         let mut run_length = 1;
+///         while let Some(next) = rows.peek_row()? {
+///             if next.get::<i64>(0).unwrap() != key {
+///                 break;
+///             }
+///             rows.next_row()?;
+///             run_length += 1;
+///         }
+///         println!("{key}: {run_length}");
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct PeekableCursor<C: AsStatementRef> {
+    block_cursor: BlockCursor<C, ColumnarAnyBuffer>,
+    /// Number of rows in the current batch.
+    num_rows: usize,
+    /// Index of the next row within the current batch.
+    position: usize,
+    /// `true` once the block cursor reported the end of the result set.
+    exhausted: bool,
+}
+
+impl<C> PeekableCursor<C>
+where
+    C: Cursor,
+{
+    /// Iterates the rows fetched by `block_cursor` one at a time.
+    pub fn new(block_cursor: BlockCursor<C, ColumnarAnyBuffer>) -> Self {
+        Self {
+            block_cursor,
+            num_rows: 0,
+            position: 0,
+            exhausted: false,
+        }
+    }
+
+    /// Looks at the next row, without consuming it. May fetch the next batch, if all rows of the
+    /// current one have been consumed.
+    ///
+    /// # Return
+    ///
+    /// `None` if the result set is consumed.
+    pub fn peek_row(&mut self) -> Result<Option<BufferedRow<'_>>, Error> {
+        if !self.fill()? {
+            return Ok(None);
+        }
+        Ok(Some(BufferedRow {
+            batch: self.block_cursor.buffer(),
+            row_index: self.position,
+        }))
+    }
+
+    /// Consumes the next row. May fetch the next batch, if all rows of the current one have been
+    /// consumed.
+    ///
+    /// # Return
+    ///
+    /// `None` if the result set is consumed.
+    pub fn next_row(&mut self) -> Result<Option<BufferedRow<'_>>, Error> {
+        if !self.fill()? {
+            return Ok(None);
+        }
+        let row_index = self.position;
+        self.position += 1;
+        Ok(Some(BufferedRow {
+            batch: self.block_cursor.buffer(),
+            row_index,
+        }))
+    }
+
+    /// Pushes the row consumed last back, so it is returned again by the next call to
+    /// [`Self::peek_row`] or [`Self::next_row`]. Rows can be pushed back only as long as they are
+    /// part of the current batch. The row consumed last is always part of it, since only peeking
+    /// or consuming the row after it fetches the next batch.
+    ///
+    /// # Panics
+    ///
+    /// If no row of the current batch has been consumed.
+    pub fn push_back(&mut self) {
+        assert!(
+            self.position > 0,
+            "Only rows of the current batch can be pushed back, yet none of them has been \
+            consumed."
+        );
+        self.position -= 1;
+    }
+
+    /// Unbinds the buffer and gives back ownership of the cursor and the buffer. Rows of the
+    /// current batch which have not been consumed are lost.
+    pub fn unbind(self) -> Result<(C, ColumnarAnyBuffer), Error> {
+        self.block_cursor.unbind()
+    }
+
+    /// Fetches batches until the current one holds a row which has not been consumed. `false` if
+    /// the result set is consumed.
+    fn fill(&mut self) -> Result<bool, Error> {
+        while self.position == self.num_rows {
+            if self.exhausted {
+                return Ok(false);
+            }
+            match self.block_cursor.fetch_with_truncation_check(true)? {
+                Some(batch) => self.num_rows = batch.num_rows(),
+                None => {
+                    self.exhausted = true;
+                    self.num_rows = 0;
+                }
+            }
+            self.position = 0;
+        }
+        Ok(true)
+    }
+}
+
+/// A row within the batch currently held by a [`PeekableCursor`].
+#[derive(Clone, Copy)]
+pub struct BufferedRow<'a> {
+    batch: &'a ColumnarAnyBuffer,
+    row_index: usize,
+}
+
+impl<'a> BufferedRow<'a> {
+    /// The batch the row is part of.
+    pub fn batch(&self) -> &'a ColumnarAnyBuffer {
+        self.batch
+    }
+
+    /// Index of the row within [`Self::batch`].
+    pub fn row_index(&self) -> usize {
+        self.row_index
+    }
+
+    /// Number of columns in the row.
+    pub fn num_cols(&self) -> usize {
+        self.batch.num_cols()
+    }
+
+    /// Value of the cell in the column at `buffer_index`. See [`ColumnarAnyBuffer::get`].
+    ///
+    /// # Panics
+    ///
+    /// If `buffer_index` is out of bounds.
+    pub fn get<T: FromCell<'a>>(&self, buffer_index: usize) -> Result<T, CellAccessError> {
+        self.batch.get(self.row_index, buffer_index)
+    }
+}
@@ -2,9 +2,14 @@ use crate::{
     execute::{
         execute_columns, execute_tables, execute_with_parameters, execute_with_parameters_polling,
     },
-    handles::{AsStatementRef, SqlText, Statement, StatementImpl, StatementRef},
+    handles::{
+        registered_context, statement_context, AsStatementRef, SqlText, Statement, StatementImpl,
+        StatementRef,
+    },
+    result_set_metadata::reported,
     CursorImpl, CursorPolling, Error, ParameterCollectionRef, Sleep,
 };
+use odbc_sys::Handle;
 
 /// A preallocated SQL statement handle intended for sequential execution of different queries. See
 /// [`crate::Connection::preallocate`].
@@ -91,6 +96,39 @@ impl<'o> Preallocated<'o> {
         execute_with_parameters(move || Ok(&mut self.statement), Some(&query), params)
     }
 
+    /// Limits the number of rows returned by result sets for subsequent calls to [`Self::execute`].
+    /// The limit is enforced by the driver or the data source, which makes it more reliable than
+    /// stopping to fetch on the client side, e.g. for previewing results of ad-hoc queries. `None`
+    /// removes the limit.
+    pub fn set_max_rows(&mut self, max_rows: Option<usize>) -> Result<(), Error> {
+        self.statement
+            .set_max_rows(max_rows.unwrap_or(0))
+            .into_result(&self.statement)
+    }
+
+    /// Limits the length in bytes of character and binary values for subsequent calls to
+    /// [`Self::execute`]. Longer values are cut off by the driver silently. `None` removes the
+    /// limit.
+    pub fn set_max_length(&mut self, max_length: Option<usize>) -> Result<(), Error> {
+        self.statement
+            .set_max_length(max_length.unwrap_or(0))
+            .into_result(&self.statement)
+    }
+
+    /// Attaches a key value pair to errors of this statement, in addition to the context of its
+    /// connection. See [`crate::Connection::set_context`]. Overrides the value the connection sets
+    /// for the same key.
+    pub fn set_context(&mut self, key: &str, value: &str) {
+        statement_context(self.statement.as_sys() as Handle).set(key, value)
+    }
+
+    /// Removes `key` from the context set with [`Self::set_context`].
+    pub fn remove_context(&mut self, key: &str) {
+        if let Some(context) = registered_context(self.statement.as_sys() as Handle) {
+            context.remove(key)
+        }
+    }
+
     /// Transfer ownership to the underlying statement handle.
     ///
     /// The resulting type is one level of indirection away from the raw pointer of the ODBC API. It
@@ -184,17 +222,13 @@ impl<'o> Preallocated<'o> {
     /// }
     /// ```
     pub fn row_count(&mut self) -> Result<Option<usize>, Error> {
-        self.statement
-            .row_count()
-            .into_result(&self.statement)
-            .map(|count| {
-                // ODBC returns -1 in case a row count is not available
-                if count == -1 {
-                    None
-                } else {
-                    Some(count.try_into().unwrap())
-                }
-            })
+        let count = self.statement.row_count().into_result(&self.statement)?;
+        // ODBC returns -1 in case a row count is not available
+        if count == -1 {
+            Ok(None)
+        } else {
+            reported(count, "row count").map(Some)
+        }
     }
 
     /// Call this method to enable asynchronous polling mode on the statement
@@ -1,19 +1,102 @@
 use crate::{
     buffers::{AnyBuffer, BufferDesc, ColumnBuffer, TextColumn},
-    execute::execute_with_parameters,
-    handles::{AsStatementRef, HasDataType, ParameterDescription, Statement, StatementRef},
-    ColumnarBulkInserter, CursorImpl, Error, ParameterCollectionRef, ResultSetMetadata,
+    error::TooLargeBufferSize,
+    execute::execute_prepared,
+    execute_many::{merge_buffer_descs, BatchError, ExecuteManyReport, ParameterRow},
+    handles::{
+        registered_context, statement_context, AsStatementRef, HasDataType, ParameterDescription,
+        Statement, StatementRef,
+    },
+    parameter::{VarCharSlice, WithDataType},
+    result_set_metadata::reported,
+    ColumnarBulkInserter, CursorImpl, DataType, Error, ParameterCollectionRef, Quirks,
+    RePreparePolicy, ResultSetMetadata, ResultSetSchema, StatementConnection,
 };
+use odbc_sys::Handle;
 
 /// A prepared query. Prepared queries are useful if the similar queries should executed more than
 /// once. See [`crate::Connection::prepare`].
 pub struct Prepared<S> {
     statement: S,
+    /// SQL data types used to bind parameters instead of their own. See
+    /// [`Self::with_parameter_types`].
+    parameter_types: Vec<Option<DataType>>,
+    /// Text of the statement, used to prepare it again. See [`Self::with_re_prepare_policy`].
+    query: String,
+    re_prepare: RePreparePolicy,
+    /// Known bugs of the driver, worked around then binding parameters. See
+    /// [`crate::Connection::quirks`].
+    quirks: Quirks,
+    /// Described on first use. See [`Self::result_set_schema`].
+    schema: Option<ResultSetSchema>,
 }
 
 impl<S> Prepared<S> {
-    pub(crate) fn new(statement: S) -> Self {
-        Self { statement }
+    pub(crate) fn new(statement: S, query: &str, quirks: Quirks) -> Self {
+        Self {
+            statement,
+            parameter_types: Vec::new(),
+            query: query.to_owned(),
+            re_prepare: RePreparePolicy::Never,
+            quirks,
+            schema: None,
+        }
+    }
+
+    /// Binds the parameters passed to [`Self::execute`] with these SQL data types, rather than the
+    /// ones the parameters report themselves. The first element applies to the first placeholder,
+    /// and so on. Parameters beyond the end of `parameter_types` keep their own type. Useful if the
+    /// driver or data source picks an unfortunate conversion for the type of a parameter.
+    ///
+    /// The C type of the parameter buffer is not affected, so the driver converts the value from
+    /// the C type to the specified SQL data type. Only applies to [`Self::execute`]. Replaces
+    /// overrides specified earlier.
+    ///
+    /// ```no_run
+    /// use odbc_api::{Connection, DataType, Error, IntoParameter};
+    ///
+    /// fn insert_event(conn: &Connection<'_>, happened_at: &str) -> Result<(), Error> {
+    ///     let mut prepared = conn
+    ///         .prepare("INSERT INTO Events (happened_at) VALUES (?)")?
+    ///         .with_parameter_types(&[DataType::Timestamp { precision: 6 }]);
+    ///     prepared.execute(&happened_at.into_parameter())?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_parameter_types(mut self, parameter_types: &[DataType]) -> Self {
+        self.parameter_types = parameter_types.iter().copied().map(Some).collect();
+        self
+    }
+
+    /// SQL data types overriding the types of the parameters. See [`Self::with_parameter_types`].
+    pub fn parameter_types(&self) -> &[Option<DataType>] {
+        &self.parameter_types
+    }
+
+    /// Prepares the statement again and retries, if [`Self::execute`] fails with an error
+    /// `re_prepare` applies to. Keeps long lived prepared statements working across schema
+    /// changes, which invalidate their metadata. By default errors are returned as they are.
+    ///
+    /// ```no_run
+    /// use odbc_api::{Connection, Error, IntoParameter, RePreparePolicy};
+    ///
+    /// fn count_orders(conn: &Connection<'_>, customer: i32) -> Result<(), Error> {
+    ///     let mut prepared = conn
+    ///         .prepare("SELECT COUNT(*) FROM Orders WHERE customer = ?")?
+    ///         .with_re_prepare_policy(RePreparePolicy::OnStaleMetadata);
+    ///     prepared.execute(&customer)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_re_prepare_policy(mut self, re_prepare: RePreparePolicy) -> Self {
+        self.re_prepare = re_prepare;
+        self
+    }
+
+    /// Decides whether the statement is prepared again, if execution fails. See
+    /// [`Self::with_re_prepare_policy`].
+    pub fn re_prepare_policy(&self) -> &RePreparePolicy {
+        &self.re_prepare
     }
 
     /// Transfer ownership to the underlying statement handle.
@@ -33,6 +116,30 @@ impl<S> Prepared<S>
 where
     S: AsStatementRef,
 {
+    /// Binds the parameter at `parameter_number` (starting at 1) with `data_type` in subsequent
+    /// calls to [`Self::execute`], or with its own type again, if `data_type` is `None`. See
+    /// [`Self::with_parameter_types`].
+    ///
+    /// Fails with [`Error::InvalidArgument`] if `parameter_number` is zero or larger than the
+    /// number of placeholders of the statement.
+    pub fn set_parameter_type(
+        &mut self,
+        parameter_number: u16,
+        data_type: Option<DataType>,
+    ) -> Result<(), Error> {
+        if parameter_number == 0 || parameter_number > self.num_params()? {
+            return Err(Error::InvalidArgument {
+                description: "parameter number must be between one and the number of parameters",
+            });
+        }
+        let index = parameter_number as usize - 1;
+        if self.parameter_types.len() <= index {
+            self.parameter_types.resize(index + 1, None);
+        }
+        self.parameter_types[index] = data_type;
+        Ok(())
+    }
+
     /// Execute the prepared statement.
     ///
     /// * `params`: Used to bind these parameters before executing the statement. You can use `()`
@@ -40,12 +147,145 @@ where
     ///   specify a parameter set size of `0`, nothing is executed, and `Ok(None)` is returned. See
     ///   the [`crate::parameter`] module level documentation for more information on how to pass
     ///   parameters.
+    ///
+    /// Depending on the [`RePreparePolicy`], the statement is prepared again and the execution
+    /// retried once, if it fails. In this case the schema cached by [`Self::result_set_schema`] is
+    /// discarded. See [`Self::with_re_prepare_policy`].
     pub fn execute(
         &mut self,
         params: impl ParameterCollectionRef,
     ) -> Result<Option<CursorImpl<StatementRef<'_>>>, Error> {
         let stmt = self.statement.as_stmt_ref();
-        execute_with_parameters(move || Ok(stmt), None, params)
+        execute_prepared(
+            stmt,
+            params,
+            &self.parameter_types,
+            &self.query,
+            &self.re_prepare,
+            &self.quirks,
+            // The result set may have changed along with the schema of the underlying tables.
+            || self.schema = None,
+        )
+    }
+
+    /// Schema of the result set the statement produces, as described by the driver after preparing
+    /// it. No data is moved, since the statement is not executed. Statements which do not produce
+    /// a result set, e.g. `INSERT`, have a schema without columns. Described once and cached, so
+    /// subsequent calls do not involve the driver. See also
+    /// [`crate::Connection::prepare_with_metadata`].
+    ///
+    /// Some drivers are not able to describe a result set before execution. Depending on the
+    /// driver this either fails, or the driver executes the statement internally.
+    pub fn result_set_schema(&mut self) -> Result<&ResultSetSchema, Error> {
+        if self.schema.is_none() {
+            self.schema = Some(ResultSetSchema::for_cursor(self)?);
+        }
+        Ok(self.schema.as_ref().unwrap())
+    }
+
+    /// Executes the statement once with all parameters set to `NULL` and discards the result set,
+    /// so the driver and the data source compile and cache the execution plan upfront, e.g. at
+    /// service startup, rather than on the first request. Returns `true` if the statement has been
+    /// executed.
+    ///
+    /// To be safe, only statements describing a result set are executed. Others, like `INSERT`,
+    /// `UPDATE` or `DELETE` are left alone and `false` is returned. Statements modifying data and
+    /// returning a result set at the same time, e.g. `INSERT ... RETURNING`, must not be warmed up.
+    ///
+    /// ```no_run
+    /// use odbc_api::{Connection, Error};
+    ///
+    /// fn prepare_lookup(conn: &Connection<'_>) -> Result<(), Error> {
+    ///     let mut lookup = conn.prepare("SELECT name FROM Users WHERE id = ?")?;
+    ///     lookup.warm_up()?;
+    ///     // ... store `lookup` and serve requests with it ...
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn warm_up(&mut self) -> Result<bool, Error> {
+        if self.num_result_cols()? == 0 {
+            return Ok(false);
+        }
+        let describe = !self.quirks.describe_param_unreliable;
+        let nulls = (1..=self.num_params()?)
+            .map(|parameter_number| {
+                // Text can be converted into any SQL type. Use the type of the parameter anyway, so
+                // the plan is chosen for it. Not all drivers are able to describe parameters.
+                let description = describe.then(|| self.describe_param(parameter_number));
+                let data_type = match description {
+                    Some(Ok(description)) if description.data_type != DataType::Unknown => {
+                        description.data_type
+                    }
+                    _ => VarCharSlice::NULL.data_type(),
+                };
+                WithDataType::new(VarCharSlice::NULL, data_type)
+            })
+            .collect::<Vec<_>>();
+        // Dropping the cursor discards the result set.
+        self.execute(&nulls[..])?;
+        Ok(true)
+    }
+
+    /// Limits the number of rows returned by result sets of subsequent executions. The limit is
+    /// enforced by the driver or the data source. `None` removes the limit.
+    pub fn set_max_rows(&mut self, max_rows: Option<usize>) -> Result<(), Error> {
+        let mut stmt = self.statement.as_stmt_ref();
+        stmt.set_max_rows(max_rows.unwrap_or(0)).into_result(&stmt)
+    }
+
+    /// Limits the length in bytes of character and binary values returned by subsequent
+    /// executions. Longer values are cut off by the driver silently. `None` removes the limit.
+    pub fn set_max_length(&mut self, max_length: Option<usize>) -> Result<(), Error> {
+        let mut stmt = self.statement.as_stmt_ref();
+        stmt.set_max_length(max_length.unwrap_or(0))
+            .into_result(&stmt)
+    }
+
+    /// Attaches a key value pair to errors of this statement, in addition to the context of its
+    /// connection. See [`crate::Connection::set_context`]. Overrides the value the connection sets
+    /// for the same key.
+    pub fn set_context(&mut self, key: &str, value: &str) {
+        let stmt = self.statement.as_stmt_ref();
+        statement_context(stmt.as_sys() as Handle).set(key, value)
+    }
+
+    /// Removes `key` from the context set with [`Self::set_context`].
+    pub fn remove_context(&mut self, key: &str) {
+        let stmt = self.statement.as_stmt_ref();
+        if let Some(context) = registered_context(stmt.as_sys() as Handle) {
+            context.remove(key)
+        }
+    }
+
+    /// Returns the statement to a clean state, so it can be reused as if it has just been prepared.
+    /// Resetting
+    ///
+    /// * closes the cursor, if any, discarding pending results,
+    /// * unbinds all column buffers and parameters,
+    /// * resets the parameter set size and row array size to one and binding to column wise,
+    /// * unbinds offsets set with [`Statement::set_row_bind_offset`] and
+    ///   [`Statement::set_param_bind_offset`],
+    /// * removes limits set with [`Self::set_max_rows`] and [`Self::set_max_length`].
+    ///
+    /// The prepared query itself is kept, as are vendor specific attributes set with
+    /// [`crate::vendor`].
+    pub fn reset(&mut self) -> Result<(), Error> {
+        let mut stmt = self.statement.as_stmt_ref();
+        stmt.discard_results().into_result(&stmt)?;
+        stmt.unbind_cols().into_result(&stmt)?;
+        stmt.reset_parameters().into_result(&stmt)?;
+        // Safe, since no buffers are bound anymore.
+        unsafe {
+            stmt.set_num_rows_fetched(None).into_result(&stmt)?;
+            stmt.set_row_bind_type(0).into_result(&stmt)?;
+            stmt.set_row_bind_offset(None).into_result(&stmt)?;
+            stmt.set_row_array_size(1).into_result(&stmt)?;
+            stmt.set_param_bind_type(0).into_result(&stmt)?;
+            stmt.set_param_bind_offset(None).into_result(&stmt)?;
+            stmt.set_paramset_size(1).into_result(&stmt)?;
+        }
+        stmt.set_max_rows(0).into_result(&stmt)?;
+        stmt.set_max_length(0).into_result(&stmt)
     }
 
     /// Describes parameter marker associated with a prepared SQL statement.
@@ -163,8 +403,11 @@ where
     ) -> Result<ColumnarBulkInserter<S, TextColumn<u8>>, Error> {
         let max_str_len = max_str_len.into_iter();
         let parameter_buffers = max_str_len
-            .map(|max_str_len| TextColumn::new(capacity, max_str_len))
-            .collect();
+            .map(|max_str_len| {
+                TextColumn::try_new(capacity, max_str_len)
+                    .map_err(TooLargeBufferSize::into_allocation_error)
+            })
+            .collect::<Result<_, _>>()?;
         // Text Columns are created with NULL as default, which is valid for insertion.
         unsafe { self.unchecked_bind_columnar_array_parameters(parameter_buffers) }
     }
@@ -226,8 +469,11 @@ where
     ) -> Result<ColumnarBulkInserter<S, AnyBuffer>, Error> {
         let parameter_buffers = descriptions
             .into_iter()
-            .map(|desc| AnyBuffer::from_desc(capacity, desc))
-            .collect();
+            .map(|desc| {
+                AnyBuffer::try_from_desc(capacity, desc)
+                    .map_err(TooLargeBufferSize::into_allocation_error)
+            })
+            .collect::<Result<_, _>>()?;
         unsafe { self.unchecked_bind_columnar_array_parameters(parameter_buffers) }
     }
 
@@ -246,11 +492,89 @@ where
 
         let parameter_buffers = descriptions
             .into_iter()
-            .map(|desc| AnyBuffer::from_desc(capacity, desc))
-            .collect();
+            .map(|desc| {
+                AnyBuffer::try_from_desc(capacity, desc)
+                    .map_err(TooLargeBufferSize::into_allocation_error)
+            })
+            .collect::<Result<_, _>>()?;
         unsafe { ColumnarBulkInserter::new(stmt, parameter_buffers) }
     }
 
+    /// Executes the statement once for each row yielded by `rows`. Rows are copied into array
+    /// bound parameter buffers and sent to the data source in batches of up to `batch_size` rows.
+    /// This is a middle ground between executing the statement for each row individually and
+    /// filling the columnar buffers of a [`crate::ColumnarBulkInserter`] yourself. Buffers are
+    /// sized to fit the values of the first batch. Should a later batch contain longer text or
+    /// binary values, new buffers are bound.
+    ///
+    /// An error executing a batch, or retrieving the number of rows it affected, does not stop the
+    /// execution of the remaining batches, but is reported in [`ExecuteManyReport::batch_errors`]. Errors binding the parameter buffers are
+    /// returned immediately.
+    ///
+    /// ```no_run
+    /// use odbc_api::{Connection, Error};
+    ///
+    /// fn insert_birth_years(conn: &Connection, birthdays: &[(&str, i16)]) -> Result<(), Error> {
+    ///     let mut prepared = conn.prepare("INSERT INTO Birthdays (name, year) VALUES (?, ?)")?;
+    ///     let report = prepared.execute_many(1000, birthdays.iter().copied())?;
+    ///     for batch in report.batch_errors {
+    ///         eprintln!("Rows starting at {} not inserted: {}", batch.first_row, batch.error);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `batch_size` is `0`.
+    pub fn execute_many<R>(
+        &mut self,
+        batch_size: usize,
+        rows: impl IntoIterator<Item = R>,
+    ) -> Result<ExecuteManyReport, Error>
+    where
+        R: ParameterRow,
+    {
+        assert!(batch_size > 0, "Batch size must be at least one.");
+        let mut rows = rows.into_iter();
+        let mut report = ExecuteManyReport::default();
+        let mut descs = Vec::new();
+        let mut inserter = None;
+        let mut first_row = 0;
+        loop {
+            let batch: Vec<R> = rows.by_ref().take(batch_size).collect();
+            if batch.is_empty() {
+                break;
+            }
+            let required = merge_buffer_descs(&descs, &R::buffer_descs(&batch));
+            if inserter.is_none() || required != descs {
+                // Unbind the previous buffers, before binding larger ones.
+                drop(inserter.take());
+                descs = required;
+                inserter = Some(self.column_inserter(batch_size, descs.iter().copied())?);
+            }
+            let inserter = inserter.as_mut().unwrap();
+            inserter.set_num_rows(batch.len());
+            for (row_index, row) in batch.iter().enumerate() {
+                row.write_into(inserter, row_index);
+            }
+            let result = inserter
+                .execute()
+                .map(|_cursor| ())
+                .and_then(|()| inserter.row_count());
+            match result {
+                Ok(row_count) => report.rows_affected += row_count.unwrap_or(0),
+                Err(error) => report.batch_errors.push(BatchError {
+                    first_row,
+                    num_rows: batch.len(),
+                    error,
+                }),
+            }
+            first_row += batch.len();
+        }
+        Ok(report)
+    }
+
     /// Number of rows affected by the last `INSERT`, `UPDATE` or `DELETE` statment. May return
     /// `None` if row count is not available. Some drivers may also allow to use this to determine
     /// how many rows have been fetched using `SELECT`. Most drivers however only know how many rows
@@ -280,14 +604,29 @@ where
     /// ```
     pub fn row_count(&mut self) -> Result<Option<usize>, Error> {
         let stmt = self.statement.as_stmt_ref();
-        stmt.row_count().into_result(&stmt).map(|count| {
-            // ODBC returns -1 in case a row count is not available
-            if count == -1 {
-                None
-            } else {
-                Some(count.try_into().unwrap())
-            }
-        })
+        let count = stmt.row_count().into_result(&stmt)?;
+        // ODBC returns -1 in case a row count is not available
+        if count == -1 {
+            Ok(None)
+        } else {
+            reported(count, "row count").map(Some)
+        }
+    }
+}
+
+impl<'env> Prepared<StatementConnection<'env>> {
+    /// Allows sending a prepared statement, which owns its connection, to different threads. This
+    /// is e.g. useful to store the prepared statement in the state of a web application or to move
+    /// it into an asynchronous task, without having to manage the lifetime of the connection
+    /// separately.
+    ///
+    /// # Safety
+    ///
+    /// Same reasoning as for [`crate::Connection::promote_to_send`] applies. By calling this
+    /// function you express your trust in the thread safety of the ODBC driver your application is
+    /// using.
+    pub unsafe fn promote_to_send(self) -> force_send_sync::Send<Self> {
+        force_send_sync::Send::new(self)
     }
 }
 
@@ -0,0 +1,250 @@
+use crate::{handles::StatementImpl, parameter::InputParameter, Connection, CursorImpl, Error};
+
+/// A minimal builder for `SELECT` statements. Identifiers (tables and columns) are always quoted,
+/// or rejected if the data source does not support quoting and they are not plain names. Values
+/// are always passed as parameters. Conditions are `&'static str`, so they can not be
+/// assembled from user input at runtime. This is not an ORM, just enough structure to avoid
+/// concatenating SQL strings in application code.
+///
+/// ```
+/// use odbc_api::{IntoParameter, Select};
+///
+/// let query = Select::from("Birthdays")
+///     .columns(["name", "year"])
+///     .filter("year > ?", 1980)
+///     .filter("name <> ?", "Peter".into_parameter())
+///     .order_by("year");
+///
+/// assert_eq!(
+///     r#"SELECT "name", "year" FROM "Birthdays" WHERE year > ? AND name <> ? ORDER BY "year""#,
+///     query.to_sql("\"").unwrap()
+/// );
+/// ```
+pub struct Select {
+    table: String,
+    columns: Vec<String>,
+    conditions: Vec<Condition>,
+    params: Vec<Box<dyn InputParameter>>,
+    order_by: Vec<(String, bool)>,
+}
+
+impl Select {
+    /// Select from the table named `table`. Use [`Self::columns`] to specify which columns to
+    /// fetch, otherwise all columns are selected.
+    pub fn from(table: impl Into<String>) -> Self {
+        Self {
+            table: table.into(),
+            columns: Vec::new(),
+            conditions: Vec::new(),
+            params: Vec::new(),
+            order_by: Vec::new(),
+        }
+    }
+
+    /// Appends columns to the select list.
+    pub fn columns<S: Into<String>>(mut self, columns: impl IntoIterator<Item = S>) -> Self {
+        self.columns.extend(columns.into_iter().map(Into::into));
+        self
+    }
+
+    /// Adds a condition to the `WHERE` clause. Multiple conditions are combined using `AND`.
+    /// `condition` must contain exactly one `?` placeholder, which is bound to `param`.
+    ///
+    /// # Panics
+    ///
+    /// If `condition` does not contain exactly one `?`.
+    pub fn filter(mut self, condition: &'static str, param: impl InputParameter + 'static) -> Self {
+        assert_eq!(
+            1,
+            condition.matches('?').count(),
+            "Condition must contain exactly one placeholder (`?`)."
+        );
+        self.conditions.push(Condition::Static(condition));
+        self.params.push(Box::new(param));
+        self
+    }
+
+    /// Adds the condition `column > ?` to the `WHERE` clause, with the placeholder bound to
+    /// `param`. Other than for [`Self::filter`], the column is quoted.
+    pub(crate) fn filter_greater_than(
+        mut self,
+        column: impl Into<String>,
+        param: impl InputParameter + 'static,
+    ) -> Self {
+        self.conditions.push(Condition::GreaterThan(column.into()));
+        self.params.push(Box::new(param));
+        self
+    }
+
+    /// Sorts the result set ascending by `column`. May be called repeatedly.
+    pub fn order_by(mut self, column: impl Into<String>) -> Self {
+        self.order_by.push((column.into(), false));
+        self
+    }
+
+    /// Sorts the result set descending by `column`. May be called repeatedly.
+    pub fn order_by_desc(mut self, column: impl Into<String>) -> Self {
+        self.order_by.push((column.into(), true));
+        self
+    }
+
+    /// Renders the statement text, quoting identifiers with `quote`. The quote is usually obtained
+    /// using [`Connection::identifier_quote_char`]. The table name may be qualified with a schema,
+    /// e.g. `dbo.Birthdays`. Fails with [`Error::UnquotableIdentifier`] if `quote` is blank and an
+    /// identifier is not a plain name.
+    pub fn to_sql(&self, quote: &str) -> Result<String, Error> {
+        let quote_ident = |ident: &str| quote_identifier(ident, quote);
+        let select_list = if self.columns.is_empty() {
+            "*".to_owned()
+        } else {
+            let columns = self
+                .columns
+                .iter()
+                .map(|c| quote_ident(c))
+                .collect::<Result<Vec<_>, _>>()?;
+            columns.join(", ")
+        };
+        let table = quote_qualified(&self.table, quote)?;
+        let mut sql = format!("SELECT {select_list} FROM {table}");
+        if !self.conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            let conditions = self
+                .conditions
+                .iter()
+                .map(|condition| match condition {
+                    Condition::Static(condition) => Ok((*condition).to_owned()),
+                    Condition::GreaterThan(column) => Ok(format!("{} > ?", quote_ident(column)?)),
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            sql.push_str(&conditions.join(" AND "));
+        }
+        if !self.order_by.is_empty() {
+            let order_by = self
+                .order_by
+                .iter()
+                .map(|(column, desc)| {
+                    let column = quote_ident(column)?;
+                    Ok(if *desc {
+                        format!("{column} DESC")
+                    } else {
+                        column
+                    })
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            sql.push_str(" ORDER BY ");
+            sql.push_str(&order_by.join(", "));
+        }
+        Ok(sql)
+    }
+
+    /// Renders the statement using the identifier quote of the data source and executes it with
+    /// the parameters of the filters bound.
+    pub fn execute<'c>(
+        &self,
+        connection: &'c Connection<'_>,
+    ) -> Result<Option<CursorImpl<StatementImpl<'c>>>, Error> {
+        let quote = connection.identifier_quote_char()?;
+        connection.execute(&self.to_sql(&quote)?, &self.params[..])
+    }
+}
+
+/// Condition within the `WHERE` clause of a [`Select`].
+enum Condition {
+    /// Condition passed by the application as is.
+    Static(&'static str),
+    /// `column > ?`, rendered with a quoted column name.
+    GreaterThan(String),
+}
+
+/// Surrounds `ident` with `quote`, doubling any occurrence of `quote` within it. If the data source
+/// does not support quoting (`quote` is blank), the identifier is used as is, but only if it is a
+/// plain name matching `[A-Za-z_][A-Za-z0-9_]*`. Anything else could alter the structure of the
+/// statement and fails with [`Error::UnquotableIdentifier`].
+pub(crate) fn quote_identifier(ident: &str, quote: &str) -> Result<String, Error> {
+    let quote = quote.trim();
+    if quote.is_empty() {
+        return if is_plain_identifier(ident) {
+            Ok(ident.to_owned())
+        } else {
+            Err(Error::UnquotableIdentifier {
+                identifier: ident.to_owned(),
+            })
+        };
+    }
+    let escaped = ident.replace(quote, &quote.repeat(2));
+    Ok(format!("{quote}{escaped}{quote}"))
+}
+
+/// Quotes each part of a name qualified with a schema or catalog, e.g. `dbo.t`, separately. See
+/// [`quote_identifier`].
+pub(crate) fn quote_qualified(name: &str, quote: &str) -> Result<String, Error> {
+    let parts = name
+        .split('.')
+        .map(|part| quote_identifier(part, quote))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(parts.join("."))
+}
+
+/// `true` if `ident` matches `[A-Za-z_][A-Za-z0-9_]*` and can therefore be used without quotes.
+fn is_plain_identifier(ident: &str) -> bool {
+    let mut chars = ident.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Error;
+
+    use super::{quote_identifier, quote_qualified, Select};
+
+    #[test]
+    fn quotes_within_identifiers_are_escaped() {
+        assert_eq!(r#""a""b""#, quote_identifier(r#"a"b"#, "\"").unwrap());
+        assert_eq!("`a``b`", quote_identifier("a`b", "`").unwrap());
+    }
+
+    #[test]
+    fn only_plain_identifiers_are_used_without_quotes() {
+        assert_eq!("_a1", quote_identifier("_a1", " ").unwrap());
+        assert_eq!("a", quote_identifier("a", "").unwrap());
+        for ident in ["", "1a", "a b", "a;DROP TABLE t", "a\"", "ä"] {
+            assert!(matches!(
+                quote_identifier(ident, " "),
+                Err(Error::UnquotableIdentifier { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn quote_parts_of_qualified_names() {
+        assert_eq!(r#""dbo"."t""#, quote_qualified("dbo.t", "\"").unwrap());
+        assert_eq!("`t`", quote_qualified("t", "`").unwrap());
+        assert_eq!("dbo.t", quote_qualified("dbo.t", " ").unwrap());
+    }
+
+    #[test]
+    fn select_all_columns_ordered_descending() {
+        let query = Select::from("t").order_by_desc("a").order_by("b");
+
+        assert_eq!(
+            "SELECT * FROM `t` ORDER BY `a` DESC, `b`",
+            query.to_sql("`").unwrap()
+        );
+    }
+
+    #[test]
+    fn select_from_qualified_table() {
+        let query = Select::from("dbo.t").columns(["a"]);
+
+        assert_eq!(r#"SELECT "a" FROM "dbo"."t""#, query.to_sql("\"").unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "exactly one placeholder")]
+    fn condition_without_placeholder() {
+        Select::from("t").filter("a = 1", 2);
+    }
+}
@@ -0,0 +1,104 @@
+use crate::{Connection, Cursor, Error, ParameterCollectionRef, ResultSetMetadata};
+
+/// Execution plan of a statement as reported by the data source. See [`Connection::explain`].
+///
+/// Each row of the result set describing the plan is kept as one line of text. Rows with more than
+/// one column, like the ones of `EXPLAIN QUERY PLAN` in SQLite, have their columns separated by
+/// tabs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryPlan {
+    format: PlanFormat,
+    rows: Vec<String>,
+}
+
+/// Format the data source describes execution plans in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanFormat {
+    /// Human readable text, e.g. PostgreSQL, SQLite, Oracle.
+    Text,
+    /// A JSON document, e.g. MySQL and MariaDB.
+    Json,
+    /// A `ShowPlanXML` document, e.g. Microsoft SQL Server.
+    Xml,
+}
+
+impl QueryPlan {
+    /// Format of the plan, which depends on the database management system.
+    pub fn format(&self) -> PlanFormat {
+        self.format
+    }
+
+    /// Rows of the result set describing the plan.
+    pub fn rows(&self) -> &[String] {
+        &self.rows
+    }
+
+    /// The whole plan as one text, with rows separated by line breaks.
+    pub fn text(&self) -> String {
+        self.rows.join("\n")
+    }
+}
+
+/// See [`Connection::explain`].
+pub(crate) fn explain(
+    connection: &Connection<'_>,
+    sql: &str,
+    params: impl ParameterCollectionRef,
+) -> Result<QueryPlan, Error> {
+    let dbms_name = connection.database_management_system_name()?;
+    let (format, prefix) = match dbms_name.as_str() {
+        "Microsoft SQL Server" => {
+            // Statements are compiled, but not executed, while `SHOWPLAN_XML` is on. The option
+            // must be set in a batch of its own.
+            connection.execute("SET SHOWPLAN_XML ON", ())?;
+            let rows = execute_and_read(connection, sql, params);
+            connection.execute("SET SHOWPLAN_XML OFF", ())?;
+            return Ok(QueryPlan {
+                format: PlanFormat::Xml,
+                rows: rows?,
+            });
+        }
+        "Oracle" => {
+            // The plan is written into the plan table, rather than returned as a result set.
+            connection.execute(&format!("EXPLAIN PLAN FOR {sql}"), params)?;
+            let display = "SELECT PLAN_TABLE_OUTPUT FROM TABLE(DBMS_XPLAN.DISPLAY())";
+            return Ok(QueryPlan {
+                format: PlanFormat::Text,
+                rows: execute_and_read(connection, display, ())?,
+            });
+        }
+        "MySQL" | "MariaDB" => (PlanFormat::Json, "EXPLAIN FORMAT=JSON"),
+        "SQLite" => (PlanFormat::Text, "EXPLAIN QUERY PLAN"),
+        _ => (PlanFormat::Text, "EXPLAIN"),
+    };
+    let rows = execute_and_read(connection, &format!("{prefix} {sql}"), params)?;
+    Ok(QueryPlan { format, rows })
+}
+
+/// Executes `sql` and fetches its result set as text, one line per row.
+fn execute_and_read(
+    connection: &Connection<'_>,
+    sql: &str,
+    params: impl ParameterCollectionRef,
+) -> Result<Vec<String>, Error> {
+    let mut cursor = match connection.execute(sql, params)? {
+        Some(cursor) => cursor,
+        None => return Ok(Vec::new()),
+    };
+    let num_cols = cursor.num_result_cols()? as u16;
+    let mut rows = Vec::new();
+    let mut buf = Vec::new();
+    while let Some(mut row) = cursor.next_row()? {
+        let mut line = String::new();
+        for col_index in 1..=num_cols {
+            if col_index != 1 {
+                line.push('\t');
+            }
+            if row.get_text(col_index, &mut buf)? {
+                line.push_str(&String::from_utf8_lossy(&buf));
+            }
+        }
+        rows.push(line);
+    }
+    Ok(rows)
+}
@@ -0,0 +1,175 @@
+use crate::DataType;
+
+/// Known bugs of an ODBC driver, which this crate works around. Detected from the name and version
+/// of the driver by [`crate::Connection::quirks`], and applied automatically to statements
+/// prepared afterwards. Use [`crate::Connection::set_quirks`] to override the detection, e.g. for a
+/// driver not yet known to this crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Quirks {
+    /// `SQLDescribeParam` reports data types which do not match the parameters of the statement,
+    /// e.g. for the Oracle ODBC driver. If `true`, [`crate::Prepared::warm_up`] binds text instead of
+    /// relying on the reported types.
+    pub describe_param_unreliable: bool,
+    /// Binding text or binary parameters with a column size of zero, i.e. as `VARCHAR(0)`, causes
+    /// errors, e.g. for the Microsoft Access driver. Older versions of the Microsoft SQL Server
+    /// driver are affected too, but not detected, since it is not known which. If `true`,
+    /// [`crate::Prepared::execute`] binds empty values with a column size of one.
+    pub zero_length_unsupported: bool,
+    /// The driver reports [`crate::buffers::Indicator::NoTotal`] rather than the length of long
+    /// text and binary values, e.g. FreeTDS for `VARCHAR(MAX)` columns. Since the length is
+    /// unknown, there is nothing to work around automatically. Consult this to choose a
+    /// [`crate::NoTotalPolicy`] bounding the memory spent on such values.
+    pub no_total_for_long_data: bool,
+}
+
+/// Drivers with known quirks. Matched case insensitive against a part of the file name reported in
+/// `SQL_DRIVER_NAME`, since the name varies between platforms, e.g. `SQORA32.DLL` and
+/// `libsqora.so.19.1`.
+const KNOWN_DRIVERS: &[KnownDriver] = &[
+    // Oracle
+    KnownDriver {
+        name: "sqora",
+        fixed_in: None,
+        quirks: Quirks {
+            describe_param_unreliable: true,
+            zero_length_unsupported: false,
+            no_total_for_long_data: false,
+        },
+    },
+    // Microsoft Access, based on the ACE database engine
+    KnownDriver {
+        name: "aceodbc",
+        fixed_in: None,
+        quirks: Quirks {
+            describe_param_unreliable: false,
+            zero_length_unsupported: true,
+            no_total_for_long_data: false,
+        },
+    },
+    // Microsoft Access, based on the older Jet database engine
+    KnownDriver {
+        name: "odbcjt32",
+        fixed_in: None,
+        quirks: Quirks {
+            describe_param_unreliable: false,
+            zero_length_unsupported: true,
+            no_total_for_long_data: false,
+        },
+    },
+    // FreeTDS, e.g. `libtdsodbc.so`
+    KnownDriver {
+        name: "tdsodbc",
+        fixed_in: None,
+        quirks: Quirks {
+            describe_param_unreliable: false,
+            zero_length_unsupported: false,
+            no_total_for_long_data: true,
+        },
+    },
+];
+
+/// Entry of [`KNOWN_DRIVERS`].
+struct KnownDriver {
+    name: &'static str,
+    /// First major and minor version no longer affected. `None` if all versions are affected.
+    fixed_in: Option<(u32, u32)>,
+    quirks: Quirks,
+}
+
+impl Quirks {
+    /// Quirks of the driver with the file name `driver_name` (`SQL_DRIVER_NAME`) in version
+    /// `driver_version` (`SQL_DRIVER_VER`, e.g. `"19.01.0000"`). No quirks, for unknown drivers.
+    pub fn for_driver(driver_name: &str, driver_version: &str) -> Self {
+        let driver_name = driver_name.to_ascii_lowercase();
+        let version = parse_version(driver_version);
+        KNOWN_DRIVERS
+            .iter()
+            .filter(|known| driver_name.contains(known.name))
+            .filter(|known| match (known.fixed_in, version) {
+                (Some(fixed_in), Some(version)) => version < fixed_in,
+                // Be cautious if the version can not be parsed.
+                _ => true,
+            })
+            .fold(Quirks::default(), |quirks, known| {
+                quirks.union(known.quirks)
+            })
+    }
+
+    fn union(self, other: Quirks) -> Quirks {
+        Quirks {
+            describe_param_unreliable: self.describe_param_unreliable
+                || other.describe_param_unreliable,
+            zero_length_unsupported: self.zero_length_unsupported || other.zero_length_unsupported,
+            no_total_for_long_data: self.no_total_for_long_data || other.no_total_for_long_data,
+        }
+    }
+
+    /// `true` if parameters must be bound with [`Self::parameter_type`].
+    pub(crate) fn affects_parameters(&self) -> bool {
+        self.zero_length_unsupported
+    }
+
+    /// SQL data type to bind a parameter of type `data_type` with.
+    pub(crate) fn parameter_type(&self, data_type: DataType) -> DataType {
+        if !self.zero_length_unsupported {
+            return data_type;
+        }
+        match data_type {
+            DataType::Varchar { length: 0 } => DataType::Varchar { length: 1 },
+            DataType::WVarchar { length: 0 } => DataType::WVarchar { length: 1 },
+            DataType::Varbinary { length: 0 } => DataType::Varbinary { length: 1 },
+            DataType::Char { length: 0 } => DataType::Char { length: 1 },
+            DataType::WChar { length: 0 } => DataType::WChar { length: 1 },
+            DataType::Binary { length: 0 } => DataType::Binary { length: 1 },
+            other => other,
+        }
+    }
+}
+
+/// Major and minor version of a driver version formatted `##.##.####`.
+fn parse_version(version: &str) -> Option<(u32, u32)> {
+    let mut parts = version.split('.');
+    let major = parts.next()?.trim().parse().ok()?;
+    let minor = parts.next()?.trim().parse().ok()?;
+    Some((major, minor))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::DataType;
+
+    use super::Quirks;
+
+    #[test]
+    fn detect_quirks_by_driver_file_name() {
+        let oracle = Quirks::for_driver("libsqora.so.19.1", "19.01.0000");
+        let access = Quirks::for_driver("ACEODBC.DLL", "16.00.5095");
+        let freetds =
+            Quirks::for_driver("/usr/lib/x86_64-linux-gnu/odbc/libtdsodbc.so", "01.03.0006");
+        let postgres = Quirks::for_driver("psqlodbcw.so", "16.00.0000");
+
+        assert!(oracle.describe_param_unreliable);
+        assert!(!oracle.zero_length_unsupported);
+        assert!(access.zero_length_unsupported);
+        assert!(freetds.no_total_for_long_data);
+        assert!(!freetds.zero_length_unsupported);
+        assert_eq!(Quirks::default(), postgres);
+    }
+
+    #[test]
+    fn bind_empty_text_with_column_size_one() {
+        let quirks = Quirks {
+            zero_length_unsupported: true,
+            ..Quirks::default()
+        };
+
+        assert_eq!(
+            DataType::Varchar { length: 1 },
+            quirks.parameter_type(DataType::Varchar { length: 0 })
+        );
+        assert_eq!(
+            DataType::Varchar { length: 5 },
+            quirks.parameter_type(DataType::Varchar { length: 5 })
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use crate::{handles::State, Error};
+
+/// States of errors reported by data sources, if the metadata of a prepared statement is stale,
+/// e.g. because a table it refers to has been altered after the statement has been prepared.
+const STALE_METADATA: &[State] = &[
+    // Microsoft SQL Server: Reported once the result set of a prepared statement changed, e.g.
+    // after `ALTER TABLE`.
+    State(*b"0100C"),
+    // IBM Db2: "The cursor or statement is not prepared", after the statement has been invalidated.
+    State(*b"26501"),
+];
+
+/// Decides whether [`crate::Prepared::execute`] prepares the statement again and retries, if the
+/// execution fails. Long lived prepared statements may be invalidated by schema changes, e.g. a
+/// rolling migration altering a table. Preparing the statement again picks up the new metadata.
+///
+/// The statement is only prepared again once per execution. If the retry fails too, its error is
+/// returned. Since the execution failed, nothing has been executed and the same parameters are
+/// bound again. Parameters streaming their values (see [`crate::parameter::Blob`]) can not be
+/// rewound though, so do not combine them with a policy other than [`Self::Never`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum RePreparePolicy {
+    /// Always return the error of the failed execution.
+    #[default]
+    Never,
+    /// Prepare again and retry if the error indicates stale metadata. Currently these are the
+    /// states `0100C` reported by Microsoft SQL Server and `26501` reported by IBM Db2.
+    OnStaleMetadata,
+    /// Prepare again and retry if the state of the error is one of these. Useful for drivers
+    /// reporting stale metadata with states not known to [`Self::OnStaleMetadata`]. E.g.
+    /// PostgreSQL reports a changed result type of a cached plan as `0A000`, which it also uses
+    /// for any other unsupported feature.
+    OnStates(Vec<State>),
+}
+
+impl RePreparePolicy {
+    /// `true` if execution should be retried after preparing the statement again.
+    pub(crate) fn applies_to(&self, error: &Error) -> bool {
+        let states = match self {
+            RePreparePolicy::Never => return false,
+            RePreparePolicy::OnStaleMetadata => STALE_METADATA,
+            RePreparePolicy::OnStates(states) => states.as_slice(),
+        };
+        match error {
+            Error::Diagnostics { record, .. } => states.contains(&record.state),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        handles::{Record, State},
+        Error,
+    };
+
+    use super::RePreparePolicy;
+
+    #[test]
+    fn retry_only_on_listed_states() {
+        let error = |state: &[u8; 5]| Error::Diagnostics {
+            record: Record {
+                state: State(*state),
+                ..Default::default()
+            },
+            function: "SQLExecute",
+        };
+
+        assert!(!RePreparePolicy::Never.applies_to(&error(b"0100C")));
+        assert!(RePreparePolicy::OnStaleMetadata.applies_to(&error(b"0100C")));
+        // Feature not supported in PostgreSQL, which does not imply stale metadata.
+        assert!(!RePreparePolicy::OnStaleMetadata.applies_to(&error(b"0A000")));
+        assert!(!RePreparePolicy::OnStaleMetadata.applies_to(&error(b"42S02")));
+        let custom = RePreparePolicy::OnStates(vec![State(*b"42S22")]);
+        assert!(custom.applies_to(&error(b"42S22")));
+        assert!(!custom.applies_to(&error(b"0A000")));
+    }
+}
@@ -0,0 +1,422 @@
+//! Renders result sets as tables for humans, e.g. in command line tools or bug reports. See
+//! [`crate::Cursor::write_table`].
+//!
+//! ```text
+//! +----+-----------+------+
+//! | id | title     | year |
+//! +----+-----------+------+
+//! | 1  | Jaws      | 1975 |
+//! | 2  | Star W... | NULL |
+//! +----+-----------+------+
+//! ```
+//!
+//! All columns are fetched as text, so the values are formatted by the driver. `NULL` is rendered
+//! distinct from empty text. Rows are written as they are fetched, so the widths of the columns
+//! are determined by the names of the columns and the values of the first batch. Later values
+//! which do not fit are truncated.
+
+use std::{
+    borrow::Cow,
+    io::{self, BufWriter, Write},
+};
+
+use crate::{buffers::TextRowSet, Cursor, Error};
+
+/// Markup of a rendered table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableFormat {
+    /// Plain text with borders drawn from `+`, `-` and `|`, like the command line clients of most
+    /// databases print tables.
+    Ascii,
+    /// Table of GitHub flavored markdown. `|` within values is escaped.
+    Markdown,
+    /// HTML `<table>` element. Values are escaped, `NULL` cells have the class `null`.
+    Html,
+}
+
+/// Options for rendering result sets as tables. See [`crate::Cursor::write_table`].
+///
+/// ```
+/// use odbc_api::render::{RenderOptions, TableFormat};
+///
+/// // Markdown table for a bug report, showing the first ten rows.
+/// let options = RenderOptions::new()
+///     .with_format(TableFormat::Markdown)
+///     .with_max_rows(Some(10));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderOptions {
+    format: TableFormat,
+    max_width: Option<usize>,
+    truncation_marker: String,
+    null: String,
+    max_rows: Option<usize>,
+    batch_size: usize,
+    max_str_len: Option<usize>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            format: TableFormat::Ascii,
+            max_width: Some(40),
+            truncation_marker: "...".to_owned(),
+            null: "NULL".to_owned(),
+            max_rows: None,
+            batch_size: 100,
+            max_str_len: Some(4096),
+        }
+    }
+}
+
+impl RenderOptions {
+    /// ASCII table, with columns at most 40 characters wide, and truncated values ending in `...`.
+    /// `NULL` is rendered as `NULL`. All rows are rendered, fetched in batches of 100. Values hold
+    /// at most 4096 bytes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Markup of the table. [`TableFormat::Ascii`] by default.
+    pub fn with_format(mut self, format: TableFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Upper bound for the width of a column in characters, including the truncation marker of
+    /// values which do not fit. `None` to never truncate values. `Some(40)` by default.
+    pub fn with_max_width(mut self, max_width: Option<usize>) -> Self {
+        self.max_width = max_width;
+        self
+    }
+
+    /// Ends truncated values. `...` by default.
+    pub fn with_truncation_marker(mut self, truncation_marker: impl Into<String>) -> Self {
+        self.truncation_marker = truncation_marker.into();
+        self
+    }
+
+    /// Rendered in place of `NULL`. `NULL` by default.
+    pub fn with_null(mut self, null: impl Into<String>) -> Self {
+        self.null = null.into();
+        self
+    }
+
+    /// Upper bound for the number of rendered rows. `None`, to render all rows, by default.
+    pub fn with_max_rows(mut self, max_rows: Option<usize>) -> Self {
+        self.max_rows = max_rows;
+        self
+    }
+
+    /// Number of rows fetched at once. Also the number of rows the widths of the columns are
+    /// determined by.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Upper bound for the length of fetched values in bytes. The size reported by the driver is
+    /// used if it is smaller. `None` to always use the size reported by the driver, which fails for
+    /// columns without an upper bound, like `VARCHAR(max)`. Longer values are not truncated, but
+    /// cause [`write_table`] to fail with [`Error::TooLargeValueForBuffer`].
+    pub fn with_max_str_len(mut self, max_str_len: Option<usize>) -> Self {
+        self.max_str_len = max_str_len;
+        self
+    }
+}
+
+/// Fetches the rows of `cursor` and writes them to `writer` as a table. Returns the number of rows
+/// rendered. See [`crate::Cursor::write_table`].
+///
+/// ```no_run
+/// use std::io::stdout;
+/// use odbc_api::{render::{write_table, RenderOptions}, Connection, Error};
+///
+/// fn print_movies(conn: &Connection<'_>) -> Result<usize, Error> {
+///     let cursor = conn.execute("SELECT title, year FROM Movies", ())?.unwrap();
+///     write_table(cursor, stdout(), &RenderOptions::new())
+/// }
+/// ```
+pub fn write_table(
+    mut cursor: impl Cursor,
+    writer: impl Write,
+    options: &RenderOptions,
+) -> Result<usize, Error> {
+    let names = cursor.column_names()?.collect::<Result<Vec<_>, _>>()?;
+    let max_rows = options.max_rows.unwrap_or(usize::MAX);
+    let batch_size = options.batch_size.min(max_rows).max(1);
+    let buffer = TextRowSet::for_cursor(batch_size, &mut cursor, options.max_str_len)?;
+    let mut row_set_cursor = cursor.bind_buffer(buffer)?;
+    let mut table = Table::new(writer, options);
+    let mut num_rows = 0;
+    let mut first = true;
+    while num_rows < max_rows {
+        let Some(batch) = row_set_cursor.fetch_with_truncation_check(true)? else {
+            break;
+        };
+        let rows = batch.num_rows().min(max_rows - num_rows);
+        let row = |row_index| (0..batch.num_cols()).map(move |col| batch.at(col, row_index));
+        if first {
+            table.fit(names.iter().map(|name| Some(name.as_bytes())));
+            for row_index in 0..rows {
+                table.fit(row(row_index));
+            }
+            table
+                .write_header(&names)
+                .map_err(Error::FailedRenderingTable)?;
+            first = false;
+        }
+        for row_index in 0..rows {
+            table
+                .write_row(row(row_index))
+                .map_err(Error::FailedRenderingTable)?;
+        }
+        num_rows += rows;
+    }
+    if first {
+        // Empty result set. The header still shows the columns.
+        table.fit(names.iter().map(|name| Some(name.as_bytes())));
+        table
+            .write_header(&names)
+            .map_err(Error::FailedRenderingTable)?;
+    }
+    table.finish().map_err(Error::FailedRenderingTable)?;
+    Ok(num_rows)
+}
+
+/// Writes the parts of a table in the configured format.
+struct Table<'o, W: Write> {
+    writer: BufWriter<W>,
+    options: &'o RenderOptions,
+    /// Width of each column in characters.
+    widths: Vec<usize>,
+}
+
+impl<'o, W> Table<'o, W>
+where
+    W: Write,
+{
+    fn new(writer: W, options: &'o RenderOptions) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+            options,
+            widths: Vec::new(),
+        }
+    }
+
+    /// Widens the columns to fit the cells of `row`, up to the maximum width.
+    fn fit<'v>(&mut self, row: impl Iterator<Item = Option<&'v [u8]>>) {
+        for (index, value) in row.enumerate() {
+            let width = self.cell(value, usize::MAX).chars().count();
+            let width = self.options.max_width.map_or(width, |max| width.min(max));
+            match self.widths.get_mut(index) {
+                Some(existing) => *existing = (*existing).max(width),
+                None => self.widths.push(width),
+            }
+        }
+        if self.options.format == TableFormat::Markdown {
+            // Delimiter row of markdown tables requires at least three dashes.
+            for width in &mut self.widths {
+                *width = (*width).max(3);
+            }
+        }
+    }
+
+    fn write_header(&mut self, names: &[String]) -> io::Result<()> {
+        let names = names.iter().map(|name| Some(name.as_bytes()));
+        match self.options.format {
+            TableFormat::Ascii => {
+                self.write_border()?;
+                self.write_line(names)?;
+                self.write_border()
+            }
+            TableFormat::Markdown => {
+                self.write_line(names)?;
+                for &width in &self.widths {
+                    write!(self.writer, "| {} ", "-".repeat(width))?;
+                }
+                writeln!(self.writer, "|")
+            }
+            TableFormat::Html => {
+                writeln!(self.writer, "<table>\n<thead>")?;
+                self.write_html_row("th", names)?;
+                writeln!(self.writer, "</thead>\n<tbody>")
+            }
+        }
+    }
+
+    fn write_row<'v>(&mut self, row: impl Iterator<Item = Option<&'v [u8]>>) -> io::Result<()> {
+        match self.options.format {
+            TableFormat::Ascii | TableFormat::Markdown => self.write_line(row),
+            TableFormat::Html => self.write_html_row("td", row),
+        }
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        match self.options.format {
+            TableFormat::Ascii => self.write_border()?,
+            TableFormat::Markdown => (),
+            TableFormat::Html => writeln!(self.writer, "</tbody>\n</table>")?,
+        }
+        self.writer.flush()
+    }
+
+    fn write_border(&mut self) -> io::Result<()> {
+        for &width in &self.widths {
+            write!(self.writer, "+{}", "-".repeat(width + 2))?;
+        }
+        writeln!(self.writer, "+")
+    }
+
+    /// Writes the cells of a row of an ASCII or markdown table, padded to the width of their
+    /// columns.
+    fn write_line<'v>(&mut self, row: impl Iterator<Item = Option<&'v [u8]>>) -> io::Result<()> {
+        for (index, value) in row.enumerate() {
+            let width = self.widths[index];
+            let cell = self.cell(value, width);
+            let padding = width.saturating_sub(cell.chars().count());
+            write!(self.writer, "| {cell}{} ", " ".repeat(padding))?;
+        }
+        writeln!(self.writer, "|")
+    }
+
+    fn write_html_row<'v>(
+        &mut self,
+        tag: &str,
+        row: impl Iterator<Item = Option<&'v [u8]>>,
+    ) -> io::Result<()> {
+        write!(self.writer, "<tr>")?;
+        for value in row {
+            let cell = self.cell(value, self.options.max_width.unwrap_or(usize::MAX));
+            let class = if value.is_none() {
+                " class=\"null\""
+            } else {
+                ""
+            };
+            write!(self.writer, "<{tag}{class}>{cell}</{tag}>")?;
+        }
+        writeln!(self.writer, "</tr>")
+    }
+
+    /// Text of a cell, escaped for the format and truncated to at most `width` characters.
+    fn cell<'v>(&self, value: Option<&'v [u8]>, width: usize) -> Cow<'v, str> {
+        let options = self.options;
+        let text = match value {
+            None => Cow::Owned(options.null.clone()),
+            Some(value) => String::from_utf8_lossy(value),
+        };
+        let text = truncate(text, width, &options.truncation_marker);
+        match options.format {
+            TableFormat::Ascii => replace_control_chars(text),
+            TableFormat::Markdown => replace_control_chars(text).replace('|', "\\|").into(),
+            TableFormat::Html => escape_html(&text).into(),
+        }
+    }
+}
+
+/// Shortens `text` to at most `width` characters, ending with `marker`.
+fn truncate<'t>(text: Cow<'t, str>, width: usize, marker: &str) -> Cow<'t, str> {
+    if text.chars().count() <= width {
+        return text;
+    }
+    let keep = width.saturating_sub(marker.chars().count());
+    let mut truncated: String = text.chars().take(keep).collect();
+    truncated.push_str(marker);
+    Cow::Owned(truncated)
+}
+
+/// Line breaks and tabs would break the layout of text tables, so they are replaced by spaces.
+fn replace_control_chars(text: Cow<'_, str>) -> Cow<'_, str> {
+    if text.chars().any(char::is_control) {
+        Cow::Owned(
+            text.chars()
+                .map(|c| if c.is_control() { ' ' } else { c })
+                .collect(),
+        )
+    } else {
+        text
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' => escaped.push_str("&amp;"),
+            '"' => escaped.push_str("&quot;"),
+            other => escaped.push(other),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RenderOptions, Table, TableFormat};
+
+    fn render(options: &RenderOptions, rows: &[&[Option<&'static str>]]) -> String {
+        let names: Vec<String> = (1..=rows[0].len()).map(|n| format!("c{n}")).collect();
+        let mut text = Vec::new();
+        let mut table = Table::new(&mut text, options);
+        let bytes = |row: &[Option<&'static str>]| {
+            row.iter()
+                .map(|value| value.map(str::as_bytes))
+                .collect::<Vec<_>>()
+        };
+        table.fit(names.iter().map(|name| Some(name.as_bytes())));
+        for row in rows {
+            table.fit(bytes(row).into_iter());
+        }
+        table.write_header(&names).unwrap();
+        for row in rows {
+            table.write_row(bytes(row).into_iter()).unwrap();
+        }
+        table.finish().unwrap();
+        drop(table);
+        String::from_utf8(text).unwrap()
+    }
+
+    #[test]
+    fn ascii_table_with_null_and_truncation() {
+        let options = RenderOptions::new().with_max_width(Some(6));
+
+        let text = render(
+            &options,
+            &[&[Some("Jaws"), None], &[Some("Star Wars"), Some("")]],
+        );
+
+        assert_eq!(
+            "+--------+------+\n\
+             | c1     | c2   |\n\
+             +--------+------+\n\
+             | Jaws   | NULL |\n\
+             | Sta... |      |\n\
+             +--------+------+\n",
+            text
+        );
+    }
+
+    #[test]
+    fn markdown_escapes_pipes() {
+        let options = RenderOptions::new().with_format(TableFormat::Markdown);
+
+        let text = render(&options, &[&[Some("a|b\nc")]]);
+
+        assert_eq!("| c1     |\n| ------ |\n| a\\|b c |\n", text);
+    }
+
+    #[test]
+    fn html_escapes_values() {
+        let options = RenderOptions::new().with_format(TableFormat::Html);
+
+        let text = render(&options, &[&[Some("<b>"), None]]);
+
+        assert_eq!(
+            "<table>\n<thead>\n<tr><th>c1</th><th>c2</th></tr>\n</thead>\n<tbody>\n\
+             <tr><td>&lt;b&gt;</td><td class=\"null\">NULL</td></tr>\n</tbody>\n</table>\n",
+            text
+        );
+    }
+}
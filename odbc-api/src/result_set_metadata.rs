@@ -91,7 +91,7 @@ pub trait ResultSetMetadata: AsStatementRef {
         let stmt = self.as_stmt_ref();
         let mut buf = vec![0; 1024];
         stmt.col_name(column_number, &mut buf).into_result(&stmt)?;
-        Ok(slice_to_utf8(&buf).unwrap())
+        reported_text(&buf, "a column name")
     }
 
     /// Use this if you want to iterate over all column names and allocate a `String` for each one.
@@ -110,50 +110,50 @@ pub trait ResultSetMetadata: AsStatementRef {
         let dt = match kind {
             SqlDataType::UNKNOWN_TYPE => DataType::Unknown,
             SqlDataType::EXT_VAR_BINARY => DataType::Varbinary {
-                length: self.col_octet_length(column_number)?.try_into().unwrap(),
+                length: reported(self.col_octet_length(column_number)?, "octet length")?,
             },
             SqlDataType::EXT_LONG_VAR_BINARY => DataType::LongVarbinary {
-                length: self.col_octet_length(column_number)?.try_into().unwrap(),
+                length: reported(self.col_octet_length(column_number)?, "octet length")?,
             },
             SqlDataType::EXT_BINARY => DataType::Binary {
-                length: self.col_octet_length(column_number)?.try_into().unwrap(),
+                length: reported(self.col_octet_length(column_number)?, "octet length")?,
             },
             SqlDataType::EXT_W_VARCHAR => DataType::WVarchar {
-                length: self.col_display_size(column_number)?.try_into().unwrap(),
+                length: reported(self.col_display_size(column_number)?, "display size")?,
             },
             SqlDataType::EXT_W_CHAR => DataType::WChar {
-                length: self.col_display_size(column_number)?.try_into().unwrap(),
+                length: reported(self.col_display_size(column_number)?, "display size")?,
             },
             SqlDataType::EXT_LONG_VARCHAR => DataType::LongVarchar {
-                length: self.col_display_size(column_number)?.try_into().unwrap(),
+                length: reported(self.col_display_size(column_number)?, "display size")?,
             },
             SqlDataType::CHAR => DataType::Char {
-                length: self.col_display_size(column_number)?.try_into().unwrap(),
+                length: reported(self.col_display_size(column_number)?, "display size")?,
             },
             SqlDataType::VARCHAR => DataType::Varchar {
-                length: self.col_display_size(column_number)?.try_into().unwrap(),
+                length: reported(self.col_display_size(column_number)?, "display size")?,
             },
             SqlDataType::NUMERIC => DataType::Numeric {
-                precision: self.col_precision(column_number)?.try_into().unwrap(),
-                scale: self.col_scale(column_number)?.try_into().unwrap(),
+                precision: reported(self.col_precision(column_number)?, "precision")?,
+                scale: reported(self.col_scale(column_number)?, "scale")?,
             },
             SqlDataType::DECIMAL => DataType::Decimal {
-                precision: self.col_precision(column_number)?.try_into().unwrap(),
-                scale: self.col_scale(column_number)?.try_into().unwrap(),
+                precision: reported(self.col_precision(column_number)?, "precision")?,
+                scale: reported(self.col_scale(column_number)?, "scale")?,
             },
             SqlDataType::INTEGER => DataType::Integer,
             SqlDataType::SMALLINT => DataType::SmallInt,
             SqlDataType::FLOAT => DataType::Float {
-                precision: self.col_precision(column_number)?.try_into().unwrap(),
+                precision: reported(self.col_precision(column_number)?, "precision")?,
             },
             SqlDataType::REAL => DataType::Real,
             SqlDataType::DOUBLE => DataType::Double,
             SqlDataType::DATE => DataType::Date,
             SqlDataType::TIME => DataType::Time {
-                precision: self.col_precision(column_number)?.try_into().unwrap(),
+                precision: reported(self.col_precision(column_number)?, "precision")?,
             },
             SqlDataType::TIMESTAMP => DataType::Timestamp {
-                precision: self.col_precision(column_number)?.try_into().unwrap(),
+                precision: reported(self.col_precision(column_number)?, "precision")?,
             },
             SqlDataType::EXT_BIG_INT => DataType::BigInt,
             SqlDataType::EXT_TINY_INT => DataType::TinyInt,
@@ -185,19 +185,37 @@ pub trait ResultSetMetadata: AsStatementRef {
 pub fn utf8_display_sizes(
     metadata: &mut impl ResultSetMetadata,
 ) -> Result<impl Iterator<Item = Result<usize, Error>> + '_, Error> {
-    let num_cols: u16 = metadata.num_result_cols()?.try_into().unwrap();
+    let num_cols: u16 = reported(metadata.num_result_cols()?.into(), "number of columns")?;
     let it = (1..(num_cols + 1)).map(move |col_index| {
         // Ask driver for buffer length
         let max_str_len = if let Some(encoded_len) = metadata.col_data_type(col_index)?.utf8_len() {
             encoded_len
         } else {
-            metadata.col_display_size(col_index)? as usize
+            reported(metadata.col_display_size(col_index)?, "display size")?
         };
         Ok(max_str_len)
     });
     Ok(it)
 }
 
+/// Converts a length, precision or other count reported by the driver. Fails with
+/// [`Error::InvalidDriverValue`], rather than panicking, if it is negative or out of range.
+pub(crate) fn reported<T>(value: isize, description: &'static str) -> Result<T, Error>
+where
+    T: TryFrom<isize>,
+{
+    T::try_from(value).map_err(|_| Error::InvalidDriverValue {
+        description,
+        value: value as i64,
+    })
+}
+
+/// Decodes text reported by the driver, e.g. the name of a column. Fails with
+/// [`Error::InvalidDriverText`], rather than panicking, if it is not correctly encoded.
+pub(crate) fn reported_text(text: &[SqlChar], description: &'static str) -> Result<String, Error> {
+    slice_to_utf8(text).map_err(|_| Error::InvalidDriverText { description })
+}
+
 /// An iterator calling `col_name` for each column_name and converting the result into UTF-8. See
 /// [`ResultSetMetada::column_names`].
 pub struct ColumnNamesIt<'c, C: ?Sized> {
@@ -209,7 +227,7 @@ pub struct ColumnNamesIt<'c, C: ?Sized> {
 
 impl<'c, C: ResultSetMetadata + ?Sized> ColumnNamesIt<'c, C> {
     fn new(cursor: &'c mut C) -> Result<Self, Error> {
-        let num_cols = cursor.num_result_cols()?.try_into().unwrap();
+        let num_cols = reported(cursor.num_result_cols()?.into(), "number of columns")?;
         Ok(Self {
             cursor,
             // Some ODBC drivers do not report the required size to hold the column name. Starting
@@ -237,7 +255,7 @@ where
             let result = stmt
                 .col_name(self.column, &mut self.buffer)
                 .into_result(&stmt)
-                .map(|()| slice_to_utf8(&self.buffer).unwrap());
+                .and_then(|()| reported_text(&self.buffer, "a column name"));
             self.column += 1;
             Some(result)
         } else {
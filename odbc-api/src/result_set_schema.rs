@@ -0,0 +1,245 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::{
+    result_set_metadata::{reported, reported_text},
+    ColumnDescription, DataType, Error, Nullability, ResultSetMetadata,
+};
+
+/// Snapshot of the columns of a result set. Can be compared against the schema of a previous run
+/// of a pipeline, in order to detect schema drift. With the `serde` feature enabled, it implements
+/// `Serialize` and `Deserialize`, so it can be persisted in between runs.
+///
+/// ```no_run
+/// use odbc_api::{Connection, Error, ResultSetSchema};
+///
+/// fn report_drift(conn: &Connection<'_>, previous: &ResultSetSchema) -> Result<(), Error> {
+///     let mut cursor = conn.execute("SELECT * FROM Movies", ())?.unwrap();
+///     let current = ResultSetSchema::for_cursor(&mut cursor)?;
+///     for change in previous.diff(&current) {
+///         println!("{change}");
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ResultSetSchema {
+    /// Columns in the order they appear in the result set.
+    pub columns: Vec<ColumnSchema>,
+}
+
+/// Name and type of a single column within a [`ResultSetSchema`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ColumnSchema {
+    /// Column name. May be empty if unavailable.
+    pub name: String,
+    /// Relational type of the column.
+    pub data_type: DataType,
+    /// Indicates whether the column is nullable or not.
+    pub nullability: Nullability,
+}
+
+impl ColumnSchema {
+    /// Creates a new column schema.
+    pub fn new(name: impl Into<String>, data_type: DataType, nullability: Nullability) -> Self {
+        Self {
+            name: name.into(),
+            data_type,
+            nullability,
+        }
+    }
+}
+
+/// A difference between two [`ResultSetSchema`]s, as reported by [`ResultSetSchema::diff`].
+/// Columns are matched by name. Its [`Display`] implementation yields a human readable description
+/// of the change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaChange {
+    /// Column only exists in the new schema.
+    ColumnAdded(ColumnSchema),
+    /// Column only exists in the old schema.
+    ColumnRemoved(ColumnSchema),
+    /// Column has a different position in the new schema. Positions start at `1`.
+    ColumnMoved {
+        name: String,
+        old_position: usize,
+        new_position: usize,
+    },
+    /// Relational type of the column changed.
+    DataTypeChanged {
+        name: String,
+        old: DataType,
+        new: DataType,
+    },
+    /// Nullability of the column changed.
+    NullabilityChanged {
+        name: String,
+        old: Nullability,
+        new: Nullability,
+    },
+}
+
+impl Display for SchemaChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaChange::ColumnAdded(column) => write!(
+                f,
+                "Column '{}' has been added with type {:?} ({:?}).",
+                column.name, column.data_type, column.nullability
+            ),
+            SchemaChange::ColumnRemoved(column) => {
+                write!(f, "Column '{}' has been removed.", column.name)
+            }
+            SchemaChange::ColumnMoved {
+                name,
+                old_position,
+                new_position,
+            } => write!(
+                f,
+                "Column '{name}' moved from position {old_position} to {new_position}."
+            ),
+            SchemaChange::DataTypeChanged { name, old, new } => write!(
+                f,
+                "Type of column '{name}' changed from {old:?} to {new:?}."
+            ),
+            SchemaChange::NullabilityChanged { name, old, new } => write!(
+                f,
+                "Nullability of column '{name}' changed from {old:?} to {new:?}."
+            ),
+        }
+    }
+}
+
+impl ResultSetSchema {
+    /// Captures the schema of the result set `cursor` is iterating over.
+    pub fn for_cursor(cursor: &mut impl ResultSetMetadata) -> Result<Self, Error> {
+        let num_cols: u16 = reported(cursor.num_result_cols()?.into(), "number of columns")?;
+        let mut description = ColumnDescription::default();
+        let columns = (1..=num_cols)
+            .map(|column_number| {
+                cursor.describe_col(column_number, &mut description)?;
+                Ok(ColumnSchema {
+                    name: reported_text(&description.name, "a column name")?,
+                    data_type: description.data_type,
+                    nullability: description.nullability,
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(Self { columns })
+    }
+
+    /// Lists the changes turning `self` into `other`. Columns are matched by name. An empty result
+    /// indicates both schemas are equal.
+    pub fn diff(&self, other: &ResultSetSchema) -> Vec<SchemaChange> {
+        let position = |schema: &ResultSetSchema, name: &str| {
+            schema.columns.iter().position(|column| column.name == name)
+        };
+        let mut changes = Vec::new();
+        for (old_index, old) in self.columns.iter().enumerate() {
+            let new_index = if let Some(new_index) = position(other, &old.name) {
+                new_index
+            } else {
+                changes.push(SchemaChange::ColumnRemoved(old.clone()));
+                continue;
+            };
+            let new = &other.columns[new_index];
+            if old_index != new_index {
+                changes.push(SchemaChange::ColumnMoved {
+                    name: old.name.clone(),
+                    old_position: old_index + 1,
+                    new_position: new_index + 1,
+                });
+            }
+            if old.data_type != new.data_type {
+                changes.push(SchemaChange::DataTypeChanged {
+                    name: old.name.clone(),
+                    old: old.data_type,
+                    new: new.data_type,
+                });
+            }
+            if old.nullability != new.nullability {
+                changes.push(SchemaChange::NullabilityChanged {
+                    name: old.name.clone(),
+                    old: old.nullability,
+                    new: new.nullability,
+                });
+            }
+        }
+        for new in &other.columns {
+            if position(self, &new.name).is_none() {
+                changes.push(SchemaChange::ColumnAdded(new.clone()));
+            }
+        }
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DataType, Nullability};
+
+    use super::{ColumnSchema, ResultSetSchema, SchemaChange};
+
+    fn schema(columns: &[(&str, DataType)]) -> ResultSetSchema {
+        ResultSetSchema {
+            columns: columns
+                .iter()
+                .map(|&(name, data_type)| ColumnSchema::new(name, data_type, Nullability::Nullable))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn diff_schemas() {
+        let old = schema(&[
+            ("id", DataType::Integer),
+            ("name", DataType::Varchar { length: 10 }),
+            ("legacy", DataType::Date),
+        ]);
+        let new = schema(&[
+            ("name", DataType::Varchar { length: 20 }),
+            ("id", DataType::Integer),
+            ("created", DataType::Date),
+        ]);
+
+        let changes: Vec<String> = old.diff(&new).iter().map(ToString::to_string).collect();
+
+        assert_eq!(
+            vec![
+                "Column 'id' moved from position 1 to 2.",
+                "Column 'name' moved from position 2 to 1.",
+                "Type of column 'name' changed from Varchar { length: 10 } to Varchar { length: 20 }.",
+                "Column 'legacy' has been removed.",
+                "Column 'created' has been added with type Date (Nullable).",
+            ],
+            changes
+        );
+        assert!(old.diff(&old).is_empty());
+        assert!(matches!(
+            old.diff(&new)[2],
+            SchemaChange::DataTypeChanged { .. }
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_roundtrip() {
+        let schema = schema(&[
+            ("id", DataType::Integer),
+            (
+                "other",
+                DataType::Other {
+                    data_type: odbc_sys::SqlDataType(-150),
+                    column_size: 0,
+                    decimal_digits: 0,
+                },
+            ),
+        ]);
+
+        let json = serde_json::to_string(&schema).unwrap();
+        let deserialized: ResultSetSchema = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(schema, deserialized);
+    }
+}
@@ -0,0 +1,181 @@
+use crate::{fixed_sized::Pod, CursorRow, Error, Nullable, ResultSetMetadata};
+
+/// Maps the rows of a result set to instances of `T`, by matching the names of the fields
+/// registered with [`Self::field`] against the column names of the result set. The names are
+/// resolved to column ordinals once, as fields are registered, so mapping individual rows does not
+/// involve any lookups by name. Column names are compared ignoring ASCII case.
+///
+/// ```no_run
+/// use odbc_api::{Connection, Cursor, Error, RowMapper};
+///
+/// #[derive(Default)]
+/// struct Movie {
+///     title: String,
+///     year: Option<i32>,
+/// }
+///
+/// fn movies(conn: &Connection<'_>) -> Result<Vec<Movie>, Error> {
+///     let mut cursor = conn.execute("SELECT year, title FROM Movies", ())?.unwrap();
+///     let mapper = RowMapper::<Movie>::new(&mut cursor)?
+///         .field("title", |movie, title| movie.title = title)?
+///         .field("year", |movie, year| movie.year = year)?;
+///     let mut movies = Vec::new();
+///     while let Some(mut row) = cursor.next_row()? {
+///         movies.push(mapper.map(&mut row)?);
+///     }
+///     Ok(movies)
+/// }
+/// ```
+pub struct RowMapper<T> {
+    column_names: Vec<String>,
+    /// Sorted by column number, since many drivers require `SQLGetData` to be called for columns
+    /// in ascending order.
+    fields: Vec<Field<T>>,
+}
+
+struct Field<T> {
+    column_number: u16,
+    extract: ExtractField<T>,
+}
+
+/// Sets a field of the target from the column with the given number of a row.
+type ExtractField<T> = Box<dyn Fn(&mut T, &mut CursorRow<'_>, u16) -> Result<(), Error>>;
+
+impl<T> RowMapper<T> {
+    /// A mapper without any fields, for the result set described by `metadata`.
+    pub fn new(metadata: &mut impl ResultSetMetadata) -> Result<Self, Error> {
+        let column_names = metadata.column_names()?.collect::<Result<_, _>>()?;
+        Ok(Self {
+            column_names,
+            fields: Vec::new(),
+        })
+    }
+
+    /// Registers a field, which is set using `set` with the value of the column named `name`.
+    /// Fails with [`Error::RowMapperUnknownColumn`] if the result set has no such column.
+    pub fn field<V>(mut self, name: &str, set: impl Fn(&mut T, V) + 'static) -> Result<Self, Error>
+    where
+        V: ColumnValue,
+    {
+        let index = self
+            .column_names
+            .iter()
+            .position(|column_name| column_name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| Error::RowMapperUnknownColumn {
+                name: name.to_owned(),
+            })?;
+        // Column numbers are 16 Bit, so only a misbehaving driver reports more columns.
+        let column_number = u16::try_from(index)
+            .ok()
+            .and_then(|index| index.checked_add(1))
+            .ok_or(Error::InvalidDriverValue {
+                description: "number of columns",
+                value: index as i64 + 1,
+            })?;
+        let field = Field {
+            column_number,
+            extract: Box::new(
+                move |target: &mut T, row: &mut CursorRow<'_>, column_number| {
+                    set(target, V::fetch(row, column_number)?);
+                    Ok(())
+                },
+            ),
+        };
+        let position = self
+            .fields
+            .partition_point(|field| field.column_number <= column_number);
+        self.fields.insert(position, field);
+        Ok(self)
+    }
+
+    /// Names of the columns of the result set in order.
+    pub fn column_names(&self) -> &[String] {
+        &self.column_names
+    }
+
+    /// Constructs a `T` from its default value and sets all registered fields from `row`. `row`
+    /// must belong to the result set the mapper has been created for.
+    pub fn map(&self, row: &mut CursorRow<'_>) -> Result<T, Error>
+    where
+        T: Default,
+    {
+        let mut target = T::default();
+        self.map_into(row, &mut target)?;
+        Ok(target)
+    }
+
+    /// Sets all registered fields of `target` from `row`.
+    pub fn map_into(&self, row: &mut CursorRow<'_>, target: &mut T) -> Result<(), Error> {
+        for field in &self.fields {
+            (field.extract)(target, row, field.column_number)?;
+        }
+        Ok(())
+    }
+}
+
+/// A value which can be fetched from a field of a [`CursorRow`], and therefore be used with
+/// [`RowMapper::field`]. Use `Option<V>` for nullable columns. `V` itself fails with
+/// [`Error::RowMapperUnexpectedNull`] if the column contains `NULL`.
+pub trait ColumnValue: Sized {
+    /// Fetches the value of the column `column_number` from `row`. Column numbers start at `1`.
+    fn fetch(row: &mut CursorRow<'_>, column_number: u16) -> Result<Self, Error>;
+}
+
+impl<T> ColumnValue for Option<T>
+where
+    T: Pod,
+{
+    fn fetch(row: &mut CursorRow<'_>, column_number: u16) -> Result<Self, Error> {
+        let mut value = Nullable::<T>::null();
+        row.get_data(column_number, &mut value)?;
+        Ok(value.into_opt())
+    }
+}
+
+impl<T> ColumnValue for T
+where
+    T: Pod,
+{
+    fn fetch(row: &mut CursorRow<'_>, column_number: u16) -> Result<Self, Error> {
+        Option::<T>::fetch(row, column_number)
+            .and_then(|value| value.ok_or(Error::RowMapperUnexpectedNull { column_number }))
+    }
+}
+
+impl ColumnValue for Option<String> {
+    fn fetch(row: &mut CursorRow<'_>, column_number: u16) -> Result<Self, Error> {
+        let mut buf = Vec::new();
+        let value = if row.get_text(column_number, &mut buf)? {
+            Some(String::from_utf8_lossy(&buf).into_owned())
+        } else {
+            None
+        };
+        Ok(value)
+    }
+}
+
+impl ColumnValue for String {
+    fn fetch(row: &mut CursorRow<'_>, column_number: u16) -> Result<Self, Error> {
+        Option::<String>::fetch(row, column_number)
+            .and_then(|value| value.ok_or(Error::RowMapperUnexpectedNull { column_number }))
+    }
+}
+
+impl ColumnValue for Option<Vec<u8>> {
+    fn fetch(row: &mut CursorRow<'_>, column_number: u16) -> Result<Self, Error> {
+        let mut buf = Vec::new();
+        let value = if row.get_binary(column_number, &mut buf)? {
+            Some(buf)
+        } else {
+            None
+        };
+        Ok(value)
+    }
+}
+
+impl ColumnValue for Vec<u8> {
+    fn fetch(row: &mut CursorRow<'_>, column_number: u16) -> Result<Self, Error> {
+        Option::<Vec<u8>>::fetch(row, column_number)
+            .and_then(|value| value.ok_or(Error::RowMapperUnexpectedNull { column_number }))
+    }
+}
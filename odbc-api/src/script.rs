@@ -0,0 +1,29 @@
+use crate::{statement_kind::split_statements, Connection, Error};
+
+/// Outcome of [`Connection::execute_script`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScriptReport {
+    /// Number of statements the script has been split into. Each of them has been executed
+    /// separately.
+    pub num_statements: usize,
+    /// Sum of the rows affected by the statements, as far as reported by the driver.
+    pub rows_affected: usize,
+}
+
+/// Executes the statements of `script` one after another. See [`Connection::execute_script`].
+pub(crate) fn execute_script(
+    connection: &Connection<'_>,
+    script: &str,
+) -> Result<ScriptReport, Error> {
+    let mut report = ScriptReport::default();
+    let mut statement = connection.preallocate()?;
+    for sql in split_statements(script) {
+        connection.check_statement_len(sql)?;
+        // Result sets are discarded, once the cursor is dropped.
+        if statement.execute(sql, ())?.is_none() {
+            report.rows_affected += statement.row_count()?.unwrap_or(0);
+        }
+        report.num_statements += 1;
+    }
+    Ok(report)
+}
@@ -0,0 +1,159 @@
+use std::sync::{Mutex, PoisonError};
+
+use crate::Connection;
+
+/// Driver libraries known to crash if called from several threads at once. Compared case
+/// insensitive with the file name reported by the driver.
+const UNSAFE_DRIVERS: &[&str] = &[
+    // Microsoft Access and Excel drivers, based on the Jet and ACE database engines.
+    "odbcjt32.dll",
+    "aceodbc.dll",
+];
+
+/// Values of `SQL_ASYNC_MODE`, the level of asynchronous execution supported by the driver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AsyncMode {
+    /// `SQL_AM_NONE`. Asynchronous execution is not supported.
+    None,
+    /// `SQL_AM_CONNECTION`. Asynchronous execution can be enabled for the connection, affecting
+    /// all of its statements.
+    Connection,
+    /// `SQL_AM_STATEMENT`. Asynchronous execution can be enabled for individual statements.
+    Statement,
+    /// A value not defined by the ODBC standard.
+    Unknown(u32),
+}
+
+impl AsyncMode {
+    pub(crate) fn from_info(value: u32) -> Self {
+        match value {
+            0 => AsyncMode::None,
+            1 => AsyncMode::Connection,
+            2 => AsyncMode::Statement,
+            other => AsyncMode::Unknown(other),
+        }
+    }
+}
+
+/// Hints whether a driver can be trusted to handle calls from several threads. Reported by
+/// [`Connection::threading_capability`].
+///
+/// ODBC requires drivers to be thread safe, yet offers no way for a driver to report whether it
+/// actually is. So this relies on a list of drivers known to misbehave. It is not conclusive, so
+/// use the result to decide whether to play it safe with a [`SerializedConnection`], not as a
+/// guarantee. The support for asynchronous execution is reported for information only. Many
+/// thread safe drivers, e.g. the one for SQLite, do not support it, so it does not tell anything
+/// about thread safety.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreadingCapability {
+    /// File name of the driver library, as reported by `SQL_DRIVER_NAME`.
+    pub driver_name: String,
+    /// Level of asynchronous execution supported by the driver, as reported by `SQL_ASYNC_MODE`.
+    pub async_mode: AsyncMode,
+}
+
+impl ThreadingCapability {
+    /// `true` if the driver is known to crash if called from several threads at once.
+    pub fn is_known_unsafe(&self) -> bool {
+        UNSAFE_DRIVERS
+            .iter()
+            .any(|unsafe_driver| self.driver_name.eq_ignore_ascii_case(unsafe_driver))
+    }
+
+    /// `true` if calls to the driver should be serialized, because it is known to be unsafe.
+    pub fn recommends_serialization(&self) -> bool {
+        self.is_known_unsafe()
+    }
+}
+
+/// A connection which can be shared between threads, but is only used by one of them at a time.
+/// Every use of the connection, including the statements and cursors created from it, happens
+/// within [`Self::with`] while holding a lock. Mitigates drivers crashing if calls on the same
+/// connection are made from several threads at once. Calls on different connections are not
+/// serialized.
+///
+/// ```no_run
+/// use std::thread;
+/// use odbc_api::{Environment, SerializedConnection};
+///
+/// let env = Environment::new()?;
+/// let conn = env.connect_with_connection_string("DSN=legacy;")?;
+/// let conn = unsafe { SerializedConnection::new(conn) };
+/// thread::scope(|scope| {
+///     for table in ["Orders", "Invoices"] {
+///         let conn = &conn;
+///         scope.spawn(move || {
+///             conn.with(|conn| conn.execute(&format!("DELETE FROM {table}"), ()).map(|_| ()))
+///         });
+///     }
+/// });
+/// # Ok::<(), odbc_api::Error>(())
+/// ```
+pub struct SerializedConnection<'c> {
+    connection: Mutex<Connection<'c>>,
+}
+
+/// All access to the connection happens while holding the lock.
+unsafe impl<'c> Send for SerializedConnection<'c> {}
+unsafe impl<'c> Sync for SerializedConnection<'c> {}
+
+impl<'c> SerializedConnection<'c> {
+    /// Wraps `connection`, so it can be used from several threads, one at a time.
+    ///
+    /// # Safety
+    ///
+    /// Like [`Connection::promote_to_send`], this expresses your trust that the driver can handle
+    /// calls on the connection from different threads, as long as they do not overlap.
+    pub unsafe fn new(connection: Connection<'c>) -> Self {
+        Self {
+            connection: Mutex::new(connection),
+        }
+    }
+
+    /// Calls `f` with exclusive access to the connection, blocking until other threads are done
+    /// using it. Statements and cursors can not outlive the call, so no other thread can use the
+    /// connection until they are dropped.
+    ///
+    /// ```
+    /// use odbc_api::{Error, SerializedConnection};
+    ///
+    /// fn archive(conn: &SerializedConnection<'_>) -> Result<(), Error> {
+    ///     conn.with(|conn| {
+    ///         conn.execute("UPDATE Orders SET status = 'archived'", ())?;
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub fn with<T>(&self, f: impl FnOnce(&Connection<'c>) -> T) -> T {
+        // A panic in another thread does not invalidate the connection itself.
+        let connection = self
+            .connection
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        f(&connection)
+    }
+
+    /// Gives back the connection.
+    pub fn into_inner(self) -> Connection<'c> {
+        self.connection
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AsyncMode, ThreadingCapability};
+
+    #[test]
+    fn recommend_serialization_for_unsafe_drivers_only() {
+        let capability = |driver_name: &str, async_mode| ThreadingCapability {
+            driver_name: driver_name.to_owned(),
+            async_mode,
+        };
+
+        assert!(capability("ACEODBC.DLL", AsyncMode::Statement).recommends_serialization());
+        assert!(!capability("libsqlite3odbc.so", AsyncMode::None).recommends_serialization());
+        assert!(!capability("libmsodbcsql-18.so", AsyncMode::Statement).recommends_serialization());
+    }
+}
@@ -0,0 +1,310 @@
+use std::{
+    env,
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, BufWriter, Read, Seek, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{buffers::TextRowSet, Cursor, Error};
+
+/// Used to give spill files created by this process distinct names.
+pub(crate) static NUM_SPILL_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Fetches an entire result set and writes it to a spill file, so it can be iterated over as often
+/// as needed without executing the query again. This is useful if the query is expensive and a
+/// downstream step processing the result may fail, or needs multiple passes. Iteration can be
+/// resumed at any batch, see [`Self::batches_from`].
+///
+/// All values are fetched as text, like they would be using a [`TextRowSet`]. The spill file uses a
+/// simple binary format private to this crate. It is only meant to live as long as the
+/// `SpoolingCursor`.
+///
+/// ```no_run
+/// use odbc_api::{Connection, Error, SpoolingCursor};
+///
+/// fn spool_orders(conn: &Connection<'_>) -> Result<SpoolingCursor, Error> {
+///     let cursor = conn.execute("SELECT id, status FROM Orders", ())?.unwrap();
+///     let spooled = SpoolingCursor::new(cursor, 10_000, Some(4096))?;
+///     // First pass
+///     for batch in spooled.batches() {
+///         let batch = batch?;
+///         // Process the batch ...
+///     }
+///     // Second pass, without querying the database again.
+///     for batch in spooled.batches() {
+///         let batch = batch?;
+///         // Process the batch ...
+///     }
+///     Ok(spooled)
+/// }
+/// ```
+#[derive(Debug)]
+pub struct SpoolingCursor {
+    file: File,
+    /// Path of the spill file, if it has been created by the `SpoolingCursor` and must be deleted
+    /// on drop.
+    temporary: Option<PathBuf>,
+    column_names: Vec<String>,
+    /// Position of each batch within the spill file.
+    offsets: Vec<u64>,
+    num_rows: usize,
+}
+
+impl SpoolingCursor {
+    /// Fetches all rows of `cursor` in batches of `batch_size` rows and writes them to a temporary
+    /// file in [`std::env::temp_dir`]. The file is deleted once the `SpoolingCursor` is dropped.
+    /// `max_str_len` limits the size of the text buffers, see [`TextRowSet::for_cursor`]. Fails
+    /// with [`Error::TooLargeValueForBuffer`] rather than spooling truncated values, if a value is
+    /// longer than `max_str_len`.
+    pub fn new(
+        cursor: impl Cursor,
+        batch_size: usize,
+        max_str_len: Option<usize>,
+    ) -> Result<Self, Error> {
+        let path = env::temp_dir().join(format!(
+            "odbc-api-spool-{}-{}",
+            process::id(),
+            NUM_SPILL_FILES.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(Error::SpillFile)?;
+        let mut spooled = Self::with_file(cursor, file, batch_size, max_str_len);
+        match &mut spooled {
+            Ok(spooled) => spooled.temporary = Some(path),
+            Err(_) => {
+                let _ = fs::remove_file(&path);
+            }
+        }
+        spooled
+    }
+
+    /// Like [`Self::new`], but writes the result set to `file`, which must be opened for reading
+    /// and writing. The file is truncated before writing and not deleted on drop.
+    pub fn with_file(
+        mut cursor: impl Cursor,
+        mut file: File,
+        batch_size: usize,
+        max_str_len: Option<usize>,
+    ) -> Result<Self, Error> {
+        let column_names = cursor.column_names()?.collect::<Result<Vec<_>, _>>()?;
+        file.set_len(0).map_err(Error::SpillFile)?;
+        file.rewind().map_err(Error::SpillFile)?;
+        let buffer = TextRowSet::for_cursor(batch_size, &mut cursor, max_str_len)?;
+        let mut row_set_cursor = cursor.bind_buffer(buffer)?;
+        let mut writer = BufWriter::new(&file);
+        let mut offsets = Vec::new();
+        let mut position = 0;
+        let mut num_rows = 0;
+        while let Some(batch) = row_set_cursor.fetch_with_truncation_check(true)? {
+            offsets.push(position);
+            position += write_batch(&mut writer, batch).map_err(Error::SpillFile)?;
+            num_rows += batch.num_rows();
+        }
+        writer.flush().map_err(Error::SpillFile)?;
+        drop(writer);
+        Ok(Self {
+            file,
+            temporary: None,
+            column_names,
+            offsets,
+            num_rows,
+        })
+    }
+
+    /// Names of the columns of the result set.
+    pub fn column_names(&self) -> &[String] {
+        &self.column_names
+    }
+
+    /// Total number of rows in the result set.
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    /// Number of batches stored in the spill file.
+    pub fn num_batches(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Path of the spill file, if it has been created by [`Self::new`].
+    pub fn path(&self) -> Option<&Path> {
+        self.temporary.as_deref()
+    }
+
+    /// Iterates over all batches, starting with the first one.
+    pub fn batches(&self) -> SpooledBatches<'_> {
+        self.batches_from(0)
+    }
+
+    /// Iterates over the batches, starting with the batch at `batch_index`. Allows to resume
+    /// processing after the last batch which has been processed successfully. Empty if
+    /// `batch_index` is not smaller than [`Self::num_batches`].
+    pub fn batches_from(&self, batch_index: usize) -> SpooledBatches<'_> {
+        SpooledBatches {
+            spool: self,
+            batch_index,
+        }
+    }
+
+    fn read_batch(&self, batch_index: usize) -> io::Result<SpooledBatch> {
+        // Iterators may be used from different threads at the same time, so we must not rely on
+        // the position of the file.
+        let mut reader = BufReader::new(ReadAt {
+            file: &self.file,
+            position: self.offsets[batch_index],
+        });
+        let num_cols = read_u64(&mut reader)? as usize;
+        let num_rows = read_u64(&mut reader)? as usize;
+        let mut columns = Vec::with_capacity(num_cols);
+        for _ in 0..num_cols {
+            let mut column = Vec::with_capacity(num_rows);
+            for _ in 0..num_rows {
+                let len = read_u64(&mut reader)?;
+                let value = if len == NULL {
+                    None
+                } else {
+                    let mut value = vec![0; len as usize];
+                    reader.read_exact(&mut value)?;
+                    Some(value)
+                };
+                column.push(value);
+            }
+            columns.push(column);
+        }
+        Ok(SpooledBatch { num_rows, columns })
+    }
+}
+
+impl Drop for SpoolingCursor {
+    fn drop(&mut self) {
+        if let Some(path) = &self.temporary {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Iterator over the batches of a [`SpoolingCursor`]. Each batch is read from the spill file as it
+/// is requested. Iterators over the same spool are independent of each other.
+#[derive(Debug)]
+pub struct SpooledBatches<'s> {
+    spool: &'s SpoolingCursor,
+    batch_index: usize,
+}
+
+impl<'s> SpooledBatches<'s> {
+    /// Index of the batch returned by the next call to `next`.
+    pub fn batch_index(&self) -> usize {
+        self.batch_index
+    }
+}
+
+impl<'s> Iterator for SpooledBatches<'s> {
+    type Item = Result<SpooledBatch, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.batch_index >= self.spool.num_batches() {
+            return None;
+        }
+        let batch = self
+            .spool
+            .read_batch(self.batch_index)
+            .map_err(Error::SpillFile);
+        self.batch_index += 1;
+        Some(batch)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.spool.num_batches().saturating_sub(self.batch_index);
+        (remaining, Some(remaining))
+    }
+}
+
+/// A batch of rows read from the spill file of a [`SpoolingCursor`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpooledBatch {
+    num_rows: usize,
+    columns: Vec<Vec<Option<Vec<u8>>>>,
+}
+
+impl SpooledBatch {
+    /// Number of rows in the batch.
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    /// Number of columns.
+    pub fn num_cols(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Value at the specified position. `None` for `NULL`. Text in the encoding of the system
+    /// locale, see [`TextRowSet::at`].
+    ///
+    /// # Panics
+    ///
+    /// If the position is out of bounds.
+    pub fn at(&self, col_index: usize, row_index: usize) -> Option<&[u8]> {
+        self.columns[col_index][row_index].as_deref()
+    }
+
+    /// Value at the specified position as `str`. `None` for `NULL`, or if the value is not valid
+    /// UTF-8.
+    pub fn at_as_str(&self, col_index: usize, row_index: usize) -> Option<&str> {
+        self.at(col_index, row_index)
+            .and_then(|bytes| std::str::from_utf8(bytes).ok())
+    }
+}
+
+/// Reads a file starting at `position`, without using or changing the position of the file itself.
+struct ReadAt<'f> {
+    file: &'f File,
+    position: u64,
+}
+
+impl Read for ReadAt<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        #[cfg(unix)]
+        let num_read = std::os::unix::fs::FileExt::read_at(self.file, buf, self.position)?;
+        // Moves the position of the file, but does not depend on it.
+        #[cfg(windows)]
+        let num_read = std::os::windows::fs::FileExt::seek_read(self.file, buf, self.position)?;
+        self.position += num_read as u64;
+        Ok(num_read)
+    }
+}
+
+/// Length written for `NULL` values.
+pub(crate) const NULL: u64 = u64::MAX;
+
+/// Writes the batch column by column. Each value is prefixed by its length. Returns the number of
+/// bytes written.
+fn write_batch(writer: &mut impl Write, batch: &TextRowSet) -> io::Result<u64> {
+    let mut written = 16;
+    writer.write_all(&(batch.num_cols() as u64).to_le_bytes())?;
+    writer.write_all(&(batch.num_rows() as u64).to_le_bytes())?;
+    for col_index in 0..batch.num_cols() {
+        for row_index in 0..batch.num_rows() {
+            if let Some(value) = batch.at(col_index, row_index) {
+                writer.write_all(&(value.len() as u64).to_le_bytes())?;
+                writer.write_all(value)?;
+                written += 8 + value.len() as u64;
+            } else {
+                writer.write_all(&NULL.to_le_bytes())?;
+                written += 8;
+            }
+        }
+    }
+    Ok(written)
+}
+
+pub(crate) fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
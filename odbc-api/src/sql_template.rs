@@ -0,0 +1,294 @@
+use crate::{
+    handles::StatementImpl, query_builder::quote_identifier, Connection, CursorImpl, Error,
+    ParameterCollectionRef,
+};
+
+/// Statement text with named placeholders for the parts of a statement, which can not be passed as
+/// parameters, i.e. identifiers and fragments of SQL. Values are still passed as parameters using
+/// `?`. Usually constructed using the [`crate::sql`] macro.
+///
+/// Placeholders are written as `{name:kind}`:
+///
+/// * `{name:ident}` is replaced with an identifier, e.g. the name of a table or a column. The
+///   identifier is always quoted, so it can not alter the structure of the statement, even if it
+///   originates from user input. If the data source does not support quoting identifiers, only
+///   plain names of ASCII letters, digits and underscores are accepted.
+/// * `{name:fragment}` is replaced with another [`SqlTemplate`]. Since templates can only be
+///   constructed from string literals, fragments can not originate from user input either.
+///
+/// Literal braces are written as `{{` and `}}`.
+///
+/// ```
+/// use odbc_api::{sql, SqlTemplate};
+///
+/// let order = sql!("ORDER BY {column:ident}", column = "year");
+/// let query = sql!(
+///     "SELECT * FROM {table:ident} WHERE title = ? {order:fragment}",
+///     table = "Movies",
+///     order = order,
+/// );
+///
+/// assert_eq!(
+///     r#"SELECT * FROM "Movies" WHERE title = ? ORDER BY "year""#,
+///     query.to_sql("\"").unwrap()
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct SqlTemplate {
+    pieces: Vec<Piece>,
+    arguments: Vec<(&'static str, TemplateValue)>,
+    /// First invalid argument passed to [`Self::arg`]. Reported once rendered, so arguments can
+    /// still be chained by [`crate::sql`].
+    invalid_argument: Option<String>,
+}
+
+/// Value substituted for a placeholder of a [`SqlTemplate`].
+#[derive(Debug, Clone)]
+pub enum TemplateValue {
+    /// Substitutes an `{name:ident}` placeholder. Quoted once rendered.
+    Ident(String),
+    /// Substitutes an `{name:fragment}` placeholder.
+    Fragment(SqlTemplate),
+}
+
+impl From<&str> for TemplateValue {
+    fn from(ident: &str) -> Self {
+        TemplateValue::Ident(ident.to_owned())
+    }
+}
+
+impl From<String> for TemplateValue {
+    fn from(ident: String) -> Self {
+        TemplateValue::Ident(ident)
+    }
+}
+
+impl From<SqlTemplate> for TemplateValue {
+    fn from(fragment: SqlTemplate) -> Self {
+        TemplateValue::Fragment(fragment)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Piece {
+    Literal(&'static str),
+    Placeholder {
+        name: &'static str,
+        kind: PlaceholderKind,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PlaceholderKind {
+    Ident,
+    Fragment,
+}
+
+impl SqlTemplate {
+    /// Parses `template`. Requiring a `'static` string ensures the template is not assembled from
+    /// user input at runtime.
+    ///
+    /// # Panics
+    ///
+    /// If `template` contains unmatched braces, or placeholders without a valid kind.
+    pub fn new(template: &'static str) -> Self {
+        Self {
+            pieces: parse(template),
+            arguments: Vec::new(),
+            invalid_argument: None,
+        }
+    }
+
+    /// Sets the value substituted for the placeholders named `name`. If the template has no
+    /// placeholder named `name`, or its kind does not match `value`, rendering the template fails
+    /// with [`Error::InvalidTemplateArgument`].
+    pub fn arg(mut self, name: &'static str, value: impl Into<TemplateValue>) -> Self {
+        let value = value.into();
+        let kind = self.pieces.iter().find_map(|piece| match *piece {
+            Piece::Placeholder {
+                name: placeholder,
+                kind,
+            } if placeholder == name => Some(kind),
+            _ => None,
+        });
+        let invalid = match (kind, &value) {
+            (None, _) => Some(format!("The template has no placeholder named `{name}`.")),
+            (Some(PlaceholderKind::Ident), TemplateValue::Ident(_))
+            | (Some(PlaceholderKind::Fragment), TemplateValue::Fragment(_)) => None,
+            (Some(_), _) => Some(format!(
+                "The value for placeholder `{name}` does not match its kind."
+            )),
+        };
+        if invalid.is_some() {
+            self.invalid_argument = self.invalid_argument.or(invalid);
+            return self;
+        }
+        self.arguments.retain(|(argument, _)| *argument != name);
+        self.arguments.push((name, value));
+        self
+    }
+
+    /// Renders the statement text, quoting identifiers with `quote`. The quote is usually obtained
+    /// using [`Connection::identifier_quote_char`]. Fails with [`Error::InvalidTemplateArgument`]
+    /// if an invalid argument has been passed to [`Self::arg`], or no value has been set for a
+    /// placeholder. Fails with [`Error::UnquotableIdentifier`] if `quote` is blank and an
+    /// identifier is not a plain name.
+    pub fn to_sql(&self, quote: &str) -> Result<String, Error> {
+        let mut sql = String::new();
+        self.render(quote, &mut sql)?;
+        Ok(sql)
+    }
+
+    /// Renders the statement using the identifier quote of the data source and executes it with
+    /// `params` bound to its `?` placeholders.
+    pub fn execute<'c>(
+        &self,
+        connection: &'c Connection<'_>,
+        params: impl ParameterCollectionRef,
+    ) -> Result<Option<CursorImpl<StatementImpl<'c>>>, Error> {
+        let quote = connection.identifier_quote_char()?;
+        connection.execute(&self.to_sql(&quote)?, params)
+    }
+
+    fn render(&self, quote: &str, sql: &mut String) -> Result<(), Error> {
+        if let Some(description) = &self.invalid_argument {
+            return Err(Error::InvalidTemplateArgument {
+                description: description.clone(),
+            });
+        }
+        for piece in &self.pieces {
+            match *piece {
+                Piece::Literal(text) => sql.push_str(text),
+                Piece::Placeholder { name, .. } => {
+                    let value = self
+                        .arguments
+                        .iter()
+                        .find(|(argument, _)| *argument == name)
+                        .map(|(_, value)| value)
+                        .ok_or_else(|| Error::InvalidTemplateArgument {
+                            description: format!("No value set for placeholder `{name}`."),
+                        })?;
+                    match value {
+                        TemplateValue::Ident(ident) => {
+                            sql.push_str(&quote_identifier(ident, quote)?)
+                        }
+                        TemplateValue::Fragment(fragment) => fragment.render(quote, sql)?,
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse(template: &'static str) -> Vec<Piece> {
+    let mut pieces = Vec::new();
+    let mut rest = template;
+    while let Some(pos) = rest.find(['{', '}']) {
+        if pos != 0 {
+            pieces.push(Piece::Literal(&rest[..pos]));
+        }
+        let tail = &rest[pos..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            pieces.push(Piece::Literal(&tail[..1]));
+            rest = &tail[2..];
+            continue;
+        }
+        assert!(
+            tail.starts_with('{'),
+            "Unmatched `}}` in SQL template. Use `}}}}` for a literal brace."
+        );
+        let end = tail
+            .find('}')
+            .expect("Unmatched `{` in SQL template. Use `{{` for a literal brace.");
+        let (name, kind) = tail[1..end]
+            .split_once(':')
+            .expect("Placeholders in SQL templates must have a kind, e.g. `{table:ident}`.");
+        let kind = match kind.trim() {
+            "ident" => PlaceholderKind::Ident,
+            "fragment" => PlaceholderKind::Fragment,
+            other => panic!(
+                "Unknown kind of placeholder `{other}` in SQL template. Expected `ident` or \
+                `fragment`."
+            ),
+        };
+        pieces.push(Piece::Placeholder {
+            name: name.trim(),
+            kind,
+        });
+        rest = &tail[end + 1..];
+    }
+    if !rest.is_empty() {
+        pieces.push(Piece::Literal(rest));
+    }
+    pieces
+}
+
+/// Constructs a [`SqlTemplate`] from a string literal and sets the values of its placeholders.
+///
+/// ```
+/// use odbc_api::sql;
+///
+/// let table = String::from("Users; DROP TABLE Users");
+/// let query = sql!("SELECT * FROM {table:ident} WHERE id = ?", table = table);
+///
+/// assert_eq!(
+///     "SELECT * FROM `Users; DROP TABLE Users` WHERE id = ?",
+///     query.to_sql("`").unwrap()
+/// );
+/// ```
+#[macro_export]
+macro_rules! sql {
+    ($template:literal $(, $name:ident = $value:expr)* $(,)?) => {
+        $crate::SqlTemplate::new($template)$(.arg(stringify!($name), $value))*
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Error;
+
+    use super::SqlTemplate;
+
+    #[test]
+    fn escaped_braces_and_repeated_placeholders() {
+        let template = sql!(
+            "SELECT '{{}}' FROM {t:ident} JOIN {t:ident} AS b",
+            t = "a\"b"
+        );
+
+        assert_eq!(
+            r#"SELECT '{}' FROM "a""b" JOIN "a""b" AS b"#,
+            template.to_sql("\"").unwrap()
+        );
+    }
+
+    #[test]
+    fn identifier_for_fragment_placeholder() {
+        let template =
+            SqlTemplate::new("SELECT * FROM t {order:fragment}").arg("order", "ORDER BY a");
+
+        let result = template.to_sql("\"");
+
+        assert!(matches!(
+            result,
+            Err(Error::InvalidTemplateArgument { description })
+                if description.contains("does not match its kind")
+        ));
+    }
+
+    #[test]
+    fn unknown_and_missing_placeholders() {
+        let unknown = sql!("SELECT * FROM {t:ident}", t = "a", u = "b");
+        let missing = sql!("SELECT {c:ident} FROM {t:ident}", t = "a");
+
+        assert!(matches!(
+            unknown.to_sql("\""),
+            Err(Error::InvalidTemplateArgument { .. })
+        ));
+        assert!(matches!(
+            missing.to_sql("\""),
+            Err(Error::InvalidTemplateArgument { .. })
+        ));
+    }
+}
@@ -1,31 +1,77 @@
+use std::{mem::ManuallyDrop, ptr};
+
 use odbc_sys::{HStmt, Handle, HandleType};
 
 use crate::{
-    handles::{drop_handle, AsHandle, AsStatementRef, Statement, StatementRef},
+    handles::{
+        detach_statement_context, drop_handle, AsHandle, AsStatementRef, ExecutionStatus,
+        Statement, StatementRef, StatementRegistration,
+    },
     Connection,
 };
 
 /// Statement handle which also takes ownership of Connection
 pub struct StatementConnection<'env> {
     handle: HStmt,
-    _parent: Connection<'env>,
+    parent: Connection<'env>,
+    /// Allows the environment to cancel this statement during shutdown.
+    registration: Option<StatementRegistration>,
 }
 
 impl<'env> StatementConnection<'env> {
     pub(crate) unsafe fn new(handle: HStmt, parent: Connection<'env>) -> Self {
+        let registration = parent.register_statement(handle);
         Self {
-            _parent: parent,
+            parent,
             handle,
+            registration,
         }
     }
 
     pub fn as_stmt_ref(&mut self) -> StatementRef<'_> {
-        unsafe { StatementRef::new(self.handle) }
+        let status = self
+            .registration
+            .as_ref()
+            .map(StatementRegistration::status);
+        unsafe { StatementRef::with_status(self.handle, status) }
+    }
+
+    /// Frees the statement handle and gives back ownership of the connection it has been allocated
+    /// on. Useful if the connection is to be returned to a pool, after the statement is no longer
+    /// required.
+    ///
+    /// ```no_run
+    /// use odbc_api::{Connection, Error, IntoParameter};
+    ///
+    /// fn insert_and_give_back<'env>(
+    ///     conn: Connection<'env>,
+    ///     names: &[&str]
+    /// ) -> Result<Connection<'env>, Error> {
+    ///     let mut prepared = conn.into_prepared("INSERT INTO Names (name) VALUES (?)")?;
+    ///     for name in names {
+    ///         prepared.execute(&name.into_parameter())?;
+    ///     }
+    ///     Ok(prepared.into_statement().into_connection())
+    /// }
+    /// ```
+    pub fn into_connection(self) -> Connection<'env> {
+        let this = ManuallyDrop::new(self);
+        unsafe {
+            // Unregister before freeing, so the handle is not cancelled after it has been freed.
+            drop(ptr::read(&this.registration));
+            detach_statement_context(this.handle as Handle);
+            drop_handle(this.handle as Handle, HandleType::Stmt);
+            // Safe: `this` is never dropped, so `parent` is moved out exactly once.
+            ptr::read(&this.parent)
+        }
     }
 }
 
 impl<'s> Drop for StatementConnection<'s> {
     fn drop(&mut self) {
+        // Unregister before freeing, so the handle is not cancelled after it has been freed.
+        self.registration = None;
+        detach_statement_context(self.handle as Handle);
         unsafe {
             drop_handle(self.handle as Handle, HandleType::Stmt);
         }
@@ -46,6 +92,12 @@ impl Statement for StatementConnection<'_> {
     fn as_sys(&self) -> HStmt {
         self.handle
     }
+
+    fn execution_status(&self) -> Option<&ExecutionStatus> {
+        self.registration
+            .as_ref()
+            .map(StatementRegistration::status)
+    }
 }
 
 impl<'o> AsStatementRef for StatementConnection<'o> {
@@ -0,0 +1,370 @@
+/// Kind of an SQL statement, as determined by [`classify_statement`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatementKind {
+    /// Returns a result set, e.g. `SELECT`, `VALUES`, `SHOW` or `EXPLAIN`, including queries with
+    /// common table expressions.
+    Query,
+    /// Data manipulation, e.g. `INSERT`, `UPDATE`, `DELETE` or `MERGE`. Reports the number of rows
+    /// affected, unless it returns rows using e.g. `RETURNING` or `OUTPUT`.
+    Dml,
+    /// Data definition, e.g. `CREATE`, `ALTER`, `DROP` or `TRUNCATE`, as well as `GRANT` and
+    /// `REVOKE`.
+    Ddl,
+    /// Invokes a stored procedure, e.g. `CALL`, `EXEC` or the ODBC escape sequence `{call ...}`.
+    /// Depending on the procedure it may return any number of result sets.
+    Call,
+    /// Controls transactions, e.g. `BEGIN TRANSACTION`, `COMMIT`, `ROLLBACK` or `SAVEPOINT`.
+    TransactionControl,
+    /// Anything else, e.g. `SET`, `USE`, vendor specific commands or empty text.
+    Other,
+}
+
+impl StatementKind {
+    /// `true` if executing the statement is expected to return a cursor. Statements of kind
+    /// [`Self::Call`] or [`Self::Other`] may also return one, so inspect the result of
+    /// [`crate::Connection::execute`] for these.
+    pub fn expects_cursor(self) -> bool {
+        self == StatementKind::Query
+    }
+}
+
+/// Classifies the first statement in `sql` by its leading keywords, without asking the data
+/// source. Allows interactive tools and middleware to decide whether to expect a cursor or a row
+/// count before executing the statement. Comments, quoted strings and identifiers are skipped, so
+/// neither `-- DELETE` nor `'DROP'` changes the outcome.
+///
+/// The analysis is lexical. It does not validate the statement and does not know the dialects of
+/// all data sources, so unknown statements are classified as [`StatementKind::Other`].
+///
+/// ```
+/// use odbc_api::{classify_statement, StatementKind};
+///
+/// assert_eq!(StatementKind::Query, classify_statement("/* report */ SELECT * FROM Movies"));
+/// assert_eq!(
+///     StatementKind::Dml,
+///     classify_statement("WITH old AS (SELECT id FROM Movies) DELETE FROM Movies")
+/// );
+/// assert_eq!(StatementKind::Call, classify_statement("{call refresh_stats(?)}"));
+/// ```
+pub fn classify_statement(sql: &str) -> StatementKind {
+    let mut tokens = Lexer::new(sql);
+    let first = loop {
+        match tokens.next() {
+            // Leading separators and parentheses, e.g. `(SELECT 1) UNION (SELECT 2)`.
+            Some(Token::Punct(b';' | b'(')) => (),
+            other => break other,
+        }
+    };
+    let keyword = match first {
+        Some(Token::Word(word)) => word.to_ascii_uppercase(),
+        Some(Token::Punct(b'{')) => return classify_escape_sequence(tokens),
+        _ => return StatementKind::Other,
+    };
+    match keyword.as_str() {
+        "WITH" => classify_main_statement(tokens),
+        "BEGIN" => match tokens.next() {
+            None | Some(Token::Punct(b';')) => StatementKind::TransactionControl,
+            Some(Token::Word(word))
+                if BEGIN_TRANSACTION
+                    .iter()
+                    .any(|keyword| word.eq_ignore_ascii_case(keyword)) =>
+            {
+                StatementKind::TransactionControl
+            }
+            // A block of procedural code, e.g. `BEGIN ... END` in Transact-SQL or PL/SQL.
+            _ => StatementKind::Other,
+        },
+        other => kind_of_keyword(other),
+    }
+}
+
+/// Keywords following `BEGIN`, if it starts a transaction rather than a block of procedural code.
+const BEGIN_TRANSACTION: &[&str] = &[
+    "TRAN",
+    "TRANSACTION",
+    "WORK",
+    "DEFERRED",
+    "IMMEDIATE",
+    "EXCLUSIVE",
+];
+
+/// Splits `sql` into statements at semicolons, which are neither part of a literal nor a comment.
+/// Statements are trimmed. Statements consisting of nothing but whitespace and comments are
+/// omitted.
+pub(crate) fn split_statements(sql: &str) -> Vec<&str> {
+    let mut statements = Vec::new();
+    let mut tokens = Lexer::new(sql);
+    let mut start = 0;
+    let mut is_blank = true;
+    while let Some(token) = tokens.next() {
+        if token == Token::Punct(b';') {
+            // The separator is a single byte in front of the current position.
+            if !is_blank {
+                statements.push(sql[start..tokens.position - 1].trim());
+            }
+            start = tokens.position;
+            is_blank = true;
+        } else {
+            is_blank = false;
+        }
+    }
+    if !is_blank {
+        statements.push(sql[start..].trim());
+    }
+    statements
+}
+
+/// Kind of a statement starting with `keyword`, in upper case.
+fn kind_of_keyword(keyword: &str) -> StatementKind {
+    match keyword {
+        "SELECT" | "VALUES" | "TABLE" | "SHOW" | "DESCRIBE" | "DESC" | "EXPLAIN" => {
+            StatementKind::Query
+        }
+        "INSERT" | "UPDATE" | "DELETE" | "MERGE" | "UPSERT" | "REPLACE" => StatementKind::Dml,
+        "CREATE" | "ALTER" | "DROP" | "TRUNCATE" | "RENAME" | "COMMENT" | "GRANT" | "REVOKE" => {
+            StatementKind::Ddl
+        }
+        "CALL" | "EXEC" | "EXECUTE" => StatementKind::Call,
+        "COMMIT" | "ROLLBACK" | "SAVEPOINT" | "RELEASE" | "START" => {
+            StatementKind::TransactionControl
+        }
+        _ => StatementKind::Other,
+    }
+}
+
+/// Classifies the statement following the common table expressions of a `WITH` clause, i.e. the
+/// first keyword outside of parentheses, which starts a query or data manipulation.
+fn classify_main_statement(tokens: Lexer<'_>) -> StatementKind {
+    let mut depth = 0usize;
+    for token in tokens {
+        match token {
+            Token::Punct(b'(') => depth += 1,
+            Token::Punct(b')') => depth = depth.saturating_sub(1),
+            Token::Punct(b';') if depth == 0 => break,
+            Token::Word(word) if depth == 0 => {
+                let kind = kind_of_keyword(&word.to_ascii_uppercase());
+                if matches!(kind, StatementKind::Query | StatementKind::Dml) {
+                    return kind;
+                }
+            }
+            _ => (),
+        }
+    }
+    StatementKind::Other
+}
+
+/// Classifies an ODBC escape sequence, e.g. `{call proc(?)}` or `{? = call proc(?)}`.
+fn classify_escape_sequence(tokens: Lexer<'_>) -> StatementKind {
+    for token in tokens {
+        match token {
+            Token::Word(word) if word.eq_ignore_ascii_case("call") => return StatementKind::Call,
+            Token::Punct(b'?' | b'=') => (),
+            _ => break,
+        }
+    }
+    StatementKind::Other
+}
+
+/// Token of SQL text, as far as it matters for [`classify_statement`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Token<'s> {
+    /// Keyword or unquoted identifier.
+    Word(&'s str),
+    /// Quoted string or identifier, or a number.
+    Literal,
+    /// Any other character.
+    Punct(u8),
+}
+
+/// Splits SQL text into tokens, skipping whitespace and comments.
+pub(crate) struct Lexer<'s> {
+    sql: &'s str,
+    /// Byte offset just past the last token.
+    pub(crate) position: usize,
+}
+
+impl<'s> Lexer<'s> {
+    pub(crate) fn new(sql: &'s str) -> Self {
+        Self { sql, position: 0 }
+    }
+
+    fn rest(&self) -> &'s [u8] {
+        &self.sql.as_bytes()[self.position..]
+    }
+
+    /// Advances past whitespace and comments.
+    fn skip_trivia(&mut self) {
+        loop {
+            let rest = self.rest();
+            if rest.first().is_some_and(|byte| byte.is_ascii_whitespace()) {
+                self.position += 1;
+            } else if rest.starts_with(b"--") {
+                self.position += rest.iter().position(|&b| b == b'\n').unwrap_or(rest.len());
+            } else if rest.starts_with(b"/*") {
+                // Comments nest in some dialects, e.g. PostgreSQL.
+                let mut depth = 0;
+                let mut index = 0;
+                while index < rest.len() {
+                    if rest[index..].starts_with(b"/*") {
+                        depth += 1;
+                        index += 2;
+                    } else if rest[index..].starts_with(b"*/") {
+                        depth -= 1;
+                        index += 2;
+                        if depth == 0 {
+                            break;
+                        }
+                    } else {
+                        index += 1;
+                    }
+                }
+                self.position += index;
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Advances past the closing `delimiter`, or to the end if the literal is not terminated.
+    fn skip_past(&mut self, delimiter: &[u8]) {
+        let rest = self.rest();
+        self.position += rest
+            .windows(delimiter.len())
+            .position(|window| window == delimiter)
+            .map_or(rest.len(), |index| index + delimiter.len());
+    }
+}
+
+impl<'s> Iterator for Lexer<'s> {
+    type Item = Token<'s>;
+
+    fn next(&mut self) -> Option<Token<'s>> {
+        self.skip_trivia();
+        let rest = self.rest();
+        let &first = rest.first()?;
+        let token = match first {
+            b'\'' | b'"' | b'`' => {
+                self.position += 1;
+                self.skip_past(&[first]);
+                Token::Literal
+            }
+            b'[' => {
+                self.position += 1;
+                self.skip_past(b"]");
+                Token::Literal
+            }
+            // Dollar quoted string of PostgreSQL, e.g. `$$text$$` or `$tag$text$tag$`.
+            b'$' if dollar_quote_tag(rest).is_some() => {
+                let tag = dollar_quote_tag(rest).unwrap();
+                self.position += tag.len();
+                self.skip_past(tag);
+                Token::Literal
+            }
+            _ if is_word_char(first) => {
+                let start = self.position;
+                let length = rest
+                    .iter()
+                    .position(|&byte| !is_word_char(byte))
+                    .unwrap_or(rest.len());
+                self.position += length;
+                if first.is_ascii_digit() {
+                    Token::Literal
+                } else {
+                    Token::Word(&self.sql[start..self.position])
+                }
+            }
+            _ => {
+                // Advance a whole character, so the position stays at a character boundary.
+                let length = self.sql[self.position..]
+                    .chars()
+                    .next()
+                    .map_or(1, char::len_utf8);
+                self.position += length;
+                Token::Punct(first)
+            }
+        };
+        Some(token)
+    }
+}
+
+/// Opening tag of a dollar quoted string at the start of `text`, including both dollar signs.
+fn dollar_quote_tag(text: &[u8]) -> Option<&[u8]> {
+    let end = text[1..].iter().position(|&byte| !is_word_char(byte))? + 1;
+    let valid = text[end] == b'$' && text.get(1).is_none_or(|byte| !byte.is_ascii_digit());
+    valid.then(|| &text[..=end])
+}
+
+fn is_word_char(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || !byte.is_ascii()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{classify_statement, split_statements, StatementKind};
+
+    #[test]
+    fn classify_by_leading_keyword() {
+        assert_eq!(StatementKind::Query, classify_statement("select 1"));
+        assert_eq!(
+            StatementKind::Query,
+            classify_statement("(SELECT 1) UNION (SELECT 2)")
+        );
+        assert_eq!(
+            StatementKind::Dml,
+            classify_statement("INSERT INTO t VALUES (1)")
+        );
+        assert_eq!(StatementKind::Ddl, classify_statement("DROP TABLE t"));
+        assert_eq!(StatementKind::Call, classify_statement("EXEC sp_who"));
+        assert_eq!(StatementKind::Call, classify_statement("{? = call f(?)}"));
+        assert_eq!(
+            StatementKind::TransactionControl,
+            classify_statement("BEGIN;")
+        );
+        assert_eq!(
+            StatementKind::TransactionControl,
+            classify_statement("begin transaction")
+        );
+        assert_eq!(
+            StatementKind::Other,
+            classify_statement("BEGIN UPDATE t SET a = 1; END")
+        );
+        assert_eq!(StatementKind::Other, classify_statement("SET NOCOUNT ON"));
+        assert_eq!(StatementKind::Other, classify_statement("  -- nothing"));
+    }
+
+    #[test]
+    fn skip_comments_and_literals() {
+        assert_eq!(
+            StatementKind::Dml,
+            classify_statement("-- SELECT\n/* outer /* SELECT */ */ DELETE FROM t")
+        );
+        assert_eq!(
+            StatementKind::Query,
+            classify_statement(
+                "WITH \"DELETE\" AS (SELECT ') INSERT' AS a) SELECT $$ UPDATE $$ FROM \"DELETE\""
+            )
+        );
+        assert_eq!(
+            StatementKind::Dml,
+            classify_statement("WITH x(a) AS (SELECT 1) UPDATE t SET a = (SELECT a FROM x)")
+        );
+    }
+
+    #[test]
+    fn split_script_at_semicolons() {
+        let script = "CREATE TABLE t (a TEXT);\n\
+            INSERT INTO t VALUES ('a;b'); -- c;d\n\
+            ;\n\
+            DO $$ BEGIN PERFORM 1; END $$\n\
+            /* done; */";
+
+        assert_eq!(
+            vec![
+                "CREATE TABLE t (a TEXT)",
+                "INSERT INTO t VALUES ('a;b')",
+                "DO $$ BEGIN PERFORM 1; END $$\n/* done; */"
+            ],
+            split_statements(script)
+        );
+    }
+}
@@ -0,0 +1,261 @@
+use std::time::Duration;
+
+use crate::{
+    handles::{Concurrency, CursorType, Statement},
+    Connection, Error,
+};
+
+/// Statement attributes set before a query is prepared or executed. Options not specified keep the
+/// driver default. Use with [`crate::Connection::execute_with_options`] or
+/// [`crate::Connection::prepare_with_options`].
+///
+/// The options are validated against the capabilities the driver reports via `SQLGetInfo`, before
+/// a statement is allocated. Requesting e.g. a scrollable cursor from a driver which only supports
+/// forward only cursors fails with [`Error::UnsupportedStatementOption`], rather than having the
+/// driver silently substitute it. Drivers which report no capabilities at all are trusted.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use odbc_api::{Connection, Error, StatementOptions};
+///
+/// fn preview(conn: &Connection<'_>, query: &str) -> Result<(), Error> {
+///     // Allows Microsoft SQL Server to use a fast forward cursor.
+///     let options = StatementOptions::new()
+///         .read_only_forward_only()
+///         .with_query_timeout(Duration::from_secs(30))
+///         .with_max_rows(100);
+///     if let Some(cursor) = conn.execute_with_options(query, (), &options)? {
+///         // Fetch at most 100 rows ...
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatementOptions {
+    query_timeout: Option<Duration>,
+    max_rows: Option<usize>,
+    cursor_type: Option<CursorType>,
+    concurrency: Option<Concurrency>,
+    use_bookmarks: bool,
+}
+
+impl StatementOptions {
+    /// Options keeping all driver defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Time to wait for the statement to execute, before the driver cancels it. Rounded up to
+    /// whole seconds. [`Duration::ZERO`] means no timeout. Drivers may substitute the value.
+    pub fn with_query_timeout(mut self, timeout: Duration) -> Self {
+        self.query_timeout = Some(timeout);
+        self
+    }
+
+    /// Limits the number of rows in the result set. The limit is enforced by the driver or the
+    /// data source, rather than by stopping to fetch on the client side. `0` means no limit.
+    pub fn with_max_rows(mut self, max_rows: usize) -> Self {
+        self.max_rows = Some(max_rows);
+        self
+    }
+
+    /// Requests a specific kind of cursor.
+    pub fn with_cursor_type(mut self, cursor_type: CursorType) -> Self {
+        self.cursor_type = Some(cursor_type);
+        self
+    }
+
+    /// Requests a specific concurrency control.
+    pub fn with_concurrency(mut self, concurrency: Concurrency) -> Self {
+        self.concurrency = Some(concurrency);
+        self
+    }
+
+    /// Enables variable length bookmarks, so the result set contains a bookmark in column `0`.
+    pub fn with_bookmarks(mut self) -> Self {
+        self.use_bookmarks = true;
+        self
+    }
+
+    /// Shorthand for a read only, forward only cursor. This combination is the cheapest way to
+    /// fetch a result set once, from start to end, which is what most applications do.
+    pub fn read_only_forward_only(self) -> Self {
+        self.with_cursor_type(CursorType::ForwardOnly)
+            .with_concurrency(Concurrency::ReadOnly)
+    }
+
+    /// The requested query timeout. `None` if the driver default is used.
+    pub fn query_timeout(&self) -> Option<Duration> {
+        self.query_timeout
+    }
+
+    /// The requested maximum number of rows. `None` if the driver default is used.
+    pub fn max_rows(&self) -> Option<usize> {
+        self.max_rows
+    }
+
+    /// The requested kind of cursor. `None` if the driver default is used.
+    pub fn cursor_type(&self) -> Option<CursorType> {
+        self.cursor_type
+    }
+
+    /// The requested concurrency control. `None` if the driver default is used.
+    pub fn concurrency(&self) -> Option<Concurrency> {
+        self.concurrency
+    }
+
+    /// `true` if bookmarks have been requested.
+    pub fn use_bookmarks(&self) -> bool {
+        self.use_bookmarks
+    }
+
+    /// Checks the capabilities reported by the driver of `connection` for the requested cursor
+    /// type, concurrency and bookmarks. Does not query the driver, if none of these are requested.
+    pub(crate) fn validate(&self, connection: &Connection<'_>) -> Result<(), Error> {
+        self.check_capabilities(|info_type| match connection.info_integer(info_type) {
+            Ok(value) => Some(value),
+            Err(error) => {
+                log::debug!("Driver does not report capability {info_type}: {error}");
+                None
+            }
+        })
+    }
+
+    /// Validates the options against the bitmasks returned by `info`. `None`, or a bitmask without
+    /// any bits set, means the capability is unknown and the option is accepted.
+    fn check_capabilities(&self, mut info: impl FnMut(u16) -> Option<u32>) -> Result<(), Error> {
+        if self.cursor_type.is_none() && self.concurrency.is_none() && !self.use_bookmarks {
+            return Ok(());
+        }
+        let mut supports = |info_type: u16, mask: u32, option: &'static str| match info(info_type) {
+            Some(bits) if bits != 0 && bits & mask == 0 => {
+                Err(Error::UnsupportedStatementOption { option })
+            }
+            _ => Ok(()),
+        };
+        // `SQL_SCROLL_OPTIONS`
+        if let Some(cursor_type) = self.cursor_type {
+            let (mask, option) = match cursor_type {
+                CursorType::ForwardOnly => (0x01, "forward only cursors"),
+                CursorType::KeysetDriven => (0x02, "keyset driven cursors"),
+                CursorType::Dynamic => (0x04, "dynamic cursors"),
+                CursorType::Static => (0x10, "static cursors"),
+            };
+            supports(44, mask, option)?;
+        }
+        // `SQL_<CURSOR TYPE>_CURSOR_ATTRIBUTES1` and `SQL_<CURSOR TYPE>_CURSOR_ATTRIBUTES2` of the
+        // cursor type in effect.
+        let (attributes1, attributes2) = match self.cursor_type.unwrap_or(CursorType::ForwardOnly) {
+            CursorType::ForwardOnly => (146, 147),
+            CursorType::KeysetDriven => (150, 151),
+            CursorType::Dynamic => (144, 145),
+            CursorType::Static => (167, 168),
+        };
+        if let Some(concurrency) = self.concurrency {
+            let (mask, option) = match concurrency {
+                Concurrency::ReadOnly => (0x01, "read only concurrency"),
+                Concurrency::Lock => (0x02, "lock concurrency"),
+                Concurrency::RowVersions => (0x04, "optimistic concurrency comparing row versions"),
+                Concurrency::Values => (0x08, "optimistic concurrency comparing values"),
+            };
+            supports(attributes2, mask, option)?;
+        }
+        if self.use_bookmarks {
+            // `SQL_CA1_BOOKMARK`
+            supports(attributes1, 0x2000, "bookmarks")?;
+        }
+        Ok(())
+    }
+
+    /// Sets the requested attributes on `statement`. The cursor type is set first, since changing
+    /// it may also change the concurrency.
+    pub(crate) fn apply_to(&self, statement: &mut impl Statement) -> Result<(), Error> {
+        if let Some(cursor_type) = self.cursor_type {
+            statement
+                .set_cursor_type(cursor_type)
+                .into_result(statement)?;
+        }
+        if let Some(concurrency) = self.concurrency {
+            statement
+                .set_concurrency(concurrency)
+                .into_result(statement)?;
+        }
+        if let Some(timeout) = self.query_timeout {
+            let timeout_sec = timeout.as_secs() + u64::from(timeout.subsec_nanos() != 0);
+            statement
+                .set_query_timeout_sec(timeout_sec.try_into().unwrap_or(usize::MAX))
+                .into_result(statement)?;
+        }
+        if let Some(max_rows) = self.max_rows {
+            statement.set_max_rows(max_rows).into_result(statement)?;
+        }
+        if self.use_bookmarks {
+            statement.set_use_bookmarks(true).into_result(statement)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        handles::{Concurrency, CursorType},
+        Error,
+    };
+
+    use super::StatementOptions;
+
+    #[test]
+    fn default_options_do_not_query_driver() {
+        let result = StatementOptions::new()
+            .with_max_rows(10)
+            .check_capabilities(|_| panic!("Must not query capabilities"));
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn reject_unsupported_cursor_type() {
+        // Driver supports only forward only cursors (`SQL_SO_FORWARD_ONLY`).
+        let info = |info_type| (info_type == 44).then_some(0x01);
+
+        let result = StatementOptions::new()
+            .with_cursor_type(CursorType::Static)
+            .check_capabilities(info);
+
+        assert!(matches!(
+            result,
+            Err(Error::UnsupportedStatementOption {
+                option: "static cursors"
+            })
+        ));
+    }
+
+    #[test]
+    fn check_concurrency_of_requested_cursor_type() {
+        // Static cursors are read only, forward only cursors support locking.
+        let info = |info_type| match info_type {
+            44 => Some(0x11),
+            147 => Some(0x03),
+            168 => Some(0x01),
+            _ => None,
+        };
+        let lock = StatementOptions::new().with_concurrency(Concurrency::Lock);
+
+        assert!(lock.check_capabilities(info).is_ok());
+        assert!(lock
+            .with_cursor_type(CursorType::Static)
+            .check_capabilities(info)
+            .is_err());
+    }
+
+    #[test]
+    fn trust_drivers_reporting_no_capabilities() {
+        let result = StatementOptions::new()
+            .with_cursor_type(CursorType::Dynamic)
+            .with_bookmarks()
+            .check_capabilities(|_| Some(0));
+
+        assert!(result.is_ok());
+    }
+}
@@ -0,0 +1,145 @@
+use crate::{
+    buffers::{AnyBuffer, BufferDesc},
+    handles::{SqlChar, Statement},
+    parameter_collection::ParameterTupleElement,
+    vendor::mssql::SOPT_SS_PARAM_FOCUS,
+    Error,
+};
+
+/// `SQL_DEFAULT_PARAM`. Indicator of a table-valued parameter without any rows.
+const DEFAULT_PARAM: isize = -5;
+
+/// A table-valued parameter of Microsoft SQL Server. Holds the rows of the table in one column
+/// buffer per column of the table type. Bind it by passing a reference as an element of a
+/// parameter tuple. Only supported by the Microsoft ODBC Driver for SQL Server.
+///
+/// ```no_run
+/// use odbc_api::{buffers::{AnyBuffer, BufferDesc}, Connection, Error, TableValuedParam};
+///
+/// /// Expects `CREATE TYPE IdList AS TABLE (id INTEGER NOT NULL)` and a stored procedure
+/// /// `DeleteOrders` taking a parameter `@ids IdList READONLY`.
+/// fn delete_orders(conn: &Connection<'_>, ids: &[i32]) -> Result<(), Error> {
+///     let mut tvp = TableValuedParam::new(
+///         "IdList",
+///         ids.len(),
+///         [BufferDesc::I32 { nullable: false }],
+///     );
+///     tvp.set_num_rows(ids.len());
+///     if let AnyBuffer::I32(column) = tvp.column_mut(0) {
+///         column[..ids.len()].copy_from_slice(ids);
+///     }
+///     conn.execute("{CALL DeleteOrders(?)}", (&tvp,))?;
+///     Ok(())
+/// }
+/// ```
+pub struct TableValuedParam {
+    /// Zero terminated name of the table type. Empty if the driver should infer it from the
+    /// stored procedure called.
+    type_name: Vec<SqlChar>,
+    columns: Vec<AnyBuffer>,
+    capacity: usize,
+    /// Number of rows, or `SQL_DEFAULT_PARAM` for an empty table. Bound as indicator of the
+    /// table-valued parameter.
+    indicator: isize,
+}
+
+impl TableValuedParam {
+    /// A table of the table type `type_name`, which can hold up to `capacity` rows, with one
+    /// column per element of `descs`. Pass an empty `type_name` to let the driver infer the type
+    /// from the parameter of the stored procedure called. The table is empty until
+    /// [`Self::set_num_rows`] is called.
+    pub fn new(
+        type_name: &str,
+        capacity: usize,
+        descs: impl IntoIterator<Item = BufferDesc>,
+    ) -> Self {
+        #[cfg(not(feature = "narrow"))]
+        let mut chars: Vec<SqlChar> = type_name.encode_utf16().collect();
+        #[cfg(feature = "narrow")]
+        let mut chars: Vec<SqlChar> = type_name.as_bytes().to_vec();
+        if !chars.is_empty() {
+            chars.push(0);
+        }
+        let columns = descs
+            .into_iter()
+            .map(|desc| AnyBuffer::from_desc(capacity, desc))
+            .collect();
+        Self {
+            type_name: chars,
+            columns,
+            capacity,
+            indicator: DEFAULT_PARAM,
+        }
+    }
+
+    /// Maximum number of rows the table can hold.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of rows sent to the data source.
+    pub fn num_rows(&self) -> usize {
+        self.indicator.max(0) as usize
+    }
+
+    /// Sets the number of rows sent to the data source. Values of the first `num_rows` rows of
+    /// each column must be set using [`Self::column_mut`].
+    ///
+    /// # Panics
+    ///
+    /// If `num_rows` exceeds the capacity of the table.
+    pub fn set_num_rows(&mut self, num_rows: usize) {
+        assert!(
+            num_rows <= self.capacity,
+            "Number of rows of a table-valued parameter must not exceed its capacity."
+        );
+        self.indicator = if num_rows == 0 {
+            DEFAULT_PARAM
+        } else {
+            num_rows as isize
+        };
+    }
+
+    /// Number of columns of the table.
+    pub fn num_cols(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Buffer holding the values of the column at the zero based `buffer_index`, e.g. to call
+    /// [`crate::buffers::TextColumn::set_value`] on.
+    pub fn column_mut(&mut self, buffer_index: usize) -> &mut AnyBuffer {
+        &mut self.columns[buffer_index]
+    }
+}
+
+/// Binds the table as the parameter with `parameter_number`, followed by its columns.
+unsafe impl ParameterTupleElement for &TableValuedParam {
+    unsafe fn bind_to(
+        &mut self,
+        parameter_number: u16,
+        stmt: &mut impl Statement,
+    ) -> Result<(), Error> {
+        stmt.bind_table_valued_parameter(
+            parameter_number,
+            &self.type_name,
+            self.capacity,
+            &self.indicator,
+        )
+        .into_result(stmt)?;
+        stmt.set_vendor_attr_int(SOPT_SS_PARAM_FOCUS, parameter_number.into())
+            .into_result(stmt)?;
+        for (index, column) in self.columns.iter().enumerate() {
+            let column_number = (index + 1).try_into().unwrap();
+            let result = stmt
+                .bind_input_parameter(column_number, column)
+                .into_result(stmt);
+            if let Err(error) = result {
+                // Do not bind subsequent parameters as columns of the table.
+                let _ = stmt.set_vendor_attr_int(SOPT_SS_PARAM_FOCUS, 0);
+                return Err(error);
+            }
+        }
+        stmt.set_vendor_attr_int(SOPT_SS_PARAM_FOCUS, 0)
+            .into_result(stmt)
+    }
+}
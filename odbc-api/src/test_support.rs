@@ -0,0 +1,277 @@
+//! Construct result sets in memory, to test code processing fetched row sets without a database.
+//!
+//! ```
+//! use odbc_api::{buffers::ColumnarAnyBuffer, test_support::ResultSetBuilder};
+//!
+//! /// Application code, usually called with batches fetched by a `BlockCursor`.
+//! fn sum_of_ids(batch: &ColumnarAnyBuffer) -> i32 {
+//!     batch.column(0).as_slice::<i32>().unwrap().iter().sum()
+//! }
+//!
+//! let result_set = ResultSetBuilder::new()
+//!     .column("id", vec![1, 2, 3])
+//!     .column("name", vec![Some("Peter"), None, Some("Anna")])
+//!     .build();
+//!
+//! assert_eq!(["id", "name"], result_set.column_names());
+//! assert_eq!(6, sum_of_ids(result_set.buffer()));
+//! ```
+//!
+//! With the `test_harness` feature enabled, this module also offers `TestDatabase`, a throwaway
+//! database for integration tests.
+
+#[cfg(feature = "test_harness")]
+mod harness;
+
+use odbc_sys::{Date, Time, Timestamp};
+
+use crate::{
+    buffers::{AnyBuffer, BufferDesc, ColumnarAnyBuffer},
+    Bit, RowSetBuffer,
+};
+
+#[cfg(feature = "test_harness")]
+pub use self::harness::TestDatabase;
+
+/// Builds a [`ResultSet`] column by column from Rust vectors.
+#[derive(Debug, Default)]
+pub struct ResultSetBuilder {
+    column_names: Vec<String>,
+    num_rows: Option<usize>,
+    buffers: Vec<AnyBuffer>,
+}
+
+impl ResultSetBuilder {
+    /// A result set without any columns.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a column to the result set. Use a vector of `Option`s, to create a nullable column.
+    ///
+    /// # Panics
+    ///
+    /// If the number of values differs from the number of values in the columns added previously.
+    pub fn column(mut self, name: impl Into<String>, values: impl ColumnValues) -> Self {
+        let num_rows = *self.num_rows.get_or_insert(values.num_rows());
+        assert_eq!(
+            num_rows,
+            values.num_rows(),
+            "All columns of a result set must have the same number of rows."
+        );
+        self.column_names.push(name.into());
+        self.buffers.push(values.into_any_buffer());
+        self
+    }
+
+    /// Creates the result set.
+    pub fn build(self) -> ResultSet {
+        let num_rows = self.num_rows.unwrap_or(0);
+        let columns = (1..).zip(self.buffers).collect();
+        let mut buffer = ColumnarAnyBuffer::new(columns);
+        *buffer.mut_num_fetch_rows() = num_rows;
+        ResultSet {
+            column_names: self.column_names,
+            buffer,
+        }
+    }
+}
+
+/// An in memory result set, created by [`ResultSetBuilder`].
+pub struct ResultSet {
+    column_names: Vec<String>,
+    buffer: ColumnarAnyBuffer,
+}
+
+impl ResultSet {
+    /// Names of the columns in the order they have been added to the builder.
+    pub fn column_names(&self) -> &[String] {
+        &self.column_names
+    }
+
+    /// The values of the result set as a single row set, just like they would be returned by
+    /// [`crate::BlockCursor::fetch`] for a [`ColumnarAnyBuffer`] bound to a cursor.
+    pub fn buffer(&self) -> &ColumnarAnyBuffer {
+        &self.buffer
+    }
+
+    /// Takes ownership of the underlying row set buffer.
+    pub fn into_buffer(self) -> ColumnarAnyBuffer {
+        self.buffer
+    }
+}
+
+/// Values of a single column which can be added to a [`ResultSetBuilder`]. Implemented for vectors
+/// of the fixed sized types supported by [`AnyBuffer`], as well as for strings and byte vectors.
+/// Vectors of `Option` create nullable columns.
+pub trait ColumnValues {
+    /// Number of rows in the column.
+    fn num_rows(&self) -> usize;
+
+    /// Creates a buffer holding the values.
+    fn into_any_buffer(self) -> AnyBuffer;
+}
+
+macro_rules! impl_column_values_for_fixed_size {
+    ($t:ty, $variant:ident, $nullable_variant:ident, $desc:ident) => {
+        impl ColumnValues for Vec<$t> {
+            fn num_rows(&self) -> usize {
+                self.len()
+            }
+
+            fn into_any_buffer(self) -> AnyBuffer {
+                AnyBuffer::$variant(self)
+            }
+        }
+
+        impl ColumnValues for Vec<Option<$t>> {
+            fn num_rows(&self) -> usize {
+                self.len()
+            }
+
+            fn into_any_buffer(self) -> AnyBuffer {
+                let num_rows = self.len();
+                let mut buffer =
+                    AnyBuffer::from_desc(num_rows, BufferDesc::$desc { nullable: true });
+                if let AnyBuffer::$nullable_variant(column) = &mut buffer {
+                    column.writer_n(num_rows).write(self.into_iter());
+                }
+                buffer
+            }
+        }
+    };
+}
+
+impl_column_values_for_fixed_size!(Date, Date, NullableDate, Date);
+impl_column_values_for_fixed_size!(Time, Time, NullableTime, Time);
+impl_column_values_for_fixed_size!(Timestamp, Timestamp, NullableTimestamp, Timestamp);
+impl_column_values_for_fixed_size!(f64, F64, NullableF64, F64);
+impl_column_values_for_fixed_size!(f32, F32, NullableF32, F32);
+impl_column_values_for_fixed_size!(i8, I8, NullableI8, I8);
+impl_column_values_for_fixed_size!(i16, I16, NullableI16, I16);
+impl_column_values_for_fixed_size!(i32, I32, NullableI32, I32);
+impl_column_values_for_fixed_size!(i64, I64, NullableI64, I64);
+impl_column_values_for_fixed_size!(u8, U8, NullableU8, U8);
+impl_column_values_for_fixed_size!(u16, U16, NullableU16, U16);
+impl_column_values_for_fixed_size!(u32, U32, NullableU32, U32);
+impl_column_values_for_fixed_size!(u64, U64, NullableU64, U64);
+impl_column_values_for_fixed_size!(Bit, Bit, NullableBit, Bit);
+
+/// Creates a narrow text column just large enough to hold the longest value.
+fn text_buffer<'a>(values: impl Iterator<Item = Option<&'a str>> + Clone) -> AnyBuffer {
+    let num_rows = values.clone().count();
+    let max_str_len = values.clone().flatten().map(str::len).max().unwrap_or(0);
+    let mut buffer = AnyBuffer::from_desc(num_rows, BufferDesc::Text { max_str_len });
+    if let AnyBuffer::Text(column) = &mut buffer {
+        for (index, value) in values.enumerate() {
+            column.set_value(index, value.map(str::as_bytes));
+        }
+    }
+    buffer
+}
+
+/// Creates a binary column just large enough to hold the longest value.
+fn binary_buffer<'a>(values: impl Iterator<Item = Option<&'a [u8]>> + Clone) -> AnyBuffer {
+    let num_rows = values.clone().count();
+    let length = values.clone().flatten().map(<[u8]>::len).max().unwrap_or(0);
+    let mut buffer = AnyBuffer::from_desc(num_rows, BufferDesc::Binary { length });
+    if let AnyBuffer::Binary(column) = &mut buffer {
+        for (index, value) in values.enumerate() {
+            column.set_value(index, value);
+        }
+    }
+    buffer
+}
+
+impl ColumnValues for Vec<&str> {
+    fn num_rows(&self) -> usize {
+        self.len()
+    }
+
+    fn into_any_buffer(self) -> AnyBuffer {
+        text_buffer(self.iter().copied().map(Some))
+    }
+}
+
+impl ColumnValues for Vec<Option<&str>> {
+    fn num_rows(&self) -> usize {
+        self.len()
+    }
+
+    fn into_any_buffer(self) -> AnyBuffer {
+        text_buffer(self.iter().copied())
+    }
+}
+
+impl ColumnValues for Vec<String> {
+    fn num_rows(&self) -> usize {
+        self.len()
+    }
+
+    fn into_any_buffer(self) -> AnyBuffer {
+        text_buffer(self.iter().map(|s| Some(s.as_str())))
+    }
+}
+
+impl ColumnValues for Vec<Option<String>> {
+    fn num_rows(&self) -> usize {
+        self.len()
+    }
+
+    fn into_any_buffer(self) -> AnyBuffer {
+        text_buffer(self.iter().map(Option::as_deref))
+    }
+}
+
+impl ColumnValues for Vec<Vec<u8>> {
+    fn num_rows(&self) -> usize {
+        self.len()
+    }
+
+    fn into_any_buffer(self) -> AnyBuffer {
+        binary_buffer(self.iter().map(|v| Some(v.as_slice())))
+    }
+}
+
+impl ColumnValues for Vec<Option<Vec<u8>>> {
+    fn num_rows(&self) -> usize {
+        self.len()
+    }
+
+    fn into_any_buffer(self) -> AnyBuffer {
+        binary_buffer(self.iter().map(Option::as_deref))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ResultSetBuilder;
+
+    #[test]
+    fn nullable_and_text_columns() {
+        let result_set = ResultSetBuilder::new()
+            .column("a", vec![Some(1i64), None])
+            .column("b", vec!["hello".to_string(), "!".to_string()])
+            .build();
+        let buffer = result_set.buffer();
+
+        assert_eq!(2, buffer.num_rows());
+        let a: Vec<_> = buffer
+            .column(0)
+            .as_nullable_slice::<i64>()
+            .unwrap()
+            .map(|v| v.copied())
+            .collect();
+        assert_eq!(vec![Some(1), None], a);
+        let b: Vec<_> = buffer.column(1).as_text_view().unwrap().iter().collect();
+        assert_eq!(vec![Some(&b"hello"[..]), Some(&b"!"[..])], b);
+    }
+
+    #[test]
+    #[should_panic(expected = "same number of rows")]
+    fn columns_of_different_length() {
+        ResultSetBuilder::new()
+            .column("a", vec![1i32])
+            .column("b", vec![1i32, 2]);
+    }
+}
@@ -0,0 +1,145 @@
+use std::{
+    cell::RefCell,
+    env::temp_dir,
+    fs,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{query_builder::quote_identifier, Connection, Environment, Error};
+
+/// Name of the SQLite ODBC driver, as it is usually registered by its installer.
+#[cfg(target_os = "windows")]
+const SQLITE_DRIVER: &str = "SQLite3 ODBC Driver";
+#[cfg(not(target_os = "windows"))]
+const SQLITE_DRIVER: &str = "SQLite3";
+
+/// Connection string for the default instance of SQL Server Express LocalDB.
+const LOCAL_DB_CONNECTION_STRING: &str = "Driver={ODBC Driver 17 for SQL Server};\
+    Server=(localdb)\\MSSQLLocalDB;Trusted_Connection=yes;";
+
+/// Used to generate unique file names for databases created within the same process.
+static NUM_DATABASES: AtomicUsize = AtomicUsize::new(0);
+
+/// A throwaway database for integration tests. Tables created with [`Self::create_table`] are
+/// dropped again then this instance goes out of scope. Database files created by [`Self::sqlite`]
+/// are deleted.
+///
+/// ```no_run
+/// use odbc_api::{test_support::TestDatabase, Environment};
+///
+/// let env = Environment::new()?;
+/// let db = TestDatabase::sqlite(&env)?;
+/// db.create_table("Birthdays", &["name VARCHAR(50)", "year INTEGER"])?;
+/// db.connection()
+///     .execute("INSERT INTO Birthdays (name, year) VALUES ('Peter', 1980)", ())?;
+/// // ... test code using `db.connection()` ...
+/// # Ok::<(), odbc_api::Error>(())
+/// ```
+pub struct TestDatabase<'env> {
+    connection: Connection<'env>,
+    /// Quoted names of the tables created with [`Self::create_table`].
+    tables: RefCell<Vec<String>>,
+    // Declared after `connection`, so the connection is closed before the file is deleted.
+    _file: Option<DeleteOnDrop>,
+}
+
+impl<'env> TestDatabase<'env> {
+    /// Creates a new, empty SQLite database in the temporary directory of the system, using the
+    /// SQLite3 ODBC driver. The database file is deleted then this instance goes out of scope.
+    pub fn sqlite(env: &'env Environment) -> Result<Self, Error> {
+        Self::sqlite_with_driver(env, SQLITE_DRIVER)
+    }
+
+    /// Like [`Self::sqlite`], but allows to specify the name the SQLite ODBC driver has been
+    /// registered with.
+    pub fn sqlite_with_driver(env: &'env Environment, driver: &str) -> Result<Self, Error> {
+        let id = NUM_DATABASES.fetch_add(1, Ordering::Relaxed);
+        let path = temp_dir().join(format!("odbc-api-test-{}-{id}.db", process::id()));
+        // There may be a leftover from a previous process which had the same id.
+        let _ = fs::remove_file(&path);
+        let file = DeleteOnDrop(path);
+        let connection_string = format!("Driver={{{driver}}};Database={};", file.path().display());
+        let connection = env.connect_with_connection_string(&connection_string)?;
+        Ok(Self {
+            connection,
+            tables: RefCell::new(Vec::new()),
+            _file: Some(file),
+        })
+    }
+
+    /// Connects to the default instance of Microsoft SQL Server Express LocalDB, using the
+    /// `ODBC Driver 17 for SQL Server`. Only tables created with [`Self::create_table`] are
+    /// cleaned up.
+    pub fn local_db(env: &'env Environment) -> Result<Self, Error> {
+        Self::from_connection_string(env, LOCAL_DB_CONNECTION_STRING)
+    }
+
+    /// Uses an existing data source for testing. Only tables created with [`Self::create_table`]
+    /// are cleaned up.
+    pub fn from_connection_string(
+        env: &'env Environment,
+        connection_string: &str,
+    ) -> Result<Self, Error> {
+        let connection = env.connect_with_connection_string(connection_string)?;
+        Ok(Self {
+            connection,
+            tables: RefCell::new(Vec::new()),
+            _file: None,
+        })
+    }
+
+    /// Connection to the test database.
+    pub fn connection(&self) -> &Connection<'env> {
+        &self.connection
+    }
+
+    /// Creates a new table. Should a table with the same name already exist, it is dropped first.
+    /// The table is dropped again then `self` goes out of scope.
+    ///
+    /// # Parameters
+    ///
+    /// * `name`: Name of the table. It is quoted using the identifier quote character of the data
+    ///   source, so it may contain characters which are not valid in unquoted identifiers.
+    /// * `columns`: Column definitions, e.g. `["a INTEGER", "b VARCHAR(10)"]`.
+    pub fn create_table(&self, name: &str, columns: &[&str]) -> Result<(), Error> {
+        let quote = self.connection.identifier_quote_char()?;
+        let name = quote_identifier(name, &quote)?;
+        self.connection
+            .execute(&format!("DROP TABLE IF EXISTS {name};"), ())?;
+        self.connection.execute(
+            &format!("CREATE TABLE {name} ({});", columns.join(", ")),
+            (),
+        )?;
+        self.tables.borrow_mut().push(name);
+        Ok(())
+    }
+}
+
+impl<'env> Drop for TestDatabase<'env> {
+    fn drop(&mut self) {
+        for table in self.tables.get_mut().drain(..).rev() {
+            // Errors are ignored, we do not want to panic in drop and the test has already
+            // concluded.
+            let _ = self
+                .connection
+                .execute(&format!("DROP TABLE IF EXISTS {table};"), ());
+        }
+    }
+}
+
+/// Deletes the file at the path then going out of scope.
+struct DeleteOnDrop(PathBuf);
+
+impl DeleteOnDrop {
+    fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for DeleteOnDrop {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
@@ -0,0 +1,231 @@
+use std::cmp::min;
+
+use crate::{
+    buffers::BufferDesc, query_builder::quote_identifier, Connection, Cursor, Error, Nullable,
+};
+
+/// Maximum lengths of the values in the columns of a result set, discovered by a probing pass
+/// before the main extraction. Drivers often report pessimistic sizes for text columns (e.g. 4000
+/// characters for an `NVARCHAR(4000)` column, which only ever holds names), so buffers allocated
+/// from the reported sizes waste a lot of memory. Use [`Self::tighten`] to shrink buffer
+/// descriptions to the lengths actually found.
+///
+/// Two ways of probing are offered:
+///
+/// * [`Connection::probe_text_lengths`] lets the data source compute the maximum length of each
+///   column with an aggregate query. This is cheap, but reports lengths in characters, which are
+///   converted into buffer sizes pessimistically (4 bytes per character for narrow text).
+/// * [`Self::scan`] fetches all rows of a result set and measures the values as received by the
+///   application. This yields exact sizes, yet transfers the data twice. Scanning only a sample
+///   is not offered, since longer values following the sample would be truncated by the tightened
+///   buffers.
+///
+/// ```no_run
+/// use odbc_api::{buffers::{BufferDesc, ColumnarAnyBuffer}, Connection, Cursor, Error};
+///
+/// fn fetch_names(conn: &Connection<'_>) -> Result<(), Error> {
+///     let query = "SELECT first_name, last_name FROM Persons";
+///     let lengths = conn.probe_text_lengths(query, &["first_name", "last_name"])?;
+///     let descs = lengths.tighten([
+///         BufferDesc::Text { max_str_len: 4000 },
+///         BufferDesc::Text { max_str_len: 4000 },
+///     ]);
+///     let buffer = ColumnarAnyBuffer::from_descs(10_000, descs);
+///     let cursor = conn.execute(query, ())?.unwrap();
+///     let mut block_cursor = cursor.bind_buffer(buffer)?;
+///     while let Some(batch) = block_cursor.fetch_with_truncation_check(true)? {
+///         // ... process batch ...
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextLengths {
+    /// Element at index `0` refers to the first column. `None` if the column only contains `NULL`,
+    /// or the result set is empty.
+    columns: Vec<Option<ColumnLength>>,
+}
+
+/// Upper bounds for the length of the values in one column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ColumnLength {
+    /// Upper bound in bytes, if fetched as UTF-8.
+    narrow: usize,
+    /// Upper bound in UTF-16 code units, if fetched as wide text.
+    wide: usize,
+}
+
+impl ColumnLength {
+    /// Upper bounds for a value of `num_chars` characters.
+    fn from_chars(num_chars: usize) -> Self {
+        // A character takes up to 4 bytes in UTF-8 and up to two code units in UTF-16.
+        Self {
+            narrow: num_chars * 4,
+            wide: num_chars * 2,
+        }
+    }
+
+    /// Upper bounds for a value of `num_bytes` bytes of UTF-8.
+    fn from_utf8_len(num_bytes: usize) -> Self {
+        // Every UTF-16 code unit is encoded with at least one byte in UTF-8.
+        Self {
+            narrow: num_bytes,
+            wide: num_bytes,
+        }
+    }
+}
+
+impl TextLengths {
+    /// Fetches all rows of `cursor` and measures the longest value of each column as narrow text.
+    pub fn scan(cursor: &mut impl Cursor) -> Result<Self, Error> {
+        let num_cols = cursor.num_result_cols()? as u16;
+        let mut columns = vec![None; num_cols as usize];
+        let mut buf = Vec::new();
+        while let Some(mut row) = cursor.next_row()? {
+            for (index, column) in columns.iter_mut().enumerate() {
+                if row.get_text(index as u16 + 1, &mut buf)? {
+                    let length = ColumnLength::from_utf8_len(buf.len());
+                    *column = Some(column.map_or(length, |known: ColumnLength| ColumnLength {
+                        narrow: known.narrow.max(length.narrow),
+                        wide: known.wide.max(length.wide),
+                    }));
+                }
+            }
+        }
+        Ok(Self { columns })
+    }
+
+    /// Maximum length of the narrow text values in the column at `buffer_index` in bytes. `None`
+    /// if the column only contains `NULL`, the result set is empty or `buffer_index` is out of
+    /// bounds.
+    pub fn max_narrow_len(&self, buffer_index: usize) -> Option<usize> {
+        self.columns
+            .get(buffer_index)
+            .copied()
+            .flatten()
+            .map(|len| len.narrow)
+    }
+
+    /// Maximum length of the wide text values in the column at `buffer_index` in UTF-16 code units.
+    /// `None` under the same conditions as [`Self::max_narrow_len`].
+    pub fn max_wide_len(&self, buffer_index: usize) -> Option<usize> {
+        self.columns
+            .get(buffer_index)
+            .copied()
+            .flatten()
+            .map(|len| len.wide)
+    }
+
+    /// Shrinks the `max_str_len` of text buffer descriptions to the probed lengths. The first
+    /// description refers to the first probed column and so on. Descriptions are never enlarged,
+    /// and other descriptions, as well as the ones of columns without any probed length, are kept
+    /// as they are.
+    pub fn tighten(&self, descs: impl IntoIterator<Item = BufferDesc>) -> Vec<BufferDesc> {
+        descs
+            .into_iter()
+            .enumerate()
+            .map(|(index, desc)| match desc {
+                BufferDesc::Text { max_str_len } => BufferDesc::Text {
+                    max_str_len: tight(max_str_len, self.max_narrow_len(index)),
+                },
+                BufferDesc::WText { max_str_len } => BufferDesc::WText {
+                    max_str_len: tight(max_str_len, self.max_wide_len(index)),
+                },
+                other => other,
+            })
+            .collect()
+    }
+}
+
+fn tight(max_str_len: usize, probed: Option<usize>) -> usize {
+    match probed {
+        // Keep room for at least one character, so empty strings can be told apart from `NULL` by
+        // drivers which do not support zero sized buffers.
+        Some(probed) => min(max_str_len, probed.max(1)),
+        None => max_str_len,
+    }
+}
+
+/// See [`Connection::probe_text_lengths`].
+pub(crate) fn probe_text_lengths(
+    connection: &Connection<'_>,
+    query: &str,
+    columns: &[&str],
+) -> Result<TextLengths, Error> {
+    let dbms_name = connection.database_management_system_name()?;
+    let quote = connection.identifier_quote_char()?;
+    let select_list = columns
+        .iter()
+        .map(|column| {
+            let column = quote_identifier(column, &quote)?;
+            Ok(format!("MAX({})", char_length(&dbms_name, &column)))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    let sql = format!("SELECT {} FROM ({query}) probed", select_list.join(", "));
+    let mut cursor = connection
+        .execute(&sql, ())?
+        .ok_or_else(|| Error::NoResultSet {
+            statement: sql.clone(),
+        })?;
+    let mut lengths = vec![None; columns.len()];
+    if let Some(mut row) = cursor.next_row()? {
+        for (index, length) in lengths.iter_mut().enumerate() {
+            let mut value = Nullable::<i64>::null();
+            row.get_data(index as u16 + 1, &mut value)?;
+            *length = value
+                .into_opt()
+                .map(|num_chars| ColumnLength::from_chars(num_chars.max(0) as usize));
+        }
+    }
+    Ok(TextLengths { columns: lengths })
+}
+
+/// Expression computing the length of `column` in characters for the database management system
+/// named `dbms_name`.
+fn char_length(dbms_name: &str, column: &str) -> String {
+    match dbms_name {
+        // `LEN` ignores trailing spaces and `DATALENGTH` counts bytes in the encoding of the column.
+        // Counting UTF-16 code units yields an upper bound for the number of characters.
+        "Microsoft SQL Server" => format!("DATALENGTH(CAST({column} AS NVARCHAR(MAX))) / 2"),
+        "SQLite" => format!("LENGTH({column})"),
+        _ => format!("CHAR_LENGTH({column})"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffers::BufferDesc;
+
+    use super::{ColumnLength, TextLengths};
+
+    #[test]
+    fn tighten_text_buffers_to_probed_lengths() {
+        let lengths = TextLengths {
+            columns: vec![
+                Some(ColumnLength::from_chars(10)),
+                Some(ColumnLength::from_utf8_len(0)),
+                None,
+                Some(ColumnLength::from_chars(10)),
+            ],
+        };
+
+        let descs = lengths.tighten([
+            BufferDesc::Text { max_str_len: 4000 },
+            BufferDesc::Text { max_str_len: 4000 },
+            BufferDesc::Text { max_str_len: 4000 },
+            BufferDesc::WText { max_str_len: 4000 },
+            BufferDesc::I32 { nullable: true },
+        ]);
+
+        assert_eq!(
+            vec![
+                BufferDesc::Text { max_str_len: 40 },
+                BufferDesc::Text { max_str_len: 1 },
+                BufferDesc::Text { max_str_len: 4000 },
+                BufferDesc::WText { max_str_len: 20 },
+                BufferDesc::I32 { nullable: true },
+            ],
+            descs
+        );
+    }
+}
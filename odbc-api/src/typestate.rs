@@ -0,0 +1,206 @@
+//! A statement API tracking the state of the statement handle in its type. Calls which are not
+//! valid in the current state of the statement (e.g. fetching from a statement which has not
+//! produced a result set) fail to compile, rather than returning an error with SQLSTATE `HY010`
+//! (function sequence error) at runtime.
+//!
+//! Every transition consumes the statement and returns it in its new state:
+//!
+//! * [`Statement::prepare`] yields a [`Statement<Prepared>`].
+//! * [`Statement::execute`] turns a [`Prepared`] statement into either an [`Executed`] one, or one
+//!   with an open cursor ([`CursorOpen`]).
+//! * [`Statement::into_prepared`] and [`Statement::close`] return to [`Prepared`], so the statement
+//!   can be executed again.
+//!
+//! This lives alongside the API offered by [`crate::Connection`] and [`crate::Prepared`], which is
+//! more flexible, but checks less at compile time.
+//!
+//! ```no_run
+//! use odbc_api::{
+//!     typestate::{Execution, Statement},
+//!     Connection, Cursor, Error, IntoParameter,
+//! };
+//!
+//! fn print_titles(conn: &Connection<'_>, years: &[i32]) -> Result<(), Error> {
+//!     let mut statement = Statement::prepare(conn, "SELECT title FROM Movies WHERE year = ?")?;
+//!     for &year in years {
+//!         statement = match statement.execute(&year.into_parameter())? {
+//!             Execution::CursorOpen(mut cursor) => {
+//!                 while let Some(mut row) = cursor.next_row()? {
+//!                     let mut title = Vec::new();
+//!                     row.get_text(1, &mut title)?;
+//!                     println!("{}", String::from_utf8_lossy(&title));
+//!                 }
+//!                 cursor.close()?
+//!             }
+//!             Execution::Executed(executed) => executed.into_prepared(),
+//!         };
+//!     }
+//!     Ok(())
+//! }
+//! ```
+//!
+//! Fetching requires an open cursor:
+//!
+//! ```compile_fail
+//! use odbc_api::{typestate::Statement, Connection, Cursor, Error};
+//!
+//! fn fetch_without_executing(conn: &Connection<'_>) -> Result<(), Error> {
+//!     let mut statement = Statement::prepare(conn, "SELECT title FROM Movies")?;
+//!     statement.next_row()?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::marker::PhantomData;
+
+use crate::{
+    cursor::bind_row_set_buffer_to_statement,
+    execute::execute_with_parameters,
+    handles::{AsStatementRef, ParameterDescription, Statement as _, StatementImpl, StatementRef},
+    result_set_metadata::reported,
+    BlockCursor, Connection, Cursor, CursorImpl, Error, ParameterCollectionRef, ResultSetMetadata,
+    RowSetBuffer,
+};
+
+/// State of a statement which has been prepared, but has no open cursor.
+#[derive(Debug, Clone, Copy)]
+pub struct Prepared;
+
+/// State of a statement which has been executed without producing a result set.
+#[derive(Debug, Clone, Copy)]
+pub struct Executed;
+
+/// State of a statement which has been executed and produced a result set, which has not been
+/// closed yet.
+#[derive(Debug, Clone, Copy)]
+pub struct CursorOpen;
+
+/// A statement handle allocated on a connection, whose state is tracked by `S`. Which of
+/// [`Prepared`], [`Executed`] or [`CursorOpen`] `S` is determines which methods are available.
+pub struct Statement<'c, S> {
+    statement: StatementImpl<'c>,
+    state: PhantomData<S>,
+}
+
+impl<'c, S> Statement<'c, S> {
+    fn new(statement: StatementImpl<'c>) -> Self {
+        Self {
+            statement,
+            state: PhantomData,
+        }
+    }
+
+    /// Transfer ownership to the underlying statement handle. Leaves the safety rails of the
+    /// typestate API, see [`crate::Prepared::into_statement`].
+    pub fn into_statement(self) -> StatementImpl<'c> {
+        self.statement
+    }
+}
+
+impl<'c> Statement<'c, Prepared> {
+    /// Allocates a statement on `connection` and prepares `query` for execution.
+    pub fn prepare(connection: &'c Connection<'_>, query: &str) -> Result<Self, Error> {
+        connection.prepare(query).map(Self::from)
+    }
+
+    /// Binds `params` and executes the statement. Whether the statement produced a result set is
+    /// only known at runtime, so the statement is returned in either state wrapped in an
+    /// [`Execution`]. Should `params` specify a parameter set size of `0`, nothing is executed and
+    /// the statement is returned as [`Executed`].
+    pub fn execute(mut self, params: impl ParameterCollectionRef) -> Result<Execution<'c>, Error> {
+        let has_cursor = execute_with_parameters(|| Ok(&mut self.statement), None, params)?
+            // Keep the cursor open. It is closed by `Statement<CursorOpen>` instead.
+            .map(CursorImpl::into_statement)
+            .is_some();
+        let execution = if has_cursor {
+            Execution::CursorOpen(Statement::new(self.statement))
+        } else {
+            Execution::Executed(Statement::new(self.statement))
+        };
+        Ok(execution)
+    }
+
+    /// Number of placeholders which must be provided with [`Self::execute`].
+    pub fn num_params(&mut self) -> Result<u16, Error> {
+        let stmt = self.statement.as_stmt_ref();
+        stmt.num_params().into_result(&stmt)
+    }
+
+    /// Data type, size, decimal digits and nullability of a parameter. `parameter_number` starts
+    /// at 1.
+    pub fn describe_param(&mut self, parameter_number: u16) -> Result<ParameterDescription, Error> {
+        let stmt = self.statement.as_stmt_ref();
+        stmt.describe_param(parameter_number).into_result(&stmt)
+    }
+}
+
+impl<'c> From<crate::Prepared<StatementImpl<'c>>> for Statement<'c, Prepared> {
+    fn from(prepared: crate::Prepared<StatementImpl<'c>>) -> Self {
+        Self::new(prepared.into_statement())
+    }
+}
+
+impl<'c> Statement<'c, Executed> {
+    /// Number of rows affected by the execution. `None` if the driver does not provide this
+    /// information.
+    pub fn row_count(&mut self) -> Result<Option<usize>, Error> {
+        let stmt = self.statement.as_stmt_ref();
+        let count = stmt.row_count().into_result(&stmt)?;
+        // ODBC returns -1 in case a row count is not available
+        if count == -1 {
+            Ok(None)
+        } else {
+            reported(count, "row count").map(Some)
+        }
+    }
+
+    /// The statement can be executed again.
+    pub fn into_prepared(self) -> Statement<'c, Prepared> {
+        Statement::new(self.statement)
+    }
+}
+
+impl<'c> Statement<'c, CursorOpen> {
+    /// Closes the cursor, discarding any remaining rows of the result set, so the statement can be
+    /// executed again.
+    pub fn close(mut self) -> Result<Statement<'c, Prepared>, Error> {
+        let mut stmt = self.statement.as_stmt_ref();
+        stmt.close_cursor().into_result(&stmt)?;
+        Ok(Statement::new(self.statement))
+    }
+}
+
+impl<'c, S> AsStatementRef for Statement<'c, S> {
+    fn as_stmt_ref(&mut self) -> StatementRef<'_> {
+        self.statement.as_stmt_ref()
+    }
+}
+
+/// Prepared statements can describe the result set they are going to produce.
+impl<'c> ResultSetMetadata for Statement<'c, Prepared> {}
+
+impl<'c> ResultSetMetadata for Statement<'c, CursorOpen> {}
+
+/// Dropping a `Statement<CursorOpen>` frees the statement handle, which implicitly closes the
+/// cursor. Use [`Statement::close`] to reuse the statement. [`BlockCursor::unbind`] gives the
+/// statement back.
+impl<'c> Cursor for Statement<'c, CursorOpen> {
+    fn bind_buffer<B>(mut self, mut row_set_buffer: B) -> Result<BlockCursor<Self, B>, Error>
+    where
+        B: RowSetBuffer,
+    {
+        let stmt = self.statement.as_stmt_ref();
+        unsafe {
+            bind_row_set_buffer_to_statement(stmt, &mut row_set_buffer)?;
+        }
+        Ok(BlockCursor::new(row_set_buffer, self))
+    }
+}
+
+/// Outcome of [`Statement::execute`].
+pub enum Execution<'c> {
+    /// The statement did not produce a result set.
+    Executed(Statement<'c, Executed>),
+    /// The statement produced a result set.
+    CursorOpen(Statement<'c, CursorOpen>),
+}
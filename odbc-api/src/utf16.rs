@@ -0,0 +1,103 @@
+/// `true` for the first code unit of a UTF-16 surrogate pair.
+fn is_high_surrogate(unit: u16) -> bool {
+    (0xD800..=0xDBFF).contains(&unit)
+}
+
+/// Longest prefix of `text` which does not end in the middle of a surrogate pair. Wide text
+/// truncated by the ODBC driver (e.g. due to a too small buffer) may end with the first half of a
+/// surrogate pair. Decoding it would yield a replacement character or an error.
+///
+/// ```
+/// use odbc_api::trim_split_surrogate;
+///
+/// // "🦀" is encoded as the surrogate pair 0xD83E 0xDD80.
+/// let truncated = [0x61, 0xD83E];
+/// assert_eq!(&[0x61], trim_split_surrogate(&truncated));
+/// ```
+pub fn trim_split_surrogate(text: &[u16]) -> &[u16] {
+    match text.last() {
+        Some(&last) if is_high_surrogate(last) => &text[..text.len() - 1],
+        _ => text,
+    }
+}
+
+/// Joins surrogate pairs split between consecutive chunks of UTF-16 text. Use this if processing
+/// large wide text values in chunks, as they are returned by repeated calls to
+/// [`crate::CursorRow::get_data`]. Each call to `get_data` resumes where the previous one stopped,
+/// yet a chunk may end with the first half of a surrogate pair. The joiner holds back that half and
+/// prepends it to the next chunk, so every chunk can be decoded on its own.
+///
+/// ```
+/// use odbc_api::{parameter::VarWCharArray, CursorRow, Error, Utf16ChunkJoiner};
+///
+/// fn print_large_text(row: &mut CursorRow<'_>, col_index: u16) -> Result<(), Error> {
+///     let mut buf = VarWCharArray::<1024>::NULL;
+///     let mut joiner = Utf16ChunkJoiner::new();
+///     loop {
+///         row.get_data(col_index, &mut buf)?;
+///         let bytes = if let Some(bytes) = buf.as_bytes() { bytes } else { break };
+///         let units: Vec<u16> = bytes
+///             .chunks_exact(2)
+///             .map(|pair| u16::from_ne_bytes([pair[0], pair[1]]))
+///             .collect();
+///         print!("{}", String::from_utf16_lossy(joiner.push(&units)));
+///         if buf.is_complete() {
+///             break;
+///         }
+///     }
+///     println!("{}", String::from_utf16_lossy(joiner.finish()));
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct Utf16ChunkJoiner {
+    /// Complete chunk returned by the last call to `push`.
+    chunk: Vec<u16>,
+    /// First half of a surrogate pair held back from the previous chunk.
+    pending: Option<u16>,
+}
+
+impl Utf16ChunkJoiner {
+    /// Creates a joiner, which has not seen any chunk yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Continues the text with the next chunk. Returns the chunk prefixed with any code unit held
+    /// back from the previous chunk, and without a trailing first half of a surrogate pair.
+    pub fn push(&mut self, chunk: &[u16]) -> &[u16] {
+        self.chunk.clear();
+        self.chunk.extend(self.pending.take());
+        self.chunk.extend_from_slice(chunk);
+        let complete_len = trim_split_surrogate(&self.chunk).len();
+        if complete_len != self.chunk.len() {
+            self.pending = self.chunk.pop();
+        }
+        &self.chunk
+    }
+
+    /// Call this after the last chunk. Returns the code unit still held back, if any. Since it is
+    /// not followed by the second half of a surrogate pair, the text is not valid UTF-16.
+    pub fn finish(&mut self) -> &[u16] {
+        self.chunk.clear();
+        self.chunk.extend(self.pending.take());
+        &self.chunk
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Utf16ChunkJoiner;
+
+    #[test]
+    fn join_surrogate_pair_split_between_chunks() {
+        let text: Vec<u16> = "a🦀b".encode_utf16().collect();
+        let mut joiner = Utf16ChunkJoiner::new();
+
+        let mut decoded = String::from_utf16(joiner.push(&text[..2])).unwrap();
+        decoded += &String::from_utf16(joiner.push(&text[2..])).unwrap();
+
+        assert_eq!("a🦀b", decoded);
+        assert!(joiner.finish().is_empty());
+    }
+}
@@ -0,0 +1,77 @@
+//! Identifiers and values of driver specific attributes for some widely used ODBC drivers. Use
+//! them together with [`crate::handles::Statement::set_vendor_attr_int`] and its siblings.
+
+/// Driver specific attributes of the Microsoft ODBC Driver for SQL Server. Taken from `msodbcsql.h`.
+pub mod mssql {
+    use crate::handles::{SqlResult, Statement};
+
+    /// `SQL_SOPT_SS_CURSOR_OPTIONS`. Specifies whether the driver uses driver specific performance
+    /// options on cursors. Takes a [`CursorOptions`] value.
+    pub const SOPT_SS_CURSOR_OPTIONS: i32 = 1230;
+    /// `SQL_SOPT_SS_NOCOUNT_STATUS`. Indicates the current setting of the `NOCOUNT` option.
+    pub const SOPT_SS_NOCOUNT_STATUS: i32 = 1231;
+    /// `SQL_SOPT_SS_DEFER_PREPARE`. Determines whether a statement is prepared immediately or
+    /// deferred until it is executed. Takes `0` (off) or `1` (on, the default).
+    pub const SOPT_SS_DEFER_PREPARE: i32 = 1232;
+    /// `SQL_SOPT_SS_QUERYNOTIFICATION_TIMEOUT`. Timeout of a query notification in seconds.
+    pub const SOPT_SS_QUERYNOTIFICATION_TIMEOUT: i32 = 1233;
+    /// `SQL_SOPT_SS_QUERYNOTIFICATION_MSGTEXT`. Message text of a query notification.
+    pub const SOPT_SS_QUERYNOTIFICATION_MSGTEXT: i32 = 1234;
+    /// `SQL_SOPT_SS_QUERYNOTIFICATION_OPTIONS`. Service options of a query notification.
+    pub const SOPT_SS_QUERYNOTIFICATION_OPTIONS: i32 = 1235;
+    /// `SQL_SOPT_SS_PARAM_FOCUS`. Number of the table-valued parameter subsequent calls to
+    /// `SQLBindParameter` bind columns of. `0` (the default) binds parameters of the statement.
+    pub const SOPT_SS_PARAM_FOCUS: i32 = 1236;
+
+    /// `SQL_SS_TABLE`. SQL data type of table-valued parameters.
+    pub const SS_TABLE: i16 = -153;
+
+    /// Values for [`SOPT_SS_CURSOR_OPTIONS`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[repr(i32)]
+    pub enum CursorOptions {
+        /// `SQL_CO_OFF`. Disables fast forward-only, read-only cursors and autofetch. This is the
+        /// default.
+        Off = 0,
+        /// `SQL_CO_FFO`. Enables fast forward-only, read-only cursors, without autofetch.
+        FastForwardOnly = 1,
+        /// `SQL_CO_AF`. Enables autofetch on any cursor type.
+        AutoFetch = 2,
+        /// `SQL_CO_FFO_AF`. Enables fast forward-only, read-only cursors with autofetch.
+        FastForwardOnlyAutoFetch = 3,
+        /// `SQL_CO_FIREHOSE_AF`. Enables autofetch for firehose (default result set) cursors.
+        FirehoseAutoFetch = 4,
+    }
+
+    /// Sets [`SOPT_SS_CURSOR_OPTIONS`] on a statement. Only call this for statements allocated on
+    /// a connection to Microsoft SQL Server.
+    pub fn set_cursor_options(stmt: &mut impl Statement, options: CursorOptions) -> SqlResult<()> {
+        // Safe: The attribute takes an integer and does not affect bound buffers.
+        unsafe { stmt.set_vendor_attr_int(SOPT_SS_CURSOR_OPTIONS, options as i32) }
+    }
+}
+
+/// Helpers for the Snowflake ODBC driver.
+///
+/// Snowflake does not offer a statement attribute for tagging queries. Instead the `QUERY_TAG`
+/// session parameter is used, which applies to all subsequent statements executed on the
+/// connection.
+pub mod snowflake {
+    /// Statement text setting the `QUERY_TAG` session parameter to `tag`. Single quotes within the
+    /// tag are escaped.
+    ///
+    /// ```
+    /// use odbc_api::vendor::snowflake::query_tag_statement;
+    ///
+    /// assert_eq!(
+    ///     "ALTER SESSION SET QUERY_TAG = 'nightly ''import'''",
+    ///     query_tag_statement("nightly 'import'")
+    /// );
+    /// ```
+    pub fn query_tag_statement(tag: &str) -> String {
+        format!(
+            "ALTER SESSION SET QUERY_TAG = '{}'",
+            tag.replace('\'', "''")
+        )
+    }
+}
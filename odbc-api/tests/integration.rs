@@ -1,6 +1,6 @@
 mod common;
 
-use odbc_sys::{SqlDataType, Timestamp};
+use odbc_sys::{AttrOdbcVersion, SqlDataType, Timestamp};
 use stdext::function_name;
 use sys::NULL_DATA;
 use tempfile::NamedTempFile;
@@ -10,20 +10,37 @@ use common::{cursor_to_string, Profile, SingleColumnRowSetBuffer, ENV};
 
 use odbc_api::{
     buffers::{
-        BufferDesc, ColumnarAnyBuffer, ColumnarBuffer, Indicator, Item, TextColumn, TextRowSet,
+        AnyBuffer, BatchExtractor, BufferBuilder, BufferDesc, CellAccessError, CellErrorCause,
+        CellValue, ColumnarAnyBuffer, ColumnarBuffer, DictionaryTextColumn, Indicator, Item,
+        ManualRowSet, TextColumn, TextRowSet,
     },
-    handles::{OutputStringBuffer, ParameterDescription, Statement},
+    canonical_decimal,
+    delimited::DelimitedOptions,
+    diagnostics,
+    handles::{AsStatementRef, OutputStringBuffer, ParameterDescription, Statement},
     parameter::InputParameter,
     parameter::{
-        Blob, BlobRead, BlobSlice, VarBinaryArray, VarCharArray, VarCharSlice, WithDataType,
+        blob_channel, Binary, Blob, BlobRead, BlobSlice, ChunkedBlob, Text, VarBinaryArray,
+        VarCharArray, VarCharSlice, WideText, WithDataType,
     },
-    sys, Bit, ColumnDescription, Connection, Cursor, DataType, Error, InOut, IntoParameter,
-    Nullability, Nullable, Out, ResultSetMetadata, U16Str, U16String,
+    render::{RenderOptions, TableFormat},
+    sql, sys, typestate, vendor, AdaptiveBatchSize, AdaptiveBlockCursor, Bit, BulkLoadStrategy,
+    CancellationRegistry, ChangeCapture, ChangeKind, ClientIdentity, ColumnDescription,
+    ColumnGroups, Connection, ConnectionEvent, ConnectionObserver, ConnectionTarget,
+    ConnectionWorker, Cursor, CursorType, DataType, DeduplicatingCursor, Environment, Error,
+    FailoverOptions, FetchPlan, FetchPlanViolation, GroupedRows, InMemoryWatermarkStore, InOut,
+    IncrementalExtraction, IntoParameter, KeepAlive, NoTotalPolicy, Nullability, Nullable, Out,
+    PeekableCursor, Quirks, RePreparePolicy, ReplicaSelection, ReplicaSet, ResultSetMetadata,
+    ResultSetSchema, RowMapper, Select, SerializedConnection, SpooledBatches, SpoolingCursor,
+    StatementOptions, TableValuedParam, TargetType, TextLengths, TruncationPolicy, U16Str,
+    U16String, WatermarkStore,
 };
 use std::{
     ffi::CString,
     io::{self, Write},
-    iter, str, thread,
+    iter, str,
+    sync::{Arc, Mutex},
+    thread,
     time::Duration,
 };
 
@@ -120,6 +137,29 @@ fn connect_to_db(profile: &Profile) {
     assert!(!conn.is_dead().unwrap())
 }
 
+/// Save the connection settings as a file DSN and connect using it.
+#[test_case(SQLITE_3; "SQLite 3")]
+fn save_and_connect_with_file_dsn(profile: &Profile) {
+    // Given
+    let path = std::env::temp_dir().join(format!("{}.dsn", table_name!()));
+    let _ = std::fs::remove_file(&path);
+
+    // When
+    let (conn, saved) = ENV
+        .connect_and_save_file_dsn(profile.connection_string, &path)
+        .unwrap();
+    drop(conn);
+
+    // Then
+    // Not every driver manager supports `SAVEFILE`.
+    if let Some(saved) = saved {
+        assert_eq!(path, saved);
+        let conn = ENV.connect_with_file_dsn(&saved, "").unwrap();
+        assert!(!conn.is_dead().unwrap());
+        std::fs::remove_file(&saved).unwrap();
+    }
+}
+
 #[test_case(MSSQL; "Microsoft SQL Server")]
 fn describe_columns(profile: &Profile) {
     let table_name = table_name!();
@@ -209,167 +249,2862 @@ fn describe_columns(profile: &Profile) {
     assert_eq!(expected, actual);
     assert_eq!(kind, cursor.col_data_type(9).unwrap());
 
-    let kind = DataType::LongVarbinary { length: 2147483647 };
-    let expected = ColumnDescription::new("j", kind, Nullability::Nullable);
-    cursor.describe_col(10, &mut actual).unwrap();
-    assert_eq!(expected, actual);
-    assert_eq!(kind, cursor.col_data_type(10).unwrap());
+    let kind = DataType::LongVarbinary { length: 2147483647 };
+    let expected = ColumnDescription::new("j", kind, Nullability::Nullable);
+    cursor.describe_col(10, &mut actual).unwrap();
+    assert_eq!(expected, actual);
+    assert_eq!(kind, cursor.col_data_type(10).unwrap());
+
+    let kind = DataType::Float { precision: 53 };
+    let expected = ColumnDescription::new("k", kind, Nullability::Nullable);
+    cursor.describe_col(11, &mut actual).unwrap();
+    assert_eq!(expected, actual);
+    assert_eq!(kind, cursor.col_data_type(11).unwrap());
+}
+
+/// Fetch text from data source using the TextBuffer type
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn bulk_fetch_text(profile: &Profile) {
+    let table_name = table_name!();
+    let (conn, table) = profile
+        .given(&table_name, &["VARCHAR(255)", "INT"])
+        .unwrap();
+
+    // Insert data
+    let insert = format!("INSERT INTO {table_name} (a,b) VALUES (?,?), (?,?),(?,?)");
+    conn.execute(
+        &insert,
+        (
+            &"Interstellar".into_parameter(),
+            &None::<i32>.into_parameter(),
+            &"2001: A Space Odyssey".into_parameter(),
+            &1968,
+            &"Jurassic Park".into_parameter(),
+            &1993,
+        ),
+    )
+    .unwrap();
+
+    let query = table.sql_all_ordered_by_id();
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    // Cursor to string helper utilizes the text buffer
+    let actual = cursor_to_string(cursor);
+    let expected = "Interstellar,NULL\n2001: A Space Odyssey,1968\nJurassic Park,1993";
+    assert_eq!(expected, actual);
+}
+
+/// Into cursor should enable users to open a connection within a function and return a cursor.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn into_cursor(profile: &Profile) {
+    let table_name = table_name!();
+    let (conn, table) = profile
+        .given(&table_name, &["VARCHAR(255)", "INT"])
+        .unwrap();
+
+    // Insert data
+    let insert = format!("INSERT INTO {table_name} (a,b) VALUES (?,?), (?,?),(?,?)");
+    conn.execute(
+        &insert,
+        (
+            &"Interstellar".into_parameter(),
+            &None::<i32>.into_parameter(),
+            &"2001: A Space Odyssey".into_parameter(),
+            &1968,
+            &"Jurassic Park".into_parameter(),
+            &1993,
+        ),
+    )
+    .unwrap();
+
+    let make_cursor = || {
+        let conn = profile.connection().unwrap();
+        let query = table.sql_all_ordered_by_id();
+        conn.into_cursor(&query, ()).unwrap().unwrap()
+    };
+    let cursor = make_cursor();
+
+    // Cursor to string helper utilizes the text buffer
+    let actual = cursor_to_string(cursor);
+    let expected = "Interstellar,NULL\n2001: A Space Odyssey,1968\nJurassic Park,1993";
+    assert_eq!(expected, actual);
+}
+
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn column_name(profile: &Profile) {
+    let table_name = table_name!();
+    let conn = profile
+        .setup_empty_table(&table_name, &["VARCHAR(255)", "INT"])
+        .unwrap();
+
+    let sql = format!("SELECT a, b FROM {table_name};");
+    let mut cursor = conn.execute(&sql, ()).unwrap().unwrap();
+
+    let name = cursor.col_name(1).unwrap();
+    assert_eq!("a", name);
+
+    let name = cursor.col_name(2).unwrap();
+    assert_eq!("b", name);
+
+    // Test the same using column descriptions
+    let mut desc = ColumnDescription::default();
+
+    cursor.describe_col(1, &mut desc).unwrap();
+    assert_eq!("a", desc.name_to_string().unwrap());
+
+    cursor.describe_col(2, &mut desc).unwrap();
+    assert_eq!("b", desc.name_to_string().unwrap());
+}
+
+/// Bind a CHAR column to a character buffer.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn bind_char(profile: &Profile) {
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["CHAR(5)"]).unwrap();
+    let insert_sql = table.sql_insert();
+    conn.execute(&insert_sql, &"Hello".into_parameter())
+        .unwrap();
+
+    let cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+    let mut buf = ColumnarBuffer::new(vec![(1, TextColumn::new(1, 5))]);
+    let mut row_set_cursor = cursor.bind_buffer(&mut buf).unwrap();
+    let batch = row_set_cursor.fetch().unwrap().unwrap();
+
+    assert_eq!(Some(&b"Hello"[..]), batch.column(0).get(0));
+}
+
+/// Bind a CHAR column to a wchar buffer
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn bind_char_to_wchar(profile: &Profile) {
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["CHAR(5)"]).unwrap();
+    let insert_sql = table.sql_insert();
+    conn.execute(&insert_sql, &"Hello".into_parameter())
+        .unwrap();
+    let sql = table.sql_all_ordered_by_id();
+
+    let cursor = conn.execute(&sql, ()).unwrap().unwrap();
+    let mut buf = ColumnarBuffer::new(vec![(1, TextColumn::<u16>::new(1, 5))]);
+    let mut row_set_cursor = cursor.bind_buffer(&mut buf).unwrap();
+    row_set_cursor.fetch().unwrap();
+    drop(row_set_cursor);
+
+    assert_eq!(
+        Some(U16String::from_str("Hello").as_ustr()),
+        buf.column(0).get(0).map(U16Str::from_slice)
+    );
+}
+
+/// Strip the padding of CHAR columns after fetch, while leaving VARCHAR columns untouched.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn trim_char_padding(profile: &Profile) {
+    let table_name = table_name!();
+    let (conn, table) = profile
+        .given(&table_name, &["CHAR(5)", "VARCHAR(5)"])
+        .unwrap();
+    conn.execute(
+        &table.sql_insert(),
+        (&"Hi".into_parameter(), &"Hi  ".into_parameter()),
+    )
+    .unwrap();
+
+    let mut cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+    let buffer = TextRowSet::for_cursor(1, &mut cursor, Some(20))
+        .unwrap()
+        .trim_char_padding(&mut cursor)
+        .unwrap();
+    let mut row_set_cursor = cursor.bind_buffer(buffer).unwrap();
+    let batch = row_set_cursor.fetch().unwrap().unwrap();
+
+    assert_eq!(Some("Hi"), batch.at_as_str(0, 0).unwrap());
+    assert_eq!(Some("Hi  "), batch.at_as_str(1, 0).unwrap());
+}
+
+/// Fetching decimals as text yields the same canonical representation for every driver.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn canonical_decimal_text(profile: &Profile) {
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["DECIMAL(5,2)"]).unwrap();
+    conn.execute(
+        &format!("INSERT INTO {table_name} (a) VALUES (1.50), (-12.05), (0)"),
+        (),
+    )
+    .unwrap();
+
+    let cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+    let actual = cursor_to_string(cursor)
+        .lines()
+        .map(|line| canonical_decimal(line).unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(["1.5", "-12.05", "0"], actual.as_slice());
+}
+
+/// Values which are not valid UTF-8 are set to NULL and reported, rather than failing the fetch.
+#[test_case(SQLITE_3; "SQLite 3")]
+fn quarantine_invalid_utf8(profile: &Profile) {
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["VARCHAR(10)"]).unwrap();
+    conn.execute(
+        &format!("INSERT INTO {table_name} (a) VALUES ('ok'), (CAST(X'FFFE' AS TEXT))"),
+        (),
+    )
+    .unwrap();
+
+    let mut cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+    let buffer = TextRowSet::for_cursor(10, &mut cursor, Some(20))
+        .unwrap()
+        .with_quarantine();
+    let mut row_set_cursor = cursor.bind_buffer(buffer).unwrap();
+    let batch = row_set_cursor.fetch().unwrap().unwrap();
+
+    assert_eq!(Some("ok"), batch.at_as_str(0, 0).unwrap());
+    assert_eq!(None, batch.at(0, 1));
+    assert_eq!(1, batch.cell_errors().len());
+    let error = &batch.cell_errors()[0];
+    assert_eq!((1, 0), (error.row, error.col));
+    assert!(matches!(error.cause, CellErrorCause::InvalidUtf8(_)));
+}
+
+/// Limit the number of rows returned by a query using `SQL_ATTR_MAX_ROWS`.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn execute_with_max_rows(profile: &Profile) {
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["INTEGER"]).unwrap();
+    conn.execute(
+        &format!("INSERT INTO {table_name} (a) VALUES (1), (2), (3)"),
+        (),
+    )
+    .unwrap();
+
+    let cursor = conn
+        .execute_with_max_rows(&table.sql_all_ordered_by_id(), (), 2)
+        .unwrap()
+        .unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!("1\n2", actual);
+}
+
+/// Batch size grows with each fetch, while all rows are still fetched exactly once.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn adaptive_block_cursor(profile: &Profile) {
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["INTEGER"]).unwrap();
+    conn.execute(
+        &format!("INSERT INTO {table_name} (a) VALUES (1), (2), (3), (4), (5), (6), (7), (8)"),
+        (),
+    )
+    .unwrap();
+
+    let cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+    let desc = BufferDesc::I32 { nullable: false };
+    let mut cursor = AdaptiveBlockCursor::new(cursor, AdaptiveBatchSize::new(1, 4), |capacity| {
+        ColumnarAnyBuffer::try_from_descs(capacity, [desc])
+    })
+    .unwrap();
+    let mut batches = Vec::new();
+    while let Some(batch) = cursor.fetch().unwrap() {
+        batches.push(batch.column(0).as_slice::<i32>().unwrap().to_vec());
+    }
+
+    assert_eq!(
+        vec![vec![1], vec![2, 3], vec![4, 5, 6, 7], vec![8]],
+        batches
+    );
+}
+
+/// Block cursors count rows, batches and bytes fetched.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn block_cursor_statistics(profile: &Profile) {
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["INTEGER"]).unwrap();
+    conn.execute(
+        &format!("INSERT INTO {table_name} (a) VALUES (1), (2), (3)"),
+        (),
+    )
+    .unwrap();
+
+    let cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+    let buffer = ColumnarAnyBuffer::from_descs(2, [BufferDesc::I32 { nullable: false }]);
+    let mut block_cursor = cursor.bind_buffer(buffer).unwrap();
+    while block_cursor.fetch().unwrap().is_some() {}
+    let statistics = block_cursor.statistics();
+
+    assert_eq!(3, statistics.rows_fetched);
+    assert_eq!(2, statistics.batches_fetched);
+    assert_eq!(12, statistics.bytes_fetched);
+    assert!(statistics.time_in_odbc <= statistics.elapsed);
+}
+
+/// Capture the schema of a result set and detect a changed column.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn result_set_schema_diff(profile: &Profile) {
+    let table_name = table_name!();
+    let conn = profile
+        .setup_empty_table(&table_name, &["INTEGER", "VARCHAR(10)"])
+        .unwrap();
+    let mut cursor = conn
+        .execute(&format!("SELECT a, b FROM {table_name}"), ())
+        .unwrap()
+        .unwrap();
+    let before = ResultSetSchema::for_cursor(&mut cursor).unwrap();
+    drop(cursor);
+
+    let mut cursor = conn
+        .execute(&format!("SELECT b, a FROM {table_name}"), ())
+        .unwrap()
+        .unwrap();
+    let after = ResultSetSchema::for_cursor(&mut cursor).unwrap();
+
+    assert_eq!(2, before.columns.len());
+    assert_eq!("a", before.columns[0].name);
+    assert!(before.diff(&before).is_empty());
+    assert_eq!(
+        vec![
+            "Column 'a' moved from position 1 to 2.".to_owned(),
+            "Column 'b' moved from position 2 to 1.".to_owned()
+        ],
+        before
+            .diff(&after)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+    );
+}
+
+/// Insert rows in batches from an iterator of tuples.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn execute_many(profile: &Profile) {
+    let table_name = table_name!();
+    let (conn, table) = profile
+        .given(&table_name, &["INTEGER", "VARCHAR(10)"])
+        .unwrap();
+    // Longer strings in the second batch require binding larger buffers.
+    let rows = [
+        (1, Some("a")),
+        (2, None),
+        (3, Some("abcdefghij")),
+        (4, Some("abc")),
+        (5, Some("ab")),
+    ];
+
+    let mut prepared = conn.prepare(&table.sql_insert()).unwrap();
+    let report = prepared.execute_many(2, rows).unwrap();
+
+    assert!(report.batch_errors.is_empty());
+    let cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+    let actual = cursor_to_string(cursor);
+    assert_eq!("1,a\n2,NULL\n3,abcdefghij\n4,abc\n5,ab", actual);
+}
+
+/// Access the cells of a fetched row set as typed values, without matching the column variants.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn get_typed_cell_values(profile: &Profile) {
+    let table_name = table_name!();
+    let (conn, table) = profile
+        .given(&table_name, &["INTEGER", "VARCHAR(10)"])
+        .unwrap();
+    conn.execute(
+        &format!("INSERT INTO {table_name} (a, b) VALUES (42, 'Hello'), (NULL, NULL)"),
+        (),
+    )
+    .unwrap();
+
+    let cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+    let buffer = ColumnarAnyBuffer::from_descs(
+        10,
+        [
+            BufferDesc::I32 { nullable: true },
+            BufferDesc::Text { max_str_len: 10 },
+        ],
+    );
+    let mut cursor = cursor.bind_buffer(buffer).unwrap();
+    let batch = cursor.fetch().unwrap().unwrap();
+
+    assert_eq!(Ok(42i64), batch.get(0, 0));
+    assert_eq!(Ok("Hello"), batch.get(0, 1));
+    assert_eq!(Ok(None::<i32>), batch.get(1, 0));
+    assert_eq!(Ok(None::<String>), batch.get(1, 1));
+    assert!(matches!(
+        batch.get::<i32>(1, 0),
+        Err(CellAccessError::UnexpectedNull { .. })
+    ));
+}
+
+/// Fetch wide text containing surrogate pairs, which exceeds the size of the initial buffer.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+fn get_wide_text_with_surrogate_pairs(profile: &Profile) {
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["NVARCHAR(MAX)"]).unwrap();
+    // Odd number of code units before the first surrogate pair, so it is split between chunks.
+    let text = format!("a{}", "🦀".repeat(300));
+    conn.execute(&table.sql_insert(), &text.as_str().into_parameter())
+        .unwrap();
+
+    let mut cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+    let mut row = cursor.next_row().unwrap().unwrap();
+    let mut buf = Vec::new();
+    let not_null = row.get_wide_text(1, &mut buf).unwrap();
+
+    assert!(not_null);
+    assert_eq!(text, String::from_utf16(&buf).unwrap());
+}
+
+/// Reset a prepared statement after limiting the number of rows and fetching with a block cursor.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn reset_prepared_statement(profile: &Profile) {
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["INTEGER"]).unwrap();
+    conn.execute(
+        &format!("INSERT INTO {table_name} (a) VALUES (1), (2), (3)"),
+        (),
+    )
+    .unwrap();
+    let mut prepared = conn.prepare(&table.sql_all_ordered_by_id()).unwrap();
+    prepared.set_max_rows(Some(1)).unwrap();
+    let buffer = ColumnarAnyBuffer::from_descs(2, [BufferDesc::I32 { nullable: false }]);
+    let mut cursor = prepared
+        .execute(())
+        .unwrap()
+        .unwrap()
+        .bind_buffer(buffer)
+        .unwrap();
+    assert_eq!(1, cursor.fetch().unwrap().unwrap().num_rows());
+    drop(cursor);
+
+    prepared.reset().unwrap();
+
+    let cursor = prepared.execute(()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+    assert_eq!("1\n2\n3", actual);
+}
+
+/// Execute a statement for two batches of one parameter buffer, which is bound only once, by
+/// changing the parameter bind offset in between.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn param_bind_offset(profile: &Profile) {
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["BIGINT"]).unwrap();
+    let values = WithDataType::new(vec![1i64, 2, 3, 4], DataType::BigInt);
+    let mut offset = Box::new(0);
+    let mut prepared = conn.prepare(&table.sql_insert()).unwrap();
+
+    let mut stmt = prepared.as_stmt_ref();
+    unsafe {
+        stmt.bind_input_parameter(1, &values)
+            .into_result(&stmt)
+            .unwrap();
+        stmt.set_paramset_size(2).into_result(&stmt).unwrap();
+        stmt.set_param_bind_offset(Some(&mut *offset))
+            .into_result(&stmt)
+            .unwrap();
+        stmt.execute().into_result(&stmt).unwrap();
+        *offset = 2 * std::mem::size_of::<i64>();
+        stmt.execute().into_result(&stmt).unwrap();
+    }
+    prepared.reset().unwrap();
+
+    assert_eq!("1\n2\n3\n4", table.content_as_string(&conn));
+}
+
+/// Fetch into an array of C structs, using a row wise layout described by the application.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn manual_row_wise_binding(profile: &Profile) {
+    #[repr(C)]
+    #[derive(Clone, Copy, Default)]
+    struct Row {
+        a: i32,
+        b: [u8; 16],
+        b_indicator: isize,
+    }
+
+    // Given
+    let table_name = table_name!();
+    let (conn, table) = profile
+        .given(&table_name, &["INTEGER", "VARCHAR(10)"])
+        .unwrap();
+    conn.execute(
+        &format!("INSERT INTO {table_name} (a, b) VALUES (1, 'one'), (2, NULL), (3, 'three')"),
+        (),
+    )
+    .unwrap();
+    let mut rows = [Row::default(); 4];
+
+    // When
+    let memory = unsafe {
+        let len = std::mem::size_of_val(&rows);
+        std::slice::from_raw_parts_mut(rows.as_mut_ptr() as *mut u8, len)
+    };
+    let row_set = ManualRowSet::row_wise(memory, std::mem::size_of::<Row>())
+        .fixed::<i32>(std::mem::offset_of!(Row, a), None)
+        .variadic(
+            sys::CDataType::Char,
+            std::mem::offset_of!(Row, b),
+            16,
+            std::mem::offset_of!(Row, b_indicator),
+        )
+        .build()
+        .unwrap();
+    let cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+    let mut cursor = cursor.bind_buffer(row_set).unwrap();
+    let batch = cursor.fetch().unwrap().unwrap();
+    assert_eq!(3, batch.num_rows());
+    assert_eq!(Some(2), batch.fixed::<i32>(0, 1));
+    assert_eq!(None, batch.value(1, 1));
+    drop(cursor);
+
+    // Then
+    assert_eq!(3, rows[2].a);
+    assert_eq!(b"three\0", &rows[2].b[..6]);
+    assert_eq!(5, rows[2].b_indicator);
+}
+
+/// Execute several queries, each one while the results of the previous one are processed.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn execute_pipelined(profile: &Profile) {
+    let table_name = table_name!();
+    let (conn, _table) = profile.given(&table_name, &["INTEGER"]).unwrap();
+    conn.execute(
+        &format!("INSERT INTO {table_name} (a) VALUES (1), (2), (3)"),
+        (),
+    )
+    .unwrap();
+    let queries: Vec<_> = (1..=3)
+        .map(|a| format!("SELECT a FROM {table_name} WHERE a = {a}"))
+        .collect();
+
+    let mut results = Vec::new();
+    conn.execute_pipelined(queries.iter().map(String::as_str), |index, cursor| {
+        results.push((index, cursor_to_string(cursor)));
+        Ok(())
+    })
+    .unwrap();
+
+    let expected: Vec<_> = (0..3)
+        .map(|index| (index, (index + 1).to_string()))
+        .collect();
+    assert_eq!(expected, results);
+}
+
+/// Insert a row and fetch the key generated for it, using the mechanism appropriate for the DBMS.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn execute_returning_keys(profile: &Profile) {
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["VARCHAR(10)"]).unwrap();
+    conn.execute(
+        &format!("INSERT INTO {table_name} (a) VALUES ('first')"),
+        (),
+    )
+    .unwrap();
+
+    let cursor = conn
+        .execute_returning_keys(&table.sql_insert(), &"second".into_parameter(), &["id"])
+        .unwrap()
+        .unwrap();
+
+    let actual = cursor_to_string(cursor);
+    assert_eq!("2", actual);
+}
+
+/// Fetch a repetitive text column and store it dictionary encoded.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn dictionary_encode_text_column(profile: &Profile) {
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["VARCHAR(10)"]).unwrap();
+    conn.execute(
+        &format!("INSERT INTO {table_name} (a) VALUES ('DE'), ('US'), (NULL), ('DE'), ('US')"),
+        (),
+    )
+    .unwrap();
+
+    let mut cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+    let buffer = TextRowSet::for_cursor(10, &mut cursor, None).unwrap();
+    let mut cursor = cursor.bind_buffer(buffer).unwrap();
+    let batch = cursor.fetch().unwrap().unwrap();
+    let encoded = DictionaryTextColumn::from_view(batch.column(0));
+
+    assert_eq!(2, encoded.num_distinct());
+    assert_eq!(&[Some(0), Some(1), None, Some(0), Some(1)], encoded.codes());
+    assert_eq!(Some(&b"US"[..]), encoded.get(4));
+}
+
+/// Only extract rows added since the previous run.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn incremental_extraction(profile: &Profile) {
+    let table_name = table_name!();
+    let (conn, _table) = profile.given(&table_name, &["VARCHAR(20)"]).unwrap();
+    let insert = |values: &str| {
+        conn.execute(&format!("INSERT INTO {table_name} (a) VALUES {values}"), ())
+            .unwrap();
+    };
+    let extract = |extraction: &mut IncrementalExtraction<InMemoryWatermarkStore>| {
+        let select = Select::from(table_name.as_str()).columns(["a"]);
+        let mut cursor = extraction.execute(&conn, select).unwrap().unwrap();
+        let mut values = Vec::new();
+        let mut buf = Vec::new();
+        while let Some(mut row) = cursor.next_row().unwrap() {
+            row.get_text(1, &mut buf).unwrap();
+            let value = String::from_utf8(buf.clone()).unwrap();
+            extraction.observe(&value);
+            values.push(value);
+        }
+        extraction.commit().unwrap();
+        values
+    };
+
+    insert("('2024-01-02'), ('2024-01-01')");
+    let mut extraction =
+        IncrementalExtraction::new("incremental", "a", InMemoryWatermarkStore::new()).unwrap();
+    let first_run = extract(&mut extraction);
+    insert("('2024-01-03')");
+    let store = extraction.into_store();
+    let mut extraction = IncrementalExtraction::new("incremental", "a", store).unwrap();
+    let second_run = extract(&mut extraction);
+
+    assert_eq!(["2024-01-01", "2024-01-02"], first_run.as_slice());
+    assert_eq!(["2024-01-03"], second_run.as_slice());
+    assert_eq!(Some("2024-01-03"), extraction.saved_watermark());
+}
+
+/// Capture inserted and updated rows by polling their audit columns.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn change_capture(profile: &Profile) {
+    let table_name = table_name!();
+    let (conn, _table) = profile
+        .given(&table_name, &["VARCHAR(20)", "VARCHAR(20)"])
+        .unwrap();
+    let insert = |values: &str| {
+        conn.execute(
+            &format!("INSERT INTO {table_name} (a, b) VALUES {values}"),
+            (),
+        )
+        .unwrap();
+    };
+    insert("('2024-01-02', '2024-01-01'), ('2024-01-01', '2024-01-01')");
+    let mut capture =
+        ChangeCapture::new("changes", &table_name, "a", InMemoryWatermarkStore::new())
+            .unwrap()
+            .with_columns(["id"])
+            .with_created_column("b")
+            .with_interval(Duration::from_millis(1), Duration::from_millis(10));
+
+    let mut changes = capture.changes(&conn);
+    let first = changes.next().unwrap().unwrap();
+    let second = changes.next().unwrap().unwrap();
+    insert("('2024-01-03', '2024-01-03')");
+    let third = changes.next().unwrap().unwrap();
+    drop(changes);
+
+    assert_eq!(
+        (ChangeKind::Inserted, "2024-01-01"),
+        (first.kind, first.watermark.as_str())
+    );
+    assert_eq!(
+        (ChangeKind::Updated, "2024-01-02"),
+        (second.kind, second.watermark.as_str())
+    );
+    assert_eq!(
+        (ChangeKind::Inserted, "2024-01-03"),
+        (third.kind, third.watermark.as_str())
+    );
+    assert_eq!(3, capture.column_names().len());
+    let mut store = capture.into_store();
+    assert_eq!(
+        Some("2024-01-02".to_owned()),
+        store.load("changes").unwrap()
+    );
+}
+
+/// Fetch a result set in pages using keyset pagination.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn paginate(profile: &Profile) {
+    let table_name = table_name!();
+    let (conn, _table) = profile.given(&table_name, &["VARCHAR(10)"]).unwrap();
+    conn.execute(
+        &format!("INSERT INTO {table_name} (a) VALUES ('A'), ('B'), ('C'), ('D'), ('E')"),
+        (),
+    )
+    .unwrap();
+
+    let pages: Vec<_> = conn
+        .paginate(&format!("SELECT id, a FROM {table_name}"), &["id"], 2)
+        .unwrap()
+        .map(|page| cursor_to_string(page.unwrap()))
+        .collect();
+
+    assert_eq!(["1,A\n2,B", "3,C\n4,D", "5,E"], pages.as_slice());
+}
+
+/// No empty page is returned, if the number of rows is a multiple of the page size.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn paginate_without_empty_trailing_page(profile: &Profile) {
+    let table_name = table_name!();
+    let (conn, _table) = profile.given(&table_name, &["VARCHAR(10)"]).unwrap();
+    conn.execute(
+        &format!("INSERT INTO {table_name} (a) VALUES ('A'), ('B'), ('C'), ('D')"),
+        (),
+    )
+    .unwrap();
+
+    let pages: Vec<_> = conn
+        .paginate(&format!("SELECT id, a FROM {table_name}"), &["id"], 2)
+        .unwrap()
+        .map(|page| cursor_to_string(page.unwrap()))
+        .collect();
+
+    assert_eq!(["1,A\n2,B", "3,C\n4,D"], pages.as_slice());
+}
+
+/// Select rows matching a list of values, which is split into chunks.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn execute_in_list(profile: &Profile) {
+    let table_name = table_name!();
+    let (conn, _table) = profile.given(&table_name, &["INTEGER"]).unwrap();
+    conn.execute(
+        &format!("INSERT INTO {table_name} (a) VALUES (1), (2), (3), (4), (5), (6)"),
+        (),
+    )
+    .unwrap();
+
+    let query = format!("SELECT a FROM {table_name} WHERE a IN (?...) ORDER BY a");
+    let mut cursor = conn.execute_in_list(&query, &[6, 1, 4, 7, 3], 2).unwrap();
+    assert_eq!(2, cursor.chunk_size());
+    // The first chunk has already been executed.
+    assert_eq!(2, cursor.num_chunks());
+    let mut rows = Vec::new();
+    while let Some(mut row) = cursor.next_row().unwrap() {
+        let mut value = 0i32;
+        row.get_data(1, &mut value).unwrap();
+        rows.push(value);
+    }
+
+    // Rows are ordered within each chunk, but not across chunks.
+    assert_eq!([1, 6, 4, 3], rows.as_slice());
+    let buffer: SingleColumnRowSetBuffer<Vec<i32>> = SingleColumnRowSetBuffer::new(1);
+    let mut block_cursor = conn
+        .execute_in_list(&query, &[6, 1, 4, 7, 3], 2)
+        .unwrap()
+        .bind_buffer(buffer)
+        .unwrap();
+    let mut fetched = Vec::new();
+    while let Some(batch) = block_cursor.fetch().unwrap() {
+        fetched.extend_from_slice(batch.get());
+    }
+    assert_eq!([1, 6, 4, 3], fetched.as_slice());
+    let mut empty = conn.execute_in_list(&query, &[] as &[i32], 2).unwrap();
+    assert!(empty.next_row().unwrap().is_none());
+}
+
+/// Statements of a script are executed one at a time, semicolons in literals are not separators.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn execute_script(profile: &Profile) {
+    // Given
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["VARCHAR(10)"]).unwrap();
+    let script = format!(
+        "INSERT INTO {table_name} (a) VALUES ('a;b');\n\
+        -- INSERT INTO {table_name} (a) VALUES ('c');\n\
+        INSERT INTO {table_name} (a) VALUES ('d'), ('e');"
+    );
+
+    // When
+    let report = conn.execute_script(&script).unwrap();
+
+    // Then
+    assert_eq!(2, report.num_statements);
+    assert_eq!(3, report.rows_affected);
+    assert_eq!("a;b\nd\ne", table.content_as_string(&conn));
+}
+
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn delete_and_update_by_keys(profile: &Profile) {
+    // Given
+    let table_name = table_name!();
+    let (conn, table) = profile
+        .given(&table_name, &["INTEGER", "VARCHAR(10)"])
+        .unwrap();
+    conn.execute(
+        &format!("INSERT INTO {table_name} (a, b) VALUES (1, 'x'), (2, 'x'), (3, 'x'), (4, 'x')"),
+        (),
+    )
+    .unwrap();
+
+    // When
+    let deleted = conn
+        .delete_by_keys(&table_name, &["a"], [(2i32,), (4,), (5,)], 2)
+        .unwrap();
+    let updated = conn
+        .update_by_keys(&table_name, &["b"], &["a"], [("y", 3i32)], 2)
+        .unwrap();
+
+    // Then
+    assert_eq!(2, deleted.rows_affected);
+    assert!(deleted.batch_errors.is_empty());
+    assert_eq!(1, updated.rows_affected);
+    let cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+    assert_eq!("1,x\n3,y", cursor_to_string(cursor));
+}
+
+/// PostgreSQL expands the rows server side, all other data sources use array bound parameters.
+#[test_case(MSSQL, BulkLoadStrategy::ArrayInsert; "Microsoft SQL Server")]
+#[test_case(MARIADB, BulkLoadStrategy::ArrayInsert; "Maria DB")]
+#[test_case(SQLITE_3, BulkLoadStrategy::ArrayInsert; "SQLite 3")]
+#[test_case(POSTGRES, BulkLoadStrategy::RecordSet; "PostgreSQL")]
+fn bulk_load(profile: &Profile, expected_strategy: BulkLoadStrategy) {
+    // Given
+    let table_name = table_name!();
+    let (conn, table) = profile
+        .given(&table_name, &["INTEGER", "VARCHAR(20)"])
+        .unwrap();
+    let rows = [
+        [Some("1"), Some("Hello")],
+        [Some("2"), None],
+        [Some("3"), Some("\"Quoted\" \\ text")],
+    ];
+
+    // When
+    let report = conn.bulk_load(&table_name, &["a", "b"], rows, 2).unwrap();
+
+    // Then
+    assert_eq!(expected_strategy, report.strategy);
+    assert_eq!(3, report.num_rows);
+    assert_eq!(2, report.num_batches);
+    let cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        "1,Hello\n2,NULL\n3,\"Quoted\" \\ text",
+        cursor_to_string(cursor)
+    );
+}
+
+/// Request a read only, forward only cursor and fetch from it.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn execute_with_read_only_forward_only_options(profile: &Profile) {
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["INTEGER"]).unwrap();
+    conn.execute(&format!("INSERT INTO {table_name} (a) VALUES (1), (2)"), ())
+        .unwrap();
+    let options = StatementOptions::new().read_only_forward_only();
+
+    let cursor = conn
+        .execute_with_options(&table.sql_all_ordered_by_id(), (), &options)
+        .unwrap()
+        .unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!("1\n2", actual);
+}
+
+/// Combine a query timeout with a row limit in one set of statement options.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn execute_with_timeout_and_max_rows_options(profile: &Profile) {
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["INTEGER"]).unwrap();
+    conn.execute(
+        &format!("INSERT INTO {table_name} (a) VALUES (1), (2), (3)"),
+        (),
+    )
+    .unwrap();
+    let options = StatementOptions::new()
+        .with_query_timeout(Duration::from_secs(10))
+        .with_max_rows(2);
+
+    let cursor = conn
+        .execute_with_options(&table.sql_all_ordered_by_id(), (), &options)
+        .unwrap()
+        .unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!("1\n2", actual);
+}
+
+/// A healthy connection answers a ping, a failing probe reports the connection as unusable.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn ping(profile: &Profile) {
+    let conn = profile.connection().unwrap();
+
+    let is_alive = conn.ping().unwrap();
+    let is_alive_with_probe = conn
+        .ping_with_probe("SELECT 1", Duration::from_secs(1))
+        .unwrap();
+    let is_alive_with_failing_probe = conn
+        .ping_with_probe("SELECT * FROM NonExistingTable", Duration::from_secs(1))
+        .unwrap();
+
+    assert!(is_alive);
+    assert!(is_alive_with_probe);
+    assert!(!is_alive_with_failing_probe);
+}
+
+/// Threads sharing a serialized connection take turns using it.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn serialized_connection(profile: &Profile) {
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["INTEGER"]).unwrap();
+    let insert = table.sql_insert();
+    let conn = unsafe { SerializedConnection::new(conn) };
+
+    thread::scope(|scope| {
+        for value in 0..4 {
+            let conn = &conn;
+            let insert = &insert;
+            scope.spawn(move || {
+                conn.with(|conn| {
+                    conn.execute(insert, &value).unwrap();
+                })
+            });
+        }
+    });
+    let capability = conn.with(|conn| conn.threading_capability()).unwrap();
+
+    let conn = conn.into_inner();
+    let cursor = conn
+        .execute(&format!("SELECT a FROM {table_name} ORDER BY a"), ())
+        .unwrap()
+        .unwrap();
+    assert_eq!("0\n1\n2\n3", cursor_to_string(cursor));
+    assert!(!capability.driver_name.is_empty());
+}
+
+/// Errors include the calls preceding them, if the call audit is enabled.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn call_audit(profile: &Profile) {
+    let mut conn = profile.connection().unwrap();
+    conn.enable_call_audit(8);
+
+    let result = conn.execute("SELECT * FROM TableDoesNotExist", ());
+
+    let error = result.map(|_| ()).unwrap_err();
+    if let Error::Diagnostics { record, function } = error {
+        let last = record.call_history.last().unwrap();
+        assert_eq!(function, last.function);
+        assert_eq!(sys::SqlReturn::ERROR, last.return_code);
+        assert_eq!(Some(record.state), last.state);
+    } else {
+        panic!("Expected Error::Diagnostics")
+    };
+    assert!(!conn.call_history().is_empty());
+}
+
+/// The call audit ends with the connection, so a connection opened afterwards does not report
+/// calls recorded before, even if its handle reuses the address of the disconnected one.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn call_audit_ends_with_disconnect(profile: &Profile) {
+    // Given
+    let mut conn = profile.connection().unwrap();
+    conn.enable_call_audit(8);
+    let _ = conn.execute("SELECT * FROM TableDoesNotExist", ());
+    conn.disconnect().map_err(|failed| failed.1).unwrap();
+
+    // When
+    let conn = profile.connection().unwrap();
+    let result = conn.execute("SELECT * FROM TableDoesNotExist", ());
+
+    // Then
+    let error = result.map(|_| ()).unwrap_err();
+    if let Error::Diagnostics { record, .. } = error {
+        assert!(record.call_history.is_empty());
+    } else {
+        panic!("Expected Error::Diagnostics")
+    };
+    assert!(conn.call_history().is_empty());
+}
+
+/// Key value pairs set on the connection are attached to errors of its statements.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn error_context(profile: &Profile) {
+    let conn = profile.connection().unwrap();
+    conn.set_context("tenant", "acme");
+    conn.set_context("job", "42");
+    let mut prepared = conn.prepare("SELECT * FROM TableDoesNotExist");
+    conn.set_context("job", "43");
+
+    let error = match prepared.as_mut() {
+        Ok(prepared) => prepared.execute(()).map(|_| ()).unwrap_err(),
+        Err(_) => prepared.map(|_| ()).unwrap_err(),
+    };
+
+    let expected = vec![
+        ("tenant".to_owned(), "acme".to_owned()),
+        ("job".to_owned(), "43".to_owned()),
+    ];
+    if let Error::Diagnostics { record, .. } = &error {
+        assert_eq!(expected, record.context);
+    } else {
+        panic!("Expected Error::Diagnostics")
+    };
+    assert!(error.to_string().contains("Context: tenant=acme, job=43"));
+}
+
+/// Statements inherit the connection context, including later changes to it, and may add entries
+/// of their own.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn statement_error_context(profile: &Profile) {
+    let conn = profile.connection().unwrap();
+    conn.set_context("tenant", "acme");
+    let mut statement = conn.preallocate().unwrap();
+    conn.set_context("job", "42");
+    statement.set_context("job", "43");
+    statement.set_context("step", "load");
+
+    let error = statement
+        .execute("SELECT * FROM TableDoesNotExist", ())
+        .map(|_| ())
+        .unwrap_err();
+
+    let expected = vec![
+        ("tenant".to_owned(), "acme".to_owned()),
+        ("job".to_owned(), "43".to_owned()),
+        ("step".to_owned(), "load".to_owned()),
+    ];
+    if let Error::Diagnostics { record, .. } = &error {
+        assert_eq!(expected, record.context);
+    } else {
+        panic!("Expected Error::Diagnostics")
+    };
+    // The statement context does not leak into the connection.
+    assert_eq!(
+        vec![
+            ("tenant".to_owned(), "acme".to_owned()),
+            ("job".to_owned(), "42".to_owned()),
+        ],
+        conn.context()
+    );
+}
+
+/// Round trip times are summarized into minimum, median and maximum.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn measure_latency(profile: &Profile) {
+    let conn = profile.connection().unwrap();
+
+    let latency = conn.measure_latency(3).unwrap();
+
+    assert!(latency.min <= latency.median);
+    assert!(latency.median <= latency.max);
+}
+
+/// An environment of its own, declaring a different ODBC version, keeps track of its connections.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn independent_environment(profile: &Profile) {
+    let env = Environment::with_odbc_version(AttrOdbcVersion::Odbc3).unwrap();
+
+    let conn = env
+        .connect_with_connection_string(profile.connection_string)
+        .unwrap();
+    let num_connections_while_open = env.num_connections();
+    drop(conn);
+    let result = unsafe {
+        Environment::set_connection_pooling_checked(sys::AttrConnectionPooling::DriverAware)
+    };
+
+    assert_eq!(1, num_connections_while_open);
+    assert_eq!(0, env.num_connections());
+    assert!(Environment::num_environments() >= 2);
+    assert!(matches!(
+        result,
+        Err(Error::ConnectionPoolingWithLiveEnvironments { .. })
+    ));
+}
+
+/// The capability report lists the DBMS name and the data types of the data source.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn capability_report(profile: &Profile) {
+    let conn = profile.connection().unwrap();
+
+    let report = diagnostics::capability_report(&conn);
+
+    let dbms_name = report
+        .info
+        .iter()
+        .find(|entry| entry.name == "SQL_DBMS_NAME")
+        .unwrap();
+    assert_eq!(
+        Some(diagnostics::InfoValue::Text(
+            conn.database_management_system_name().unwrap()
+        )),
+        dbms_name.value
+    );
+    assert!(!report.types.is_empty());
+    assert!(report.to_string().contains("SQL_DBMS_NAME"));
+}
+
+/// Spooled results can be iterated over multiple times, and iteration can resume at any batch.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn spool_result_set_to_disk(profile: &Profile) {
+    let table_name = table_name!();
+    let (conn, table) = profile
+        .given(&table_name, &["INTEGER", "VARCHAR(10)"])
+        .unwrap();
+    let insert = table.sql_insert();
+    conn.execute(&insert, (&1, &"one".into_parameter()))
+        .unwrap();
+    conn.execute(&insert, (&2, &Nullable::<i32>::null()))
+        .unwrap();
+    conn.execute(&insert, (&3, &"three".into_parameter()))
+        .unwrap();
+    let cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+
+    let spooled = SpoolingCursor::new(cursor, 2, None).unwrap();
+    let spill_file = spooled.path().unwrap().to_owned();
+    let values = |batches: SpooledBatches| {
+        let mut values = Vec::new();
+        for batch in batches {
+            let batch = batch.unwrap();
+            for row_index in 0..batch.num_rows() {
+                values.push(batch.at_as_str(1, row_index).map(str::to_owned));
+            }
+        }
+        values
+    };
+    let first_pass = values(spooled.batches());
+    let second_pass = values(spooled.batches());
+    let resumed = values(spooled.batches_from(1));
+
+    assert_eq!(["a", "b"], spooled.column_names());
+    assert_eq!(3, spooled.num_rows());
+    assert_eq!(2, spooled.num_batches());
+    let expected = vec![Some("one".to_owned()), None, Some("three".to_owned())];
+    assert_eq!(expected, first_pass);
+    assert_eq!(expected, second_pass);
+    assert_eq!(vec![Some("three".to_owned())], resumed);
+    drop(spooled);
+    assert!(!spill_file.exists());
+}
+
+/// Result sets written as Avro object container file, with decimals as logical type.
+#[cfg(feature = "avro")]
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn write_avro(profile: &Profile) {
+    use odbc_api::avro::AvroOptions;
+
+    let table_name = table_name!();
+    let (conn, table) = profile
+        .given(&table_name, &["INTEGER", "DECIMAL(5,2)", "VARCHAR(10)"])
+        .unwrap();
+    let insert =
+        format!("INSERT INTO {table_name} (a, b, c) VALUES (1, 12.34, 'one'), (2, NULL, NULL)");
+    conn.execute(&insert, ()).unwrap();
+    let cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+
+    let mut avro = Vec::new();
+    let num_rows = cursor
+        .write_avro(&mut avro, &AvroOptions::new().with_record_name("Test"))
+        .unwrap();
+
+    assert_eq!(2, num_rows);
+    assert!(avro.starts_with(b"Obj\x01"));
+    let avro = String::from_utf8_lossy(&avro);
+    assert!(avro.contains(r#""name":"Test""#));
+    assert!(avro.contains(r#""logicalType":"decimal","precision":5,"scale":2"#));
+}
+
+/// Result sets written as Arrow IPC stream, with the schema message first and the end of stream
+/// marker last.
+#[cfg(feature = "arrow")]
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn write_arrow_ipc(profile: &Profile) {
+    use odbc_api::arrow::ArrowIpcOptions;
+
+    let table_name = table_name!();
+    let (conn, table) = profile
+        .given(&table_name, &["INTEGER", "DECIMAL(5,2)", "VARCHAR(10)"])
+        .unwrap();
+    let insert =
+        format!("INSERT INTO {table_name} (a, b, c) VALUES (1, 12.34, 'one'), (2, NULL, NULL)");
+    conn.execute(&insert, ()).unwrap();
+    let cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+
+    let mut stream = Vec::new();
+    let num_rows = cursor
+        .write_arrow_ipc(&mut stream, &ArrowIpcOptions::new().with_batch_size(1))
+        .unwrap();
+
+    assert_eq!(2, num_rows);
+    assert!(stream.starts_with(&[0xFF, 0xFF, 0xFF, 0xFF]));
+    assert!(stream.ends_with(&[0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 0]));
+    // Column names are part of the schema message, values of the record batches.
+    assert!(stream.windows(3).any(|window| window == b"one"));
+}
+
+/// Objects of newline delimited JSON are inserted by column name, malformed lines are quarantined.
+#[cfg(feature = "ndjson")]
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn load_ndjson(profile: &Profile) {
+    use odbc_api::ndjson::NdjsonLoader;
+
+    let table_name = table_name!();
+    let (conn, table) = profile
+        .given(&table_name, &["INTEGER", "VARCHAR(10)"])
+        .unwrap();
+    let input = "{\"a\": 1, \"b\": \"one\"}\n\
+        not json\n\
+        \n\
+        {\"b\": \"two\", \"a\": 2, \"c\": true}\n\
+        [3]\n\
+        {\"a\": 3}\n";
+
+    let report = NdjsonLoader::new()
+        .with_batch_size(2)
+        .with_columns(["a", "b"])
+        .load(&conn, &table_name, input.as_bytes())
+        .unwrap();
+
+    assert_eq!(3, report.num_inserted);
+    let quarantined: Vec<_> = report
+        .quarantined
+        .iter()
+        .map(|line| line.line_number)
+        .collect();
+    assert_eq!(vec![2, 5], quarantined);
+    assert_eq!("1,one\n2,two\n3,NULL", table.content_as_string(&conn));
+}
+
+/// A recorded result set is replayed with the values and the schema reported by the driver.
+#[cfg(feature = "mock")]
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn record_and_replay_result_set(profile: &Profile) {
+    use odbc_api::{
+        mock::{MockConnection, MockResultSet},
+        GenericConnection, ResultSetSchema,
+    };
+
+    // Given
+    let table_name = table_name!();
+    let (conn, table) = profile
+        .given(&table_name, &["INTEGER", "VARCHAR(10)"])
+        .unwrap();
+    let insert = format!("INSERT INTO {table_name} (a, b) VALUES (1, 'one'), (2, NULL)");
+    conn.execute(&insert, ()).unwrap();
+    let query = table.sql_all_ordered_by_id();
+    let mut cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let expected_schema = ResultSetSchema::for_cursor(&mut cursor).unwrap();
+
+    // When
+    let mut file = Vec::new();
+    MockResultSet::record(cursor, 1, None)
+        .unwrap()
+        .save(&mut file)
+        .unwrap();
+    let replayed = MockConnection::new()
+        .with_result_set(&query, MockResultSet::load(file.as_slice()).unwrap());
+    let mut cursor = replayed.execute(&query, &[]).unwrap().unwrap();
+
+    // Then
+    assert_eq!(expected_schema, cursor.result_set_schema().unwrap());
+    let mut rows = Vec::new();
+    while let Some(row) = cursor.next_text_row().unwrap() {
+        rows.push(row);
+    }
+    assert_eq!(
+        vec![
+            vec![Some("1".to_string()), Some("one".to_string())],
+            vec![Some("2".to_string()), None]
+        ],
+        rows
+    );
+}
+
+/// Batches are processed on the thread pool, results are reported in the order of the batches.
+#[cfg(feature = "rayon")]
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn parallel_fetch(profile: &Profile) {
+    use odbc_api::parallel::ParallelFetch;
+
+    // Given
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["INTEGER"]).unwrap();
+    for value in 1..=10 {
+        conn.execute(&table.sql_insert(), &value).unwrap();
+    }
+    let cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+
+    // When
+    let sums = ParallelFetch::new()
+        .with_max_in_flight(2)
+        .run(
+            cursor,
+            |_cursor| Ok(ColumnarBuffer::<Vec<i32>>::new(vec![(1, vec![0; 3])])),
+            |batch| batch.column(0)[..batch.num_rows()].iter().sum::<i32>(),
+        )
+        .unwrap();
+
+    // Then
+    assert_eq!(vec![6, 15, 24, 10], sums);
+}
+
+/// Fetching from within the only thread of the pool does not wait for tasks queued to the thread
+/// itself.
+#[cfg(feature = "rayon")]
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn parallel_fetch_within_pool(profile: &Profile) {
+    use odbc_api::parallel::ParallelFetch;
+
+    // Given
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["INTEGER"]).unwrap();
+    for value in 1..=10 {
+        conn.execute(&table.sql_insert(), &value).unwrap();
+    }
+    let query = table.sql_all_ordered_by_id();
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap();
+
+    // When
+    let sums = pool.install(|| {
+        let conn = profile.connection().unwrap();
+        let cursor = conn.execute(&query, ()).unwrap().unwrap();
+        ParallelFetch::new()
+            .with_thread_pool(&pool)
+            .with_max_in_flight(1)
+            .run(
+                cursor,
+                |_cursor| Ok(ColumnarBuffer::<Vec<i32>>::new(vec![(1, vec![0; 3])])),
+                |batch| batch.column(0)[..batch.num_rows()].iter().sum::<i32>(),
+            )
+            .unwrap()
+    });
+
+    // Then
+    assert_eq!(vec![6, 15, 24, 10], sums);
+}
+
+/// Rows are bulk copied into the table, while rows not matching the bound columns are rejected.
+/// The test executable must be linked against the driver library, e.g. with
+/// `RUSTFLAGS="-L /opt/microsoft/msodbcsql17/lib64 -l msodbcsql-17"`.
+#[cfg(feature = "mssql-bcp")]
+#[test_case(MSSQL; "Microsoft SQL Server")]
+fn bulk_copy(profile: &Profile) {
+    use odbc_api::mssql_bcp::{BcpColumn, BcpValue, BulkCopy};
+
+    // Given
+    let table_name = table_name!();
+    let conn = ENV
+        .connect_with_bulk_copy(profile.connection_string)
+        .unwrap();
+    conn.execute(&format!("DROP TABLE IF EXISTS {table_name}"), ())
+        .unwrap();
+    conn.execute(
+        &format!("CREATE TABLE {table_name} (a INT, b NVARCHAR(5))"),
+        (),
+    )
+    .unwrap();
+
+    // When
+    let columns = [BcpColumn::I32, BcpColumn::Text { max_str_len: 5 }];
+    let mut bcp = BulkCopy::new(&conn, &table_name, &columns).unwrap();
+    bcp.send_row(&[BcpValue::I32(1), BcpValue::Text("Grü")])
+        .unwrap();
+    bcp.send_row(&[BcpValue::I32(2), BcpValue::Null]).unwrap();
+    let missing_value = bcp.send_row(&[BcpValue::I32(3)]);
+    let wrong_type = bcp.send_row(&[BcpValue::Text("4"), BcpValue::Null]);
+    let num_rows = bcp.finish().unwrap();
+
+    // Then
+    assert!(matches!(missing_value, Err(Error::InvalidArgument { .. })));
+    assert!(matches!(wrong_type, Err(Error::InvalidArgument { .. })));
+    assert_eq!(2, num_rows);
+    let cursor = conn
+        .execute(&format!("SELECT a, b FROM {table_name} ORDER BY a"), ())
+        .unwrap()
+        .unwrap();
+    assert_eq!("1,Grü\n2,NULL", cursor_to_string(cursor));
+}
+
+/// Text is sent and fetched in the declared narrow encoding of the driver, rather than as UTF-8.
+/// SQLite stores the bytes as they are, so the Latin-1 representation must round trip.
+#[cfg(feature = "encoding")]
+#[test_case(SQLITE_3; "SQLite 3")]
+fn narrow_encoding_round_trip(profile: &Profile) {
+    use odbc_api::encoding::encoding_rs::WINDOWS_1252;
+
+    // Given
+    let table_name = table_name!();
+    let (mut conn, table) = profile.given(&table_name, &["VARCHAR(20)"]).unwrap();
+    conn.set_narrow_encoding(WINDOWS_1252);
+
+    // When
+    let text = conn.encode_narrow("Grüße").unwrap();
+    conn.execute(&table.sql_insert(), &text).unwrap();
+    let cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+    let mut block_cursor = cursor
+        .bind_buffer(TextRowSet::from_max_str_lens(1, [20]).unwrap())
+        .unwrap();
+    let batch = block_cursor.fetch().unwrap().unwrap();
+
+    // Then
+    assert_eq!(Some(&b"Gr\xFC\xDFe"[..]), batch.at(0, 0));
+    assert_eq!(
+        "Grüße",
+        batch.at_decoded(0, 0, conn.narrow_encoding()).unwrap()
+    );
+}
+
+/// Switch to the catalog the connection is already using, and read it back.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+fn set_current_catalog(profile: &Profile) {
+    let conn = profile.connection().unwrap();
+    let catalog = conn.current_catalog().unwrap();
+
+    conn.set_current_catalog(&catalog).unwrap();
+
+    assert_eq!(catalog, conn.current_catalog().unwrap());
+}
+
+/// Switch to the schema the connection is already using, and read it back.
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn set_current_schema(profile: &Profile) {
+    let conn = profile.connection().unwrap();
+    let schema = conn.current_schema().unwrap().unwrap();
+
+    conn.set_current_schema(&schema).unwrap();
+
+    assert_eq!(Some(schema), conn.current_schema().unwrap());
+}
+
+/// Microsoft SQL Server does not allow switching the default schema of a session.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+fn set_current_schema_unsupported(profile: &Profile) {
+    let conn = profile.connection().unwrap();
+
+    let result = conn.set_current_schema("dbo");
+
+    assert!(matches!(
+        result,
+        Err(Error::UnsupportedSchemaSwitching { .. })
+    ));
+    assert_eq!(Some("dbo".to_owned()), conn.current_schema().unwrap());
+}
+
+/// Statements are counted while alive, and in total.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn statement_accounting(profile: &Profile) {
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["INTEGER"]).unwrap();
+    let allocated_before = conn.allocated_statement_count();
+
+    let prepared = conn.prepare(&table.sql_insert()).unwrap();
+    let cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+    let num_active = conn.active_statement_count();
+    drop(cursor);
+    drop(prepared);
+
+    assert_eq!(2, num_active);
+    assert_eq!(0, conn.active_statement_count());
+    assert_eq!(allocated_before + 2, conn.allocated_statement_count());
+}
+
+/// Insert and fetch rows using the typestate API, reusing the statement handles.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn typestate_statement(profile: &Profile) {
+    // Given
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["INTEGER"]).unwrap();
+
+    // When
+    let mut insert = typestate::Statement::prepare(&conn, &table.sql_insert()).unwrap();
+    for value in [1, 2] {
+        insert = match insert.execute(&value).unwrap() {
+            typestate::Execution::Executed(mut executed) => {
+                assert_eq!(Some(1), executed.row_count().unwrap());
+                executed.into_prepared()
+            }
+            typestate::Execution::CursorOpen(_) => panic!("INSERT must not produce a result set"),
+        };
+    }
+    let query = format!("SELECT a FROM {table_name} WHERE a = ?");
+    let mut select = typestate::Statement::prepare(&conn, &query).unwrap();
+    let mut fetched = Vec::new();
+    for value in [2, 1] {
+        select = match select.execute(&value).unwrap() {
+            typestate::Execution::CursorOpen(mut cursor) => {
+                let mut row = cursor.next_row().unwrap().unwrap();
+                let mut a = 0i32;
+                row.get_data(1, &mut a).unwrap();
+                fetched.push(a);
+                cursor.close().unwrap()
+            }
+            typestate::Execution::Executed(_) => panic!("SELECT must produce a result set"),
+        };
+    }
+
+    // Then
+    assert_eq!(vec![2, 1], fetched);
+}
+
+/// Skip a target which is not available and connect to the next one.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn connect_first_available(profile: &Profile) {
+    // Given
+    let targets = [
+        ConnectionTarget::connection_string("Driver={Driver_which_does_not_exist};"),
+        ConnectionTarget::connection_string(profile.connection_string),
+    ];
+    let options = FailoverOptions::new()
+        .with_login_timeout(Some(Duration::from_secs(5)))
+        .with_rounds(1);
+
+    // When
+    let (conn, index) = ENV
+        .connect_first_available_with_options(&targets, &options)
+        .unwrap();
+
+    // Then
+    assert_eq!(1, index);
+    assert!(!conn.is_dead().unwrap());
+}
+
+/// All targets unavailable
+#[test]
+fn connect_first_available_fails_if_no_target_is_available() {
+    let targets = [ConnectionTarget::connection_string(
+        "Driver={Driver_which_does_not_exist};",
+    )];
+    let options = FailoverOptions::new()
+        .with_rounds(2)
+        .with_retry_delay(Duration::from_millis(1));
+
+    let result = ENV.connect_first_available_with_options(&targets, &options);
+
+    assert!(matches!(
+        result,
+        Err(Error::NoConnectionTargetAvailable {
+            num_targets: 1,
+            num_attempts: 2,
+            ..
+        })
+    ));
+}
+
+/// A replica failing to connect is quarantined and skipped by subsequent connects.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn replica_set_quarantines_failing_replica(profile: &Profile) {
+    // Given
+    let replicas = ReplicaSet::new(vec![
+        ConnectionTarget::connection_string("Driver={Driver_which_does_not_exist};"),
+        ConnectionTarget::connection_string(profile.connection_string),
+    ]);
+
+    // When
+    let (_first, first_index) = replicas.connect(&ENV).unwrap();
+    let (_second, second_index) = replicas.connect(&ENV).unwrap();
+
+    // Then
+    assert_eq!(1, first_index);
+    assert_eq!(1, second_index);
+    assert!(!replicas.is_healthy(0));
+    assert_eq!(1, replicas.num_healthy());
+}
+
+/// Measuring latencies quarantines unreachable replicas and prefers the fastest reachable one.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn replica_set_lowest_latency(profile: &Profile) {
+    // Given
+    let replicas = ReplicaSet::new(vec![
+        ConnectionTarget::connection_string("Driver={Driver_which_does_not_exist};"),
+        ConnectionTarget::connection_string(profile.connection_string),
+    ])
+    .with_selection(ReplicaSelection::LowestLatency);
+
+    // When
+    let latencies = replicas.measure_latencies(&ENV, 2);
+    let (_conn, index) = replicas.connect(&ENV).unwrap();
+
+    // Then
+    assert!(latencies[0].is_none());
+    assert!(latencies[1].is_some());
+    assert_eq!(latencies[1], replicas.latency(1));
+    assert_eq!(1, index);
+}
+
+/// Session initialization statements are executed on every connect.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn session_initialization(profile: &Profile) {
+    // Given
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["INTEGER"]).unwrap();
+    // Use an environment of our own, so other tests are not affected
+    let env = Environment::new().unwrap();
+    env.set_session_initialization([format!("INSERT INTO {table_name} (a) VALUES (42)")]);
+
+    // When
+    for _ in 0..2 {
+        env.connect_with_connection_string(profile.connection_string)
+            .unwrap();
+    }
+
+    // Then
+    assert_eq!("42\n42", table.content_as_string(&conn));
+}
+
+/// Override the SQL data type a prepared statement binds its parameter with.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn prepared_with_parameter_types(profile: &Profile) {
+    // Given
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["INTEGER"]).unwrap();
+
+    // When
+    let mut prepared = conn
+        .prepare(&table.sql_insert())
+        .unwrap()
+        .with_parameter_types(&[DataType::Integer]);
+    prepared.execute(&"42".into_parameter()).unwrap();
+
+    // Then
+    assert_eq!(&[Some(DataType::Integer)], prepared.parameter_types());
+    assert_eq!("42", table.content_as_string(&conn));
+}
+
+/// Drivers used in the tests have no known quirks, but workarounds can be enabled anyway.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn override_quirks(profile: &Profile) {
+    // Given
+    let table_name = table_name!();
+    let (mut conn, table) = profile.given(&table_name, &["VARCHAR(10)"]).unwrap();
+    assert_eq!(Quirks::default(), conn.quirks());
+
+    // When
+    conn.set_quirks(Quirks {
+        zero_length_unsupported: true,
+        describe_param_unreliable: true,
+        ..Quirks::default()
+    });
+    let mut prepared = conn.prepare(&table.sql_insert()).unwrap();
+    prepared.execute(&"".into_parameter()).unwrap();
+    drop(prepared);
+
+    // Then
+    assert!(conn.quirks().zero_length_unsupported);
+    let cursor = conn
+        .execute(&format!("SELECT COUNT(*) FROM {table_name}"), ())
+        .unwrap()
+        .unwrap();
+    assert_eq!("1", cursor_to_string(cursor));
+}
+
+/// Prepared statements keep working after the table they query has been altered.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+fn re_prepare_after_schema_change(profile: &Profile) {
+    // Given
+    let table_name = table_name!();
+    let (mut conn, _table) = profile.given(&table_name, &["INTEGER"]).unwrap();
+    // Audit the calls of the prepared statement, to tell whether it has been prepared again.
+    conn.enable_call_audit(256);
+    let mut prepared = conn
+        .prepare(&format!("SELECT * FROM {table_name}"))
+        .unwrap()
+        .with_re_prepare_policy(RePreparePolicy::OnStaleMetadata);
+    assert_eq!(2, prepared.result_set_schema().unwrap().columns.len());
+    prepared.execute(()).unwrap();
+
+    // When
+    conn.execute(&format!("ALTER TABLE {table_name} ADD b INTEGER"), ())
+        .unwrap();
+    let num_calls_before = conn.call_history().len();
+    let mut cursor = prepared.execute(()).unwrap().unwrap();
+    let num_result_cols = cursor.num_result_cols().unwrap();
+    drop(cursor);
+
+    // Then
+    assert_eq!(3, num_result_cols);
+    // The cached schema is described again.
+    assert_eq!(3, prepared.result_set_schema().unwrap().columns.len());
+    let calls = conn.call_history();
+    let failed = calls[num_calls_before..]
+        .iter()
+        .position(|call| call.function == "SQLExecute" && call.return_code == sys::SqlReturn::ERROR)
+        .expect("Execution after schema change must fail");
+    assert!(
+        calls[num_calls_before + failed..]
+            .iter()
+            .any(|call| call.function == "SQLPrepare"),
+        "Statement must be prepared again after the failed execution"
+    );
+}
+
+/// Bind text as an integer, using `IntoParameter::with_data_type`.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn parameter_with_data_type(profile: &Profile) {
+    // Given
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["INTEGER"]).unwrap();
+
+    // When
+    let parameter = "42".with_data_type(DataType::Integer);
+    conn.execute(&table.sql_insert(), &parameter).unwrap();
+
+    // Then
+    assert_eq!("42", table.content_as_string(&conn));
+}
+
+/// Fetch SMALLINT and INTEGER columns into nullable buffers of small and unsigned integer types.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn fetch_small_and_unsigned_integers(profile: &Profile) {
+    // Given
+    let table_name = table_name!();
+    let (conn, table) = profile
+        .given(
+            &table_name,
+            &["SMALLINT", "SMALLINT", "INTEGER", "INTEGER", "INTEGER"],
+        )
+        .unwrap();
+    let insert = table.sql_insert();
+    conn.execute(&insert, (&1, &2, &3, &4, &5)).unwrap();
+    conn.execute(
+        &insert,
+        (
+            &Nullable::<i16>::null(),
+            &Nullable::<i16>::null(),
+            &Nullable::<i32>::null(),
+            &Nullable::<i32>::null(),
+            &Nullable::<i32>::null(),
+        ),
+    )
+    .unwrap();
+
+    // When
+    let cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+    let descs = [
+        BufferDesc::I8 { nullable: true },
+        BufferDesc::U8 { nullable: true },
+        BufferDesc::U16 { nullable: true },
+        BufferDesc::U32 { nullable: true },
+        BufferDesc::U64 { nullable: true },
+    ];
+    let buffer = ColumnarAnyBuffer::from_descs(10, descs);
+    let mut cursor = cursor.bind_buffer(buffer).unwrap();
+    let batch = cursor.fetch().unwrap().unwrap();
+
+    // Then
+    assert_eq!(2, batch.num_rows());
+    assert_eq!(Ok(Some(1i8)), batch.get(0, 0));
+    assert_eq!(Ok(Some(2u8)), batch.get(0, 1));
+    assert_eq!(Ok(Some(3u16)), batch.get(0, 2));
+    assert_eq!(Ok(Some(4u32)), batch.get(0, 3));
+    assert_eq!(Ok(Some(5u64)), batch.get(0, 4));
+    let unsigned: Vec<_> = batch
+        .column(4)
+        .as_nullable_slice::<u64>()
+        .unwrap()
+        .collect();
+    assert_eq!(vec![Some(&5), None], unsigned);
+}
+
+/// Floating point buffers for DECIMAL columns require an explicit opt-in.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn lossy_decimal_buffer_requires_opt_in(profile: &Profile) {
+    // Given
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["DECIMAL(5,2)"]).unwrap();
+    conn.execute(&table.sql_insert(), &"1.50".into_parameter())
+        .unwrap();
+    let descs = [BufferDesc::F64 { nullable: true }];
+
+    // When
+    let mut cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+    let strict = BufferBuilder::new(10).build_for(&mut cursor, descs);
+    let lenient = BufferBuilder::new(10)
+        .allow_lossy_decimals()
+        .build_for(&mut cursor, descs)
+        .unwrap();
+    let mut cursor = cursor.bind_buffer(lenient).unwrap();
+    let batch = cursor.fetch().unwrap().unwrap();
+
+    // Then
+    assert!(matches!(
+        strict,
+        Err(Error::LossyFloatBuffer {
+            column_number: 1,
+            ..
+        })
+    ));
+    assert_eq!(Ok(Some(1.5f64)), batch.get(0, 0));
+}
+
+/// Map rows to a struct by column name, with fields registered in a different order than the
+/// columns appear in the result set.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn row_mapper(profile: &Profile) {
+    // Given
+    #[derive(Default, Debug, PartialEq)]
+    struct Movie {
+        title: String,
+        year: Option<i32>,
+    }
+    let table_name = table_name!();
+    let (conn, table) = profile
+        .given(&table_name, &["INTEGER", "VARCHAR(50)"])
+        .unwrap();
+    let insert = table.sql_insert();
+    conn.execute(&insert, (&1968, &"2001: A Space Odyssey".into_parameter()))
+        .unwrap();
+    conn.execute(
+        &insert,
+        (
+            &None::<i32>.into_parameter(),
+            &"Jurassic Park".into_parameter(),
+        ),
+    )
+    .unwrap();
+
+    // When
+    let mut cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+    let mapper = RowMapper::<Movie>::new(&mut cursor)
+        .unwrap()
+        .field("B", |movie, title| movie.title = title)
+        .unwrap()
+        .field("a", |movie, year| movie.year = year)
+        .unwrap();
+    let mut movies = Vec::new();
+    while let Some(mut row) = cursor.next_row().unwrap() {
+        movies.push(mapper.map(&mut row).unwrap());
+    }
+    let unknown = RowMapper::<Movie>::new(&mut cursor)
+        .unwrap()
+        .field("director", |_movie, _director: String| ());
+
+    // Then
+    assert_eq!(
+        vec![
+            Movie {
+                title: "2001: A Space Odyssey".to_owned(),
+                year: Some(1968)
+            },
+            Movie {
+                title: "Jurassic Park".to_owned(),
+                year: None
+            },
+        ],
+        movies
+    );
+    assert!(matches!(unknown, Err(Error::RowMapperUnknownColumn { .. })));
+}
+
+/// Append the rows of each batch to a `Vec` of structs, using one closure per column.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn fetch_into_vec_of_structs(profile: &Profile) {
+    // Given
+    #[derive(Default, Debug, PartialEq)]
+    struct Movie {
+        year: Option<i32>,
+        title: String,
+    }
+    let table_name = table_name!();
+    let (conn, table) = profile
+        .given(&table_name, &["INTEGER", "VARCHAR(50)"])
+        .unwrap();
+    let insert = table.sql_insert();
+    conn.execute(&insert, (&1968, &"2001: A Space Odyssey".into_parameter()))
+        .unwrap();
+    conn.execute(&insert, (&1993, &"Jurassic Park".into_parameter()))
+        .unwrap();
+    conn.execute(
+        &insert,
+        (&None::<i32>.into_parameter(), &"Unknown".into_parameter()),
+    )
+    .unwrap();
+
+    // When
+    let cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+    let buffer = ColumnarAnyBuffer::from_descs(
+        2,
+        [
+            BufferDesc::I32 { nullable: true },
+            BufferDesc::Text { max_str_len: 50 },
+        ],
+    );
+    let extractor = BatchExtractor::new()
+        .column(0, |movie: &mut Movie, year| movie.year = year)
+        .text_column(1, |movie: &mut Movie, title| {
+            movie.title = String::from_utf8(title.unwrap().to_vec()).unwrap()
+        });
+    let mut block_cursor = cursor.bind_buffer(buffer).unwrap();
+    let mut movies = Vec::new();
+    let mut num_batches = 0;
+    while block_cursor.fetch_into(&mut movies, &extractor).unwrap() {
+        num_batches += 1;
+    }
+
+    // Then
+    assert_eq!(2, num_batches);
+    assert_eq!(
+        vec![
+            Movie {
+                year: Some(1968),
+                title: "2001: A Space Odyssey".to_owned()
+            },
+            Movie {
+                year: Some(1993),
+                title: "Jurassic Park".to_owned()
+            },
+            Movie {
+                year: None,
+                title: "Unknown".to_owned()
+            },
+        ],
+        movies
+    );
+}
+
+/// Commit the transactions of several connections with one call to the environment.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn commit_and_rollback_all_connections_of_an_environment(profile: &Profile) {
+    // Given
+    let env = Environment::new().unwrap();
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["INTEGER"]).unwrap();
+    let first = env
+        .connect_with_connection_string(profile.connection_string)
+        .unwrap();
+    let second = env
+        .connect_with_connection_string(profile.connection_string)
+        .unwrap();
+    first.set_autocommit(false).unwrap();
+    second.set_autocommit(false).unwrap();
+    let insert = table.sql_insert();
+
+    // When
+    first.execute(&insert, &1).unwrap();
+    second.execute(&insert, &2).unwrap();
+    env.commit_all().unwrap();
+    first.execute(&insert, &3).unwrap();
+    env.rollback_all().unwrap();
+
+    // Then
+    assert_eq!("1\n2", table.content_as_string(&conn));
+}
+
+/// Disconnecting refuses to do so while leaked statements are still alive, unless forced.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn disconnect_with_leaked_statement(profile: &Profile) {
+    // Given
+    let conn = ENV
+        .connect_with_connection_string(profile.connection_string)
+        .unwrap();
+    let idle = ENV
+        .connect_with_connection_string(profile.connection_string)
+        .unwrap();
+    std::mem::forget(conn.prepare("SELECT 1").unwrap());
+
+    // When
+    let (conn, error) = *conn.disconnect().unwrap_err();
+    let forced = conn.disconnect_force();
+    let graceful = idle.disconnect();
+
+    // Then
+    assert!(matches!(error, Error::ActiveStatements { num_active: 1 }));
+    assert!(forced.is_ok());
+    assert!(graceful.is_ok());
+}
+
+/// Shutting down an environment refuses further connections, but leaves freeing the handles of idle
+/// statements and connections to their owners.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn shutdown_environment(profile: &Profile) {
+    // Given
+    let env = Environment::new().unwrap();
+    let conn = env
+        .connect_with_connection_string(profile.connection_string)
+        .unwrap();
+    let prepared = conn.prepare("SELECT 1").unwrap();
+
+    // When
+    let report = env.shutdown(Duration::from_millis(50));
+    let result = env.connect_with_connection_string(profile.connection_string);
+
+    // Then
+    assert_eq!(0, report.num_cancelled);
+    assert_eq!(1, report.num_connections);
+    assert!(env.is_shut_down());
+    assert!(matches!(result, Err(Error::EnvironmentShutDown)));
+    drop(prepared);
+    drop(conn);
+}
+
+/// Statements are registered for cancellation while they run and deregistered afterwards.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn cancellation_registry(profile: &Profile) {
+    // Given
+    let conn = ENV
+        .connect_with_connection_string(profile.connection_string)
+        .unwrap();
+    let mut first = conn.preallocate().unwrap();
+    let mut second = conn.preallocate().unwrap();
+    let registry = CancellationRegistry::new();
+
+    // When
+    let (cancelled, duplicate) = registry
+        .run("query", &mut first, |first| {
+            first.execute("SELECT 1", ()).unwrap();
+            let duplicate = registry.run("query", &mut second, |_| ());
+            (registry.cancel(&"query").unwrap(), duplicate)
+        })
+        .unwrap();
+
+    // Then
+    assert!(cancelled);
+    assert!(matches!(duplicate, Err(Error::QueryIdInUse)));
+    assert!(!registry.is_running(&"query"));
+    assert!(!registry.cancel(&"query").unwrap());
+}
+
+/// Discover the maximum length of a text column with an aggregate query and with a scan.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn probe_text_lengths(profile: &Profile) {
+    // Given
+    let table_name = table_name!();
+    let (conn, table) = profile
+        .given(&table_name, &["VARCHAR(100)", "VARCHAR(100)"])
+        .unwrap();
+    let insert = table.sql_insert();
+    conn.execute(
+        &insert,
+        (&"abc".into_parameter(), &None::<&str>.into_parameter()),
+    )
+    .unwrap();
+    conn.execute(
+        &insert,
+        (&"hello".into_parameter(), &None::<&str>.into_parameter()),
+    )
+    .unwrap();
+    let query = format!("SELECT a, b FROM {table_name}");
+
+    // When
+    let aggregated = conn.probe_text_lengths(&query, &["a", "b"]).unwrap();
+    let mut cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let scanned = TextLengths::scan(&mut cursor).unwrap();
+
+    // Then
+    assert_eq!(Some(20), aggregated.max_narrow_len(0));
+    assert_eq!(None, aggregated.max_narrow_len(1));
+    assert_eq!(Some(5), scanned.max_narrow_len(0));
+    assert_eq!(
+        vec![
+            BufferDesc::Text { max_str_len: 5 },
+            BufferDesc::Text { max_str_len: 100 }
+        ],
+        scanned.tighten([
+            BufferDesc::Text { max_str_len: 100 },
+            BufferDesc::Text { max_str_len: 100 }
+        ])
+    );
+}
+
+/// Fetch values longer than the bound text buffer, growing it on truncation.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn grow_text_buffer_on_truncation(profile: &Profile) {
+    // Given
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["VARCHAR(100)"]).unwrap();
+    let insert = table.sql_insert();
+    conn.execute(&insert, &"abc".into_parameter()).unwrap();
+    conn.execute(&insert, &"Hello, World!".into_parameter())
+        .unwrap();
+    let options = StatementOptions::new().with_cursor_type(CursorType::Static);
+    let cursor = conn
+        .execute_with_options(&table.sql_all_ordered_by_id(), (), &options)
+        .unwrap()
+        .unwrap();
+    let buffer = ColumnarAnyBuffer::from_descs(10, [BufferDesc::Text { max_str_len: 5 }]);
+    let mut block_cursor = cursor.bind_buffer(buffer).unwrap();
+
+    // When
+    let batch = block_cursor
+        .fetch_with_truncation_policy(TruncationPolicy::Grow)
+        .unwrap()
+        .unwrap();
+
+    // Then
+    let values: Vec<_> = batch
+        .column(0)
+        .as_text_view()
+        .unwrap()
+        .iter()
+        .map(|value| value.map(|bytes| String::from_utf8(bytes.to_owned()).unwrap()))
+        .collect();
+    assert_eq!(
+        vec![Some("abc".to_owned()), Some("Hello, World!".to_owned())],
+        values
+    );
+}
+
+/// Values longer than the bound text buffer cause an error naming the column and the size.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn cap_text_buffer(profile: &Profile) {
+    // Given
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["VARCHAR(100)"]).unwrap();
+    conn.execute(&table.sql_insert(), &"Hello, World!".into_parameter())
+        .unwrap();
+    let cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+    let buffer = ColumnarAnyBuffer::from_descs(10, [BufferDesc::Text { max_str_len: 5 }]);
+    let mut block_cursor = cursor.bind_buffer(buffer).unwrap();
+
+    // When
+    let result = block_cursor.fetch_with_truncation_policy(TruncationPolicy::Cap);
+
+    // Then
+    assert!(matches!(
+        result,
+        Err(Error::ValueTooLarge {
+            column: 1,
+            size: Some(13),
+            cap: 5
+        })
+    ));
+}
+
+/// Profile the values of a result set, while fetching it.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn column_statistics(profile: &Profile) {
+    // Given
+    let table_name = table_name!();
+    let (conn, table) = profile
+        .given(&table_name, &["INTEGER", "VARCHAR(20)"])
+        .unwrap();
+    conn.execute(
+        &format!("INSERT INTO {table_name} (a, b) VALUES (3, 'abc'), (1, NULL), (3, 'hello')"),
+        (),
+    )
+    .unwrap();
+    let cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+    let buffer = ColumnarAnyBuffer::from_descs(
+        2,
+        [
+            BufferDesc::I32 { nullable: true },
+            BufferDesc::Text { max_str_len: 20 },
+        ],
+    )
+    .with_column_statistics();
+
+    // When
+    let mut block_cursor = cursor.bind_buffer(buffer).unwrap();
+    while block_cursor.fetch().unwrap().is_some() {}
+    let (_cursor, buffer) = block_cursor.unbind().unwrap();
+
+    // Then
+    let statistics = buffer.column_statistics();
+    let (ints, texts) = (&statistics[0], &statistics[1]);
+    assert_eq!(0, ints.num_nulls());
+    assert_eq!(Some(CellValue::Integer(1)), ints.min());
+    assert_eq!(Some(CellValue::Integer(3)), ints.max());
+    assert_eq!(2, ints.estimated_distinct());
+    assert_eq!(1, texts.num_nulls());
+    assert_eq!(2, texts.num_values());
+    assert_eq!(Some(5), texts.max_len());
+}
+
+/// Substitute the table name into a statement using a template, while passing values as
+/// parameters.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn execute_sql_template(profile: &Profile) {
+    // Given
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["INTEGER"]).unwrap();
+    conn.execute(&table.sql_insert(), &1).unwrap();
+    conn.execute(&table.sql_insert(), &2).unwrap();
+    let template = sql!(
+        "SELECT {column:ident} FROM {table:ident} WHERE {column:ident} > ?",
+        table = table_name.as_str(),
+        column = "a",
+    );
+
+    // When
+    let cursor = template.execute(&conn, &1).unwrap().unwrap();
+
+    // Then
+    let actual = cursor_to_string(cursor);
+    assert_eq!("2", actual);
+}
+
+/// Export a result set as CSV, quoting values containing the delimiter.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn write_delimited(profile: &Profile) {
+    // Given
+    let table_name = table_name!();
+    let (conn, table) = profile
+        .given(&table_name, &["VARCHAR(20)", "INTEGER"])
+        .unwrap();
+    conn.execute(&table.sql_insert(), (&"Hello, World".into_parameter(), &1))
+        .unwrap();
+    conn.execute(
+        &table.sql_insert(),
+        (&"Hi".into_parameter(), &Nullable::<i32>::null()),
+    )
+    .unwrap();
+    let cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+
+    // When
+    let mut out = Vec::new();
+    let num_rows = cursor
+        .write_delimited(&mut out, &DelimitedOptions::new())
+        .unwrap();
+
+    // Then
+    assert_eq!(2, num_rows);
+    assert_eq!(
+        "a,b\n\"Hello, World\",1\nHi,\n",
+        str::from_utf8(&out).unwrap()
+    );
+}
+
+/// Render a result set as a markdown table, truncating long values and showing `NULL`.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn write_table(profile: &Profile) {
+    // Given
+    let table_name = table_name!();
+    let (conn, table) = profile
+        .given(&table_name, &["VARCHAR(20)", "INTEGER"])
+        .unwrap();
+    conn.execute(&table.sql_insert(), (&"Hello, World".into_parameter(), &1))
+        .unwrap();
+    conn.execute(
+        &table.sql_insert(),
+        (&"Hi".into_parameter(), &Nullable::<i32>::null()),
+    )
+    .unwrap();
+    let cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+
+    // When
+    let mut out = Vec::new();
+    let options = RenderOptions::new()
+        .with_format(TableFormat::Markdown)
+        .with_max_width(Some(8));
+    let num_rows = cursor.write_table(&mut out, &options).unwrap();
+
+    // Then
+    assert_eq!(2, num_rows);
+    assert_eq!(
+        "| a        | b    |\n\
+         | -------- | ---- |\n\
+         | Hello... | 1    |\n\
+         | Hi       | NULL |\n",
+        str::from_utf8(&out).unwrap()
+    );
+}
+
+/// Capture the execution plan of a query, regardless of the dialect of the data source.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn explain_query(profile: &Profile) {
+    // Given
+    let table_name = table_name!();
+    let (conn, _table) = profile.given(&table_name, &["INTEGER"]).unwrap();
+
+    // When
+    let plan = conn
+        .explain(&format!("SELECT a FROM {table_name} WHERE a = ?"), &1)
+        .unwrap();
+
+    // Then
+    assert!(!plan.rows().is_empty());
+    assert!(plan
+        .text()
+        .to_lowercase()
+        .contains(&table_name.to_lowercase()));
+    // Explaining the query must not have left `SHOWPLAN_XML` on.
+    let cursor = conn.execute("SELECT 42", ()).unwrap().unwrap();
+    assert_eq!("42", cursor_to_string(cursor));
+}
+
+/// Resetting a connection rolls back open transactions and switches back to auto-commit mode.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn reset_connection(profile: &Profile) {
+    // Given
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["INTEGER"]).unwrap();
+    conn.set_autocommit(false).unwrap();
+    conn.execute(&table.sql_insert(), &1).unwrap();
+
+    // When
+    conn.reset().unwrap();
+    conn.execute(&table.sql_insert(), &2).unwrap();
+
+    // Then
+    drop(conn);
+    let conn = profile.connection().unwrap();
+    assert_eq!("2", table.content_as_string(&conn));
+}
+
+/// Fetch columns by name into the types declared in a fetch plan.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn fetch_plan(profile: &Profile) {
+    // Given
+    let table_name = table_name!();
+    let (conn, table) = profile
+        .given(&table_name, &["INTEGER", "VARCHAR(20)"])
+        .unwrap();
+    conn.execute(&table.sql_insert(), (&42, &"Hello".into_parameter()))
+        .unwrap();
+    let plan = FetchPlan::new()
+        .column("b", TargetType::Text { max_len: 20 }, true)
+        .column("a", TargetType::I64, true);
+    let mut cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+
+    // When
+    let buffer = plan.compile(&mut cursor, 10).unwrap();
+    let mut row_set_cursor = cursor.bind_buffer(buffer).unwrap();
+    let batch = row_set_cursor.fetch().unwrap().unwrap();
+
+    // Then
+    assert_eq!(
+        Some(&b"Hello"[..]),
+        batch.column(0).as_text_view().unwrap().get(0)
+    );
+    assert_eq!(
+        Some(&42),
+        batch
+            .column(1)
+            .as_nullable_slice::<i64>()
+            .unwrap()
+            .next()
+            .unwrap()
+    );
+}
+
+/// A fetch plan fetching text into a date is rejected before anything is fetched.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn fetch_plan_incompatible_type(profile: &Profile) {
+    // Given
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["VARCHAR(20)"]).unwrap();
+    let plan = FetchPlan::new().column("a", TargetType::Date, true);
+    let mut cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+
+    // When
+    let result = plan.validate(&mut cursor);
 
-    let kind = DataType::Float { precision: 53 };
-    let expected = ColumnDescription::new("k", kind, Nullability::Nullable);
-    cursor.describe_col(11, &mut actual).unwrap();
-    assert_eq!(expected, actual);
-    assert_eq!(kind, cursor.col_data_type(11).unwrap());
+    // Then
+    assert!(matches!(
+        result,
+        Err(Error::FetchPlanMismatch {
+            violation: FetchPlanViolation::IncompatibleType { .. },
+            ..
+        })
+    ));
 }
 
-/// Fetch text from data source using the TextBuffer type
+/// Warming up executes queries with `NULL` parameters, but leaves statements without result sets
+/// alone.
 #[test_case(MSSQL; "Microsoft SQL Server")]
 #[test_case(MARIADB; "Maria DB")]
 #[test_case(SQLITE_3; "SQLite 3")]
 #[test_case(POSTGRES; "PostgreSQL")]
-fn bulk_fetch_text(profile: &Profile) {
+fn warm_up_prepared(profile: &Profile) {
+    // Given
     let table_name = table_name!();
-    let (conn, table) = profile
-        .given(&table_name, &["VARCHAR(255)", "INT"])
+    let (conn, table) = profile.given(&table_name, &["INTEGER"]).unwrap();
+    let mut query = conn
+        .prepare(&format!("SELECT a FROM {table_name} WHERE a = ?"))
         .unwrap();
+    let mut insert = conn.prepare(&table.sql_insert()).unwrap();
 
-    // Insert data
-    let insert = format!("INSERT INTO {table_name} (a,b) VALUES (?,?), (?,?),(?,?)");
-    conn.execute(
-        &insert,
-        (
-            &"Interstellar".into_parameter(),
-            &None::<i32>.into_parameter(),
-            &"2001: A Space Odyssey".into_parameter(),
-            &1968,
-            &"Jurassic Park".into_parameter(),
-            &1993,
-        ),
-    )
-    .unwrap();
+    // When
+    let query_warmed_up = query.warm_up().unwrap();
+    let insert_warmed_up = insert.warm_up().unwrap();
 
-    let query = table.sql_all_ordered_by_id();
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    // Cursor to string helper utilizes the text buffer
-    let actual = cursor_to_string(cursor);
-    let expected = "Interstellar,NULL\n2001: A Space Odyssey,1968\nJurassic Park,1993";
-    assert_eq!(expected, actual);
+    // Then
+    assert!(query_warmed_up);
+    assert!(!insert_warmed_up);
+    assert_eq!("", table.content_as_string(&conn));
 }
 
-/// Into cursor should enable users to open a connection within a function and return a cursor.
+/// Describe the result set of a statement before executing it.
 #[test_case(MSSQL; "Microsoft SQL Server")]
 #[test_case(MARIADB; "Maria DB")]
 #[test_case(SQLITE_3; "SQLite 3")]
 #[test_case(POSTGRES; "PostgreSQL")]
-fn into_cursor(profile: &Profile) {
+fn prepare_with_metadata(profile: &Profile) {
+    // Given
     let table_name = table_name!();
     let (conn, table) = profile
-        .given(&table_name, &["VARCHAR(255)", "INT"])
+        .given(&table_name, &["INTEGER", "VARCHAR(10)"])
         .unwrap();
-
-    // Insert data
-    let insert = format!("INSERT INTO {table_name} (a,b) VALUES (?,?), (?,?),(?,?)");
     conn.execute(
-        &insert,
-        (
-            &"Interstellar".into_parameter(),
-            &None::<i32>.into_parameter(),
-            &"2001: A Space Odyssey".into_parameter(),
-            &1968,
-            &"Jurassic Park".into_parameter(),
-            &1993,
-        ),
+        &format!("INSERT INTO {table_name} (a, b) VALUES (1, 'x')"),
+        (),
     )
     .unwrap();
 
-    let make_cursor = || {
-        let conn = profile.connection().unwrap();
-        let query = table.sql_all_ordered_by_id();
-        conn.into_cursor(&query, ()).unwrap().unwrap()
-    };
-    let cursor = make_cursor();
+    // When
+    let mut prepared = conn
+        .prepare_with_metadata(&table.sql_all_ordered_by_id())
+        .unwrap();
+    let column_names: Vec<_> = prepared
+        .result_set_schema()
+        .unwrap()
+        .columns
+        .iter()
+        .map(|column| column.name.clone())
+        .collect();
+    let cursor = prepared.execute(()).unwrap().unwrap();
 
-    // Cursor to string helper utilizes the text buffer
-    let actual = cursor_to_string(cursor);
-    let expected = "Interstellar,NULL\n2001: A Space Odyssey,1968\nJurassic Park,1993";
-    assert_eq!(expected, actual);
+    // Then
+    assert_eq!(["a", "b"], column_names.as_slice());
+    assert_eq!("1,x", cursor_to_string(cursor));
 }
 
+/// Only the first occurrence of each key is emitted, even across batches. Batches consisting of
+/// duplicates only are skipped.
 #[test_case(MSSQL; "Microsoft SQL Server")]
 #[test_case(MARIADB; "Maria DB")]
 #[test_case(SQLITE_3; "SQLite 3")]
 #[test_case(POSTGRES; "PostgreSQL")]
-fn column_name(profile: &Profile) {
+fn deduplicate_rows(profile: &Profile) {
+    // Given
     let table_name = table_name!();
-    let conn = profile
-        .setup_empty_table(&table_name, &["VARCHAR(255)", "INT"])
+    let (conn, table) = profile
+        .given(&table_name, &["INTEGER", "VARCHAR(10)"])
         .unwrap();
+    let insert = table.sql_insert();
+    for (a, b) in [(1, "x"), (2, "y"), (1, "z"), (3, "w"), (2, "v")] {
+        conn.execute(&insert, (&a, &b.into_parameter())).unwrap();
+    }
+    let cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+    let descs = [
+        BufferDesc::I32 { nullable: true },
+        BufferDesc::Text { max_str_len: 10 },
+    ];
+    let buffer = ColumnarAnyBuffer::from_descs(2, descs);
+    let mut cursor = DeduplicatingCursor::new(cursor.bind_buffer(buffer).unwrap(), vec![0]);
 
-    let sql = format!("SELECT a, b FROM {table_name};");
-    let mut cursor = conn.execute(&sql, ()).unwrap().unwrap();
-
-    let name = cursor.col_name(1).unwrap();
-    assert_eq!("a", name);
+    // When
+    let mut values = Vec::new();
+    while let Some(rows) = cursor.fetch().unwrap() {
+        for &row_index in rows.row_indices() {
+            values.push(rows.batch().get::<String>(row_index, 1).unwrap());
+        }
+    }
 
-    let name = cursor.col_name(2).unwrap();
-    assert_eq!("b", name);
+    // Then
+    assert_eq!(vec!["x", "y", "w"], values);
+    assert_eq!(2, cursor.num_duplicates());
+}
 
-    // Test the same using column descriptions
-    let mut desc = ColumnDescription::default();
+/// Peeking at the next row does not consume it, even if it is part of the next batch. Consumed rows
+/// can be pushed back.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn peek_row_across_batches(profile: &Profile) {
+    // Given
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["INTEGER"]).unwrap();
+    let insert = table.sql_insert();
+    for a in [1, 2, 3] {
+        conn.execute(&insert, &a).unwrap();
+    }
+    let cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+    let buffer = ColumnarAnyBuffer::from_descs(2, [BufferDesc::I32 { nullable: true }]);
+    let mut rows = PeekableCursor::new(cursor.bind_buffer(buffer).unwrap());
 
-    cursor.describe_col(1, &mut desc).unwrap();
-    assert_eq!("a", desc.name_to_string().unwrap());
+    // When
+    let mut values = Vec::new();
+    values.push(rows.peek_row().unwrap().unwrap().get::<i32>(0).unwrap());
+    values.push(rows.next_row().unwrap().unwrap().get::<i32>(0).unwrap());
+    values.push(rows.next_row().unwrap().unwrap().get::<i32>(0).unwrap());
+    rows.push_back();
+    values.push(rows.next_row().unwrap().unwrap().get::<i32>(0).unwrap());
+    // Fetches the second batch
+    values.push(rows.peek_row().unwrap().unwrap().get::<i32>(0).unwrap());
+    values.push(rows.next_row().unwrap().unwrap().get::<i32>(0).unwrap());
+    let end = rows.peek_row().unwrap().is_none();
 
-    cursor.describe_col(2, &mut desc).unwrap();
-    assert_eq!("b", desc.name_to_string().unwrap());
+    // Then
+    assert_eq!(vec![1, 1, 2, 2, 3, 3], values);
+    assert!(end);
+    assert!(rows.next_row().unwrap().is_none());
 }
 
-/// Bind a CHAR column to a character buffer.
+/// Consecutive rows sharing a key form a group, even if the group spans several batches. Rows of a
+/// group which are not consumed are skipped.
 #[test_case(MSSQL; "Microsoft SQL Server")]
 #[test_case(MARIADB; "Maria DB")]
 #[test_case(SQLITE_3; "SQLite 3")]
 #[test_case(POSTGRES; "PostgreSQL")]
-fn bind_char(profile: &Profile) {
+fn group_consecutive_rows_by_key(profile: &Profile) {
+    // Given
     let table_name = table_name!();
-    let (conn, table) = profile.given(&table_name, &["CHAR(5)"]).unwrap();
-    let insert_sql = table.sql_insert();
-    conn.execute(&insert_sql, &"Hello".into_parameter())
+    let (conn, table) = profile
+        .given(&table_name, &["INTEGER", "VARCHAR(10)"])
         .unwrap();
-
+    let insert = table.sql_insert();
+    for (a, b) in [(1, "x"), (1, "y"), (1, "z"), (2, "w"), (3, "v"), (3, "u")] {
+        conn.execute(&insert, (&a, &b.into_parameter())).unwrap();
+    }
     let cursor = conn
         .execute(&table.sql_all_ordered_by_id(), ())
         .unwrap()
         .unwrap();
-    let mut buf = ColumnarBuffer::new(vec![(1, TextColumn::new(1, 5))]);
-    let mut row_set_cursor = cursor.bind_buffer(&mut buf).unwrap();
-    let batch = row_set_cursor.fetch().unwrap().unwrap();
+    let descs = [
+        BufferDesc::I32 { nullable: true },
+        BufferDesc::Text { max_str_len: 10 },
+    ];
+    let buffer = ColumnarAnyBuffer::from_descs(2, descs);
+    let rows = PeekableCursor::new(cursor.bind_buffer(buffer).unwrap());
+    let mut groups = GroupedRows::new(rows, vec![0]);
 
-    assert_eq!(Some(&b"Hello"[..]), batch.column(0).get(0));
+    // When
+    let mut actual = Vec::new();
+    while groups.next_group().unwrap() {
+        let mut values = Vec::new();
+        // Consume at most two rows of each group
+        for _ in 0..2 {
+            if let Some(row) = groups.next_row().unwrap() {
+                values.push(row.get::<String>(1).unwrap());
+            }
+        }
+        actual.push(values.join(","));
+    }
+
+    // Then
+    assert_eq!(vec!["x,y", "w", "v,u"], actual);
 }
 
-/// Bind a CHAR column to a wchar buffer
+/// Each column group presents its own columns of the batch, which is fetched only once.
 #[test_case(MSSQL; "Microsoft SQL Server")]
 #[test_case(MARIADB; "Maria DB")]
 #[test_case(SQLITE_3; "SQLite 3")]
 #[test_case(POSTGRES; "PostgreSQL")]
-fn bind_char_to_wchar(profile: &Profile) {
+fn split_result_set_into_column_groups(profile: &Profile) {
+    // Given
     let table_name = table_name!();
-    let (conn, table) = profile.given(&table_name, &["CHAR(5)"]).unwrap();
-    let insert_sql = table.sql_insert();
-    conn.execute(&insert_sql, &"Hello".into_parameter())
+    let (conn, table) = profile
+        .given(&table_name, &["INTEGER", "VARCHAR(10)", "INTEGER"])
         .unwrap();
-    let sql = table.sql_all_ordered_by_id();
+    conn.execute(&table.sql_insert(), (&1, &"one".into_parameter(), &10))
+        .unwrap();
+    let cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+    let descs = [
+        BufferDesc::I32 { nullable: true },
+        BufferDesc::Text { max_str_len: 10 },
+        BufferDesc::I32 { nullable: true },
+    ];
+    let buffer = ColumnarAnyBuffer::from_descs(10, descs);
+    let mut groups = ColumnGroups::new(
+        cursor.bind_buffer(buffer).unwrap(),
+        vec![vec![1], vec![2, 0]],
+    );
 
-    let cursor = conn.execute(&sql, ()).unwrap().unwrap();
-    let mut buf = ColumnarBuffer::new(vec![(1, TextColumn::<u16>::new(1, 5))]);
-    let mut row_set_cursor = cursor.bind_buffer(&mut buf).unwrap();
-    row_set_cursor.fetch().unwrap();
-    drop(row_set_cursor);
+    // When
+    let batch = groups.fetch().unwrap().unwrap();
+    let names = batch.group(0);
+    let numbers = batch.group(1);
 
-    assert_eq!(
-        Some(U16String::from_str("Hello").as_ustr()),
-        buf.column(0).get(0).map(U16Str::from_slice)
-    );
+    // Then
+    assert_eq!(1, names.num_cols());
+    assert_eq!("one", names.get::<String>(0, 0).unwrap());
+    assert_eq!(10, numbers.get::<i32>(0, 0).unwrap());
+    assert_eq!(1, numbers.get::<i32>(0, 1).unwrap());
 }
 
 /// Bind a BIT column to a Bit buffer.
@@ -1279,6 +4014,113 @@ fn preallocated(profile: &Profile) {
     }
 }
 
+/// Set a driver specific statement attribute before executing a query.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+fn vendor_statement_attribute(profile: &Profile) {
+    // Given
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["INTEGER"]).unwrap();
+    conn.execute(&format!("INSERT INTO {table_name} (a) VALUES (42)"), ())
+        .unwrap();
+    let mut prealloc = conn.preallocate().unwrap();
+
+    // When
+    vendor::mssql::set_cursor_options(
+        &mut prealloc.as_stmt_ref(),
+        vendor::mssql::CursorOptions::FastForwardOnlyAutoFetch,
+    )
+    .into_result(&prealloc.as_stmt_ref())
+    .unwrap();
+    let cursor = prealloc
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+
+    // Then
+    assert_eq!("42", cursor_to_string(cursor));
+}
+
+/// Stamp an application name on an open session.
+#[test_case(MSSQL, false; "Microsoft SQL Server")]
+#[test_case(POSTGRES, true; "PostgreSQL")]
+fn apply_client_identity(profile: &Profile, expected_applied: bool) {
+    let conn = profile.connection().unwrap();
+    let identity = ClientIdentity::new("odbc-api-tests");
+
+    let applied = identity.apply_to(&conn).unwrap();
+
+    assert_eq!(expected_applied, applied);
+    if applied {
+        let cursor = conn
+            .execute("SELECT current_setting('application_name')", ())
+            .unwrap()
+            .unwrap();
+        assert_eq!("odbc-api-tests", cursor_to_string(cursor));
+    }
+}
+
+/// Observe transaction boundaries and closing of a connection.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn connection_observer(profile: &Profile) {
+    // Given
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+    let observer: Arc<dyn ConnectionObserver> =
+        Arc::new(move |event| sink.lock().unwrap().push(event));
+    let mut conn = profile.connection().unwrap();
+    conn.set_observer(Some(observer));
+
+    // When
+    conn.set_autocommit(false).unwrap();
+    conn.commit().unwrap();
+    conn.rollback().unwrap();
+    drop(conn);
+
+    // Then
+    assert_eq!(
+        vec![
+            ConnectionEvent::AutocommitChanged(false),
+            ConnectionEvent::Committed,
+            ConnectionEvent::RolledBack,
+            ConnectionEvent::Closed
+        ],
+        *events.lock().unwrap()
+    );
+}
+
+/// Fetch rows using the query builder, with identifiers quoted by the driver specific quote.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn select_with_query_builder(profile: &Profile) {
+    // Given
+    let table_name = table_name!();
+    let (conn, _table) = profile
+        .given(&table_name, &["INTEGER", "VARCHAR(10)"])
+        .unwrap();
+    conn.execute(
+        &format!("INSERT INTO {table_name} (a, b) VALUES (1, 'one'), (2, 'two'), (3, 'three')"),
+        (),
+    )
+    .unwrap();
+
+    // When
+    let cursor = Select::from(table_name.as_str())
+        .columns(["b"])
+        .filter("a > ?", 1)
+        .order_by_desc("a")
+        .execute(&conn)
+        .unwrap()
+        .unwrap();
+
+    // Then
+    assert_eq!("three\ntwo", cursor_to_string(cursor));
+}
+
 /// Reuse a preallocated handle. Verify that columns bound to the statement during a previous
 /// execution are not dereferenced during a second one.
 #[test_case(MSSQL; "Microsoft SQL Server")]
@@ -1978,9 +4820,63 @@ fn output_parameter(profile: &Profile) {
     )
     .unwrap();
 
-    // See magic numbers hardcoded in setup.sql
-    assert_eq!(Some(99), ret.into_opt());
-    assert_eq!(Some(7 + 5), param.into_opt());
+    // See magic numbers hardcoded in setup.sql
+    assert_eq!(Some(99), ret.into_opt());
+    assert_eq!(Some(7 + 5), param.into_opt());
+}
+
+#[test_case(MSSQL; "Microsoft SQL Server")]
+fn table_valued_parameter(profile: &Profile) {
+    let conn = profile.connection().unwrap();
+    conn.execute(
+        "IF OBJECT_ID('TestTableValuedParam') IS NOT NULL DROP PROCEDURE TestTableValuedParam",
+        (),
+    )
+    .unwrap();
+    conn.execute(
+        "IF TYPE_ID('TestTableValuedParamType') IS NOT NULL DROP TYPE TestTableValuedParamType",
+        (),
+    )
+    .unwrap();
+    conn.execute(
+        "CREATE TYPE TestTableValuedParamType AS TABLE (id INTEGER NOT NULL, name VARCHAR(10))",
+        (),
+    )
+    .unwrap();
+    conn.execute(
+        r#"CREATE PROCEDURE TestTableValuedParam
+        @rows TestTableValuedParamType READONLY,
+        @offset INTEGER
+        AS
+        SELECT id + @offset, name FROM @rows ORDER BY id
+        "#,
+        (),
+    )
+    .unwrap();
+
+    let mut tvp = TableValuedParam::new(
+        "TestTableValuedParamType",
+        10,
+        [
+            BufferDesc::I32 { nullable: false },
+            BufferDesc::Text { max_str_len: 10 },
+        ],
+    );
+    tvp.set_num_rows(2);
+    if let AnyBuffer::I32(ids) = tvp.column_mut(0) {
+        ids[..2].copy_from_slice(&[1, 2]);
+    }
+    if let AnyBuffer::Text(names) = tvp.column_mut(1) {
+        names.set_value(0, Some(b"one"));
+        names.set_value(1, None);
+    }
+    let cursor = conn
+        .execute("{CALL TestTableValuedParam(?, ?)}", (&tvp, &10))
+        .unwrap()
+        .unwrap();
+
+    let actual = cursor_to_string(cursor);
+    assert_eq!("11,one\n12,NULL", actual);
 }
 
 #[test_case(MSSQL; "Microsoft SQL Server")]
@@ -2467,6 +5363,184 @@ fn large_get_binary(profile: &Profile) {
     assert_eq!(input, actual);
 }
 
+/// Fetch a large binary value with an upper limit applied in case the driver does not report its
+/// length.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+// #[test_case(MARIADB; "Maria DB")] Does not support Varbinary(max) syntax
+// #[test_case(SQLITE_3; "SQLite 3")] Does not support Varbinary(max) syntax
+// #[test_case(POSTGRES; "PostgreSQL")] Does not support Varbinary(max) syntax
+fn large_get_binary_with_no_total_cap(profile: &Profile) {
+    let table_name = table_name!();
+    let conn = profile
+        .setup_empty_table(&table_name, &["Varbinary(max)"])
+        .unwrap();
+
+    let input = vec![42; 2000];
+
+    conn.execute(
+        &format!("INSERT INTO {table_name} (a) VALUES (?)"),
+        &input.as_slice().into_parameter(),
+    )
+    .unwrap();
+
+    let mut cursor = conn
+        .execute(&format!("SELECT a FROM {table_name} ORDER BY id"), ())
+        .unwrap()
+        .unwrap();
+
+    let mut row = cursor.next_row().unwrap().unwrap();
+    let mut actual = Vec::new();
+
+    let policy = NoTotalPolicy::Cap { max_bytes: 1000 };
+    let fetch = row
+        .get_variadic_with_policy::<Binary>(1, &mut actual, policy)
+        .unwrap();
+
+    // The driver does not report the length of `VARBINARY(max)` values fetched in chunks.
+    assert!(fetch.not_null);
+    assert!(fetch.no_total);
+    assert!(fetch.truncated);
+    assert_eq!(&input[..1000], actual);
+}
+
+/// A cap with an odd number of bytes must not split a UTF-16 code unit of wide text.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+// #[test_case(MARIADB; "Maria DB")] Does not support NVarchar(max) syntax
+// #[test_case(SQLITE_3; "SQLite 3")] Does not support NVarchar(max) syntax
+// #[test_case(POSTGRES; "PostgreSQL")] Does not support NVarchar(max) syntax
+fn large_get_wide_text_with_odd_no_total_cap(profile: &Profile) {
+    let table_name = table_name!();
+    let conn = profile
+        .setup_empty_table(&table_name, &["NVarchar(max)"])
+        .unwrap();
+    let input = "a".repeat(2000);
+    conn.execute(
+        &format!("INSERT INTO {table_name} (a) VALUES (?)"),
+        &input.as_str().into_parameter(),
+    )
+    .unwrap();
+    let mut cursor = conn
+        .execute(&format!("SELECT a FROM {table_name} ORDER BY id"), ())
+        .unwrap()
+        .unwrap();
+    let mut row = cursor.next_row().unwrap().unwrap();
+    let mut actual = Vec::new();
+
+    let policy = NoTotalPolicy::Cap { max_bytes: 1001 };
+    let fetch = row
+        .get_variadic_with_policy::<WideText>(1, &mut actual, policy)
+        .unwrap();
+
+    assert!(fetch.no_total);
+    assert!(fetch.truncated);
+    let expected: Vec<u8> = "a"
+        .repeat(500)
+        .encode_utf16()
+        .flat_map(u16::to_ne_bytes)
+        .collect();
+    assert_eq!(expected, actual);
+}
+
+/// Hash a binary value larger than the chunks it is fetched in, without holding it in memory.
+#[cfg(feature = "digest")]
+#[test_case(MSSQL; "Microsoft SQL Server")]
+// #[test_case(MARIADB; "Maria DB")] Does not support Varbinary(max) syntax
+// #[test_case(SQLITE_3; "SQLite 3")] Does not support Varbinary(max) syntax
+// #[test_case(POSTGRES; "PostgreSQL")] Does not support Varbinary(max) syntax
+fn get_digest_of_large_binary(profile: &Profile) {
+    use digest::{consts::U8, Digest, FixedOutput, HashMarker, Output, OutputSizeUser, Update};
+
+    /// 64 Bit FNV-1a, so the test does not depend on a crate implementing a cryptographic hash.
+    #[derive(Clone)]
+    struct Fnv(u64);
+
+    impl Default for Fnv {
+        fn default() -> Self {
+            Fnv(0xcbf2_9ce4_8422_2325)
+        }
+    }
+
+    impl Update for Fnv {
+        fn update(&mut self, data: &[u8]) {
+            for &byte in data {
+                self.0 = (self.0 ^ byte as u64).wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+    }
+
+    impl OutputSizeUser for Fnv {
+        type OutputSize = U8;
+    }
+
+    impl FixedOutput for Fnv {
+        fn finalize_into(self, out: &mut Output<Self>) {
+            out.copy_from_slice(&self.0.to_be_bytes());
+        }
+    }
+
+    impl HashMarker for Fnv {}
+
+    // Given a value spanning several chunks of 8 KiB and a NULL
+    let table_name = table_name!();
+    let conn = profile
+        .setup_empty_table(&table_name, &["Varbinary(max)"])
+        .unwrap();
+    let input: Vec<u8> = (0..20_000).map(|index| (index % 251) as u8).collect();
+    let insert = format!("INSERT INTO {table_name} (a) VALUES (?)");
+    conn.execute(&insert, &input.as_slice().into_parameter())
+        .unwrap();
+    conn.execute(&insert, &None::<&[u8]>.into_parameter())
+        .unwrap();
+
+    // When
+    let mut cursor = conn
+        .execute(&format!("SELECT a FROM {table_name} ORDER BY id"), ())
+        .unwrap()
+        .unwrap();
+    let mut row = cursor.next_row().unwrap().unwrap();
+    let hash = row.get_digest::<Fnv>(1).unwrap();
+    let mut row = cursor.next_row().unwrap().unwrap();
+    let null_hash = row.get_digest::<Fnv>(1).unwrap();
+
+    // Then
+    assert_eq!(Some(Fnv::digest(&input)), hash);
+    assert_eq!(None, null_hash);
+}
+
+/// Refuse to fetch a value exceeding the maximum size, rather than allocating memory for it.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn get_text_exceeding_cap(profile: &Profile) {
+    // Given a text with 1000 characters
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["VARCHAR(1000)"]).unwrap();
+    let input = "a".repeat(1000);
+    conn.execute(&table.sql_insert(), &input.as_str().into_parameter())
+        .unwrap();
+
+    // When
+    let mut cursor = conn
+        .execute(&table.sql_all_ordered_by_id(), ())
+        .unwrap()
+        .unwrap();
+    let mut row = cursor.next_row().unwrap().unwrap();
+    let mut actual = Vec::new();
+    let result = row.get_variadic_capped::<Text>(1, &mut actual, 999);
+
+    // Then
+    assert!(matches!(
+        result,
+        Err(Error::ValueTooLarge {
+            column: 1,
+            // Unknown, if the driver does not report the length of the value.
+            size: Some(1000) | None,
+            cap: 999,
+        })
+    ));
+}
+
 /// Demonstrates applying an upper limit to a text buffer and detecting truncation.
 #[test_case(MSSQL; "Microsoft SQL Server")]
 #[test_case(MARIADB; "Maria DB")]
@@ -2704,6 +5778,36 @@ fn send_long_data_binary_vec(profile: &Profile) {
     assert_eq!(input, output);
 }
 
+/// Put long data in chunks of a fixed size, keeping count of the bytes transferred.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn send_long_data_in_chunks(profile: &Profile) {
+    // Given
+    let table_name = table_name!();
+    let conn = profile
+        .setup_empty_table(&table_name, &[profile.blob_type])
+        .unwrap();
+    let input: Vec<_> = (0..12000).map(|i| (i % 256) as u8).collect();
+
+    // When
+    let mut chunked = ChunkedBlob::new(BlobSlice::from_byte_slice(&input), 5000);
+    let progress = chunked.progress();
+    let insert = format!("INSERT INTO {table_name} (a) VALUES (?)");
+    conn.execute(&insert, &mut chunked.as_blob_param()).unwrap();
+
+    // Then
+    assert_eq!(12000, progress.bytes_pushed());
+    assert_eq!(3, progress.chunks_pushed());
+    let select = format!("SELECT a FROM {table_name}");
+    let mut result = conn.execute(&select, ()).unwrap().unwrap();
+    let mut row = result.next_row().unwrap().unwrap();
+    let mut output = Vec::new();
+    row.get_binary(1, &mut output).unwrap();
+    assert_eq!(input, output);
+}
+
 #[test_case(MSSQL; "Microsoft SQL Server")]
 #[test_case(MARIADB; "Maria DB")]
 #[test_case(SQLITE_3; "SQLite 3")]
@@ -3438,6 +6542,41 @@ fn grow_batch_size_during_bulk_insert(profile: &Profile) {
     assert_eq!("1\n2\n3", actual);
 }
 
+/// A prepared statement owning its connection can be created within a function, sent to another
+/// thread and hand back the connection then it is no longer needed.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn prepared_owning_connection(profile: &Profile) {
+    // Given
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["INTEGER"]).unwrap();
+    conn.execute(&format!("INSERT INTO {table_name} (a) VALUES (1),(2)"), ())
+        .unwrap();
+    let make_prepared = || {
+        let conn = profile.connection().unwrap();
+        conn.into_prepared(&table.sql_all_ordered_by_id()).unwrap()
+    };
+
+    // When
+    let prepared = unsafe { make_prepared().promote_to_send() };
+    let (actual, conn) = thread::spawn(move || {
+        let mut prepared = prepared;
+        let cursor = prepared.execute(()).unwrap().unwrap();
+        let actual = cursor_to_string(cursor);
+        let conn = prepared.unwrap().into_statement().into_connection();
+        (actual, unsafe { conn.promote_to_send() })
+    })
+    .join()
+    .unwrap();
+
+    // Then
+    assert_eq!("1\n2", actual);
+    // Connection is still usable after the prepared statement has been freed.
+    assert_eq!("1\n2", table.content_as_string(&conn));
+}
+
 #[test_case(MSSQL; "Microsoft SQL Server")]
 #[test_case(MARIADB; "Maria DB")]
 #[test_case(SQLITE_3; "SQLite 3")]
@@ -3576,6 +6715,45 @@ async fn async_preallocated_statement_execution(profile: &Profile) {
     assert_eq!("Hello, World!", actual);
 }
 
+/// Feed long data from an asynchronous source, while the statement is executing. SQLite is not
+/// tested, since it inserts empty data if no size hint is provided.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(POSTGRES; "PostgreSQL")]
+#[tokio::test]
+async fn async_send_long_data_from_channel(profile: &Profile) {
+    // Given
+    let table_name = table_name!();
+    let conn = profile
+        .setup_empty_table(&table_name, &[profile.blob_type])
+        .unwrap();
+    let input: Vec<_> = (0..12000).map(|i| (i % 256) as u8).collect();
+    let sleep = || tokio::time::sleep(Duration::from_millis(1));
+
+    // When
+    let (mut sender, mut receiver) = blob_channel(1, input.len());
+    let parts = input.clone();
+    let feed = async move {
+        for part in parts.chunks(1000) {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+            sender.send(part.to_vec()).await.unwrap();
+        }
+    };
+    let mut param = receiver.as_blob_param();
+    let insert = format!("INSERT INTO {table_name} (a) VALUES (?)");
+    let execute = conn.execute_polling(&insert, &mut param, sleep);
+    let (result, ()) = tokio::join!(execute, feed);
+    result.unwrap();
+
+    // Then
+    let select = format!("SELECT a FROM {table_name}");
+    let mut result = conn.execute(&select, ()).unwrap().unwrap();
+    let mut row = result.next_row().unwrap().unwrap();
+    let mut output = Vec::new();
+    row.get_binary(1, &mut output).unwrap();
+    assert_eq!(input, output);
+}
+
 #[test_case(MSSQL; "Microsoft SQL Server")]
 #[test_case(MARIADB; "Maria DB")]
 #[test_case(SQLITE_3; "SQLite 3")]
@@ -3615,3 +6793,122 @@ async fn async_bulk_fetch(profile: &Profile) {
     // Then
     assert_eq!(1000, sum_rows_fetched)
 }
+
+/// Execute and fetch on the thread of a connection worker, without support of the driver for
+/// asynchronous execution.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+#[tokio::test]
+async fn connection_worker(profile: &Profile) {
+    // Given a table with three records
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["INTEGER"]).unwrap();
+    for value in [1i32, 2, 3] {
+        conn.execute(&table.sql_insert(), &value).unwrap();
+    }
+    drop(conn);
+    let profile = *profile;
+    let query = format!("SELECT a FROM {table_name} WHERE a > ? ORDER BY id");
+
+    // When
+    let mut worker = ConnectionWorker::connect(move || profile.connection())
+        .await
+        .unwrap();
+    let mut cursor = worker
+        .execute(query, vec![Box::new(1i32)])
+        .await
+        .unwrap()
+        .unwrap();
+    let mut values = Vec::new();
+    let mut buffer = ColumnarBuffer::<Vec<i32>>::new(vec![(1, vec![0; 1])]);
+    while let Some(batch) = cursor.fetch(buffer).await.unwrap() {
+        values.extend_from_slice(&batch.column(0)[..batch.num_rows()]);
+        buffer = batch;
+    }
+    cursor.close().await.unwrap();
+    worker.close().await.unwrap();
+
+    // Then
+    assert_eq!(vec![2, 3], values);
+}
+
+/// Touching the connection in between polls must not interfere with execution or fetching.
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+#[tokio::test]
+async fn async_fetch_with_keep_alive(profile: &Profile) {
+    // Given a table with a hundred records
+    let table_name = table_name!();
+    let (conn, table) = profile.given(&table_name, &["INTEGER"]).unwrap();
+    let prepared = conn.prepare(&table.sql_insert()).unwrap();
+    let mut inserter = prepared.into_text_inserter(100, [10]).unwrap();
+    for index in 0..100 {
+        inserter
+            .append([Some(index.to_string().as_bytes())].iter().copied())
+            .unwrap();
+    }
+    inserter.execute().unwrap();
+    let query = table.sql_all_ordered_by_id();
+    let sleep = || tokio::time::sleep(Duration::from_millis(10));
+
+    // When touching the connection on every poll
+    let mut keep_alive = KeepAlive::new(&conn, Duration::ZERO, sleep).unwrap();
+    let cursor = conn
+        .execute_polling(&query, (), &mut keep_alive)
+        .await
+        .unwrap()
+        .unwrap();
+    let buffer = TextRowSet::from_max_str_lens(10, [10usize]).unwrap();
+    let mut row_set_cursor = cursor.bind_buffer(buffer).unwrap();
+    let mut sum_rows_fetched = 0;
+    while let Some(batch) = row_set_cursor.fetch(&mut keep_alive).await.unwrap() {
+        sum_rows_fetched += batch.num_rows();
+    }
+
+    // Then
+    assert_eq!(100, sum_rows_fetched);
+    // Probes are either executed or skipped due to the active statement, but never fail.
+    assert!(keep_alive.take_error().is_none());
+    assert!(keep_alive.num_touches() + keep_alive.num_skipped() > 0);
+}
+
+#[cfg(feature = "test_harness")]
+#[test_case(MSSQL; "Microsoft SQL Server")]
+#[test_case(MARIADB; "Maria DB")]
+#[test_case(SQLITE_3; "SQLite 3")]
+#[test_case(POSTGRES; "PostgreSQL")]
+fn test_database_harness(profile: &Profile) {
+    use odbc_api::test_support::TestDatabase;
+
+    // Given a throwaway database with a table
+    let table_name = table_name!();
+    let db = TestDatabase::from_connection_string(&ENV, profile.connection_string).unwrap();
+    db.create_table(&table_name, &["a INTEGER"]).unwrap();
+    let quote = db.connection().identifier_quote_char().unwrap();
+
+    // When
+    db.connection()
+        .execute(
+            &format!("INSERT INTO {quote}{table_name}{quote} (a) VALUES (42)"),
+            (),
+        )
+        .unwrap();
+    let cursor = db
+        .connection()
+        .execute(&format!("SELECT a FROM {quote}{table_name}{quote}"), ())
+        .unwrap()
+        .unwrap();
+
+    // Then
+    assert_eq!("42", cursor_to_string(cursor));
+    // The table is dropped together with the harness
+    drop(db);
+    let conn = profile.connection().unwrap();
+    assert!(conn
+        .execute(&format!("SELECT a FROM {quote}{table_name}{quote}"), ())
+        .is_err());
+}